@@ -0,0 +1,54 @@
+//! Asks the first model of the library a question and prints the reply.
+//!
+//! cargo run -p icebreaker_core --example chat -- "What is an icebreaker?"
+use icebreaker_core::assistant::{Backend, BootEvent};
+use icebreaker_core::chat::Item;
+use icebreaker_core::model::{FileAndAPI, FileOrAPI};
+use icebreaker_core::{ChatSession, Error, ModelManager, Settings};
+
+use sipper::Sipper;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let question = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "Hello! Who are you?".to_owned());
+
+    let models = ModelManager::open(Settings::fetch().unwrap_or_default()).await?;
+
+    let Some(file) = models.files().into_iter().next() else {
+        eprintln!("The library has no models. Try the download example first!");
+        return Ok(());
+    };
+
+    let file = match file {
+        FileOrAPI::File(file) => FileAndAPI {
+            file: Some(file),
+            api: None,
+        },
+        FileOrAPI::API(api) => FileAndAPI {
+            file: None,
+            api: Some(api),
+        },
+    };
+
+    let mut boot = models.boot(file, Backend::Cpu).pin();
+
+    while let Some(event) = boot.sip().await {
+        if let BootEvent::Progressed { stage, percent } = event {
+            eprintln!("{stage} {percent}%");
+        }
+    }
+
+    let mut session = ChatSession::new(boot.await?);
+    session.send(question).await?;
+
+    if let Some(Item::Reply(reply)) = session.history().last() {
+        println!("{}", reply.content);
+    }
+
+    let id = session.save().await?;
+    eprintln!("Saved as chat {id:?}");
+
+    Ok(())
+}
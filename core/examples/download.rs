@@ -0,0 +1,40 @@
+//! Downloads a GGUF file from Hugging Face into the library.
+//!
+//! cargo run -p icebreaker_core --example download -- \
+//!     bartowski/Llama-3.2-1B-Instruct-GGUF Llama-3.2-1B-Instruct-Q4_K_M.gguf
+use icebreaker_core::model::{self, File};
+use icebreaker_core::{DownloadManager, Error, ModelManager, Settings};
+
+use sipper::Sipper;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let mut args = std::env::args().skip(1);
+
+    let (Some(model), Some(name)) = (args.next(), args.next()) else {
+        eprintln!("Usage: download <author/model> <file.gguf>");
+        return Ok(());
+    };
+
+    let models = ModelManager::open(Settings::fetch().unwrap_or_default()).await?;
+    let downloads = DownloadManager::new(models);
+
+    let mut download = downloads
+        .download(File {
+            model: model::Id(model),
+            name,
+            size: None,
+            hub: model::Hub::default(),
+        })
+        .pin();
+
+    while let Some(progress) = download.sip().await {
+        if let Some((_total, percent)) = progress.percent() {
+            eprint!("\rDownloading... {percent}%");
+        }
+    }
+
+    println!("\n{}", download.await?.display());
+
+    Ok(())
+}
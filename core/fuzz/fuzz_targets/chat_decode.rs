@@ -0,0 +1,11 @@
+//! Feeds arbitrary bytes to the chat decoder, which must fail instead of panicking.
+//!
+//! Run with `cargo fuzz run chat_decode` from the `core` directory.
+#![no_main]
+
+use icebreaker_core::Chat;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|bytes: &[u8]| {
+    let _ = Chat::decode(bytes);
+});
@@ -0,0 +1,176 @@
+use chrono::{DateTime, Local};
+use decoder::{decode, encode, Value};
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{LazyLock, Mutex, RwLock};
+
+static POLICIES: RwLock<Policies> = RwLock::new(Policies {
+    tools: BTreeMap::new(),
+});
+
+static PENDING: LazyLock<Mutex<HashMap<Id, oneshot::Sender<bool>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub const BUILT_IN: &[&str] = &[
+    "search",
+    "scrape_text",
+    crate::web::FETCH,
+    crate::filesystem::READ_FILE,
+    crate::filesystem::LIST_DIRECTORY,
+    crate::reminder::TOOL,
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Policy {
+    #[default]
+    Auto,
+    Ask,
+    Deny,
+}
+
+impl Policy {
+    pub const ALL: &[Self] = &[Self::Auto, Self::Ask, Self::Deny];
+
+    fn decode(value: Value) -> decoder::Result<Self> {
+        let policy = decode::string(value)?;
+
+        Ok(match policy.as_str() {
+            "ask" => Self::Ask,
+            "deny" => Self::Deny,
+            _ => Self::Auto,
+        })
+    }
+
+    fn encode(self) -> Value {
+        encode::string(match self {
+            Self::Auto => "auto",
+            Self::Ask => "ask",
+            Self::Deny => "deny",
+        })
+    }
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Auto => "Auto-approve",
+            Self::Ask => "Ask every time",
+            Self::Deny => "Deny",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Policies {
+    pub tools: BTreeMap<String, Policy>,
+}
+
+impl Policies {
+    pub fn get(&self, tool: &str) -> Policy {
+        self.tools.get(tool).copied().unwrap_or_default()
+    }
+
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let tools = decode::sequence(|value| {
+            let mut entry = decode::map(value)?;
+
+            Ok((
+                entry.required("tool", decode::string)?,
+                entry.required("policy", Policy::decode)?,
+            ))
+        })(value)?;
+
+        Ok(Self {
+            tools: tools.into_iter().collect(),
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        encode::sequence(
+            self.tools
+                .iter()
+                .filter(|(_tool, policy)| **policy != Policy::Auto)
+                .map(|(tool, policy)| {
+                    encode::map([("tool", encode::string(tool)), ("policy", policy.encode())])
+                        .into_value()
+                }),
+        )
+    }
+}
+
+pub fn configure(policies: Policies) {
+    if let Ok(mut configuration) = POLICIES.write() {
+        *configuration = policies;
+    }
+}
+
+pub(crate) fn policy(tool: &str) -> Policy {
+    POLICIES
+        .read()
+        .map(|policies| policies.get(tool))
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Request {
+    pub id: Id,
+    pub tool: String,
+    pub inputs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Id(Uuid);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    pub tool: String,
+    pub inputs: Vec<String>,
+    pub at: DateTime<Local>,
+    pub by_user: bool,
+}
+
+impl Record {
+    pub(crate) fn new(tool: &str, inputs: &[String], by_user: bool) -> Self {
+        Self {
+            tool: tool.to_owned(),
+            inputs: inputs.to_vec(),
+            at: Local::now(),
+            by_user,
+        }
+    }
+}
+
+// A dropped request counts as a denial
+pub(crate) fn ask(tool: &str, inputs: &[String]) -> (Request, oneshot::Receiver<bool>) {
+    let (sender, receiver) = oneshot::channel();
+
+    let request = Request {
+        id: Id(Uuid::new_v4()),
+        tool: tool.to_owned(),
+        inputs: inputs.to_vec(),
+    };
+
+    if let Ok(mut pending) = PENDING.lock() {
+        // Requests of cancelled replies are never answered
+        pending.retain(|_id, sender| !sender.is_closed());
+
+        let _ = pending.insert(request.id, sender);
+    }
+
+    (request, receiver)
+}
+
+pub fn respond(id: Id, is_approved: bool) {
+    let sender = PENDING
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.remove(&id));
+
+    if let Some(sender) = sender {
+        let _ = sender.send(is_approved);
+    }
+}
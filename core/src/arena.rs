@@ -0,0 +1,159 @@
+use crate::assistant::{Assistant, Reply};
+use crate::directory;
+use crate::Error;
+
+use langchain_rust::schemas::Message;
+use serde::{Deserialize, Serialize};
+use sipper::{sipper, Sipper, Straw};
+use tokio::fs;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+const SYSTEM_PROMPT: &str = "You are a helpful assistant.";
+const INITIAL_RATING: f64 = 1000.0;
+const K_FACTOR: f64 = 32.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    A,
+    B,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vote {
+    A,
+    B,
+    Tie,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Replied(Side, Reply),
+}
+
+pub fn duel<'a>(
+    a: &'a Assistant,
+    b: &'a Assistant,
+    prompt: String,
+) -> impl Straw<(), Event, Error> + 'a {
+    sipper(move |sender| async move {
+        let messages = [Message::new_human_message(prompt)];
+
+        let reply = |assistant: &'a Assistant, side| {
+            let messages = &messages;
+            let sender = sender.clone();
+
+            async move {
+                assistant
+                    .reply(SYSTEM_PROMPT, messages, &[])
+                    .with(move |(reply, _token)| Event::Replied(side, reply))
+                    .run(&sender)
+                    .await
+            }
+        };
+
+        let (a, b) = futures::join!(reply(a, Side::A), reply(b, Side::B));
+        let _ = a?;
+        let _ = b?;
+
+        Ok(())
+    })
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Scores {
+    ratings: BTreeMap<String, Rating>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Rating {
+    pub elo: f64,
+    pub wins: u32,
+    pub losses: u32,
+    pub ties: u32,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            elo: INITIAL_RATING,
+            wins: 0,
+            losses: 0,
+            ties: 0,
+        }
+    }
+}
+
+impl Scores {
+    pub async fn fetch() -> Result<Self, Error> {
+        let bytes = match fs::read(Self::path()).await {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    pub async fn save(self) -> Result<Self, Error> {
+        let path = Self::path();
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).await?;
+        }
+
+        fs::write(path, serde_json::to_vec_pretty(&self)?).await?;
+
+        Ok(self)
+    }
+
+    pub fn record(&mut self, a: &str, b: &str, vote: Vote) {
+        let rating_a = self.ratings.get(a).copied().unwrap_or_default();
+        let rating_b = self.ratings.get(b).copied().unwrap_or_default();
+
+        let expected_a = 1.0 / (1.0 + 10f64.powf((rating_b.elo - rating_a.elo) / 400.0));
+        let score_a = match vote {
+            Vote::A => 1.0,
+            Vote::B => 0.0,
+            Vote::Tie => 0.5,
+        };
+
+        let delta = K_FACTOR * (score_a - expected_a);
+
+        let a = self.ratings.entry(a.to_owned()).or_default();
+        a.elo += delta;
+
+        match vote {
+            Vote::A => a.wins += 1,
+            Vote::B => a.losses += 1,
+            Vote::Tie => a.ties += 1,
+        }
+
+        let b = self.ratings.entry(b.to_owned()).or_default();
+        b.elo -= delta;
+
+        match vote {
+            Vote::A => b.losses += 1,
+            Vote::B => b.wins += 1,
+            Vote::Tie => b.ties += 1,
+        }
+    }
+
+    pub fn leaderboard(&self) -> Vec<(&str, &Rating)> {
+        let mut ratings: Vec<_> = self
+            .ratings
+            .iter()
+            .map(|(model, rating)| (model.as_str(), rating))
+            .collect();
+
+        ratings.sort_by(|(_, a), (_, b)| b.elo.total_cmp(&a.elo));
+        ratings
+    }
+
+    fn path() -> PathBuf {
+        directory::data().join("arena.json")
+    }
+}
@@ -1,12 +1,19 @@
+use crate::audit;
+use crate::diagnostics;
 use crate::model;
 use crate::model::APIAccess;
 use crate::model::APIType;
 use crate::model::EndpointId;
 use crate::Error;
 use crate::model::StatusCheck;
+use crate::provider::Provider;
+use crate::ssh;
+use crate::telemetry;
+use crate::timeout;
 
 use langchain_rust::chain::LLMChainBuilder;
 use langchain_rust::language_models::llm::LLM;
+use langchain_rust::language_models::options::CallOptions;
 use langchain_rust::llm::nanogpt::NanoGPT;
 use langchain_rust::llm::OpenAIConfig;
 use langchain_rust::prompt::MessageFormatterStruct;
@@ -22,13 +29,16 @@ use tokio::process;
 use langchain_rust::schemas::Message as LMessage;
 use std::env;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone)]
 pub struct Assistant {
     pub file: model::FileAndAPI,
     lib: model::Library,
+    parameters: Parameters,
+    fallbacks: Vec<model::ModelOnline>,
+    backend: Backend,
     _server: Arc<Server>,
 }
 
@@ -44,6 +54,8 @@ impl Assistant {
         file: model::FileAndAPI,
         backend: Backend,
     ) -> impl Straw<Self, BootEvent, Error> {
+        use futures::future;
+        use std::sync::Mutex;
         use tokio::io::{self, AsyncBufReadExt};
         use tokio::process;
         use tokio::task;
@@ -63,8 +75,6 @@ impl Assistant {
         }
 
         sipper(move |sender| async move {
-            let directory = lib.directory();
-
             let file = match &file {
                 model::FileAndAPI { file: Some(f), .. } => f.clone(),
                 model::FileAndAPI { api: Some(ap), .. } => {
@@ -73,7 +83,10 @@ impl Assistant {
                             file: None,
                             api: Some(ap.clone()),
                         },
+                        fallbacks: lib.fallbacks(&ap.endpoint_id).to_vec(),
                         lib,
+                        parameters: Parameters::default(),
+                        backend,
                         _server: Server::API.into(),
                     });
                 }
@@ -82,6 +95,7 @@ impl Assistant {
 
             let mut sender = Sender(sender);
 
+            let directory = lib.directory_of(&file);
             let file2 = file.clone();
 
             let mut download =
@@ -112,7 +126,7 @@ impl Assistant {
 
             sender.progress("Detecting executor...", 0).await;
 
-            let (server, stdout, stderr) = if let Ok(version) =
+            let (mut server, command, stdout, stderr) = if let Ok(version) =
                 process::Command::new("llama-server")
                     .arg("--version")
                     .output()
@@ -137,13 +151,19 @@ impl Assistant {
                     ))
                     .await;
 
-                let mut server =
-                    Server::launch_with_executable("llama-server", &model_path, backend)?;
+                let arguments = Server::arguments(&model_path, backend);
+
+                let mut server = Server::launch_with_executable("llama-server", &arguments)?;
 
                 let stdout = server.stdout.take();
                 let stderr = server.stderr.take();
 
-                (Server::Process(server), stdout, stderr)
+                (
+                    Server::Process(Mutex::new(server)),
+                    format!("llama-server {arguments}"),
+                    stdout,
+                    stderr,
+                )
             } else if let Ok(_docker) = process::Command::new("docker")
                 .arg("version")
                 .output()
@@ -251,13 +271,21 @@ impl Assistant {
                     .stderr(std::process::Stdio::piped())
                     .spawn()?;
 
-                (server, logs.stdout.take(), logs.stderr.take())
+                (
+                    server,
+                    format!("docker {command}"),
+                    logs.stdout.take(),
+                    logs.stderr.take(),
+                )
             } else {
                 return Err(Error::NoExecutorAvailable(capture!()));
             };
 
+            let tail = Arc::new(Mutex::new(diagnostics::Tail::default()));
+
             let log_output = {
                 let mut sender = sender.clone();
+                let tail = tail.clone();
 
                 let mut lines = {
                     use futures::stream;
@@ -279,6 +307,11 @@ impl Assistant {
                     while let Some(line) = lines.next().await {
                         if let Ok(log) = line {
                             log::debug!("{log}");
+
+                            if let Ok(mut tail) = tail.lock() {
+                                tail.push(log.clone());
+                            }
+
                             sender.log(log).await;
                         }
                     }
@@ -306,25 +339,46 @@ impl Assistant {
             }
             .boxed();
 
-            let log_handle = task::spawn(log_output);
+            let mut log_handle = task::spawn(log_output);
+
+            // The output of the server ends when it exits
+            let is_healthy = match future::select(check_health, &mut log_handle).await {
+                future::Either::Left((is_healthy, _)) => is_healthy,
+                future::Either::Right(_) => false,
+            };
 
-            if check_health.await {
+            if is_healthy {
                 log_handle.abort();
 
                 return Ok(Self {
+                    fallbacks: lib.fallbacks(&file.endpoint()).to_vec(),
                     file: model::FileAndAPI {
                         file: file.into(),
                         ..Default::default()
                     },
                     lib,
+                    parameters: Parameters::default(),
+                    backend,
                     _server: Arc::new(server),
                 });
             }
 
-            Err(Error::ExecutorFailed(
-                "llama-server exited unexpectedly",
-                capture!(),
-            ))
+            let exit_code = match &mut server {
+                Server::Process(process) => match process.get_mut() {
+                    Ok(process) => process.wait().await.ok().and_then(|status| status.code()),
+                    Err(_) => None,
+                },
+                Server::Container(_) | Server::API => None,
+            };
+
+            let report = diagnostics::Report {
+                model: file.model.name().to_string(),
+                command,
+                exit_code,
+                output: tail.lock().map(|tail| tail.lines()).unwrap_or_default(),
+            };
+
+            Err(Error::ServerCrashed(Arc::new(report), capture!()))
         })
     }
 
@@ -339,10 +393,18 @@ impl Assistant {
             let mut reasoning_started_at: Option<Instant> = None;
             let mut content = String::new();
             let mut reasoning_content = String::new();
+            let mut endpoint = None;
+            let mut usage = None;
+            let started_at = Instant::now();
+            let mut first_token = None;
 
             let mut completion = self.complete(prompt, messages, append).pin();
 
             while let Some(token) = completion.sip().await {
+                if matches!(token, Token::Reasoning(_) | Token::Talking(_)) {
+                    let _ = first_token.get_or_insert_with(|| started_at.elapsed());
+                }
+
                 match &token {
                     Token::Reasoning(token) => {
                         reasoning = {
@@ -366,6 +428,19 @@ impl Assistant {
                     Token::Talking(token) => {
                         content.push_str(token);
                     }
+                    Token::Served(name) => {
+                        // Drop anything streamed by the endpoint that failed
+                        reasoning = None;
+                        reasoning_started_at = None;
+                        content.clear();
+                        reasoning_content.clear();
+                        first_token = None;
+                        usage = None;
+                        endpoint = Some(name.clone());
+                    }
+                    Token::Usage(new_usage) => {
+                        usage = Some(*new_usage);
+                    }
                 }
 
                 progress
@@ -378,20 +453,69 @@ impl Assistant {
                             } else {
                                 None
                             },
+                            endpoint: endpoint.clone(),
+                            provenance: Some(Provenance {
+                                first_token,
+                                ..self.provenance()
+                            }),
+                            usage,
                         },
                         token,
                     ))
                     .await;
             }
 
+            completion.await?;
+
+            // Local servers report how long they took, but not the energy spent
+            if let (Some(usage), Some(_target)) = (&mut usage, self.telemetry()) {
+                if let (Some(duration), Some(watts)) = (usage.duration, telemetry::power().await) {
+                    usage.energy = Some(watts * duration.as_secs_f64() / 3600.0);
+                }
+            }
+
             Ok(Reply {
                 reasoning: reasoning.clone(),
                 content: content.trim().to_owned(),
                 last_token: None,
+                endpoint,
+                provenance: Some(Provenance {
+                    first_token,
+                    ..self.provenance()
+                }),
+                usage,
             })
         })
     }
 
+    // The model is asked again once, with the parsing error, if its answer
+    // does not follow the schema
+    pub async fn structured<T>(
+        &self,
+        mut messages: Vec<LMessage>,
+        parse: impl Fn(&str) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        const SYSTEM_PROMPT: &str = "You extract structured data from text. \
+            You only ever answer with JSON that follows the given schema.";
+
+        let reply = self.reply(SYSTEM_PROMPT, &messages, &[]).await?;
+
+        match parse(&reply.content) {
+            Ok(value) => Ok(value),
+            Err(error) => {
+                messages.push(LMessage::new_ai_message(reply.content));
+                messages.push(LMessage::new_human_message(format!(
+                    "That answer could not be parsed ({error}). \
+                    Answer again with only the JSON object."
+                )));
+
+                let reply = self.reply(SYSTEM_PROMPT, &messages, &[]).await?;
+
+                parse(&reply.content)
+            }
+        }
+    }
+
     pub fn complete<'a>(
         &'a self,
         system_prompt: &'a str,
@@ -399,164 +523,386 @@ impl Assistant {
         append: &'a [LMessage],
     ) -> impl Straw<(), Token, Error> + 'a {
         sipper(move |mut sender| async move {
-            match self._server.as_ref() {
-                Server::API => {
-                    let model = self.file.api.as_ref().unwrap();
-                    match model.config.kind {
-                        APIType::NanoGPT => {
-                            use futures::StreamExt;
-                            use langchain_rust::{
-                                chain::{Chain, LLMChainBuilder},
-                                fmt_message, fmt_template, message_formatter,
-                                prompt::HumanMessagePromptTemplate,
-                                prompt_args,
-                                schemas::messages::Message,
-                                template_fstring,
-                            };
-
-                            use langchain_rust::llm::nanogpt::*;
-
-                            let nano: NanoGPT<OpenAIConfig> =
-                                NanoGPT::new(model.config.openai_compat.clone().unwrap().into())
-                                    .with_model(model.endpoint_id.slash_id().0.clone());
-
-                            let mut fmt = MessageFormatterStruct::new();
-                            for msg in messages {
-                                fmt.add_message(msg.clone());
+            let primary = match self._server.as_ref() {
+                Server::API => self.file.api.as_ref(),
+                Server::Process(_) | Server::Container(_) => None,
+            };
+
+            let mut endpoints = std::iter::once(primary)
+                .chain(self.fallbacks.iter().map(Some))
+                .enumerate()
+                .peekable();
+
+            while let Some((attempt, endpoint)) = endpoints.next() {
+                if attempt > 0 {
+                    let name = endpoint.map_or_else(
+                        || self.name().to_owned(),
+                        |model| model.endpoint_id.to_string(),
+                    );
+
+                    let _ = sender.send(Token::Served(name)).await;
+                }
+
+                let mut has_tokens = false;
+
+                let result = match endpoint {
+                    Some(model) => {
+                        self.stream_api(
+                            model,
+                            system_prompt,
+                            messages,
+                            append,
+                            &mut sender,
+                            &mut has_tokens,
+                        )
+                        .await
+                    }
+                    None => {
+                        self.stream_local(
+                            system_prompt,
+                            messages,
+                            append,
+                            &mut sender,
+                            &mut has_tokens,
+                        )
+                        .await
+                    }
+                };
+
+                match result {
+                    // Nothing was streamed yet, so the next endpoint can take over
+                    Err(error) if !has_tokens && endpoints.peek().is_some() => {
+                        warn!("endpoint failed, failing over: {error}");
+                    }
+                    result => return result,
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    async fn stream_api(
+        &self,
+        model: &model::ModelOnline,
+        system_prompt: &str,
+        messages: &[LMessage],
+        append: &[LMessage],
+        sender: &mut sipper::Sender<Token>,
+        has_tokens: &mut bool,
+    ) -> Result<(), Error> {
+        match model.config.kind {
+            APIType::NanoGPT => {
+                use futures::StreamExt;
+                use langchain_rust::{
+                    chain::{Chain, LLMChainBuilder},
+                    fmt_message, fmt_template, message_formatter,
+                    prompt::HumanMessagePromptTemplate,
+                    prompt_args,
+                    schemas::messages::Message,
+                    template_fstring,
+                };
+
+                use langchain_rust::llm::nanogpt::*;
+
+                let model_id = model.endpoint_id.slash_id().0.clone();
+
+                let mut audit = {
+                    let mut body = json!({
+                        "model": model_id,
+                        "messages": to_messages(system_prompt, messages, append),
+                    });
+
+                    self.parameters.apply(&mut body);
+
+                    audit::Entry::start(
+                        model.config.kind.clone().into(),
+                        &model_id,
+                        "nanogpt",
+                        &body,
+                    )
+                };
+
+                let nano: NanoGPT<OpenAIConfig> =
+                    NanoGPT::new(model.config.openai_compat.clone().unwrap().into())
+                        .with_model(model_id)
+                        .with_options(self.parameters.to_options());
+
+                let mut fmt = MessageFormatterStruct::new();
+                fmt.add_message(Message::new_system_message(system_prompt));
+                for msg in messages {
+                    fmt.add_message(msg.clone());
+                }
+                for msg in append {
+                    fmt.add_message(msg.clone());
+                }
+                let chain = LLMChainBuilder::new()
+                    .llm(nano)
+                    .prompt(fmt)
+                    .build()
+                    .unwrap();
+
+                let timeout = timeout::current(model.config.kind.clone().into());
+
+                let vars = prompt_args! {};
+                let mut stream = match timeout.next(true, chain.stream(vars)).await? {
+                    Ok(stream) => stream,
+                    Err(error) => {
+                        warn!("Error: {:?}", error);
+
+                        if let Some(mut audit) = audit {
+                            audit.error(format!("{error:?}"));
+                            audit.finish();
+                        }
+
+                        return Err(Error::InvalidResponse("nanogpt", capture!()));
+                    }
+                };
+
+                loop {
+                    let result = match timeout.next(!*has_tokens, stream.next()).await {
+                        Ok(Some(result)) => result,
+                        Ok(None) => break,
+                        Err(error) => {
+                            if let Some(mut audit) = audit {
+                                audit.error(&error);
+                                audit.finish();
                             }
-                            for msg in append {
-                                fmt.add_message(msg.clone());
+
+                            return Err(error);
+                        }
+                    };
+
+                    *has_tokens = true;
+
+                    match result {
+                        Ok(data) => {
+                            if let Some(audit) = &mut audit {
+                                audit.content(&data.content);
                             }
-                            let chain = LLMChainBuilder::new()
-                                .llm(nano)
-                                .prompt(fmt)
-                                .build()
-                                .unwrap();
-
-                            let vars = prompt_args! {};
-                            let mut stream = chain.stream(vars).await.unwrap();
-                            while let Some(result) = stream.next().await {
-                                match result {
-                                    Ok(data) => {
-                                        sender.send(Token::Talking(data.content)).await;
-                                    },
-                                    Err(e) => {
-                                        warn!("Error: {:?}", e);
-                                    }
-                                }
+
+                            sender.send(Token::Talking(data.content)).await;
+                        },
+                        Err(e) => {
+                            warn!("Error: {:?}", e);
+
+                            if let Some(audit) = &mut audit {
+                                audit.error(e);
                             }
                         }
-                        _ => unimplemented!(),
                     }
                 }
-                Server::Process(_) | Server::Container(_) => {
-                    let client = reqwest::Client::new();
-
-                    let request = {
-                        let messages: Vec<_> = [("system", system_prompt)]
-                            .into_iter()
-                            .chain(messages.iter().chain(append).map(|k| unimplemented!()))
-                            .map(|(role, content)| {
-                                json!({
-                                    "role": role,
-                                    "content": content
-                                })
-                            })
-                            .collect();
 
-                        client
-                            .post(format!(
-                                "http://localhost:{port}/v1/chat/completions",
-                                port = Self::HOST_PORT
-                            ))
-                            .json(&json!({
-                                "model": format!("{model}", model = self.name()),
-                                "messages": messages,
-                                "stream": true,
-                                "cache_prompt": true,
-                            }))
-                    };
+                if let Some(audit) = audit {
+                    audit.finish();
+                }
+            }
+            APIType::AzureOpenAI => {
+                let Some(azure) = &model.config.azure else {
+                    return Err(Error::NotConfigured("Azure OpenAI", capture!()));
+                };
 
-                    let mut response = request.send().await?.error_for_status()?;
-                    let mut buffer = Vec::new();
-                    let mut is_reasoning = None;
+                let deployment = &model.endpoint_id.slash_id().0;
+                let url = azure.url(deployment);
 
-                    while let Some(chunk) = response.chunk().await? {
-                        buffer.extend(chunk);
+                let timeout = timeout::current(Provider::AzureOpenAI);
+                let client = reqwest::Client::builder()
+                    .connect_timeout(timeout.connect())
+                    .build()?;
 
-                        let mut lines = buffer
-                            .split(|byte| *byte == 0x0A)
-                            .filter(|bytes| !bytes.is_empty());
+                let mut body = json!({
+                    "messages": to_messages(system_prompt, messages, append),
+                    "stream": true,
+                    "stream_options": { "include_usage": true },
+                });
 
-                        let last_line = if buffer.ends_with(&[0x0A]) {
-                            &[]
-                        } else {
-                            lines.next_back().unwrap_or_default()
-                        };
+                self.parameters.apply(&mut body);
 
-                        for line in lines {
-                            if let Ok(data) = std::str::from_utf8(line) {
-                                #[derive(Deserialize)]
-                                struct Data {
-                                    choices: Vec<Choice>,
-                                }
-
-                                #[derive(Deserialize)]
-                                struct Choice {
-                                    delta: Delta,
-                                }
-
-                                #[derive(Deserialize)]
-                                struct Delta {
-                                    content: Option<String>,
-                                }
-
-                                if data == "data: [DONE]" {
-                                    break;
-                                }
-
-                                let mut data: Data = serde_json::from_str(
-                                    data.trim().strip_prefix("data: ").unwrap_or(data),
-                                )?;
-
-                                if let Some(choice) = data.choices.first_mut() {
-                                    if let Some(content) = &mut choice.delta.content {
-                                        match is_reasoning {
-                                            None if content.contains("<think>") => {
-                                                is_reasoning = Some(true);
-                                                *content = content.replace("<think>", "");
-                                            }
-                                            Some(true) if content.contains("</think>") => {
-                                                is_reasoning = Some(false);
-                                                *content = content.replace("</think>", "");
-                                            }
-                                            _ => {}
-                                        }
-
-                                        let _ = sender
-                                            .send(if is_reasoning.unwrap_or_default() {
-                                                Token::Reasoning(content.clone())
-                                            } else {
-                                                Token::Talking(content.clone())
-                                            })
-                                            .await;
-                                    }
-                                }
-                            };
-                        }
+                let audit = audit::Entry::start(Provider::AzureOpenAI, deployment, &url, &body);
 
-                        buffer = last_line.to_vec();
-                    }
+                let request = client
+                    .post(&url)
+                    .header("api-key", &azure.api_key)
+                    .json(&body);
+
+                return stream_chat(
+                    request,
+                    timeout,
+                    audit,
+                    model.cost.as_ref(),
+                    sender,
+                    has_tokens,
+                )
+                .await;
+            }
+            APIType::Groq
+            | APIType::Together
+            | APIType::Mistral
+            | APIType::OpenAICompatible
+            | APIType::Ssh => {
+                let id = &model.endpoint_id.slash_id().0;
+
+                let base_url = match &model.config.ssh {
+                    Some(remote) => ssh::connect(remote, id).await?,
+                    None => model
+                        .config
+                        .base_url()
+                        .map(str::to_owned)
+                        .ok_or_else(|| Error::NotConfigured("the base URL", capture!()))?,
+                };
+
+                let url = format!("{base_url}/chat/completions");
+                let provider = Provider::from(model.config.kind.clone());
+
+                let timeout = timeout::current(provider);
+                let client = reqwest::Client::builder()
+                    .connect_timeout(timeout.connect())
+                    .build()?;
+
+                let mut body = json!({
+                    "model": id,
+                    "messages": to_messages(system_prompt, messages, append),
+                    "stream": true,
+                });
+
+                // Mistral reports usage without being asked, and does not know the option
+                if model.config.kind != APIType::Mistral {
+                    body["stream_options"] = json!({ "include_usage": true });
+                }
+
+                self.parameters.apply(&mut body);
+
+                let audit = audit::Entry::start(provider, id, &url, &body);
+                let mut request = client.post(&url).json(&body);
+
+                if let Some(api_key) = &model.config.api_key {
+                    request = request.bearer_auth(api_key);
                 }
+
+                return stream_chat(
+                    request,
+                    timeout,
+                    audit,
+                    model.cost.as_ref(),
+                    sender,
+                    has_tokens,
+                )
+                .await;
             }
-            Ok(())
-        })
+            // Fallbacks may be any bookmarked endpoint, so they fail like any other
+            _ => {
+                return Err(Error::Unsupported(
+                    Provider::from(model.config.kind.clone()).name(),
+                    capture!(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn stream_local(
+        &self,
+        system_prompt: &str,
+        messages: &[LMessage],
+        append: &[LMessage],
+        sender: &mut sipper::Sender<Token>,
+        has_tokens: &mut bool,
+    ) -> Result<(), Error> {
+        let timeout = timeout::current(Provider::Local);
+        let client = reqwest::Client::builder()
+            .connect_timeout(timeout.connect())
+            .build()?;
+
+        let endpoint = format!(
+            "http://localhost:{port}/v1/chat/completions",
+            port = Self::HOST_PORT
+        );
+
+        let (request, audit) = {
+            let mut body = json!({
+                "model": format!("{model}", model = self.name()),
+                "messages": to_messages(system_prompt, messages, append),
+                "stream": true,
+                "stream_options": { "include_usage": true },
+                "cache_prompt": true,
+            });
+
+            self.parameters.apply(&mut body);
+
+            let audit = audit::Entry::start(Provider::Local, self.name(), &endpoint, &body);
+
+            (client.post(&endpoint).json(&body), audit)
+        };
+
+        stream_chat(request, timeout, audit, None, sender, has_tokens).await
     }
 
     pub fn name(&self) -> &str {
         self.file.slash_id().name()
     }
 
+    // Nothing is dropped when the app exits, so the server must be stopped here
+    pub async fn shutdown(&self) {
+        self._server.stop().await;
+    }
+
+    pub fn telemetry(&self) -> Option<telemetry::Target> {
+        match self._server.as_ref() {
+            Server::Process(process) => process
+                .lock()
+                .ok()
+                .and_then(|process| process.id())
+                .map(telemetry::Target::Process),
+            Server::Container(id) => Some(telemetry::Target::Container(id.clone())),
+            Server::API => None,
+        }
+    }
+
+    pub fn parameters(&self) -> Parameters {
+        self.parameters
+    }
+
+    pub fn with_parameters(mut self, parameters: Parameters) -> Self {
+        self.parameters = parameters;
+        self
+    }
+
+    pub fn context_window(&self) -> usize {
+        match self._server.as_ref() {
+            // llama-server falls back to its default context size without `--ctx-size`
+            Server::Process(_) | Server::Container(_) => 4_096,
+            Server::API => 128_000,
+        }
+    }
+
+    pub fn provenance(&self) -> Provenance {
+        let backend = match self._server.as_ref() {
+            Server::API => self
+                .file
+                .api
+                .as_ref()
+                .map(|api| format!("{:?} API", api.config.kind))
+                .unwrap_or_else(|| "API".to_owned()),
+            Server::Process(_) => format!("llama-server ({:?})", self.backend),
+            Server::Container(_) => format!("llama.cpp container ({:?})", self.backend),
+        };
+
+        Provenance {
+            model: self.file.slash_id().0.clone(),
+            file: self.file.file.as_ref().map(|file| file.name.clone()),
+            quantization: self
+                .file
+                .file
+                .as_ref()
+                .and_then(|file| file.variant())
+                .map(str::to_owned),
+            backend,
+            parameters: self.parameters,
+            first_token: None,
+        }
+    }
+
     pub async fn check_api_status(&self) -> Result<StatusCheck, Error> {
         if let Server::API = self._server.as_ref() {
             self.file.api.as_ref().unwrap().check().await
@@ -609,11 +955,364 @@ impl SimpleMessage {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Parameters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+}
+
+impl Parameters {
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            temperature: self.temperature.or(fallback.temperature),
+            top_p: self.top_p.or(fallback.top_p),
+            max_tokens: self.max_tokens.or(fallback.max_tokens),
+        }
+    }
+
+    fn apply(&self, body: &mut serde_json::Value) {
+        if let Some(temperature) = self.temperature {
+            body["temperature"] = json!(temperature);
+        }
+
+        if let Some(top_p) = self.top_p {
+            body["top_p"] = json!(top_p);
+        }
+
+        if let Some(max_tokens) = self.max_tokens {
+            body["max_tokens"] = json!(max_tokens);
+        }
+    }
+
+    fn to_options(self) -> CallOptions {
+        let mut options = CallOptions::default();
+
+        if let Some(temperature) = self.temperature {
+            options = options.with_temperature(temperature);
+        }
+
+        if let Some(top_p) = self.top_p {
+            options = options.with_top_p(top_p);
+        }
+
+        if let Some(max_tokens) = self.max_tokens {
+            options = options.with_max_tokens(max_tokens);
+        }
+
+        options
+    }
+}
+
+async fn stream_chat(
+    request: reqwest::RequestBuilder,
+    timeout: timeout::Timeout,
+    mut audit: Option<audit::Entry>,
+    cost: Option<&model::Cost>,
+    sender: &mut sipper::Sender<Token>,
+    has_tokens: &mut bool,
+) -> Result<(), Error> {
+    let response = timeout
+        .next(true, request.send())
+        .await
+        .and_then(|response| Ok(response?.error_for_status()?));
+
+    let mut response = match response {
+        Ok(response) => response,
+        Err(error) => {
+            if let Some(mut audit) = audit {
+                audit.error(&error);
+                audit.finish();
+            }
+
+            return Err(error);
+        }
+    };
+
+    let mut buffer = Vec::new();
+    let mut is_reasoning = None;
+
+    loop {
+        let chunk = match timeout
+            .next(!*has_tokens, response.chunk())
+            .await
+            .and_then(|chunk| Ok(chunk?))
+        {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(error) => {
+                if let Some(mut audit) = audit {
+                    audit.error(&error);
+                    audit.finish();
+                }
+
+                return Err(error);
+            }
+        };
+
+        *has_tokens = true;
+        buffer.extend(chunk);
+
+        let mut lines = buffer
+            .split(|byte| *byte == 0x0A)
+            .filter(|bytes| !bytes.is_empty());
+
+        let last_line = if buffer.ends_with(&[0x0A]) {
+            &[]
+        } else {
+            lines.next_back().unwrap_or_default()
+        };
+
+        for line in lines {
+            if let Ok(data) = std::str::from_utf8(line) {
+                #[derive(Deserialize)]
+                struct Data {
+                    #[serde(default)]
+                    choices: Vec<Choice>,
+                    usage: Option<Usage>,
+                    timings: Option<Timings>,
+                }
+
+                #[derive(Deserialize)]
+                struct Timings {
+                    prompt_n: u64,
+                    prompt_ms: f64,
+                    predicted_n: u64,
+                    predicted_ms: f64,
+                }
+
+                #[derive(Deserialize)]
+                struct Choice {
+                    delta: Delta,
+                }
+
+                #[derive(Deserialize)]
+                struct Delta {
+                    content: Option<String>,
+                }
+
+                if data == "data: [DONE]" {
+                    break;
+                }
+
+                let mut data: Data =
+                    serde_json::from_str(data.trim().strip_prefix("data: ").unwrap_or(data))?;
+
+                // Usually in the last chunk, without any choices
+                let usage = match (data.usage, data.timings) {
+                    (usage, Some(timings)) => Some(Usage {
+                        duration: Some(Duration::from_secs_f64(
+                            (timings.prompt_ms + timings.predicted_ms) / 1000.0,
+                        )),
+                        ..usage.unwrap_or(Usage {
+                            prompt_tokens: timings.prompt_n,
+                            completion_tokens: timings.predicted_n,
+                            ..Usage::default()
+                        })
+                    }),
+                    (usage, None) => usage,
+                };
+
+                if let Some(usage) = usage {
+                    let _ = sender.send(Token::Usage(usage.priced(cost))).await;
+                }
+
+                if let Some(choice) = data.choices.first_mut() {
+                    if let Some(content) = &mut choice.delta.content {
+                        match is_reasoning {
+                            None if content.contains("<think>") => {
+                                is_reasoning = Some(true);
+                                *content = content.replace("<think>", "");
+                            }
+                            Some(true) if content.contains("</think>") => {
+                                is_reasoning = Some(false);
+                                *content = content.replace("</think>", "");
+                            }
+                            _ => {}
+                        }
+
+                        if let Some(audit) = &mut audit {
+                            if is_reasoning.unwrap_or_default() {
+                                audit.reasoning(content);
+                            } else {
+                                audit.content(content);
+                            }
+                        }
+
+                        let _ = sender
+                            .send(if is_reasoning.unwrap_or_default() {
+                                Token::Reasoning(content.clone())
+                            } else {
+                                Token::Talking(content.clone())
+                            })
+                            .await;
+                    }
+                }
+            };
+        }
+
+        buffer = last_line.to_vec();
+    }
+
+    if let Some(audit) = audit {
+        audit.finish();
+    }
+
+    Ok(())
+}
+
+fn to_messages(
+    system_prompt: &str,
+    messages: &[LMessage],
+    append: &[LMessage],
+) -> Vec<serde_json::Value> {
+    [json!({
+        "role": "system",
+        "content": system_prompt,
+    })]
+    .into_iter()
+    .chain(messages.iter().chain(append).map(to_json))
+    .collect()
+}
+
+fn to_json(message: &LMessage) -> serde_json::Value {
+    use langchain_rust::schemas::MessageType;
+
+    let role = match message.message_type {
+        MessageType::SystemMessage => "system",
+        MessageType::AIMessage => "assistant",
+        MessageType::HumanMessage => "user",
+        MessageType::ToolMessage => "tool",
+    };
+
+    match &message.images {
+        Some(images) if !images.is_empty() => json!({
+            "role": role,
+            "content": [json!({ "type": "text", "text": message.content })]
+                .into_iter()
+                .chain(images.iter().map(|image| json!({
+                    "type": "image_url",
+                    "image_url": { "url": image.image_url },
+                })))
+                .collect::<Vec<_>>(),
+        }),
+        _ => json!({
+            "role": role,
+            "content": message.content,
+        }),
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Reply {
     pub reasoning: Option<Reasoning>,
     pub content: String,
     pub last_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Usage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub energy: Option<f64>,
+}
+
+impl Usage {
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    fn priced(self, cost: Option<&model::Cost>) -> Self {
+        let cost = self.cost.or_else(|| {
+            let cost = cost?;
+            let price = |tokens: u64, quantity: &model::Quantity| {
+                tokens as f64 * quantity.num / quantity.denom
+            };
+
+            Some(
+                price(self.prompt_tokens, &cost.prompt)
+                    + price(self.completion_tokens, &cost.completion),
+            )
+        });
+
+        Self { cost, ..self }
+    }
+}
+
+impl std::ops::Add for Usage {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            prompt_tokens: self.prompt_tokens + other.prompt_tokens,
+            completion_tokens: self.completion_tokens + other.completion_tokens,
+            cost: sum(self.cost, other.cost),
+            duration: sum(self.duration, other.duration),
+            energy: sum(self.energy, other.energy),
+        }
+    }
+}
+
+fn sum<T: std::ops::Add<Output = T> + Default>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (None, None) => None,
+        (a, b) => Some(a.unwrap_or_default() + b.unwrap_or_default()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantization: Option<String>,
+    pub backend: String,
+    #[serde(default)]
+    pub parameters: Parameters,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_token: Option<Duration>,
+}
+
+impl Provenance {
+    pub fn details(&self) -> Vec<(&'static str, String)> {
+        let parameters = &self.parameters;
+
+        [
+            Some(("Model", self.model.clone())),
+            self.file.clone().map(|file| ("File", file)),
+            self.quantization
+                .clone()
+                .map(|quantization| ("Quantization", quantization)),
+            Some(("Backend", self.backend.clone())),
+            parameters
+                .temperature
+                .map(|temperature| ("Temperature", temperature.to_string())),
+            parameters.top_p.map(|top_p| ("Top P", top_p.to_string())),
+            parameters
+                .max_tokens
+                .map(|max_tokens| ("Max tokens", max_tokens.to_string())),
+            self.first_token
+                .map(|first_token| ("First token", format!("{} ms", first_token.as_millis()))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -622,25 +1321,24 @@ pub struct Reasoning {
     pub duration: Duration,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     Reasoning(String),
     Talking(String),
+    // A fallback endpoint took over; previous tokens should be discarded
+    Served(String),
+    Usage(Usage),
 }
 
 #[derive(Debug)]
 enum Server {
     Container(String),
-    Process(process::Child),
+    Process(Mutex<process::Child>),
     API,
 }
 
 impl Server {
-    fn launch_with_executable(
-        executable: &'static str,
-        file: &Path,
-        backend: Backend,
-    ) -> Result<process::Child, Error> {
+    fn arguments(file: &Path, backend: Backend) -> String {
         let gpu_flags = match backend {
             Backend::Cpu => "",
             Backend::Cuda | Backend::Rocm => "--gpu-layers 80",
@@ -648,11 +1346,18 @@ impl Server {
 
         let custom_args = env::var("ICEBREAKER_LLAMA_CPP_ARGS").unwrap_or_default();
 
+        format!(
+            "--model {file} --port 8080 --host 0.0.0.0 {gpu_flags} {custom_args}",
+            file = file.display(),
+        )
+    }
+
+    fn launch_with_executable(
+        executable: &'static str,
+        arguments: &str,
+    ) -> Result<process::Child, Error> {
         let server = process::Command::new(executable)
-            .args(Self::parse_args(&format!(
-                "--model {file} --port 8080 --host 0.0.0.0 {gpu_flags} {custom_args}",
-                file = file.display(),
-            )))
+            .args(Self::parse_args(arguments))
             .kill_on_drop(true)
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
@@ -669,6 +1374,28 @@ impl Server {
     }
 }
 
+impl Server {
+    async fn stop(&self) {
+        match self {
+            Self::Process(process) => {
+                if let Ok(mut process) = process.lock() {
+                    let _ = process.start_kill();
+                }
+            }
+            Self::Container(id) => {
+                let _ = process::Command::new("docker")
+                    .args(["stop", id])
+                    .stdin(std::process::Stdio::null())
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null())
+                    .status()
+                    .await;
+            }
+            Self::API => {}
+        }
+    }
+}
+
 impl Drop for Server {
     fn drop(&mut self) {
         use std::process;
@@ -0,0 +1,83 @@
+use crate::directory;
+use crate::Error;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use thiserror::capture;
+use tokio::fs;
+use tokio::task;
+use uuid::Uuid;
+
+use std::io::Cursor;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    pub id: Id,
+    pub kind: Kind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Id(Uuid);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Kind {
+    Image { width: u32, height: u32 },
+}
+
+impl Attachment {
+    pub async fn image(width: u32, height: u32, rgba: Vec<u8>) -> Result<Self, Error> {
+        let attachment = Self {
+            id: Id(Uuid::new_v4()),
+            kind: Kind::Image { width, height },
+            text: None,
+        };
+
+        let png = task::spawn_blocking(move || {
+            let mut png = Vec::new();
+
+            image::RgbaImage::from_raw(width, height, rgba)
+                .ok_or(Error::InvalidImage(capture!()))?
+                .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+                .map_err(|_| Error::InvalidImage(capture!()))?;
+
+            Ok::<_, Error>(png)
+        })
+        .await??;
+
+        let path = attachment.path();
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).await?;
+        }
+
+        fs::write(path, png).await?;
+
+        Ok(attachment)
+    }
+
+    pub fn path(&self) -> PathBuf {
+        let extension = match self.kind {
+            Kind::Image { .. } => "png",
+        };
+
+        directory::data()
+            .join("attachments")
+            .join(format!("{}.{extension}", self.id.0.simple()))
+    }
+
+    pub fn data_url(&self) -> Result<String, Error> {
+        let bytes = std::fs::read(self.path())?;
+
+        let mime = match self.kind {
+            Kind::Image { .. } => "image/png",
+        };
+
+        Ok(format!(
+            "data:{mime};base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(bytes)
+        ))
+    }
+}
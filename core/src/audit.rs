@@ -0,0 +1,263 @@
+use crate::directory;
+use crate::provider::Provider;
+
+use decoder::{decode, encode, Value};
+use serde_json::json;
+use tokio::task;
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::Instant;
+
+const DEFAULT_MAX_SIZE: u64 = 5 * 1024 * 1024;
+const DEFAULT_MAX_FILES: usize = 5;
+
+const SECRETS: &[&str] = &[
+    "api_key",
+    "apikey",
+    "access_token",
+    "authorization",
+    "password",
+    "secret",
+];
+
+static CONFIGURATION: RwLock<Audit> = RwLock::new(Audit::DISABLED);
+static WRITER: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Audit {
+    pub providers: Vec<Provider>,
+    pub max_size: u64,
+    pub max_files: usize,
+}
+
+impl Audit {
+    const DISABLED: Self = Self {
+        providers: Vec::new(),
+        max_size: DEFAULT_MAX_SIZE,
+        max_files: DEFAULT_MAX_FILES,
+    };
+
+    pub fn is_enabled(&self, provider: Provider) -> bool {
+        self.providers.contains(&provider)
+    }
+
+    pub fn toggle(&mut self, provider: Provider, enabled: bool) {
+        self.providers.retain(|candidate| *candidate != provider);
+
+        if enabled {
+            self.providers.push(provider);
+            self.providers.sort_by_key(|provider| provider.key());
+        }
+    }
+
+    pub fn directory() -> PathBuf {
+        directory::data().join("audit")
+    }
+
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut audit = decode::map(value)?;
+
+        let providers = audit
+            .optional(
+                "providers",
+                decode::sequence(|value| {
+                    let key = decode::string(value)?;
+
+                    Provider::from_key(&key)
+                        .ok_or_else(|| decoder::Error::custom(format!("unknown provider: {key}")))
+                }),
+            )?
+            .unwrap_or_default();
+
+        Ok(Self {
+            providers,
+            max_size: audit
+                .optional("max_size", decode::u64)?
+                .unwrap_or(DEFAULT_MAX_SIZE),
+            max_files: audit
+                .optional("max_files", decode::u64)?
+                .map_or(DEFAULT_MAX_FILES, |files| files as usize),
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        encode::map([
+            (
+                "providers",
+                encode::sequence(
+                    self.providers
+                        .iter()
+                        .map(|provider| encode::string(provider.key())),
+                ),
+            ),
+            ("max_size", encode::u64(self.max_size)),
+            ("max_files", encode::u64(self.max_files as u64)),
+        ])
+        .into_value()
+    }
+}
+
+impl Default for Audit {
+    fn default() -> Self {
+        Self::DISABLED
+    }
+}
+
+pub fn configure(audit: Audit) {
+    if let Ok(mut configuration) = CONFIGURATION.write() {
+        *configuration = audit;
+    }
+}
+
+#[derive(Debug)]
+pub struct Entry {
+    provider: Provider,
+    model: String,
+    endpoint: String,
+    request: serde_json::Value,
+    started_at: Instant,
+    reasoning: String,
+    content: String,
+    errors: Vec<String>,
+}
+
+impl Entry {
+    pub fn start(
+        provider: Provider,
+        model: &str,
+        endpoint: &str,
+        request: &serde_json::Value,
+    ) -> Option<Self> {
+        if !CONFIGURATION.read().ok()?.is_enabled(provider) {
+            return None;
+        }
+
+        Some(Self {
+            provider,
+            model: model.to_owned(),
+            endpoint: endpoint.to_owned(),
+            request: redact(request.clone()),
+            started_at: Instant::now(),
+            reasoning: String::new(),
+            content: String::new(),
+            errors: Vec::new(),
+        })
+    }
+
+    pub fn reasoning(&mut self, token: &str) {
+        self.reasoning.push_str(token);
+    }
+
+    pub fn content(&mut self, token: &str) {
+        self.content.push_str(token);
+    }
+
+    pub fn error(&mut self, error: impl ToString) {
+        self.errors.push(error.to_string());
+    }
+
+    pub fn finish(self) {
+        let Ok(configuration) = CONFIGURATION.read().map(|audit| audit.clone()) else {
+            return;
+        };
+
+        let record = json!({
+            "timestamp": chrono::Local::now().to_rfc3339(),
+            "provider": self.provider.key(),
+            "model": self.model,
+            "endpoint": self.endpoint,
+            "duration_ms": self.started_at.elapsed().as_millis() as u64,
+            "request": self.request,
+            "response": {
+                "reasoning": (!self.reasoning.is_empty()).then_some(self.reasoning),
+                "content": self.content,
+                "errors": self.errors,
+            },
+        });
+
+        let provider = self.provider;
+
+        drop(task::spawn_blocking(move || {
+            if let Err(error) = append(&configuration, provider, &record.to_string()) {
+                log::warn!("failed to write audit log: {error}");
+            }
+        }));
+    }
+}
+
+fn append(audit: &Audit, provider: Provider, line: &str) -> io::Result<()> {
+    let _guard = WRITER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let directory = Audit::directory();
+    fs::create_dir_all(&directory)?;
+
+    let current = file(&directory, provider, 0);
+
+    if fs::metadata(&current).is_ok_and(|metadata| metadata.len() >= audit.max_size) {
+        rotate(&directory, provider, audit.max_files.max(1))?;
+    }
+
+    let mut log = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(current)?;
+
+    writeln!(log, "{line}")
+}
+
+fn rotate(directory: &Path, provider: Provider, max_files: usize) -> io::Result<()> {
+    let _ = fs::remove_file(file(directory, provider, max_files - 1));
+
+    for generation in (0..max_files - 1).rev() {
+        let from = file(directory, provider, generation);
+
+        if from.exists() {
+            fs::rename(from, file(directory, provider, generation + 1))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn file(directory: &Path, provider: Provider, generation: usize) -> PathBuf {
+    if generation == 0 {
+        directory.join(format!("{}.jsonl", provider.key()))
+    } else {
+        directory.join(format!("{}.{generation}.jsonl", provider.key()))
+    }
+}
+
+fn redact(value: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| {
+                    let is_secret = SECRETS
+                        .iter()
+                        .any(|secret| key.eq_ignore_ascii_case(secret));
+
+                    if is_secret {
+                        (key, Value::String("[redacted]".to_owned()))
+                    } else {
+                        (key, redact(value))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(redact).collect()),
+        Value::String(string) if string.starts_with("data:") => {
+            let header = string.split(',').next().unwrap_or_default();
+
+            Value::String(format!("{header},[omitted]"))
+        }
+        value => value,
+    }
+}
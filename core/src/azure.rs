@@ -0,0 +1,77 @@
+use decoder::{decode, encode, Value};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_API_VERSION: &str = "2024-10-21";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Azure {
+    pub endpoint: String,
+    pub api_key: String,
+    pub api_version: String,
+    pub deployments: Vec<String>,
+}
+
+impl Default for Azure {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            api_key: String::new(),
+            api_version: DEFAULT_API_VERSION.to_owned(),
+            deployments: Vec::new(),
+        }
+    }
+}
+
+impl Azure {
+    pub fn is_configured(&self) -> bool {
+        !self.endpoint.trim().is_empty()
+            && !self.api_key.trim().is_empty()
+            && !self.deployments.is_empty()
+    }
+
+    pub fn url(&self, deployment: &str) -> String {
+        let api_version = if self.api_version.trim().is_empty() {
+            DEFAULT_API_VERSION
+        } else {
+            self.api_version.trim()
+        };
+
+        format!(
+            "{endpoint}/openai/deployments/{deployment}/chat/completions?api-version={api_version}",
+            endpoint = self.endpoint.trim().trim_end_matches('/'),
+        )
+    }
+
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut azure = decode::map(value)?;
+        let default = Self::default();
+
+        Ok(Self {
+            endpoint: azure
+                .optional("endpoint", decode::string)?
+                .unwrap_or_default(),
+            api_key: azure
+                .optional("api_key", decode::string)?
+                .unwrap_or_default(),
+            api_version: azure
+                .optional("api_version", decode::string)?
+                .unwrap_or(default.api_version),
+            deployments: azure
+                .optional("deployments", decode::sequence(decode::string))?
+                .unwrap_or_default(),
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        encode::map([
+            ("endpoint", encode::string(&self.endpoint)),
+            ("api_key", encode::string(&self.api_key)),
+            ("api_version", encode::string(&self.api_version)),
+            (
+                "deployments",
+                encode::sequence(self.deployments.iter().map(encode::string)),
+            ),
+        ])
+        .into_value()
+    }
+}
@@ -0,0 +1,200 @@
+use crate::directory;
+use crate::Error;
+
+use serde::{Deserialize, Serialize};
+use thiserror::capture;
+use tokio::task;
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+// Bumped whenever older versions cannot read the layout of the app data
+const VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+
+const SKIPPED: &[&str] = &["audit", directory::PROFILES];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub app_version: String,
+    pub created_at: chrono::DateTime<chrono::Local>,
+    pub includes_models: bool,
+    pub files: usize,
+}
+
+impl Manifest {
+    pub fn is_compatible(&self) -> bool {
+        self.version <= VERSION
+    }
+}
+
+// Model weights are never included; with `models` set, only their metadata is
+pub async fn create(path: PathBuf, models: bool) -> Result<Manifest, Error> {
+    Ok(task::spawn_blocking(move || write(&path, models)).await??)
+}
+
+pub async fn inspect(path: PathBuf) -> Result<Manifest, Error> {
+    Ok(task::spawn_blocking(move || manifest(&path)).await??)
+}
+
+pub async fn restore(path: PathBuf) -> Result<Manifest, Error> {
+    Ok(task::spawn_blocking(move || extract(&path)).await??)
+}
+
+fn write(path: &Path, models: bool) -> Result<Manifest, Error> {
+    let file = fs::File::create(path)?;
+    let mut archive = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default();
+
+    let mut files = 0;
+
+    for (prefix, root) in roots() {
+        for entry in walk(&root)? {
+            let relative = entry.strip_prefix(&root).unwrap_or(&entry);
+
+            let Some(first) = relative.components().next() else {
+                continue;
+            };
+
+            let first = first.as_os_str().to_string_lossy();
+
+            if SKIPPED.contains(&first.as_ref()) {
+                continue;
+            }
+
+            if first == "models"
+                && (!models
+                    || entry
+                        .extension()
+                        .is_some_and(|extension| extension == "gguf"))
+            {
+                continue;
+            }
+
+            let name = Path::new(prefix)
+                .join(relative)
+                .to_string_lossy()
+                .replace('\\', "/");
+
+            archive.start_file(name, options).map_err(zip_error)?;
+            let _ = io::copy(&mut fs::File::open(&entry)?, &mut archive)?;
+
+            files += 1;
+        }
+    }
+
+    let manifest = Manifest {
+        version: VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_owned(),
+        created_at: chrono::Local::now(),
+        includes_models: models,
+        files,
+    };
+
+    archive.start_file(MANIFEST, options).map_err(zip_error)?;
+    archive.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+    let _ = archive.finish().map_err(zip_error)?;
+
+    Ok(manifest)
+}
+
+fn manifest(path: &Path) -> Result<Manifest, Error> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?).map_err(zip_error)?;
+
+    let mut json = String::new();
+    let _ = archive
+        .by_name(MANIFEST)
+        .map_err(|_| Error::InvalidBackup("the manifest is missing", capture!()))?
+        .read_to_string(&mut json)?;
+
+    Ok(serde_json::from_str(&json)?)
+}
+
+fn extract(path: &Path) -> Result<Manifest, Error> {
+    let manifest = manifest(path)?;
+
+    if !manifest.is_compatible() {
+        return Err(Error::InvalidBackup(
+            "it was made by a newer version of icebreaker",
+            capture!(),
+        ));
+    }
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?).map_err(zip_error)?;
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(zip_error)?;
+
+        // Guards against entries escaping the app directories
+        let Some(name) = file.enclosed_name() else {
+            continue;
+        };
+
+        let mut components = name.components();
+
+        let Some(prefix) = components.next() else {
+            continue;
+        };
+
+        let Some((_, root)) = roots()
+            .into_iter()
+            .find(|(candidate, _)| prefix.as_os_str() == *candidate)
+        else {
+            continue;
+        };
+
+        let target = root.join(components.as_path());
+
+        if file.is_dir() {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let _ = io::copy(&mut file, &mut fs::File::create(&target)?)?;
+    }
+
+    Ok(manifest)
+}
+
+fn roots() -> [(&'static str, PathBuf); 2] {
+    [("config", directory::config()), ("data", directory::data())]
+}
+
+fn walk(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(directory) = pending.pop() {
+        let entries = match fs::read_dir(&directory) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(error),
+        };
+
+        for entry in entries {
+            let entry = entry?;
+            let kind = entry.file_type()?;
+
+            if kind.is_dir() {
+                pending.push(entry.path());
+            } else if kind.is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+fn zip_error(error: zip::result::ZipError) -> Error {
+    Error::from(io::Error::other(error))
+}
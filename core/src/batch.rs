@@ -0,0 +1,218 @@
+use crate::assistant::Assistant;
+use crate::Error;
+
+use futures::stream::{self, StreamExt};
+use langchain_rust::schemas::Message;
+use serde::{Deserialize, Serialize};
+use sipper::{sipper, Sipper, Straw};
+use tokio::fs;
+use tokio::task;
+
+use std::path::{Path, PathBuf};
+
+const SYSTEM_PROMPT: &str = "You are a helpful assistant.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Jsonl,
+}
+
+impl Format {
+    pub fn detect(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("csv") => Self::Csv,
+            _ => Self::Jsonl,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Output {
+    pub prompt: String,
+    pub output: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Started(usize),
+    Progressed(usize, String),
+    Finished(usize, Result<String, Error>),
+}
+
+pub async fn load(path: PathBuf) -> Result<Vec<String>, Error> {
+    let content = fs::read_to_string(&path).await?;
+    let format = Format::detect(&path);
+
+    task::spawn_blocking(move || match format {
+        Format::Csv => Ok(prompts_from_csv(&content)),
+        Format::Jsonl => prompts_from_jsonl(&content),
+    })
+    .await?
+}
+
+pub fn run(
+    assistant: &Assistant,
+    prompts: Vec<String>,
+    concurrency: usize,
+) -> impl Straw<(), Event, Error> {
+    let assistant = assistant.clone();
+
+    sipper(move |sender| async move {
+        stream::iter(prompts.into_iter().enumerate())
+            .for_each_concurrent(concurrency.max(1), |(i, prompt)| {
+                let assistant = &assistant;
+                let mut sender = sender.clone();
+
+                async move {
+                    sender.send(Event::Started(i)).await;
+
+                    let messages = [Message::new_human_message(prompt)];
+
+                    let result = assistant
+                        .reply(SYSTEM_PROMPT, &messages, &[])
+                        .with(move |(reply, _token)| Event::Progressed(i, reply.content))
+                        .run(&sender)
+                        .await
+                        .map(|reply| reply.content);
+
+                    sender.send(Event::Finished(i, result)).await;
+                }
+            })
+            .await;
+
+        Ok(())
+    })
+}
+
+pub async fn export(path: PathBuf, outputs: Vec<Output>) -> Result<(), Error> {
+    let format = Format::detect(&path);
+
+    let content = task::spawn_blocking(move || match format {
+        Format::Csv => Ok::<_, Error>(outputs_to_csv(&outputs)),
+        Format::Jsonl => {
+            let mut lines = String::new();
+
+            for output in &outputs {
+                lines.push_str(&serde_json::to_string(output)?);
+                lines.push('\n');
+            }
+
+            Ok(lines)
+        }
+    })
+    .await??;
+
+    fs::write(path, content).await?;
+
+    Ok(())
+}
+
+fn prompts_from_jsonl(content: &str) -> Result<Vec<String>, Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Line {
+        Prompt(String),
+        Object { prompt: String },
+    }
+
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let line: Line = serde_json::from_str(line)?;
+
+            Ok(match line {
+                Line::Prompt(prompt) | Line::Object { prompt } => prompt,
+            })
+        })
+        .collect()
+}
+
+fn prompts_from_csv(content: &str) -> Vec<String> {
+    let mut records = parse_csv(content).into_iter();
+
+    let Some(header) = records.next() else {
+        return Vec::new();
+    };
+
+    let column = header
+        .iter()
+        .position(|name| name.trim().eq_ignore_ascii_case("prompt"));
+
+    let first = column.is_none().then(|| header[0].clone());
+    let column = column.unwrap_or(0);
+
+    first
+        .into_iter()
+        .chain(
+            records.filter_map(|mut record| {
+                (column < record.len()).then(|| record.swap_remove(column))
+            }),
+        )
+        .filter(|prompt| !prompt.trim().is_empty())
+        .collect()
+}
+
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                let _ = chars.next();
+            }
+            '"' => {
+                quoted = !quoted;
+            }
+            ',' if !quoted => {
+                record.push(std::mem::take(&mut field));
+            }
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => {
+                field.push(c);
+            }
+        }
+    }
+
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    records
+}
+
+fn outputs_to_csv(outputs: &[Output]) -> String {
+    fn escape(field: &str) -> String {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_owned()
+        }
+    }
+
+    let mut csv = String::from("prompt,output,error\n");
+
+    for output in outputs {
+        csv.push_str(&escape(&output.prompt));
+        csv.push(',');
+        csv.push_str(&escape(output.output.as_deref().unwrap_or_default()));
+        csv.push(',');
+        csv.push_str(&escape(output.error.as_deref().unwrap_or_default()));
+        csv.push('\n');
+    }
+
+    csv
+}
@@ -0,0 +1,147 @@
+use crate::directory;
+use crate::Error;
+
+use sha2::{Digest, Sha256};
+use thiserror::capture;
+use tokio::fs;
+use tokio::task;
+
+use std::fmt::Write;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const LIMIT: u64 = 200 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+pub async fn image(url: String) -> Result<Image, Error> {
+    let path = path(&url);
+
+    let bytes = match fs::read(&path).await {
+        Ok(bytes) => {
+            let _ = task::spawn_blocking({
+                let path = path.clone();
+                move || touch(&path)
+            })
+            .await;
+
+            bytes
+        }
+        Err(_) => {
+            let bytes = reqwest::get(&url)
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?
+                .to_vec();
+
+            store(path, bytes.clone()).await?;
+
+            bytes
+        }
+    };
+
+    task::spawn_blocking(move || decode(&bytes)).await?
+}
+
+pub async fn clear() -> Result<(), Error> {
+    match fs::remove_dir_all(root()).await {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error.into()),
+        _ => Ok(()),
+    }
+}
+
+pub async fn size() -> Result<u64, Error> {
+    let entries = task::spawn_blocking(|| entries(&root())).await??;
+
+    Ok(entries.iter().map(|(_, size, _)| size).sum())
+}
+
+async fn store(path: PathBuf, bytes: Vec<u8>) -> Result<(), Error> {
+    fs::create_dir_all(root()).await?;
+    fs::write(&path, bytes).await?;
+
+    task::spawn_blocking(|| evict(&root(), LIMIT)).await??;
+
+    Ok(())
+}
+
+fn decode(bytes: &[u8]) -> Result<Image, Error> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|_| Error::InvalidImage(capture!()))?
+        .into_rgba8();
+
+    Ok(Image {
+        width: image.width(),
+        height: image.height(),
+        rgba: image.into_raw(),
+    })
+}
+
+fn evict(root: &Path, limit: u64) -> io::Result<()> {
+    let mut entries = entries(root)?;
+    let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+
+    entries.sort_by_key(|(_, _, used)| *used);
+
+    for (path, size, _) in entries {
+        if total <= limit {
+            break;
+        }
+
+        std::fs::remove_file(path)?;
+        total -= size;
+    }
+
+    Ok(())
+}
+
+fn entries(root: &Path) -> io::Result<Vec<(PathBuf, u64, SystemTime)>> {
+    let directory = match std::fs::read_dir(root) {
+        Ok(directory) => directory,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+
+    let mut entries = Vec::new();
+
+    for entry in directory {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+
+        if metadata.is_file() {
+            entries.push((entry.path(), metadata.len(), metadata.modified()?));
+        }
+    }
+
+    Ok(entries)
+}
+
+fn touch(path: &Path) -> io::Result<()> {
+    std::fs::File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(SystemTime::now())
+}
+
+fn path(url: &str) -> PathBuf {
+    let hash =
+        Sha256::digest(url.as_bytes())
+            .iter()
+            .fold(String::with_capacity(64), |mut hex, byte| {
+                let _ = write!(hex, "{byte:02x}");
+                hex
+            });
+
+    root().join(hash)
+}
+
+fn root() -> PathBuf {
+    directory::base_data().join("cache").join("images")
+}
@@ -1,15 +1,27 @@
 mod schema;
 
-use crate::assistant::{self, Assistant, Reply, Token};
+pub use schema::Format;
+
+use crate::assistant::{self, Assistant, Parameters, Reply, Token};
+use crate::attachment::Attachment;
+use crate::codebase;
+use crate::database::Database;
 use crate::directory;
+use crate::execution;
+use crate::hook;
 use crate::model;
+use crate::persistence;
+use crate::persona::Persona;
 use crate::plan::{self, Plan};
+use crate::request;
+use crate::todo::Todo;
 use crate::Error;
 
-use langchain_rust::schemas::Message;
-use log::info;
+use langchain_rust::schemas::{ImageContent, Message};
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use sipper::{sipper, Sipper, Straw};
+use thiserror::capture;
 use tokio::fs;
 use tokio::task;
 use uuid::Uuid;
@@ -17,6 +29,19 @@ use uuid::Uuid;
 use std::fmt;
 use std::io;
 use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+static FORMAT: RwLock<Format> = RwLock::new(Format::Json);
+
+pub fn configure(format: Format) {
+    if let Ok(mut configuration) = FORMAT.write() {
+        *configuration = format;
+    }
+}
+
+fn format() -> Format {
+    FORMAT.read().map(|format| *format).unwrap_or_default()
+}
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Chat {
@@ -24,6 +49,16 @@ pub struct Chat {
     pub file: model::FileAndAPI,
     pub title: Option<String>,
     pub history: Vec<Item>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona: Option<Persona>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock: Option<Lock>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub todos: Vec<Todo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checkpoints: Vec<Checkpoint>,
+    #[serde(default)]
+    pub is_read_only: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,11 +67,61 @@ pub enum Item {
     User(String),
     Reply(Reply),
     Plan(Plan),
+    Attachment(Attachment),
+    Execution(execution::Output),
 }
 
 impl Chat {
-    async fn path(id: &Id) -> Result<PathBuf, Error> {
-        Ok(storage_dir().await?.join(format!("{}.json", id.0.simple())))
+    pub fn directory() -> PathBuf {
+        directory::data().join("chats")
+    }
+
+    async fn path(id: &Id, format: Format) -> Result<PathBuf, Error> {
+        Ok(storage_dir()
+            .await?
+            .join(format!("{}.{}", id.0.simple(), format.extension())))
+    }
+
+    async fn read(id: &Id) -> Result<Option<(PathBuf, Vec<u8>)>, Error> {
+        for format in Format::ALL {
+            let path = Self::path(id, *format).await?;
+
+            match fs::read(&path).await {
+                Ok(bytes) => return Ok(Some((path, bytes))),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn store(id: &Id, bytes: Vec<u8>) -> Result<(), Error> {
+        let format = Format::of(&bytes);
+
+        persistence::write(Self::path(id, format).await?, bytes).await?;
+
+        let _ = Self::remove(id, |other| other != format).await?;
+
+        Ok(())
+    }
+
+    async fn remove(id: &Id, formats: impl Fn(Format) -> bool) -> Result<bool, Error> {
+        let mut is_removed = false;
+
+        for format in Format::ALL
+            .iter()
+            .copied()
+            .filter(|format| formats(*format))
+        {
+            match fs::remove_file(Self::path(id, format).await?).await {
+                Ok(()) => is_removed = true,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        Ok(is_removed)
     }
 
     pub async fn list() -> Result<Vec<Entry>, Error> {
@@ -46,11 +131,13 @@ impl Chat {
     }
 
     pub async fn fetch(id: Id) -> Result<Self, Error> {
-        let json = fs::read_to_string(Self::path(&id).await?).await?;
+        let Some((_path, bytes)) = Self::read(&id).await? else {
+            return Err(io::Error::from(io::ErrorKind::NotFound).into());
+        };
 
         let _ = LastOpened::update(id).await;
 
-        task::spawn_blocking(move || schema::decode(&json)).await?
+        task::spawn_blocking(move || schema::decode(&bytes)).await?
     }
 
     pub async fn fetch_last_opened() -> Result<Self, Error> {
@@ -63,6 +150,10 @@ impl Chat {
         file: model::FileAndAPI,
         title: Option<String>,
         history: Vec<Item>,
+        persona: Option<Persona>,
+        lock: Option<Lock>,
+        todos: Vec<Todo>,
+        checkpoints: Vec<Checkpoint>,
     ) -> Result<Self, Error> {
         let id = Id(Uuid::new_v4());
         let chat = Self {
@@ -70,6 +161,11 @@ impl Chat {
             file,
             title,
             history,
+            persona,
+            lock,
+            todos,
+            checkpoints,
+            is_read_only: false,
         }
         .save()
         .await?;
@@ -83,11 +179,17 @@ impl Chat {
         })
         .await?;
 
+        hook::fire(hook::Event::ChatCreated, hook::chat(&chat));
+
         Ok(chat)
     }
 
     pub async fn save(self) -> Result<Self, Error> {
         if let Ok(current) = Self::fetch(self.id).await {
+            if current.is_read_only {
+                return Err(Error::ChatReadOnly(capture!()));
+            }
+
             if current.title != self.title {
                 let mut list = List::fetch().await?;
 
@@ -99,15 +201,132 @@ impl Chat {
             }
         }
 
-        let (bytes, chat) = task::spawn_blocking(move || (schema::encode(&self), self)).await?;
+        self.write().await
+    }
+
+    pub async fn set_read_only(id: Id, is_read_only: bool) -> Result<Self, Error> {
+        let chat = Self {
+            is_read_only,
+            ..Self::fetch(id).await?
+        };
+
+        chat.write().await
+    }
+
+    async fn write(self) -> Result<Self, Error> {
+        let format = format();
+        let (bytes, chat) =
+            task::spawn_blocking(move || (schema::encode(&self, format), self)).await?;
 
-        fs::write(Self::path(&chat.id).await?, bytes?).await?;
+        Self::store(&chat.id, bytes?).await?;
+
+        hook::fire(hook::Event::ChatSaved, hook::chat(&chat));
 
         Ok(chat)
     }
 
+    pub(crate) async fn import(bytes: Vec<u8>) -> Result<Self, Error> {
+        let format = format();
+
+        let (chat, bytes) = task::spawn_blocking(move || -> Result<_, Error> {
+            let chat = schema::decode(&bytes)?;
+
+            if Format::of(&bytes) == format {
+                Ok((chat, bytes))
+            } else {
+                let bytes = schema::encode(&chat, format)?;
+
+                Ok((chat, bytes))
+            }
+        })
+        .await??;
+
+        Self::store(&chat.id, bytes).await?;
+
+        let mut list = List::fetch().await?;
+
+        match list.entries.iter_mut().find(|entry| entry.id == chat.id) {
+            Some(entry) => {
+                entry.title = chat.title.clone();
+            }
+            None => {
+                list.entries.insert(
+                    0,
+                    Entry {
+                        id: chat.id,
+                        file: chat.file.clone(),
+                        title: chat.title.clone(),
+                    },
+                );
+            }
+        }
+
+        list.save().await?;
+
+        Ok(chat)
+    }
+
+    pub async fn convert(format: Format) -> Result<usize, Error> {
+        let list = List::fetch().await?;
+        let mut converted = 0;
+
+        for entry in list.entries {
+            let Some((path, bytes)) = Self::read(&entry.id).await? else {
+                continue;
+            };
+
+            // Binary chats of older versions were stored with the JSON name
+            if Format::of(&bytes) == format && path == Self::path(&entry.id, format).await? {
+                continue;
+            }
+
+            let bytes = if Format::of(&bytes) == format {
+                bytes
+            } else {
+                task::spawn_blocking(move || {
+                    schema::decode(&bytes).and_then(|chat| schema::encode(&chat, format))
+                })
+                .await??
+            };
+
+            Self::store(&entry.id, bytes).await?;
+            converted += 1;
+        }
+
+        info!("converted {converted} chats to {format}");
+
+        Ok(converted)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        schema::decode(bytes)
+    }
+
+    pub(crate) fn encode(&self) -> Result<Vec<u8>, Error> {
+        schema::encode(self, Format::Json)
+    }
+
+    pub(crate) async fn shared(id: Id) -> Result<Option<Vec<u8>>, Error> {
+        let Some((_path, bytes)) = Self::read(&id).await? else {
+            return Ok(None);
+        };
+
+        if Format::of(&bytes) == Format::Json {
+            return Ok(Some(bytes));
+        }
+
+        let bytes = task::spawn_blocking(move || {
+            schema::decode(&bytes).and_then(|chat| schema::encode(&chat, Format::Json))
+        })
+        .await??;
+
+        Ok(Some(bytes))
+    }
+
     pub async fn delete(id: Id) -> Result<(), Error> {
-        fs::remove_file(Self::path(&id).await?).await?;
+        if !Self::remove(&id, |_format| true).await? {
+            return Err(io::Error::from(io::ErrorKind::NotFound).into());
+        }
 
         let _ = List::remove(&id).await;
 
@@ -143,6 +362,7 @@ impl fmt::Debug for Chat {
 
 #[derive(Debug, Clone)]
 pub enum Event {
+    ContextFitted(usize),
     ReplyAdded,
     ReplyChanged(Reply),
     PlanAdded,
@@ -151,47 +371,160 @@ pub enum Event {
 
 const SYSTEM_PROMPT: &str = "You are a helpful assistant.";
 
-#[derive(Debug, Clone, Copy, Default)]
+const CODEBASE_EXCERPTS: usize = 6;
+
+const REPLY_TOKENS: usize = 1_024;
+
+#[derive(Debug, Clone, Default)]
 pub struct Strategy {
     pub search: bool,
+    pub fetch: bool,
+    pub codebase: Option<Arc<codebase::Index>>,
+    pub database: Option<Database>,
+    pub agent: Option<usize>,
+    pub parameters: Option<Parameters>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub name: String,
+    pub length: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lock {
+    pub file: model::FileAndAPI,
+    pub parameters: Parameters,
+    pub system_prompt: String,
+}
+
+impl Lock {
+    pub fn new(assistant: &Assistant, strategy: &Strategy, persona: Option<&Persona>) -> Self {
+        let (assistant, system_prompt) = configure(assistant, strategy, persona);
+
+        Self {
+            file: assistant.file.clone(),
+            parameters: assistant.parameters(),
+            system_prompt,
+        }
+    }
 }
 
 pub fn complete(
     assistant: &Assistant,
     items: &[Item],
     strategy: Strategy,
+    persona: Option<&Persona>,
+    lock: Option<&Lock>,
 ) -> impl Straw<(), Event, Error> {
-    let assistant = assistant.clone();
-    let history = history(items);
+    let is_locked_out = lock.is_some_and(|lock| lock.file != assistant.file);
+
+    let (assistant, system_prompt) = match lock {
+        Some(lock) => (
+            assistant.clone().with_parameters(lock.parameters),
+            lock.system_prompt.clone(),
+        ),
+        None => configure(assistant, &strategy, persona),
+    };
+
+    let query = items.iter().rev().find_map(|item| match item {
+        Item::User(query) => Some(query.clone()),
+        _ => None,
+    });
+
+    let first = fit(&assistant, &system_prompt, items);
+    let history = history(&items[first..]);
 
     sipper(move |mut sender| async move {
-        if strategy.search {
+        if is_locked_out {
+            return Err(Error::ChatLocked(capture!()));
+        }
+
+        let _generating = request::generating();
+
+        let _ = sender.send(Event::ContextFitted(first)).await;
+
+        let context = match (&strategy.codebase, &query) {
+            (Some(index), Some(query)) => index.context(query, CODEBASE_EXCERPTS).await?,
+            _ => None,
+        };
+
+        let system_prompt = match context {
+            Some(context) => format!("{system_prompt}\n\n{context}"),
+            None => system_prompt,
+        };
+
+        let system_prompt = match &strategy.database {
+            Some(database) => {
+                let schema = database.schema().await?;
+
+                format!("{system_prompt}\n\n{}", database.prompt(&schema))
+            }
+            None => system_prompt,
+        };
+
+        if let Some(budget) = strategy.agent {
             let _ = sender.send(Event::PlanAdded).await;
 
-            Plan::search(&assistant, &history)
+            Plan::agent(&assistant, &history, strategy.fetch, budget)
+                .with(Event::PlanChanged)
+                .run(&sender)
+                .await?;
+        } else if strategy.search || strategy.fetch {
+            let _ = sender.send(Event::PlanAdded).await;
+
+            Plan::search(&assistant, &history, strategy.fetch)
                 .with(Event::PlanChanged)
                 .run(&sender)
                 .await?;
         } else {
-            reply(&assistant, &history).run(sender).await?;
+            reply(&assistant, &system_prompt, &history)
+                .run(sender)
+                .await?;
         }
 
         Ok(())
     })
 }
 
+fn configure(
+    assistant: &Assistant,
+    strategy: &Strategy,
+    persona: Option<&Persona>,
+) -> (Assistant, String) {
+    let (assistant, system_prompt) = match persona {
+        Some(persona) => (
+            assistant.clone().with_parameters(persona.parameters),
+            persona.system_prompt.clone(),
+        ),
+        None => (assistant.clone(), SYSTEM_PROMPT.to_owned()),
+    };
+
+    let assistant = match strategy.parameters {
+        Some(parameters) => {
+            let fallback = assistant.parameters();
+
+            assistant.with_parameters(parameters.or(fallback))
+        }
+        None => assistant,
+    };
+
+    (assistant, system_prompt)
+}
+
 fn reply<'a>(
     assistant: &'a Assistant,
+    system_prompt: &'a str,
     messages: &'a [Message],
 ) -> impl Straw<(), Event, Error> + 'a {
     sipper(move |mut sender| async move {
         let _ = sender.send(Event::ReplyAdded).await;
 
         let _reply = assistant
-            .reply(SYSTEM_PROMPT, messages, &[])
+            .reply(system_prompt, messages, &[])
             .with(|(reply, _new_token)| Event::ReplyChanged(reply))
             .run(sender)
-            .await;
+            .await?;
 
         Ok(())
     })
@@ -235,6 +568,38 @@ pub fn title(assistant: &Assistant, items: &[Item]) -> impl Straw<String, String
     })
 }
 
+pub async fn todos(assistant: Assistant, items: Vec<Item>) -> Result<Vec<Todo>, Error> {
+    let schema = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "items": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "task": { "type": "string" },
+                        "owner": { "type": ["string", "null"] },
+                        "due": { "type": ["string", "null"] }
+                    },
+                    "required": ["task"]
+                }
+            }
+        },
+        "required": ["items"]
+    });
+
+    let mut messages = history(&items);
+    messages.push(Message::new_human_message(format!(
+        "List the action items of our conversation so far: the tasks that \
+        someone decided, agreed, or was asked to do. Write each task as a short \
+        imperative sentence. Only include an owner or a due date if they were \
+        mentioned. Answer only with a JSON object following this schema, with \
+        an empty list if there are none:\n\n{schema}"
+    )));
+
+    assistant.structured(messages, Todo::from_json).await
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Id(Uuid);
 
@@ -249,6 +614,10 @@ impl Id {
             .map_err(decoder::Error::custom)
     }
 
+    pub(crate) fn parse(id: &str) -> Option<Self> {
+        Uuid::parse_str(id).ok().map(Self)
+    }
+
     pub fn encode(self) -> decoder::Value {
         use decoder::encode::string;
 
@@ -306,9 +675,7 @@ impl List {
     async fn save(self) -> Result<(), Error> {
         let json = task::spawn_blocking(move || serde_json::to_vec_pretty(&self)).await?;
 
-        fs::write(Self::path().await?, json?).await?;
-
-        Ok(())
+        persistence::write(Self::path().await?, json?).await
     }
 }
 
@@ -330,9 +697,7 @@ impl LastOpened {
     async fn update(id: Id) -> Result<(), Error> {
         let json = serde_json::to_vec(&LastOpened(id))?;
 
-        fs::write(Self::path().await?, json).await?;
-
-        Ok(())
+        persistence::write(Self::path().await?, json).await
     }
 
     async fn delete() -> Result<(), Error> {
@@ -343,23 +708,86 @@ impl LastOpened {
 }
 
 async fn storage_dir() -> Result<PathBuf, io::Error> {
-    let directory = directory::data().join("chats");
+    let directory = Chat::directory();
 
     fs::create_dir_all(&directory).await?;
 
     Ok(directory)
 }
 
+// The last item is always kept
+fn fit(assistant: &Assistant, system_prompt: &str, items: &[Item]) -> usize {
+    let reply = assistant
+        .parameters()
+        .max_tokens
+        .map_or(REPLY_TOKENS, |max_tokens| max_tokens as usize);
+
+    let budget = assistant
+        .context_window()
+        .saturating_sub(reply + estimate(system_prompt));
+
+    let mut used = 0;
+    let mut first = items.len();
+
+    for (index, item) in items.iter().enumerate().rev() {
+        used += match item {
+            Item::User(query) => estimate(query),
+            Item::Reply(reply) => estimate(&reply.content),
+            Item::Plan(plan) => plan.answers().map(|reply| estimate(&reply.content)).sum(),
+            Item::Attachment(attachment) => attachment.text.as_deref().map_or(0, estimate),
+            Item::Execution(output) => estimate(&output.to_prompt()),
+        };
+
+        if used > budget && first < items.len() {
+            break;
+        }
+
+        first = index;
+    }
+
+    first
+}
+
+fn estimate(text: &str) -> usize {
+    const CHARACTERS_PER_TOKEN: usize = 4;
+
+    text.chars().count().div_ceil(CHARACTERS_PER_TOKEN)
+}
+
 fn history(items: &[Item]) -> Vec<Message> {
+    let mut images = Vec::new();
+
     items
         .iter()
         .flat_map(|item| match item {
-            Item::User(query) => vec![Message::new_human_message(query.clone())],
+            Item::User(query) => {
+                let mut message = Message::new_human_message(query.clone());
+
+                if !images.is_empty() {
+                    message.images = Some(std::mem::take(&mut images));
+                }
+
+                vec![message]
+            }
             Item::Reply(reply) => vec![Message::new_ai_message(reply.content.clone())],
             Item::Plan(plan) => plan
                 .answers()
                 .map(|reply| Message::new_ai_message(reply.content.clone()))
                 .collect(),
+            Item::Attachment(Attachment {
+                text: Some(text), ..
+            }) => vec![Message::new_human_message(format!(
+                "I attached an image. This is the text in it, read with OCR:\n\n```\n{text}\n```"
+            ))],
+            Item::Attachment(attachment) => {
+                match attachment.data_url() {
+                    Ok(url) => images.push(ImageContent::from(url.as_str())),
+                    Err(error) => warn!("attachment unavailable: {error}"),
+                }
+
+                vec![]
+            }
+            Item::Execution(output) => vec![Message::new_human_message(output.to_prompt())],
         })
         .collect()
 }
@@ -1,14 +1,92 @@
 use crate::{Chat, Error};
 
 mod old;
+#[cfg(test)]
+mod tests;
 
-pub fn decode(json: &str) -> Result<Chat, Error> {
-    let chat: Chat = serde_json::from_str(json)?;
+use decoder::Value;
+
+use std::fmt;
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+const COMPRESSION_LEVEL: i32 = 3;
+
+// Both formats are always read, so changing it only affects the chats saved next
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Json,
+    Binary,
+}
+
+impl Format {
+    pub const ALL: &'static [Self] = &[Self::Json, Self::Binary];
+
+    pub fn of(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&ZSTD_MAGIC) {
+            Self::Binary
+        } else {
+            Self::Json
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Binary => "chat.zst",
+        }
+    }
+
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let format = decoder::decode::string(value)?;
+
+        Ok(match format.as_str() {
+            "binary" => Self::Binary,
+            _ => Self::Json,
+        })
+    }
+
+    pub(crate) fn encode(self) -> Value {
+        decoder::encode::string(match self {
+            Self::Json => "json",
+            Self::Binary => "binary",
+        })
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Json => "JSON",
+            Self::Binary => "Compressed binary",
+        })
+    }
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Chat, Error> {
+    let chat: Chat = match Format::of(bytes) {
+        Format::Json => match serde_json::from_slice(bytes) {
+            Ok(chat) => chat,
+            // Chats saved before replies and plans were reshaped
+            Err(error) => serde_json::from_slice::<old::Schema>(bytes)
+                .map(old::Schema::into_data)
+                .map_err(|_| error)?,
+        },
+        Format::Binary => rmp_serde::from_slice(&zstd::decode_all(bytes)?)?,
+    };
 
     Ok(chat)
 }
 
-pub fn encode(chat: &Chat) -> Result<String, Error> {
+pub fn encode(chat: &Chat, format: Format) -> Result<Vec<u8>, Error> {
+    match format {
+        Format::Json => Ok(serde_json::to_vec_pretty(chat)?),
+        Format::Binary => {
+            // Named fields keep the defaults of new fields working for old chats
+            let bytes = rmp_serde::to_vec_named(chat)?;
 
-    Ok(serde_json::to_string_pretty(chat)?)
+            Ok(zstd::encode_all(bytes.as_slice(), COMPRESSION_LEVEL)?)
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use crate::assistant;
-use crate::chat::{Id, Item};
+use crate::chat::{Chat, Id, Item};
 use crate::model;
 use crate::plan;
 use crate::web;
@@ -22,6 +22,25 @@ pub struct Schema {
     pub history: Vec<Message>,
 }
 
+impl Schema {
+    pub fn into_data(self) -> Chat {
+        Chat {
+            id: self.id,
+            file: model::FileAndAPI {
+                file: Some(self.file),
+                api: None,
+            },
+            title: self.title,
+            history: self.history.into_iter().map(Message::into_data).collect(),
+            persona: None,
+            lock: None,
+            todos: Vec::new(),
+            checkpoints: Vec::new(),
+            is_read_only: false,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub enum Message {
     User(String),
@@ -61,6 +80,9 @@ impl Reply {
             },
             content: self.content,
             last_token: None,
+            endpoint: None,
+            provenance: None,
+            usage: None,
         }
     }
 }
@@ -108,6 +130,8 @@ impl Plan {
             reasoning: self.reasoning.map(Reasoning::into_data),
             steps: self.steps.into_iter().map(Step::into_data).collect(),
             outcomes: self.outcomes.into_iter().map(Outcome::into_data).collect(),
+            citations: Vec::new(),
+            calls: Vec::new(),
         }
     }
 }
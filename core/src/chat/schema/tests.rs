@@ -0,0 +1,186 @@
+use super::{decode, encode, Format};
+use crate::assistant::{Reasoning, Reply, Usage};
+use crate::chat::{Chat, Checkpoint, Id, Item};
+use crate::model;
+use crate::todo::Todo;
+
+use proptest::prelude::*;
+use uuid::Uuid;
+
+use std::time::Duration;
+
+fn file() -> impl Strategy<Value = model::File> {
+    (
+        "[a-z]{1,8}/[a-z0-9-]{1,12}",
+        "[a-z0-9-]{1,12}\\.gguf",
+        any::<bool>(),
+    )
+        .prop_map(|(model, name, is_modelscope)| model::File {
+            model: model::Id(model),
+            name,
+            size: None,
+            hub: if is_modelscope {
+                model::Hub::ModelScope
+            } else {
+                model::Hub::HuggingFace
+            },
+        })
+}
+
+fn reply() -> impl Strategy<Value = Reply> {
+    (
+        proptest::option::of((".*", any::<u32>())),
+        ".*",
+        proptest::option::of(".*"),
+        proptest::option::of((any::<u32>(), any::<u32>(), any::<u16>())),
+    )
+        .prop_map(|(reasoning, content, endpoint, usage)| Reply {
+            reasoning: reasoning.map(|(content, millis)| Reasoning {
+                content,
+                duration: Duration::from_millis(millis.into()),
+            }),
+            content,
+            last_token: None,
+            endpoint,
+            provenance: None,
+            usage: usage.map(|(prompt, completion, seconds)| Usage {
+                prompt_tokens: prompt.into(),
+                completion_tokens: completion.into(),
+                duration: Some(Duration::from_secs(seconds.into())),
+                ..Usage::default()
+            }),
+        })
+}
+
+fn item() -> impl Strategy<Value = Item> {
+    prop_oneof![".*".prop_map(Item::User), reply().prop_map(Item::Reply)]
+}
+
+fn chat() -> impl Strategy<Value = Chat> {
+    (
+        any::<u128>(),
+        file(),
+        proptest::option::of(".*"),
+        proptest::collection::vec(item(), 0..16),
+        proptest::collection::vec(
+            ("\\PC{1,20}", proptest::option::of(".*"), any::<bool>()),
+            0..4,
+        ),
+        proptest::collection::vec(("\\PC{1,20}", 0..16usize), 0..4),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(id, file, title, history, todos, checkpoints, is_read_only)| Chat {
+                id: Id(Uuid::from_u128(id)),
+                file: model::FileAndAPI {
+                    file: Some(file),
+                    api: None,
+                },
+                title,
+                history,
+                persona: None,
+                lock: None,
+                todos: todos
+                    .into_iter()
+                    .map(|(task, owner, is_done)| Todo {
+                        task,
+                        owner,
+                        due: None,
+                        is_done,
+                    })
+                    .collect(),
+                checkpoints: checkpoints
+                    .into_iter()
+                    .map(|(name, length)| Checkpoint { name, length })
+                    .collect(),
+                is_read_only,
+            },
+        )
+}
+
+fn json(chat: &Chat) -> serde_json::Value {
+    serde_json::to_value(chat).expect("Serialize chat")
+}
+
+proptest! {
+    #[test]
+    fn json_round_trip(chat in chat()) {
+        let decoded = decode(&encode(&chat, Format::Json).unwrap()).unwrap();
+
+        prop_assert_eq!(json(&decoded), json(&chat));
+    }
+
+    #[test]
+    fn binary_round_trip(chat in chat()) {
+        let bytes = encode(&chat, Format::Binary).unwrap();
+
+        prop_assert_eq!(Format::of(&bytes), Format::Binary);
+        prop_assert_eq!(json(&decode(&bytes).unwrap()), json(&chat));
+    }
+
+    #[test]
+    fn formats_convert_losslessly(chat in chat()) {
+        let binary = encode(&chat, Format::Binary).unwrap();
+        let json_bytes = encode(&decode(&binary).unwrap(), Format::Json).unwrap();
+
+        prop_assert_eq!(json_bytes, encode(&chat, Format::Json).unwrap());
+    }
+
+    #[test]
+    fn decode_never_panics(bytes in proptest::collection::vec(any::<u8>(), 0..512)) {
+        let _ = decode(&bytes);
+    }
+
+    #[test]
+    fn corrupted_binary_is_an_error(chat in chat(), cut in 1..64usize) {
+        let bytes = encode(&chat, Format::Binary).unwrap();
+        let truncated = &bytes[..bytes.len().saturating_sub(cut).max(4)];
+
+        prop_assert!(decode(truncated).is_err());
+    }
+}
+
+#[test]
+fn old_chats_load() {
+    let old = r#"{
+        "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+        "file": { "model": "bartowski/Llama-3.2-1B-Instruct-GGUF", "name": "Llama-3.2-1B-Instruct-Q4_K_M.gguf" },
+        "title": "Greetings",
+        "history": [
+            { "User": "Hello!" },
+            { "Assistant": "Hi! How can I help?" },
+            { "Assistant": { "reasoning": "The user greets me.", "reasoning_time": { "secs": 2, "nanos": 0 }, "content": "Hello again." } }
+        ]
+    }"#;
+
+    let chat = decode(old.as_bytes()).expect("Decode old chat");
+
+    assert_eq!(chat.title.as_deref(), Some("Greetings"));
+    assert_eq!(chat.history.len(), 3);
+
+    let Item::Reply(reply) = &chat.history[2] else {
+        panic!("Expected a reply");
+    };
+
+    assert_eq!(reply.content, "Hello again.");
+    assert_eq!(
+        reply.reasoning.as_ref().map(|reasoning| reasoning.duration),
+        Some(Duration::from_secs(2))
+    );
+}
+
+#[test]
+fn chats_without_new_fields_load() {
+    let chat = r#"{
+        "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+        "file": { "file": { "model": "a/b", "name": "c.gguf" }, "api": null },
+        "title": null,
+        "history": [{ "User": "Hi" }]
+    }"#;
+
+    let chat = decode(chat.as_bytes()).expect("Decode chat");
+
+    assert!(chat.checkpoints.is_empty());
+    assert!(chat.todos.is_empty());
+    assert!(!chat.is_read_only);
+}
@@ -0,0 +1,383 @@
+use crate::hosted;
+use crate::model::APIType;
+use crate::Error;
+
+use decoder::{decode, encode, Value};
+use thiserror::capture;
+use tokio::task;
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+const CHUNK_LINES: usize = 60;
+const CHUNK_OVERLAP: usize = 10;
+const MAX_FILE_SIZE: u64 = 512 * 1024;
+const DIMENSIONS: u32 = 4096;
+const EMBEDDING_BATCH: usize = 64;
+
+static CONFIGURATION: RwLock<Embedder> = RwLock::new(Embedder::Hashed);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Embeddings {
+    #[default]
+    Hashed,
+    Mistral,
+}
+
+impl Embeddings {
+    pub const ALL: &[Self] = &[Self::Hashed, Self::Mistral];
+
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let embeddings = decode::string(value)?;
+
+        Ok(match embeddings.as_str() {
+            "mistral" => Self::Mistral,
+            _ => Self::Hashed,
+        })
+    }
+
+    pub(crate) fn encode(self) -> Value {
+        encode::string(match self {
+            Self::Hashed => "hashed",
+            Self::Mistral => "mistral",
+        })
+    }
+}
+
+impl std::fmt::Display for Embeddings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Hashed => "Local (feature hashing)",
+            Self::Mistral => "Mistral (mistral-embed)",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Embedder {
+    Hashed,
+    Mistral { api_key: Option<String> },
+}
+
+pub fn configure(embeddings: Embeddings, keys: &hosted::Keys) {
+    let embedder = match embeddings {
+        Embeddings::Hashed => Embedder::Hashed,
+        Embeddings::Mistral => Embedder::Mistral {
+            api_key: keys.get(&APIType::Mistral).map(str::to_owned),
+        },
+    };
+
+    if let Ok(mut configuration) = CONFIGURATION.write() {
+        *configuration = embedder;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Index {
+    root: PathBuf,
+    summary: String,
+    chunks: Vec<Chunk>,
+    embedder: Embedder,
+}
+
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub path: PathBuf,
+    pub start: usize,
+    pub end: usize,
+    pub content: String,
+    vector: Vector,
+}
+
+impl Index {
+    pub async fn build(root: PathBuf) -> Result<Self, Error> {
+        let embedder = CONFIGURATION
+            .read()
+            .map(|embedder| embedder.clone())
+            .unwrap_or(Embedder::Hashed);
+
+        let mut index = task::spawn_blocking(move || Self::walk(root)).await?;
+
+        if let Embedder::Mistral { api_key } = &embedder {
+            let Some(api_key) = api_key else {
+                return Err(Error::NotConfigured("the Mistral API key", capture!()));
+            };
+
+            for batch in index.chunks.chunks_mut(EMBEDDING_BATCH) {
+                let inputs: Vec<_> = batch
+                    .iter()
+                    .map(|chunk| format!("{}\n{}", chunk.path.display(), chunk.content))
+                    .collect();
+
+                let embeddings = hosted::mistral_embeddings(api_key, &inputs).await?;
+
+                for (chunk, embedding) in batch.iter_mut().zip(embeddings) {
+                    chunk.vector = Vector::dense(embedding);
+                }
+            }
+
+            index.summary.push_str(" · mistral-embed");
+        }
+
+        index.embedder = embedder;
+
+        Ok(index)
+    }
+
+    fn walk(root: PathBuf) -> Self {
+        let mut files = 0;
+        let mut chunks = Vec::new();
+
+        for entry in ignore::WalkBuilder::new(&root).build().flatten() {
+            let path = entry.path();
+
+            if !entry.file_type().is_some_and(|kind| kind.is_file())
+                || !entry
+                    .metadata()
+                    .is_ok_and(|metadata| metadata.len() <= MAX_FILE_SIZE)
+            {
+                continue;
+            }
+
+            // Skip binary files
+            let Ok(content) = fs::read_to_string(path) else {
+                continue;
+            };
+
+            let relative = path.strip_prefix(&root).unwrap_or(path).to_path_buf();
+
+            files += 1;
+            chunks.extend(chunk(&relative, &content));
+        }
+
+        let summary = format!(
+            "{} · {files} files · {} chunks",
+            root.display(),
+            chunks.len()
+        );
+
+        Self {
+            root,
+            summary,
+            chunks,
+            embedder: Embedder::Hashed,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.root.display().to_string())
+    }
+
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<&Chunk>, Error> {
+        let query = match &self.embedder {
+            Embedder::Mistral {
+                api_key: Some(api_key),
+            } => {
+                let embedding = hosted::mistral_embeddings(api_key, &[query.to_owned()])
+                    .await?
+                    .pop()
+                    .unwrap_or_default();
+
+                Vector::dense(embedding)
+            }
+            _ => Vector::embed(query),
+        };
+
+        let mut scored: Vec<_> = self
+            .chunks
+            .iter()
+            .map(|chunk| (query.similarity(&chunk.vector), chunk))
+            .filter(|(score, _)| *score > 0.0)
+            .collect();
+
+        scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+        Ok(scored
+            .into_iter()
+            .take(limit)
+            .map(|(_, chunk)| chunk)
+            .collect())
+    }
+
+    pub async fn context(&self, query: &str, limit: usize) -> Result<Option<String>, Error> {
+        let chunks = self.search(query, limit).await?;
+
+        if chunks.is_empty() {
+            return Ok(None);
+        }
+
+        let mut context = format!(
+            "The user is asking about the codebase in \"{name}\". \
+            These excerpts may be relevant; refer to files by their path when you use them.",
+            name = self.name()
+        );
+
+        for chunk in chunks {
+            let language = chunk
+                .path
+                .extension()
+                .map(|extension| extension.to_string_lossy())
+                .unwrap_or_default();
+
+            context.push_str(&format!(
+                "\n\n{path}:{start}-{end}\n```{language}\n{content}\n```",
+                path = chunk.path.display(),
+                start = chunk.start,
+                end = chunk.end,
+                content = chunk.content.trim_end(),
+            ));
+        }
+
+        Ok(Some(context))
+    }
+}
+
+fn chunk(path: &Path, content: &str) -> Vec<Chunk> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while start < lines.len() {
+        let end = (start + CHUNK_LINES).min(lines.len());
+        let content = lines[start..end].join("\n");
+
+        if !content.trim().is_empty() {
+            // Paths often carry as much meaning as the code itself
+            let vector = Vector::embed(&format!("{}\n{content}", path.display()));
+
+            chunks.push(Chunk {
+                path: path.to_path_buf(),
+                start: start + 1,
+                end,
+                content,
+                vector,
+            });
+        }
+
+        if end == lines.len() {
+            break;
+        }
+
+        start = end - CHUNK_OVERLAP;
+    }
+
+    chunks
+}
+
+#[derive(Debug, Clone)]
+enum Vector {
+    Hashed(Vec<(u32, f32)>),
+    Dense(Vec<f32>),
+}
+
+impl Vector {
+    fn dense(mut embedding: Vec<f32>) -> Self {
+        let norm = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+        if norm > 0.0 {
+            for x in &mut embedding {
+                *x /= norm;
+            }
+        }
+
+        Self::Dense(embedding)
+    }
+
+    fn embed(text: &str) -> Self {
+        let mut counts = BTreeMap::<u32, f32>::new();
+
+        for token in tokens(text) {
+            *counts.entry(hash(&token) % DIMENSIONS).or_default() += 1.0;
+        }
+
+        let mut entries: Vec<_> = counts
+            .into_iter()
+            .map(|(dimension, count)| (dimension, 1.0 + count.ln()))
+            .collect();
+
+        let norm = entries
+            .iter()
+            .map(|(_, weight)| weight * weight)
+            .sum::<f32>()
+            .sqrt();
+
+        if norm > 0.0 {
+            for (_, weight) in &mut entries {
+                *weight /= norm;
+            }
+        }
+
+        Self::Hashed(entries)
+    }
+
+    fn similarity(&self, other: &Self) -> f32 {
+        let (lhs, rhs) = match (self, other) {
+            (Self::Hashed(lhs), Self::Hashed(rhs)) => (lhs, rhs),
+            (Self::Dense(lhs), Self::Dense(rhs)) => {
+                return lhs.iter().zip(rhs).map(|(x, y)| x * y).sum();
+            }
+            _ => return 0.0,
+        };
+
+        let (mut i, mut j) = (0, 0);
+        let mut dot = 0.0;
+
+        while let (Some(&(a, x)), Some(&(b, y))) = (lhs.get(i), rhs.get(j)) {
+            match a.cmp(&b) {
+                Ordering::Less => i += 1,
+                Ordering::Greater => j += 1,
+                Ordering::Equal => {
+                    dot += x * y;
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+
+        dot
+    }
+}
+
+fn tokens(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .flat_map(|word| {
+            let mut parts = Vec::new();
+            let mut current = String::new();
+            let mut previous_lowercase = false;
+
+            for c in word.chars() {
+                if c.is_uppercase() && previous_lowercase && !current.is_empty() {
+                    parts.push(std::mem::take(&mut current));
+                }
+
+                previous_lowercase = c.is_lowercase();
+                current.extend(c.to_lowercase());
+            }
+
+            parts.push(current);
+
+            if parts.len() > 1 {
+                parts.push(word.to_lowercase());
+            }
+
+            parts
+        })
+        .filter(|token| token.chars().count() > 1)
+}
+
+fn hash(token: &str) -> u32 {
+    // FNV-1a
+    token.bytes().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x01000193)
+    })
+}
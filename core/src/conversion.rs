@@ -0,0 +1,328 @@
+use crate::mirror;
+use crate::model::{Directory, File, Hub, Id, Size};
+use crate::request;
+use crate::Error;
+
+use decoder::{decode, encode, Value};
+use serde::Deserialize;
+use sipper::{sipper, Straw};
+use thiserror::capture;
+use tokio::fs;
+use tokio::process;
+
+use std::fmt;
+use std::path::Path;
+use std::process::Stdio;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Converter {
+    pub python: String,
+    pub script: String,
+    pub quantize: String,
+}
+
+impl Default for Converter {
+    fn default() -> Self {
+        Self {
+            python: "python3".to_owned(),
+            script: "convert_hf_to_gguf.py".to_owned(),
+            quantize: "llama-quantize".to_owned(),
+        }
+    }
+}
+
+impl Converter {
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut converter = decode::map(value)?;
+        let default = Self::default();
+
+        Ok(Self {
+            python: converter
+                .optional("python", decode::string)?
+                .unwrap_or(default.python),
+            script: converter
+                .optional("script", decode::string)?
+                .unwrap_or(default.script),
+            quantize: converter
+                .optional("quantize", decode::string)?
+                .unwrap_or(default.quantize),
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        encode::map([
+            ("python", encode::string(&self.python)),
+            ("script", encode::string(&self.script)),
+            ("quantize", encode::string(&self.quantize)),
+        ])
+        .into_value()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quantization {
+    #[default]
+    Q4KM,
+    Q5KM,
+    Q8_0,
+    F16,
+}
+
+impl Quantization {
+    pub const ALL: &[Self] = &[Self::Q4KM, Self::Q5KM, Self::Q8_0, Self::F16];
+
+    fn bits(self) -> f64 {
+        match self {
+            Self::Q4KM => 4.9,
+            Self::Q5KM => 5.7,
+            Self::Q8_0 => 8.5,
+            Self::F16 => 16.0,
+        }
+    }
+
+    fn is_direct(self) -> bool {
+        matches!(self, Self::Q8_0 | Self::F16)
+    }
+}
+
+impl fmt::Display for Quantization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Q4KM => "Q4_K_M",
+            Self::Q5KM => "Q5_K_M",
+            Self::Q8_0 => "Q8_0",
+            Self::F16 => "F16",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    pub model: Id,
+    files: Vec<(String, u64)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Estimate {
+    pub disk: Size,
+    pub output: Size,
+    pub minutes: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Progress {
+    Downloading {
+        file: usize,
+        total: usize,
+        progress: Option<request::Progress>,
+    },
+    Converting,
+    Quantizing,
+}
+
+impl Source {
+    pub async fn fetch(id: Id) -> Result<Option<Self>, Error> {
+        #[derive(Deserialize)]
+        struct Entry {
+            r#type: String,
+            path: String,
+            size: u64,
+        }
+
+        let entries: Vec<Entry> = reqwest::Client::new()
+            .get(format!("{}/models/{}/tree/main", mirror::api(), id.0))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let files: Vec<_> = entries
+            .into_iter()
+            .filter(|entry| entry.r#type == "file" && is_needed(&entry.path))
+            .map(|entry| (entry.path, entry.size))
+            .collect();
+
+        if !files.iter().any(|(path, _)| path.ends_with(".safetensors")) {
+            return Ok(None);
+        }
+
+        Ok(Some(Self { model: id, files }))
+    }
+
+    fn weights(&self) -> u64 {
+        self.files
+            .iter()
+            .filter(|(path, _)| path.ends_with(".safetensors"))
+            .map(|(_, size)| size)
+            .sum()
+    }
+
+    pub fn estimate(&self, quantization: Quantization) -> Estimate {
+        let weights = self.weights();
+        let output = (weights as f64 * quantization.bits() / 16.0) as u64;
+
+        // The weights, an intermediate 16-bit GGUF to quantize, and the result
+        let disk = if quantization.is_direct() {
+            weights + output
+        } else {
+            weights * 2 + output
+        };
+
+        // About a minute per gigabyte and step on an ordinary machine, downloads aside
+        let steps = if quantization.is_direct() { 1 } else { 2 };
+        let minutes = (weights / 1_000_000_000).max(1) * steps;
+
+        Estimate {
+            disk: Size::from(disk),
+            output: Size::from(output),
+            minutes,
+        }
+    }
+}
+
+fn is_needed(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+
+    path.ends_with(".safetensors")
+        || name.ends_with(".json")
+        || name.ends_with(".tiktoken")
+        || name == "tokenizer.model"
+        || name == "merges.txt"
+        || name == "vocab.txt"
+}
+
+pub fn convert(
+    source: Source,
+    quantization: Quantization,
+    converter: Converter,
+    directory: Directory,
+) -> impl Straw<File, Progress, Error> {
+    sipper(move |mut progress| async move {
+        let model_directory = directory.path().join(&source.model.0);
+
+        // Hidden from scans of the library, which only look at the files of each model
+        let work = model_directory.join(".conversion");
+        fs::create_dir_all(&work).await?;
+
+        let total = source.files.len();
+
+        for (file, (path, _size)) in source.files.iter().enumerate() {
+            let destination = work.join(path);
+
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+
+            progress
+                .send(Progress::Downloading {
+                    file,
+                    total,
+                    progress: None,
+                })
+                .await;
+
+            request::download_file(
+                format!(
+                    "{}/{id}/resolve/main/{path}?download=true",
+                    mirror::url(),
+                    id = source.model.0
+                ),
+                &destination,
+            )
+            .with(move |download| Progress::Downloading {
+                file,
+                total,
+                progress: Some(download),
+            })
+            .run(&progress)
+            .await?;
+        }
+
+        let name = format!("{}-{quantization}.gguf", source.model.name());
+        let model_path = model_directory.join(&name);
+        let temp_path = model_path.with_extension("tmp");
+
+        progress.send(Progress::Converting).await;
+
+        if quantization.is_direct() {
+            run(
+                process::Command::new(converter.python.trim())
+                    .arg(converter.script.trim())
+                    .arg(&work)
+                    .arg("--outfile")
+                    .arg(&temp_path)
+                    .arg("--outtype")
+                    .arg(quantization.to_string().to_lowercase()),
+                "the conversion script",
+            )
+            .await?;
+        } else {
+            let intermediate = work.join("model-F16.gguf");
+
+            run(
+                process::Command::new(converter.python.trim())
+                    .arg(converter.script.trim())
+                    .arg(&work)
+                    .arg("--outfile")
+                    .arg(&intermediate)
+                    .arg("--outtype")
+                    .arg("f16"),
+                "the conversion script",
+            )
+            .await?;
+
+            progress.send(Progress::Quantizing).await;
+
+            run(
+                process::Command::new(converter.quantize.trim())
+                    .arg(&intermediate)
+                    .arg(&temp_path)
+                    .arg(quantization.to_string()),
+                "llama-quantize",
+            )
+            .await?;
+        }
+
+        fs::rename(&temp_path, &model_path).await?;
+        clean(&work).await;
+
+        Ok(File {
+            model: source.model,
+            name,
+            size: Some(Size::from(fs::metadata(&model_path).await?.len())),
+            hub: Hub::HuggingFace,
+        })
+    })
+}
+
+async fn run(command: &mut process::Command, tool: &'static str) -> Result<(), Error> {
+    let output = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .map(str::trim)
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("{tool} exited with an error"));
+
+        return Err(Error::ConversionFailed(reason, capture!()));
+    }
+
+    Ok(())
+}
+
+async fn clean(work: &Path) {
+    if let Err(error) = fs::remove_dir_all(work).await {
+        log::warn!("Conversion files could not be removed: {error}");
+    }
+}
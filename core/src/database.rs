@@ -0,0 +1,223 @@
+use crate::table::Table;
+use crate::Error;
+
+use decoder::{decode, encode, Value};
+use thiserror::capture;
+use tokio::process;
+use tokio::time;
+
+use std::fmt;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+const MAX_ROWS: usize = 1_000;
+
+const MAX_SCHEMA: usize = 8_000;
+
+const READ_ONLY: &[&str] = &[
+    "select", "with", "explain", "show", "values", "table", "pragma",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Database {
+    pub name: String,
+    pub kind: Kind,
+    pub connection: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Kind {
+    #[default]
+    Sqlite,
+    Postgres,
+}
+
+impl Kind {
+    pub const ALL: &[Self] = &[Self::Sqlite, Self::Postgres];
+
+    fn decode(value: Value) -> decoder::Result<Self> {
+        let kind = decode::string(value)?;
+
+        Ok(match kind.as_str() {
+            "postgres" => Self::Postgres,
+            _ => Self::Sqlite,
+        })
+    }
+
+    fn encode(self) -> Value {
+        encode::string(match self {
+            Self::Sqlite => "sqlite",
+            Self::Postgres => "postgres",
+        })
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Sqlite => "SQLite",
+            Self::Postgres => "Postgres",
+        })
+    }
+}
+
+impl Database {
+    pub async fn query(self, sql: String) -> Result<Table, Error> {
+        let sql = check(&sql)?;
+        let output = self.run(&sql).await?;
+
+        let mut table = Table::from_csv(&output);
+        table.rows.truncate(MAX_ROWS);
+
+        Ok(table)
+    }
+
+    pub async fn schema(&self) -> Result<String, Error> {
+        let sql = match self.kind {
+            Kind::Sqlite => {
+                "SELECT sql FROM sqlite_master WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%'"
+            }
+            Kind::Postgres => {
+                "SELECT table_schema || '.' || table_name AS name, \
+                string_agg(column_name || ' ' || data_type, ', ' ORDER BY ordinal_position) \
+                FROM information_schema.columns \
+                WHERE table_schema NOT IN ('pg_catalog', 'information_schema') \
+                GROUP BY table_schema, table_name ORDER BY name"
+            }
+        };
+
+        let table = Table::from_csv(&self.run(sql).await?);
+
+        let mut schema: String = table
+            .rows
+            .iter()
+            .map(|row| match self.kind {
+                Kind::Sqlite => format!("{};\n", row.join(" ")),
+                Kind::Postgres => format!("{}\n", row.join(": ")),
+            })
+            .collect();
+
+        if schema.len() > MAX_SCHEMA {
+            let mut end = MAX_SCHEMA;
+
+            while !schema.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            schema.truncate(end);
+            schema.push_str("\n[schema truncated]");
+        }
+
+        Ok(schema)
+    }
+
+    async fn run(&self, sql: &str) -> Result<String, Error> {
+        let mut command = match self.kind {
+            Kind::Sqlite => {
+                let mut command = process::Command::new("sqlite3");
+                let _ = command
+                    .args(["-readonly", "-safe", "-bail", "-csv", "-header"])
+                    .arg(&self.connection)
+                    .arg(sql);
+
+                command
+            }
+            Kind::Postgres => {
+                let mut command = process::Command::new("psql");
+                let _ = command
+                    .env("PGOPTIONS", "-c default_transaction_read_only=on")
+                    .args(["--csv", "--no-psqlrc", "-v", "ON_ERROR_STOP=1"])
+                    .args(["-d", &self.connection])
+                    .args(["-c", sql]);
+
+                command
+            }
+        };
+
+        let _ = command.kill_on_drop(true);
+
+        let output = time::timeout(TIMEOUT, command.output())
+            .await
+            .map_err(|_| Error::TimedOut("database query", capture!()))??;
+
+        if !output.status.success() {
+            let reason = String::from_utf8_lossy(&output.stderr).trim().to_owned();
+
+            return Err(Error::QueryFailed(
+                if reason.is_empty() {
+                    format!("{} exited with {}", self.kind, output.status)
+                } else {
+                    reason
+                },
+                capture!(),
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut database = decode::map(value)?;
+
+        Ok(Self {
+            name: database.required("name", decode::string)?,
+            kind: database.optional("kind", Kind::decode)?.unwrap_or_default(),
+            connection: database.required("connection", decode::string)?,
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        encode::map([
+            ("name", encode::string(&self.name)),
+            ("kind", self.kind.encode()),
+            ("connection", encode::string(&self.connection)),
+        ])
+        .into_value()
+    }
+
+    pub(crate) fn prompt(&self, schema: &str) -> String {
+        format!(
+            "You can query the \"{name}\" {kind} database of the user. \
+            To do so, write a single read-only SQL query in a ```sql code block. \
+            The user reviews the query and runs it, and the results are shown to them. \
+            Never run queries that modify data. The schema of the database is:\n{schema}",
+            name = self.name,
+            kind = self.kind,
+        )
+    }
+}
+
+impl fmt::Display for Database {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+// Only a single statement that reads data is accepted
+fn check(sql: &str) -> Result<String, Error> {
+    let sql = sql.trim().trim_end_matches(';').trim();
+
+    // Client commands, like `.shell` or `\!`, would run outside the database
+    if sql.starts_with(['.', '\\']) || sql.contains(';') {
+        return Err(Error::QueryFailed(
+            "only a single SQL statement can be run".to_owned(),
+            capture!(),
+        ));
+    }
+
+    let keyword = sql
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    if !READ_ONLY.contains(&keyword.as_str()) {
+        return Err(Error::QueryFailed(
+            format!("{} statements are not allowed", keyword.to_uppercase()),
+            capture!(),
+        ));
+    }
+
+    Ok(sql.to_owned())
+}
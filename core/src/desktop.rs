@@ -0,0 +1,83 @@
+use crate::Error;
+
+use thiserror::capture;
+use tokio::process;
+
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
+use std::process::Stdio;
+
+pub async fn open(target: impl AsRef<OsStr>) -> Result<(), Error> {
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+
+    run(process::Command::new(program).arg(target)).await
+}
+
+pub async fn reveal(path: impl AsRef<Path>) -> Result<(), Error> {
+    let path = path.as_ref();
+
+    if cfg!(target_os = "windows") {
+        let mut select = OsString::from("/select,");
+        select.push(path);
+
+        run(process::Command::new("explorer").arg(select)).await
+    } else if cfg!(target_os = "macos") {
+        run(process::Command::new("open").arg("-R").arg(path)).await
+    } else {
+        // xdg-open cannot select files, so the folder is opened instead
+        open(path.parent().unwrap_or(path)).await
+    }
+}
+
+pub async fn notify(title: &str, body: &str) -> Result<(), Error> {
+    if cfg!(target_os = "windows") {
+        let script = format!(
+            "Add-Type -AssemblyName System.Windows.Forms; \
+            $icon = New-Object System.Windows.Forms.NotifyIcon; \
+            $icon.Icon = [System.Drawing.SystemIcons]::Information; \
+            $icon.Visible = $true; \
+            $icon.ShowBalloonTip(10000, '{}', '{}', 'Info'); \
+            Start-Sleep -Seconds 10; \
+            $icon.Dispose()",
+            title.replace('\'', "''"),
+            body.replace('\'', "''")
+        );
+
+        run(process::Command::new("powershell")
+            .args(["-NoProfile", "-Command"])
+            .arg(script))
+        .await
+    } else if cfg!(target_os = "macos") {
+        let script = format!("display notification {body:?} with title {title:?}");
+
+        run(process::Command::new("osascript").arg("-e").arg(script)).await
+    } else {
+        run(process::Command::new("notify-send")
+            .args(["--app-name", "icebreaker"])
+            .arg(title)
+            .arg(body))
+        .await
+    }
+}
+
+async fn run(command: &mut process::Command) -> Result<(), Error> {
+    let status = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await?;
+
+    // explorer exits with 1 even when it succeeds
+    if !status.success() && !cfg!(target_os = "windows") {
+        return Err(Error::OpenFailed(status.to_string(), capture!()));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+const TAIL: usize = 40;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub model: String,
+    pub command: String,
+    pub exit_code: Option<i32>,
+    pub output: Vec<String>,
+}
+
+impl Report {
+    pub fn suggestions(&self) -> Vec<&'static str> {
+        let output = self.output.join("\n").to_lowercase();
+        let mentions = |needles: &[&str]| needles.iter().any(|needle| output.contains(needle));
+
+        let mut suggestions = Vec::new();
+
+        if mentions(&["out of memory", "failed to allocate", "cudamalloc"]) {
+            suggestions.push(
+                "The model does not fit in memory. Offload fewer layers to the GPU \
+                (e.g. ICEBREAKER_LLAMA_CPP_ARGS=\"--gpu-layers 20\") or pick a smaller quantization.",
+            );
+        }
+
+        if mentions(&["unknown model architecture", "unsupported model"]) {
+            suggestions.push(
+                "Your llama.cpp does not support this model yet. Update llama-server to a newer release.",
+            );
+        }
+
+        if mentions(&[
+            "invalid magic",
+            "failed to read",
+            "tensor data is not within",
+        ]) {
+            suggestions.push(
+                "The model file looks corrupted or incomplete. Delete it and download it again.",
+            );
+        }
+
+        if mentions(&["address already in use", "couldn't bind"]) {
+            suggestions.push("Another program is using port 8080. Close it and try again.");
+        }
+
+        if mentions(&["cuda", "rocm", "hip", "vulkan"]) && suggestions.is_empty() {
+            suggestions.push(
+                "The GPU backend failed. Update your drivers or run on the CPU \
+                (ICEBREAKER_LLAMA_CPP_ARGS=\"--gpu-layers 0\").",
+            );
+        }
+
+        if suggestions.is_empty() {
+            suggestions.push(
+                "Try a smaller quantization of the model, or offload fewer layers to the GPU.",
+            );
+        }
+
+        suggestions
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "### llama-server crashed while booting {}", self.model)?;
+        writeln!(f)?;
+
+        match self.exit_code {
+            Some(code) => writeln!(f, "- Exit code: `{code}`")?,
+            None => writeln!(f, "- Exit code: unknown")?,
+        }
+
+        writeln!(
+            f,
+            "- OS: `{} {}`",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        )?;
+        writeln!(f, "- Command: `{}`", self.command)?;
+        writeln!(f)?;
+        writeln!(f, "```")?;
+
+        for line in &self.output {
+            writeln!(f, "{line}")?;
+        }
+
+        write!(f, "```")
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Tail(VecDeque<String>);
+
+impl Tail {
+    pub fn push(&mut self, line: String) {
+        if self.0.len() == TAIL {
+            let _ = self.0.pop_front();
+        }
+
+        self.0.push_back(line);
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.0.iter().cloned().collect()
+    }
+}
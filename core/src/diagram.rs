@@ -0,0 +1,743 @@
+use crate::Error;
+
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+use regex::Regex;
+use tokio::fs;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+const CHAR_WIDTH: f32 = 8.0;
+const LINE_HEIGHT: f32 = 20.0;
+const PADDING: f32 = 15.0;
+const RANK_GAP: f32 = 50.0;
+const NODE_GAP: f32 = 30.0;
+const MARGIN: f32 = 20.0;
+
+#[derive(Debug, Clone)]
+pub struct Diagram {
+    pub svg: String,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Diagram {
+    pub fn parse(code: &str) -> Option<Self> {
+        let flowchart = Flowchart::parse(code)?;
+
+        if flowchart.nodes.is_empty() {
+            return None;
+        }
+
+        Some(flowchart.layout().render())
+    }
+
+    pub async fn save(self, path: PathBuf) -> Result<(), Error> {
+        fs::write(path, self.svg).await?;
+
+        Ok(())
+    }
+}
+
+pub fn find(markdown: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut block: Option<String> = None;
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(language)))
+                if language.trim() == "mermaid" =>
+            {
+                block = Some(String::new());
+            }
+            Event::Text(text) => {
+                if let Some(block) = &mut block {
+                    block.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                blocks.extend(block.take().map(|block| block.trim().to_owned()));
+            }
+            _ => {}
+        }
+    }
+
+    blocks
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Down,
+    Up,
+    Right,
+    Left,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Rectangle,
+    Round,
+    Circle,
+    Rhombus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stroke {
+    Solid,
+    Dotted,
+    Thick,
+}
+
+#[derive(Debug)]
+struct Node {
+    label: String,
+    shape: Shape,
+}
+
+#[derive(Debug)]
+struct Edge {
+    from: usize,
+    to: usize,
+    label: Option<String>,
+    stroke: Stroke,
+    has_arrow: bool,
+}
+
+#[derive(Debug)]
+struct Flowchart {
+    direction: Direction,
+    ids: HashMap<String, usize>,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+impl Flowchart {
+    fn parse(code: &str) -> Option<Self> {
+        const IGNORED: &[&str] = &[
+            "subgraph",
+            "end",
+            "direction",
+            "classDef",
+            "class",
+            "style",
+            "linkStyle",
+            "click",
+        ];
+
+        let mut lines = code
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with("%%"));
+
+        let mut header = lines.next()?.trim_end_matches(';').split_whitespace();
+
+        let direction = match header.next()? {
+            "graph" | "flowchart" => match header.next().unwrap_or("TD") {
+                "TD" | "TB" => Direction::Down,
+                "BT" => Direction::Up,
+                "LR" => Direction::Right,
+                "RL" => Direction::Left,
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        let mut flowchart = Self {
+            direction,
+            ids: HashMap::new(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        };
+
+        for statement in lines.flat_map(|line| line.split(';')).map(str::trim) {
+            let keyword = statement.split_whitespace().next().unwrap_or_default();
+
+            if statement.is_empty() || IGNORED.contains(&keyword) {
+                continue;
+            }
+
+            flowchart.statement(statement)?;
+        }
+
+        Some(flowchart)
+    }
+
+    fn statement(&mut self, statement: &str) -> Option<()> {
+        let (mut from, mut rest) = self.node(statement)?;
+
+        while !rest.trim().is_empty() {
+            let (link, after_link) = link(rest)?;
+            let (to, after_node) = self.node(after_link)?;
+
+            self.edges.push(Edge {
+                from,
+                to,
+                label: link.label,
+                stroke: link.stroke,
+                has_arrow: link.has_arrow,
+            });
+
+            from = to;
+            rest = after_node;
+        }
+
+        Some(())
+    }
+
+    fn node<'a>(&mut self, input: &'a str) -> Option<(usize, &'a str)> {
+        let input = input.trim_start();
+        let end = input
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(input.len());
+
+        if end == 0 {
+            return None;
+        }
+
+        let (id, rest) = input.split_at(end);
+        let (shape, rest) = match shape(rest) {
+            Some((shape, label, rest)) => (Some((shape, label)), rest),
+            None => (None, rest),
+        };
+
+        // Class shorthands, like `A:::important`, only affect styling
+        let rest = match rest.strip_prefix(":::") {
+            Some(class) => class.trim_start_matches(|c: char| c.is_alphanumeric() || c == '_'),
+            None => rest,
+        };
+
+        let index = match self.ids.get(id) {
+            Some(index) => *index,
+            None => {
+                let _ = self.ids.insert(id.to_owned(), self.nodes.len());
+
+                self.nodes.push(Node {
+                    label: id.to_owned(),
+                    shape: Shape::Rectangle,
+                });
+
+                self.nodes.len() - 1
+            }
+        };
+
+        if let Some((shape, label)) = shape {
+            self.nodes[index] = Node { label, shape };
+        }
+
+        Some((index, rest))
+    }
+
+    fn layout(self) -> Layout {
+        let count = self.nodes.len();
+
+        let mut successors = vec![Vec::new(); count];
+
+        for (index, edge) in self.edges.iter().enumerate() {
+            successors[edge.from].push((edge.to, index));
+        }
+
+        // Links closing a cycle are ignored when ranking
+        let mut is_back_edge = vec![false; self.edges.len()];
+        let mut visits = vec![Visit::Pending; count];
+
+        for node in 0..count {
+            visit(node, &successors, &mut visits, &mut is_back_edge);
+        }
+
+        let forward: Vec<&Edge> = self
+            .edges
+            .iter()
+            .zip(&is_back_edge)
+            .filter(|(edge, is_back)| !**is_back && edge.from != edge.to)
+            .map(|(edge, _)| edge)
+            .collect();
+
+        let mut ranks = vec![0; count];
+
+        // The forward links form a DAG, so ranks settle in fewer passes than nodes
+        for _ in 0..count {
+            let mut changed = false;
+
+            for edge in &forward {
+                if ranks[edge.to] < ranks[edge.from] + 1 {
+                    ranks[edge.to] = ranks[edge.from] + 1;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        let mut layers = vec![Vec::new(); ranks.iter().max().map_or(0, |max| max + 1)];
+
+        for (node, rank) in ranks.iter().enumerate() {
+            layers[*rank].push(node);
+        }
+
+        // Nodes are moved next to the average position of their predecessors
+        let mut positions = vec![0.0; count];
+
+        for layer in &mut layers {
+            for (position, node) in layer.iter().enumerate() {
+                positions[*node] = position as f32;
+            }
+        }
+
+        for layer in layers.iter_mut().skip(1) {
+            let key = |node: usize| {
+                let predecessors: Vec<f32> = forward
+                    .iter()
+                    .filter(|edge| edge.to == node)
+                    .map(|edge| positions[edge.from])
+                    .collect();
+
+                if predecessors.is_empty() {
+                    positions[node]
+                } else {
+                    predecessors.iter().sum::<f32>() / predecessors.len() as f32
+                }
+            };
+
+            let mut keyed: Vec<(usize, f32)> =
+                layer.iter().map(|node| (*node, key(*node))).collect();
+
+            keyed.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+            for (position, (node, _)) in keyed.iter().enumerate() {
+                positions[*node] = position as f32;
+            }
+
+            *layer = keyed.into_iter().map(|(node, _)| node).collect();
+        }
+
+        let sizes: Vec<(f32, f32)> = self.nodes.iter().map(Node::size).collect();
+        let is_vertical = matches!(self.direction, Direction::Down | Direction::Up);
+
+        // The main axis follows the ranks, and the cross axis the nodes in a rank
+        let main = |(width, height): (f32, f32)| if is_vertical { height } else { width };
+        let cross = |(width, height): (f32, f32)| if is_vertical { width } else { height };
+
+        let breadths: Vec<f32> = layers
+            .iter()
+            .map(|layer| {
+                layer.iter().map(|node| cross(sizes[*node])).sum::<f32>()
+                    + NODE_GAP * layer.len().saturating_sub(1) as f32
+            })
+            .collect();
+
+        let breadth = breadths.iter().copied().fold(0.0, f32::max);
+        let mut centers = vec![(0.0, 0.0); count];
+        let mut depth = 0.0;
+
+        for (layer, layer_breadth) in layers.iter().zip(&breadths) {
+            let thickness = layer
+                .iter()
+                .map(|node| main(sizes[*node]))
+                .fold(0.0, f32::max);
+
+            let mut offset = (breadth - layer_breadth) / 2.0;
+
+            for node in layer {
+                let size = cross(sizes[*node]);
+
+                centers[*node] = (depth + thickness / 2.0, offset + size / 2.0);
+                offset += size + NODE_GAP;
+            }
+
+            depth += thickness + RANK_GAP;
+        }
+
+        let depth = (depth - RANK_GAP).max(0.0);
+
+        let nodes = self
+            .nodes
+            .into_iter()
+            .zip(centers)
+            .zip(sizes)
+            .map(|((node, (along, across)), (width, height))| {
+                let along = match self.direction {
+                    Direction::Up | Direction::Left => depth - along,
+                    Direction::Down | Direction::Right => along,
+                };
+
+                let (x, y) = if is_vertical {
+                    (across, along)
+                } else {
+                    (along, across)
+                };
+
+                Placed {
+                    node,
+                    x: x + MARGIN,
+                    y: y + MARGIN,
+                    width,
+                    height,
+                }
+            })
+            .collect();
+
+        let (width, height) = if is_vertical {
+            (breadth, depth)
+        } else {
+            (depth, breadth)
+        };
+
+        Layout {
+            nodes,
+            edges: self.edges,
+            width: width + MARGIN * 2.0,
+            height: height + MARGIN * 2.0,
+        }
+    }
+}
+
+impl Node {
+    fn size(&self) -> (f32, f32) {
+        let lines = self.label.lines().count().max(1) as f32;
+        let columns = self
+            .label
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or_default() as f32;
+
+        let width = columns * CHAR_WIDTH + PADDING * 2.0;
+        let height = lines * LINE_HEIGHT + PADDING;
+
+        match self.shape {
+            Shape::Rectangle | Shape::Round => (width, height),
+            Shape::Circle => {
+                let diameter = width.max(height);
+
+                (diameter, diameter)
+            }
+            Shape::Rhombus => (width * 1.5, height * 1.8),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    Pending,
+    Active,
+    Done,
+}
+
+fn visit(
+    node: usize,
+    successors: &[Vec<(usize, usize)>],
+    visits: &mut [Visit],
+    is_back_edge: &mut [bool],
+) {
+    if visits[node] != Visit::Pending {
+        return;
+    }
+
+    visits[node] = Visit::Active;
+
+    for (next, edge) in &successors[node] {
+        match visits[*next] {
+            Visit::Pending => visit(*next, successors, visits, is_back_edge),
+            Visit::Active => is_back_edge[*edge] = true,
+            Visit::Done => {}
+        }
+    }
+
+    visits[node] = Visit::Done;
+}
+
+struct Link {
+    label: Option<String>,
+    stroke: Stroke,
+    has_arrow: bool,
+}
+
+fn link(input: &str) -> Option<(Link, &str)> {
+    static LINK: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r"^\s*(?:(?:--|-\.|==)\s+([^|]+?)\s+)?<?(-{2,}|-?\.+-|={2,})(>)?\s*(?:\|([^|]*)\|)?",
+        )
+        .expect("Valid pattern")
+    });
+
+    let captures = LINK.captures(input)?;
+    let body = captures.get(2)?.as_str();
+
+    let stroke = if body.contains('.') {
+        Stroke::Dotted
+    } else if body.starts_with('=') {
+        Stroke::Thick
+    } else {
+        Stroke::Solid
+    };
+
+    let label = captures
+        .get(1)
+        .or(captures.get(4))
+        .map(|label| text(label.as_str()))
+        .filter(|label| !label.is_empty());
+
+    let link = Link {
+        label,
+        stroke,
+        has_arrow: captures.get(3).is_some(),
+    };
+
+    Some((link, &input[captures.get(0)?.end()..]))
+}
+
+fn shape(input: &str) -> Option<(Shape, String, &str)> {
+    const SHAPES: &[(&str, &str, Shape)] = &[
+        ("(((", ")))", Shape::Circle),
+        ("((", "))", Shape::Circle),
+        ("([", "])", Shape::Round),
+        ("[[", "]]", Shape::Rectangle),
+        ("[(", ")]", Shape::Round),
+        ("[/", "/]", Shape::Rectangle),
+        ("[\\", "\\]", Shape::Rectangle),
+        ("[/", "\\]", Shape::Rectangle),
+        ("[\\", "/]", Shape::Rectangle),
+        ("{{", "}}", Shape::Rhombus),
+        ("(", ")", Shape::Round),
+        ("[", "]", Shape::Rectangle),
+        ("{", "}", Shape::Rhombus),
+        (">", "]", Shape::Rectangle),
+    ];
+
+    SHAPES.iter().find_map(|(open, close, shape)| {
+        let inner = input.strip_prefix(open)?;
+
+        // Quoted labels may contain the closing delimiter
+        let start = if inner.trim_start().starts_with('"') {
+            let quote = inner.find('"')?;
+
+            quote + 1 + inner[quote + 1..].find('"')?
+        } else {
+            0
+        };
+
+        let end = start + inner[start..].find(close)?;
+
+        Some((*shape, text(&inner[..end]), &inner[end + close.len()..]))
+    })
+}
+
+fn text(label: &str) -> String {
+    let label = label.trim();
+    let label = label
+        .strip_prefix('"')
+        .and_then(|label| label.strip_suffix('"'))
+        .unwrap_or(label);
+
+    label
+        .replace("<br/>", "\n")
+        .replace("<br />", "\n")
+        .replace("<br>", "\n")
+        .replace("#quot;", "\"")
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+struct Layout {
+    nodes: Vec<Placed>,
+    edges: Vec<Edge>,
+    width: f32,
+    height: f32,
+}
+
+struct Placed {
+    node: Node,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+impl Placed {
+    fn boundary(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let (dx, dy) = (x - self.x, y - self.y);
+
+        if dx == 0.0 && dy == 0.0 {
+            return (self.x, self.y);
+        }
+
+        let (half_width, half_height) = (self.width / 2.0, self.height / 2.0);
+
+        let t = match self.node.shape {
+            Shape::Rectangle | Shape::Round => (half_width / dx.abs()).min(half_height / dy.abs()),
+            Shape::Circle => half_width / dx.hypot(dy),
+            Shape::Rhombus => 1.0 / (dx.abs() / half_width + dy.abs() / half_height),
+        };
+
+        (self.x + dx * t, self.y + dy * t)
+    }
+}
+
+impl Layout {
+    fn render(self) -> Diagram {
+        const TEXT: &str = "#333333";
+        const FILL: &str = "#ECECFF";
+        const BORDER: &str = "#9370DB";
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.0}\" height=\"{height:.0}\" \
+            viewBox=\"0 0 {width:.0} {height:.0}\" font-family=\"sans-serif\" font-size=\"14\">\n\
+            <defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" \
+            markerWidth=\"8\" markerHeight=\"8\" orient=\"auto\">\
+            <path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"{TEXT}\"/></marker></defs>\n\
+            <rect width=\"100%\" height=\"100%\" fill=\"#FFFFFF\"/>\n",
+            width = self.width,
+            height = self.height,
+        );
+
+        let mut labels = Vec::new();
+
+        for edge in &self.edges {
+            let (from, to) = (&self.nodes[edge.from], &self.nodes[edge.to]);
+
+            let (x1, y1, x2, y2) = if edge.from == edge.to {
+                // Loops go around the right side of the node
+                let (x, y) = (from.x + from.width / 2.0, from.y);
+
+                svg.push_str(&format!(
+                    "<path d=\"M {x:.1} {:.1} C {:.1} {:.1}, {:.1} {:.1}, {x:.1} {:.1}\" \
+                    fill=\"none\"{}/>\n",
+                    y - 8.0,
+                    x + 30.0,
+                    y - 20.0,
+                    x + 30.0,
+                    y + 20.0,
+                    y + 8.0,
+                    stroke(edge),
+                ));
+
+                (x, y, x + 30.0, y)
+            } else {
+                let (x1, y1) = from.boundary((to.x, to.y));
+                let (x2, y2) = to.boundary((from.x, from.y));
+
+                svg.push_str(&format!(
+                    "<line x1=\"{x1:.1}\" y1=\"{y1:.1}\" x2=\"{x2:.1}\" y2=\"{y2:.1}\"{}/>\n",
+                    stroke(edge),
+                ));
+
+                (x1, y1, x2, y2)
+            };
+
+            if let Some(label) = &edge.label {
+                labels.push((label, (x1 + x2) / 2.0, (y1 + y2) / 2.0));
+            }
+        }
+
+        for placed in &self.nodes {
+            let (x, y) = (placed.x, placed.y);
+            let (half_width, half_height) = (placed.width / 2.0, placed.height / 2.0);
+            let style = format!("fill=\"{FILL}\" stroke=\"{BORDER}\" stroke-width=\"1\"");
+
+            svg.push_str(&match placed.node.shape {
+                Shape::Rectangle | Shape::Round => format!(
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"{}\" {style}/>\n",
+                    x - half_width,
+                    y - half_height,
+                    placed.width,
+                    placed.height,
+                    if placed.node.shape == Shape::Round {
+                        half_height
+                    } else {
+                        3.0
+                    },
+                ),
+                Shape::Circle => {
+                    format!("<circle cx=\"{x:.1}\" cy=\"{y:.1}\" r=\"{half_width:.1}\" {style}/>\n")
+                }
+                Shape::Rhombus => format!(
+                    "<polygon points=\"{x:.1},{:.1} {:.1},{y:.1} {x:.1},{:.1} {:.1},{y:.1}\" {style}/>\n",
+                    y - half_height,
+                    x + half_width,
+                    y + half_height,
+                    x - half_width,
+                ),
+            });
+
+            svg.push_str(&lines(&placed.node.label, x, y, TEXT));
+        }
+
+        for (label, x, y) in labels {
+            let columns = label
+                .lines()
+                .map(|line| line.chars().count())
+                .max()
+                .unwrap_or_default() as f32;
+
+            let lines_count = label.lines().count().max(1) as f32;
+            let (width, height) = (columns * CHAR_WIDTH + 8.0, lines_count * LINE_HEIGHT);
+
+            svg.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{width:.1}\" height=\"{height:.1}\" \
+                fill=\"#FFFFFF\" opacity=\"0.9\"/>\n",
+                x - width / 2.0,
+                y - height / 2.0,
+            ));
+
+            svg.push_str(&lines(label, x, y, TEXT));
+        }
+
+        svg.push_str("</svg>\n");
+
+        Diagram {
+            svg,
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+fn stroke(edge: &Edge) -> String {
+    let mut stroke = format!(
+        " stroke=\"#333333\" stroke-width=\"{}\"",
+        if edge.stroke == Stroke::Thick {
+            3.0
+        } else {
+            1.5
+        }
+    );
+
+    if edge.stroke == Stroke::Dotted {
+        stroke.push_str(" stroke-dasharray=\"4 4\"");
+    }
+
+    if edge.has_arrow {
+        stroke.push_str(" marker-end=\"url(#arrow)\"");
+    }
+
+    stroke
+}
+
+fn lines(text: &str, x: f32, y: f32, color: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let top = y - lines.len() as f32 * LINE_HEIGHT / 2.0;
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            format!(
+                "<text x=\"{x:.1}\" y=\"{:.1}\" text-anchor=\"middle\" fill=\"{color}\">{}</text>\n",
+                top + LINE_HEIGHT * index as f32 + LINE_HEIGHT * 0.7,
+                escape(line),
+            )
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
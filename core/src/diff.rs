@@ -0,0 +1,141 @@
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+use similar::{ChangeTag, TextDiff};
+
+const MIN_SIMILARITY: f32 = 0.3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlock {
+    pub language: String,
+    pub code: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Revision {
+    pub language: String,
+    pub lines: Vec<Line>,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Line {
+    pub change: Change,
+    pub old: Option<usize>,
+    pub new: Option<usize>,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Equal,
+    Insert,
+    Delete,
+}
+
+pub fn code_blocks(markdown: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<CodeBlock> = None;
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                current = Some(CodeBlock {
+                    language: match kind {
+                        CodeBlockKind::Fenced(language) => language.to_string(),
+                        CodeBlockKind::Indented => String::new(),
+                    },
+                    code: String::new(),
+                });
+            }
+            Event::Text(text) => {
+                if let Some(block) = &mut current {
+                    block.code.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some(block) = current.take() {
+                    blocks.push(block);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    blocks
+}
+
+pub fn revision<'a>(
+    earlier: impl DoubleEndedIterator<Item = &'a str>,
+    reply: &str,
+) -> Option<Revision> {
+    let revised = code_blocks(reply)
+        .into_iter()
+        .max_by_key(|block| block.code.len())?;
+
+    let mut best: Option<(f32, CodeBlock)> = None;
+
+    for message in earlier.rev() {
+        for block in code_blocks(message) {
+            if block.code == revised.code {
+                continue;
+            }
+
+            if !block.language.is_empty()
+                && !revised.language.is_empty()
+                && block.language != revised.language
+            {
+                continue;
+            }
+
+            let similarity = TextDiff::from_lines(&block.code, &revised.code).ratio();
+
+            if similarity >= MIN_SIMILARITY
+                && best.as_ref().is_none_or(|(best, _)| similarity > *best)
+            {
+                best = Some((similarity, block));
+            }
+        }
+    }
+
+    let (_, original) = best?;
+
+    Some(diff(&original.code, revised))
+}
+
+fn diff(original: &str, revised: CodeBlock) -> Revision {
+    let diff = TextDiff::from_lines(original, &revised.code);
+
+    let mut insertions = 0;
+    let mut deletions = 0;
+
+    let lines = diff
+        .iter_all_changes()
+        .map(|change| {
+            let kind = match change.tag() {
+                ChangeTag::Equal => Change::Equal,
+                ChangeTag::Insert => {
+                    insertions += 1;
+                    Change::Insert
+                }
+                ChangeTag::Delete => {
+                    deletions += 1;
+                    Change::Delete
+                }
+            };
+
+            Line {
+                change: kind,
+                old: change.old_index().map(|index| index + 1),
+                new: change.new_index().map(|index| index + 1),
+                content: change.value().trim_end_matches('\n').to_owned(),
+            }
+        })
+        .collect();
+
+    Revision {
+        language: revised.language,
+        lines,
+        insertions,
+        deletions,
+    }
+}
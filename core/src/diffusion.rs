@@ -0,0 +1,301 @@
+use crate::attachment::Attachment;
+use crate::Error;
+
+use base64::Engine;
+use decoder::{decode, encode, Value};
+use serde::Deserialize;
+use thiserror::capture;
+use tokio::fs;
+use tokio::process;
+use tokio::task;
+use uuid::Uuid;
+
+use std::env;
+use std::fmt;
+use std::process::Stdio;
+
+const DEFAULT_PROGRAM: &str = "sd";
+const DEFAULT_STEPS: u64 = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diffusion {
+    pub backend: Backend,
+    pub program: String,
+    pub model: String,
+    pub base_url: String,
+    pub api_key: String,
+    pub api_model: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    #[default]
+    Local,
+    Api,
+}
+
+impl Backend {
+    pub const ALL: &[Self] = &[Self::Local, Self::Api];
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Local => "stable-diffusion.cpp",
+            Self::Api => "Image API",
+        })
+    }
+}
+
+impl Default for Diffusion {
+    fn default() -> Self {
+        Self {
+            backend: Backend::default(),
+            program: DEFAULT_PROGRAM.to_owned(),
+            model: String::new(),
+            base_url: String::new(),
+            api_key: String::new(),
+            api_model: String::new(),
+        }
+    }
+}
+
+impl Diffusion {
+    pub fn is_configured(&self) -> bool {
+        match self.backend {
+            Backend::Local => !self.program.trim().is_empty() && !self.model.trim().is_empty(),
+            Backend::Api => !self.base_url.trim().is_empty(),
+        }
+    }
+
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut diffusion = decode::map(value)?;
+        let default = Self::default();
+
+        Ok(Self {
+            backend: match diffusion.optional("backend", decode::string)?.as_deref() {
+                Some("api") => Backend::Api,
+                _ => Backend::Local,
+            },
+            program: diffusion
+                .optional("program", decode::string)?
+                .unwrap_or(default.program),
+            model: diffusion
+                .optional("model", decode::string)?
+                .unwrap_or_default(),
+            base_url: diffusion
+                .optional("base_url", decode::string)?
+                .unwrap_or_default(),
+            api_key: diffusion
+                .optional("api_key", decode::string)?
+                .unwrap_or_default(),
+            api_model: diffusion
+                .optional("api_model", decode::string)?
+                .unwrap_or_default(),
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        encode::map([
+            (
+                "backend",
+                encode::string(match self.backend {
+                    Backend::Local => "local",
+                    Backend::Api => "api",
+                }),
+            ),
+            ("program", encode::string(&self.program)),
+            ("model", encode::string(&self.model)),
+            ("base_url", encode::string(&self.base_url)),
+            ("api_key", encode::string(&self.api_key)),
+            ("api_model", encode::string(&self.api_model)),
+        ])
+        .into_value()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub prompt: String,
+    pub size: Size,
+    pub steps: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Size {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Size {
+    pub const ALL: &[Self] = &[
+        Self::square(512),
+        Self::square(768),
+        Self::square(1024),
+        Self {
+            width: 768,
+            height: 512,
+        },
+        Self {
+            width: 512,
+            height: 768,
+        },
+    ];
+
+    const fn square(side: u32) -> Self {
+        Self {
+            width: side,
+            height: side,
+        }
+    }
+}
+
+impl Default for Size {
+    fn default() -> Self {
+        Self::square(512)
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl Request {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            size: Size::default(),
+            steps: DEFAULT_STEPS,
+        }
+    }
+}
+
+pub async fn generate(diffusion: Diffusion, request: Request) -> Result<Attachment, Error> {
+    if !diffusion.is_configured() {
+        return Err(Error::NotConfigured("image generation", capture!()));
+    }
+
+    let png = match diffusion.backend {
+        Backend::Local => local(&diffusion, &request).await?,
+        Backend::Api => api(&diffusion, &request).await?,
+    };
+
+    let (width, height, rgba) = task::spawn_blocking(move || {
+        let image = image::load_from_memory(&png)
+            .map_err(|_| Error::InvalidImage(capture!()))?
+            .into_rgba8();
+
+        Ok::<_, Error>((image.width(), image.height(), image.into_raw()))
+    })
+    .await??;
+
+    Attachment::image(width, height, rgba).await
+}
+
+async fn local(diffusion: &Diffusion, request: &Request) -> Result<Vec<u8>, Error> {
+    let output = env::temp_dir().join(format!("icebreaker-{}.png", Uuid::new_v4().simple()));
+
+    let result = process::Command::new(diffusion.program.trim())
+        .arg("--model")
+        .arg(diffusion.model.trim())
+        .arg("--prompt")
+        .arg(&request.prompt)
+        .arg("--width")
+        .arg(request.size.width.to_string())
+        .arg("--height")
+        .arg(request.size.height.to_string())
+        .arg("--steps")
+        .arg(request.steps.to_string())
+        .arg("--output")
+        .arg(&output)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+
+    if !result.status.success() {
+        let _ = fs::remove_file(&output).await;
+
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        let reason = stderr
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or("stable-diffusion.cpp exited with an error");
+
+        return Err(Error::GenerationFailed(
+            reason.trim().to_owned(),
+            capture!(),
+        ));
+    }
+
+    let png = fs::read(&output).await?;
+    let _ = fs::remove_file(&output).await;
+
+    Ok(png)
+}
+
+async fn api(diffusion: &Diffusion, request: &Request) -> Result<Vec<u8>, Error> {
+    #[derive(Deserialize)]
+    struct Response {
+        data: Vec<Image>,
+    }
+
+    #[derive(Deserialize)]
+    struct Image {
+        b64_json: Option<String>,
+        url: Option<String>,
+    }
+
+    let mut body = serde_json::json!({
+        "prompt": request.prompt,
+        "size": request.size.to_string(),
+        "n": 1,
+        "response_format": "b64_json",
+    });
+
+    // OpenAI rejects parameters it does not know, while most other providers
+    // take the number of steps
+    if !diffusion.base_url.contains("api.openai.com") {
+        body["steps"] = request.steps.into();
+    }
+
+    if !diffusion.api_model.trim().is_empty() {
+        body["model"] = diffusion.api_model.trim().into();
+    }
+
+    let mut http = reqwest::Client::new()
+        .post(format!(
+            "{}/images/generations",
+            diffusion.base_url.trim().trim_end_matches('/')
+        ))
+        .json(&body);
+
+    if !diffusion.api_key.trim().is_empty() {
+        http = http.bearer_auth(diffusion.api_key.trim());
+    }
+
+    let response: Response = http.send().await?.error_for_status()?.json().await?;
+
+    let Some(image) = response.data.into_iter().next() else {
+        return Err(Error::InvalidResponse("image API", capture!()));
+    };
+
+    // Some providers ignore the response format and link to the image instead
+    match (image.b64_json, image.url) {
+        (Some(data), _) => base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|_| Error::InvalidResponse("image API", capture!())),
+        (None, Some(url)) => Ok(reqwest::get(url)
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?
+            .to_vec()),
+        (None, None) => Err(Error::InvalidResponse("image API", capture!())),
+    }
+}
@@ -1,19 +1,46 @@
-use std::path::Path;
-use std::sync::LazyLock;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
 
-pub fn config() -> &'static Path {
+pub const PROFILES: &str = "profiles";
+
+static PROFILE: RwLock<Option<String>> = RwLock::new(None);
+
+pub fn config() -> PathBuf {
+    profiled(base_config())
+}
+
+pub fn data() -> PathBuf {
+    profiled(base_data())
+}
+
+pub fn base_config() -> &'static Path {
     PROJECT
         .as_ref()
         .map(directories::ProjectDirs::config_dir)
         .unwrap_or(Path::new("./config"))
 }
 
-pub fn data() -> &'static Path {
+pub fn base_data() -> &'static Path {
     PROJECT
         .as_ref()
         .map(directories::ProjectDirs::data_dir)
         .unwrap_or(Path::new("./data"))
 }
 
+pub fn select(profile: Option<String>) {
+    if let Ok(mut current) = PROFILE.write() {
+        *current = profile;
+    }
+}
+
+fn profiled(base: &Path) -> PathBuf {
+    let profile = PROFILE.read().ok().and_then(|profile| profile.clone());
+
+    match profile {
+        Some(profile) => base.join(PROFILES).join(profile),
+        None => base.to_path_buf(),
+    }
+}
+
 static PROJECT: LazyLock<Option<directories::ProjectDirs>> =
     LazyLock::new(|| directories::ProjectDirs::from("rs.icebreaker", "", "icebreaker"));
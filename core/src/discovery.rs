@@ -0,0 +1,143 @@
+use crate::model::{APIAccess, APIType};
+use crate::Error;
+
+use futures::stream::{self, StreamExt};
+use serde::Deserialize;
+use thiserror::capture;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::time;
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+const PORTS: &[(u16, Kind)] = &[
+    (8080, Kind::LlamaCpp),
+    (8000, Kind::Vllm),
+    (1234, Kind::LmStudio),
+];
+
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(300);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+const CONCURRENCY: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Server {
+    pub kind: Kind,
+    pub address: SocketAddr,
+    pub models: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    LlamaCpp,
+    Vllm,
+    LmStudio,
+}
+
+impl Server {
+    pub fn base_url(&self) -> String {
+        format!("http://{}/v1", self.address)
+    }
+
+    pub fn access(&self) -> APIAccess {
+        APIAccess {
+            openai_compat: None,
+            azure: None,
+            ssh: None,
+            api_key: None,
+            base_url: Some(self.base_url()),
+            kind: APIType::OpenAICompatible,
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::LlamaCpp => "llama.cpp",
+            Self::Vllm => "vLLM",
+            Self::LmStudio => "LM Studio",
+        })
+    }
+}
+
+pub async fn scan() -> Result<Vec<Server>, Error> {
+    let local = local_address().await?;
+    let [a, b, c, _] = local.octets();
+
+    let candidates = (1..=254).flat_map(|host| {
+        PORTS.iter().map(move |&(port, kind)| {
+            (
+                SocketAddr::new(IpAddr::V4(Ipv4Addr::new(a, b, c, host)), port),
+                kind,
+            )
+        })
+    });
+
+    let mut servers: Vec<Server> = stream::iter(candidates)
+        .map(|(address, kind)| probe(address, kind))
+        .buffer_unordered(CONCURRENCY)
+        .filter_map(|server| async move { server })
+        .collect()
+        .await;
+
+    servers.sort_by_key(|server| server.address);
+
+    Ok(servers)
+}
+
+async fn probe(address: SocketAddr, kind: Kind) -> Option<Server> {
+    #[derive(Deserialize)]
+    struct Response {
+        data: Vec<Model>,
+    }
+
+    #[derive(Deserialize)]
+    struct Model {
+        id: String,
+        #[serde(default)]
+        owned_by: String,
+    }
+
+    let _stream = time::timeout(CONNECT_TIMEOUT, TcpStream::connect(address))
+        .await
+        .ok()?
+        .ok()?;
+
+    let response: Response = reqwest::Client::new()
+        .get(format!("http://{address}/v1/models"))
+        .timeout(PROBE_TIMEOUT)
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+
+    // Servers may listen on any port, so trust what they say about themselves
+    let kind = match response.data.first().map(|model| model.owned_by.as_str()) {
+        Some("llamacpp") => Kind::LlamaCpp,
+        Some("vllm") => Kind::Vllm,
+        _ => kind,
+    };
+
+    Some(Server {
+        kind,
+        address,
+        models: response.data.into_iter().map(|model| model.id).collect(),
+    })
+}
+
+// Connecting a UDP socket sends no packets; it only picks a route
+async fn local_address() -> Result<Ipv4Addr, Error> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.connect((Ipv4Addr::new(192, 0, 2, 1), 80)).await?;
+
+    match socket.local_addr()?.ip() {
+        IpAddr::V4(address) if !address.is_loopback() => Ok(address),
+        _ => Err(Error::NotConfigured("a local network", capture!())),
+    }
+}
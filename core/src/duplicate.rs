@@ -0,0 +1,235 @@
+use crate::Error;
+
+use sha2::{Digest, Sha256};
+use tokio::task;
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fmt::Write;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Duplicate {
+    pub size: u64,
+    pub paths: Vec<PathBuf>,
+    pub is_verified: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Hardlink,
+    Delete,
+}
+
+impl Duplicate {
+    pub fn name(&self) -> String {
+        self.paths
+            .first()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    pub fn reclaimable(&self) -> u64 {
+        self.size * (self.paths.len() as u64).saturating_sub(1)
+    }
+}
+
+// Files with the same name and size are assumed to be copies; the rest are
+// compared by checksum. Copies in earlier directories are kept
+pub async fn find(directories: Vec<PathBuf>) -> Result<Vec<Duplicate>, Error> {
+    task::spawn_blocking(move || {
+        let mut linked = HashSet::new();
+        let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+
+        for directory in &directories {
+            for path in models(directory) {
+                let metadata = fs::metadata(&path)?;
+
+                // Empty files and files linked together take no extra space
+                if metadata.len() == 0 {
+                    continue;
+                }
+
+                if identity(&metadata).is_some_and(|identity| !linked.insert(identity)) {
+                    continue;
+                }
+
+                by_size.entry(metadata.len()).or_default().push(path);
+            }
+        }
+
+        let mut duplicates = Vec::new();
+
+        for (size, paths) in by_size {
+            if paths.len() < 2 {
+                continue;
+            }
+
+            let mut by_name: HashMap<OsString, Vec<PathBuf>> = HashMap::new();
+
+            for path in paths {
+                let name = path.file_name().unwrap_or_default().to_owned();
+
+                by_name.entry(name).or_default().push(path);
+            }
+
+            let mut unnamed = Vec::new();
+
+            for (_name, paths) in by_name {
+                if paths.len() > 1 {
+                    duplicates.push(Duplicate {
+                        size,
+                        paths,
+                        is_verified: false,
+                    });
+                } else {
+                    unnamed.extend(paths);
+                }
+            }
+
+            if unnamed.len() < 2 {
+                continue;
+            }
+
+            let mut by_checksum: HashMap<String, Vec<PathBuf>> = HashMap::new();
+
+            for path in unnamed {
+                by_checksum.entry(checksum(&path)?).or_default().push(path);
+            }
+
+            duplicates.extend(
+                by_checksum
+                    .into_values()
+                    .filter(|paths| paths.len() > 1)
+                    .map(|paths| Duplicate {
+                        size,
+                        paths,
+                        is_verified: true,
+                    }),
+            );
+        }
+
+        let priority = |path: &PathBuf| {
+            directories
+                .iter()
+                .position(|directory| path.starts_with(directory))
+                .unwrap_or(directories.len())
+        };
+
+        for duplicate in &mut duplicates {
+            duplicate
+                .paths
+                .sort_by(|a, b| (priority(a), a).cmp(&(priority(b), b)));
+        }
+
+        duplicates.sort_by_key(|duplicate| std::cmp::Reverse(duplicate.reclaimable()));
+
+        Ok(duplicates)
+    })
+    .await?
+}
+
+// Copies whose contents differ from the kept file are left alone
+pub async fn resolve(duplicate: Duplicate, strategy: Strategy) -> Result<u64, Error> {
+    task::spawn_blocking(move || {
+        let Some((kept, copies)) = duplicate.paths.split_first() else {
+            return Ok(0);
+        };
+
+        let expected = checksum(kept)?;
+        let mut freed = 0;
+
+        for copy in copies {
+            if checksum(copy)? != expected {
+                log::warn!("{} differs from {}", copy.display(), kept.display());
+                continue;
+            }
+
+            match strategy {
+                Strategy::Hardlink => {
+                    let link = copy.with_extension("link");
+
+                    fs::hard_link(kept, &link)?;
+                    fs::rename(&link, copy)?;
+                }
+                Strategy::Delete => {
+                    fs::remove_file(copy)?;
+                }
+            }
+
+            freed += duplicate.size;
+        }
+
+        Ok(freed)
+    })
+    .await?
+}
+
+pub(crate) fn checksum(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 1 << 20];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+
+        if read == 0 {
+            break;
+        }
+
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        }))
+}
+
+fn models(directory: &Path) -> Vec<PathBuf> {
+    let mut pending = vec![directory.to_path_buf()];
+    let mut models = Vec::new();
+
+    while let Some(directory) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&directory) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() => pending.push(path),
+                Ok(kind)
+                    if kind.is_file()
+                        && path
+                            .extension()
+                            .is_some_and(|extension| extension == "gguf") =>
+                {
+                    models.push(path);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    models
+}
+
+#[cfg(unix)]
+fn identity(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn identity(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
@@ -0,0 +1,356 @@
+use crate::Error;
+
+use decoder::{decode, encode, Value};
+use serde::{Deserialize, Serialize};
+use thiserror::capture;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::process;
+use tokio::time;
+use uuid::Uuid;
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+const DEFAULT_TIMEOUT: u64 = 10;
+
+const MAX_OUTPUT: usize = 8_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sandbox {
+    pub enabled: bool,
+    pub python: String,
+    pub shell: String,
+    pub timeout: u64,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            python: "python3".to_owned(),
+            shell: "sh".to_owned(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl Sandbox {
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut sandbox = decode::map(value)?;
+        let default = Self::default();
+
+        Ok(Self {
+            enabled: sandbox
+                .optional("enabled", decode::bool)?
+                .unwrap_or(default.enabled),
+            python: sandbox
+                .optional("python", decode::string)?
+                .unwrap_or(default.python),
+            shell: sandbox
+                .optional("shell", decode::string)?
+                .unwrap_or(default.shell),
+            timeout: sandbox
+                .optional("timeout", decode::u64)?
+                .unwrap_or(default.timeout),
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        encode::map([
+            ("enabled", encode::bool(self.enabled)),
+            ("python", encode::string(&self.python)),
+            ("shell", encode::string(&self.shell)),
+            ("timeout", encode::u64(self.timeout)),
+        ])
+        .into_value()
+    }
+
+    fn interpreter(&self, language: Language) -> (&str, Vec<&str>) {
+        let (interpreter, stdin) = match language {
+            Language::Python => (&self.python, "-"),
+            Language::Shell => (&self.shell, "-s"),
+        };
+
+        let mut parts = interpreter.split_whitespace();
+        let program = parts.next().unwrap_or_default();
+
+        (program, parts.chain([stdin]).collect())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    Python,
+    Shell,
+}
+
+impl Language {
+    pub fn detect(tag: &str) -> Option<Self> {
+        match tag.trim().to_lowercase().as_str() {
+            "python" | "python3" | "py" => Some(Self::Python),
+            "sh" | "shell" | "bash" | "zsh" => Some(Self::Shell),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Python => "python",
+            Self::Shell => "sh",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Output {
+    pub language: Language,
+    pub code: String,
+    pub stdout: String,
+    pub stderr: String,
+    pub status: Option<i32>,
+    pub timed_out: bool,
+}
+
+impl Output {
+    pub fn is_success(&self) -> bool {
+        !self.timed_out && self.status == Some(0)
+    }
+
+    pub fn summary(&self) -> String {
+        if self.timed_out {
+            "Timed out".to_owned()
+        } else {
+            match self.status {
+                Some(code) => format!("Exited with status {code}"),
+                None => "Terminated by signal".to_owned(),
+            }
+        }
+    }
+
+    pub fn to_prompt(&self) -> String {
+        let mut prompt = format!(
+            "I ran this {language} code:\n\n```{language}\n{code}\n```\n\n{summary}.",
+            language = self.language.name(),
+            code = self.code.trim_end(),
+            summary = self.summary(),
+        );
+
+        for (name, stream) in [("stdout", &self.stdout), ("stderr", &self.stderr)] {
+            if stream.trim().is_empty() {
+                continue;
+            }
+
+            prompt.push_str(&format!("\n\n{name}:\n```\n{}\n```", truncate(stream)));
+        }
+
+        prompt
+    }
+}
+
+// The code can only write to its directory, and the home folder is hidden
+pub async fn run(sandbox: Sandbox, language: Language, code: String) -> Result<Output, Error> {
+    if !sandbox.enabled {
+        return Err(Error::SandboxUnavailable(
+            "code execution is disabled",
+            capture!(),
+        ));
+    }
+
+    let (program, arguments) = sandbox.interpreter(language);
+
+    let workspace = env::temp_dir().join(format!("icebreaker-{}", Uuid::new_v4().simple()));
+    fs::create_dir_all(&workspace).await?;
+
+    let mut command = match isolated(program, &arguments, &workspace).await {
+        Ok(command) => command,
+        Err(error) => {
+            let _ = fs::remove_dir_all(&workspace).await;
+
+            return Err(error);
+        }
+    };
+
+    let _ = command
+        .current_dir(&workspace)
+        .env_clear()
+        .env("PATH", env::var_os("PATH").unwrap_or_default())
+        .env("HOME", &workspace)
+        .env("TMPDIR", &workspace)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let result = async {
+        let mut child = command.spawn()?;
+        let stdin = child.stdin.take();
+        let input = code.as_bytes();
+
+        // The program may never read its input, so writing it is timed too
+        let run = async move {
+            if let Some(mut stdin) = stdin {
+                let write = async move {
+                    let _ = stdin.write_all(input).await;
+                };
+
+                let ((), output) = futures::join!(write, child.wait_with_output());
+
+                output
+            } else {
+                child.wait_with_output().await
+            }
+        };
+
+        let timeout = Duration::from_secs(sandbox.timeout.max(1));
+
+        match time::timeout(timeout, run).await {
+            Ok(output) => Ok::<_, Error>(Some(output?)),
+            Err(_elapsed) => Ok(None),
+        }
+    }
+    .await;
+
+    let _ = fs::remove_dir_all(&workspace).await;
+
+    let output = match result? {
+        Some(output) => Output {
+            language,
+            code,
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            status: output.status.code(),
+            timed_out: false,
+        },
+        None => Output {
+            language,
+            code,
+            stdout: String::new(),
+            stderr: String::new(),
+            status: None,
+            timed_out: true,
+        },
+    };
+
+    Ok(output)
+}
+
+fn private() -> Vec<PathBuf> {
+    let mut folders: Vec<PathBuf> = ["/home", "/root", "/Users"].map(PathBuf::from).into();
+    folders.extend(env::var_os("HOME").map(PathBuf::from));
+
+    folders.retain(|folder| folder.is_dir());
+    folders
+}
+
+#[cfg(target_os = "linux")]
+async fn isolated(
+    program: &str,
+    arguments: &[&str],
+    workspace: &Path,
+) -> Result<process::Command, Error> {
+    let is_installed = process::Command::new("bwrap")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .is_ok_and(|status| status.success());
+
+    if !is_installed {
+        return Err(Error::SandboxUnavailable(
+            "bubblewrap (bwrap) is not installed",
+            capture!(),
+        ));
+    }
+
+    // Everything is read-only, except for the workspace; a fresh network
+    // namespace only has an unconfigured loopback device
+    let mut command = process::Command::new("bwrap");
+    let _ = command
+        .args(["--ro-bind", "/", "/", "--dev", "/dev", "--proc", "/proc"])
+        .args(["--unshare-net", "--unshare-pid", "--die-with-parent"]);
+
+    for folder in private() {
+        let _ = command.arg("--tmpfs").arg(folder);
+    }
+
+    let _ = command
+        .arg("--tmpfs")
+        .arg(env::temp_dir())
+        .arg("--bind")
+        .arg(workspace)
+        .arg(workspace)
+        .arg("--")
+        .arg(program)
+        .args(arguments);
+
+    Ok(command)
+}
+
+#[cfg(target_os = "macos")]
+async fn isolated(
+    program: &str,
+    arguments: &[&str],
+    workspace: &Path,
+) -> Result<process::Command, Error> {
+    // The sandbox matches the real paths, past any links like /var
+    let literal = |path: &Path| {
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+        format!(
+            "\"{}\"",
+            path.display()
+                .to_string()
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+        )
+    };
+
+    // The last matching rule wins
+    let mut profile = "(version 1)(allow default)(deny network*)".to_owned();
+
+    for folder in private() {
+        profile.push_str(&format!(
+            "(deny file-read* file-write* (subpath {}))",
+            literal(&folder)
+        ));
+    }
+
+    profile.push_str(&format!(
+        "(deny file-write*)\
+        (allow file-write* (subpath {workspace}) (literal \"/dev/null\") (literal \"/dev/tty\"))\
+        (allow file-read* (subpath {workspace}))",
+        workspace = literal(workspace)
+    ));
+
+    let mut command = process::Command::new("sandbox-exec");
+    let _ = command.arg("-p").arg(profile).arg(program).args(arguments);
+
+    Ok(command)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+async fn isolated(
+    _program: &str,
+    _arguments: &[&str],
+    _workspace: &Path,
+) -> Result<process::Command, Error> {
+    Err(Error::SandboxUnavailable(
+        "isolation is not supported on this platform",
+        capture!(),
+    ))
+}
+
+fn truncate(output: &str) -> String {
+    if output.chars().count() <= MAX_OUTPUT {
+        return output.trim_end().to_owned();
+    }
+
+    format!(
+        "{}\n[output truncated]",
+        output.chars().take(MAX_OUTPUT).collect::<String>()
+    )
+}
@@ -0,0 +1,63 @@
+pub mod html;
+pub mod json;
+pub mod notebook;
+pub mod pdf;
+
+use crate::Error;
+use crate::Url;
+
+use regex::Regex;
+use thiserror::capture;
+
+use std::sync::LazyLock;
+
+pub fn redact(text: &str) -> String {
+    static PATTERNS: LazyLock<Vec<(Regex, &'static str)>> = LazyLock::new(|| {
+        [
+            (r"\b(sk|pk|rk)-[A-Za-z0-9_\-]{16,}", "[REDACTED KEY]"),
+            (r"\bhf_[A-Za-z0-9]{16,}", "[REDACTED TOKEN]"),
+            (
+                r"(?i)\bbearer\s+[A-Za-z0-9_\-\.=]{16,}",
+                "Bearer [REDACTED]",
+            ),
+            (
+                r"(?i)\b(api[_-]?key|token|secret|password)\s*[:=]\s*\S+",
+                "$1=[REDACTED]",
+            ),
+            (r"[\w.+\-]+@[\w\-]+\.[\w.\-]+", "[REDACTED EMAIL]"),
+        ]
+        .into_iter()
+        .map(|(pattern, replacement)| (Regex::new(pattern).expect("Valid pattern"), replacement))
+        .collect()
+    });
+
+    let mut text = PATTERNS
+        .iter()
+        .fold(text.to_owned(), |text, (pattern, replacement)| {
+            pattern.replace_all(&text, *replacement).into_owned()
+        });
+
+    if let Some(directories) = directories::BaseDirs::new() {
+        let home = directories.home_dir().to_string_lossy();
+
+        if !home.is_empty() {
+            text = text.replace(home.as_ref(), "~");
+        }
+    }
+
+    text
+}
+
+pub async fn paste(endpoint: Url, content: String) -> Result<Url, Error> {
+    let response = reqwest::Client::new()
+        .post(endpoint)
+        .header(reqwest::header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .body(content)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    Url::parse(response.trim()).map_err(|_| Error::InvalidResponse("paste endpoint", capture!()))
+}
@@ -0,0 +1,302 @@
+use crate::chat::{Chat, Item};
+use crate::export;
+use crate::plan;
+use crate::{Error, Url};
+
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::SyntaxSet;
+use tokio::fs;
+use tokio::task;
+
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+pub async fn save(chat: Chat, path: PathBuf) -> Result<(), Error> {
+    let html = task::spawn_blocking(move || render(&chat, true)).await?;
+
+    fs::write(path, html).await?;
+
+    Ok(())
+}
+
+pub async fn paste(chat: Chat, endpoint: Url) -> Result<Url, Error> {
+    let html = task::spawn_blocking(move || render(&chat, true)).await?;
+
+    export::paste(endpoint, html).await
+}
+
+pub fn render(chat: &Chat, redact: bool) -> String {
+    let sanitize = |content: &str| {
+        if redact {
+            export::redact(content)
+        } else {
+            content.to_owned()
+        }
+    };
+
+    let model = chat.file.slash_id();
+    let title = chat.title.as_deref().unwrap_or(model.name());
+
+    let variant = chat
+        .file
+        .file
+        .as_ref()
+        .and_then(|file| file.variant())
+        .map(str::to_owned)
+        .or_else(|| {
+            chat.file
+                .api
+                .as_ref()
+                .map(|api| format!("{:?}", api.config.kind))
+        })
+        .into_iter()
+        .chain(chat.persona.as_ref().map(|persona| persona.name.clone()))
+        .collect::<Vec<_>>()
+        .join(" · ");
+
+    let mut body = String::new();
+
+    for item in &chat.history {
+        match item {
+            Item::User(content) => {
+                let _ = write!(
+                    body,
+                    "<section class=\"user\">{}</section>",
+                    markdown(&sanitize(content))
+                );
+            }
+            Item::Reply(reply) => {
+                let _ = write!(body, "<section class=\"assistant\">");
+
+                if let Some(reasoning) = &reply.reasoning {
+                    let _ = write!(
+                        body,
+                        "<details><summary>Reasoned for {} seconds</summary>{}</details>",
+                        reasoning.duration.as_secs(),
+                        markdown(&sanitize(&reasoning.content))
+                    );
+                }
+
+                let _ = write!(body, "{}", markdown(&sanitize(&reply.content)));
+
+                if let Some(provenance) = &reply.provenance {
+                    let details = provenance
+                        .details()
+                        .into_iter()
+                        .map(|(label, value)| format!("{label}: {value}"))
+                        .collect::<Vec<_>>()
+                        .join(" · ");
+
+                    let _ = write!(body, "<p class=\"provenance\">{}</p>", escape(&details));
+                }
+
+                let _ = write!(body, "</section>");
+            }
+            Item::Plan(plan) => {
+                let _ = write!(body, "<section class=\"assistant\">");
+
+                for outcome in &plan.outcomes {
+                    match outcome {
+                        plan::Outcome::Search(plan::Status::Done(links)) => {
+                            let _ = write!(body, "<ul class=\"sources\">");
+
+                            for link in links {
+                                let _ = write!(
+                                    body,
+                                    "<li><a href=\"{url}\">{url}</a></li>",
+                                    url = escape(link.as_str())
+                                );
+                            }
+
+                            let _ = write!(body, "</ul>");
+                        }
+                        plan::Outcome::Answer(plan::Status::Done(reply)) => {
+                            let _ = write!(body, "{}", markdown(&sanitize(&reply.content)));
+                        }
+                        _ => {}
+                    }
+                }
+
+                let _ = write!(body, "</section>");
+            }
+            Item::Attachment(attachment) => {
+                if let Ok(url) = attachment.data_url() {
+                    let _ = write!(body, "<img class=\"attachment\" src=\"{url}\">");
+                }
+            }
+            Item::Execution(output) => {
+                let _ = write!(
+                    body,
+                    "<section class=\"user\">{}</section>",
+                    markdown(&sanitize(&output.to_prompt()))
+                );
+            }
+        }
+    }
+
+    format!(
+        "<!DOCTYPE html>\
+        <html lang=\"en\">\
+        <head>\
+        <meta charset=\"utf-8\">\
+        <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+        <meta name=\"generator\" content=\"Icebreaker\">\
+        <title>{title}</title>\
+        <style>{STYLE}</style>\
+        </head>\
+        <body>\
+        <header>\
+        <h1>{title}</h1>\
+        <p class=\"model\">{model} <span>{variant}</span></p>\
+        <p class=\"date\">Exported on {date}</p>\
+        </header>\
+        <main>{body}</main>\
+        </body>\
+        </html>",
+        title = escape(&sanitize(title)),
+        model = escape(&model.0),
+        variant = escape(&variant),
+        date = chrono::Local::now().format("%-e %B, %Y"),
+    )
+}
+
+fn safe(url: CowStr<'_>) -> CowStr<'_> {
+    let scheme: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .take_while(|c| *c != ':')
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    if url.contains(':') && matches!(scheme.as_str(), "javascript" | "data" | "vbscript") {
+        CowStr::Borrowed("#")
+    } else {
+        url
+    }
+}
+
+fn markdown(content: &str) -> String {
+    let mut code: Option<(String, String)> = None;
+
+    let events = Parser::new_ext(
+        content,
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS,
+    )
+    .filter_map(|event| match event {
+        Event::Start(Tag::CodeBlock(kind)) => {
+            let language = match kind {
+                CodeBlockKind::Fenced(language) => language.to_string(),
+                CodeBlockKind::Indented => String::new(),
+            };
+
+            code = Some((language, String::new()));
+
+            None
+        }
+        Event::Text(text) if code.is_some() => {
+            if let Some((_language, code)) = &mut code {
+                code.push_str(&text);
+            }
+
+            None
+        }
+        Event::End(TagEnd::CodeBlock) => {
+            let (language, code) = code.take()?;
+
+            Some(Event::Html(highlight(&code, &language).into()))
+        }
+        // Replies may contain anything, so raw HTML is shown as text
+        Event::Html(html) | Event::InlineHtml(html) => Some(Event::Text(html)),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Some(Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe(dest_url),
+            title,
+            id,
+        })),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Some(Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe(dest_url),
+            title,
+            id,
+        })),
+        event => Some(event),
+    });
+
+    let mut html = String::new();
+    html::push_html(&mut html, events);
+
+    html
+}
+
+fn highlight(code: &str, language: &str) -> String {
+    static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+    static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+    let syntax = SYNTAXES
+        .find_syntax_by_token(language)
+        .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+
+    syntect::html::highlighted_html_for_string(
+        code,
+        &SYNTAXES,
+        syntax,
+        &THEMES.themes["InspiredGitHub"],
+    )
+    .unwrap_or_else(|_| format!("<pre><code>{}</code></pre>", escape(code)))
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+const STYLE: &str = "\
+body { margin: 0; background: #f6f6f8; color: #1e1e2e; \
+font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif; line-height: 1.5; }\
+header, main { max-width: 720px; margin: 0 auto; padding: 20px; }\
+header { text-align: center; }\
+header h1 { margin-bottom: 5px; }\
+.model { font-family: monospace; color: #6c6f85; margin: 0; }\
+.model span { border: 1px solid #ccd0da; border-radius: 4px; padding: 1px 5px; }\
+.date { color: #9ca0b0; font-size: 12px; }\
+section { margin: 20px 0; }\
+section.user { margin-left: 60px; padding: 5px 15px; background: #e6e9ef; border-radius: 10px; }\
+details { font-size: 13px; color: #6c6f85; border-left: 2px solid #ccd0da; padding-left: 10px; }\
+pre { padding: 10px; border-radius: 5px; overflow-x: auto; font-size: 13px; }\
+table { border-collapse: collapse; }\
+th, td { border: 1px solid #ccd0da; padding: 4px 8px; }\
+.sources { font-family: monospace; font-size: 12px; }\
+.provenance { font-family: monospace; font-size: 11px; color: #9ca0b0; }\
+img.attachment { display: block; max-width: 240px; margin: 20px 0 -10px auto; border-radius: 10px; }\
+@media print { \
+body { background: none; -webkit-print-color-adjust: exact; print-color-adjust: exact; }\
+header, main { max-width: none; padding: 0; }\
+pre { white-space: pre-wrap; overflow-wrap: anywhere; overflow-x: visible; }\
+pre, img, tr, details { break-inside: avoid; }\
+a { color: inherit; }\
+}\
+";
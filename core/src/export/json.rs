@@ -0,0 +1,29 @@
+use crate::chat::Chat;
+use crate::Error;
+
+use tokio::fs;
+use tokio::task;
+
+use std::path::PathBuf;
+
+pub const EXTENSION: &str = "icebreaker.json";
+
+pub async fn save(chat: Chat, path: PathBuf) -> Result<(), Error> {
+    let json = task::spawn_blocking(move || chat.encode()).await??;
+
+    fs::write(path, json).await?;
+
+    Ok(())
+}
+
+pub async fn open(path: PathBuf) -> Result<Chat, Error> {
+    let bytes = fs::read(path).await?;
+
+    task::spawn_blocking(move || Chat::decode(&bytes)).await?
+}
+
+pub async fn import(path: PathBuf) -> Result<Chat, Error> {
+    let bytes = fs::read(path).await?;
+
+    Chat::import(bytes).await
+}
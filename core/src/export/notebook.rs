@@ -0,0 +1,178 @@
+use crate::chat::{Chat, Item};
+use crate::execution::{self, Language};
+use crate::plan;
+use crate::Error;
+
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+use serde_json::{json, Value};
+use tokio::fs;
+
+use std::path::PathBuf;
+
+pub async fn save(chat: Chat, path: PathBuf) -> Result<(), Error> {
+    let notebook = serde_json::to_string_pretty(&render(&chat))?;
+
+    fs::write(path, notebook).await?;
+
+    Ok(())
+}
+
+pub fn render(chat: &Chat) -> Value {
+    let mut cells = Vec::new();
+
+    if let Some(title) = &chat.title {
+        cells.push(Cell::Markdown(format!("# {title}")));
+    }
+
+    for item in &chat.history {
+        match item {
+            Item::User(content) => {
+                cells.push(Cell::Markdown(format!("**You:**\n\n{content}")));
+            }
+            Item::Reply(reply) => {
+                cells.extend(split(&reply.content));
+            }
+            Item::Plan(plan) => {
+                for outcome in &plan.outcomes {
+                    if let plan::Outcome::Answer(plan::Status::Done(reply)) = outcome {
+                        cells.extend(split(&reply.content));
+                    }
+                }
+            }
+            Item::Attachment(_) => {}
+            Item::Execution(output) => attach(&mut cells, output),
+        }
+    }
+
+    json!({
+        "cells": cells.iter().map(Cell::to_json).collect::<Vec<_>>(),
+        "metadata": {
+            "kernelspec": {
+                "display_name": "Python 3",
+                "language": "python",
+                "name": "python3"
+            },
+            "language_info": {
+                "name": "python"
+            },
+            "icebreaker": {
+                "model": chat.file.slash_id().0
+            }
+        },
+        "nbformat": 4,
+        "nbformat_minor": 5
+    })
+}
+
+enum Cell {
+    Markdown(String),
+    Code {
+        source: String,
+        outputs: Vec<execution::Output>,
+    },
+}
+
+impl Cell {
+    fn to_json(&self) -> Value {
+        match self {
+            Cell::Markdown(source) => json!({
+                "cell_type": "markdown",
+                "metadata": {},
+                "source": lines(source),
+            }),
+            Cell::Code { source, outputs } => {
+                let outputs: Vec<_> = outputs
+                    .iter()
+                    .flat_map(|output| {
+                        [("stdout", &output.stdout), ("stderr", &output.stderr)]
+                            .into_iter()
+                            .filter(|(_, text)| !text.is_empty())
+                            .map(|(name, text)| {
+                                json!({
+                                    "output_type": "stream",
+                                    "name": name,
+                                    "text": lines(text),
+                                })
+                            })
+                    })
+                    .collect();
+
+                json!({
+                    "cell_type": "code",
+                    "execution_count": null,
+                    "metadata": {},
+                    "source": lines(source),
+                    "outputs": outputs,
+                })
+            }
+        }
+    }
+}
+
+fn split(markdown: &str) -> Vec<Cell> {
+    let mut cells = Vec::new();
+    let mut prose_start = 0;
+    let mut code: Option<(usize, String)> = None;
+
+    let push_prose = |cells: &mut Vec<Cell>, prose: &str| {
+        let prose = prose.trim();
+
+        if !prose.is_empty() {
+            cells.push(Cell::Markdown(prose.to_owned()));
+        }
+    };
+
+    for (event, range) in Parser::new(markdown).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(language)))
+                if Language::detect(&language) == Some(Language::Python) =>
+            {
+                push_prose(&mut cells, &markdown[prose_start..range.start]);
+
+                code = Some((range.end, String::new()));
+            }
+            Event::Text(text) => {
+                if let Some((_, source)) = &mut code {
+                    source.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((end, source)) = code.take() {
+                    cells.push(Cell::Code {
+                        source: source.trim_end().to_owned(),
+                        outputs: Vec::new(),
+                    });
+
+                    prose_start = end;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    push_prose(&mut cells, &markdown[prose_start..]);
+
+    cells
+}
+
+fn attach(cells: &mut Vec<Cell>, output: &execution::Output) {
+    let code = output.code.trim_end();
+
+    let existing = cells.iter_mut().rev().find_map(|cell| match cell {
+        Cell::Code { source, outputs } if source == code => Some(outputs),
+        _ => None,
+    });
+
+    match existing {
+        Some(outputs) => outputs.push(output.clone()),
+        None if output.language == Language::Python => cells.push(Cell::Code {
+            source: code.to_owned(),
+            outputs: vec![output.clone()],
+        }),
+        None => cells.push(Cell::Markdown(output.to_prompt())),
+    }
+}
+
+fn lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
@@ -0,0 +1,90 @@
+use crate::chat::Chat;
+use crate::export::html;
+use crate::{Error, Url};
+
+use thiserror::capture;
+use tokio::fs;
+use tokio::process;
+use tokio::task;
+use tokio::time;
+use uuid::Uuid;
+
+use std::env;
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+const BROWSERS: &[&str] = &[
+    "chromium",
+    "chromium-browser",
+    "google-chrome",
+    "google-chrome-stable",
+    "microsoft-edge",
+    "msedge",
+    "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+    "/Applications/Chromium.app/Contents/MacOS/Chromium",
+];
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+pub async fn save(chat: Chat, path: PathBuf) -> Result<(), Error> {
+    let html = task::spawn_blocking(move || html::render(&chat, true)).await?;
+
+    let page = env::temp_dir().join(format!("icebreaker-{}.html", Uuid::new_v4().simple()));
+    fs::write(&page, html).await?;
+
+    let result = time::timeout(TIMEOUT, print(&page, &path)).await;
+    let _ = fs::remove_file(&page).await;
+
+    result.map_err(|_| Error::TimedOut("PDF export", capture!()))?
+}
+
+async fn print(page: &Path, output: &Path) -> Result<(), Error> {
+    let url = Url::from_file_path(page)
+        .map_err(|_| Error::PdfFailed("invalid temporary path".to_owned(), capture!()))?;
+
+    let mut print_to_pdf = OsString::from("--print-to-pdf=");
+    print_to_pdf.push(output);
+
+    for browser in BROWSERS {
+        let result = process::Command::new(browser)
+            .arg("--headless")
+            .arg("--disable-gpu")
+            .arg("--no-pdf-header-footer")
+            .arg("--print-to-pdf-no-header")
+            .arg(&print_to_pdf)
+            .arg(url.as_str())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .output()
+            .await;
+
+        let result = match result {
+            Ok(result) => result,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(error.into()),
+        };
+
+        if !result.status.success() || !fs::try_exists(output).await? {
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            let reason = stderr
+                .lines()
+                .rev()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or("the browser exited with an error");
+
+            return Err(Error::PdfFailed(reason.trim().to_owned(), capture!()));
+        }
+
+        return Ok(());
+    }
+
+    Err(Error::PdfFailed(
+        "Chrome, Chromium or Edge must be installed".to_owned(),
+        capture!(),
+    ))
+}
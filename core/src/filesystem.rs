@@ -0,0 +1,237 @@
+use crate::directory;
+
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use url::Url;
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+static ROOTS: RwLock<Vec<PathBuf>> = RwLock::new(Vec::new());
+
+const MAX_READ: usize = 32_000;
+
+const MAX_ENTRIES: usize = 500;
+
+pub(crate) const READ_FILE: &str = "read_file";
+pub(crate) const LIST_DIRECTORY: &str = "list_directory";
+
+pub fn configure(roots: Vec<PathBuf>) {
+    if let Ok(mut configuration) = ROOTS.write() {
+        *configuration = roots;
+    }
+}
+
+fn roots() -> Vec<PathBuf> {
+    ROOTS.read().map(|roots| roots.clone()).unwrap_or_default()
+}
+
+pub(crate) fn is_tool(name: &str) -> bool {
+    (name == READ_FILE || name == LIST_DIRECTORY) && !roots().is_empty()
+}
+
+pub fn access_log() -> PathBuf {
+    directory::data().join("file_access.log")
+}
+
+pub(crate) fn description() -> String {
+    let roots = roots();
+
+    if roots.is_empty() {
+        return String::new();
+    }
+
+    let roots = roots
+        .iter()
+        .map(|root| root.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "\n- {READ_FILE}: Read a text file of the user. The input is the path of the file.\
+        \n- {LIST_DIRECTORY}: List the files and folders in a directory of the user. \
+        The input is the path of the directory.\
+        \nOnly paths inside these directories can be accessed: {roots}"
+    )
+}
+
+pub(crate) async fn call(tool: &str, inputs: &[String]) -> (Url, String) {
+    let Some(input) = inputs.first() else {
+        return (root_url(), "A path is needed.".to_owned());
+    };
+
+    let (path, result) = match resolve(input.trim()).await {
+        Ok(path) => {
+            let result = if tool == READ_FILE {
+                read(&path).await
+            } else {
+                list(&path).await
+            };
+
+            (path, result)
+        }
+        Err(error) => (PathBuf::from(input.trim()), Err(error)),
+    };
+
+    record(tool, &path, result.as_ref().err()).await;
+
+    let url = Url::from_file_path(&path).unwrap_or_else(|()| root_url());
+
+    (url, result.unwrap_or_else(|error| error))
+}
+
+fn root_url() -> Url {
+    Url::parse("file:///").expect("Valid URL")
+}
+
+async fn resolve(input: &str) -> Result<PathBuf, String> {
+    // Whether files outside of the roots exist is not revealed either
+    let denied = || format!("Access denied: {input} is not in the approved directories.");
+
+    let roots = roots();
+    let path = Path::new(input);
+
+    let candidates: Vec<PathBuf> = if path.is_absolute() {
+        let mut is_inside = roots.iter().any(|root| path.starts_with(root));
+
+        for root in &roots {
+            if let Ok(root) = fs::canonicalize(root).await {
+                is_inside |= path.starts_with(root);
+            }
+        }
+
+        if !is_inside {
+            return Err(denied());
+        }
+
+        vec![path.to_path_buf()]
+    } else {
+        roots.iter().map(|root| root.join(path)).collect()
+    };
+
+    for candidate in candidates {
+        let Ok(candidate) = fs::canonicalize(&candidate).await else {
+            continue;
+        };
+
+        for root in &roots {
+            let Ok(root) = fs::canonicalize(root).await else {
+                continue;
+            };
+
+            if candidate.starts_with(&root) {
+                return Ok(candidate);
+            }
+        }
+    }
+
+    Err(denied())
+}
+
+async fn read(path: &Path) -> Result<String, String> {
+    let metadata = fs::metadata(path)
+        .await
+        .map_err(|error| error.to_string())?;
+
+    // Devices and pipes may never end
+    if !metadata.is_file() {
+        return Err(format!("{} is not a file.", path.display()));
+    }
+
+    let file = fs::File::open(path)
+        .await
+        .map_err(|error| error.to_string())?;
+
+    // One byte more than the limit tells whether the file is longer
+    let mut bytes = Vec::new();
+    let _ = file
+        .take(MAX_READ as u64 + 1)
+        .read_to_end(&mut bytes)
+        .await
+        .map_err(|error| error.to_string())?;
+
+    if bytes.contains(&0) {
+        return Err(format!("{} is not a text file.", path.display()));
+    }
+
+    let content = String::from_utf8_lossy(&bytes);
+
+    if content.len() > MAX_READ {
+        let mut end = MAX_READ;
+
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        Ok(format!("{}\n[file truncated]", &content[..end]))
+    } else {
+        Ok(content.into_owned())
+    }
+}
+
+async fn list(path: &Path) -> Result<String, String> {
+    let mut entries = fs::read_dir(path)
+        .await
+        .map_err(|error| error.to_string())?;
+
+    let mut lines = Vec::new();
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        let line = match entry.metadata().await {
+            Ok(metadata) if metadata.is_dir() => format!("{name}/"),
+            Ok(metadata) => format!("{name} ({} bytes)", metadata.len()),
+            Err(_) => name,
+        };
+
+        lines.push(line);
+    }
+
+    lines.sort();
+
+    if lines.len() > MAX_ENTRIES {
+        let rest = lines.len() - MAX_ENTRIES;
+
+        lines.truncate(MAX_ENTRIES);
+        lines.push(format!("[{rest} more entries]"));
+    }
+
+    if lines.is_empty() {
+        return Ok(format!("{} is empty.", path.display()));
+    }
+
+    Ok(lines.join("\n"))
+}
+
+async fn record(tool: &str, path: &Path, error: Option<&String>) {
+    let line = format!(
+        "{} {tool} {} {}\n",
+        chrono::Local::now().to_rfc3339(),
+        path.display(),
+        error.map_or("ok", String::as_str),
+    );
+
+    log::info!("File access: {}", line.trim_end());
+
+    let path = access_log();
+
+    if let Some(directory) = path.parent() {
+        let _ = fs::create_dir_all(directory).await;
+    }
+
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .await;
+
+    let result = match file {
+        Ok(mut file) => file.write_all(line.as_bytes()).await,
+        Err(error) => Err(error),
+    };
+
+    if let Err(error) = result {
+        log::warn!("File access could not be recorded: {error}");
+    }
+}
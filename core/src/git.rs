@@ -0,0 +1,120 @@
+use crate::Error;
+
+use thiserror::capture;
+use tokio::process;
+
+use std::path::{Path, PathBuf};
+
+const MAX_DIFF: usize = 40_000;
+
+#[derive(Debug, Clone)]
+pub struct Repository {
+    root: PathBuf,
+    branch: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Changes {
+    Staged,
+    Uncommitted,
+}
+
+impl Repository {
+    pub async fn open(path: PathBuf) -> Result<Self, Error> {
+        let root = git(&path, &["rev-parse", "--show-toplevel"]).await?;
+        let root = PathBuf::from(root.trim());
+
+        let branch = git(&root, &["rev-parse", "--abbrev-ref", "HEAD"])
+            .await
+            .map(|branch| branch.trim().to_owned())
+            .unwrap_or_default();
+
+        Ok(Self { root, branch })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn name(&self) -> String {
+        self.root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.root.display().to_string())
+    }
+
+    pub fn branch(&self) -> &str {
+        &self.branch
+    }
+
+    pub async fn diff(self, changes: Changes) -> Result<String, Error> {
+        let arguments: &[&str] = match changes {
+            Changes::Staged => &["diff", "--staged", "--no-color", "--no-ext-diff"],
+            Changes::Uncommitted => &["diff", "HEAD", "--no-color", "--no-ext-diff"],
+        };
+
+        git(&self.root, arguments).await
+    }
+
+    pub async fn log(self, limit: usize) -> Result<String, Error> {
+        git(
+            &self.root,
+            &[
+                "log",
+                "--no-color",
+                &format!("--max-count={limit}"),
+                "--format=%h %s",
+            ],
+        )
+        .await
+    }
+}
+
+impl Changes {
+    pub fn prompt(self, diff: &str, log: &str) -> String {
+        let diff = if diff.chars().count() > MAX_DIFF {
+            format!(
+                "{}\n[diff truncated]",
+                diff.chars().take(MAX_DIFF).collect::<String>()
+            )
+        } else {
+            diff.trim_end().to_owned()
+        };
+
+        match self {
+            Changes::Staged => format!(
+                "Write a commit message for these staged changes. \
+                Use a concise summary line of at most 72 characters, \
+                followed by a blank line and a short explanation of what changed and why. \
+                Match the style of the recent commits.\n\n\
+                Recent commits:\n```\n{log}\n```\n\n\
+                Staged changes:\n```diff\n{diff}\n```",
+                log = log.trim_end(),
+            ),
+            Changes::Uncommitted => format!(
+                "Review this diff like an experienced maintainer would. \
+                Point out bugs, risky changes, missing tests, and unclear code, \
+                referring to files and lines. Finish with a short overall verdict.\n\n\
+                ```diff\n{diff}\n```"
+            ),
+        }
+    }
+}
+
+async fn git(directory: &Path, arguments: &[&str]) -> Result<String, Error> {
+    let output = process::Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(arguments)
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        log::warn!("git failed: {}", String::from_utf8_lossy(&output.stderr));
+
+        return Err(Error::GitFailed("git command was unsuccessful", capture!()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
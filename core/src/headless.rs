@@ -0,0 +1,243 @@
+use crate::assistant::{Backend, BootEvent, Reply};
+use crate::chat::{self, Item, Strategy};
+use crate::model::{self, Change, EndpointId, FileOrAPI, ScanProgress, Store};
+use crate::persona::Persona;
+use crate::request;
+use crate::{Assistant, Chat, Error, Plan, Settings};
+
+use futures::Stream;
+use sipper::{sipper, Sipper, Straw};
+use tokio::sync::Semaphore;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const PARALLEL_DOWNLOADS: usize = 2;
+
+#[derive(Clone)]
+pub struct ModelManager {
+    store: Store,
+    settings: Settings,
+}
+
+impl ModelManager {
+    pub async fn open(settings: Settings) -> Result<Self, Error> {
+        let manager = Self {
+            store: Store::default(),
+            settings,
+        };
+
+        manager.rescan().await?;
+
+        Ok(manager)
+    }
+
+    pub fn library(&self) -> Arc<model::Library> {
+        self.store.snapshot()
+    }
+
+    pub fn files(&self) -> Vec<FileOrAPI> {
+        self.library().files.values().cloned().collect()
+    }
+
+    pub fn rescan(&self) -> impl Straw<(), ScanProgress, Error> {
+        self.store.scan(self.settings.clone())
+    }
+
+    pub fn changes(&self) -> impl Stream<Item = Change> {
+        self.store.changes()
+    }
+
+    pub fn boot(
+        &self,
+        file: model::FileAndAPI,
+        backend: Backend,
+    ) -> impl Straw<Assistant, BootEvent, Error> {
+        Assistant::boot(model::Library::clone(&self.library()), file, backend)
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+}
+
+#[derive(Clone)]
+pub struct DownloadManager {
+    models: ModelManager,
+    slots: Arc<Semaphore>,
+}
+
+impl DownloadManager {
+    pub fn new(models: ModelManager) -> Self {
+        Self::with_parallelism(models, PARALLEL_DOWNLOADS)
+    }
+
+    pub fn with_parallelism(models: ModelManager, downloads: usize) -> Self {
+        Self {
+            models,
+            slots: Arc::new(Semaphore::new(downloads.max(1))),
+        }
+    }
+
+    pub fn download(&self, file: model::File) -> impl Straw<PathBuf, request::Progress, Error> {
+        let manager = self.clone();
+
+        sipper(move |sender| async move {
+            // The semaphore is never closed, so a slot is always granted
+            let _slot = manager.slots.acquire().await;
+
+            let library = manager.models.library();
+            let path = file.download(library.directory_of(&file), sender).await?;
+
+            manager.models.store.update(Change::Files, |library| {
+                let _ = Arc::make_mut(&mut library.files)
+                    .insert(EndpointId::Local(file.model.clone()), FileOrAPI::File(file));
+            });
+
+            Ok(path)
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct ChatSession {
+    assistant: Assistant,
+    id: Option<chat::Id>,
+    title: Option<String>,
+    history: Vec<Item>,
+    strategy: Strategy,
+    persona: Option<Persona>,
+}
+
+impl ChatSession {
+    pub fn new(assistant: Assistant) -> Self {
+        Self {
+            assistant,
+            id: None,
+            title: None,
+            history: Vec::new(),
+            strategy: Strategy::default(),
+            persona: None,
+        }
+    }
+
+    pub async fn resume(assistant: Assistant, id: chat::Id) -> Result<Self, Error> {
+        let chat = Chat::fetch(id).await?;
+
+        Ok(Self {
+            assistant,
+            id: Some(chat.id),
+            title: chat.title,
+            history: chat.history,
+            strategy: Strategy::default(),
+            persona: chat.persona,
+        })
+    }
+
+    pub fn with_strategy(self, strategy: Strategy) -> Self {
+        Self { strategy, ..self }
+    }
+
+    pub fn with_persona(self, persona: Persona) -> Self {
+        Self {
+            persona: Some(persona),
+            ..self
+        }
+    }
+
+    pub fn with_title(self, title: impl Into<String>) -> Self {
+        Self {
+            title: Some(title.into()),
+            ..self
+        }
+    }
+
+    pub fn id(&self) -> Option<chat::Id> {
+        self.id
+    }
+
+    pub fn history(&self) -> &[Item] {
+        &self.history
+    }
+
+    pub fn last_reply(&self) -> Option<&Reply> {
+        self.history.iter().rev().find_map(|item| match item {
+            Item::Reply(reply) => Some(reply),
+            _ => None,
+        })
+    }
+
+    pub fn send(&mut self, message: impl Into<String>) -> impl Straw<(), chat::Event, Error> + '_ {
+        self.history.push(Item::User(message.into()));
+
+        let completion = chat::complete(
+            &self.assistant,
+            &self.history,
+            self.strategy.clone(),
+            self.persona.as_ref(),
+            None,
+        );
+
+        sipper(move |mut sender| async move {
+            let mut completion = completion.pin();
+
+            while let Some(event) = completion.sip().await {
+                self.apply(event.clone());
+                sender.send(event).await;
+            }
+
+            completion.await
+        })
+    }
+
+    pub async fn save(&mut self) -> Result<chat::Id, Error> {
+        let chat = match self.id {
+            Some(id) => {
+                let mut chat = Chat::fetch(id).await?;
+                chat.title = self.title.clone();
+                chat.history = self.history.clone();
+                chat.persona = self.persona.clone();
+
+                chat.save().await?
+            }
+            None => {
+                Chat::create(
+                    self.assistant.file.clone(),
+                    self.title.clone(),
+                    self.history.clone(),
+                    self.persona.clone(),
+                    None,
+                    Vec::new(),
+                    Vec::new(),
+                )
+                .await?
+            }
+        };
+
+        self.id = Some(chat.id);
+
+        Ok(chat.id)
+    }
+
+    fn apply(&mut self, event: chat::Event) {
+        match event {
+            chat::Event::ContextFitted(_) => {}
+            chat::Event::ReplyAdded => {
+                self.history.push(Item::Reply(Reply::default()));
+            }
+            chat::Event::ReplyChanged(new_reply) => {
+                if let Some(Item::Reply(reply)) = self.history.last_mut() {
+                    *reply = new_reply;
+                }
+            }
+            chat::Event::PlanAdded => {
+                self.history.push(Item::Plan(Plan::default()));
+            }
+            chat::Event::PlanChanged(event) => {
+                if let Some(Item::Plan(plan)) = self.history.last_mut() {
+                    plan.apply(event);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use crate::chat::{self, Chat};
+use crate::Error;
+
+use decoder::{decode, encode, Value};
+use serde_json::json;
+use thiserror::capture;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process;
+use tokio::time;
+
+use std::fmt;
+use std::process::Stdio;
+use std::sync::RwLock;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+static CONFIGURATION: RwLock<Vec<Hook>> = RwLock::new(Vec::new());
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hook {
+    pub event: Event,
+    pub command: String,
+    pub inject: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    ChatCreated,
+    ChatSaved,
+    MessageReceived,
+    DownloadFinished,
+}
+
+impl Event {
+    pub const ALL: &[Self] = &[
+        Self::ChatCreated,
+        Self::ChatSaved,
+        Self::MessageReceived,
+        Self::DownloadFinished,
+    ];
+
+    pub fn can_inject(self) -> bool {
+        matches!(self, Self::MessageReceived)
+    }
+
+    fn slug(self) -> &'static str {
+        match self {
+            Self::ChatCreated => "chat_created",
+            Self::ChatSaved => "chat_saved",
+            Self::MessageReceived => "message_received",
+            Self::DownloadFinished => "download_finished",
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ChatCreated => "Chat created",
+            Self::ChatSaved => "Chat saved",
+            Self::MessageReceived => "Message received",
+            Self::DownloadFinished => "Download finished",
+        })
+    }
+}
+
+impl Hook {
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut hook = decode::map(value)?;
+
+        let event = hook.required("event", decode::string)?;
+
+        Ok(Self {
+            event: Event::ALL
+                .iter()
+                .copied()
+                .find(|candidate| candidate.slug() == event)
+                .ok_or_else(|| decoder::Error::custom(format!("unknown event: {event}")))?,
+            command: hook.required("command", decode::string)?,
+            inject: hook.optional("inject", decode::bool)?.unwrap_or_default(),
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        encode::map([
+            ("event", encode::string(self.event.slug())),
+            ("command", encode::string(&self.command)),
+            ("inject", encode::bool(self.inject)),
+        ])
+        .into_value()
+    }
+}
+
+pub fn configure(hooks: Vec<Hook>) {
+    if let Ok(mut configuration) = CONFIGURATION.write() {
+        *configuration = hooks;
+    }
+}
+
+pub fn injects(event: Event) -> bool {
+    event.can_inject() && hooks(event).iter().any(|hook| hook.inject)
+}
+
+pub fn message(chat: Option<chat::Id>, content: &str) -> serde_json::Value {
+    json!({ "chat": chat, "content": content })
+}
+
+pub(crate) fn chat(chat: &Chat) -> serde_json::Value {
+    json!({
+        "chat": chat.id,
+        "title": chat.title,
+        "model": chat.file.slash_id().0,
+    })
+}
+
+pub fn fire(event: Event, payload: serde_json::Value) {
+    let hooks = hooks(event);
+
+    if hooks.is_empty() {
+        return;
+    }
+
+    let _ = tokio::spawn(async move {
+        for hook in hooks {
+            if let Err(error) = run(&hook, &payload).await {
+                log::warn!("Hook failed ({}): {error}", hook.command);
+            }
+        }
+    });
+}
+
+pub async fn collect(event: Event, payload: serde_json::Value) -> Result<Vec<String>, Error> {
+    let mut outputs = Vec::new();
+
+    for hook in hooks(event) {
+        let output = run(&hook, &payload).await?;
+
+        if hook.inject && event.can_inject() && !output.trim().is_empty() {
+            outputs.push(output.trim().to_owned());
+        }
+    }
+
+    Ok(outputs)
+}
+
+async fn run(hook: &Hook, payload: &serde_json::Value) -> Result<String, Error> {
+    let mut event = json!({ "event": hook.event.slug() });
+
+    if let (Some(event), Some(payload)) = (event.as_object_mut(), payload.as_object()) {
+        event.extend(payload.clone());
+    }
+
+    let mut child = shell(&hook.command)
+        .env("ICEBREAKER_EVENT", hook.event.slug())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let mut stdin = child.stdin.take().expect("piped stdin");
+    let mut stdout = child.stdout.take().expect("piped stdout");
+
+    let input = serde_json::to_vec(&event)?;
+    let mut output = String::new();
+
+    // The input is written while the output is read, since the command may
+    // not read its input at all, or only after writing
+    let result = time::timeout(TIMEOUT, async {
+        let write = async move {
+            let _ = stdin.write_all(&input).await;
+        };
+
+        let ((), read) = futures::join!(write, stdout.read_to_string(&mut output));
+        let _ = read?;
+
+        child.wait().await
+    })
+    .await;
+
+    let Ok(status) = result else {
+        return Err(Error::TimedOut("hook", capture!()));
+    };
+
+    if !status?.success() {
+        return Err(Error::HookFailed(hook.command.clone(), capture!()));
+    }
+
+    Ok(output)
+}
+
+fn hooks(event: Event) -> Vec<Hook> {
+    CONFIGURATION
+        .read()
+        .map(|hooks| {
+            hooks
+                .iter()
+                .filter(|hook| hook.event == event && !hook.command.trim().is_empty())
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> process::Command {
+    let mut shell = process::Command::new("cmd");
+    let _ = shell.arg("/C").arg(command);
+
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> process::Command {
+    let mut shell = process::Command::new("sh");
+    let _ = shell.arg("-c").arg(command);
+
+    shell
+}
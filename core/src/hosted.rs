@@ -0,0 +1,279 @@
+use crate::model::{APIAccess, APIType, Cost, EndpointId, Id, ModelOnline, Quantity};
+use crate::Error;
+
+use decoder::{decode, encode, Value};
+use serde::Deserialize;
+use thiserror::capture;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Keys {
+    pub groq: String,
+    pub together: String,
+    pub mistral: String,
+}
+
+impl Keys {
+    pub fn get(&self, kind: &APIType) -> Option<&str> {
+        let key = match kind {
+            APIType::Groq => &self.groq,
+            APIType::Together => &self.together,
+            APIType::Mistral => &self.mistral,
+            _ => return None,
+        };
+
+        Some(key.trim()).filter(|key| !key.is_empty())
+    }
+
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut keys = decode::map(value)?;
+
+        Ok(Self {
+            groq: keys.optional("groq", decode::string)?.unwrap_or_default(),
+            together: keys
+                .optional("together", decode::string)?
+                .unwrap_or_default(),
+            mistral: keys
+                .optional("mistral", decode::string)?
+                .unwrap_or_default(),
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        encode::map([
+            ("groq", encode::string(&self.groq)),
+            ("together", encode::string(&self.together)),
+            ("mistral", encode::string(&self.mistral)),
+        ])
+        .into_value()
+    }
+}
+
+// Prices are USD per 1M tokens and speeds tokens per second, from groq.com/pricing
+const GROQ_PRICING: &[(&str, f64, f64, u32)] = &[
+    ("llama-3.1-8b-instant", 0.05, 0.08, 840),
+    ("llama-3.3-70b-versatile", 0.59, 0.79, 394),
+    ("meta-llama/llama-4-scout-17b-16e-instruct", 0.11, 0.34, 594),
+    (
+        "meta-llama/llama-4-maverick-17b-128e-instruct",
+        0.20,
+        0.60,
+        562,
+    ),
+    ("qwen/qwen3-32b", 0.29, 0.59, 662),
+    ("moonshotai/kimi-k2-instruct", 1.00, 3.00, 200),
+    ("openai/gpt-oss-20b", 0.10, 0.50, 1000),
+    ("openai/gpt-oss-120b", 0.15, 0.75, 500),
+];
+
+pub async fn groq(api: &APIAccess) -> Result<Vec<ModelOnline>, Error> {
+    #[derive(Deserialize)]
+    struct Response {
+        data: Vec<Model>,
+    }
+
+    #[derive(Deserialize)]
+    struct Model {
+        id: String,
+        #[serde(default = "active")]
+        active: bool,
+    }
+
+    fn active() -> bool {
+        true
+    }
+
+    let response: Response = list(api).await?;
+
+    Ok(response
+        .data
+        .into_iter()
+        .filter(|model| model.active && is_chat(&model.id))
+        .map(|model| {
+            let pricing = GROQ_PRICING.iter().find(|(id, ..)| *id == model.id);
+
+            ModelOnline {
+                endpoint_id: EndpointId::Remote {
+                    api_type: APIType::Groq,
+                    id: Id(model.id),
+                },
+                cost: pricing.map(|(_, prompt, completion, _)| Cost {
+                    prompt: Quantity::usd_per_1m(*prompt),
+                    completion: Quantity::usd_per_1m(*completion),
+                }),
+                speed: pricing.map(|(.., speed)| *speed),
+                function_calling: false,
+                config: api.clone(),
+                state_check: Default::default(),
+            }
+        })
+        .collect())
+}
+
+pub async fn together(api: &APIAccess) -> Result<Vec<ModelOnline>, Error> {
+    #[derive(Deserialize)]
+    struct Model {
+        id: String,
+        #[serde(rename = "type", default)]
+        kind: String,
+        pricing: Option<Pricing>,
+    }
+
+    #[derive(Deserialize)]
+    struct Pricing {
+        input: f64,
+        output: f64,
+    }
+
+    let models: Vec<Model> = list(api).await?;
+
+    Ok(models
+        .into_iter()
+        .filter(|model| model.kind == "chat")
+        .map(|model| ModelOnline {
+            endpoint_id: EndpointId::Remote {
+                api_type: APIType::Together,
+                id: Id(model.id),
+            },
+            cost: model
+                .pricing
+                .filter(|pricing| pricing.input > 0.0 || pricing.output > 0.0)
+                .map(|pricing| Cost {
+                    prompt: Quantity::usd_per_1m(pricing.input),
+                    completion: Quantity::usd_per_1m(pricing.output),
+                }),
+            speed: None,
+            function_calling: false,
+            config: api.clone(),
+            state_check: Default::default(),
+        })
+        .collect())
+}
+
+pub async fn mistral(api: &APIAccess) -> Result<Vec<ModelOnline>, Error> {
+    #[derive(Deserialize)]
+    struct Response {
+        data: Vec<Model>,
+    }
+
+    #[derive(Deserialize)]
+    struct Model {
+        id: String,
+        #[serde(default)]
+        capabilities: Capabilities,
+        #[serde(default)]
+        deprecation: Option<String>,
+    }
+
+    #[derive(Deserialize, Default)]
+    struct Capabilities {
+        #[serde(default)]
+        completion_chat: bool,
+        #[serde(default)]
+        function_calling: bool,
+    }
+
+    let response: Response = list(api).await?;
+
+    Ok(response
+        .data
+        .into_iter()
+        .filter(|model| model.capabilities.completion_chat && model.deprecation.is_none())
+        .map(|model| ModelOnline {
+            endpoint_id: EndpointId::Remote {
+                api_type: APIType::Mistral,
+                id: Id(model.id),
+            },
+            cost: None,
+            speed: None,
+            function_calling: model.capabilities.function_calling,
+            config: api.clone(),
+            state_check: Default::default(),
+        })
+        .collect())
+}
+
+pub async fn mistral_embeddings(api_key: &str, inputs: &[String]) -> Result<Vec<Vec<f32>>, Error> {
+    #[derive(Deserialize)]
+    struct Response {
+        data: Vec<Embedding>,
+    }
+
+    #[derive(Deserialize)]
+    struct Embedding {
+        index: usize,
+        embedding: Vec<f32>,
+    }
+
+    let base_url = APIType::Mistral.base_url().unwrap_or_default();
+
+    let mut response: Response = reqwest::Client::new()
+        .post(format!("{base_url}/embeddings"))
+        .bearer_auth(api_key)
+        .json(&serde_json::json!({
+            "model": "mistral-embed",
+            "input": inputs,
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    response.data.sort_by_key(|embedding| embedding.index);
+
+    Ok(response
+        .data
+        .into_iter()
+        .map(|embedding| embedding.embedding)
+        .collect())
+}
+
+pub async fn openai_compatible(api: &APIAccess) -> Result<Vec<ModelOnline>, Error> {
+    #[derive(Deserialize)]
+    struct Response {
+        data: Vec<Model>,
+    }
+
+    #[derive(Deserialize)]
+    struct Model {
+        id: String,
+    }
+
+    let response: Response = list(api).await?;
+
+    Ok(response
+        .data
+        .into_iter()
+        .map(|model| ModelOnline {
+            endpoint_id: EndpointId::Remote {
+                api_type: APIType::OpenAICompatible,
+                id: Id(model.id),
+            },
+            cost: None,
+            speed: None,
+            function_calling: false,
+            config: api.clone(),
+            state_check: Default::default(),
+        })
+        .collect())
+}
+
+async fn list<T: serde::de::DeserializeOwned>(api: &APIAccess) -> Result<T, Error> {
+    let Some(base_url) = api.base_url() else {
+        return Err(Error::NotConfigured("the base URL", capture!()));
+    };
+
+    let mut request = reqwest::Client::new().get(format!("{base_url}/models"));
+
+    if let Some(api_key) = &api.api_key {
+        request = request.bearer_auth(api_key);
+    }
+
+    Ok(request.send().await?.error_for_status()?.json().await?)
+}
+
+fn is_chat(id: &str) -> bool {
+    ["whisper", "tts", "guard", "embed"]
+        .iter()
+        .all(|kind| !id.contains(kind))
+}
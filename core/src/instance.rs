@@ -0,0 +1,169 @@
+use crate::directory;
+
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use sipper::sipper;
+use tokio::fs;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::time;
+
+use std::env;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::path::{self, Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const ATTEMPTS: usize = 10;
+
+#[derive(Debug)]
+pub struct Lock {
+    _file: Option<File>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Activation {
+    pub arguments: Vec<String>,
+}
+
+impl Activation {
+    // The running instance may be working in a different directory
+    pub fn current() -> Self {
+        Self {
+            arguments: env::args()
+                .skip(1)
+                .map(|argument| {
+                    let path = Path::new(&argument);
+
+                    if path.is_relative() && path.exists() {
+                        path::absolute(path)
+                            .map(|path| path.to_string_lossy().into_owned())
+                            .unwrap_or(argument)
+                    } else {
+                        argument
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+pub fn lock() -> Option<Lock> {
+    let path = directory::base_data().join("instance.lock");
+
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+
+    let file = match OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+    {
+        Ok(file) => file,
+        Err(error) => {
+            log::warn!("Instance lock unavailable: {error}");
+
+            return Some(Lock { _file: None });
+        }
+    };
+
+    match file.try_lock() {
+        Ok(()) => Some(Lock { _file: Some(file) }),
+        Err(TryLockError::WouldBlock) => None,
+        Err(TryLockError::Error(error)) => {
+            log::warn!("Instance lock unavailable: {error}");
+
+            Some(Lock { _file: None })
+        }
+    }
+}
+
+// The running instance may still be starting, so wait for it a few seconds
+pub fn activate(activation: &Activation) -> bool {
+    let Ok(message) = serde_json::to_string(activation) else {
+        return false;
+    };
+
+    for _ in 0..ATTEMPTS {
+        if forward(&message) {
+            return true;
+        }
+
+        thread::sleep(Duration::from_millis(300));
+    }
+
+    false
+}
+
+pub fn listen() -> impl Stream<Item = Activation> {
+    sipper(|sender| async move {
+        let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await {
+            Ok(listener) => listener,
+            Err(error) => {
+                log::warn!("Instance listener unavailable: {error}");
+                return;
+            }
+        };
+
+        let port = match listener.local_addr() {
+            Ok(address) => address.port(),
+            Err(error) => {
+                log::warn!("Instance listener unavailable: {error}");
+                return;
+            }
+        };
+
+        let path = port_path();
+
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent).await;
+        }
+
+        if let Err(error) = fs::write(&path, port.to_string()).await {
+            log::warn!("Instance port could not be stored: {error}");
+            return;
+        }
+
+        while let Ok((stream, _address)) = listener.accept().await {
+            let mut sender = sender.clone();
+
+            let _ = tokio::spawn(async move {
+                let mut line = String::new();
+                let read = BufReader::new(stream).read_line(&mut line);
+
+                if let Ok(Ok(_)) = time::timeout(TIMEOUT, read).await {
+                    match serde_json::from_str(line.trim()) {
+                        Ok(activation) => sender.send(activation).await,
+                        Err(error) => log::warn!("Invalid instance activation: {error}"),
+                    }
+                }
+            });
+        }
+    })
+}
+
+fn forward(message: &str) -> bool {
+    let Some(port) = std::fs::read_to_string(port_path())
+        .ok()
+        .and_then(|port| port.trim().parse::<u16>().ok())
+    else {
+        return false;
+    };
+
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+
+    let Ok(mut stream) = TcpStream::connect_timeout(&address, TIMEOUT) else {
+        return false;
+    };
+
+    writeln!(stream, "{message}").is_ok()
+}
+
+fn port_path() -> PathBuf {
+    directory::base_data().join("instance.port")
+}
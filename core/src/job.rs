@@ -0,0 +1,252 @@
+use crate::chat;
+use crate::model;
+use crate::request;
+use crate::Error;
+
+use futures::future::{AbortHandle, Abortable};
+use futures::stream::{self, Stream};
+use sipper::{sipper, Sipper, Straw};
+use thiserror::capture;
+use tokio::sync::broadcast;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::{LazyLock, Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+const BACKLOG: usize = 64;
+
+static RUNNING: LazyLock<Mutex<BTreeMap<Id, (Job, AbortHandle)>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+static EVENTS: LazyLock<broadcast::Sender<Event>> = LazyLock::new(|| broadcast::channel(BACKLOG).0);
+
+static NEXT: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id(u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Scan,
+    Download,
+    StatusCheck,
+    Generation,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Scan => "Scan",
+            Self::Download => "Download",
+            Self::StatusCheck => "Status check",
+            Self::Generation => "Reply",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub percent: Option<u32>,
+    pub stage: Option<&'static str>,
+}
+
+impl From<model::ScanProgress> for Progress {
+    fn from(progress: model::ScanProgress) -> Self {
+        Self {
+            percent: percent(progress.scanned, progress.total),
+            stage: None,
+        }
+    }
+}
+
+impl From<model::StatusProgress> for Progress {
+    fn from(progress: model::StatusProgress) -> Self {
+        Self {
+            percent: percent(progress.checked, progress.total),
+            stage: None,
+        }
+    }
+}
+
+impl From<request::Progress> for Progress {
+    fn from(progress: request::Progress) -> Self {
+        Self {
+            percent: progress.percent().map(|(_total, percent)| percent),
+            stage: None,
+        }
+    }
+}
+
+impl From<chat::Event> for Progress {
+    fn from(event: chat::Event) -> Self {
+        Self {
+            percent: None,
+            stage: Some(match event {
+                chat::Event::ContextFitted(_) => "Reading",
+                chat::Event::ReplyAdded | chat::Event::ReplyChanged(_) => "Replying",
+                chat::Event::PlanAdded | chat::Event::PlanChanged(_) => "Planning",
+            }),
+        }
+    }
+}
+
+fn percent(done: usize, total: usize) -> Option<u32> {
+    (total > 0).then(|| (done.min(total) * 100 / total) as u32)
+}
+
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: Id,
+    pub kind: Kind,
+    pub title: String,
+    pub progress: Progress,
+    pub started_at: Instant,
+}
+
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    Completed,
+    Failed(Error),
+    Cancelled,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Started(Job),
+    Progressed(Id, Progress),
+    Finished(Id, Outcome),
+}
+
+pub fn run<T, P>(
+    kind: Kind,
+    title: impl Into<String>,
+    straw: impl Straw<T, P, Error>,
+) -> impl Straw<T, P, Error>
+where
+    P: Clone + Into<Progress>,
+{
+    let title = title.into();
+
+    sipper(move |mut sender| async move {
+        let (abort, registration) = AbortHandle::new_pair();
+        let mut job = Running::start(kind, title, abort);
+        let id = job.id;
+
+        let work = async {
+            let mut straw = straw.pin();
+            let mut last = Progress::default();
+
+            while let Some(progress) = straw.sip().await {
+                let next = progress.clone().into();
+
+                if next != last {
+                    update(id, next.clone());
+                    last = next;
+                }
+
+                sender.send(progress).await;
+            }
+
+            straw.await
+        };
+
+        let result = Abortable::new(work, registration)
+            .await
+            .unwrap_or_else(|_aborted| Err(Error::Cancelled(capture!())));
+
+        job.outcome = Some(match &result {
+            Ok(_) => Outcome::Completed,
+            Err(Error::Cancelled(..)) => Outcome::Cancelled,
+            Err(error) => Outcome::Failed(error.clone()),
+        });
+
+        result
+    })
+}
+
+pub fn future<T>(
+    kind: Kind,
+    title: impl Into<String>,
+    future: impl Future<Output = Result<T, Error>>,
+) -> impl Straw<T, Progress, Error> {
+    run(kind, title, sipper(move |_sender| future))
+}
+
+pub fn cancel(id: Id) -> bool {
+    let Some((_job, abort)) = running().remove(&id) else {
+        return false;
+    };
+
+    abort.abort();
+
+    true
+}
+
+pub fn list() -> Vec<Job> {
+    running()
+        .values()
+        .map(|(job, _abort)| job.clone())
+        .collect()
+}
+
+pub fn events() -> impl Stream<Item = Event> {
+    stream::unfold(EVENTS.subscribe(), |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, receiver)),
+                // Subscribers are expected to read the list again anyway
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+fn running() -> MutexGuard<'static, BTreeMap<Id, (Job, AbortHandle)>> {
+    RUNNING.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn update(id: Id, progress: Progress) {
+    if let Some((job, _abort)) = running().get_mut(&id) {
+        job.progress = progress.clone();
+    }
+
+    let _ = EVENTS.send(Event::Progressed(id, progress));
+}
+
+struct Running {
+    id: Id,
+    outcome: Option<Outcome>,
+}
+
+impl Running {
+    fn start(kind: Kind, title: String, abort: AbortHandle) -> Self {
+        let id = Id(NEXT.fetch_add(1, atomic::Ordering::Relaxed));
+
+        let job = Job {
+            id,
+            kind,
+            title,
+            progress: Progress::default(),
+            started_at: Instant::now(),
+        };
+
+        let _ = running().insert(id, (job.clone(), abort));
+        let _ = EVENTS.send(Event::Started(job));
+
+        Self { id, outcome: None }
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        let _ = running().remove(&self.id);
+        let _ = EVENTS.send(Event::Finished(
+            self.id,
+            self.outcome.take().unwrap_or(Outcome::Cancelled),
+        ));
+    }
+}
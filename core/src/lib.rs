@@ -1,15 +1,68 @@
 #![feature(error_generic_member_access)]
 #![feature(arbitrary_self_types)]
 
+pub mod approval;
+pub mod arena;
 pub mod assistant;
+pub mod attachment;
+pub mod audit;
+pub mod azure;
+pub mod backup;
+pub mod batch;
+pub mod cache;
 pub mod chat;
+pub mod codebase;
+pub mod conversion;
+pub mod database;
+pub mod desktop;
+pub mod diagnostics;
+pub mod diagram;
+pub mod diff;
+pub mod diffusion;
+pub mod discovery;
+pub mod duplicate;
+pub mod execution;
+pub mod export;
+pub mod filesystem;
+pub mod git;
+pub mod headless;
+pub mod hook;
+pub mod hosted;
+pub mod instance;
+pub mod job;
+pub mod link;
+pub mod maintenance;
+pub mod mirror;
 pub mod model;
+pub mod ocr;
+pub mod persona;
 pub mod plan;
+pub mod plugin;
+pub mod preset;
+pub mod profile;
+pub mod prompt;
+pub mod provider;
+pub mod quick_action;
+pub mod reminder;
+pub mod request;
+pub mod session;
 pub mod settings;
+pub mod setup;
+pub mod ssh;
+pub mod summary;
+pub mod sync;
+pub mod table;
+pub mod telemetry;
+pub mod timeout;
+pub mod todo;
+pub mod transcription;
+pub mod translation;
 pub mod web;
+pub mod workspace;
 
 pub use assistant::Assistant;
 pub use chat::Chat;
+pub use headless::{ChatSession, DownloadManager, ModelManager};
 pub use model::HFModel;
 pub use plan::Plan;
 use rcu_cell::RcuCell;
@@ -19,7 +72,7 @@ use thiserror::{capture, thiserror, Error};
 pub use url::Url;
 
 mod directory;
-mod request;
+mod persistence;
 
 use std::io;
 use std::sync::Arc;
@@ -36,8 +89,18 @@ pub enum Error {
     DockerFailed(&'static str),
     #[error("executor failed: {0}")]
     ExecutorFailed(&'static str),
+    #[error("llama-server exited unexpectedly")]
+    ServerCrashed(Arc<diagnostics::Report>),
+    #[error("git operation failed: {0}")]
+    GitFailed(&'static str),
+    #[error("remote machine failed: {0}")]
+    RemoteFailed(String),
     #[error("JSON deserialization failed: {0}")]
     InvalidJson(Arc<serde_json::Error>),
+    #[error("MessagePack deserialization failed: {0}")]
+    InvalidMessagePack(Arc<rmp_serde::decode::Error>),
+    #[error("MessagePack serialization impossible: {0}")]
+    ImpossibleMessagePack(Arc<rmp_serde::encode::Error>),
     #[error("TOML deserialization failed: {0}")]
     InvalidToml(Arc<toml::de::Error>),
     #[error("TOML serialization impossible: {0}")]
@@ -46,6 +109,52 @@ pub enum Error {
     DecoderFailed(Arc<decoder::Error>),
     #[error("task join failed: {0}")]
     JoinFailed(Arc<task::JoinError>),
+    #[error("invalid profile: {0}")]
+    InvalidProfile(&'static str),
+    #[error("invalid backup: {0}")]
+    InvalidBackup(&'static str),
+    #[error("model not found: {0}")]
+    ModelNotFound(String),
+    #[error("checksum mismatch: {0}")]
+    ChecksumMismatch(String),
+    #[error("invalid response from {0}")]
+    InvalidResponse(&'static str),
+    #[error("request timed out: {0}")]
+    TimedOut(&'static str),
+    #[error("{0} is not configured")]
+    NotConfigured(&'static str),
+    #[error("invalid image data")]
+    InvalidImage,
+    #[error("image generation failed: {0}")]
+    GenerationFailed(String),
+    #[error("conversion to GGUF failed: {0}")]
+    ConversionFailed(String),
+    #[error("transcription failed: {0}")]
+    TranscriptionFailed(String),
+    #[error("text recognition failed: {0}")]
+    OcrFailed(String),
+    #[error("query failed: {0}")]
+    QueryFailed(String),
+    #[error("invalid plugin: {0}")]
+    InvalidPlugin(&'static str),
+    #[error("plugin failed: {0}")]
+    PluginFailed(String),
+    #[error("hook exited with an error: {0}")]
+    HookFailed(String),
+    #[error("opening failed: {0}")]
+    OpenFailed(String),
+    #[error("PDF export failed: {0}")]
+    PdfFailed(String),
+    #[error("the chat is locked to another model")]
+    ChatLocked,
+    #[error("the chat is read-only")]
+    ChatReadOnly,
+    #[error("{0} models cannot be chatted with here")]
+    Unsupported(&'static str),
+    #[error("the job was cancelled")]
+    Cancelled,
+    #[error("code sandbox unavailable: {0}")]
+    SandboxUnavailable(&'static str),
     #[error("no suitable executor was found: neither llama-server nor docker are installed")]
     NoExecutorAvailable,
 }
@@ -68,6 +177,18 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+impl From<rmp_serde::decode::Error> for Error {
+    fn from(error: rmp_serde::decode::Error) -> Self {
+        Self::InvalidMessagePack(Arc::new(error), capture!())
+    }
+}
+
+impl From<rmp_serde::encode::Error> for Error {
+    fn from(error: rmp_serde::encode::Error) -> Self {
+        Self::ImpossibleMessagePack(Arc::new(error), capture!())
+    }
+}
+
 impl From<toml::ser::Error> for Error {
     fn from(error: toml::ser::Error) -> Self {
         Self::ImpossibleToml(Arc::new(error), capture!())
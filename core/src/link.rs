@@ -0,0 +1,209 @@
+use crate::export;
+use crate::model;
+use crate::Error;
+use crate::Url;
+
+use std::env;
+use std::path::PathBuf;
+
+pub const SCHEME: &str = "icebreaker";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Link {
+    Model(model::Id),
+    NewChat { prompt: Option<String> },
+    ModelFile(PathBuf),
+    ChatFile(PathBuf),
+}
+
+impl Link {
+    pub fn parse(link: &str) -> Option<Self> {
+        let url = Url::parse(link.trim()).ok()?;
+
+        if url.scheme() != SCHEME {
+            return None;
+        }
+
+        let segments: Vec<_> = url
+            .path_segments()
+            .into_iter()
+            .flatten()
+            .filter(|segment| !segment.is_empty())
+            .collect();
+
+        match (url.host_str()?, segments.as_slice()) {
+            ("model", [author, name]) => Some(Self::Model(model::Id(format!("{author}/{name}")))),
+            ("chat", ["new"]) => Some(Self::NewChat {
+                prompt: url
+                    .query_pairs()
+                    .find(|(key, _)| key == "prompt")
+                    .map(|(_, prompt)| prompt.into_owned()),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn file(argument: &str) -> Option<Self> {
+        let path = match Url::parse(argument) {
+            Ok(url) if url.scheme() == "file" => url.to_file_path().ok()?,
+            _ => PathBuf::from(argument),
+        };
+
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+
+        if !path.is_file() {
+            return None;
+        }
+
+        if name.ends_with(".gguf") {
+            Some(Self::ModelFile(path))
+        } else if name.ends_with(&format!(".{}", export::json::EXTENSION)) {
+            Some(Self::ChatFile(path))
+        } else {
+            None
+        }
+    }
+
+    pub fn find(arguments: &[String]) -> Option<Self> {
+        arguments
+            .iter()
+            .find_map(|argument| Self::parse(argument).or_else(|| Self::file(argument)))
+    }
+}
+
+pub async fn register() -> Result<(), Error> {
+    let executable = env::current_exe()?;
+
+    platform::register(&executable.to_string_lossy()).await
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::SCHEME;
+    use crate::export;
+    use crate::Error;
+
+    use thiserror::capture;
+    use tokio::fs;
+    use tokio::process;
+
+    const DESKTOP_FILE: &str = "icebreaker-url-handler.desktop";
+    const MODEL_TYPE: &str = "application/x-gguf";
+    const CHAT_TYPE: &str = "application/x-icebreaker-chat";
+
+    pub async fn register(executable: &str) -> Result<(), Error> {
+        let Some(base) = directories::BaseDirs::new() else {
+            return Err(Error::NotConfigured("the home directory", capture!()));
+        };
+
+        let packages = base.data_dir().join("mime").join("packages");
+        fs::create_dir_all(&packages).await?;
+
+        fs::write(
+            packages.join("icebreaker.xml"),
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                <mime-info xmlns=\"http://www.freedesktop.org/standards/shared-mime-info\">\n\
+                <mime-type type=\"{MODEL_TYPE}\">\n\
+                <comment>GGUF model</comment>\n\
+                <glob pattern=\"*.gguf\"/>\n\
+                </mime-type>\n\
+                <mime-type type=\"{CHAT_TYPE}\">\n\
+                <comment>Icebreaker chat</comment>\n\
+                <sub-class-of type=\"application/json\"/>\n\
+                <glob pattern=\"*.{extension}\" weight=\"60\"/>\n\
+                </mime-type>\n\
+                </mime-info>\n",
+                extension = export::json::EXTENSION,
+            ),
+        )
+        .await?;
+
+        let _ = process::Command::new("update-mime-database")
+            .arg(base.data_dir().join("mime"))
+            .status()
+            .await?;
+
+        let applications = base.data_dir().join("applications");
+        fs::create_dir_all(&applications).await?;
+
+        fs::write(
+            applications.join(DESKTOP_FILE),
+            format!(
+                "[Desktop Entry]\n\
+                Type=Application\n\
+                Name=Icebreaker\n\
+                Exec=\"{executable}\" %u\n\
+                MimeType=x-scheme-handler/{SCHEME};{MODEL_TYPE};{CHAT_TYPE};\n\
+                NoDisplay=true\n"
+            ),
+        )
+        .await?;
+
+        let _ = process::Command::new("xdg-mime")
+            .args(["default", DESKTOP_FILE])
+            .arg(format!("x-scheme-handler/{SCHEME}"))
+            .args([MODEL_TYPE, CHAT_TYPE])
+            .status()
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::SCHEME;
+    use crate::Error;
+
+    use tokio::process;
+
+    pub async fn register(executable: &str) -> Result<(), Error> {
+        let key = format!("HKCU\\Software\\Classes\\{SCHEME}");
+        let open = format!("{key}\\shell\\open\\command");
+        let command = format!("\"{executable}\" \"%1\"");
+
+        // Windows only looks at the last extension, so exported chats are left alone
+        let model = "HKCU\\Software\\Classes\\Icebreaker.Model";
+        let model_open = format!("{model}\\shell\\open\\command");
+
+        for arguments in [
+            vec!["add", key.as_str(), "/ve", "/d", "URL:Icebreaker", "/f"],
+            vec!["add", key.as_str(), "/v", "URL Protocol", "/d", "", "/f"],
+            vec!["add", open.as_str(), "/ve", "/d", command.as_str(), "/f"],
+            vec!["add", model, "/ve", "/d", "GGUF model", "/f"],
+            vec![
+                "add",
+                model_open.as_str(),
+                "/ve",
+                "/d",
+                command.as_str(),
+                "/f",
+            ],
+            vec![
+                "add",
+                "HKCU\\Software\\Classes\\.gguf",
+                "/ve",
+                "/d",
+                "Icebreaker.Model",
+                "/f",
+            ],
+        ] {
+            let _ = process::Command::new("reg")
+                .args(arguments)
+                .status()
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod platform {
+    use crate::Error;
+
+    pub async fn register(_executable: &str) -> Result<(), Error> {
+        Ok(())
+    }
+}
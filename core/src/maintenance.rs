@@ -0,0 +1,268 @@
+use crate::duplicate;
+use crate::mirror;
+use crate::model::{Directory, File, Hub, Id, Size};
+use crate::Error;
+
+use serde::Deserialize;
+use sipper::{sipper, Straw};
+use tokio::fs;
+use tokio::task;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+// Partial downloads left untouched for longer are abandoned
+const ABANDONED_AFTER: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    pub checked: usize,
+    pub unverified: usize,
+    pub issues: Vec<Issue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    Corrupted { file: File, reason: Corruption },
+    Leftover { path: PathBuf, size: Size },
+    EmptyDirectory(PathBuf),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corruption {
+    Size { expected: Size, actual: Size },
+    Checksum,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub checked: usize,
+    pub total: usize,
+}
+
+pub fn check(directory: Directory, checksums: bool) -> impl Straw<Report, Progress, Error> {
+    sipper(move |mut progress| async move {
+        let root = directory.path().to_path_buf();
+        let listing = task::spawn_blocking(move || list(&root)).await?;
+
+        let mut report = Report::default();
+        let mut by_model: HashMap<Id, Vec<(File, PathBuf)>> = HashMap::new();
+
+        for (file, path) in listing.models {
+            by_model
+                .entry(file.model.clone())
+                .or_default()
+                .push((file, path));
+        }
+
+        let total = by_model.values().map(Vec::len).sum();
+        progress.send(Progress { checked: 0, total }).await;
+
+        for (model, files) in by_model {
+            // Imported files are not in Hugging Face, and neither are deleted repositories
+            let entries = if model.author() == "local" {
+                None
+            } else {
+                remote(&model).await.ok()
+            };
+
+            for (file, path) in files {
+                report.checked += 1;
+
+                let expected = entries
+                    .as_ref()
+                    .and_then(|entries| entries.iter().find(|entry| entry.path == file.name));
+
+                match expected {
+                    Some(expected) => {
+                        if let Some(reason) = verify(&path, expected, checksums).await? {
+                            report.issues.push(Issue::Corrupted { file, reason });
+                        }
+                    }
+                    None => {
+                        report.unverified += 1;
+                    }
+                }
+
+                progress
+                    .send(Progress {
+                        checked: report.checked,
+                        total,
+                    })
+                    .await;
+            }
+        }
+
+        report.issues.extend(listing.leftovers);
+        report
+            .issues
+            .extend(listing.empty.into_iter().map(Issue::EmptyDirectory));
+
+        Ok(report)
+    })
+}
+
+pub async fn repair(issue: Issue, directory: Directory) -> Result<Option<File>, Error> {
+    match issue {
+        Issue::Corrupted { file, .. } => {
+            fs::remove_file(directory.path().join(file.relative_path())).await?;
+
+            Ok(Some(file))
+        }
+        Issue::Leftover { path, .. } => {
+            fs::remove_file(path).await?;
+
+            Ok(None)
+        }
+        Issue::EmptyDirectory(path) => {
+            fs::remove_dir(path).await?;
+
+            Ok(None)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Entry {
+    path: String,
+    size: u64,
+    lfs: Option<Lfs>,
+}
+
+#[derive(Deserialize)]
+struct Lfs {
+    oid: String,
+}
+
+async fn remote(model: &Id) -> Result<Vec<Entry>, Error> {
+    Ok(reqwest::Client::new()
+        .get(format!("{}/models/{}/tree/main", mirror::api(), model.0))
+        .query(&[("recursive", "true")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+async fn verify(
+    path: &Path,
+    expected: &Entry,
+    checksums: bool,
+) -> Result<Option<Corruption>, Error> {
+    let actual = fs::metadata(path).await?.len();
+
+    if actual != expected.size {
+        return Ok(Some(Corruption::Size {
+            expected: Size::from(expected.size),
+            actual: Size::from(actual),
+        }));
+    }
+
+    let Some(lfs) = expected.lfs.as_ref().filter(|_| checksums) else {
+        return Ok(None);
+    };
+
+    let path = path.to_path_buf();
+    let checksum = task::spawn_blocking(move || duplicate::checksum(&path)).await??;
+
+    Ok((checksum != lfs.oid).then_some(Corruption::Checksum))
+}
+
+struct Listing {
+    models: Vec<(File, PathBuf)>,
+    leftovers: Vec<Issue>,
+    empty: Vec<PathBuf>,
+}
+
+fn list(root: &Path) -> Listing {
+    use std::fs;
+
+    let mut listing = Listing {
+        models: Vec::new(),
+        leftovers: Vec::new(),
+        empty: Vec::new(),
+    };
+
+    let mut pending = vec![root.to_path_buf()];
+    let now = SystemTime::now();
+
+    while let Some(directory) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&directory) else {
+            continue;
+        };
+
+        let mut is_empty = true;
+
+        for entry in entries.flatten() {
+            is_empty = false;
+
+            let path = entry.path();
+
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+
+            if metadata.is_dir() {
+                // Hidden directories hold work in progress, like conversions
+                if !entry.file_name().to_string_lossy().starts_with('.') {
+                    pending.push(path);
+                }
+
+                continue;
+            }
+
+            match path.extension().and_then(|extension| extension.to_str()) {
+                Some("gguf") => {
+                    if let Some(file) = file(root, &path, metadata.len()) {
+                        listing.models.push((file, path));
+                    }
+                }
+                Some("tmp") => {
+                    let is_abandoned = metadata
+                        .modified()
+                        .ok()
+                        .and_then(|modified| now.duration_since(modified).ok())
+                        .is_some_and(|age| age > ABANDONED_AFTER);
+
+                    if is_abandoned {
+                        listing.leftovers.push(Issue::Leftover {
+                            path,
+                            size: Size::from(metadata.len()),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if is_empty && directory != root {
+            listing.empty.push(directory);
+        }
+    }
+
+    listing
+}
+
+fn file(root: &Path, path: &Path, size: u64) -> Option<File> {
+    let relative = path.strip_prefix(root).ok()?;
+    let mut components = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy());
+
+    let author = components.next()?;
+    let model = components.next()?;
+    let name: Vec<_> = components.collect();
+
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(File {
+        model: Id(format!("{author}/{model}")),
+        name: name.join("/"),
+        size: Some(Size::from(size)),
+        hub: Hub::HuggingFace,
+    })
+}
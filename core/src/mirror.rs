@@ -0,0 +1,47 @@
+use crate::Error;
+use crate::Url;
+
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+static CONFIGURATION: RwLock<Option<Url>> = RwLock::new(None);
+
+pub const DEFAULT: &str = "https://huggingface.co";
+
+pub const KNOWN: &[&str] = &[DEFAULT, "https://hf-mirror.com"];
+
+pub fn configure(mirror: Option<Url>) {
+    if let Ok(mut configuration) = CONFIGURATION.write() {
+        *configuration = mirror;
+    }
+}
+
+pub(crate) fn url() -> String {
+    CONFIGURATION
+        .read()
+        .ok()
+        .and_then(|mirror| mirror.as_ref().map(Url::to_string))
+        .map(|url| url.trim_end_matches('/').to_owned())
+        .unwrap_or_else(|| DEFAULT.to_owned())
+}
+
+pub(crate) fn api() -> String {
+    format!("{}/api", url())
+}
+
+pub async fn check(mirror: Url) -> Result<Duration, Error> {
+    let start = Instant::now();
+
+    let _ = reqwest::Client::new()
+        .get(format!(
+            "{}/api/models",
+            mirror.as_str().trim_end_matches('/')
+        ))
+        .query(&[("limit", "1")])
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(start.elapsed())
+}
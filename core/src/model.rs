@@ -1,10 +1,28 @@
+pub mod hugging_face;
+mod index;
+mod modelscope;
+mod store;
+#[cfg(test)]
+mod tests;
+
+use crate::azure::Azure;
 use crate::directory;
+use crate::hook;
+use crate::hosted;
+use crate::mirror;
 use crate::model;
+use crate::persistence;
+use crate::plugin::{self, Plugin};
 use crate::request;
+use crate::ssh::Ssh;
 use crate::Error;
 use crate::Settings;
 
+pub use index::Progress as ScanProgress;
+pub use store::{Change, Store};
+
 use decoder::{decode, encode, Value};
+use futures::stream::FuturesUnordered;
 use langchain_rust::document_loaders::dotenvy;
 use langchain_rust::language_models::llm::LLM;
 use langchain_rust::language_models::options::CallOptions;
@@ -18,8 +36,9 @@ use rcu_cell::ArcRCUNonNull;
 use serde::{Deserialize, Serialize};
 use sipper::StreamExt;
 use sipper::{sipper, Sipper, Straw};
+use thiserror::capture;
 use tokio::fs;
-use tokio::sync::watch;
+use tokio::sync::{watch, Semaphore};
 
 use std::borrow::Cow;
 use std::collections::BTreeMap;
@@ -27,30 +46,91 @@ use std::fmt;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-const HF_URL: &str = "https://huggingface.co";
-const API_URL: &str = "https://huggingface.co/api";
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct APIAccess {
     pub openai_compat: Option<OpenAIConfigSerde>,
+    #[serde(default)]
+    pub azure: Option<Azure>,
+    #[serde(default)]
+    pub ssh: Option<Ssh>,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub base_url: Option<String>,
     pub kind: APIType,
 }
 
+impl APIAccess {
+    pub fn base_url(&self) -> Option<&str> {
+        self.base_url.as_deref().or(self.kind.base_url())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct HFModel {
     pub id: Id,
     pub last_modified: chrono::DateTime<chrono::Local>,
     pub downloads: Downloads,
     pub likes: Likes,
+    pub hub: Hub,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Hub {
+    #[default]
+    HuggingFace,
+    ModelScope,
+}
+
+impl Hub {
+    pub const ALL: &[Self] = &[Self::HuggingFace, Self::ModelScope];
+
+    fn decode(value: Value) -> decoder::Result<Self> {
+        let hub = decode::string(value)?;
+
+        Ok(match hub.as_str() {
+            "modelscope" => Self::ModelScope,
+            _ => Self::HuggingFace,
+        })
+    }
+
+    fn encode(self) -> Value {
+        encode::string(match self {
+            Self::HuggingFace => "huggingface",
+            Self::ModelScope => "modelscope",
+        })
+    }
+
+    pub fn page(self, id: &Id) -> String {
+        match self {
+            Self::HuggingFace => id.url(),
+            Self::ModelScope => modelscope::page(id),
+        }
+    }
+}
+
+impl fmt::Display for Hub {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::HuggingFace => "Hugging Face",
+            Self::ModelScope => "ModelScope",
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelOnline {
     pub endpoint_id: EndpointId,
     pub cost: Option<Cost>,
+    // In tokens per second
+    #[serde(default)]
+    pub speed: Option<u32>,
+    #[serde(default)]
+    pub function_calling: bool,
     /// All the information needed to access this API
     pub config: APIAccess,
-    pub state_check: ArcRCUNonNull<StatusCheck>,
+    #[serde(skip)]
+    pub(crate) state_check: ArcRCUNonNull<StatusCheck>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -60,19 +140,64 @@ pub enum StatusCheck {
     CheckingStatus,
     Up {
         rtt: time::Duration,
+        checked_at: chrono::DateTime<chrono::Local>,
+    },
+    Down {
+        checked_at: chrono::DateTime<chrono::Local>,
     },
-    Down,
+}
+
+impl StatusCheck {
+    pub fn down() -> Self {
+        Self::Down {
+            checked_at: chrono::Local::now(),
+        }
+    }
+
+    pub fn age(&self) -> Option<time::Duration> {
+        match self {
+            Self::Up { checked_at, .. } | Self::Down { checked_at } => Some(
+                (chrono::Local::now() - *checked_at)
+                    .to_std()
+                    .unwrap_or_default(),
+            ),
+            Self::Unchecked | Self::CheckingStatus => None,
+        }
+    }
+
+    pub fn fresh(&self, max_age: time::Duration) -> Self {
+        match self.age() {
+            Some(age) if age > max_age => Self::Unchecked,
+            _ => self.clone(),
+        }
+    }
 }
 
 impl ModelOnline {
+    pub fn status(&self) -> StatusCheck {
+        StatusCheck::clone(&self.state_check.read())
+    }
+
+    pub async fn refresh_status(&self) -> Result<StatusCheck, Error> {
+        let _ = self.state_check.write(StatusCheck::CheckingStatus);
+
+        let status = self.check().await?;
+        let _ = self.state_check.write(status.clone());
+
+        Ok(status)
+    }
+
     pub async fn check(&self) -> Result<StatusCheck, Error> {
         info!("checking model");
         match self.config.kind {
             APIType::NanoGPT => {
-                let nanogpt: NanoGPT<OpenAIConfig> =
-                    NanoGPT::new(self.config.openai_compat.clone().unwrap().into())
-                        .with_model(self.endpoint_id.slash_id().to_owned())
-                        .with_options(CallOptions::new().with_max_tokens(10).with_max_length(10));
+                let Some(config) = self.config.openai_compat.clone() else {
+                    return Err(Error::NotConfigured("NanoGPT", capture!()));
+                };
+
+                let nanogpt: NanoGPT<OpenAIConfig> = NanoGPT::new(config.into())
+                    .with_model(self.endpoint_id.slash_id().to_owned())
+                    .with_options(CallOptions::new().with_max_tokens(10).with_max_length(10));
 
                 let start = time::Instant::now();
                 let sx = nanogpt
@@ -84,15 +209,18 @@ impl ModelOnline {
                             let du = time::Instant::now() - start;
                             info!("{:?}: {:?}", &self.endpoint_id.slash_id(), &resp.content);
 
-                            Ok(StatusCheck::Up { rtt: du })
+                            Ok(StatusCheck::Up {
+                                rtt: du,
+                                checked_at: chrono::Local::now(),
+                            })
                         } else {
-                            Ok(StatusCheck::Down)
+                            Ok(StatusCheck::down())
                         }
                     }
-                    Err(_) => Ok(StatusCheck::Down),
+                    Err(_) => Ok(StatusCheck::down()),
                 }
             }
-            _ => Ok(StatusCheck::Down),
+            _ => Ok(StatusCheck::down()),
         }
     }
 }
@@ -107,19 +235,63 @@ impl Model {
     pub async fn check(&self) -> Result<StatusCheck, Error> {
         match self {
             Model::API(ap) => ap.check().await,
-            _ => Ok(StatusCheck::Down),
+            _ => Ok(StatusCheck::down()),
         }
     }
     pub async fn update_status(self) -> Result<(), Error> {
         match self {
             Model::API(api) => {
-                let _ = api.state_check.write(StatusCheck::CheckingStatus);
-                let _ = api.state_check.write(api.check().await?);
+                let _ = api.refresh_status().await?;
+
                 Ok(())
             }
             _ => Ok(()),
         }
     }
+
+    pub fn update_statuses(
+        models: Vec<Self>,
+        concurrency: usize,
+    ) -> impl Straw<(), StatusProgress, Error> {
+        sipper(move |mut sender| async move {
+            let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+            let total = models.len();
+            let mut checked = 0;
+
+            let mut checks: FuturesUnordered<_> = models
+                .into_iter()
+                .map(|model| {
+                    let semaphore = semaphore.clone();
+
+                    async move {
+                        let _permit = semaphore.acquire_owned().await;
+                        let id = model.endpoint_id();
+
+                        (id, model.update_status().await)
+                    }
+                })
+                .collect();
+
+            sender.send(StatusProgress { checked, total }).await;
+
+            while let Some((id, result)) = checks.next().await {
+                if let Err(error) = result {
+                    log::warn!("Status check of {id} failed: {error}");
+                }
+
+                checked += 1;
+                sender.send(StatusProgress { checked, total }).await;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusProgress {
+    pub checked: usize,
+    pub total: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
@@ -129,6 +301,26 @@ pub enum APIType {
     OpenAI,
     #[default]
     OpenAICompatible,
+    AzureOpenAI,
+    Groq,
+    Together,
+    Mistral,
+    Ssh,
+}
+
+impl APIType {
+    pub fn base_url(&self) -> Option<&'static str> {
+        match self {
+            Self::Groq => Some("https://api.groq.com/openai/v1"),
+            Self::Together => Some("https://api.together.xyz/v1"),
+            Self::Mistral => Some("https://api.mistral.ai/v1"),
+            Self::NanoGPT
+            | Self::OpenAI
+            | Self::OpenAICompatible
+            | Self::AzureOpenAI
+            | Self::Ssh => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -167,8 +359,12 @@ impl Model {
         for (id, api) in api.api_src.iter() {
             match &api.kind {
                 APIType::NanoGPT => {
-                    let nanogpt: NanoGPT<OpenAIConfig> =
-                        NanoGPT::new(api.openai_compat.clone().unwrap().into());
+                    let Some(config) = api.openai_compat.clone() else {
+                        log::warn!("NanoGPT is not configured");
+                        continue;
+                    };
+
+                    let nanogpt: NanoGPT<OpenAIConfig> = NanoGPT::new(config.into());
                     let models = nanogpt.get_models(true).await?;
                     for m in models.data {
                         let _ = resp.insert(
@@ -185,13 +381,100 @@ impl Model {
                                     prompt: Quantity::usd_per_1m(p.prompt),
                                     completion: Quantity::usd_per_1m(p.completion),
                                 }),
+                                speed: None,
+                                function_calling: false,
+                                config: api.clone(),
+                                state_check: Default::default(),
+                            }),
+                        );
+                    }
+                }
+                APIType::AzureOpenAI => {
+                    let deployments = api.azure.iter().flat_map(|azure| azure.deployments.iter());
+
+                    for deployment in deployments {
+                        let endpoint_id = EndpointId::Remote {
+                            api_type: APIType::AzureOpenAI,
+                            id: Id(deployment.clone()),
+                        };
+
+                        let _ = resp.insert(
+                            endpoint_id.clone(),
+                            Model::API(ModelOnline {
+                                endpoint_id,
+                                cost: None,
+                                speed: None,
+                                function_calling: false,
+                                config: api.clone(),
+                                state_check: Default::default(),
+                            }),
+                        );
+                    }
+                }
+                APIType::Ssh => {
+                    let Some(ssh) = &api.ssh else {
+                        continue;
+                    };
+
+                    for path in ssh.models().await? {
+                        let endpoint_id = EndpointId::Remote {
+                            api_type: APIType::Ssh,
+                            id: Id(path),
+                        };
+
+                        let _ = resp.insert(
+                            endpoint_id.clone(),
+                            Model::API(ModelOnline {
+                                endpoint_id,
+                                cost: None,
+                                speed: None,
+                                function_calling: false,
                                 config: api.clone(),
                                 state_check: Default::default(),
                             }),
                         );
                     }
                 }
-                _ => todo!(),
+                APIType::Groq
+                | APIType::Together
+                | APIType::Mistral
+                | APIType::OpenAICompatible => {
+                    let models = match api.kind {
+                        APIType::Groq => hosted::groq(api).await?,
+                        APIType::Together => hosted::together(api).await?,
+                        APIType::Mistral => hosted::mistral(api).await?,
+                        _ => hosted::openai_compatible(api).await?,
+                    };
+
+                    resp.extend(
+                        models
+                            .into_iter()
+                            .map(|model| (model.endpoint_id.clone(), Model::API(model))),
+                    );
+                }
+                kind => {
+                    log::warn!("Listing {kind:?} models is not supported");
+                }
+            }
+        }
+
+        let plugins = Plugin::enabled().await.unwrap_or_else(|error| {
+            log::warn!("Plugins unavailable: {error}");
+            Vec::new()
+        });
+
+        for provider in plugins.iter().flat_map(|plugin| &plugin.manifest.providers) {
+            match hosted::openai_compatible(&plugin::access(provider)).await {
+                Ok(models) => {
+                    resp.extend(
+                        models
+                            .into_iter()
+                            .map(|model| (model.endpoint_id.clone(), Model::API(model))),
+                    );
+                }
+                Err(error) => {
+                    log::warn!("Provider {} unavailable: {error}", provider.name);
+                }
             }
         }
 
@@ -222,20 +505,24 @@ impl HFModel {
     }
 
     pub async fn list() -> Result<Vec<Self>, Error> {
-        Self::search(String::new()).await
+        Self::search(Hub::HuggingFace, String::new()).await
     }
 
-    pub async fn search(query: String) -> Result<Vec<Self>, Error> {
-        let client = reqwest::Client::new();
+    pub fn search(hub: Hub, query: String) -> impl Straw<Vec<Self>, Vec<Self>, Error> {
+        sipper(move |mut sender| async move {
+            match hub {
+                Hub::HuggingFace => Self::search_hugging_face(query).run(sender).await,
+                Hub::ModelScope => {
+                    let models = modelscope::search(query).await?;
+                    sender.send(models.clone()).await;
 
-        let request = client.get(format!("{API_URL}/models")).query(&[
-            ("search", query.as_ref()),
-            ("filter", "text-generation"),
-            ("filter", "gguf"),
-            ("limit", "100"),
-            ("full", "true"),
-        ]);
+                    Ok(models)
+                }
+            }
+        })
+    }
 
+    fn search_hugging_face(query: String) -> impl Straw<Vec<Self>, Vec<Self>, Error> {
         #[derive(Deserialize)]
         struct Response {
             id: Id,
@@ -253,20 +540,108 @@ impl HFModel {
             Other(String),
         }
 
-        let response = request.send().await?;
-        let mut models: Vec<Response> = response.json().await?;
+        impl Response {
+            fn into_model(self) -> Option<HFModel> {
+                (self.gated == Gated::Bool(false)).then_some(HFModel {
+                    id: self.id,
+                    last_modified: self.last_modified,
+                    downloads: self.downloads,
+                    likes: self.likes,
+                    hub: Hub::HuggingFace,
+                })
+            }
+        }
 
-        models.retain(|model| model.gated == Gated::Bool(false));
+        sipper(move |mut sender| async move {
+            let search = hugging_face::search();
+
+            // Only the fields we use; `full` responses are much heavier
+            let request = hugging_face::get(format!("{}/models", mirror::api())).query(&[
+                ("search", query.as_ref()),
+                ("filter", "text-generation"),
+                ("filter", "gguf"),
+                ("limit", "100"),
+                ("expand[]", "lastModified"),
+                ("expand[]", "downloads"),
+                ("expand[]", "likes"),
+                ("expand[]", "gated"),
+            ]);
+
+            let mut response = search.send(request).await?;
+            let mut objects = Objects::default();
+            let mut models = Vec::new();
+
+            while let Some(chunk) = response.chunk().await? {
+                // Stale results are dropped as soon as a newer search starts
+                search.check()?;
+
+                let batch = objects
+                    .feed(&chunk)
+                    .into_iter()
+                    .map(|object| serde_json::from_slice::<Response>(&object))
+                    .filter_map(|response| match response {
+                        Ok(response) => response.into_model(),
+                        Err(error) => {
+                            log::warn!("Invalid Hugging Face model: {error}");
+                            None
+                        }
+                    })
+                    .collect::<Vec<_>>();
 
-        Ok(models
-            .into_iter()
-            .map(|model| Self {
-                id: model.id.clone(),
-                last_modified: model.last_modified,
-                downloads: model.downloads,
-                likes: model.likes,
-            })
-            .collect())
+                if !batch.is_empty() {
+                    models.extend(batch.iter().cloned());
+                    sender.send(batch).await;
+                }
+            }
+
+            Ok(models)
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct Objects {
+    buffer: Vec<u8>,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl Objects {
+    fn feed(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut objects = Vec::new();
+
+        for &byte in bytes {
+            if self.depth > 1 || (self.depth == 1 && byte == b'{') {
+                self.buffer.push(byte);
+            }
+
+            if self.in_string {
+                match byte {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => {}
+                }
+
+                continue;
+            }
+
+            match byte {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' => {
+                    self.depth = self.depth.saturating_sub(1);
+
+                    if self.depth == 1 && byte == b'}' {
+                        objects.push(std::mem::take(&mut self.buffer));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        objects
     }
 }
 
@@ -299,6 +674,10 @@ impl Id {
             .map(|(author, _name)| author)
             .unwrap_or(&self.0)
     }
+
+    pub fn url(&self) -> String {
+        format!("{}/{}", mirror::url(), self.0)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -307,16 +686,22 @@ pub struct Details {
     pub downloads: Downloads,
     pub likes: Likes,
     pub architecture: Option<String>,
-    pub parameters: Parameters,
+    pub parameters: Option<Parameters>,
 }
 
 impl Details {
-    pub async fn fetch(id: EndpointId) -> Result<Self, Error> {
+    pub async fn fetch(id: EndpointId, hub: Hub) -> Result<Self, Error> {
         let id = match id {
             EndpointId::Local(d) => d,
-            _ => unreachable!(),
+            id @ EndpointId::Remote { .. } => {
+                return Err(Error::ModelNotFound(id.to_string(), capture!()));
+            }
         };
 
+        if hub == Hub::ModelScope {
+            return modelscope::details(&id).await;
+        }
+
         #[derive(Deserialize)]
         struct Response {
             #[serde(rename = "lastModified")]
@@ -333,17 +718,15 @@ impl Details {
             total: u64,
         }
 
-        let client = reqwest::Client::new();
-        let request = client.get(format!("{}/models/{}", API_URL, id.0));
-
-        let response: Response = request.send().await?.error_for_status()?.json().await?;
+        let response: Response =
+            hugging_face::json(format!("{}/models/{}", mirror::api(), id.0)).await?;
 
         Ok(Self {
             last_modified: response.last_modified,
             downloads: response.downloads,
             likes: response.likes,
             architecture: response.gguf.architecture,
-            parameters: Parameters(response.gguf.total),
+            parameters: Some(Parameters(response.gguf.total)),
         })
     }
 }
@@ -397,6 +780,8 @@ pub struct File {
     pub name: String,
     #[serde(default)]
     pub size: Option<Size>,
+    #[serde(default)]
+    pub hub: Hub,
 }
 
 impl File {
@@ -427,6 +812,36 @@ impl FileAndAPI {
             panic!("FileOrAPI is empty");
         }
     }
+
+    // Local models are served without a projector
+    pub fn supports_vision(&self) -> bool {
+        const VISION: &[&str] = &[
+            "gpt-4o",
+            "gpt-4.1",
+            "gpt-5",
+            "claude",
+            "gemini",
+            "vision",
+            "-vl",
+            "llava",
+            "pixtral",
+            "gemma-3",
+            "llama-4",
+            "minicpm-v",
+        ];
+
+        if self.file.is_some() {
+            return false;
+        }
+
+        let Some(api) = &self.api else {
+            return false;
+        };
+
+        let id = api.endpoint_id.slash_id().0.to_lowercase();
+
+        VISION.iter().any(|name| id.contains(name))
+    }
 }
 
 impl PartialEq for ModelOnline {
@@ -485,9 +900,10 @@ impl FileAndAPI {
 }
 
 impl File {
-    pub async fn list(id: Id) -> Result<Files, Error> {
-        let client = reqwest::Client::new();
-        let request = client.get(format!("{}/models/{}/tree/main", API_URL, id.0));
+    pub async fn list(id: Id, hub: Hub) -> Result<Files, Error> {
+        if hub == Hub::ModelScope {
+            return modelscope::files(&id).await;
+        }
 
         #[derive(Debug, Deserialize)]
         struct Entry {
@@ -496,15 +912,28 @@ impl File {
             size: u64,
         }
 
-        let entries: Vec<Entry> = request.send().await?.error_for_status()?.json().await?;
+        let entries: Vec<Entry> =
+            hugging_face::json(format!("{}/models/{}/tree/main", mirror::api(), id.0)).await?;
+
+        Ok(Self::group(
+            &id,
+            Hub::HuggingFace,
+            entries
+                .into_iter()
+                .filter(|entry| entry.r#type == "file")
+                .map(|entry| (entry.path, entry.size)),
+        ))
+    }
+
+    fn group(id: &Id, hub: Hub, entries: impl IntoIterator<Item = (String, u64)>) -> Files {
         let mut files: BTreeMap<Bits, Vec<File>> = BTreeMap::new();
 
-        for entry in entries {
-            if entry.r#type != "file" || !entry.path.ends_with(".gguf") {
+        for (path, size) in entries {
+            if !path.ends_with(".gguf") {
                 continue;
             }
 
-            let file_stem = entry.path.trim_end_matches(".gguf");
+            let file_stem = path.trim_end_matches(".gguf");
             let variant = file_stem.rsplit(['-', '.']).next().unwrap_or(file_stem);
             let precision = variant
                 .split('_')
@@ -525,12 +954,13 @@ impl File {
 
             files.push(File {
                 model: id.clone(),
-                name: entry.path,
-                size: Some(Size(entry.size)),
+                name: path,
+                size: Some(Size(size)),
+                hub,
             })
         }
 
-        Ok(files)
+        files
     }
 
     pub async fn download<'a>(
@@ -559,21 +989,61 @@ impl File {
             return Ok(model_path);
         }
 
-        let url = format!(
-            "{}/{id}/resolve/main/{filename}?download=true",
-            HF_URL,
-            id = self.model.0,
-            filename = self.name
-        );
+        let url = match self.hub {
+            Hub::HuggingFace => format!(
+                "{}/{id}/resolve/main/{filename}?download=true",
+                mirror::url(),
+                id = self.model.0,
+                filename = self.name
+            ),
+            Hub::ModelScope => modelscope::download_url(self),
+        };
 
         let temp_path = model_path.with_extension("tmp");
 
         request::download_file(url, &temp_path).run(sender).await?;
         fs::rename(temp_path, &model_path).await?;
 
+        hook::fire(
+            hook::Event::DownloadFinished,
+            serde_json::json!({
+                "model": self.model.0,
+                "file": self.name,
+                "path": model_path,
+            }),
+        );
+
         Ok(model_path)
     }
 
+    pub async fn import(source: PathBuf, directory: Directory) -> Result<Self, Error> {
+        let (Some(name), Some(stem)) = (source.file_name(), source.file_stem()) else {
+            return Err(
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "not a model file").into(),
+            );
+        };
+
+        let model = Id(format!("local/{}", stem.display()));
+        let name = name.display().to_string();
+
+        let directory = directory.0.join(&model.0);
+        let model_path = directory.join(&name);
+
+        fs::create_dir_all(&directory).await?;
+
+        if !fs::try_exists(&model_path).await? && fs::hard_link(&source, &model_path).await.is_err()
+        {
+            let _ = fs::copy(&source, &model_path).await?;
+        }
+
+        Ok(Self {
+            model,
+            name,
+            size: Some(Size(fs::metadata(&model_path).await?.len())),
+            hub: Hub::default(),
+        })
+    }
+
     pub fn decode(value: decoder::Value) -> decoder::Result<Self> {
         use decoder::decode::{map, string, u64};
 
@@ -583,13 +1053,24 @@ impl File {
             model: Id(file.required("model", string)?),
             name: file.required("name", string)?,
             size: file.optional("size", u64)?.map(Size),
+            hub: file.optional("hub", Hub::decode)?.unwrap_or_default(),
         })
     }
 
     pub fn encode(self) -> decoder::Value {
-        use decoder::encode::{map, string};
+        use decoder::encode::{map, string, u64};
+
+        let mut file = vec![("model", string(self.model.0)), ("name", string(self.name))];
+
+        if let Some(Size(size)) = self.size {
+            file.push(("size", u64(size)));
+        }
+
+        if self.hub != Hub::default() {
+            file.push(("hub", self.hub.encode()));
+        }
 
-        map([("model", string(self.model.0)), ("name", string(self.name))]).into()
+        map(file).into()
     }
 
     pub fn variant(&self) -> Option<&str> {
@@ -602,6 +1083,12 @@ impl File {
     pub fn relative_path(&self) -> PathBuf {
         PathBuf::from(&self.model.0).join(&self.name)
     }
+
+    pub async fn delete(self, directory: Directory) -> Result<(), Error> {
+        fs::remove_file(directory.0.join(self.relative_path())).await?;
+
+        Ok(())
+    }
 }
 
 impl fmt::Display for File {
@@ -626,7 +1113,7 @@ pub struct Size(u64);
 
 impl fmt::Display for Size {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.0.ilog10() {
+        match self.0.checked_ilog10().unwrap_or_default() {
             0..3 => write!(f, "{} B", self.0),
             3..6 => write!(f, "{} KB", self.0 / 1000),
             6..9 => write!(f, "{} MB", self.0 / 1_000_000),
@@ -636,6 +1123,12 @@ impl fmt::Display for Size {
     }
 }
 
+impl From<u64> for Size {
+    fn from(bytes: u64) -> Self {
+        Self(bytes)
+    }
+}
+
 impl PartialEq<u64> for Size {
     fn eq(&self, other: &u64) -> bool {
         &self.0 == other
@@ -651,7 +1144,7 @@ impl Readme {
     pub async fn fetch(id: Id) -> Result<Self, Error> {
         let response = reqwest::get(format!(
             "{url}/{id}/raw/main/README.md",
-            url = HF_URL,
+            url = mirror::url(),
             id = id.0
         ))
         .await?;
@@ -662,14 +1155,17 @@ impl Readme {
     }
 }
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time;
 #[derive(Debug, Clone, Default)]
 pub struct Library {
     directory: Directory,
+    shared: Option<Directory>,
+    shared_files: HashSet<EndpointId>,
     pub api_src: HashMap<APIType, APIAccess>,
-    pub files: HashMap<EndpointId, FileOrAPI>,
+    pub files: Arc<HashMap<EndpointId, FileOrAPI>>,
     pub bookmarks: Vec<EndpointId>,
+    pub failover: Vec<Failover>,
 }
 
 #[derive(Clone, Serialize, Deserialize, Default)]
@@ -677,6 +1173,15 @@ pub struct APIBookmarks {
     pub api_src: HashMap<APIType, APIAccess>,
     pub apis: HashMap<EndpointId, ModelOnline>,
     pub bookmarks: Vec<EndpointId>,
+    #[serde(default)]
+    pub failover: Vec<Failover>,
+}
+
+// Local files can only be the primary endpoint, since they need to be booted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Failover {
+    pub primary: EndpointId,
+    pub fallbacks: Vec<ModelOnline>,
 }
 
 #[derive(Hash, PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
@@ -694,55 +1199,45 @@ impl EndpointId {
     }
 }
 
+impl fmt::Display for EndpointId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local(id) => write!(f, "{} (local)", id.0),
+            Self::Remote { api_type, id } => write!(f, "{} ({api_type:?})", id.0),
+        }
+    }
+}
+
 impl Library {
-    /// Only scans local model files and basic init
-    pub async fn scan(mut self: Arc<Self>, settings: Settings) -> Result<Arc<Self>, Error> {
-        let lib = Arc::make_mut(&mut self);
+    async fn scan_with(
+        &mut self,
+        settings: Settings,
+        sender: sipper::Sender<ScanProgress>,
+    ) -> Result<(), Error> {
+        let lib = self;
         let directory = &settings.library;
         let bookmarks_file = settings.bookmarks();
 
-        let mut files: HashMap<EndpointId, FileOrAPI> = HashMap::new();
         let directory = directory.as_ref();
         fs::create_dir_all(directory).await?;
 
-        let mut list = fs::read_dir(directory).await?;
-
-        while let Some(author) = list.next_entry().await? {
-            if !author.file_type().await?.is_dir() {
-                continue;
-            }
-
-            let mut directory = fs::read_dir(author.path()).await?;
-
-            while let Some(model) = directory.next_entry().await? {
-                if !model.file_type().await?.is_dir() {
-                    continue;
-                }
-
-                let mut directory = fs::read_dir(model.path()).await?;
+        let files = scan_files(directory, sender.clone()).await?;
+
+        // The shared library is read-only and may be unreachable (e.g. an unmounted drive)
+        let shared = match &settings.shared_library {
+            Some(shared) => scan_files(shared.path(), sender)
+                .await
+                .unwrap_or_else(|error| {
+                    log::warn!("Shared library unavailable: {error}");
+                    HashMap::new()
+                }),
+            None => HashMap::new(),
+        };
 
-                while let Some(file) = directory.next_entry().await? {
-                    if !file.file_type().await?.is_file()
-                        || file.path().extension().unwrap_or_default() != "gguf"
-                    {
-                        continue;
-                    }
-                    let id = Id(format!(
-                        "{}/{}",
-                        author.file_name().display(),
-                        model.file_name().display(),
-                    ));
-                    let f_id = EndpointId::Local(id.clone());
-                    let file = FileOrAPI::File(File {
-                        model: id,
-                        name: file.file_name().display().to_string(),
-                        size: Some(Size(file.metadata().await?.len())),
-                    });
-
-                    let _ = files.insert(f_id, file);
-                }
-            }
-        }
+        let shared: HashMap<_, _> = shared
+            .into_iter()
+            .filter(|(id, _)| !files.contains_key(id))
+            .collect();
 
         info!("reading {:?}", &bookmarks_file);
         let bookmarks: APIBookmarks = match fs::read_to_string(&bookmarks_file).await {
@@ -751,31 +1246,45 @@ impl Library {
         };
 
         lib.directory = Directory(directory.to_path_buf());
-        lib.files.extend(
+        lib.shared = settings.shared_library.clone();
+        let lib_files = Arc::make_mut(&mut lib.files);
+        lib_files.retain(|id, _| !lib.shared_files.contains(id));
+        lib.shared_files = shared.keys().cloned().collect();
+        lib_files.extend(
             bookmarks
                 .apis
                 .into_iter()
                 .map(|(id, api)| (id, FileOrAPI::API(api)))
+                .chain(shared)
                 .chain(files),
         );
 
         lib.api_src = bookmarks.api_src;
         lib.bookmarks = bookmarks.bookmarks;
+        lib.failover = bookmarks.failover;
 
         let nano_config = OpenAIConfig::new()
             .with_api_base("https://nano-gpt.com/api/v1")
             .with_api_key(dotenvy::var("NANOGPT_KEY").expect("provide key"));
         let api = APIAccess {
             openai_compat: Some(nano_config.into()),
+            azure: None,
+            ssh: None,
+            api_key: None,
+            base_url: None,
             kind: model::APIType::NanoGPT,
         };
         let _ = lib.api_src.insert(model::APIType::NanoGPT, api);
 
-        info!("{} {}", lib.files.len(), self.files.len());
-        Ok(self)
+        lib.configure_azure(&settings.azure);
+        lib.configure_hosted(&settings.keys);
+        lib.configure_ssh(&settings.ssh);
+
+        info!("{} model files", lib.files.len());
+        Ok(())
     }
 
-    pub async fn save_bookmarks(self: Arc<Self>, settings: Settings) -> Result<Arc<Self>, Error> {
+    pub async fn save_bookmarks(&self, settings: Settings) -> Result<(), Error> {
         let bookmarks_file = settings.bookmarks();
         let api_bookmarks = APIBookmarks {
             api_src: self.api_src.clone(),
@@ -788,17 +1297,16 @@ impl Library {
                 })
                 .collect(),
             bookmarks: self.bookmarks.clone(),
+            failover: self.failover.clone(),
         };
         let json = serde_json::to_string_pretty(&api_bookmarks)?;
         info!("writing bookmarks to {:?}", &bookmarks_file);
-        fs::write(bookmarks_file, json).await?;
-
-        Ok(self)
+        persistence::write(bookmarks_file, json).await
     }
 
     pub async fn status_check(self: Arc<Self>, id: EndpointId) -> Result<(), Error> {
         if let Some(FileOrAPI::API(api)) = self.files.get(&id) {
-            let _ = api.state_check.write(api.check().await?);
+            let _ = api.refresh_status().await?;
         } else {
             info!("skipped");
         }
@@ -806,14 +1314,140 @@ impl Library {
         Ok(())
     }
 
+    pub fn fallbacks(&self, primary: &EndpointId) -> &[ModelOnline] {
+        self.failover
+            .iter()
+            .find(|failover| &failover.primary == primary)
+            .map(|failover| failover.fallbacks.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn set_fallbacks(&mut self, primary: EndpointId, fallbacks: Vec<ModelOnline>) {
+        self.failover.retain(|failover| failover.primary != primary);
+
+        if !fallbacks.is_empty() {
+            self.failover.push(Failover { primary, fallbacks });
+        }
+    }
+
+    pub fn configure_azure(&mut self, azure: &Azure) {
+        if azure.is_configured() {
+            let _ = self.api_src.insert(
+                APIType::AzureOpenAI,
+                APIAccess {
+                    openai_compat: None,
+                    azure: Some(azure.clone()),
+                    ssh: None,
+                    api_key: None,
+                    base_url: None,
+                    kind: APIType::AzureOpenAI,
+                },
+            );
+        } else {
+            let _ = self.api_src.remove(&APIType::AzureOpenAI);
+        }
+    }
+
+    pub fn configure_ssh(&mut self, ssh: &Ssh) {
+        if ssh.is_configured() {
+            let _ = self.api_src.insert(
+                APIType::Ssh,
+                APIAccess {
+                    openai_compat: None,
+                    azure: None,
+                    ssh: Some(ssh.clone()),
+                    api_key: None,
+                    base_url: None,
+                    kind: APIType::Ssh,
+                },
+            );
+        } else {
+            let _ = self.api_src.remove(&APIType::Ssh);
+        }
+    }
+
+    pub fn configure_hosted(&mut self, keys: &hosted::Keys) {
+        for kind in [APIType::Groq, APIType::Together, APIType::Mistral] {
+            match keys.get(&kind) {
+                Some(key) => {
+                    let _ = self.api_src.insert(
+                        kind.clone(),
+                        APIAccess {
+                            openai_compat: None,
+                            azure: None,
+                            ssh: None,
+                            api_key: Some(key.to_owned()),
+                            base_url: None,
+                            kind,
+                        },
+                    );
+                }
+                None => {
+                    let _ = self.api_src.remove(&kind);
+                }
+            }
+        }
+    }
+
     pub fn directory(&self) -> &Directory {
         &self.directory
     }
+
+    pub fn shared(&self) -> Option<&Directory> {
+        self.shared.as_ref()
+    }
+
+    pub fn is_shared(&self, id: &EndpointId) -> bool {
+        self.shared_files.contains(id)
+    }
+
+    // Files missing from both libraries belong to the user one
+    pub fn directory_of(&self, file: &File) -> &Directory {
+        match &self.shared {
+            Some(shared)
+                if !self.directory.0.join(file.relative_path()).is_file()
+                    && shared.0.join(file.relative_path()).is_file() =>
+            {
+                shared
+            }
+            _ => &self.directory,
+        }
+    }
+}
+
+async fn scan_files(
+    directory: &Path,
+    sender: sipper::Sender<ScanProgress>,
+) -> Result<HashMap<EndpointId, FileOrAPI>, Error> {
+    let files = index::scan(directory, sender).await?;
+
+    Ok(files
+        .into_iter()
+        .map(|(author, model, name, size)| {
+            let id = Id(format!("{author}/{model}"));
+
+            (
+                EndpointId::Local(id.clone()),
+                FileOrAPI::File(File {
+                    model: id,
+                    name,
+                    size: Some(Size(size)),
+                    hub: Hub::default(),
+                }),
+            )
+        })
+        .collect())
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Directory(PathBuf);
 
+impl From<PathBuf> for Directory {
+    fn from(path: PathBuf) -> Self {
+        Self(path)
+    }
+}
+
 impl Directory {
     pub fn decode(value: Value) -> decoder::Result<Self> {
         decode::string(value).map(PathBuf::from).map(Self)
@@ -834,7 +1468,7 @@ impl Directory {
 
 impl Default for Directory {
     fn default() -> Self {
-        Self(directory::data().join("models"))
+        Self(directory::base_data().join("models"))
     }
 }
 
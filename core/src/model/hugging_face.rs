@@ -0,0 +1,163 @@
+use crate::Error;
+
+use futures::future::{BoxFuture, FutureExt, Shared};
+use reqwest::{header, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use thiserror::capture;
+use tokio::time;
+
+use std::collections::HashMap;
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+const RETRIES: u32 = 5;
+
+const BACKOFF: Duration = Duration::from_secs(1);
+
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+
+static RESUME_AT: Mutex<Option<Instant>> = Mutex::new(None);
+
+static IN_FLIGHT: LazyLock<Mutex<HashMap<String, Fetch>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static SEARCH: AtomicU64 = AtomicU64::new(0);
+
+type Fetch = Shared<BoxFuture<'static, Result<Arc<[u8]>, Error>>>;
+
+pub fn backoff() -> Option<Duration> {
+    let resume_at = (*resume_at())?;
+
+    resume_at
+        .checked_duration_since(Instant::now())
+        .filter(|remaining| !remaining.is_zero())
+}
+
+pub(crate) fn get(url: impl AsRef<str>) -> RequestBuilder {
+    CLIENT.get(url.as_ref())
+}
+
+pub(crate) async fn json<T: DeserializeOwned>(url: String) -> Result<T, Error> {
+    let bytes = fetch(url).await?;
+
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub(crate) async fn send(request: RequestBuilder) -> Result<Response, Error> {
+    let mut backoff = BACKOFF;
+
+    for _ in 0..RETRIES {
+        wait().await;
+
+        let Some(attempt) = request.try_clone() else {
+            break;
+        };
+
+        let response = attempt.send().await?;
+
+        if response.status() != StatusCode::TOO_MANY_REQUESTS {
+            return Ok(response.error_for_status()?);
+        }
+
+        let delay = retry_after(&response).unwrap_or(backoff).min(MAX_BACKOFF);
+        log::warn!("Hugging Face rate limit hit, retrying in {delay:?}");
+
+        let mut resume_at = resume_at();
+        let next = Instant::now() + delay;
+
+        if resume_at.is_none_or(|current| current < next) {
+            *resume_at = Some(next);
+        }
+
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+
+    wait().await;
+
+    Ok(request.send().await?.error_for_status()?)
+}
+
+pub(crate) fn search() -> Search {
+    Search(SEARCH.fetch_add(1, atomic::Ordering::Relaxed) + 1)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Search(u64);
+
+impl Search {
+    pub async fn send(self, request: RequestBuilder) -> Result<Response, Error> {
+        wait().await;
+        self.check()?;
+
+        send(request).await
+    }
+
+    pub fn check(self) -> Result<(), Error> {
+        if SEARCH.load(atomic::Ordering::Relaxed) == self.0 {
+            Ok(())
+        } else {
+            Err(Error::Cancelled(capture!()))
+        }
+    }
+}
+
+fn fetch(url: String) -> Fetch {
+    let mut in_flight = in_flight();
+
+    if let Some(fetch) = in_flight.get(&url) {
+        return fetch.clone();
+    }
+
+    let fetch = {
+        let url = url.clone();
+
+        async move {
+            let result: Result<Arc<[u8]>, Error> = async {
+                let response = send(get(&url)).await?;
+
+                Ok(Arc::from(response.bytes().await?.as_ref()))
+            }
+            .await;
+
+            let _ = in_flight().remove(&url);
+
+            result
+        }
+        .boxed()
+        .shared()
+    };
+
+    let _ = in_flight.insert(url, fetch.clone());
+
+    fetch
+}
+
+async fn wait() {
+    while let Some(remaining) = backoff() {
+        time::sleep(remaining).await;
+    }
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds = response
+        .headers()
+        .get(header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+
+    Some(Duration::from_secs(seconds))
+}
+
+fn resume_at() -> MutexGuard<'static, Option<Instant>> {
+    RESUME_AT.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn in_flight() -> MutexGuard<'static, HashMap<String, Fetch>> {
+    IN_FLIGHT.lock().unwrap_or_else(PoisonError::into_inner)
+}
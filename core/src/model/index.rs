@@ -0,0 +1,209 @@
+use crate::directory;
+use crate::Error;
+
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use sipper::Sender;
+use tokio::fs;
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const CONCURRENCY: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub scanned: usize,
+    pub total: usize,
+}
+
+pub type Files = Vec<(String, String, String, u64)>;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Index {
+    libraries: HashMap<PathBuf, Library>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Library {
+    authors: BTreeMap<String, Listing<String>>,
+    models: BTreeMap<String, Listing<(String, u64)>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Listing<T> {
+    modified: SystemTime,
+    entries: Vec<T>,
+}
+
+pub async fn scan(root: &Path, mut sender: Sender<Progress>) -> Result<Files, Error> {
+    let mut index = Index::fetch().await;
+    let cached = index.libraries.remove(root).unwrap_or_default();
+
+    let mut authors = Vec::new();
+    let mut entries = fs::read_dir(root).await?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_type().await?.is_dir() {
+            authors.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+
+    let authors: Vec<(String, Listing<String>)> = stream::iter(authors)
+        .map(|author| {
+            let cached = cached.authors.get(&author);
+            let path = root.join(&author);
+
+            async move {
+                let listing = list(&path, cached, |name, kind, _path| async move {
+                    Ok(kind.is_dir().then_some(name))
+                })
+                .await?;
+
+                Ok::<_, Error>((author, listing))
+            }
+        })
+        .buffer_unordered(CONCURRENCY)
+        .filter_map(|result| async move { skip_missing(result) })
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<_, _>>()?;
+
+    let models: Vec<(String, String)> = authors
+        .iter()
+        .flat_map(|(author, listing)| {
+            listing
+                .entries
+                .iter()
+                .map(move |model| (author.clone(), model.clone()))
+        })
+        .collect();
+
+    let total = models.len();
+    let mut scanned = 0;
+
+    sender.send(Progress { scanned, total }).await;
+
+    let mut results = stream::iter(models)
+        .map(|(author, model)| {
+            let key = format!("{author}/{model}");
+            let cached = cached.models.get(&key);
+            let path = root.join(&author).join(&model);
+
+            async move {
+                let listing = list(&path, cached, |name, kind, path| async move {
+                    if !kind.is_file() || path.extension().unwrap_or_default() != "gguf" {
+                        return Ok(None);
+                    }
+
+                    Ok(Some((name, fs::metadata(&path).await?.len())))
+                })
+                .await?;
+
+                Ok::<_, Error>((key, author, model, listing))
+            }
+        })
+        .buffer_unordered(CONCURRENCY);
+
+    let mut library = Library {
+        authors: authors.into_iter().collect(),
+        models: BTreeMap::new(),
+    };
+
+    let mut files = Files::new();
+
+    while let Some(result) = results.next().await {
+        scanned += 1;
+        sender.send(Progress { scanned, total }).await;
+
+        let Some((key, author, model, listing)) = skip_missing(result).transpose()? else {
+            continue;
+        };
+
+        files.extend(
+            listing
+                .entries
+                .iter()
+                .map(|(name, size)| (author.clone(), model.clone(), name.clone(), *size)),
+        );
+
+        let _ = library.models.insert(key, listing);
+    }
+
+    let _ = index.libraries.insert(root.to_path_buf(), library);
+    index.save().await;
+
+    Ok(files)
+}
+
+async fn list<T, F, Fut>(
+    path: &Path,
+    cached: Option<&Listing<T>>,
+    filter: F,
+) -> Result<Listing<T>, Error>
+where
+    T: Clone + Ord,
+    F: Fn(String, std::fs::FileType, PathBuf) -> Fut,
+    Fut: Future<Output = Result<Option<T>, Error>>,
+{
+    let modified = fs::metadata(path).await?.modified()?;
+
+    if let Some(cached) = cached.filter(|cached| cached.modified == modified) {
+        return Ok(cached.clone());
+    }
+
+    let mut entries = Vec::new();
+    let mut directory = fs::read_dir(path).await?;
+
+    while let Some(entry) = directory.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        if let Some(value) = filter(name, entry.file_type().await?, entry.path()).await? {
+            entries.push(value);
+        }
+    }
+
+    entries.sort();
+
+    Ok(Listing { modified, entries })
+}
+
+fn skip_missing<T>(result: Result<T, Error>) -> Option<Result<T, Error>> {
+    match result {
+        Err(Error::IOFailed(error, ..)) if error.kind() == io::ErrorKind::NotFound => None,
+        result => Some(result),
+    }
+}
+
+impl Index {
+    async fn fetch() -> Self {
+        match fs::read(path()).await {
+            Ok(json) => serde_json::from_slice(&json).unwrap_or_default(),
+            Err(_) => Self::default(),
+        }
+    }
+
+    async fn save(&self) {
+        let Ok(json) = serde_json::to_vec(self) else {
+            return;
+        };
+
+        let path = path();
+
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent).await;
+        }
+
+        if let Err(error) = fs::write(path, json).await {
+            log::warn!("Library index could not be stored: {error}");
+        }
+    }
+}
+
+fn path() -> PathBuf {
+    directory::base_data().join("library-index.json")
+}
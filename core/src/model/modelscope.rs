@@ -0,0 +1,155 @@
+use crate::model::{Details, Downloads, File, Files, HFModel, Hub, Id, Likes};
+use crate::Error;
+
+use serde::Deserialize;
+
+const URL: &str = "https://modelscope.cn";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Response<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Model {
+    #[serde(default)]
+    downloads: u64,
+    #[serde(default)]
+    stars: u64,
+    #[serde(default)]
+    last_updated_time: i64,
+}
+
+pub fn page(id: &Id) -> String {
+    format!("{URL}/models/{}", id.0)
+}
+
+pub fn download_url(file: &File) -> String {
+    format!(
+        "{URL}/models/{id}/resolve/master/{name}",
+        id = file.model.0,
+        name = file.name
+    )
+}
+
+pub async fn search(query: String) -> Result<Vec<HFModel>, Error> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Data {
+        model: Models,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Models {
+        #[serde(default)]
+        models: Vec<Entry>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Entry {
+        name: String,
+        path: String,
+        #[serde(flatten)]
+        model: Model,
+    }
+
+    let response: Response<Data> = reqwest::Client::new()
+        .put(format!("{URL}/api/v1/dolphin/models"))
+        .json(&serde_json::json!({
+            "Name": query,
+            "PageSize": 100,
+            "PageNumber": 1,
+            "SortBy": "Default",
+            "Target": "",
+            "SingleCriterion": [],
+            "Criterion": [{
+                "category": "libraries",
+                "predicate": "contains",
+                "values": ["gguf"],
+            }],
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(response
+        .data
+        .model
+        .models
+        .into_iter()
+        .map(|entry| HFModel {
+            id: Id(format!("{}/{}", entry.path, entry.name)),
+            last_modified: last_modified(entry.model.last_updated_time),
+            downloads: Downloads(entry.model.downloads),
+            likes: Likes(entry.model.stars),
+            hub: Hub::ModelScope,
+        })
+        .collect())
+}
+
+pub async fn details(id: &Id) -> Result<Details, Error> {
+    let response: Response<Model> = reqwest::get(format!("{URL}/api/v1/models/{}", id.0))
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    // ModelScope does not read the metadata of GGUF files
+    Ok(Details {
+        last_modified: last_modified(response.data.last_updated_time),
+        downloads: Downloads(response.data.downloads),
+        likes: Likes(response.data.stars),
+        architecture: None,
+        parameters: None,
+    })
+}
+
+pub async fn files(id: &Id) -> Result<Files, Error> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Data {
+        #[serde(default)]
+        files: Vec<Entry>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Entry {
+        path: String,
+        r#type: String,
+        #[serde(default)]
+        size: u64,
+    }
+
+    let response: Response<Data> = reqwest::Client::new()
+        .get(format!("{URL}/api/v1/models/{}/repo/files", id.0))
+        .query(&[("Revision", "master"), ("Recursive", "true")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(File::group(
+        id,
+        Hub::ModelScope,
+        response
+            .data
+            .files
+            .into_iter()
+            .filter(|entry| entry.r#type == "blob")
+            .map(|entry| (entry.path, entry.size)),
+    ))
+}
+
+fn last_modified(timestamp: i64) -> chrono::DateTime<chrono::Local> {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|time| time.with_timezone(&chrono::Local))
+        .unwrap_or_else(chrono::Local::now)
+}
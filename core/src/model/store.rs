@@ -0,0 +1,96 @@
+use crate::model::{Library, ScanProgress};
+use crate::{Error, Settings};
+
+use futures::stream::{self, Stream};
+use rcu_cell::ArcRCUNonNull;
+use sipper::{sipper, Straw};
+use tokio::sync::broadcast;
+
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, PoisonError};
+
+const BACKLOG: usize = 64;
+
+#[derive(Clone)]
+pub struct Store {
+    library: ArcRCUNonNull<Library>,
+    writer: Arc<Mutex<()>>,
+    changes: Arc<broadcast::Sender<Change>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Scanned,
+    Bookmarks,
+    Files,
+    Providers,
+    Failover,
+}
+
+impl Store {
+    pub fn new(library: Library) -> Self {
+        let (changes, _) = broadcast::channel(BACKLOG);
+
+        Self {
+            library: ArcRCUNonNull::new(library),
+            writer: Arc::new(Mutex::new(())),
+            changes: Arc::new(changes),
+        }
+    }
+
+    pub fn snapshot(&self) -> Arc<Library> {
+        self.library.read()
+    }
+
+    // The model files are only copied when the change touches them
+    pub fn update<T>(&self, change: Change, f: impl FnOnce(&mut Library) -> T) -> T {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let mut library = Library::clone(&self.snapshot());
+        let output = f(&mut library);
+
+        let _ = self.library.write(library);
+        let _ = self.changes.send(change);
+
+        output
+    }
+
+    pub fn scan(&self, settings: Settings) -> impl Straw<(), ScanProgress, Error> {
+        let store = self.clone();
+
+        sipper(move |sender| async move {
+            let mut library = Library::clone(&store.snapshot());
+            library.scan_with(settings, sender).await?;
+
+            store.update(Change::Scanned, |current| *current = library);
+
+            Ok(())
+        })
+    }
+
+    pub async fn save_bookmarks(self, settings: Settings) -> Result<(), Error> {
+        self.snapshot().save_bookmarks(settings).await
+    }
+
+    pub fn changes(&self) -> impl Stream<Item = Change> {
+        stream::unfold(self.changes.subscribe(), |mut receiver| async move {
+            match receiver.recv().await {
+                Ok(change) => Some((change, receiver)),
+                Err(broadcast::error::RecvError::Lagged(_)) => Some((Change::Scanned, receiver)),
+                Err(broadcast::error::RecvError::Closed) => None,
+            }
+        })
+    }
+}
+
+impl Default for Store {
+    fn default() -> Self {
+        Self::new(Library::default())
+    }
+}
+
+impl Hash for Store {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.changes).hash(state);
+    }
+}
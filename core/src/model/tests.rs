@@ -0,0 +1,38 @@
+use super::{APIType, Details, EndpointId, File, Hub, Id, Size};
+use crate::Error;
+
+use proptest::prelude::*;
+
+fn file() -> impl Strategy<Value = File> {
+    (
+        "[A-Za-z0-9_.-]{1,16}/[A-Za-z0-9_.-]{1,24}",
+        "[A-Za-z0-9_.-]{1,24}\\.gguf",
+        proptest::option::of(0..1u64 << 50),
+        prop_oneof![Just(Hub::HuggingFace), Just(Hub::ModelScope)],
+    )
+        .prop_map(|(model, name, size, hub)| File {
+            model: Id(model),
+            name,
+            size: size.map(Size),
+            hub,
+        })
+}
+
+proptest! {
+    #[test]
+    fn file_round_trip(file in file()) {
+        prop_assert_eq!(File::decode(file.clone().encode()).unwrap(), file);
+    }
+}
+
+#[tokio::test]
+async fn remote_details_are_not_found() {
+    let id = EndpointId::Remote {
+        api_type: APIType::NanoGPT,
+        id: Id("removed/model".to_owned()),
+    };
+
+    let details = Details::fetch(id, Hub::HuggingFace).await;
+
+    assert!(matches!(details, Err(Error::ModelNotFound(..))));
+}
@@ -0,0 +1,84 @@
+use crate::attachment::Attachment;
+use crate::Error;
+
+use decoder::{decode, encode, Value};
+use thiserror::capture;
+use tokio::process;
+
+use std::process::Stdio;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ocr {
+    pub program: String,
+    pub languages: String,
+}
+
+impl Default for Ocr {
+    fn default() -> Self {
+        Self {
+            program: "tesseract".to_owned(),
+            languages: "eng".to_owned(),
+        }
+    }
+}
+
+impl Ocr {
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut ocr = decode::map(value)?;
+        let default = Self::default();
+
+        Ok(Self {
+            program: ocr
+                .optional("program", decode::string)?
+                .unwrap_or(default.program),
+            languages: ocr
+                .optional("languages", decode::string)?
+                .unwrap_or(default.languages),
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        encode::map([
+            ("program", encode::string(&self.program)),
+            ("languages", encode::string(&self.languages)),
+        ])
+        .into_value()
+    }
+}
+
+pub async fn recognize(ocr: Ocr, mut attachment: Attachment) -> Result<Attachment, Error> {
+    let mut command = process::Command::new(ocr.program.trim());
+
+    let _ = command.arg(attachment.path()).arg("stdout");
+
+    if !ocr.languages.trim().is_empty() {
+        let _ = command.arg("-l").arg(ocr.languages.trim());
+    }
+
+    let output = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        return Err(Error::OcrFailed(
+            stderr
+                .lines()
+                .rev()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or("tesseract exited with an error")
+                .trim()
+                .to_owned(),
+            capture!(),
+        ));
+    }
+
+    attachment.text = Some(String::from_utf8_lossy(&output.stdout).trim().to_owned());
+
+    Ok(attachment)
+}
@@ -0,0 +1,96 @@
+use crate::Error;
+
+use tokio::task;
+use uuid::Uuid;
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard, PoisonError};
+
+static WRITING: LazyLock<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub async fn write(path: impl AsRef<Path>, bytes: impl Into<Vec<u8>>) -> Result<(), Error> {
+    let path = path.as_ref().to_path_buf();
+    let bytes = bytes.into();
+
+    task::spawn_blocking(move || {
+        let turn = turn(&path);
+        let result = {
+            let _turn = turn.lock().unwrap_or_else(PoisonError::into_inner);
+
+            replace(&path, &bytes)
+        };
+
+        drop(turn);
+        finish(&path);
+
+        result
+    })
+    .await??;
+
+    Ok(())
+}
+
+fn replace(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let directory = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(directory)?;
+
+    let temporary = temporary(path);
+
+    let result = (|| {
+        let mut file = File::create(&temporary)?;
+
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&temporary, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+
+    result?;
+
+    // The rename only survives a crash once the directory is flushed too
+    #[cfg(unix)]
+    File::open(directory)?.sync_all()?;
+
+    Ok(())
+}
+
+// Other processes may be saving the same file, so no two writes share one
+fn temporary(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(
+        ".{}-{}.tmp",
+        process::id(),
+        Uuid::new_v4().simple()
+    ));
+
+    path.with_file_name(name)
+}
+
+fn turn(path: &Path) -> Arc<Mutex<()>> {
+    writing().entry(path.to_path_buf()).or_default().clone()
+}
+
+fn finish(path: &Path) {
+    let mut writing = writing();
+
+    if writing
+        .get(path)
+        .is_some_and(|turn| Arc::strong_count(turn) == 1)
+    {
+        let _ = writing.remove(path);
+    }
+}
+
+fn writing() -> MutexGuard<'static, HashMap<PathBuf, Arc<Mutex<()>>>> {
+    WRITING.lock().unwrap_or_else(PoisonError::into_inner)
+}
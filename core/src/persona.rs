@@ -0,0 +1,166 @@
+use crate::assistant::Parameters;
+use crate::directory;
+use crate::Error;
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Persona {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub system_prompt: String,
+    #[serde(default)]
+    pub parameters: Parameters,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_class: Option<String>,
+}
+
+impl Persona {
+    pub fn builtins() -> Vec<Self> {
+        vec![
+            Self {
+                name: "Coder".to_owned(),
+                description: "Writes, reviews, and explains code.".to_owned(),
+                system_prompt: "You are an expert software engineer. \
+                    Write correct, idiomatic, and well-structured code. \
+                    Prefer concise explanations and always use fenced code blocks \
+                    with the language specified."
+                    .to_owned(),
+                parameters: Parameters {
+                    temperature: Some(0.2),
+                    ..Parameters::default()
+                },
+                model_class: Some("coder".to_owned()),
+            },
+            Self {
+                name: "Translator".to_owned(),
+                description: "Translates text while preserving tone and formatting.".to_owned(),
+                system_prompt: "You are a professional translator. \
+                    Translate the text you are given faithfully, preserving its tone, \
+                    formatting, and meaning. Reply only with the translation unless \
+                    asked otherwise."
+                    .to_owned(),
+                parameters: Parameters {
+                    temperature: Some(0.3),
+                    ..Parameters::default()
+                },
+                model_class: Some("instruct".to_owned()),
+            },
+            Self {
+                name: "Writing Editor".to_owned(),
+                description: "Improves clarity, grammar, and style of your writing.".to_owned(),
+                system_prompt: "You are a meticulous writing editor. \
+                    Improve the clarity, grammar, and flow of the text you are given \
+                    while keeping the author's voice. Briefly explain significant changes."
+                    .to_owned(),
+                parameters: Parameters {
+                    temperature: Some(0.7),
+                    ..Parameters::default()
+                },
+                model_class: Some("instruct".to_owned()),
+            },
+            Self {
+                name: "SQL Analyst".to_owned(),
+                description: "Writes and optimizes SQL queries for your data.".to_owned(),
+                system_prompt: "You are a senior data analyst fluent in SQL. \
+                    Write correct and efficient queries, state any assumptions about \
+                    the schema, and explain the results you expect."
+                    .to_owned(),
+                parameters: Parameters {
+                    temperature: Some(0.1),
+                    ..Parameters::default()
+                },
+                model_class: Some("coder".to_owned()),
+            },
+        ]
+    }
+
+    pub async fn list() -> Result<Vec<Self>, Error> {
+        let mut personas = Self::builtins();
+
+        let mut entries = match fs::read_dir(Self::directory()).await {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(personas),
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut custom = Vec::new();
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+
+            if path.extension().is_none_or(|extension| extension != "toml") {
+                continue;
+            }
+
+            match Self::import(&path).await {
+                Ok(persona) => custom.push(persona),
+                Err(error) => log::warn!("invalid persona {}: {error}", path.display()),
+            }
+        }
+
+        custom.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for persona in custom {
+            if let Some(builtin) = personas.iter_mut().find(|p| p.name == persona.name) {
+                *builtin = persona;
+            } else {
+                personas.push(persona);
+            }
+        }
+
+        Ok(personas)
+    }
+
+    pub async fn save(self) -> Result<Self, Error> {
+        fs::create_dir_all(Self::directory()).await?;
+
+        self.export(Self::directory().join(format!("{}.toml", self.slug())))
+            .await
+    }
+
+    pub async fn delete(self) -> Result<(), Error> {
+        match fs::remove_file(Self::directory().join(format!("{}.toml", self.slug()))).await {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn import(path: &Path) -> Result<Self, Error> {
+        let toml = fs::read_to_string(path).await?;
+
+        Ok(toml::from_str(&toml)?)
+    }
+
+    pub async fn export(self, path: PathBuf) -> Result<Self, Error> {
+        fs::write(path, toml::to_string_pretty(&self)?).await?;
+
+        Ok(self)
+    }
+
+    pub fn is_builtin(&self) -> bool {
+        Self::builtins().contains(self)
+    }
+
+    fn slug(&self) -> String {
+        self.name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+
+    fn directory() -> PathBuf {
+        directory::config().join("personas")
+    }
+}
@@ -1,19 +1,28 @@
+use crate::approval::{self, Policy};
 use crate::assistant::{Assistant, Reasoning, Reply, SimpleMessage};
+use crate::filesystem;
+use crate::plugin::{self, Plugin};
+use crate::reminder;
 use crate::web;
 use crate::Error;
 
 use langchain_rust::schemas::{Message, MessageType};
 use serde::{Deserialize, Serialize};
 use sipper::{sipper, Sender, Sipper, Straw};
+use thiserror::capture;
 use url::Url;
 
 use std::collections::{BTreeMap, HashMap};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Plan {
     pub reasoning: Option<Reasoning>,
     pub steps: Vec<Step>,
     pub outcomes: Vec<Outcome>,
+    #[serde(default)]
+    pub citations: Vec<Citation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<approval::Record>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +33,19 @@ pub struct Step {
     pub inputs: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Citation {
+    pub number: usize,
+    pub url: Url,
+    pub excerpt: String,
+}
+
+impl Citation {
+    pub fn marker(&self) -> String {
+        format!("[{}]", self.number)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Outcome {
     Search(Status<Vec<Url>>),
@@ -68,13 +90,48 @@ pub enum Event {
     Designing(Reasoning),
     Designed(Plan),
     OutcomeAdded(Outcome),
-    OutcomeChanged(Outcome),
+    OutcomeChanged(usize, Outcome),
+    Cited(Vec<Citation>),
+    StepAdded(Step),
+    ApprovalRequested(approval::Request),
+    Approved(approval::Record),
 }
 
 impl Plan {
+    pub fn apply(&mut self, event: Event) {
+        match event {
+            Event::Designing(reasoning) => {
+                self.reasoning = Some(reasoning);
+            }
+            Event::Designed(plan) => {
+                self.reasoning = plan.reasoning;
+                self.steps = plan.steps;
+            }
+            Event::OutcomeAdded(outcome) => {
+                self.outcomes.push(outcome);
+            }
+            Event::OutcomeChanged(index, new_outcome) => {
+                if let Some(outcome) = self.outcomes.get_mut(index) {
+                    *outcome = new_outcome;
+                }
+            }
+            Event::Cited(citations) => {
+                self.citations = citations;
+            }
+            Event::StepAdded(step) => {
+                self.steps.push(step);
+            }
+            Event::ApprovalRequested(_) => {}
+            Event::Approved(record) => {
+                self.calls.push(record);
+            }
+        }
+    }
+
     pub fn search<'a>(
         assistant: &'a Assistant,
         history: &'a [Message],
+        can_fetch: bool,
     ) -> impl Straw<(), Event, Error> + 'a {
         sipper(move |mut progress| async move {
             let Some(query) = history.iter().rev().find_map(|item| {
@@ -87,13 +144,21 @@ impl Plan {
                 return Ok(());
             };
 
+            let plugins = Plugin::enabled().await.unwrap_or_else(|error| {
+                log::warn!("Plugins unavailable: {error}");
+                Vec::new()
+            });
+
             let plan = {
                 let mut attempt = 0;
 
                 loop {
                     log::info!("Designing plan ({attempt})...");
 
-                    match design(assistant, history).run(&progress).await {
+                    match design(assistant, history, &plugins, can_fetch)
+                        .run(&progress)
+                        .await
+                    {
                         Err(error) if attempt < 3 => {
                             log::warn!("Plan design failed: {error}");
                         }
@@ -106,7 +171,7 @@ impl Plan {
 
             progress.send(Event::Designed(plan.clone())).await;
 
-            let _ = execute(assistant, history, &query, &plan)
+            let _ = execute(assistant, history, &query, &plan, &plugins, can_fetch)
                 .run(progress)
                 .await?;
 
@@ -114,6 +179,85 @@ impl Plan {
         })
     }
 
+    pub fn agent<'a>(
+        assistant: &'a Assistant,
+        history: &'a [Message],
+        can_fetch: bool,
+        budget: usize,
+    ) -> impl Straw<(), Event, Error> + 'a {
+        sipper(move |mut progress| async move {
+            let Some(query) = history.iter().rev().find_map(|item| {
+                if item.message_type == MessageType::HumanMessage {
+                    Some(item.content.clone())
+                } else {
+                    None
+                }
+            }) else {
+                return Ok(());
+            };
+
+            let plugins = Plugin::enabled().await.unwrap_or_else(|error| {
+                log::warn!("Plugins unavailable: {error}");
+                Vec::new()
+            });
+
+            let mut plan = Plan {
+                reasoning: None,
+                steps: Vec::new(),
+                outcomes: Vec::new(),
+                citations: Vec::new(),
+                calls: Vec::new(),
+            };
+
+            let mut outputs = Outputs::default();
+
+            loop {
+                let remaining = budget.saturating_sub(plan.steps.len());
+
+                let step = if remaining > 1 {
+                    next_step(
+                        assistant, history, &plan, &outputs, &plugins, can_fetch, remaining,
+                    )
+                    .run(&progress)
+                    .await
+                    .unwrap_or_else(|error| {
+                        log::warn!("Agent step failed: {error}");
+                        final_answer(&plan)
+                    })
+                } else {
+                    final_answer(&plan)
+                };
+
+                let index = plan.steps.len();
+                let is_answer = step.function == "answer";
+
+                log::info!("Agent step {index}: {}", step.description);
+
+                plan.steps.push(step.clone());
+                progress.send(Event::StepAdded(step.clone())).await;
+                progress.send(Event::OutcomeAdded(pending(&step))).await;
+
+                let context = Context {
+                    assistant,
+                    history,
+                    query: &query,
+                    plan: &plan,
+                    plugins: &plugins,
+                    can_fetch,
+                };
+
+                let outcome = call(context, index, &outputs, progress.clone()).await?;
+
+                outputs.insert(&step.evidence, &outcome);
+                plan.outcomes.push(outcome);
+
+                if is_answer {
+                    return Ok(());
+                }
+            }
+        })
+    }
+
     pub fn answers(&self) -> impl Iterator<Item = &Reply> {
         self.outcomes.iter().filter_map(|outcome| match outcome {
             Outcome::Answer(Status::Done(reply)) => Some(reply),
@@ -125,13 +269,17 @@ impl Plan {
 fn design<'a>(
     assistant: &'a Assistant,
     history: &'a [Message],
+    plugins: &'a [Plugin],
+    can_fetch: bool,
 ) -> impl Straw<Plan, Event, Error> + 'a {
     sipper(move |progress| async move {
         let reply = assistant
             .reply(
                 "You are a helpful assistant.",
                 history,
-                &[Message::new_system_message(BROWSE_PROMPT.to_owned())],
+                &[Message::new_system_message(browse_prompt(
+                    plugins, can_fetch,
+                ))],
             )
             .filter_with(|(reply, _token)| reply.reasoning.map(Event::Designing))
             .run(progress)
@@ -147,263 +295,595 @@ fn design<'a>(
             reasoning: reply.reasoning,
             steps: serde_json::from_str(plan)?,
             outcomes: Vec::new(),
+            citations: Vec::new(),
+            calls: Vec::new(),
         })
     })
 }
 
-fn execute<'a>(
+const MAX_OBSERVATION: usize = 4_000;
+
+fn next_step<'a>(
     assistant: &'a Assistant,
     history: &'a [Message],
-    query: &'a str,
     plan: &'a Plan,
-) -> impl Straw<Vec<Outcome>, Event, Error> + 'a {
-    struct Process {
-        outcomes: Vec<Outcome>,
-        outputs: HashMap<String, Output>,
-        sender: Sender<Event>,
-    }
+    outputs: &'a Outputs,
+    plugins: &'a [Plugin],
+    can_fetch: bool,
+    remaining: usize,
+) -> impl Straw<Step, Event, Error> + 'a {
+    sipper(move |progress| async move {
+        let observations: String = plan
+            .steps
+            .iter()
+            .zip(&plan.outcomes)
+            .map(|(step, outcome)| {
+                let output = match outcome {
+                    Outcome::Search(Status::Errored(error))
+                    | Outcome::ScrapeText(Status::Errored(error))
+                    | Outcome::Answer(Status::Errored(error)) => error.clone(),
+                    _ => outputs.texts(&[format!("${}", step.evidence)]).join("\n\n"),
+                };
+
+                let output = if output.len() > MAX_OBSERVATION {
+                    let mut end = MAX_OBSERVATION;
+
+                    while !output.is_char_boundary(end) {
+                        end -= 1;
+                    }
+
+                    format!("{}\n[output truncated]", &output[..end])
+                } else {
+                    output
+                };
+
+                format!(
+                    "\n\n${evidence} = {function}({inputs}):\n{output}",
+                    evidence = step.evidence,
+                    function = step.function,
+                    inputs = step.inputs.join(", "),
+                )
+            })
+            .collect();
+
+        let prompt = AGENT_PROMPT
+            .replace("{plugin_tools}", &tools(plugins, can_fetch))
+            .replace(
+                "{observations}",
+                if observations.is_empty() {
+                    "\n\nNone yet."
+                } else {
+                    &observations
+                },
+            )
+            .replace("{remaining}", &remaining.to_string())
+            .replace("{next}", &plan.steps.len().to_string());
+
+        let reply = assistant
+            .reply(
+                "You are a helpful assistant.",
+                history,
+                &[Message::new_system_message(prompt)],
+            )
+            .filter_with(|(reply, _token)| reply.reasoning.map(Event::Designing))
+            .run(progress)
+            .await?;
+
+        let content = &reply.content;
+
+        let (Some(start), Some(end)) = (content.find('{'), content.rfind('}')) else {
+            return Err(Error::InvalidResponse("the model", capture!()));
+        };
+
+        Ok(serde_json::from_str(&content[start..=end])?)
+    })
+}
 
-    #[derive(Debug)]
-    enum Output {
-        Links(Vec<reqwest::Url>),
-        Text(Vec<String>),
-        Answer,
+fn final_answer(plan: &Plan) -> Step {
+    Step {
+        evidence: "final_answer".to_owned(),
+        description: "Answer with the evidence gathered".to_owned(),
+        function: "answer".to_owned(),
+        inputs: plan
+            .steps
+            .iter()
+            .map(|step| format!("${}", step.evidence))
+            .collect(),
     }
+}
 
-    impl Process {
-        fn links(&self, inputs: &[String]) -> Vec<Url> {
-            inputs
-                .iter()
-                .flat_map(|input| {
-                    if input.starts_with('$') {
-                        if let Some(Output::Links(links)) =
-                            self.outputs.get(input.trim_start_matches('$').trim())
-                        {
-                            links.clone()
-                        } else {
-                            Vec::new()
-                        }
+const MAX_CONCURRENT_CALLS: usize = 4;
+
+#[derive(Clone, Copy)]
+struct Context<'a> {
+    assistant: &'a Assistant,
+    history: &'a [Message],
+    query: &'a str,
+    plan: &'a Plan,
+    plugins: &'a [Plugin],
+    can_fetch: bool,
+}
+
+#[derive(Debug)]
+enum Output {
+    Links(Vec<reqwest::Url>),
+    Text(Vec<web::Summary>),
+    Answer,
+}
+
+#[derive(Debug, Default)]
+struct Outputs(HashMap<String, Output>);
+
+impl Outputs {
+    fn links(&self, inputs: &[String]) -> Vec<Url> {
+        inputs
+            .iter()
+            .flat_map(|input| {
+                if input.starts_with('$') {
+                    if let Some(Output::Links(links)) =
+                        self.0.get(input.trim_start_matches('$').trim())
+                    {
+                        links.clone()
                     } else {
-                        Url::parse(input)
-                            .ok()
-                            .map(|url| vec![url])
-                            .unwrap_or_default()
+                        Vec::new()
                     }
-                })
-                .collect()
-        }
+                } else {
+                    Url::parse(input)
+                        .ok()
+                        .map(|url| vec![url])
+                        .unwrap_or_default()
+                }
+            })
+            .collect()
+    }
 
-        fn text(&self, inputs: &[String]) -> Vec<String> {
-            inputs
-                .iter()
-                .filter_map(|input| {
-                    if input.starts_with('$') {
-                        let evidence = input.trim_start_matches('$').trim();
-
-                        if let Output::Text(text) = self.outputs.get(evidence)? {
-                            Some(text)
-                        } else {
-                            None
-                        }
+    fn texts(&self, inputs: &[String]) -> Vec<String> {
+        inputs
+            .iter()
+            .flat_map(|input| {
+                let Some(evidence) = input.strip_prefix('$') else {
+                    return vec![input.clone()];
+                };
+
+                match self.0.get(evidence.trim()) {
+                    Some(Output::Links(links)) => links.iter().map(ToString::to_string).collect(),
+                    Some(Output::Text(summaries)) => summaries
+                        .iter()
+                        .map(|summary| summary.content.clone())
+                        .collect(),
+                    Some(Output::Answer) | None => Vec::new(),
+                }
+            })
+            .collect()
+    }
+
+    fn sources(&self, inputs: &[String]) -> Vec<Citation> {
+        inputs
+            .iter()
+            .filter_map(|input| {
+                if input.starts_with('$') {
+                    let evidence = input.trim_start_matches('$').trim();
+
+                    if let Output::Text(summaries) = self.0.get(evidence)? {
+                        Some(summaries)
                     } else {
                         None
                     }
+                } else {
+                    None
+                }
+            })
+            .flatten()
+            .enumerate()
+            .map(|(i, summary)| Citation {
+                number: i + 1,
+                url: summary.url.clone(),
+                excerpt: summary.content.clone(),
+            })
+            .collect()
+    }
+
+    fn insert(&mut self, evidence: &str, outcome: &Outcome) {
+        let output = match outcome {
+            Outcome::Search(Status::Done(links)) => Output::Links(links.clone()),
+            Outcome::ScrapeText(Status::Done(summaries)) => Output::Text(summaries.clone()),
+            Outcome::Answer(Status::Done(_)) => Output::Answer,
+            _ => return,
+        };
+
+        let _ = self.0.insert(evidence.to_owned(), output);
+    }
+}
+
+// A wave takes the next steps until one needs the evidence of another one in
+// the wave. Answers always run alone
+fn execute<'a>(
+    assistant: &'a Assistant,
+    history: &'a [Message],
+    query: &'a str,
+    plan: &'a Plan,
+    plugins: &'a [Plugin],
+    can_fetch: bool,
+) -> impl Straw<Vec<Outcome>, Event, Error> + 'a {
+    use futures::stream::{self, StreamExt};
+    use futures::FutureExt;
+
+    let context = Context {
+        assistant,
+        history,
+        query,
+        plan,
+        plugins,
+        can_fetch,
+    };
+
+    sipper(move |mut sender| async move {
+        let mut outputs = Outputs::default();
+        let mut outcomes = Vec::new();
+        let mut next = 0;
+
+        while next < plan.steps.len() {
+            let wave = next..next + independent(&plan.steps[next..]);
+
+            for step in &plan.steps[wave.clone()] {
+                log::info!("Running: {}", step.description);
+
+                sender.send(Event::OutcomeAdded(pending(step))).await;
+            }
+
+            let mut results: Vec<(usize, Result<Outcome, Error>)> = stream::iter(wave.clone())
+                .map(|index| {
+                    call(context, index, &outputs, sender.clone())
+                        .map(move |result| (index, result))
                 })
-                .flatten()
-                .map(|text| format!("```\n{text}\n```"))
+                .buffer_unordered(MAX_CONCURRENT_CALLS)
                 .collect()
-        }
+                .await;
+
+            results.sort_by_key(|(index, _result)| *index);
+
+            for (index, result) in results {
+                let step = &plan.steps[index];
+
+                let outcome = match result {
+                    Ok(outcome) => outcome,
+                    Err(error) if step.function == "answer" => return Err(error),
+                    Err(error) => {
+                        log::warn!("{} failed: {error}", step.description);
 
-        async fn push(&mut self, outcome: Outcome) {
-            self.outcomes.push(outcome.clone());
-            self.sender.send(Event::OutcomeAdded(outcome)).await;
+                        let outcome = errored(step, &error);
+
+                        sender
+                            .send(Event::OutcomeChanged(index, outcome.clone()))
+                            .await;
+
+                        outcome
+                    }
+                };
+
+                outputs.insert(&step.evidence, &outcome);
+                outcomes.push(outcome);
+            }
+
+            next = wave.end;
         }
 
-        async fn start<T>(&mut self, outcome: impl Fn(Status<T>) -> Outcome)
-        where
-            T: Default,
-        {
-            self.push(outcome(Status::Active(T::default()))).await;
+        Ok(outcomes)
+    })
+}
+
+fn independent(steps: &[Step]) -> usize {
+    let mut evidence = Vec::new();
+
+    for (count, step) in steps.iter().enumerate() {
+        let is_dependent = step.inputs.iter().any(|input| {
+            input
+                .strip_prefix('$')
+                .is_some_and(|input| evidence.contains(&input.trim()))
+        });
+
+        if count > 0 && (is_dependent || step.function == "answer") {
+            return count;
         }
 
-        async fn update(&mut self, outcome: Outcome) {
-            let _ = self.outcomes.pop();
-            self.outcomes.push(outcome.clone());
-            self.sender.send(Event::OutcomeChanged(outcome)).await;
+        if step.function == "answer" {
+            return 1;
         }
 
-        async fn done(&mut self, name: &str) {
-            let Some(outcome) = self.outcomes.pop() else {
-                return;
-            };
+        evidence.push(step.evidence.as_str());
+    }
 
-            let (output, outcome) = match outcome {
-                Outcome::Search(Status::Active(links)) => (
-                    Output::Links(links.clone()),
-                    Outcome::Search(Status::Done(links)),
-                ),
-                Outcome::ScrapeText(Status::Active(summaries)) => (
-                    Output::Text(
-                        summaries
-                            .iter()
-                            .map(web::Summary::content)
-                            .map(str::to_owned)
-                            .collect(),
-                    ),
-                    Outcome::ScrapeText(Status::Done(summaries)),
-                ),
-                Outcome::Answer(Status::Active(reply)) => {
-                    (Output::Answer, Outcome::Answer(Status::Done(reply)))
-                }
-                _ => {
-                    return;
-                }
-            };
+    steps.len()
+}
 
-            let _ = self.outputs.insert(name.to_owned(), output);
-            self.outcomes.push(outcome.clone());
+fn pending(step: &Step) -> Outcome {
+    match step.function.as_str() {
+        "search" => Outcome::Search(Status::Active(Vec::new())),
+        "answer" => Outcome::Answer(Status::Active(Reply::default())),
+        _ => Outcome::ScrapeText(Status::Active(Vec::new())),
+    }
+}
 
-            self.sender
-                .send(Event::OutcomeChanged(outcome.clone()))
-                .await;
-        }
+fn errored(step: &Step, error: &Error) -> Outcome {
+    let error = error.to_string();
+
+    match step.function.as_str() {
+        "search" => Outcome::Search(Status::Errored(error)),
+        "answer" => Outcome::Answer(Status::Errored(error)),
+        _ => Outcome::ScrapeText(Status::Errored(error)),
     }
+}
 
-    sipper(move |sender| async move {
-        let mut process = Process {
-            outcomes: Vec::new(),
-            outputs: HashMap::new(),
-            sender: sender.clone(),
-        };
+async fn call(
+    context: Context<'_>,
+    index: usize,
+    outputs: &Outputs,
+    mut sender: Sender<Event>,
+) -> Result<Outcome, Error> {
+    let step = &context.plan.steps[index];
+    let inputs = outputs.texts(&step.inputs);
+
+    if step.function != "answer" && !approve(&step.function, &inputs, &mut sender).await {
+        let outcome = Outcome::ScrapeText(Status::Errored(format!(
+            "The call to {} was denied.",
+            step.function
+        )));
+
+        sender
+            .send(Event::OutcomeChanged(index, outcome.clone()))
+            .await;
+
+        return Ok(outcome);
+    }
 
-        for (i, step) in plan.steps.iter().enumerate() {
-            log::info!("Running: {}", step.description);
+    let text = |url: Url, content: String| {
+        Outcome::ScrapeText(Status::Done(vec![web::Summary { url, content }]))
+    };
 
-            match step.function.as_str() {
-                "search" => {
-                    let query = step.inputs.first().map(String::as_str).unwrap_or_default();
+    let outcome = match step.function.as_str() {
+        "search" => {
+            let query = step.inputs.first().map(String::as_str).unwrap_or_default();
 
-                    process.start(Outcome::Search).await;
+            let search = web::search(query).await?;
 
-                    let search = web::search(query).await?;
+            Outcome::Search(Status::Done(search.results))
+        }
+        "scrape_text" => {
+            use futures::stream::FuturesUnordered;
+            use futures::{FutureExt, StreamExt};
+
+            let mut output = BTreeMap::new();
+            let mut order = Vec::new();
+
+            let links = outputs.links(&step.inputs);
+
+            let mut scrape = sipper(move |sender| {
+                links
+                    .iter()
+                    .cloned()
+                    .map(|link| web::summarize(context.assistant, context.query, link))
+                    .enumerate()
+                    .map(|(i, scrape)| {
+                        scrape
+                            .with(move |progress| (i, progress))
+                            .run(&sender)
+                            .map(move |result| (i, result))
+                    })
+                    .collect::<FuturesUnordered<_>>()
+                    .collect::<Vec<_>>()
+            })
+            .pin();
 
-                    process
-                        .update(Outcome::Search(Status::Active(search.results)))
-                        .await;
+            while let Some((i, summary)) = scrape.sip().await {
+                let _ = output.insert(i, summary);
 
-                    process.done(&step.evidence).await;
+                if !order.contains(&i) {
+                    order.push(i);
                 }
-                "scrape_text" => {
-                    use futures::stream::FuturesUnordered;
-                    use futures::{FutureExt, StreamExt};
-
-                    let mut output = BTreeMap::new();
-                    let mut order = Vec::new();
-
-                    let links = process.links(&step.inputs);
-
-                    process.start(Outcome::ScrapeText).await;
-
-                    let mut scrape = sipper(move |sender| {
-                        links
-                            .iter()
-                            .cloned()
-                            .map(|link| web::summarize(assistant, query, link))
-                            .enumerate()
-                            .map(|(i, scrape)| {
-                                scrape
-                                    .with(move |progress| (i, progress))
-                                    .run(&sender)
-                                    .map(move |result| (i, result))
-                            })
-                            .collect::<FuturesUnordered<_>>()
-                            .collect::<Vec<_>>()
-                    })
-                    .pin();
 
-                    while let Some((i, summary)) = scrape.sip().await {
-                        let _ = output.insert(i, summary);
+                sender
+                    .send(Event::OutcomeChanged(
+                        index,
+                        Outcome::ScrapeText(Status::Active(
+                            order
+                                .iter()
+                                .filter_map(|i| output.get(i))
+                                .cloned()
+                                .collect(),
+                        )),
+                    ))
+                    .await;
+            }
 
-                        if !order.contains(&i) {
-                            order.push(i);
-                        }
+            let summaries = scrape.await;
 
-                        process
-                            .update(Outcome::ScrapeText(Status::Active(
-                                order
-                                    .iter()
-                                    .filter_map(|i| output.get(i))
-                                    .cloned()
-                                    .collect(),
-                            )))
-                            .await;
+            for (i, summary) in summaries {
+                match summary {
+                    Ok(summary) => {
+                        let _ = output.insert(i, summary);
+                    }
+                    Err(error) => {
+                        log::error!("Scraping failed: {error}");
                     }
+                }
+            }
+
+            Outcome::ScrapeText(Status::Done(output.into_values().collect()))
+        }
+        "answer" => {
+            let steps = context
+                .plan
+                .steps
+                .iter()
+                .take(index)
+                .map(|step| format!("- {}", step.description))
+                .collect::<Vec<_>>()
+                .join("\n");
 
-                    let summaries = scrape.await;
+            let sources = outputs.sources(&step.inputs);
 
-                    for (i, summary) in summaries {
-                        match summary {
-                            Ok(summary) => {
-                                let _ = output.insert(i, summary);
-                            }
-                            Err(error) => {
-                                log::error!("Scraping failed: {error}");
-                            }
-                        }
-                    }
+            let outputs = sources
+                .iter()
+                .map(|source| {
+                    format!(
+                        "{marker} {url}\n```\n{excerpt}\n```",
+                        marker = source.marker(),
+                        url = source.url,
+                        excerpt = source.excerpt
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n");
 
-                    process
-                        .update(Outcome::ScrapeText(Status::Active(
-                            output.values().cloned().collect(),
-                        )))
-                        .await;
+            if !sources.is_empty() {
+                sender.send(Event::Cited(sources)).await;
+            }
 
-                    process.done(&step.evidence).await;
-                }
-                "answer" => {
-                    process.start(Outcome::Answer).await;
+            let query = [
+                Message::new_system_message(format!(
+                    "In order to figure out the user's request, you have already \
+                performed certain actions to gather information. Here is a \
+                summary of the steps executed so far:\n\
+                \n\
+                {steps}\n\n\
+                The outputs of the actions considered relevant to the user request \
+                are provided next:\n\
+                {outputs}\n\
+                Analyze the outputs carefully before replying to the user. \
+                When a statement relies on one of the outputs, cite it right after \
+                the statement using its number in brackets, like [1]."
+                )),
+                Message::new_human_message(context.query.to_owned()),
+            ];
+
+            let mut reply = context
+                .assistant
+                .reply("You are a helpful assistant.", context.history, &query)
+                .pin();
+
+            let mut answer = Reply::default();
+
+            while let Some((reply, _token)) = reply.sip().await {
+                answer = reply.clone();
+
+                sender
+                    .send(Event::OutcomeChanged(
+                        index,
+                        Outcome::Answer(Status::Active(reply)),
+                    ))
+                    .await;
+            }
 
-                    let steps = plan
-                        .steps
-                        .iter()
-                        .take(i)
-                        .map(|step| format!("- {}", step.description))
-                        .collect::<Vec<_>>()
-                        .join("\n");
-
-                    let outputs = process.text(&step.inputs).join("\n\n");
-
-                    let query = [
-                        Message::new_system_message(format!(
-                            "In order to figure out the user's request, you have already \
-                        performed certain actions to gather information. Here is a \
-                        summary of the steps executed so far:\n\
-                        \n\
-                        {steps}\n\n\
-                        The outputs of the actions considered relevant to the user request \
-                        are provided next:\n\
-                        {outputs}\n\
-                        Analyze the outputs carefully before replying to the user."
-                        )),
-                        Message::new_human_message(query.to_owned()),
-                    ];
+            Outcome::Answer(Status::Done(answer))
+        }
+        reminder::TOOL if reminder::is_enabled() => text(
+            Url::parse("icebreaker://reminders").expect("Valid URL"),
+            reminder::call(&inputs).await,
+        ),
+        web::FETCH if context.can_fetch => {
+            Outcome::ScrapeText(Status::Done(vec![web::fetch_tool(&inputs).await]))
+        }
+        tool if filesystem::is_tool(tool) => {
+            let (url, content) = filesystem::call(tool, &inputs).await;
+
+            text(url, content)
+        }
+        tool => {
+            let Some(plugin) = context
+                .plugins
+                .iter()
+                .find(|plugin| plugin.tool(tool).is_some())
+            else {
+                log::warn!("Unknown function in plan: {tool}");
 
-                    let mut reply = assistant
-                        .reply("You are a helpful assistant.", history, &query)
-                        .pin();
+                let outcome = Outcome::ScrapeText(Status::Errored(format!(
+                    "{tool} is not an available tool."
+                )));
 
-                    while let Some((reply, _token)) = reply.sip().await {
-                        process.update(Outcome::Answer(Status::Active(reply))).await;
-                    }
+                sender
+                    .send(Event::OutcomeChanged(index, outcome.clone()))
+                    .await;
+
+                return Ok(outcome);
+            };
 
-                    process.done(&step.evidence).await;
+            let request = plugin::Request::Tool {
+                name: tool.to_owned(),
+                inputs,
+            };
+
+            let content = match plugin.call(request).await {
+                Ok(
+                    plugin::Response::Text(content)
+                    | plugin::Response::Prompt(content)
+                    | plugin::Response::Reply(content),
+                ) => content,
+                Ok(plugin::Response::Error(error)) => error,
+                Err(error) => {
+                    log::error!("Plugin tool failed: {error}");
+                    error.to_string()
                 }
-                _ => {}
-            }
+            };
+
+            text(plugin.url(tool), content)
         }
+    };
 
-        Ok(process.outcomes)
-    })
+    sender
+        .send(Event::OutcomeChanged(index, outcome.clone()))
+        .await;
+
+    Ok(outcome)
+}
+
+async fn approve(tool: &str, inputs: &[String], sender: &mut Sender<Event>) -> bool {
+    let record = match approval::policy(tool) {
+        Policy::Auto => Some(approval::Record::new(tool, inputs, false)),
+        Policy::Deny => None,
+        Policy::Ask => {
+            let (request, decision) = approval::ask(tool, inputs);
+
+            sender.send(Event::ApprovalRequested(request)).await;
+
+            decision
+                .await
+                .unwrap_or_default()
+                .then(|| approval::Record::new(tool, inputs, true))
+        }
+    };
+
+    let Some(record) = record else {
+        log::info!("Tool call denied: {tool}");
+        return false;
+    };
+
+    sender.send(Event::Approved(record)).await;
+
+    true
+}
+
+fn browse_prompt(plugins: &[Plugin], can_fetch: bool) -> String {
+    BROWSE_PROMPT.replace("{plugin_tools}", &tools(plugins, can_fetch))
+}
+
+fn tools(plugins: &[Plugin], can_fetch: bool) -> String {
+    let mut tools: String = plugins
+        .iter()
+        .flat_map(|plugin| &plugin.manifest.tools)
+        .map(|tool| format!("\n- {}: {}", tool.name, tool.description))
+        .collect();
+
+    if reminder::is_enabled() {
+        tools.push_str(&reminder::description());
+    }
+
+    tools.push_str(&filesystem::description());
+
+    if can_fetch {
+        tools.push_str(&web::fetch_description());
+    }
+
+    tools
 }
 
 const BROWSE_PROMPT: &str = r#"Please construct a systematic plan to generate an optimal response to the user instruction, utilizing a set of provided actions. Each step will correspond to an evidence value, which will be the output of one of the available actions given an input string.
@@ -412,7 +892,7 @@ Here are the tools available to be called:
 
 - search: Search for information using the Google search engine. This action is helpful in locating a suitable list of sites that may contain the answer to the user's query. It does not directly answer the question but finds a list of sites that might have the answer.
 - scrape_text: Load one or more websites from the input string, where input is one or more links, and produces plain text output containing the content of the links.
-- answer: Answer a question by reasoning from evidence obtained with previous actions.
+- answer: Answer a question by reasoning from evidence obtained with previous actions.{plugin_tools}
 
 The output should be in JSON:
 
@@ -440,3 +920,28 @@ The output should be in JSON:
 ]
 ```
 Reply only with the plan in JSON."#;
+
+const AGENT_PROMPT: &str = r#"You are an agent that fulfills the user instruction by calling tools one at a time. After every call, you are shown its output before choosing the next one.
+
+Here are the tools available to be called:
+
+- search: Search for information using the Google search engine. It finds a list of sites that might have the answer.
+- scrape_text: Load one or more websites, where the inputs are links or the evidence of a search, and produce plain text with their content.
+- answer: Answer the user by reasoning from the evidence of previous calls. The inputs are the evidence to cite.{plugin_tools}
+
+The outputs of the calls made so far are:{observations}
+
+You have {remaining} calls left, including the answer. Answer as soon as you have enough information.
+
+Reply only with the next call in JSON:
+
+```json
+{
+    "evidence": "step_{next}",
+    "description": "Search how to cook an omelette",
+    "function": "search",
+    "inputs": ["how to cook an omelette best recipe"]
+}
+```
+
+Refer to the output of a previous call with its evidence, like "$step_0"."#;
@@ -0,0 +1,304 @@
+use crate::directory;
+use crate::model::{APIAccess, APIType};
+use crate::Error;
+
+pub use icebreaker_plugin::{Command, Manifest, Provider, Request, Response, Tool, API_VERSION};
+
+use serde::{Deserialize, Serialize};
+use thiserror::capture;
+use tokio::fs;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process;
+use tokio::time;
+use url::Url;
+
+use std::collections::BTreeSet;
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+const MANIFEST: &str = "plugin.toml";
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plugin {
+    pub manifest: Manifest,
+    pub directory: PathBuf,
+    pub is_enabled: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct State {
+    enabled: BTreeSet<String>,
+}
+
+impl Plugin {
+    pub fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    pub fn is_compatible(&self) -> bool {
+        self.manifest.is_compatible()
+    }
+
+    pub async fn list() -> Result<Vec<Self>, Error> {
+        let state = State::fetch().await?;
+
+        let mut entries = match fs::read_dir(plugins()).await {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut plugins = Vec::new();
+
+        while let Some(entry) = entries.next_entry().await? {
+            let directory = entry.path();
+
+            match manifest(&directory).await {
+                Ok(manifest) => plugins.push(Self {
+                    is_enabled: state.enabled.contains(&manifest.name),
+                    manifest,
+                    directory,
+                }),
+                Err(error) => {
+                    log::warn!("Invalid plugin at {}: {error}", directory.display());
+                }
+            }
+        }
+
+        plugins.sort_by(|a, b| a.name().cmp(b.name()));
+
+        Ok(plugins)
+    }
+
+    pub async fn enabled() -> Result<Vec<Self>, Error> {
+        let mut plugins = Self::list().await?;
+        plugins.retain(|plugin| plugin.is_enabled && plugin.is_compatible());
+
+        Ok(plugins)
+    }
+
+    pub async fn install(source: PathBuf) -> Result<Self, Error> {
+        let manifest = manifest(&source).await?;
+
+        if manifest.name.trim().is_empty()
+            || manifest
+                .name
+                .contains(|c: char| matches!(c, '/' | '\\') || c.is_control())
+            || manifest.name.starts_with('.')
+        {
+            return Err(Error::InvalidPlugin("invalid name", capture!()));
+        }
+
+        if !manifest.is_compatible() {
+            return Err(Error::InvalidPlugin(
+                "built for a different API version",
+                capture!(),
+            ));
+        }
+
+        if manifest.command.is_empty() {
+            return Err(Error::InvalidPlugin("missing command", capture!()));
+        }
+
+        let directory = plugins().join(&manifest.name);
+
+        if fs::try_exists(&directory).await? {
+            fs::remove_dir_all(&directory).await?;
+        }
+
+        copy(&source, &directory).await?;
+
+        Ok(Self {
+            manifest,
+            directory,
+            is_enabled: false,
+        })
+    }
+
+    pub async fn set_enabled(self, is_enabled: bool) -> Result<Self, Error> {
+        let mut state = State::fetch().await?;
+
+        if is_enabled {
+            let _ = state.enabled.insert(self.manifest.name.clone());
+        } else {
+            let _ = state.enabled.remove(&self.manifest.name);
+        }
+
+        state.save().await?;
+
+        Ok(Self { is_enabled, ..self })
+    }
+
+    pub async fn uninstall(self) -> Result<(), Error> {
+        let plugin = self.set_enabled(false).await?;
+
+        match fs::remove_dir_all(&plugin.directory).await {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn call(&self, request: Request) -> Result<Response, Error> {
+        let Some((program, arguments)) = self.manifest.command.split_first() else {
+            return Err(Error::InvalidPlugin("missing command", capture!()));
+        };
+
+        // Relative paths point inside the plugin; bare names are looked up in the PATH
+        let program = if Path::new(program).components().count() > 1 {
+            self.directory.join(program)
+        } else {
+            PathBuf::from(program)
+        };
+
+        let mut child = process::Command::new(program)
+            .args(arguments)
+            .current_dir(&self.directory)
+            .env("ICEBREAKER_PLUGIN_API", API_VERSION.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let mut stdin = child.stdin.take().expect("piped stdin");
+        let stdout = child.stdout.take().expect("piped stdout");
+
+        let mut line = serde_json::to_string(&request)?;
+        line.push('\n');
+
+        stdin.write_all(line.as_bytes()).await?;
+        drop(stdin);
+
+        let mut response = String::new();
+
+        let read = BufReader::new(stdout).read_line(&mut response);
+
+        if time::timeout(TIMEOUT, read).await.is_err() {
+            return Err(Error::TimedOut("plugin", capture!()));
+        }
+
+        let _ = child.wait().await?;
+
+        match serde_json::from_str(&response) {
+            Ok(Response::Error(error)) => Err(Error::PluginFailed(error, capture!())),
+            Ok(response) => Ok(response),
+            Err(_) => Err(Error::InvalidResponse("plugin", capture!())),
+        }
+    }
+
+    pub fn tool(&self, name: &str) -> Option<&Tool> {
+        self.manifest.tools.iter().find(|tool| tool.name == name)
+    }
+
+    pub fn url(&self, tool: &str) -> Url {
+        let mut url = Url::parse("icebreaker-plugin:///").expect("Valid URL");
+
+        let _ = url
+            .path_segments_mut()
+            .expect("Hierarchical URL")
+            .push(self.name())
+            .push(tool);
+
+        url
+    }
+}
+
+pub fn access(provider: &Provider) -> APIAccess {
+    APIAccess {
+        openai_compat: None,
+        azure: None,
+        ssh: None,
+        api_key: provider
+            .api_key_env
+            .as_deref()
+            .and_then(|variable| env::var(variable).ok()),
+        base_url: Some(provider.base_url.trim_end_matches('/').to_owned()),
+        kind: APIType::OpenAICompatible,
+    }
+}
+
+pub fn command<'a>(plugins: &'a [Plugin], prompt: &str) -> Option<(&'a Plugin, Request)> {
+    let prompt = prompt.trim().strip_prefix('/')?;
+
+    let (name, argument) = prompt
+        .split_once(char::is_whitespace)
+        .unwrap_or((prompt, ""));
+
+    let plugin = plugins.iter().find(|plugin| {
+        plugin
+            .manifest
+            .commands
+            .iter()
+            .any(|command| command.name == name)
+    })?;
+
+    Some((
+        plugin,
+        Request::Command {
+            name: name.to_owned(),
+            argument: argument.trim().to_owned(),
+        },
+    ))
+}
+
+impl State {
+    async fn fetch() -> Result<Self, Error> {
+        match fs::read(state()).await {
+            Ok(json) => Ok(serde_json::from_slice(&json)?),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    async fn save(&self) -> Result<(), Error> {
+        let path = state();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        fs::write(path, serde_json::to_vec_pretty(self)?).await?;
+
+        Ok(())
+    }
+}
+
+async fn manifest(directory: &Path) -> Result<Manifest, Error> {
+    let manifest = fs::read_to_string(directory.join(MANIFEST)).await?;
+
+    Ok(toml::from_str(&manifest)?)
+}
+
+async fn copy(source: &Path, target: &Path) -> Result<(), Error> {
+    let mut pending = vec![(source.to_path_buf(), target.to_path_buf())];
+
+    while let Some((source, target)) = pending.pop() {
+        fs::create_dir_all(&target).await?;
+
+        let mut entries = fs::read_dir(&source).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let destination = target.join(entry.file_name());
+
+            if entry.file_type().await?.is_dir() {
+                pending.push((entry.path(), destination));
+            } else {
+                let _ = fs::copy(entry.path(), destination).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn plugins() -> PathBuf {
+    directory::config().join("plugins")
+}
+
+fn state() -> PathBuf {
+    directory::config().join("plugins.json")
+}
@@ -0,0 +1,71 @@
+use crate::assistant::Parameters;
+
+use decoder::{decode, encode, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preset {
+    pub name: String,
+    pub parameters: Parameters,
+}
+
+impl Preset {
+    pub fn builtins() -> Vec<Self> {
+        [
+            ("Creative", 1.0, 0.95),
+            ("Balanced", 0.7, 0.9),
+            ("Precise", 0.2, 0.5),
+        ]
+        .into_iter()
+        .map(|(name, temperature, top_p)| Self {
+            name: name.to_owned(),
+            parameters: Parameters {
+                temperature: Some(temperature),
+                top_p: Some(top_p),
+                max_tokens: None,
+            },
+        })
+        .collect()
+    }
+
+    // Decimals are stored as strings, since the settings only hold integers otherwise
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut preset = decode::map(value)?;
+
+        let decimal = |value: Value| {
+            decode::string(value)?
+                .parse::<f32>()
+                .map_err(decoder::Error::custom)
+        };
+
+        Ok(Self {
+            name: preset.required("name", decode::string)?,
+            parameters: Parameters {
+                temperature: preset.optional("temperature", decimal)?,
+                top_p: preset.optional("top_p", decimal)?,
+                max_tokens: preset
+                    .optional("max_tokens", decode::u64)?
+                    .map(|max_tokens| max_tokens as u32),
+            },
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        let parameters = &self.parameters;
+
+        let mut preset = vec![("name", encode::string(&self.name))];
+
+        if let Some(temperature) = parameters.temperature {
+            preset.push(("temperature", encode::string(temperature.to_string())));
+        }
+
+        if let Some(top_p) = parameters.top_p {
+            preset.push(("top_p", encode::string(top_p.to_string())));
+        }
+
+        if let Some(max_tokens) = parameters.max_tokens {
+            preset.push(("max_tokens", encode::u64(max_tokens.into())));
+        }
+
+        encode::map(preset).into_value()
+    }
+}
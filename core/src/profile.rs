@@ -0,0 +1,136 @@
+use crate::directory;
+use crate::Error;
+
+use serde::{Deserialize, Serialize};
+use thiserror::capture;
+use tokio::fs;
+
+use std::path::PathBuf;
+
+pub const DEFAULT: &str = "Default";
+
+const MAX_NAME_LENGTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profiles {
+    pub current: String,
+    pub names: Vec<String>,
+    pub ask_at_startup: bool,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Self {
+            current: DEFAULT.to_owned(),
+            names: vec![DEFAULT.to_owned()],
+            ask_at_startup: false,
+        }
+    }
+}
+
+impl Profiles {
+    pub fn fetch() -> Self {
+        let Ok(json) = std::fs::read_to_string(Self::path()) else {
+            return Self::default();
+        };
+
+        let mut profiles: Self = serde_json::from_str(&json).unwrap_or_default();
+
+        if !profiles.names.iter().any(|name| name == DEFAULT) {
+            profiles.names.insert(0, DEFAULT.to_owned());
+        }
+
+        if !profiles.names.contains(&profiles.current) {
+            profiles.current = DEFAULT.to_owned();
+        }
+
+        profiles
+    }
+
+    pub async fn save(self) -> Result<Self, Error> {
+        let path = Self::path();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        fs::write(path, serde_json::to_vec_pretty(&self)?).await?;
+
+        Ok(self)
+    }
+
+    pub async fn create(mut self, name: String) -> Result<Self, Error> {
+        let name = name.trim().to_owned();
+
+        validate(&name)?;
+
+        if self
+            .names
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(&name))
+        {
+            return Err(Error::InvalidProfile("the name is taken", capture!()));
+        }
+
+        fs::create_dir_all(directory(&name)).await?;
+
+        self.names.push(name);
+        self.save().await
+    }
+
+    pub async fn delete(mut self, name: String) -> Result<Self, Error> {
+        if name == DEFAULT || name == self.current {
+            return Err(Error::InvalidProfile(
+                "the default and current profiles cannot be deleted",
+                capture!(),
+            ));
+        }
+
+        for base in [directory::base_config(), directory::base_data()] {
+            let path = base.join(directory::PROFILES).join(&name);
+
+            if fs::try_exists(&path).await? {
+                fs::remove_dir_all(path).await?;
+            }
+        }
+
+        self.names.retain(|existing| existing != &name);
+        self.save().await
+    }
+
+    fn path() -> PathBuf {
+        directory::base_config().join("profiles.json")
+    }
+}
+
+pub fn activate(name: &str) {
+    directory::select((name != DEFAULT).then(|| name.to_owned()));
+}
+
+fn validate(name: &str) -> Result<(), Error> {
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        return Err(Error::InvalidProfile(
+            "names must have between 1 and 32 characters",
+            capture!(),
+        ));
+    }
+
+    // Names become directories
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+    {
+        return Err(Error::InvalidProfile(
+            "names may only contain letters, numbers, spaces, dashes, and underscores",
+            capture!(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn directory(name: &str) -> PathBuf {
+    directory::base_config()
+        .join(directory::PROFILES)
+        .join(name)
+}
@@ -0,0 +1,112 @@
+use crate::directory;
+use crate::Error;
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use std::io;
+use std::path::PathBuf;
+
+const LIMIT: usize = 1_000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    prompts: Vec<String>,
+}
+
+impl History {
+    pub async fn fetch() -> Result<Self, Error> {
+        let json = match fs::read_to_string(Self::path()).await {
+            Ok(json) => json,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    pub async fn save(self) -> Result<(), Error> {
+        let path = Self::path();
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).await?;
+        }
+
+        fs::write(path, serde_json::to_string(&self)?).await?;
+
+        Ok(())
+    }
+
+    pub fn push(&mut self, prompt: &str) {
+        let prompt = prompt.trim();
+
+        if prompt.is_empty() {
+            return;
+        }
+
+        self.prompts.retain(|existing| existing != prompt);
+        self.prompts.push(prompt.to_owned());
+
+        if self.prompts.len() > LIMIT {
+            let _ = self.prompts.drain(..self.prompts.len() - LIMIT);
+        }
+    }
+
+    pub fn recent(&self) -> impl Iterator<Item = &str> {
+        self.prompts.iter().rev().map(String::as_str)
+    }
+
+    pub fn search(&self, query: &str) -> Vec<&str> {
+        let query = query.trim().to_lowercase();
+
+        if query.is_empty() {
+            return self.recent().collect();
+        }
+
+        let mut matches: Vec<_> = self
+            .recent()
+            .enumerate()
+            .filter_map(|(age, prompt)| Some((score(&query, prompt)?, age, prompt)))
+            .collect();
+
+        matches.sort_by_key(|(score, age, _)| (std::cmp::Reverse(*score), *age));
+        matches.into_iter().map(|(_, _, prompt)| prompt).collect()
+    }
+
+    fn path() -> PathBuf {
+        directory::data().join("prompts.json")
+    }
+}
+
+fn score(query: &str, prompt: &str) -> Option<usize> {
+    let prompt = prompt.to_lowercase();
+
+    if prompt.contains(query) {
+        return Some(usize::MAX - prompt.len());
+    }
+
+    // Subsequence match, rewarding consecutive characters
+    let mut candidates = prompt.chars();
+    let mut score = 0;
+    let mut streak = 0;
+
+    for c in query.chars() {
+        loop {
+            match candidates.next() {
+                Some(candidate) if candidate == c => {
+                    streak += 1;
+                    score += streak;
+                    break;
+                }
+                Some(_) => {
+                    streak = 0;
+                }
+                None => return None,
+            }
+        }
+    }
+
+    Some(score)
+}
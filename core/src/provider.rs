@@ -0,0 +1,84 @@
+use crate::model::APIType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Provider {
+    Local,
+    NanoGPT,
+    OpenAI,
+    OpenAICompatible,
+    AzureOpenAI,
+    Groq,
+    Together,
+    Mistral,
+    Ssh,
+}
+
+impl Provider {
+    pub const ALL: &[Self] = &[
+        Self::Local,
+        Self::NanoGPT,
+        Self::OpenAI,
+        Self::OpenAICompatible,
+        Self::AzureOpenAI,
+        Self::Groq,
+        Self::Together,
+        Self::Mistral,
+        Self::Ssh,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Local => "Local",
+            Self::NanoGPT => "NanoGPT",
+            Self::OpenAI => "OpenAI",
+            Self::OpenAICompatible => "OpenAI-compatible",
+            Self::AzureOpenAI => "Azure OpenAI",
+            Self::Groq => "Groq",
+            Self::Together => "Together",
+            Self::Mistral => "Mistral",
+            Self::Ssh => "Remote (SSH)",
+        }
+    }
+
+    pub(crate) fn from_key(key: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|provider| provider.key() == key)
+    }
+
+    pub(crate) fn key(self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::NanoGPT => "nanogpt",
+            Self::OpenAI => "openai",
+            Self::OpenAICompatible => "openai_compatible",
+            Self::AzureOpenAI => "azure_openai",
+            Self::Groq => "groq",
+            Self::Together => "together",
+            Self::Mistral => "mistral",
+            Self::Ssh => "ssh",
+        }
+    }
+}
+
+impl From<APIType> for Provider {
+    fn from(kind: APIType) -> Self {
+        match kind {
+            APIType::NanoGPT => Self::NanoGPT,
+            APIType::OpenAI => Self::OpenAI,
+            APIType::OpenAICompatible => Self::OpenAICompatible,
+            APIType::AzureOpenAI => Self::AzureOpenAI,
+            APIType::Groq => Self::Groq,
+            APIType::Together => Self::Together,
+            APIType::Mistral => Self::Mistral,
+            APIType::Ssh => Self::Ssh,
+        }
+    }
+}
+
+impl std::fmt::Display for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,94 @@
+use crate::directory;
+use crate::Error;
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuickAction {
+    pub name: String,
+    pub prompt: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct File {
+    #[serde(default)]
+    actions: Vec<QuickAction>,
+}
+
+impl QuickAction {
+    pub fn defaults() -> Vec<Self> {
+        [
+            (
+                "Explain simpler",
+                "Explain that again in simpler terms, as if I were new to the topic.",
+            ),
+            (
+                "Make it shorter",
+                "Make that answer shorter and more to the point.",
+            ),
+            ("Translate to English", "Translate that answer to English."),
+            (
+                "Add tests",
+                "Write tests for the code in that answer, covering the important edge cases.",
+            ),
+        ]
+        .into_iter()
+        .map(|(name, prompt)| Self {
+            name: name.to_owned(),
+            prompt: prompt.to_owned(),
+        })
+        .collect()
+    }
+
+    pub async fn list() -> Result<Vec<Self>, Error> {
+        let path = Self::path();
+
+        let toml = match fs::read_to_string(&path).await {
+            Ok(toml) => toml,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                let actions = Self::defaults();
+
+                if let Some(directory) = path.parent() {
+                    fs::create_dir_all(directory).await?;
+                }
+
+                fs::write(
+                    path,
+                    toml::to_string_pretty(&File {
+                        actions: actions.clone(),
+                    })?,
+                )
+                .await?;
+
+                return Ok(actions);
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        let file: File = toml::from_str(&toml)?;
+
+        Ok(file.actions)
+    }
+
+    pub fn prompt(&self, reply: Option<&str>) -> String {
+        let Some(reply) = reply else {
+            return self.prompt.clone();
+        };
+
+        let quote = reply
+            .lines()
+            .map(|line| format!("> {line}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        format!("{quote}\n\n{}", self.prompt)
+    }
+
+    pub fn path() -> PathBuf {
+        directory::config().join("quick_actions.toml")
+    }
+}
@@ -0,0 +1,167 @@
+use crate::directory;
+use crate::Error;
+
+use chrono::{DateTime, Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use uuid::Uuid;
+
+use std::io;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+static ENABLED: RwLock<bool> = RwLock::new(false);
+
+pub(crate) const TOOL: &str = "remind";
+
+pub fn configure(enabled: bool) {
+    if let Ok(mut configuration) = ENABLED.write() {
+        *configuration = enabled;
+    }
+}
+
+pub(crate) fn is_enabled() -> bool {
+    ENABLED.read().map(|enabled| *enabled).unwrap_or_default()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: Id,
+    pub at: DateTime<Local>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Id(Uuid);
+
+#[derive(Default, Serialize, Deserialize)]
+struct File {
+    #[serde(default)]
+    reminders: Vec<Reminder>,
+}
+
+impl Reminder {
+    pub async fn list() -> Result<Vec<Self>, Error> {
+        Ok(File::fetch().await?.reminders)
+    }
+
+    pub async fn create(at: DateTime<Local>, message: String) -> Result<Self, Error> {
+        let reminder = Self {
+            id: Id(Uuid::new_v4()),
+            at,
+            message,
+        };
+
+        let mut file = File::fetch().await?;
+        file.reminders.push(reminder.clone());
+        file.save().await?;
+
+        Ok(reminder)
+    }
+
+    pub async fn delete(id: Id) -> Result<(), Error> {
+        let mut file = File::fetch().await?;
+        file.reminders.retain(|reminder| reminder.id != id);
+        file.save().await
+    }
+
+    pub async fn take_due() -> Result<Vec<Self>, Error> {
+        let mut file = File::fetch().await?;
+        let now = Local::now();
+
+        let (due, pending): (Vec<_>, Vec<_>) = file
+            .reminders
+            .into_iter()
+            .partition(|reminder| reminder.at <= now);
+
+        file.reminders = pending;
+
+        if !due.is_empty() {
+            file.save().await?;
+        }
+
+        Ok(due)
+    }
+}
+
+impl File {
+    fn path() -> PathBuf {
+        directory::data().join("reminders.json")
+    }
+
+    async fn fetch() -> Result<Self, Error> {
+        let json = match fs::read_to_string(Self::path()).await {
+            Ok(json) => json,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut file: Self = serde_json::from_str(&json)?;
+        file.reminders.sort_by_key(|reminder| reminder.at);
+
+        Ok(file)
+    }
+
+    async fn save(self) -> Result<(), Error> {
+        let path = Self::path();
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).await?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(&self)?).await?;
+
+        Ok(())
+    }
+}
+
+pub(crate) fn description() -> String {
+    format!(
+        "\n- {TOOL}: Set a reminder that is shown to the user as a desktop notification. \
+        The inputs are the local time of the reminder, formatted as YYYY-MM-DD HH:MM, and \
+        the message to show. The current local time is {}.",
+        Local::now().format("%Y-%m-%d %H:%M (%A)")
+    )
+}
+
+pub(crate) async fn call(inputs: &[String]) -> String {
+    let [time, message, ..] = inputs else {
+        return "A reminder needs a time and a message.".to_owned();
+    };
+
+    let Some(at) = parse(time) else {
+        return format!("\"{time}\" is not a valid time. Use the YYYY-MM-DD HH:MM format.");
+    };
+
+    if at <= Local::now() {
+        return format!("{} is in the past.", at.format("%Y-%m-%d %H:%M"));
+    }
+
+    match Reminder::create(at, message.trim().to_owned()).await {
+        Ok(reminder) => format!(
+            "A reminder was set for {}: {}",
+            reminder.at.format("%Y-%m-%d %H:%M"),
+            reminder.message
+        ),
+        Err(error) => {
+            log::error!("Reminder could not be set: {error}");
+
+            format!("The reminder could not be set: {error}")
+        }
+    }
+}
+
+fn parse(time: &str) -> Option<DateTime<Local>> {
+    let time = time.trim();
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(time) {
+        return Some(time.with_timezone(&Local));
+    }
+
+    ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S"]
+        .into_iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(time, format).ok())
+        .and_then(|time| time.and_local_timezone(Local).earliest())
+}
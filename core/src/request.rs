@@ -1,12 +1,142 @@
 use crate::Error;
 
-use reqwest::IntoUrl;
+use decoder::{decode, encode, Value};
+use reqwest::header;
+use reqwest::{IntoUrl, StatusCode};
 use sipper::{sipper, Straw};
 use tokio::fs;
 use tokio::io::{self, AsyncWriteExt};
+use tokio::time;
 
 use std::path::Path;
-use std::time::Instant;
+use std::sync::atomic::{self, AtomicUsize};
+use std::sync::{Mutex, MutexGuard, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+static THROTTLE: RwLock<Throttle> = RwLock::new(Throttle {
+    limit: None,
+    pause_while_generating: false,
+});
+
+static BUCKET: Mutex<Option<Bucket>> = Mutex::new(None);
+
+static GENERATING: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Throttle {
+    // In KB/s
+    pub limit: Option<u64>,
+    pub pause_while_generating: bool,
+}
+
+impl Throttle {
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut throttle = decode::map(value)?;
+
+        Ok(Self {
+            // Zero means unlimited
+            limit: throttle
+                .optional("limit", decode::u64)?
+                .filter(|limit| *limit > 0),
+            pause_while_generating: throttle
+                .optional("pause_while_generating", decode::bool)?
+                .unwrap_or_default(),
+        })
+    }
+
+    pub(crate) fn encode(self) -> Value {
+        encode::map([
+            ("limit", encode::u64(self.limit.unwrap_or_default())),
+            (
+                "pause_while_generating",
+                encode::bool(self.pause_while_generating),
+            ),
+        ])
+        .into_value()
+    }
+}
+
+pub fn configure(throttle: Throttle) {
+    if let Ok(mut configuration) = THROTTLE.write() {
+        *configuration = throttle;
+    }
+}
+
+pub fn generating() -> Generating {
+    let _ = GENERATING.fetch_add(1, atomic::Ordering::Relaxed);
+
+    Generating
+}
+
+#[derive(Debug)]
+pub struct Generating;
+
+impl Drop for Generating {
+    fn drop(&mut self) {
+        let _ = GENERATING.fetch_sub(1, atomic::Ordering::Relaxed);
+    }
+}
+
+fn is_paused() -> bool {
+    THROTTLE
+        .read()
+        .is_ok_and(|throttle| throttle.pause_while_generating)
+        && GENERATING.load(atomic::Ordering::Relaxed) > 0
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    since: Instant,
+    received: u64,
+}
+
+async fn throttle(received: u64) {
+    if is_paused() {
+        while is_paused() {
+            time::sleep(Duration::from_millis(500)).await;
+        }
+
+        // The time paused does not count towards the rate
+        *bucket() = None;
+
+        return;
+    }
+
+    let Some(limit) = THROTTLE.read().ok().and_then(|throttle| throttle.limit) else {
+        *bucket() = None;
+
+        return;
+    };
+
+    let delay = {
+        let mut bucket = bucket();
+
+        let current = bucket.get_or_insert_with(|| Bucket {
+            since: Instant::now(),
+            received: 0,
+        });
+
+        current.received += received;
+
+        let expected = Duration::from_secs_f64(current.received as f64 / (limit * 1000) as f64);
+        let elapsed = current.since.elapsed();
+
+        // Idle time is not saved up for bursts later
+        if expected <= elapsed {
+            *bucket = None;
+        }
+
+        expected.saturating_sub(elapsed)
+    };
+
+    if !delay.is_zero() {
+        time::sleep(delay).await;
+    }
+}
+
+fn bucket() -> MutexGuard<'static, Option<Bucket>> {
+    BUCKET.lock().unwrap_or_else(PoisonError::into_inner)
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct Progress {
@@ -32,12 +162,44 @@ pub fn download_file<'a>(
 ) -> impl Straw<(), Progress, Error> + 'a {
     sipper(move |mut progress| async move {
         let destination = destination.as_ref();
-        let mut file = io::BufWriter::new(fs::File::create(destination).await?);
 
-        let mut download = reqwest::get(url).await?;
+        // Downloads interrupted before (e.g. by closing the app) are resumed
+        let existing = fs::metadata(destination)
+            .await
+            .map(|metadata| metadata.len())
+            .unwrap_or_default();
+
+        let mut request = reqwest::Client::new().get(url);
+
+        if existing > 0 {
+            request = request.header(header::RANGE, format!("bytes={existing}-"));
+        }
+
+        let download = request.send().await?;
+
+        // The previous download had already finished
+        if download.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            return Ok(());
+        }
+
+        let mut download = download.error_for_status()?;
+        let is_resumed = download.status() == StatusCode::PARTIAL_CONTENT;
+
+        let file = if is_resumed {
+            fs::OpenOptions::new()
+                .append(true)
+                .open(destination)
+                .await?
+        } else {
+            fs::File::create(destination).await?
+        };
+
+        let mut file = io::BufWriter::new(file);
         let start = Instant::now();
-        let total = download.content_length();
-        let mut downloaded = 0;
+        let mut downloaded = if is_resumed { existing } else { 0 };
+        let total = download
+            .content_length()
+            .map(|remaining| remaining + downloaded);
 
         progress
             .send(Progress {
@@ -47,9 +209,11 @@ pub fn download_file<'a>(
             })
             .await;
 
+        let resumed_at = downloaded;
+
         while let Some(chunk) = download.chunk().await? {
             downloaded += chunk.len() as u64;
-            let speed = (downloaded as f32 / start.elapsed().as_secs_f32()) as u64;
+            let speed = ((downloaded - resumed_at) as f32 / start.elapsed().as_secs_f32()) as u64;
 
             progress
                 .send(Progress {
@@ -60,6 +224,8 @@ pub fn download_file<'a>(
                 .await;
 
             file.write_all(&chunk).await?;
+
+            throttle(chunk.len() as u64).await;
         }
 
         file.flush().await?;
@@ -0,0 +1,80 @@
+use crate::directory;
+use crate::workspace::Layout;
+use crate::Error;
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub screen: Screen,
+    pub search: Search,
+    pub scroll: Option<f32>,
+    pub zen: BTreeSet<Screen>,
+    pub layout: Layout,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Screen {
+    #[default]
+    Conversation,
+    Search,
+    Settings,
+    Batch,
+    Arena,
+    Personas,
+    Git,
+    Translate,
+    Plugins,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Search {
+    pub query: String,
+    pub show_filters: bool,
+    pub show_local_models: bool,
+    pub show_online_models: bool,
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            show_filters: false,
+            show_local_models: false,
+            show_online_models: true,
+        }
+    }
+}
+
+impl Session {
+    pub fn fetch() -> Self {
+        std::fs::read(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub async fn save(self) -> Result<(), Error> {
+        let path = Self::path();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        fs::write(path, serde_json::to_vec_pretty(&self)?).await?;
+
+        Ok(())
+    }
+
+    fn path() -> PathBuf {
+        directory::data().join("session.json")
+    }
+}
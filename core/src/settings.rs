@@ -1,17 +1,118 @@
+use crate::approval;
+use crate::audit::Audit;
+use crate::azure::Azure;
+use crate::chat;
+use crate::codebase::Embeddings;
+use crate::conversion::Converter;
+use crate::database::Database;
+use crate::diffusion::Diffusion;
 use crate::directory;
+use crate::execution::Sandbox;
+use crate::hook::Hook;
+use crate::hosted;
 use crate::model;
+use crate::ocr::Ocr;
+use crate::persistence;
+use crate::preset::Preset;
+use crate::request::Throttle;
+use crate::ssh::Ssh;
+use crate::timeout::Timeouts;
+use crate::transcription::Whisper;
+use crate::web;
 use crate::Error;
+use crate::Url;
 
 use decoder::{decode, encode, Value};
 use log::warn;
-use tokio::fs;
 
 use std::path::PathBuf;
+use std::time::Duration;
 
-#[derive(Debug, Clone, Default)]
+const DEFAULT_COLLAPSE_HEIGHT: u32 = 600;
+const DEFAULT_STATUS_CHECKS: usize = 4;
+const DEFAULT_AGENT_STEPS: usize = 8;
+const DEFAULT_STATUS_MAX_AGE: Duration = Duration::from_secs(15 * 60);
+const DEFAULT_STATUS_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone)]
 pub struct Settings {
     pub library: model::Directory,
+    pub shared_library: Option<model::Directory>,
     pub theme: Theme,
+    pub share_endpoint: Option<Url>,
+    pub mirror: Option<Url>,
+    pub clipboard_watch: bool,
+    pub clipboard_actions: Vec<ClipboardAction>,
+    pub collapse_height: u32,
+    pub reminders: bool,
+    pub file_roots: Vec<PathBuf>,
+    pub fetch: web::Domains,
+    pub databases: Vec<Database>,
+    pub tool_policies: approval::Policies,
+    pub agent_steps: usize,
+    pub preload: Option<model::File>,
+    pub chat_format: chat::Format,
+    pub sandbox: Sandbox,
+    pub audit: Audit,
+    pub timeouts: Timeouts,
+    pub throttle: Throttle,
+    pub status_checks: usize,
+    pub status_max_age: Duration,
+    pub status_interval: Option<Duration>,
+    pub azure: Azure,
+    pub keys: hosted::Keys,
+    pub embeddings: Embeddings,
+    pub ssh: Ssh,
+    pub diffusion: Diffusion,
+    pub converter: Converter,
+    pub whisper: Whisper,
+    pub ocr: Ocr,
+    pub hooks: Vec<Hook>,
+    pub presets: Vec<Preset>,
+    pub sync: Option<PathBuf>,
+    pub window: Window,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            library: model::Directory::default(),
+            shared_library: None,
+            theme: Theme::default(),
+            share_endpoint: None,
+            mirror: None,
+            clipboard_watch: false,
+            clipboard_actions: ClipboardAction::defaults(),
+            collapse_height: DEFAULT_COLLAPSE_HEIGHT,
+            reminders: false,
+            file_roots: Vec::new(),
+            fetch: web::Domains::default(),
+            databases: Vec::new(),
+            tool_policies: approval::Policies::default(),
+            agent_steps: DEFAULT_AGENT_STEPS,
+            preload: None,
+            chat_format: chat::Format::default(),
+            sandbox: Sandbox::default(),
+            audit: Audit::default(),
+            timeouts: Timeouts::default(),
+            throttle: Throttle::default(),
+            status_checks: DEFAULT_STATUS_CHECKS,
+            status_max_age: DEFAULT_STATUS_MAX_AGE,
+            status_interval: Some(DEFAULT_STATUS_INTERVAL),
+            azure: Azure::default(),
+            keys: hosted::Keys::default(),
+            embeddings: Embeddings::default(),
+            ssh: Ssh::default(),
+            diffusion: Diffusion::default(),
+            converter: Converter::default(),
+            whisper: Whisper::default(),
+            ocr: Ocr::default(),
+            hooks: Vec::new(),
+            presets: Vec::new(),
+            sync: None,
+            window: Window::default(),
+        }
+    }
 }
 
 impl Settings {
@@ -29,48 +130,417 @@ impl Settings {
     pub async fn save(self) -> Result<(), Error> {
         let toml = toml::to_string_pretty(&self.encode())?;
 
-        let path = Self::path();
-
-        if let Some(directory) = path.parent() {
-            fs::create_dir_all(directory).await?;
-        }
-
-        fs::write(path, toml).await?;
-
-        Ok(())
+        persistence::write(Self::path(), toml).await
     }
 
-    fn decode(value: Value) -> decoder::Result<Self> {
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
         let mut settings = decode::map(value)?;
 
         let library = settings
             .optional("library", model::Directory::decode)?
             .unwrap_or_default();
 
+        let shared_library = settings.optional("shared_library", model::Directory::decode)?;
+
         let theme = settings
             .optional("theme", Theme::decode)?
             .unwrap_or_default();
 
-        Ok(Self { library, theme })
+        let share_endpoint = settings.optional("share_endpoint", |value| {
+            Url::parse(&decode::string(value)?).map_err(decoder::Error::custom)
+        })?;
+
+        let mirror = settings.optional("hf_mirror", |value| {
+            Url::parse(&decode::string(value)?).map_err(decoder::Error::custom)
+        })?;
+
+        let clipboard_watch = settings
+            .optional("clipboard_watch", decode::bool)?
+            .unwrap_or_default();
+
+        let clipboard_actions = settings
+            .optional(
+                "clipboard_actions",
+                decode::sequence(ClipboardAction::decode),
+            )?
+            .unwrap_or_else(ClipboardAction::defaults);
+
+        let collapse_height = settings
+            .optional("collapse_height", decode::u64)?
+            .map_or(DEFAULT_COLLAPSE_HEIGHT, |height| height as u32);
+
+        let sandbox = settings
+            .optional("sandbox", Sandbox::decode)?
+            .unwrap_or_default();
+
+        let audit = settings
+            .optional("audit", Audit::decode)?
+            .unwrap_or_default();
+
+        let timeouts = settings
+            .optional("timeouts", Timeouts::decode)?
+            .unwrap_or_default();
+
+        let throttle = settings
+            .optional("downloads", Throttle::decode)?
+            .unwrap_or_default();
+
+        let status_checks = settings
+            .optional("status_checks", decode::u64)?
+            .map_or(DEFAULT_STATUS_CHECKS, |checks| checks as usize);
+
+        let status_max_age = settings
+            .optional("status_max_age", decode::u64)?
+            .map_or(DEFAULT_STATUS_MAX_AGE, Duration::from_secs);
+
+        // Zero disables the periodic checks
+        let status_interval = settings
+            .optional("status_interval", decode::u64)?
+            .map_or(Some(DEFAULT_STATUS_INTERVAL), |seconds| {
+                (seconds > 0).then(|| Duration::from_secs(seconds))
+            });
+
+        let azure = settings
+            .optional("azure", Azure::decode)?
+            .unwrap_or_default();
+
+        let keys = settings
+            .optional("keys", hosted::Keys::decode)?
+            .unwrap_or_default();
+
+        let embeddings = settings
+            .optional("embeddings", Embeddings::decode)?
+            .unwrap_or_default();
+
+        let ssh = settings.optional("ssh", Ssh::decode)?.unwrap_or_default();
+
+        let diffusion = settings
+            .optional("diffusion", Diffusion::decode)?
+            .unwrap_or_default();
+
+        let converter = settings
+            .optional("conversion", Converter::decode)?
+            .unwrap_or_default();
+
+        let whisper = settings
+            .optional("whisper", Whisper::decode)?
+            .unwrap_or_default();
+
+        let ocr = settings.optional("ocr", Ocr::decode)?.unwrap_or_default();
+
+        let hooks = settings
+            .optional("hooks", decode::sequence(Hook::decode))?
+            .unwrap_or_default();
+
+        let presets = settings
+            .optional("presets", decode::sequence(Preset::decode))?
+            .unwrap_or_default();
+
+        let sync = settings
+            .optional("sync_folder", decode::string)?
+            .map(PathBuf::from);
+
+        let reminders = settings
+            .optional("reminders", decode::bool)?
+            .unwrap_or_default();
+
+        let file_roots = settings
+            .optional(
+                "file_roots",
+                decode::sequence(|value| decode::string(value).map(PathBuf::from)),
+            )?
+            .unwrap_or_default();
+
+        let fetch = settings
+            .optional("fetch", web::Domains::decode)?
+            .unwrap_or_default();
+
+        let databases = settings
+            .optional("databases", decode::sequence(Database::decode))?
+            .unwrap_or_default();
+
+        let tool_policies = settings
+            .optional("tool_policies", approval::Policies::decode)?
+            .unwrap_or_default();
+
+        let agent_steps = settings
+            .optional("agent_steps", decode::u64)?
+            .map_or(DEFAULT_AGENT_STEPS, |steps| steps.max(1) as usize);
+
+        let preload = settings.optional("preload", model::File::decode)?;
+
+        let chat_format = settings
+            .optional("chat_format", chat::Format::decode)?
+            .unwrap_or_default();
+
+        let window = settings
+            .optional("window", Window::decode)?
+            .unwrap_or_default();
+
+        Ok(Self {
+            library,
+            shared_library,
+            theme,
+            share_endpoint,
+            mirror,
+            clipboard_watch,
+            clipboard_actions,
+            collapse_height,
+            reminders,
+            file_roots,
+            fetch,
+            databases,
+            tool_policies,
+            agent_steps,
+            preload,
+            chat_format,
+            sandbox,
+            audit,
+            timeouts,
+            throttle,
+            status_checks,
+            status_max_age,
+            status_interval,
+            azure,
+            keys,
+            embeddings,
+            ssh,
+            diffusion,
+            converter,
+            whisper,
+            ocr,
+            hooks,
+            presets,
+            sync,
+            window,
+        })
     }
 
     fn encode(&self) -> Value {
-        encode::map([
+        let mut settings = vec![
             ("library", self.library.encode()),
             ("theme", self.theme.encode()),
-        ])
-        .into_value()
+            ("clipboard_watch", encode::bool(self.clipboard_watch)),
+            (
+                "clipboard_actions",
+                encode::sequence(self.clipboard_actions.iter().map(ClipboardAction::encode)),
+            ),
+            ("collapse_height", encode::u64(self.collapse_height.into())),
+            ("reminders", encode::bool(self.reminders)),
+            ("fetch", self.fetch.encode()),
+            (
+                "databases",
+                encode::sequence(self.databases.iter().map(Database::encode)),
+            ),
+            ("tool_policies", self.tool_policies.encode()),
+            ("agent_steps", encode::u64(self.agent_steps as u64)),
+            ("chat_format", self.chat_format.encode()),
+            (
+                "file_roots",
+                encode::sequence(
+                    self.file_roots
+                        .iter()
+                        .map(|root| encode::string(root.to_string_lossy().into_owned())),
+                ),
+            ),
+            ("sandbox", self.sandbox.encode()),
+            ("audit", self.audit.encode()),
+            ("timeouts", self.timeouts.encode()),
+            ("downloads", self.throttle.encode()),
+            ("status_checks", encode::u64(self.status_checks as u64)),
+            ("status_max_age", encode::u64(self.status_max_age.as_secs())),
+            (
+                "status_interval",
+                encode::u64(
+                    self.status_interval
+                        .map_or(0, |interval| interval.as_secs()),
+                ),
+            ),
+            ("azure", self.azure.encode()),
+            ("keys", self.keys.encode()),
+            ("embeddings", self.embeddings.encode()),
+            ("ssh", self.ssh.encode()),
+            ("diffusion", self.diffusion.encode()),
+            ("conversion", self.converter.encode()),
+            ("whisper", self.whisper.encode()),
+            ("ocr", self.ocr.encode()),
+            (
+                "hooks",
+                encode::sequence(self.hooks.iter().map(Hook::encode)),
+            ),
+            (
+                "presets",
+                encode::sequence(self.presets.iter().map(Preset::encode)),
+            ),
+            ("window", self.window.encode()),
+        ];
+
+        if let Some(endpoint) = &self.share_endpoint {
+            settings.push(("share_endpoint", encode::string(endpoint.as_str())));
+        }
+
+        if let Some(mirror) = &self.mirror {
+            settings.push(("hf_mirror", encode::string(mirror.as_str())));
+        }
+
+        if let Some(shared_library) = &self.shared_library {
+            settings.push(("shared_library", shared_library.encode()));
+        }
+
+        if let Some(file) = &self.preload {
+            settings.push(("preload", file.clone().encode()));
+        }
+
+        if let Some(folder) = &self.sync {
+            settings.push((
+                "sync_folder",
+                encode::string(folder.to_string_lossy().into_owned()),
+            ));
+        }
+
+        encode::map(settings).into_value()
     }
 
     fn path() -> PathBuf {
         directory::config().join("settings.toml")
     }
 
-    pub fn bookmarks(&self) -> PathBuf  {
+    pub fn bookmarks(&self) -> PathBuf {
         directory::config().join("bookmarks.json")
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    pub width: u32,
+    pub height: u32,
+    pub position: Option<(i32, i32)>,
+    pub monitor: Option<(u32, u32)>,
+    pub sidebar_width: u32,
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Self {
+            width: 1024,
+            height: 768,
+            position: None,
+            monitor: None,
+            sidebar_width: 250,
+        }
+    }
+}
+
+impl Window {
+    fn decode(value: Value) -> decoder::Result<Self> {
+        let mut window = decode::map(value)?;
+        let default = Self::default();
+
+        let dimension = |value| -> decoder::Result<u32> {
+            u32::try_from(decode::u64(value)?).map_err(decoder::Error::custom)
+        };
+
+        // Stored as "x,y", since coordinates may be negative
+        let pair = |value| -> decoder::Result<(i32, i32)> {
+            let pair = decode::string(value)?;
+
+            pair.split_once(',')
+                .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+                .ok_or_else(|| decoder::Error::custom(format!("invalid position: {pair}")))
+        };
+
+        Ok(Self {
+            width: window
+                .optional("width", dimension)?
+                .unwrap_or(default.width),
+            height: window
+                .optional("height", dimension)?
+                .unwrap_or(default.height),
+            position: window.optional("position", pair)?,
+            monitor: window
+                .optional("monitor", decode::sequence(dimension))?
+                .and_then(|size| Some((*size.first()?, *size.get(1)?))),
+            sidebar_width: window
+                .optional("sidebar_width", dimension)?
+                .unwrap_or(default.sidebar_width),
+        })
+    }
+
+    fn encode(&self) -> Value {
+        let mut window = vec![
+            ("width", encode::u64(u64::from(self.width))),
+            ("height", encode::u64(u64::from(self.height))),
+            ("sidebar_width", encode::u64(u64::from(self.sidebar_width))),
+        ];
+
+        if let Some((x, y)) = self.position {
+            window.push(("position", encode::string(format!("{x},{y}"))));
+        }
+
+        if let Some((width, height)) = self.monitor {
+            window.push((
+                "monitor",
+                encode::sequence([u64::from(width), u64::from(height)].map(encode::u64)),
+            ));
+        }
+
+        encode::map(window).into_value()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardAction {
+    pub name: String,
+    pub prompt: String,
+}
+
+impl ClipboardAction {
+    pub fn defaults() -> Vec<Self> {
+        [
+            ("Summarize", "Summarize the following text:\n\n{text}"),
+            (
+                "Explain",
+                "Explain the following text in simple terms:\n\n{text}",
+            ),
+            (
+                "Translate",
+                "Translate the following text to English:\n\n{text}",
+            ),
+        ]
+        .into_iter()
+        .map(|(name, prompt)| Self {
+            name: name.to_owned(),
+            prompt: prompt.to_owned(),
+        })
+        .collect()
+    }
+
+    pub fn prompt(&self, text: &str) -> String {
+        if self.prompt.contains("{text}") {
+            self.prompt.replace("{text}", text)
+        } else {
+            format!("{}\n\n{text}", self.prompt)
+        }
+    }
+
+    fn decode(value: Value) -> decoder::Result<Self> {
+        let mut action = decode::map(value)?;
+
+        Ok(Self {
+            name: action.required("name", decode::string)?,
+            prompt: action.required("prompt", decode::string)?,
+        })
+    }
+
+    fn encode(&self) -> Value {
+        encode::map([
+            ("name", encode::string(&self.name)),
+            ("prompt", encode::string(&self.prompt)),
+        ])
+        .into_value()
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub enum Theme {
     Light,
@@ -0,0 +1,163 @@
+use crate::duplicate;
+use crate::model::{self, APIType, Directory, FileOrAPI, Library};
+use crate::request;
+use crate::Error;
+
+use serde::{Deserialize, Serialize};
+use sipper::{sipper, Sipper, Straw};
+use thiserror::capture;
+use tokio::fs;
+use tokio::task;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub app_version: String,
+    pub created_at: chrono::DateTime<chrono::Local>,
+    #[serde(default)]
+    pub models: Vec<Model>,
+    #[serde(default)]
+    pub providers: Vec<Provider>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Model {
+    pub repository: String,
+    pub file: String,
+    #[serde(default)]
+    pub quantization: Option<String>,
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provider {
+    pub kind: APIType,
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub model: usize,
+    pub total: usize,
+    pub percent: u32,
+}
+
+pub async fn export(library: Arc<Library>, path: PathBuf) -> Result<Manifest, Error> {
+    let mut files: Vec<_> = library
+        .files
+        .values()
+        .filter_map(|file| match file {
+            FileOrAPI::File(file) => Some(file.clone()),
+            FileOrAPI::API(_) => None,
+        })
+        .collect();
+
+    files.sort_by(|a, b| (&a.model.0, &a.name).cmp(&(&b.model.0, &b.name)));
+
+    let mut models = Vec::with_capacity(files.len());
+
+    for file in files {
+        let path = library
+            .directory_of(&file)
+            .path()
+            .join(file.relative_path());
+
+        let sha256 = task::spawn_blocking(move || duplicate::checksum(&path)).await??;
+
+        models.push(Model {
+            repository: file.model.0.clone(),
+            quantization: file.variant().map(str::to_owned),
+            file: file.name,
+            sha256: Some(sha256),
+        });
+    }
+
+    let mut providers: Vec<_> = library
+        .api_src
+        .values()
+        .map(|access| Provider {
+            kind: access.kind.clone(),
+            base_url: access.base_url.clone(),
+        })
+        .collect();
+
+    providers.sort_by_key(|provider| format!("{:?}", provider.kind));
+
+    let manifest = Manifest {
+        app_version: env!("CARGO_PKG_VERSION").to_owned(),
+        created_at: chrono::Local::now(),
+        models,
+        providers,
+    };
+
+    fs::write(path, toml::to_string_pretty(&manifest)?).await?;
+
+    Ok(manifest)
+}
+
+pub async fn read(path: PathBuf) -> Result<Manifest, Error> {
+    let manifest = fs::read_to_string(path).await?;
+
+    Ok(toml::from_str(&manifest)?)
+}
+
+pub fn install(models: Vec<Model>, directory: Directory) -> impl Straw<usize, Progress, Error> {
+    sipper(move |mut sender| async move {
+        let total = models.len();
+        let mut downloaded = 0;
+
+        for (index, model) in models.into_iter().enumerate() {
+            let file = model::File {
+                model: model::Id(model.repository.clone()),
+                name: model.file.clone(),
+                size: None,
+                hub: model::Hub::default(),
+            };
+
+            let path = directory.path().join(file.relative_path());
+
+            if fs::try_exists(&path).await? {
+                continue;
+            }
+
+            let path = sipper(|downloads| async { file.download(&directory, downloads).await })
+                .with(move |progress: request::Progress| Progress {
+                    model: index,
+                    total,
+                    percent: progress.percent().map_or(0, |(_total, percent)| percent),
+                })
+                .run(&sender)
+                .await?;
+
+            if let Some(expected) = model.sha256 {
+                let actual = {
+                    let path = path.clone();
+
+                    task::spawn_blocking(move || duplicate::checksum(&path)).await??
+                };
+
+                if !actual.eq_ignore_ascii_case(&expected) {
+                    let _ = fs::remove_file(&path).await;
+
+                    return Err(Error::ChecksumMismatch(model.file, capture!()));
+                }
+            }
+
+            downloaded += 1;
+        }
+
+        sender
+            .send(Progress {
+                model: total,
+                total,
+                percent: 100,
+            })
+            .await;
+
+        Ok(downloaded)
+    })
+}
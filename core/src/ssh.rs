@@ -0,0 +1,228 @@
+use crate::Error;
+
+use decoder::{decode, encode, Value};
+use serde::{Deserialize, Serialize};
+use thiserror::capture;
+use tokio::process;
+use tokio::sync::Mutex;
+use tokio::time;
+
+use std::net::{Ipv4Addr, TcpListener};
+use std::process::Stdio;
+use std::time::Duration;
+
+const DEFAULT_PORT: u16 = 22;
+const DEFAULT_SERVER: &str = "llama-server";
+
+const BOOT_TIMEOUT: Duration = Duration::from_secs(300);
+
+static TUNNEL: Mutex<Option<Tunnel>> = Mutex::const_new(None);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ssh {
+    pub host: String,
+    pub port: u16,
+    pub identity: String,
+    pub directory: String,
+    pub server: String,
+}
+
+impl Default for Ssh {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: DEFAULT_PORT,
+            identity: String::new(),
+            directory: String::new(),
+            server: DEFAULT_SERVER.to_owned(),
+        }
+    }
+}
+
+impl Ssh {
+    pub fn is_configured(&self) -> bool {
+        !self.host.trim().is_empty() && !self.directory.trim().is_empty()
+    }
+
+    pub async fn models(&self) -> Result<Vec<String>, Error> {
+        let directory = self.directory.trim().trim_end_matches('/');
+
+        let output = self
+            .command()
+            .arg(format!("find {} -name '*.gguf' -type f", quote(directory)))
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            return Err(Error::RemoteFailed(
+                String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+                capture!(),
+            ));
+        }
+
+        let mut models: Vec<_> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|path| path.strip_prefix(directory))
+            .map(|path| path.trim_start_matches('/').to_owned())
+            // Skip all but the first part of split models
+            .filter(|path| !path.contains("-of-") || path.contains("-00001-of-"))
+            .collect();
+
+        models.sort();
+
+        Ok(models)
+    }
+
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut ssh = decode::map(value)?;
+        let default = Self::default();
+
+        Ok(Self {
+            host: ssh.optional("host", decode::string)?.unwrap_or_default(),
+            port: ssh
+                .optional("port", decode::u64)?
+                .and_then(|port| u16::try_from(port).ok())
+                .unwrap_or(default.port),
+            identity: ssh
+                .optional("identity", decode::string)?
+                .unwrap_or_default(),
+            directory: ssh
+                .optional("directory", decode::string)?
+                .unwrap_or_default(),
+            server: ssh
+                .optional("server", decode::string)?
+                .unwrap_or(default.server),
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        encode::map([
+            ("host", encode::string(&self.host)),
+            ("port", encode::u64(u64::from(self.port))),
+            ("identity", encode::string(&self.identity)),
+            ("directory", encode::string(&self.directory)),
+            ("server", encode::string(&self.server)),
+        ])
+        .into_value()
+    }
+
+    fn command(&self) -> process::Command {
+        let mut command = process::Command::new("ssh");
+
+        let _ = command
+            .args(["-o", "BatchMode=yes", "-o", "ConnectTimeout=10"])
+            .arg("-p")
+            .arg(self.port.to_string());
+
+        if !self.identity.trim().is_empty() {
+            let _ = command.arg("-i").arg(self.identity.trim());
+        }
+
+        let _ = command.arg(self.host.trim());
+
+        command
+    }
+}
+
+#[derive(Debug)]
+struct Tunnel {
+    ssh: Ssh,
+    model: String,
+    port: u16,
+    process: process::Child,
+}
+
+pub async fn connect(ssh: &Ssh, model: &str) -> Result<String, Error> {
+    let mut tunnel = TUNNEL.lock().await;
+
+    if let Some(current) = tunnel.as_mut() {
+        let is_alive = current
+            .process
+            .try_wait()
+            .is_ok_and(|status| status.is_none());
+
+        if is_alive && current.ssh == *ssh && current.model == model {
+            return Ok(url(current.port));
+        }
+    }
+
+    // Dropping the old tunnel hangs up on its server
+    *tunnel = None;
+
+    let port = free_port()?;
+    let path = format!("{}/{model}", ssh.directory.trim().trim_end_matches('/'));
+
+    let mut process = ssh
+        .command()
+        // A terminal makes the remote server exit when the tunnel closes
+        .arg("-tt")
+        .args(["-o", "ExitOnForwardFailure=yes"])
+        .arg("-L")
+        .arg(format!("{port}:127.0.0.1:{port}"))
+        .arg(format!(
+            "{server} --model {path} --host 127.0.0.1 --port {port}",
+            server = ssh.server.trim(),
+            path = quote(&path),
+        ))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let client = reqwest::Client::new();
+    let health = format!("http://127.0.0.1:{port}/health");
+
+    let boot = async {
+        loop {
+            if let Some(status) = process.try_wait()? {
+                return Err(Error::RemoteFailed(
+                    format!("the tunnel closed with {status}"),
+                    capture!(),
+                ));
+            }
+
+            if client
+                .get(&health)
+                .send()
+                .await
+                .is_ok_and(|response| response.status().is_success())
+            {
+                return Ok(());
+            }
+
+            time::sleep(Duration::from_secs(1)).await;
+        }
+    };
+
+    time::timeout(BOOT_TIMEOUT, boot)
+        .await
+        .map_err(|_elapsed| {
+            Error::TimedOut("the remote model took too long to load", capture!())
+        })??;
+
+    *tunnel = Some(Tunnel {
+        ssh: ssh.clone(),
+        model: model.to_owned(),
+        port,
+        process,
+    });
+
+    Ok(url(port))
+}
+
+fn url(port: u16) -> String {
+    format!("http://127.0.0.1:{port}/v1")
+}
+
+fn free_port() -> Result<u16, Error> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+
+    Ok(listener.local_addr()?.port())
+}
+
+fn quote(argument: &str) -> String {
+    format!("'{}'", argument.replace('\'', r"'\''"))
+}
@@ -0,0 +1,201 @@
+use crate::assistant::{Assistant, Reply};
+use crate::directory;
+use crate::Error;
+
+use langchain_rust::schemas::Message;
+use sipper::{sipper, Straw};
+use tokio::fs;
+use url::Url;
+use uuid::Uuid;
+
+use std::path::PathBuf;
+
+// Roughly 2k tokens
+const CHUNK_SIZE: usize = 8_000;
+
+const SYSTEM_PROMPT: &str = "You summarize documents faithfully and concisely. \
+    Keep names, numbers, and conclusions. Never add information that is not in the text.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    Mapped { done: usize, total: usize },
+    Reducing { round: usize },
+}
+
+impl Progress {
+    pub fn percent(self) -> u32 {
+        match self {
+            Self::Mapped { done, total } => (done * 90 / total.max(1)) as u32,
+            Self::Reducing { .. } => 90,
+        }
+    }
+}
+
+pub fn summarize(
+    assistant: &Assistant,
+    name: String,
+    document: String,
+) -> impl Straw<Reply, Progress, Error> {
+    let assistant = assistant.clone();
+
+    sipper(move |mut progress| async move {
+        let chunks = chunk(&document, CHUNK_SIZE);
+        let total = chunks.len();
+
+        let directory = directory::data()
+            .join("summaries")
+            .join(Uuid::new_v4().simple().to_string());
+
+        fs::create_dir_all(&directory).await?;
+
+        let mut summaries = Vec::with_capacity(total);
+        let mut links = Vec::with_capacity(total);
+
+        progress.send(Progress::Mapped { done: 0, total }).await;
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            let summary = ask(
+                &assistant,
+                format!(
+                    "This is part {part} of {total} of \"{name}\". \
+                    Summarize it in a few paragraphs:\n\n{chunk}",
+                    part = i + 1,
+                ),
+            )
+            .await?;
+
+            let path = directory.join(format!("part-{}.md", i + 1));
+            fs::write(
+                &path,
+                format!("# {name}: part {} of {total}\n\n{summary}\n", i + 1),
+            )
+            .await?;
+
+            links.push(link(i + 1, path));
+            summaries.push(summary);
+
+            progress.send(Progress::Mapped { done: i + 1, total }).await;
+        }
+
+        let mut round = 0;
+
+        // Combines neighbouring summaries until they all fit in a single prompt
+        while summaries.join("\n\n").len() > CHUNK_SIZE && summaries.len() > 1 {
+            round += 1;
+            progress.send(Progress::Reducing { round }).await;
+
+            let mut groups = group(&summaries, CHUNK_SIZE);
+
+            // Long summaries may not fit together; pair them up to keep shrinking
+            if groups.len() == summaries.len() {
+                groups = summaries.chunks(2).map(|pair| pair.join("\n\n")).collect();
+            }
+
+            let mut combined = Vec::new();
+
+            for group in groups {
+                combined.push(
+                    ask(
+                        &assistant,
+                        format!(
+                            "These are consecutive summaries of parts of \"{name}\". \
+                            Merge them into a single summary:\n\n{group}"
+                        ),
+                    )
+                    .await?,
+                );
+            }
+
+            summaries = combined;
+        }
+
+        progress.send(Progress::Reducing { round: round + 1 }).await;
+
+        let synthesis = ask(
+            &assistant,
+            format!(
+                "These are summaries of the parts of \"{name}\", in order. \
+                Write the final summary of the whole document, starting with \
+                a one-sentence overview and then its key points:\n\n{}",
+                summaries.join("\n\n")
+            ),
+        )
+        .await?;
+
+        Ok(Reply {
+            reasoning: None,
+            content: format!(
+                "{synthesis}\n\n---\n\nSummaries of each part: {}",
+                links.join(" · ")
+            ),
+            last_token: None,
+            endpoint: None,
+            provenance: Some(assistant.provenance()),
+            usage: None,
+        })
+    })
+}
+
+async fn ask(assistant: &Assistant, prompt: String) -> Result<String, Error> {
+    let messages = [Message::new_human_message(prompt)];
+    let reply = assistant.reply(SYSTEM_PROMPT, &messages, &[]).await?;
+
+    Ok(reply.content)
+}
+
+fn link(part: usize, path: PathBuf) -> String {
+    match Url::from_file_path(&path) {
+        Ok(url) => format!("[{part}]({url})"),
+        Err(()) => part.to_string(),
+    }
+}
+
+fn chunk(text: &str, size: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text.trim();
+
+    while rest.len() > size {
+        let mut end = size;
+
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let window = &rest[..end];
+
+        let split = ["\n\n", "\n", " "]
+            .iter()
+            .find_map(|separator| {
+                window
+                    .rfind(separator)
+                    .filter(|position| *position > size / 2)
+                    .map(|position| position + separator.len())
+            })
+            .unwrap_or(end);
+
+        chunks.push(rest[..split].trim());
+        rest = rest[split..].trim_start();
+    }
+
+    if !rest.is_empty() {
+        chunks.push(rest);
+    }
+
+    chunks
+}
+
+fn group(texts: &[String], size: usize) -> Vec<String> {
+    let mut groups: Vec<String> = Vec::new();
+
+    for text in texts {
+        match groups.last_mut() {
+            Some(group) if group.len() + text.len() + 2 <= size => {
+                group.push_str("\n\n");
+                group.push_str(text);
+            }
+            _ => groups.push(text.clone()),
+        }
+    }
+
+    groups
+}
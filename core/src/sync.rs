@@ -0,0 +1,376 @@
+use crate::chat::{self, Chat, Format};
+use crate::directory;
+use crate::persistence;
+use crate::settings::Settings;
+use crate::Error;
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use uuid::Uuid;
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const SETTINGS: &str = "settings.toml";
+const CHATS: &str = "chats";
+
+#[derive(Debug, Clone, Default)]
+pub struct Summary {
+    pub pushed: usize,
+    pub pulled: usize,
+    pub settings: bool,
+    pub conflicts: Vec<Conflict>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    file: String,
+    version: Version,
+    pub local: Arc<Chat>,
+    pub remote: Arc<Chat>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    KeepLocal,
+    KeepRemote,
+    KeepBoth,
+}
+
+pub async fn run(folder: PathBuf) -> Result<Summary, Error> {
+    let mut state = State::fetch().await?;
+    let mut summary = Summary::default();
+
+    let mut files = vec![SETTINGS.to_owned()];
+    files.extend(chats(&directory::data().join(CHATS)).await?);
+    files.extend(chats(&folder.join(CHATS)).await?);
+    files.sort();
+    files.dedup();
+
+    for file in files {
+        sync(&folder, &file, &mut state, &mut summary).await?;
+    }
+
+    state.save().await?;
+
+    Ok(summary)
+}
+
+pub async fn resolve(
+    folder: PathBuf,
+    conflict: Conflict,
+    resolution: Resolution,
+) -> Result<(), Error> {
+    let mut state = State::fetch().await?;
+
+    match resolution {
+        Resolution::KeepLocal => {}
+        Resolution::KeepRemote => {
+            let bytes = fs::read(folder.join(&conflict.file)).await?;
+            let _ = Chat::import(bytes).await?;
+        }
+        Resolution::KeepBoth => {
+            let remote = Arc::unwrap_or_clone(conflict.remote);
+            let title = format!(
+                "{} (other device)",
+                remote.title.as_deref().unwrap_or("Untitled")
+            );
+
+            let _ = Chat::create(
+                remote.file,
+                Some(title),
+                remote.history,
+                remote.persona,
+                remote.lock,
+                remote.todos,
+                remote.checkpoints,
+            )
+            .await?;
+        }
+    }
+
+    let mut version = conflict.version;
+    version.increment(state.device);
+
+    push(&folder, &conflict.file, version, &mut state).await?;
+
+    state.save().await
+}
+
+async fn sync(
+    folder: &Path,
+    file: &str,
+    state: &mut State,
+    summary: &mut Summary,
+) -> Result<(), Error> {
+    let local = local(file).await?;
+    let remote = read(&folder.join(file)).await?;
+    let remote_version = Version::fetch(&folder.join(sidecar(file))).await?;
+
+    let synced = state.files.get(file);
+    let mut version = synced
+        .map(|synced| synced.version.clone())
+        .unwrap_or_default();
+
+    if let Some(local) = &local {
+        if synced.is_none_or(|synced| synced.hash != hash(local)) {
+            version.increment(state.device);
+        }
+    }
+
+    match (local, remote) {
+        (None, None) => {}
+        (Some(_), None) => {
+            push(folder, file, version, state).await?;
+            summary.pushed += 1;
+        }
+        (None, Some(remote)) => {
+            pull(file, remote, remote_version, state, summary).await?;
+        }
+        (Some(local), Some(remote)) => match version.compare(&remote_version) {
+            Order::Equal => {}
+            Order::Ahead => {
+                push(folder, file, version, state).await?;
+                summary.pushed += 1;
+            }
+            Order::Behind => {
+                pull(file, remote, remote_version, state, summary).await?;
+            }
+            Order::Concurrent if local == remote || file == SETTINGS => {
+                version.merge(&remote_version);
+                version.increment(state.device);
+
+                push(folder, file, version, state).await?;
+                summary.pushed += 1;
+            }
+            Order::Concurrent => {
+                let local = Chat::decode(&local)?;
+                let remote = Chat::decode(&remote)?;
+
+                version.merge(&remote_version);
+
+                summary.conflicts.push(Conflict {
+                    file: file.to_owned(),
+                    version,
+                    local: Arc::new(local),
+                    remote: Arc::new(remote),
+                });
+            }
+        },
+    }
+
+    Ok(())
+}
+
+async fn push(folder: &Path, file: &str, version: Version, state: &mut State) -> Result<(), Error> {
+    let Some(bytes) = local(file).await? else {
+        return Ok(());
+    };
+
+    let hash = hash(&bytes);
+
+    // The content goes first; a newer version next to older content would
+    // make other devices skip the change for good
+    persistence::write(folder.join(file), bytes).await?;
+    persistence::write(
+        folder.join(sidecar(file)),
+        serde_json::to_vec_pretty(&version)?,
+    )
+    .await?;
+
+    let _ = state
+        .files
+        .insert(file.to_owned(), Synced { version, hash });
+
+    Ok(())
+}
+
+async fn pull(
+    file: &str,
+    remote: Vec<u8>,
+    version: Version,
+    state: &mut State,
+    summary: &mut Summary,
+) -> Result<(), Error> {
+    if file == SETTINGS {
+        let mut settings = Settings::decode(toml::from_str(&String::from_utf8_lossy(&remote))?)?;
+
+        // Paths and windows are specific to each device
+        if let Ok(current) = Settings::fetch() {
+            settings.library = current.library;
+            settings.sync = current.sync;
+            settings.window = current.window;
+        }
+
+        settings.save().await?;
+        summary.settings = true;
+    } else {
+        let _ = Chat::import(remote).await?;
+    }
+
+    summary.pulled += 1;
+
+    let bytes = local(file).await?.unwrap_or_default();
+
+    let _ = state.files.insert(
+        file.to_owned(),
+        Synced {
+            version,
+            hash: hash(&bytes),
+        },
+    );
+
+    Ok(())
+}
+
+async fn chats(directory: &Path) -> Result<Vec<String>, Error> {
+    let mut entries = match fs::read_dir(directory).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error.into()),
+    };
+
+    let mut files = Vec::new();
+
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        // Skips the chat list and the copies sync programs make on conflict
+        let id = Format::ALL.iter().find_map(|format| {
+            name.strip_suffix(format.extension())?
+                .strip_suffix('.')
+                .filter(|id| Uuid::parse_str(id).is_ok())
+        });
+
+        // Chats are shared as JSON, whatever the format of each device
+        if let Some(id) = id {
+            files.push(format!("{CHATS}/{id}.json"));
+        }
+    }
+
+    Ok(files)
+}
+
+async fn local(file: &str) -> Result<Option<Vec<u8>>, Error> {
+    let chat = file
+        .strip_prefix(CHATS)
+        .and_then(|name| name.strip_prefix('/')?.strip_suffix(".json"))
+        .and_then(chat::Id::parse);
+
+    match chat {
+        Some(id) => Chat::shared(id).await,
+        None => read(&local_path(file)).await,
+    }
+}
+
+fn local_path(file: &str) -> PathBuf {
+    if file == SETTINGS {
+        directory::config().join(file)
+    } else {
+        directory::data().join(file)
+    }
+}
+
+fn sidecar(file: &str) -> String {
+    format!("{file}.version")
+}
+
+async fn read(path: &Path) -> Result<Option<Vec<u8>>, Error> {
+    match fs::read(path).await {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Version(BTreeMap<Uuid, u64>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Order {
+    Equal,
+    Ahead,
+    Behind,
+    Concurrent,
+}
+
+impl Version {
+    async fn fetch(path: &Path) -> Result<Self, Error> {
+        let Some(bytes) = read(path).await? else {
+            return Ok(Self::default());
+        };
+
+        Ok(serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    fn increment(&mut self, device: Uuid) {
+        *self.0.entry(device).or_default() += 1;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (device, count) in &other.0 {
+            let current = self.0.entry(*device).or_default();
+            *current = (*current).max(*count);
+        }
+    }
+
+    fn compare(&self, other: &Self) -> Order {
+        let mut is_ahead = false;
+        let mut is_behind = false;
+
+        for device in self.0.keys().chain(other.0.keys()) {
+            let mine = self.0.get(device).copied().unwrap_or_default();
+            let theirs = other.0.get(device).copied().unwrap_or_default();
+
+            is_ahead |= mine > theirs;
+            is_behind |= mine < theirs;
+        }
+
+        match (is_ahead, is_behind) {
+            (false, false) => Order::Equal,
+            (true, false) => Order::Ahead,
+            (false, true) => Order::Behind,
+            (true, true) => Order::Concurrent,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct State {
+    device: Uuid,
+    files: BTreeMap<String, Synced>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Synced {
+    version: Version,
+    hash: u64,
+}
+
+impl State {
+    fn path() -> PathBuf {
+        directory::data().join("sync.json")
+    }
+
+    async fn fetch() -> Result<Self, Error> {
+        match read(&Self::path()).await? {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            None => Ok(Self {
+                device: Uuid::new_v4(),
+                files: BTreeMap::new(),
+            }),
+        }
+    }
+
+    async fn save(&self) -> Result<(), Error> {
+        persistence::write(Self::path(), serde_json::to_vec_pretty(self)?).await
+    }
+}
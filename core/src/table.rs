@@ -0,0 +1,237 @@
+use crate::assistant::Assistant;
+use crate::Error;
+
+use langchain_rust::schemas::Message;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use thiserror::capture;
+use tokio::fs;
+
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        #[derive(Deserialize)]
+        struct Raw {
+            columns: Vec<String>,
+            rows: Vec<Vec<Value>>,
+        }
+
+        let start = json.find('{');
+        let end = json.rfind('}');
+
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err(Error::InvalidResponse("the model", capture!()));
+        };
+
+        let raw: Raw = serde_json::from_str(&json[start..=end])?;
+
+        if raw.columns.is_empty() {
+            return Err(Error::InvalidResponse("the model", capture!()));
+        }
+
+        let width = raw.columns.len();
+
+        let rows = raw
+            .rows
+            .into_iter()
+            .map(|row| {
+                let mut cells: Vec<String> = row
+                    .into_iter()
+                    .map(|cell| match cell {
+                        Value::String(cell) => cell,
+                        Value::Null => String::new(),
+                        cell => cell.to_string(),
+                    })
+                    .collect();
+
+                cells.resize(width, String::new());
+                cells
+            })
+            .collect();
+
+        Ok(Self {
+            columns: raw.columns,
+            rows,
+        })
+    }
+
+    pub fn from_csv(csv: &str) -> Self {
+        let mut records = Vec::new();
+        let mut record = Vec::new();
+        let mut field = String::new();
+        let mut is_quoted = false;
+        let mut chars = csv.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if is_quoted && chars.peek() == Some(&'"') => {
+                    let _ = chars.next();
+                    field.push('"');
+                }
+                '"' => {
+                    is_quoted = !is_quoted;
+                }
+                ',' if !is_quoted => {
+                    record.push(std::mem::take(&mut field));
+                }
+                '\r' if !is_quoted => {}
+                '\n' if !is_quoted => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                c => field.push(c),
+            }
+        }
+
+        if !field.is_empty() || !record.is_empty() {
+            record.push(field);
+            records.push(record);
+        }
+
+        let mut records = records.into_iter();
+        let columns = records.next().unwrap_or_default();
+
+        let rows = records
+            .map(|mut row| {
+                row.resize(columns.len(), String::new());
+                row
+            })
+            .collect();
+
+        Self { columns, rows }
+    }
+
+    pub fn from_markdown(markdown: &str) -> Vec<Self> {
+        let mut tables = Vec::new();
+        let mut table: Option<Self> = None;
+        let mut row = Vec::new();
+        let mut cell: Option<String> = None;
+
+        for event in Parser::new_ext(markdown, Options::ENABLE_TABLES) {
+            match event {
+                Event::Start(Tag::Table(_)) => {
+                    table = Some(Self::default());
+                }
+                Event::Start(Tag::TableCell) => {
+                    cell = Some(String::new());
+                }
+                Event::Text(text) | Event::Code(text) => {
+                    if let Some(cell) = &mut cell {
+                        cell.push_str(&text);
+                    }
+                }
+                Event::End(TagEnd::TableCell) => {
+                    row.extend(cell.take().map(|cell| cell.trim().to_owned()));
+                }
+                Event::End(TagEnd::TableHead) => {
+                    if let Some(table) = &mut table {
+                        table.columns = std::mem::take(&mut row);
+                    }
+                }
+                Event::End(TagEnd::TableRow) => {
+                    if let Some(table) = &mut table {
+                        let mut cells = std::mem::take(&mut row);
+                        cells.resize(table.columns.len(), String::new());
+
+                        table.rows.push(cells);
+                    }
+                }
+                Event::End(TagEnd::Table) => {
+                    tables.extend(table.take());
+                }
+                _ => {}
+            }
+        }
+
+        tables
+    }
+
+    pub fn to_csv(&self) -> String {
+        fn escape(cell: &str) -> String {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.to_owned()
+            }
+        }
+
+        std::iter::once(&self.columns)
+            .chain(&self.rows)
+            .map(|row| {
+                row.iter()
+                    .map(|cell| escape(cell))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        let rows: Vec<Value> = self
+            .rows
+            .iter()
+            .map(|row| {
+                Value::Object(
+                    self.columns
+                        .iter()
+                        .cloned()
+                        .zip(row.iter().cloned().map(Value::String))
+                        .collect(),
+                )
+            })
+            .collect();
+
+        serde_json::to_string_pretty(&rows).unwrap_or_default()
+    }
+
+    pub async fn save(self, path: PathBuf) -> Result<(), Error> {
+        let content = if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            self.to_json()
+        } else {
+            self.to_csv()
+        };
+
+        fs::write(path, content).await?;
+
+        Ok(())
+    }
+}
+
+pub async fn extract(assistant: Assistant, text: String) -> Result<Table, Error> {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "columns": { "type": "array", "items": { "type": "string" } },
+            "rows": {
+                "type": "array",
+                "items": {
+                    "type": "array",
+                    "items": { "type": ["string", "number", "boolean", "null"] }
+                }
+            }
+        },
+        "required": ["columns", "rows"]
+    });
+
+    let request = format!(
+        "Extract the structured data in the following text as a table. \
+        Answer only with a JSON object following this schema, with one \
+        value per column in every row:\n\n{schema}\n\nText:\n\n{text}"
+    );
+
+    assistant
+        .structured(vec![Message::new_human_message(request)], Table::from_json)
+        .await
+}
@@ -0,0 +1,277 @@
+use futures::stream::{self, Stream};
+use serde::Deserialize;
+use tokio::process;
+use tokio::time;
+
+use std::collections::BTreeMap;
+use std::process::Stdio;
+use std::time::Duration;
+
+const INTERVAL: Duration = Duration::from_secs(2);
+
+const PRESSURE: f32 = 0.95;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Target {
+    Process(u32),
+    Container(String),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sample {
+    pub gpu: Option<Gpu>,
+    pub memory: Option<Memory>,
+    // In bytes
+    pub rss: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gpu {
+    pub utilization: u32,
+    pub used: u64,
+    pub total: u64,
+}
+
+// In bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Memory {
+    pub available: u64,
+    pub total: u64,
+}
+
+impl Sample {
+    pub fn is_under_pressure(&self) -> bool {
+        self.gpu.is_some_and(|gpu| gpu.memory() > PRESSURE)
+            || self.memory.is_some_and(|memory| memory.used() > PRESSURE)
+    }
+}
+
+impl Gpu {
+    pub fn memory(&self) -> f32 {
+        fraction(self.used, self.total)
+    }
+}
+
+impl Memory {
+    pub fn used(&self) -> f32 {
+        fraction(self.total - self.available.min(self.total), self.total)
+    }
+}
+
+pub fn watch(target: Target) -> impl Stream<Item = Sample> {
+    stream::unfold((target, true), |(target, is_first)| async move {
+        if !is_first {
+            time::sleep(INTERVAL).await;
+        }
+
+        let sample = sample(&target).await;
+
+        Some((sample, (target, false)))
+    })
+}
+
+pub async fn sample(target: &Target) -> Sample {
+    let (gpu, memory, rss) = futures::join!(gpu(), memory(), rss(target));
+
+    Sample { gpu, memory, rss }
+}
+
+// Apple Silicon has no dedicated GPU memory
+async fn gpu() -> Option<Gpu> {
+    if let Some(output) = run(
+        "nvidia-smi",
+        &[
+            "--query-gpu=utilization.gpu,memory.used,memory.total",
+            "--format=csv,noheader,nounits",
+        ],
+    )
+    .await
+    {
+        let line = output.lines().next()?;
+        let mut values = line
+            .split(',')
+            .map(|value| value.trim().parse::<u64>().ok());
+
+        let (Some(utilization), Some(used), Some(total)) =
+            (values.next()?, values.next()?, values.next()?)
+        else {
+            return None;
+        };
+
+        return Some(Gpu {
+            utilization: utilization as u32,
+            used: used * 1024 * 1024,
+            total: total * 1024 * 1024,
+        });
+    }
+
+    let output = run(
+        "rocm-smi",
+        &["--showuse", "--showmeminfo", "vram", "--json"],
+    )
+    .await?;
+    let cards: BTreeMap<String, BTreeMap<String, serde_json::Value>> =
+        serde_json::from_str(&output).ok()?;
+
+    let card = cards.values().next()?;
+
+    let field = |name: &str| -> Option<u64> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Number {
+            Integer(u64),
+            Text(String),
+        }
+
+        match Number::deserialize(card.get(name)?).ok()? {
+            Number::Integer(value) => Some(value),
+            Number::Text(value) => value.trim().parse().ok(),
+        }
+    };
+
+    Some(Gpu {
+        utilization: field("GPU use (%)")? as u32,
+        used: field("VRAM Total Used Memory (B)")?,
+        total: field("VRAM Total Memory (B)")?,
+    })
+}
+
+pub async fn power() -> Option<f64> {
+    let output = run(
+        "nvidia-smi",
+        &["--query-gpu=power.draw", "--format=csv,noheader,nounits"],
+    )
+    .await?;
+
+    // Every GPU is assumed to be serving the model
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.trim().parse::<f64>().ok())
+        .sum()
+}
+
+#[cfg(target_os = "linux")]
+async fn memory() -> Option<Memory> {
+    let meminfo = tokio::fs::read_to_string("/proc/meminfo").await.ok()?;
+
+    let field = |name: &str| {
+        meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .and_then(|value| {
+                value
+                    .trim()
+                    .trim_end_matches("kB")
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+            })
+            .map(|kilobytes| kilobytes * 1024)
+    };
+
+    Some(Memory {
+        available: field("MemAvailable")?,
+        total: field("MemTotal")?,
+    })
+}
+
+#[cfg(target_os = "macos")]
+async fn memory() -> Option<Memory> {
+    let total = run("sysctl", &["-n", "hw.memsize"])
+        .await?
+        .trim()
+        .parse()
+        .ok()?;
+    let vm_stat = run("vm_stat", &[]).await?;
+
+    let page_size: u64 = vm_stat
+        .lines()
+        .next()?
+        .split("page size of ")
+        .nth(1)?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+
+    let pages = |name: &str| {
+        vm_stat
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .and_then(|value| value.trim().trim_end_matches('.').parse::<u64>().ok())
+            .unwrap_or_default()
+    };
+
+    let available =
+        (pages("Pages free") + pages("Pages inactive") + pages("Pages speculative")) * page_size;
+
+    Some(Memory { available, total })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+async fn memory() -> Option<Memory> {
+    None
+}
+
+async fn rss(target: &Target) -> Option<u64> {
+    match target {
+        Target::Process(pid) => {
+            let output = run("ps", &["-o", "rss=", "-p", &pid.to_string()]).await?;
+            let kilobytes: u64 = output.trim().parse().ok()?;
+
+            Some(kilobytes * 1024)
+        }
+        Target::Container(id) => {
+            let output = run(
+                "docker",
+                &["stats", "--no-stream", "--format", "{{.MemUsage}}", id],
+            )
+            .await?;
+
+            // e.g. "1.5GiB / 31.2GiB"
+            parse_size(output.split('/').next()?)
+        }
+    }
+}
+
+fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let split = size.find(|c: char| c.is_ascii_alphabetic())?;
+    let (number, unit) = size.split_at(split);
+    let number: f64 = number.trim().parse().ok()?;
+
+    let multiplier = match unit {
+        "B" => 1.0,
+        "KiB" | "kB" => 1024.0,
+        "MiB" | "MB" => 1024.0 * 1024.0,
+        "GiB" | "GB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+
+    Some((number * multiplier) as u64)
+}
+
+async fn run(program: &str, arguments: &[&str]) -> Option<String> {
+    let output = process::Command::new(program)
+        .args(arguments)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn fraction(used: u64, total: u64) -> f32 {
+    if total == 0 {
+        return 0.0;
+    }
+
+    used as f32 / total as f32
+}
@@ -0,0 +1,152 @@
+use crate::provider::Provider;
+use crate::Error;
+
+use decoder::{decode, encode, Value};
+use thiserror::capture;
+use tokio::time;
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::RwLock;
+use std::time::Duration;
+
+static CONFIGURATION: RwLock<Timeouts> = RwLock::new(Timeouts(BTreeMap::new()));
+
+// In seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeout {
+    pub connect: u64,
+    pub first_token: u64,
+    pub inter_token: u64,
+}
+
+impl Timeout {
+    pub fn recommended(provider: Provider) -> Self {
+        match provider {
+            // Prompt processing on the CPU can take a long while
+            Provider::Local | Provider::Ssh => Self {
+                connect: 10,
+                first_token: 300,
+                inter_token: 60,
+            },
+            Provider::NanoGPT
+            | Provider::OpenAI
+            | Provider::OpenAICompatible
+            | Provider::AzureOpenAI
+            | Provider::Groq
+            | Provider::Together
+            | Provider::Mistral => Self {
+                connect: 15,
+                first_token: 120,
+                inter_token: 30,
+            },
+        }
+    }
+
+    // NanoGPT and OpenAI models are not reached through a client that can be
+    // given one
+    pub fn has_connect(provider: Provider) -> bool {
+        !matches!(provider, Provider::NanoGPT | Provider::OpenAI)
+    }
+
+    pub fn connect(self) -> Duration {
+        Duration::from_secs(self.connect.max(1))
+    }
+
+    pub async fn next<T>(self, is_first: bool, token: impl Future<Output = T>) -> Result<T, Error> {
+        let (seconds, reason) = if is_first {
+            (
+                self.first_token,
+                "no response before the first-token deadline",
+            )
+        } else {
+            (self.inter_token, "the model stopped streaming tokens")
+        };
+
+        time::timeout(Duration::from_secs(seconds.max(1)), token)
+            .await
+            .map_err(|_elapsed| Error::TimedOut(reason, capture!()))
+    }
+
+    fn decode(value: Value, default: Self) -> decoder::Result<Self> {
+        let mut timeout = decode::map(value)?;
+
+        Ok(Self {
+            connect: timeout
+                .optional("connect", decode::u64)?
+                .unwrap_or(default.connect),
+            first_token: timeout
+                .optional("first_token", decode::u64)?
+                .unwrap_or(default.first_token),
+            inter_token: timeout
+                .optional("inter_token", decode::u64)?
+                .unwrap_or(default.inter_token),
+        })
+    }
+
+    fn encode(self) -> Value {
+        encode::map([
+            ("connect", encode::u64(self.connect)),
+            ("first_token", encode::u64(self.first_token)),
+            ("inter_token", encode::u64(self.inter_token)),
+        ])
+        .into_value()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Timeouts(BTreeMap<Provider, Timeout>);
+
+impl Timeouts {
+    pub fn get(&self, provider: Provider) -> Timeout {
+        self.0
+            .get(&provider)
+            .copied()
+            .unwrap_or_else(|| Timeout::recommended(provider))
+    }
+
+    pub fn set(&mut self, provider: Provider, timeout: Timeout) {
+        if timeout == Timeout::recommended(provider) {
+            let _ = self.0.remove(&provider);
+        } else {
+            let _ = self.0.insert(provider, timeout);
+        }
+    }
+
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut timeouts = decode::map(value)?;
+        let mut overrides = BTreeMap::new();
+
+        for &provider in Provider::ALL {
+            if let Some(timeout) = timeouts.optional(provider.key(), |value| {
+                Timeout::decode(value, Timeout::recommended(provider))
+            })? {
+                let _ = overrides.insert(provider, timeout);
+            }
+        }
+
+        Ok(Self(overrides))
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        encode::map(
+            self.0
+                .iter()
+                .map(|(provider, timeout)| (provider.key(), timeout.encode())),
+        )
+        .into_value()
+    }
+}
+
+pub fn configure(timeouts: Timeouts) {
+    if let Ok(mut configuration) = CONFIGURATION.write() {
+        *configuration = timeouts;
+    }
+}
+
+pub(crate) fn current(provider: Provider) -> Timeout {
+    CONFIGURATION
+        .read()
+        .map(|timeouts| timeouts.get(provider))
+        .unwrap_or_else(|_| Timeout::recommended(provider))
+}
@@ -0,0 +1,90 @@
+use crate::Error;
+
+use serde::{Deserialize, Serialize};
+use thiserror::capture;
+use tokio::fs;
+
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Todo {
+    pub task: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
+    #[serde(default)]
+    pub is_done: bool,
+}
+
+impl Todo {
+    pub fn from_json(json: &str) -> Result<Vec<Self>, Error> {
+        #[derive(Deserialize)]
+        struct Raw {
+            items: Vec<Item>,
+        }
+
+        #[derive(Deserialize)]
+        struct Item {
+            task: String,
+            #[serde(default)]
+            owner: Option<String>,
+            #[serde(default)]
+            due: Option<String>,
+        }
+
+        let start = json.find('{');
+        let end = json.rfind('}');
+
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err(Error::InvalidResponse("the model", capture!()));
+        };
+
+        let raw: Raw = serde_json::from_str(&json[start..=end])?;
+
+        fn non_empty(value: Option<String>) -> Option<String> {
+            value
+                .map(|value| value.trim().to_owned())
+                .filter(|value| !value.is_empty())
+        }
+
+        Ok(raw
+            .items
+            .into_iter()
+            .filter(|item| !item.task.trim().is_empty())
+            .map(|item| Self {
+                task: item.task.trim().to_owned(),
+                owner: non_empty(item.owner),
+                due: non_empty(item.due),
+                is_done: false,
+            })
+            .collect())
+    }
+
+    pub fn to_markdown(todos: &[Self]) -> String {
+        todos
+            .iter()
+            .map(|todo| {
+                let mut line =
+                    format!("- [{}] {}", if todo.is_done { "x" } else { " " }, todo.task);
+
+                let details: Vec<&str> = [todo.owner.as_deref(), todo.due.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+
+                if !details.is_empty() {
+                    line.push_str(&format!(" ({})", details.join(", ")));
+                }
+
+                line + "\n"
+            })
+            .collect()
+    }
+
+    pub async fn save(todos: Vec<Self>, path: PathBuf) -> Result<(), Error> {
+        fs::write(path, Self::to_markdown(&todos)).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use crate::Error;
+
+use decoder::{decode, encode, Value};
+use sipper::{sipper, Straw};
+use thiserror::capture;
+use tokio::fs;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process;
+use uuid::Uuid;
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Stdio;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Whisper {
+    pub program: String,
+    pub model: String,
+    pub ffmpeg: String,
+}
+
+impl Default for Whisper {
+    fn default() -> Self {
+        Self {
+            program: "whisper-cli".to_owned(),
+            model: String::new(),
+            ffmpeg: "ffmpeg".to_owned(),
+        }
+    }
+}
+
+impl Whisper {
+    pub fn is_configured(&self) -> bool {
+        !self.program.trim().is_empty() && !self.model.trim().is_empty()
+    }
+
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut whisper = decode::map(value)?;
+        let default = Self::default();
+
+        Ok(Self {
+            program: whisper
+                .optional("program", decode::string)?
+                .unwrap_or(default.program),
+            model: whisper
+                .optional("model", decode::string)?
+                .unwrap_or_default(),
+            ffmpeg: whisper
+                .optional("ffmpeg", decode::string)?
+                .unwrap_or(default.ffmpeg),
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        encode::map([
+            ("program", encode::string(&self.program)),
+            ("model", encode::string(&self.model)),
+            ("ffmpeg", encode::string(&self.ffmpeg)),
+        ])
+        .into_value()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub stage: &'static str,
+    pub percent: u32,
+}
+
+pub fn transcribe(whisper: Whisper, file: PathBuf) -> impl Straw<String, Progress, Error> {
+    sipper(move |mut progress| async move {
+        if !whisper.is_configured() {
+            return Err(Error::NotConfigured("whisper.cpp", capture!()));
+        }
+
+        progress
+            .send(Progress {
+                stage: "Converting audio...",
+                percent: 0,
+            })
+            .await;
+
+        let wav = env::temp_dir().join(format!("icebreaker-{}.wav", Uuid::new_v4().simple()));
+
+        let converted = process::Command::new(whisper.ffmpeg.trim())
+            .arg("-y")
+            .arg("-i")
+            .arg(&file)
+            .args(["-vn", "-ar", "16000", "-ac", "1", "-c:a", "pcm_s16le"])
+            .arg(&wav)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .status()
+            .await?;
+
+        if !converted.success() {
+            let _ = fs::remove_file(&wav).await;
+
+            return Err(Error::TranscriptionFailed(
+                "ffmpeg could not read the file".to_owned(),
+                capture!(),
+            ));
+        }
+
+        let result = async {
+            let mut child = process::Command::new(whisper.program.trim())
+                .arg("--model")
+                .arg(whisper.model.trim())
+                .arg("--file")
+                .arg(&wav)
+                .args(["--no-timestamps", "--print-progress"])
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn()?;
+
+            let mut stdout = child.stdout.take().expect("piped stdout");
+            let stderr = child.stderr.take().expect("piped stderr");
+
+            let reading = tokio::spawn(async move {
+                let mut transcript = String::new();
+                let _ = stdout.read_to_string(&mut transcript).await?;
+
+                Ok::<_, Error>(transcript)
+            });
+
+            let mut lines = BufReader::new(stderr).lines();
+            let mut last_line = String::new();
+
+            // e.g. "whisper_print_progress_callback: progress =  45%"
+            while let Some(line) = lines.next_line().await? {
+                let percent = line
+                    .split_once("progress =")
+                    .and_then(|(_, percent)| percent.trim().trim_end_matches('%').parse().ok());
+
+                match percent {
+                    Some(percent) => {
+                        progress
+                            .send(Progress {
+                                stage: "Transcribing...",
+                                percent,
+                            })
+                            .await;
+                    }
+                    None if !line.trim().is_empty() => {
+                        last_line = line;
+                    }
+                    None => {}
+                }
+            }
+
+            let status = child.wait().await?;
+            let transcript = reading.await??;
+
+            if !status.success() {
+                return Err(Error::TranscriptionFailed(last_line, capture!()));
+            }
+
+            Ok(transcript
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n"))
+        }
+        .await;
+
+        let _ = fs::remove_file(&wav).await;
+
+        result
+    })
+}
@@ -0,0 +1,139 @@
+use crate::assistant::Assistant;
+use crate::directory;
+use crate::Error;
+
+use langchain_rust::schemas::Message;
+use serde::{Deserialize, Serialize};
+use sipper::{sipper, Sipper, Straw};
+use tokio::fs;
+
+use std::io;
+use std::path::PathBuf;
+
+pub const LANGUAGES: &[&str] = &[
+    "Arabic",
+    "Chinese",
+    "Dutch",
+    "English",
+    "French",
+    "German",
+    "Hindi",
+    "Italian",
+    "Japanese",
+    "Korean",
+    "Polish",
+    "Portuguese",
+    "Russian",
+    "Spanish",
+    "Swedish",
+    "Turkish",
+    "Ukrainian",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Translation {
+    pub source: Option<String>,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Glossary {
+    pub terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Term {
+    pub source: String,
+    pub target: String,
+}
+
+impl Glossary {
+    pub async fn fetch() -> Result<Self, Error> {
+        match fs::read(path()).await {
+            Ok(json) => Ok(serde_json::from_slice(&json)?),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub async fn save(self) -> Result<Self, Error> {
+        let path = path();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        fs::write(path, serde_json::to_vec_pretty(&self)?).await?;
+
+        Ok(self)
+    }
+}
+
+pub fn translate(
+    assistant: &Assistant,
+    target: String,
+    glossary: Glossary,
+    text: String,
+) -> impl Straw<Translation, Translation, Error> {
+    let assistant = assistant.clone();
+
+    sipper(move |sender| async move {
+        let mut system_prompt = format!(
+            "You are a professional translator. Translate the text of the user into {target}. \
+            Start your reply with the name in English of the language of the text \
+            between square brackets, like [French], followed by a new line and the \
+            translation alone. Keep the formatting, and do not explain anything."
+        );
+
+        let terms: Vec<_> = glossary
+            .terms
+            .iter()
+            .filter(|term| !term.source.trim().is_empty() && text.contains(term.source.trim()))
+            .collect();
+
+        if !terms.is_empty() {
+            system_prompt.push_str("\n\nAlways translate these terms as follows:");
+
+            for term in terms {
+                system_prompt.push_str(&format!(
+                    "\n- {} → {}",
+                    term.source.trim(),
+                    term.target.trim()
+                ));
+            }
+        }
+
+        let messages = [Message::new_human_message(text)];
+
+        let reply = assistant
+            .reply(&system_prompt, &messages, &[])
+            .with(|(reply, _token)| parse(&reply.content))
+            .run(sender)
+            .await?;
+
+        Ok(parse(&reply.content))
+    })
+}
+
+fn parse(reply: &str) -> Translation {
+    let reply = reply.trim_start();
+
+    let Some((language, rest)) = reply
+        .strip_prefix('[')
+        .and_then(|reply| reply.split_once(']'))
+    else {
+        return Translation {
+            source: None,
+            content: reply.to_owned(),
+        };
+    };
+
+    Translation {
+        source: Some(language.trim().to_owned()).filter(|language| !language.is_empty()),
+        content: rest.trim().to_owned(),
+    }
+}
+
+fn path() -> PathBuf {
+    directory::config().join("glossary.json")
+}
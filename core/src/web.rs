@@ -2,9 +2,89 @@ use crate::assistant::SimpleMessage;
 use crate::{Assistant, Error, Url};
 use langchain_rust::schemas::Message as LMessage;
 
+use decoder::{decode, encode, Value};
+use scraper::{ElementRef, Selector};
 use serde::{Deserialize, Serialize};
 use sipper::{sipper, Sipper, Straw};
-use std::sync::LazyLock;
+use url::Host;
+
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, LazyLock, RwLock};
+
+static DOMAINS: RwLock<Domains> = RwLock::new(Domains {
+    allow: Vec::new(),
+    deny: Vec::new(),
+});
+
+pub(crate) const FETCH: &str = "fetch_url";
+
+const MAX_FETCH: usize = 32_000;
+
+// In bytes
+const MAX_BODY: usize = 2_000_000;
+
+const MAX_REDIRECTS: usize = 10;
+
+// Denied domains win over allowed ones, and subdomains are included
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Domains {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl Domains {
+    pub fn permits(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+
+        if url.host().is_none_or(|host| is_internal(&host)) {
+            return false;
+        }
+
+        let matches = |domain: &String| {
+            let domain = domain.trim().trim_start_matches("*.").to_lowercase();
+
+            !domain.is_empty() && (host == domain || host.ends_with(&format!(".{domain}")))
+        };
+
+        !self.deny.iter().any(matches) && (self.allow.is_empty() || self.allow.iter().any(matches))
+    }
+
+    pub(crate) fn decode(value: Value) -> decoder::Result<Self> {
+        let mut domains = decode::map(value)?;
+
+        Ok(Self {
+            allow: domains
+                .optional("allow", decode::sequence(decode::string))?
+                .unwrap_or_default(),
+            deny: domains
+                .optional("deny", decode::sequence(decode::string))?
+                .unwrap_or_default(),
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Value {
+        encode::map([
+            (
+                "allow",
+                encode::sequence(self.allow.iter().map(encode::string)),
+            ),
+            (
+                "deny",
+                encode::sequence(self.deny.iter().map(encode::string)),
+            ),
+        ])
+        .into_value()
+    }
+}
+
+pub fn configure(domains: Domains) {
+    if let Ok(mut configuration) = DOMAINS.write() {
+        *configuration = domains;
+    }
+}
 
 pub struct Search {
     pub results: Vec<Url>,
@@ -90,6 +170,240 @@ pub fn summarize<'a>(
     })
 }
 
+pub(crate) fn fetch_description() -> String {
+    format!(
+        "\n- {FETCH}: Download a web page and read its content as markdown. \
+        The input is the URL of the page. Use it when the user gives a link."
+    )
+}
+
+pub(crate) async fn fetch_tool(inputs: &[String]) -> Summary {
+    let url = inputs
+        .first()
+        .and_then(|input| Url::parse(input.trim()).ok())
+        .filter(|url| matches!(url.scheme(), "http" | "https"));
+
+    let Some(url) = url else {
+        return Summary {
+            url: Url::parse("about:blank").expect("Valid URL"),
+            content: "A valid http or https URL is needed.".to_owned(),
+        };
+    };
+
+    let is_permitted = DOMAINS
+        .read()
+        .map(|domains| domains.permits(&url))
+        .unwrap_or_default();
+
+    let content = if is_permitted {
+        fetch(url.clone()).await.unwrap_or_else(|error| {
+            log::warn!("Fetching {url} failed: {error}");
+
+            format!("The page could not be fetched: {error}")
+        })
+    } else {
+        log::info!("Fetching denied: {url}");
+
+        format!(
+            "Access denied: {} is not allowed by the user.",
+            url.host_str().unwrap_or_default()
+        )
+    };
+
+    Summary { url, content }
+}
+
+pub async fn fetch(url: Url) -> Result<String, Error> {
+    log::info!("Fetching page: {url}");
+
+    let mut response = FETCH_CLIENT
+        .get(url.clone())
+        .send()
+        .await?
+        .error_for_status()?;
+    let mut body = Vec::new();
+
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+
+        if body.len() >= MAX_BODY {
+            body.truncate(MAX_BODY);
+            break;
+        }
+    }
+
+    let html = String::from_utf8_lossy(&body);
+    let mut markdown = to_markdown(&html, &url);
+
+    if markdown.len() > MAX_FETCH {
+        let mut end = MAX_FETCH;
+
+        while !markdown.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        markdown.truncate(end);
+        markdown.push_str("\n\n[page truncated]");
+    }
+
+    Ok(markdown)
+}
+
+fn to_markdown(html: &str, base: &Url) -> String {
+    let html = scraper::Html::parse_document(html);
+
+    let main = ["article", "main", "[role=main]", "body"]
+        .into_iter()
+        .filter_map(|selector| Selector::parse(selector).ok())
+        .find_map(|selector| html.select(&selector).next());
+
+    let mut markdown = String::new();
+
+    if let Some(main) = main {
+        write(main, base, &mut markdown);
+    }
+
+    let mut output = String::with_capacity(markdown.len());
+    let mut is_blank = true;
+
+    for line in markdown.lines().map(str::trim_end) {
+        if line.trim().is_empty() {
+            if !is_blank {
+                output.push('\n');
+            }
+
+            is_blank = true;
+        } else {
+            output.push_str(line);
+            output.push('\n');
+
+            is_blank = false;
+        }
+    }
+
+    output.trim().to_owned()
+}
+
+fn write(element: ElementRef<'_>, base: &Url, output: &mut String) {
+    fn block(output: &mut String) {
+        if !output.is_empty() && !output.ends_with("\n\n") {
+            output.push_str(if output.ends_with('\n') { "\n" } else { "\n\n" });
+        }
+    }
+
+    fn inline(element: ElementRef<'_>) -> String {
+        element
+            .text()
+            .flat_map(str::split_whitespace)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    for child in element.children() {
+        if let Some(text) = child.value().as_text() {
+            let words: Vec<_> = text.split_whitespace().collect();
+
+            if words.is_empty() {
+                if !text.is_empty() && !output.ends_with([' ', '\n']) {
+                    output.push(' ');
+                }
+
+                continue;
+            }
+
+            if text.starts_with(char::is_whitespace) && !output.ends_with([' ', '\n']) {
+                output.push(' ');
+            }
+
+            output.push_str(&words.join(" "));
+
+            if text.ends_with(char::is_whitespace) {
+                output.push(' ');
+            }
+
+            continue;
+        }
+
+        let Some(child) = ElementRef::wrap(child) else {
+            continue;
+        };
+
+        match child.value().name() {
+            "script" | "style" | "noscript" | "template" | "nav" | "header" | "footer"
+            | "aside" | "form" | "button" | "iframe" | "svg" | "img" => {}
+            heading @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+                let level = heading[1..].parse().unwrap_or(1);
+
+                block(output);
+                output.push_str(&"#".repeat(level));
+                output.push(' ');
+                output.push_str(&inline(child));
+                block(output);
+            }
+            "pre" => {
+                block(output);
+                output.push_str("```\n");
+                output.push_str(child.text().collect::<String>().trim_end());
+                output.push_str("\n```");
+                block(output);
+            }
+            "code" => {
+                output.push('`');
+                output.push_str(&inline(child));
+                output.push('`');
+            }
+            "a" => {
+                let text = inline(child);
+                let href = child
+                    .value()
+                    .attr("href")
+                    .and_then(|href| base.join(href).ok())
+                    .filter(|href| matches!(href.scheme(), "http" | "https"));
+
+                match href {
+                    Some(href) if !text.is_empty() => {
+                        output.push_str(&format!("[{text}]({href})"));
+                    }
+                    _ => output.push_str(&text),
+                }
+            }
+            "strong" | "b" => {
+                output.push_str(&format!("**{}**", inline(child)));
+            }
+            "em" | "i" => {
+                output.push_str(&format!("*{}*", inline(child)));
+            }
+            "br" => output.push('\n'),
+            "li" => {
+                if !output.is_empty() && !output.ends_with('\n') {
+                    output.push('\n');
+                }
+
+                output.push_str("- ");
+                write(child, base, output);
+                output.push('\n');
+            }
+            "blockquote" => {
+                block(output);
+                output.push_str("> ");
+                output.push_str(&inline(child));
+                block(output);
+            }
+            "td" | "th" => {
+                write(child, base, output);
+                output.push_str(" | ");
+            }
+            "p" | "div" | "section" | "article" | "main" | "ul" | "ol" | "table" | "tr"
+            | "figure" | "dl" | "dd" | "dt" => {
+                block(output);
+                write(child, base, output);
+                block(output);
+            }
+            _ => write(child, base, output),
+        }
+    }
+}
+
 async fn scrape(url: Url) -> Result<String, Error> {
     log::info!("Scraping text: {url}");
 
@@ -120,8 +434,113 @@ async fn scrape(url: Url) -> Result<String, Error> {
     Ok(lines.join("\n"))
 }
 
+fn is_internal(host: &Host<&str>) -> bool {
+    match host {
+        Host::Domain(domain) => {
+            let domain = domain.trim_end_matches('.').to_lowercase();
+
+            domain == "localhost"
+                || [".localhost", ".local", ".internal"]
+                    .iter()
+                    .any(|suffix| domain.ends_with(suffix))
+        }
+        Host::Ipv4(ip) => is_internal_ip(IpAddr::V4(*ip)),
+        Host::Ipv6(ip) => is_internal_ip(IpAddr::V6(*ip)),
+    }
+}
+
+fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                // Carrier-grade NAT
+                || (ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64)
+        }
+        IpAddr::V6(ip) => {
+            ip.to_ipv4_mapped()
+                .is_some_and(|ip| is_internal_ip(IpAddr::V4(ip)))
+                || ip.is_loopback()
+                || ip.is_unspecified()
+                // Unique local and link-local
+                || ip.segments()[0] & 0xfe00 == 0xfc00
+                || ip.segments()[0] & 0xffc0 == 0xfe80
+        }
+    }
+}
+
+// No domain may point the assistant to the machine or its network
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|address| !is_internal_ip(address.ip()))
+                .collect();
+
+            if addresses.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("{} has no public address", name.as_str()),
+                )
+                .into());
+            }
+
+            let addresses: reqwest::dns::Addrs = Box::new(addresses.into_iter());
+
+            Ok(addresses)
+        })
+    }
+}
+
 static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
-    let headers = reqwest::header::HeaderMap::from_iter([
+    reqwest::Client::builder()
+        .use_rustls_tls()
+        .default_headers(headers())
+        .build()
+        .expect("Build reqwest client")
+});
+
+static FETCH_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    let redirects = reqwest::redirect::Policy::custom(|attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            return attempt.error("too many redirects");
+        }
+
+        let is_permitted = matches!(attempt.url().scheme(), "http" | "https")
+            && DOMAINS
+                .read()
+                .map(|domains| domains.permits(attempt.url()))
+                .unwrap_or_default();
+
+        if is_permitted {
+            attempt.follow()
+        } else {
+            let error = format!(
+                "the redirect to {} is not allowed by the user",
+                attempt.url().host_str().unwrap_or_default()
+            );
+
+            attempt.error(error)
+        }
+    });
+
+    reqwest::Client::builder()
+        .use_rustls_tls()
+        .default_headers(headers())
+        .redirect(redirects)
+        .dns_resolver(Arc::new(PublicResolver))
+        .build()
+        .expect("Build reqwest client")
+});
+
+fn headers() -> reqwest::header::HeaderMap {
+    reqwest::header::HeaderMap::from_iter([
         (
             reqwest::header::USER_AGENT,
             reqwest::header::HeaderValue::from_static(
@@ -134,11 +553,5 @@ static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
             reqwest::header::ACCEPT,
             reqwest::header::HeaderValue::from_static("*/*"),
         ),
-    ]);
-
-    reqwest::Client::builder()
-        .use_rustls_tls()
-        .default_headers(headers)
-        .build()
-        .expect("Build reqwest client")
-});
+    ])
+}
@@ -0,0 +1,103 @@
+use crate::directory;
+use crate::Error;
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Workspace {
+    pub name: String,
+    pub layout: Layout,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Layout {
+    Pane(Pane),
+    Split {
+        axis: Axis,
+        ratio: f32,
+        a: Box<Layout>,
+        b: Box<Layout>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Pane {
+    Screen,
+    Models,
+    Log,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+impl Layout {
+    pub fn is_valid(&self) -> bool {
+        fn screens(layout: &Layout) -> usize {
+            match layout {
+                Layout::Pane(Pane::Screen) => 1,
+                Layout::Pane(_) => 0,
+                Layout::Split { a, b, .. } => screens(a) + screens(b),
+            }
+        }
+
+        screens(self) == 1
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::Pane(Pane::Screen)
+    }
+}
+
+impl Workspace {
+    pub fn list() -> Vec<Self> {
+        std::fs::read(path())
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub async fn save(self) -> Result<Vec<Self>, Error> {
+        let mut workspaces = Self::list();
+
+        match workspaces
+            .iter_mut()
+            .find(|workspace| workspace.name == self.name)
+        {
+            Some(workspace) => *workspace = self,
+            None => workspaces.push(self),
+        }
+
+        store(workspaces).await
+    }
+
+    pub async fn delete(name: String) -> Result<Vec<Self>, Error> {
+        let mut workspaces = Self::list();
+        workspaces.retain(|workspace| workspace.name != name);
+
+        store(workspaces).await
+    }
+}
+
+async fn store(workspaces: Vec<Workspace>) -> Result<Vec<Workspace>, Error> {
+    let path = path();
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    fs::write(path, serde_json::to_vec_pretty(&workspaces)?).await?;
+
+    Ok(workspaces)
+}
+
+fn path() -> PathBuf {
+    directory::config().join("workspaces.json")
+}
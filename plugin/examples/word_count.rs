@@ -0,0 +1,17 @@
+use icebreaker_plugin::{serve, Request, Response};
+
+fn main() -> std::io::Result<()> {
+    serve(|request| match request {
+        Request::Command { name, argument } if name == "words" => {
+            Response::Reply(format!("{} words", argument.split_whitespace().count()))
+        }
+        Request::Tool { name, inputs } if name == "count_words" => Response::Text(
+            inputs
+                .iter()
+                .map(|input| format!("{input}: {} words", input.split_whitespace().count()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        _ => Response::Error("unknown request".to_owned()),
+    })
+}
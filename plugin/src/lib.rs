@@ -0,0 +1,104 @@
+//! The extension API of icebreaker.
+//!
+//! A plugin is a directory with a `plugin.toml` manifest and a program. The
+//! manifest declares what the plugin adds—tools, slash commands, and
+//! providers—and how to run it:
+//!
+//! ```toml
+//! name = "word-count"
+//! version = "0.1.0"
+//! description = "Counts the words of a text"
+//! api_version = 1
+//! command = ["./word-count"]
+//!
+//! [[commands]]
+//! name = "words"
+//! description = "Counts the words of the given text"
+//! ```
+//!
+//! icebreaker runs the program once per call, writes a [`Request`] to its
+//! standard input as a single line of JSON, and reads a [`Response`] from its
+//! standard output the same way. [`serve`] takes care of both ends.
+//!
+//! Plugins built for a different [`API_VERSION`] are never run.
+use serde::{Deserialize, Serialize};
+
+use std::io::{self, BufRead, Write};
+
+// Only changes when an existing plugin would stop working
+pub const API_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    pub api_version: u32,
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub tools: Vec<Tool>,
+    #[serde(default)]
+    pub commands: Vec<Command>,
+    #[serde(default)]
+    pub providers: Vec<Provider>,
+}
+
+impl Manifest {
+    pub fn is_compatible(&self) -> bool {
+        self.api_version == API_VERSION
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tool {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Command {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provider {
+    pub name: String,
+    pub base_url: String,
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Request {
+    Tool { name: String, inputs: Vec<String> },
+    Command { name: String, argument: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "content", rename_all = "snake_case")]
+pub enum Response {
+    Text(String),
+    Prompt(String),
+    Reply(String),
+    Error(String),
+}
+
+pub fn serve(handler: impl FnOnce(Request) -> Response) -> io::Result<()> {
+    let mut line = String::new();
+    let _ = io::stdin().lock().read_line(&mut line)?;
+
+    let response = match serde_json::from_str(&line) {
+        Ok(request) => handler(request),
+        Err(error) => Response::Error(format!("invalid request: {error}")),
+    };
+
+    let mut stdout = io::stdout().lock();
+
+    serde_json::to_writer(&mut stdout, &response)?;
+    writeln!(stdout)?;
+    stdout.flush()
+}
@@ -0,0 +1,129 @@
+use crate::core::job::{self, Job, Kind};
+use crate::icon;
+
+use iced::time::Instant;
+use iced::widget::{button, column, container, horizontal_space, progress_bar, row, text, Text};
+use iced::{Center, Element, Font};
+
+#[derive(Debug, Default)]
+pub struct Activity {
+    jobs: Vec<Job>,
+    is_open: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Changed(job::Event),
+    Toggle,
+    Close,
+    Cancel(job::Id),
+}
+
+impl Activity {
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Changed(_event) => {
+                // The list is read again, so missed events are caught up with
+                self.jobs = job::list();
+
+                if self.jobs.is_empty() {
+                    self.is_open = false;
+                }
+            }
+            Message::Toggle => {
+                self.is_open = !self.is_open && !self.jobs.is_empty();
+            }
+            Message::Close => {
+                self.is_open = false;
+            }
+            Message::Cancel(id) => {
+                let _ = job::cancel(id);
+
+                self.jobs.retain(|job| job.id != id);
+            }
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn view(&self) -> Option<Element<'_, Message>> {
+        if !self.is_open || self.jobs.is_empty() {
+            return None;
+        }
+
+        let now = Instant::now();
+
+        let jobs = self.jobs.iter().map(|job| {
+            let elapsed = now.duration_since(job.started_at).as_secs();
+
+            let status = match (job.progress.stage, job.progress.percent) {
+                (Some(stage), Some(percent)) => format!("{stage}... {percent}%"),
+                (Some(stage), None) => format!("{stage}..."),
+                (None, Some(percent)) => format!("{percent}%"),
+                (None, None) => "Running...".to_owned(),
+            };
+
+            column![
+                row![
+                    symbol(job.kind).size(12),
+                    text(&job.title).size(12).font(Font::MONOSPACE),
+                    horizontal_space(),
+                    button(icon::cancel().size(12))
+                        .padding(0)
+                        .on_press(Message::Cancel(job.id))
+                        .style(button::text),
+                ]
+                .spacing(5)
+                .align_y(Center),
+                progress_bar(0.0..=100.0, job.progress.percent.unwrap_or_default() as f32).girth(4),
+                text!(
+                    "{kind} · {status} · {minutes}:{seconds:02}",
+                    kind = job.kind,
+                    minutes = elapsed / 60,
+                    seconds = elapsed % 60,
+                )
+                .size(10)
+                .style(text::secondary),
+            ]
+            .spacing(5)
+            .into()
+        });
+
+        Some(
+            container(
+                column![
+                    row![
+                        text("Activity").size(14),
+                        horizontal_space(),
+                        button(icon::cancel().size(12))
+                            .padding(0)
+                            .on_press(Message::Close)
+                            .style(button::text),
+                    ]
+                    .align_y(Center),
+                    column(jobs).spacing(10),
+                ]
+                .spacing(10)
+                .width(350),
+            )
+            .padding(10)
+            .style(container::bordered_box)
+            .into(),
+        )
+    }
+}
+
+fn symbol<'a>(kind: Kind) -> Text<'a> {
+    match kind {
+        Kind::Scan => icon::folder(),
+        Kind::Download => icon::download(),
+        Kind::StatusCheck => icon::refresh(),
+        Kind::Generation => icon::chat(),
+    }
+}
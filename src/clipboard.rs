@@ -0,0 +1,144 @@
+use crate::core::attachment::Attachment;
+use crate::core::settings::ClipboardAction;
+use crate::core::Error;
+use crate::icon;
+
+use iced::clipboard;
+use iced::time::{self, Duration};
+use iced::widget::{button, column, container, horizontal_space, row, text};
+use iced::{Center, Element, Subscription, Task};
+
+const MAX_PREVIEW: usize = 120;
+
+#[derive(Debug, Default)]
+pub struct Watcher {
+    last: Option<String>,
+    primed: bool,
+    offer: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Poll,
+    Read(Option<String>),
+    Run(usize),
+    Dismiss,
+}
+
+pub enum Action {
+    None,
+    Run(Task<Message>),
+    Ask(String),
+}
+
+impl Watcher {
+    pub fn update(&mut self, message: Message, actions: &[ClipboardAction]) -> Action {
+        match message {
+            Message::Poll => Action::Run(clipboard::read().map(Message::Read)),
+            Message::Read(content) => {
+                let content = content
+                    .map(|content| content.trim().to_owned())
+                    .filter(|content| !content.is_empty());
+
+                if content != self.last {
+                    // Skip whatever was already copied when watching started
+                    if self.primed {
+                        self.offer.clone_from(&content);
+                    }
+
+                    self.last = content;
+                }
+
+                self.primed = true;
+
+                Action::None
+            }
+            Message::Run(index) => {
+                let (Some(text), Some(action)) = (self.offer.take(), actions.get(index)) else {
+                    return Action::None;
+                };
+
+                Action::Ask(action.prompt(&text))
+            }
+            Message::Dismiss => {
+                self.offer = None;
+
+                Action::None
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn view<'a>(
+        &'a self,
+        actions: &'a [ClipboardAction],
+        can_ask: bool,
+    ) -> Option<Element<'a, Message>> {
+        let offer = self.offer.as_ref()?;
+
+        let preview = if offer.chars().count() > MAX_PREVIEW {
+            format!("{}…", offer.chars().take(MAX_PREVIEW).collect::<String>())
+        } else {
+            offer.clone()
+        };
+
+        let header = row![
+            icon::clipboard().size(12).style(text::secondary),
+            text("Copied text").size(12).style(text::secondary),
+            horizontal_space(),
+            button(icon::cancel().size(12))
+                .padding(0)
+                .on_press(Message::Dismiss)
+                .style(button::text),
+        ]
+        .spacing(5)
+        .align_y(Center);
+
+        let actions = row(actions.iter().enumerate().map(|(index, action)| {
+            button(text(&action.name).size(12))
+                .on_press_maybe(can_ask.then_some(Message::Run(index)))
+                .style(button::secondary)
+                .into()
+        }))
+        .spacing(5);
+
+        Some(
+            container(
+                column![header, text(preview).size(14), actions]
+                    .spacing(10)
+                    .max_width(350),
+            )
+            .padding(10)
+            .style(container::bordered_box)
+            .into(),
+        )
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        time::every(Duration::from_secs(1)).map(|_| Message::Poll)
+    }
+}
+
+pub async fn read_image() -> Result<Option<Attachment>, Error> {
+    let image = tokio::task::spawn_blocking(|| {
+        let mut clipboard = arboard::Clipboard::new().ok()?;
+
+        clipboard.get_image().ok()
+    })
+    .await?;
+
+    let Some(image) = image else {
+        return Ok(None);
+    };
+
+    Attachment::image(
+        image.width as u32,
+        image.height as u32,
+        image.bytes.into_owned(),
+    )
+    .await
+    .map(Some)
+}
@@ -0,0 +1,117 @@
+use crate::core::conversion::{self, Converter, Progress, Quantization, Source};
+use crate::core::model::{Directory, File, Id};
+use crate::core::Error;
+use crate::icon;
+
+use iced::task::{self, Task};
+use iced::widget::{button, column, container, horizontal_space, progress_bar, row, text};
+use iced::{Center, Element, Font};
+
+pub struct Conversion {
+    model: Id,
+    quantization: Quantization,
+    progress: Option<Progress>,
+    _task: task::Handle,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Progressed(Progress),
+    Finished(Result<File, Error>),
+    Cancel,
+}
+
+pub enum Action {
+    None,
+    Cancel,
+    Finished(Result<File, Error>),
+}
+
+impl Conversion {
+    pub fn start(
+        source: Source,
+        quantization: Quantization,
+        converter: Converter,
+        directory: Directory,
+    ) -> (Self, Task<Message>) {
+        let model = source.model.clone();
+
+        let (task, handle) = Task::sip(
+            conversion::convert(source, quantization, converter, directory),
+            Message::Progressed,
+            Message::Finished,
+        )
+        .abortable();
+
+        (
+            Self {
+                model,
+                quantization,
+                progress: None,
+                _task: handle.abort_on_drop(),
+            },
+            task,
+        )
+    }
+
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::Progressed(progress) => {
+                self.progress = Some(progress);
+
+                Action::None
+            }
+            Message::Finished(result) => Action::Finished(result),
+            Message::Cancel => Action::Cancel,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let (percent, status) = match self.progress {
+            None => (0.0, "Starting...".to_owned()),
+            Some(Progress::Downloading {
+                file,
+                total,
+                progress,
+            }) => {
+                let percent = progress
+                    .and_then(|progress| progress.percent())
+                    .map_or(0, |(_total, percent)| percent);
+
+                (
+                    (file as f32 + percent as f32 / 100.0) / total as f32 * 100.0,
+                    format!("Downloading weights ({}/{total}) {percent}%", file + 1),
+                )
+            }
+            Some(Progress::Converting) => (100.0, "Converting to GGUF...".to_owned()),
+            Some(Progress::Quantizing) => {
+                (100.0, format!("Quantizing to {}...", self.quantization))
+            }
+        };
+
+        container(
+            column![
+                row![
+                    icon::cubes().size(12),
+                    text!("{} ({})", self.model.name(), self.quantization)
+                        .size(12)
+                        .font(Font::MONOSPACE),
+                    horizontal_space(),
+                    button(icon::cancel().size(12))
+                        .padding(0)
+                        .on_press(Message::Cancel)
+                        .style(button::text),
+                ]
+                .spacing(5)
+                .align_y(Center),
+                progress_bar(0.0..=100.0, percent).girth(4),
+                text(status).size(10).style(text::secondary),
+            ]
+            .spacing(5)
+            .width(350),
+        )
+        .padding(10)
+        .style(container::bordered_box)
+        .into()
+    }
+}
@@ -0,0 +1,36 @@
+use iced::task::{self, Task};
+use iced::time::Duration;
+
+#[derive(Debug)]
+pub struct Debounce {
+    delay: Duration,
+    timer: Option<task::Handle>,
+}
+
+impl Debounce {
+    pub fn new(delay: Duration) -> Self {
+        Self { delay, timer: None }
+    }
+
+    pub fn run<T>(&mut self, message: T) -> Task<T>
+    where
+        T: Send + 'static,
+    {
+        let delay = self.delay;
+
+        let (task, handle) = Task::future(async move {
+            tokio::time::sleep(delay).await;
+
+            message
+        })
+        .abortable();
+
+        self.timer = Some(handle.abort_on_drop());
+
+        task
+    }
+
+    pub fn cancel(&mut self) {
+        self.timer = None;
+    }
+}
@@ -0,0 +1,247 @@
+use crate::core::job;
+use crate::core::model::{File, Library};
+use crate::core::request;
+use crate::core::Error;
+use crate::icon;
+
+use iced::task::{self, sipper, Task};
+use iced::widget::{button, column, container, horizontal_space, progress_bar, row, text};
+use iced::{Center, Element, Font};
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+#[derive(Default)]
+pub struct Queue {
+    active: Option<Active>,
+    pending: VecDeque<(File, Then)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Then {
+    Notify,
+    Boot,
+}
+
+impl Then {
+    fn toggle(self) -> Self {
+        match self {
+            Self::Notify => Self::Boot,
+            Self::Boot => Self::Notify,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Notify => "Then notify",
+            Self::Boot => "Then boot",
+        }
+    }
+}
+
+struct Active {
+    file: File,
+    then: Then,
+    progress: Option<request::Progress>,
+    _task: task::Handle,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Progressed(request::Progress),
+    Finished(Result<PathBuf, Error>),
+    Cancel,
+    Remove(usize),
+    Then(File, Then),
+}
+
+pub enum Action {
+    None,
+    Run(Task<Message>),
+    Finished(File, Then, Result<PathBuf, Error>, Task<Message>),
+}
+
+impl Queue {
+    pub fn enqueue(
+        &mut self,
+        files: impl IntoIterator<Item = File>,
+        then: Then,
+        library: &Library,
+    ) -> Task<Message> {
+        for file in files {
+            if library.files.contains_key(&file.endpoint()) || self.contains(&file) {
+                continue;
+            }
+
+            self.pending.push_back((file, then));
+        }
+
+        self.next(library)
+    }
+
+    pub fn update(&mut self, message: Message, library: &Library) -> Action {
+        match message {
+            Message::Progressed(progress) => {
+                if let Some(active) = &mut self.active {
+                    active.progress = Some(progress);
+                }
+
+                Action::None
+            }
+            Message::Finished(result) => {
+                let Some(active) = self.active.take() else {
+                    return Action::None;
+                };
+
+                Action::Finished(active.file, active.then, result, self.next(library))
+            }
+            Message::Cancel => {
+                self.active = None;
+
+                Action::Run(self.next(library))
+            }
+            Message::Remove(index) => {
+                let _ = self.pending.remove(index);
+
+                Action::None
+            }
+            Message::Then(file, then) => {
+                match &mut self.active {
+                    Some(active) if active.file == file => {
+                        active.then = then;
+                    }
+                    _ => {
+                        if let Some((_, pending)) = self
+                            .pending
+                            .iter_mut()
+                            .find(|(pending, _)| *pending == file)
+                        {
+                            *pending = then;
+                        }
+                    }
+                }
+
+                Action::None
+            }
+        }
+    }
+
+    pub fn contains(&self, file: &File) -> bool {
+        self.active
+            .as_ref()
+            .is_some_and(|active| &active.file == file)
+            || self.pending.iter().any(|(pending, _)| pending == file)
+    }
+
+    fn next(&mut self, library: &Library) -> Task<Message> {
+        if self.active.is_some() {
+            return Task::none();
+        }
+
+        let Some((file, then)) = self.pending.pop_front() else {
+            return Task::none();
+        };
+
+        let directory = library.directory_of(&file).clone();
+
+        let (download, handle) = Task::sip(
+            {
+                let file = file.clone();
+
+                job::run(
+                    job::Kind::Download,
+                    file.name.clone(),
+                    sipper(move |sender| async move { file.download(&directory, sender).await }),
+                )
+            },
+            Message::Progressed,
+            Message::Finished,
+        )
+        .abortable();
+
+        self.active = Some(Active {
+            file,
+            then,
+            progress: None,
+            _task: handle.abort_on_drop(),
+        });
+
+        download
+    }
+
+    pub fn view(&self) -> Option<Element<'_, Message>> {
+        let active = self.active.as_ref()?;
+
+        let (percent, status) = match active.progress.and_then(request::Progress::percent) {
+            Some((_total, percent)) => (
+                percent,
+                format!(
+                    "{percent}% @ {speed:.2} MB/s",
+                    speed =
+                        active.progress.map_or(0, |progress| progress.speed) as f32 / 10f32.powi(6),
+                ),
+            ),
+            None => (0, "Starting...".to_owned()),
+        };
+
+        let current = column![
+            row![
+                icon::download().size(12),
+                text(&active.file.name).size(12).font(Font::MONOSPACE),
+                horizontal_space(),
+                then(&active.file, active.then, 12.0),
+                button(icon::cancel().size(12))
+                    .padding(0)
+                    .on_press(Message::Cancel)
+                    .style(button::text),
+            ]
+            .spacing(5)
+            .align_y(Center),
+            progress_bar(0.0..=100.0, percent as f32).girth(4),
+            text(status).size(10).style(text::secondary),
+        ]
+        .spacing(5);
+
+        let pending = self
+            .pending
+            .iter()
+            .enumerate()
+            .map(|(index, (file, then))| {
+                row![
+                    icon::clock().size(10).style(text::secondary),
+                    text(&file.name)
+                        .size(10)
+                        .font(Font::MONOSPACE)
+                        .style(text::secondary),
+                    horizontal_space(),
+                    self::then(file, *then, 10.0),
+                    button(icon::cancel().size(10))
+                        .padding(0)
+                        .on_press(Message::Remove(index))
+                        .style(button::text),
+                ]
+                .spacing(5)
+                .align_y(Center)
+                .into()
+            });
+
+        Some(
+            container(
+                column![current, column(pending).spacing(5)]
+                    .spacing(10)
+                    .width(350),
+            )
+            .padding(10)
+            .style(container::bordered_box)
+            .into(),
+        )
+    }
+}
+
+fn then<'a>(file: &File, then: Then, size: f32) -> Element<'a, Message> {
+    button(text(then.label()).size(size))
+        .padding(0)
+        .on_press(Message::Then(file.clone(), then.toggle()))
+        .style(button::text)
+        .into()
+}
@@ -1,109 +1,434 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 use icebreaker_core as core;
 use icebreaker_core::model::APIAccess;
-use icebreaker_core::model::Library;
 use langchain_rust::document_loaders::dotenvy;
 use langchain_rust::llm::nanogpt::NanoGPT;
 use langchain_rust::llm::OpenAIConfig;
 use log::info;
 use log::warn;
 
+mod activity;
 mod browser;
+mod clipboard;
+mod conversion;
+mod debounce;
+mod download;
 mod icon;
+mod pane;
 mod screen;
 mod theme;
+mod toast;
 mod ui;
 mod widget;
 
+use crate::core::approval;
 use crate::core::assistant;
+use crate::core::audit;
+use crate::core::chat;
+use crate::core::codebase;
+use crate::core::desktop;
+use crate::core::export;
+use crate::core::filesystem;
+use crate::core::hook;
+use crate::core::instance;
+use crate::core::job;
+use crate::core::link::{self, Link};
+use crate::core::mirror;
 use crate::core::model;
+use crate::core::persona::Persona;
+use crate::core::plugin::Plugin;
+use crate::core::profile::{self, Profiles};
+use crate::core::reminder::{self, Reminder};
+use crate::core::request;
+use crate::core::session::{self, Session};
+use crate::core::setup;
+use crate::core::sync;
+use crate::core::timeout;
+use crate::core::web;
+use crate::core::workspace::Workspace;
 use crate::core::{Chat, Error, Settings};
+use crate::pane::Pane;
+use crate::screen::arena;
+use crate::screen::batch;
 use crate::screen::conversation;
+use crate::screen::git;
+use crate::screen::personas;
+use crate::screen::plugins;
+use crate::screen::profiles;
 use crate::screen::search;
 use crate::screen::search::status_check;
 use crate::screen::settings;
+use crate::screen::translate;
 use crate::screen::Screen;
+use crate::widget::splitter::{self, Splitter};
 
+use iced::border;
+use iced::padding;
 use iced::system;
-use iced::widget::{button, column, container, row, rule, vertical_rule, vertical_space, Text};
-use iced::{Element, Fill, Subscription, Task, Theme};
+use iced::time::{self, Duration};
+use iced::widget::{
+    bottom_right, button, center, column, container, pane_grid, row, scrollable, stack, text, Text,
+};
+use iced::window;
+use iced::{Bottom, Center, Element, Fill, Font, Point, Size, Subscription, Task, Theme};
+use iced_palace::widget::ellipsized_text;
 
 use std::borrow::Cow;
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 use std::mem;
+use std::path::PathBuf;
 use std::sync::Arc;
 
+const NARROW_WIDTH: u32 = 800;
+
+const WINDOW_MARGIN: f32 = 50.0;
+
 pub fn main() -> iced::Result {
     tracing_subscriber::fmt::init();
     let path = dotenvy::dotenv().unwrap();
     warn!("using {:?}", path);
 
+    // Later launches hand their arguments (e.g. deep links) to the running app
+    let Some(_lock) = instance::lock() else {
+        if !instance::activate(&instance::Activation::current()) {
+            warn!("icebreaker is already running, but it did not answer");
+        }
+
+        return Ok(());
+    };
+
+    let window = {
+        profile::activate(&Profiles::fetch().current);
+
+        Settings::fetch().unwrap_or_default().window
+    };
+
     iced::application(Icebreaker::new, Icebreaker::update, Icebreaker::view)
         .title(Icebreaker::title)
         .subscription(Icebreaker::subscription)
         .theme(Icebreaker::theme)
         .font(icon::FONT)
+        .window(window::Settings {
+            size: Size::new(window.width as f32, window.height as f32),
+            position: window.position.map_or(window::Position::Default, |(x, y)| {
+                window::Position::Specific(Point::new(x as f32, y as f32))
+            }),
+            exit_on_close_request: false,
+            ..window::Settings::default()
+        })
         .run()
 }
 
 struct Icebreaker {
     screen: Screen,
     last_conversation: Option<screen::Conversation>,
+    preloading: Option<screen::Conversation>,
+    pending_chat: Option<Result<Chat, Error>>,
     system: Option<system::Information>,
+    store: model::Store,
     library: Arc<model::Library>,
     theme: Theme,
     settings: Settings,
+    clipboard: clipboard::Watcher,
+    toasts: toast::Toasts,
+    activity: activity::Activity,
+    downloads: download::Queue,
+    conversion: Option<conversion::Conversion>,
+    pending_persona: Option<Persona>,
+    pending_link: Option<Link>,
+    pending_draft: Option<String>,
+    scanning: Option<model::ScanProgress>,
+    zen: BTreeSet<session::Screen>,
+    is_sidebar_pinned: bool,
+    splitter: Splitter,
+    panes: pane_grid::State<Pane>,
 }
 
 #[derive(Debug, Clone)]
 enum Message {
-    Loaded {
-        last_chat: Result<Chat, Error>,
-        system: Box<system::Information>,
-    },
-    Scanned(Result<Arc<model::Library>, Error>),
+    LastChatFetched(Result<Chat, Error>),
+    SystemFetched(Box<system::Information>),
+    Scanning(model::ScanProgress),
+    Scanned(Result<(), Error>),
+    LibraryChanged(model::Change),
     Escape,
     Search(search::Message),
     Conversation(conversation::Message),
     Settings(settings::Message),
+    Batch(batch::Message),
+    Arena(arena::Message),
+    Personas(personas::Message),
+    Git(git::Message),
+    Translate(translate::Message),
+    Plugins(plugins::Message),
+    Profiles(profiles::Message),
+    Clipboard(clipboard::Message),
+    Toast(toast::Message),
+    Activity(activity::Message),
+    Download(download::Message),
+    Conversion(conversion::Message),
+    OpenPendingLink,
+    CheckReminders,
+    RemindersDue(Result<Vec<Reminder>, Error>),
+    ModelImported(Result<model::File, Error>),
+    ChatFileOpened(PathBuf, Result<Chat, Error>),
+    Activated(instance::Activation),
     OpenChats,
     OpenSearch,
     OpenSettings,
-    SettingsSaved(Result<Arc<Library>, Error>),
+    OpenBatch,
+    OpenArena,
+    OpenPersonas,
+    OpenGit,
+    OpenTranslate,
+    OpenPlugins,
     SettingsSavedNull(Result<(), Error>),
     Ignore(Result<(), Error>),
-    StatusUpdated(Result<(), Error>),
+    Synced(Result<sync::Summary, Error>),
+    Window(window::Id, window::Event),
+    MonitorChecked(window::Id, Option<Size>),
+    ToggleZen,
+    PaneDragged(pane_grid::DragEvent),
+    PaneResized(pane_grid::ResizeEvent),
+    ClosePane(pane_grid::Pane),
+    Splitter(splitter::Drag),
+    CloseRequested(window::Id),
+    Closing(window::Id, Option<Size>),
 }
 
 impl Icebreaker {
     pub fn new() -> (Self, Task<Message>) {
+        let profiles = Profiles::fetch();
+        profile::activate(&profiles.current);
+
         let settings = Settings::fetch().unwrap_or_default();
-        let library = Arc::new(model::Library::default());
-
-        (
-            Self {
-                screen: Screen::Loading,
-                library: library.clone(),
-                last_conversation: None,
-                system: None,
-                settings: settings.clone(),
-                theme: theme::from_data(&settings.theme),
-            },
-            Task::batch([
-                Task::future(Chat::fetch_last_opened()).then(|last_chat| {
-                    system::fetch_information()
-                        .map(Box::new)
-                        .map(move |system| Message::Loaded {
-                            last_chat: last_chat.clone(),
-                            system,
-                        })
-                }),
-                Task::perform(
-                    Library::scan(library.clone(), settings.clone()),
-                    Message::Scanned,
+
+        let mut icebreaker = Self {
+            screen: Screen::Loading,
+            store: model::Store::default(),
+            library: Arc::default(),
+            last_conversation: None,
+            preloading: None,
+            pending_chat: None,
+            system: None,
+            theme: theme::from_data(&settings.theme),
+            settings,
+            clipboard: clipboard::Watcher::default(),
+            toasts: toast::Toasts::default(),
+            activity: activity::Activity::default(),
+            downloads: download::Queue::default(),
+            conversion: None,
+            pending_persona: None,
+            pending_link: Link::find(&std::env::args().skip(1).collect::<Vec<_>>()),
+            pending_draft: None,
+            scanning: None,
+            zen: BTreeSet::new(),
+            is_sidebar_pinned: false,
+            splitter: Splitter::new(settings.window.sidebar_width),
+            panes: pane_grid::State::new(Pane::Screen).0,
+        };
+
+        let requested = std::env::args()
+            .skip_while(|argument| argument != "--profile")
+            .nth(1)
+            .filter(|name| profiles.names.contains(name));
+
+        let task = match requested {
+            Some(name) => icebreaker.start(name),
+            None if profiles.ask_at_startup && profiles.names.len() > 1 => {
+                icebreaker.open_profiles()
+            }
+            None => icebreaker.start(profiles.current),
+        };
+
+        let check_monitor = window::get_oldest().and_then(|window| {
+            window::monitor_size(window)
+                .map(move |monitor| Message::MonitorChecked(window, monitor))
+        });
+
+        (icebreaker, Task::batch([task, check_monitor]))
+    }
+
+    fn start(&mut self, name: String) -> Task<Message> {
+        profile::activate(&name);
+
+        let settings = Settings::fetch().unwrap_or_default();
+        audit::configure(settings.audit.clone());
+        timeout::configure(settings.timeouts.clone());
+        request::configure(settings.throttle);
+        mirror::configure(settings.mirror.clone());
+        hook::configure(settings.hooks.clone());
+        codebase::configure(settings.embeddings, &settings.keys);
+        reminder::configure(settings.reminders);
+        chat::configure(settings.chat_format);
+        filesystem::configure(settings.file_roots.clone());
+        web::configure(settings.fetch.clone());
+        approval::configure(settings.tool_policies.clone());
+
+        let store = model::Store::default();
+
+        self.screen = Screen::Loading;
+        self.store = store.clone();
+        self.library = store.snapshot();
+        self.last_conversation = None;
+        self.preloading = None;
+        self.pending_chat = None;
+        self.pending_persona = None;
+        let session = Session::fetch();
+        self.zen = session.zen;
+        self.panes = pane::from_layout(&session.layout);
+        self.theme = theme::from_data(&settings.theme);
+        // The window stays where it is
+        self.settings = Settings {
+            window: self.settings.window,
+            ..settings.clone()
+        };
+
+        let profiles = Profiles {
+            current: name,
+            ..Profiles::fetch()
+        };
+
+        // The system is already known when switching profiles
+        let preload = self.preload();
+
+        Task::batch([
+            Task::perform(Chat::fetch_last_opened(), Message::LastChatFetched),
+            system::fetch_information()
+                .map(Box::new)
+                .map(Message::SystemFetched),
+            preload,
+            Task::sip(
+                job::run(
+                    job::Kind::Scan,
+                    "Model library",
+                    store.scan(settings.clone()),
                 ),
-            ]),
-        )
+                Message::Scanning,
+                Message::Scanned,
+            ),
+            settings.sync.clone().map_or_else(Task::none, |folder| {
+                Task::perform(sync::run(folder), Message::Synced)
+            }),
+            Task::perform(profiles.save(), |result| {
+                Message::Ignore(result.map(|_profiles| ()))
+            }),
+            Task::perform(link::register(), Message::Ignore),
+        ])
+    }
+
+    fn load(&mut self, last_chat: Result<Chat, Error>) -> Task<Message> {
+        let session = Session::fetch();
+
+        // The size of the preloaded file is not known until the library is scanned
+        let is_preloaded = |conversation: &screen::Conversation, chat: &Chat| {
+            conversation
+                .file()
+                .file
+                .as_ref()
+                .zip(chat.file.file.as_ref())
+                .is_some_and(|(preloaded, file)| {
+                    preloaded.model == file.model && preloaded.name == file.name
+                })
+        };
+
+        let conversation = match (self.preloading.take(), last_chat) {
+            // The last chat is opened in the conversation already booting its model
+            (Some(mut conversation), Ok(last_chat)) if is_preloaded(&conversation, &last_chat) => {
+                let open = match conversation.update(
+                    &self.library,
+                    &self.settings,
+                    conversation::Message::ChatFetched(Ok(Chat {
+                        file: conversation.file().clone(),
+                        ..last_chat
+                    })),
+                ) {
+                    conversation::Action::None => Task::none(),
+                    conversation::Action::Run(task) => task,
+                };
+
+                let scroll = conversation.scroll_to(session.scroll);
+
+                Some((conversation, Task::batch([open, scroll])))
+            }
+            // Otherwise, a new chat is started with the preloaded model
+            (Some(conversation), _) => Some((conversation, Task::none())),
+            (None, Ok(last_chat)) => {
+                let (mut conversation, task) =
+                    screen::Conversation::open(&self.library, last_chat, self.backend());
+                let scroll = conversation.scroll_to(session.scroll);
+
+                Some((conversation, Task::batch([task, scroll])))
+            }
+            (None, Err(error)) => {
+                log::warn!("{error}");
+
+                None
+            }
+        };
+
+        let Some((conversation, task)) = conversation else {
+            let task = self.open_search();
+
+            if let Screen::Search(search) = &mut self.screen {
+                search.restore(session.search);
+            }
+
+            return task;
+        };
+
+        let task = task.map(Message::Conversation);
+
+        if session.screen == session::Screen::Conversation {
+            self.screen = Screen::Conversation(conversation);
+
+            return task;
+        }
+
+        self.last_conversation = Some(conversation);
+
+        let open = match session.screen {
+            session::Screen::Conversation | session::Screen::Search => self.open_search(),
+            session::Screen::Settings => self.open_settings(),
+            session::Screen::Batch => self.open_batch(),
+            session::Screen::Arena => self.open_arena(),
+            session::Screen::Personas => self.open_personas(),
+            session::Screen::Git => self.open_git(),
+            session::Screen::Translate => self.open_translate(),
+            session::Screen::Plugins => self.open_plugins(),
+        };
+
+        if let Screen::Search(search) = &mut self.screen {
+            search.restore(session.search);
+        }
+
+        Task::batch([task, open])
+    }
+
+    fn preload(&mut self) -> Task<Message> {
+        let Some(file) = self.settings.preload.clone() else {
+            return Task::none();
+        };
+
+        if !matches!(self.screen, Screen::Loading)
+            || self.system.is_none()
+            || self.preloading.is_some()
+        {
+            return Task::none();
+        }
+
+        let (conversation, task) = screen::Conversation::new(
+            &self.library,
+            model::FileAndAPI {
+                file: Some(file),
+                api: None,
+            },
+            self.backend(),
+        );
+
+        self.preloading = Some(conversation);
+
+        task.map(Message::Conversation)
     }
 
     fn title(&self) -> String {
@@ -112,6 +437,13 @@ impl Icebreaker {
             Screen::Search(search) => search.title(),
             Screen::Conversation(conversation) => conversation.title(),
             Screen::Settings(settings) => settings.title(),
+            Screen::Batch(batch) => batch.title(),
+            Screen::Arena(arena) => arena.title(),
+            Screen::Personas(personas) => personas.title(),
+            Screen::Git(git) => git.title(),
+            Screen::Translate(translate) => translate.title(),
+            Screen::Plugins(plugins) => plugins.title(),
+            Screen::Profiles(profiles) => profiles.title(),
         };
 
         format!("{title} - Icebreaker")
@@ -119,206 +451,988 @@ impl Icebreaker {
 
     fn update(&mut self, message: Message) -> Task<Message> {
         match message {
-            Message::Loaded { last_chat, system } => {
-                let backend = assistant::Backend::detect(&system.graphics_adapter);
+            Message::LastChatFetched(last_chat) => {
+                // Chats wait for the graphics of the system to boot their model
+                if self.system.is_none() && (last_chat.is_ok() || self.settings.preload.is_some()) {
+                    self.pending_chat = Some(last_chat);
+
+                    return Task::none();
+                }
+
+                Task::batch([self.load(last_chat), Task::done(Message::OpenPendingLink)])
+            }
+            Message::SystemFetched(system) => {
                 self.system = Some(*system);
-                match last_chat {
-                    Ok(last_chat) => {
-                        let (conversation, task) =
-                            screen::Conversation::open(&self.library, last_chat, backend);
 
-                        self.screen = Screen::Conversation(conversation);
+                let preload = self.preload();
 
-                        task.map(Message::Conversation)
-                    }
-                    Err(error) => {
-                        log::warn!("{error}");
+                let Some(last_chat) = self.pending_chat.take() else {
+                    return preload;
+                };
 
-                        self.open_search()
-                    }
-                }
+                Task::batch([
+                    preload,
+                    self.load(last_chat),
+                    Task::done(Message::OpenPendingLink),
+                ])
+            }
+            Message::Scanning(progress) => {
+                self.scanning = Some(progress);
+
+                Task::none()
             }
-            Message::Scanned(Ok(library)) => {
-                let old_library = std::mem::replace(&mut self.library, library);
+            Message::Scanned(Ok(())) => {
+                self.scanning = None;
+
+                let task = self.refresh_library();
                 info!("scanned {}", self.library.files.len());
 
-                if old_library.directory() != self.library.directory() {
-                    self.save_settings()
+                task
+            }
+            Message::LibraryChanged(change) => {
+                let task = self.refresh_library();
+
+                if let Screen::Search(search) = &mut self.screen {
+                    Task::batch([
+                        task,
+                        search
+                            .changed(self.library.clone(), change)
+                            .map(Message::Search),
+                    ])
                 } else {
-                    Task::none()
+                    task
                 }
             }
             Message::Search(message) => {
                 if let Screen::Search(search) = &mut self.screen {
-                    let action = search.update(
-                        message,
-                        Arc::<_>::make_mut(&mut self.library),
-                        &mut self.settings,
-                    );
+                    let action = search.update(message, &self.library, &mut self.settings);
 
                     match action {
                         search::Action::None => Task::none(),
                         search::Action::Run(task) => task.map(Message::Search),
-                        search::Action::Boot(file) => {
-                            let backend = self
-                                .system
-                                .as_ref()
-                                .map(|system| assistant::Backend::detect(&system.graphics_adapter))
-                                .unwrap_or(assistant::Backend::Cpu);
+                        search::Action::Error(error) => {
+                            self.notify(error);
 
-                            let (conversation, task) =
-                                screen::Conversation::new(&self.library, file, backend);
+                            Task::none()
+                        }
+                        search::Action::Boot(file) => self.boot(file),
+                        search::Action::Download(files, then) => self
+                            .downloads
+                            .enqueue(files, then, &self.library)
+                            .map(Message::Download),
+                        search::Action::Convert(source, quantization) => {
+                            if self.conversion.is_some() {
+                                self.toasts
+                                    .info("Wait for the running conversion to finish first.");
 
-                            self.screen = Screen::Conversation(conversation);
-                            self.last_conversation = None;
+                                return Task::none();
+                            }
+
+                            let (conversion, task) = conversion::Conversion::start(
+                                source,
+                                quantization,
+                                self.settings.converter.clone(),
+                                self.library.directory().clone(),
+                            );
 
-                            task.map(Message::Conversation)
+                            self.conversion = Some(conversion);
+
+                            task.map(Message::Conversion)
                         }
                         search::Action::Bookmark(id, add) => {
-                            let lib = Arc::<_>::make_mut(&mut self.library);
-                            if add {
-                                if !lib.bookmarks.contains(&id) {
-                                    lib.bookmarks.push(id.clone());
+                            self.update_library(model::Change::Bookmarks, |lib| {
+                                if add {
+                                    if !lib.bookmarks.contains(&id) {
+                                        lib.bookmarks.push(id.clone());
+                                    }
+                                } else {
+                                    lib.bookmarks.retain(|bookmark_id| bookmark_id != &id);
                                 }
-                            } else {
-                                lib.bookmarks.retain(|bookmark_id| bookmark_id != &id);
-                            }
+                            });
 
-                            Task::perform(
-                                self.library
-                                    .to_owned()
-                                    .save_bookmarks(self.settings.clone()),
-                                Message::SettingsSaved,
-                            )
+                            self.save_bookmarks()
                         }
-                        search::Action::Wrap(mesg) => match mesg {
-                            search::Message::CheckStatus { bookmarks, first_n } => {
-                                let mut tasks = Vec::new();
-                                let mut scheduled_ids = HashSet::new();
-                                if bookmarks {
-                                    for id in self.library.bookmarks.iter() {
-                                        let _ = scheduled_ids.insert(id.clone());
-                                    }
-                                }
-                                for (id, _) in search.models.iter().take(first_n) {
-                                    let _ = scheduled_ids.insert(id.clone());
+                        search::Action::MoveBookmark { from, to } => {
+                            self.update_library(model::Change::Bookmarks, |lib| {
+                                if from < lib.bookmarks.len() && to < lib.bookmarks.len() {
+                                    let bookmark = lib.bookmarks.remove(from);
+                                    lib.bookmarks.insert(to, bookmark);
                                 }
+                            });
+
+                            self.save_bookmarks()
+                        }
+                        search::Action::Delete(id) => {
+                            let delete = self.update_library(model::Change::Files, |lib| {
+                                lib.bookmarks.retain(|bookmark_id| bookmark_id != &id);
 
-                                for id in scheduled_ids {
-                                    let m: Option<&model::Model> = search.models.get(&id);
+                                // Files in the shared library are read-only
+                                match lib.files.get(&id) {
+                                    Some(model::FileOrAPI::File(file)) if !lib.is_shared(&id) => {
+                                        let file = file.clone();
+                                        let directory = lib.directory().clone();
+                                        let _ = Arc::make_mut(&mut lib.files).remove(&id);
 
-                                    if let Some(m) = m {
-                                        tasks.push(Task::perform(
-                                            m.clone().update_status(),
-                                            Message::StatusUpdated,
-                                        ));
+                                        Task::perform(file.delete(directory), Message::Ignore)
                                     }
+                                    _ => Task::none(),
+                                }
+                            });
+
+                            Task::batch([delete, self.save_bookmarks()])
+                        }
+                        search::Action::Failover(primary, fallbacks) => {
+                            self.update_library(model::Change::Failover, |lib| {
+                                lib.set_fallbacks(primary, fallbacks);
+                            });
+
+                            self.save_bookmarks()
+                        }
+                    }
+                } else {
+                    Task::none()
+                }
+            }
+            Message::Conversation(message) => {
+                let conversation = if let Screen::Conversation(conversation) = &mut self.screen {
+                    Some(conversation)
+                } else {
+                    self.last_conversation.as_mut().or(self.preloading.as_mut())
+                };
+
+                let Some(conversation) = conversation else {
+                    return Task::none();
+                };
+
+                let action = conversation.update(&self.library, &self.settings, message);
+
+                match action {
+                    conversation::Action::None => Task::none(),
+                    conversation::Action::Run(task) => task.map(Message::Conversation),
+                }
+            }
+            Message::Settings(message) => {
+                let Screen::Settings(screen_settings) = &mut self.screen else {
+                    return Task::none();
+                };
+
+                match screen_settings.update(message) {
+                    settings::Action::None => Task::none(),
+                    settings::Action::Error(error) => {
+                        self.notify(error);
+
+                        Task::none()
+                    }
+                    settings::Action::ChangeTheme(theme) => {
+                        self.theme = theme;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeClipboardWatch(watch) => {
+                        self.settings.clipboard_watch = watch;
+                        self.clipboard.reset();
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeSandbox(sandbox) => {
+                        self.settings.sandbox = sandbox;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeAudit(audit) => {
+                        self.settings.audit = audit.clone();
+                        audit::configure(audit);
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeAzure(azure) => {
+                        self.update_library(model::Change::Providers, |lib| {
+                            lib.configure_azure(&azure);
+                        });
+                        self.settings.azure = azure;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeDiffusion(diffusion) => {
+                        self.settings.diffusion = diffusion;
+
+                        self.save_settings()
+                    }
+                    settings::Action::Redownload(file) => {
+                        // The broken file is gone already, even if the library was not scanned again
+                        self.update_library(model::Change::Files, |lib| {
+                            let _ = Arc::make_mut(&mut lib.files).remove(&file.endpoint());
+                        });
+
+                        self.downloads
+                            .enqueue([file], download::Then::Notify, &self.library)
+                            .map(Message::Download)
+                    }
+                    settings::Action::ChangeConverter(converter) => {
+                        self.settings.converter = converter;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeWhisper(whisper) => {
+                        self.settings.whisper = whisper;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeOcr(ocr) => {
+                        self.settings.ocr = ocr;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeHooks(hooks) => {
+                        hook::configure(hooks.clone());
+                        self.settings.hooks = hooks;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeKeys(keys) => {
+                        self.update_library(model::Change::Providers, |lib| {
+                            lib.configure_hosted(&keys);
+                        });
+                        codebase::configure(self.settings.embeddings, &keys);
+                        self.settings.keys = keys;
+
+                        self.save_settings()
+                    }
+                    settings::Action::RegisterServer(access) => {
+                        self.update_library(model::Change::Providers, |lib| {
+                            let _ = lib.api_src.insert(model::APIType::OpenAICompatible, access);
+                        });
+
+                        self.save_bookmarks()
+                    }
+                    settings::Action::ExportSetup(path) => {
+                        Task::perform(setup::export(self.library.clone(), path), |result| {
+                            Message::Settings(settings::Message::SetupExported(result))
+                        })
+                    }
+                    settings::Action::InstallSetup(manifest) => {
+                        let has_server = self
+                            .library
+                            .api_src
+                            .contains_key(&model::APIType::OpenAICompatible);
+
+                        // Servers on the local network need no secrets
+                        let server = manifest
+                            .providers
+                            .iter()
+                            .find(|provider| {
+                                provider.kind == model::APIType::OpenAICompatible
+                                    && provider.base_url.is_some()
+                            })
+                            .filter(|_| !has_server)
+                            .map(|provider| model::APIAccess {
+                                openai_compat: None,
+                                azure: None,
+                                ssh: None,
+                                api_key: None,
+                                base_url: provider.base_url.clone(),
+                                kind: model::APIType::OpenAICompatible,
+                            });
+
+                        let register = match server {
+                            Some(access) => {
+                                self.update_library(model::Change::Providers, |lib| {
+                                    let _ = lib
+                                        .api_src
+                                        .insert(model::APIType::OpenAICompatible, access);
+                                });
+
+                                self.save_bookmarks()
+                            }
+                            None => Task::none(),
+                        };
+
+                        let install = Task::sip(
+                            setup::install(manifest.models, self.library.directory().clone()),
+                            settings::Message::SetupInstalling,
+                            settings::Message::SetupInstalled,
+                        )
+                        .map(Message::Settings)
+                        .chain(self.scan());
+
+                        Task::batch([register, install])
+                    }
+                    settings::Action::Restored => {
+                        Task::batch([self.reload_settings(), self.open_settings()])
+                    }
+                    settings::Action::OpenProfiles => self.open_profiles(),
+                    settings::Action::OpenPane(kind) => {
+                        pane::open(&mut self.panes, kind);
+
+                        Task::none()
+                    }
+                    settings::Action::SaveWorkspace(name) => {
+                        let workspace = Workspace {
+                            name,
+                            layout: pane::to_layout(&self.panes),
+                        };
+
+                        Task::perform(workspace.save(), |result| {
+                            Message::Settings(settings::Message::WorkspacesSaved(result))
+                        })
+                    }
+                    settings::Action::ApplyWorkspace(layout) => {
+                        self.panes = pane::from_layout(&layout);
+
+                        Task::none()
+                    }
+                    settings::Action::ChangeSyncFolder(folder) => {
+                        self.settings.sync = folder;
+
+                        self.save_settings()
+                    }
+                    settings::Action::SettingsSynced(summary) => {
+                        // Reopen the section with the outcome of the sync
+                        let summary = sync::Summary {
+                            settings: false,
+                            ..summary
+                        };
+
+                        Task::batch([
+                            self.reload_settings(),
+                            self.open_settings(),
+                            Task::done(Message::Settings(settings::Message::Open(
+                                settings::Section::Sync,
+                            ))),
+                            Task::done(Message::Settings(settings::Message::Synced(Ok(summary)))),
+                        ])
+                    }
+                    settings::Action::ChangeSsh(ssh) => {
+                        self.update_library(model::Change::Providers, |lib| {
+                            lib.configure_ssh(&ssh);
+                        });
+                        self.settings.ssh = ssh;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeEmbeddings(embeddings) => {
+                        codebase::configure(embeddings, &self.settings.keys);
+                        self.settings.embeddings = embeddings;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeTimeouts(timeouts) => {
+                        self.settings.timeouts = timeouts.clone();
+                        timeout::configure(timeouts);
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeThrottle(throttle) => {
+                        self.settings.throttle = throttle;
+                        request::configure(throttle);
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeStatusChecks(checks) => {
+                        self.settings.status_checks = checks;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeStatusInterval(interval) => {
+                        self.settings.status_interval = interval;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeStatusMaxAge(max_age) => {
+                        self.settings.status_max_age = max_age;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeCollapseHeight(height) => {
+                        self.settings.collapse_height = height;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeFetchDomains(domains) => {
+                        self.settings.fetch = domains.clone();
+                        web::configure(domains);
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeAgentSteps(steps) => {
+                        self.settings.agent_steps = steps;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangePreload(file) => {
+                        self.settings.preload = file;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeChatFormat(format) => {
+                        self.settings.chat_format = format;
+                        chat::configure(format);
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeToolPolicies(policies) => {
+                        self.settings.tool_policies = policies.clone();
+                        approval::configure(policies);
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeDatabases(databases) => {
+                        self.settings.databases = databases;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeFileRoots(roots) => {
+                        self.settings.file_roots = roots.clone();
+                        filesystem::configure(roots);
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeReminders(reminders) => {
+                        self.settings.reminders = reminders;
+                        reminder::configure(reminders);
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeClipboardActions(actions) => {
+                        self.settings.clipboard_actions = actions;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangePresets(presets) => {
+                        self.settings.presets = presets;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeMirror(mirror) => {
+                        self.settings.mirror = mirror.clone();
+                        mirror::configure(mirror);
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeShareEndpoint(endpoint) => {
+                        self.settings.share_endpoint = endpoint;
+
+                        self.save_settings()
+                    }
+                    settings::Action::ChangeSharedLibrary(directory) => {
+                        self.settings.shared_library = directory.map(model::Directory::from);
+
+                        Task::batch([self.save_settings(), self.scan()])
+                    }
+                    settings::Action::ChangeLibraryFolder(library) => self.scan(),
+                    settings::Action::Run(task) => task.map(Message::Settings),
+                }
+            }
+            Message::Batch(message) => {
+                let Screen::Batch(batch) = &mut self.screen else {
+                    return Task::none();
+                };
+
+                let assistant = self
+                    .last_conversation
+                    .as_ref()
+                    .and_then(screen::Conversation::assistant);
+
+                match batch.update(message, assistant) {
+                    batch::Action::None => Task::none(),
+                    batch::Action::Run(task) => task.map(Message::Batch),
+                }
+            }
+            Message::Translate(message) => {
+                let Screen::Translate(translate) = &mut self.screen else {
+                    return Task::none();
+                };
+
+                let assistant = self
+                    .last_conversation
+                    .as_ref()
+                    .and_then(screen::Conversation::assistant);
+
+                match translate.update(message, assistant) {
+                    translate::Action::None => Task::none(),
+                    translate::Action::Run(task) => task.map(Message::Translate),
+                }
+            }
+            Message::Plugins(message) => {
+                let Screen::Plugins(plugins) = &mut self.screen else {
+                    return Task::none();
+                };
+
+                match plugins.update(message) {
+                    plugins::Action::None => Task::none(),
+                    plugins::Action::Run(task) => task.map(Message::Plugins),
+                    plugins::Action::Changed => {
+                        if self.last_conversation.is_none() {
+                            return Task::none();
+                        }
+
+                        Task::perform(Plugin::enabled(), conversation::Message::PluginsListed)
+                            .map(Message::Conversation)
+                    }
+                }
+            }
+            Message::Arena(message) => {
+                let backend = self.backend();
+
+                let Screen::Arena(arena) = &mut self.screen else {
+                    return Task::none();
+                };
+
+                match arena.update(message, &self.library, backend) {
+                    arena::Action::None => Task::none(),
+                    arena::Action::Run(task) => task.map(Message::Arena),
+                }
+            }
+            Message::Personas(message) => {
+                let Screen::Personas(personas) = &mut self.screen else {
+                    return Task::none();
+                };
+
+                match personas.update(message) {
+                    personas::Action::None => Task::none(),
+                    personas::Action::Run(task) => task.map(Message::Personas),
+                    personas::Action::Start(persona) => {
+                        if let Some(mut conversation) = self.last_conversation.take() {
+                            let task = conversation.start(persona);
+                            self.screen = Screen::Conversation(conversation);
+
+                            return task.map(Message::Conversation);
+                        }
+
+                        let query = persona.model_class.clone();
+                        self.pending_persona = Some(persona);
+
+                        let search = self.open_search();
+
+                        match query {
+                            Some(query) => search.chain(Task::done(Message::Search(
+                                search::Message::SearchChanged(query),
+                            ))),
+                            None => search,
+                        }
+                    }
+                }
+            }
+            Message::Toast(message) => {
+                self.toasts.update(message);
+
+                Task::none()
+            }
+            Message::Activity(message) => {
+                self.activity.update(message);
+
+                Task::none()
+            }
+            Message::Download(message) => match self.downloads.update(message, &self.library) {
+                download::Action::None => Task::none(),
+                download::Action::Run(task) => task.map(Message::Download),
+                download::Action::Finished(file, then, result, next) => {
+                    let next = next.map(Message::Download);
+
+                    match result {
+                        Ok(_path) => {
+                            let then = match then {
+                                download::Then::Notify => {
+                                    self.toasts.info(format!("{} is downloaded.", file.name));
+
+                                    Task::none()
                                 }
+                                download::Then::Boot => self.boot(model::FileAndAPI {
+                                    file: Some(file),
+                                    api: None,
+                                }),
+                            };
+
+                            Task::batch([next, self.scan(), then])
+                        }
+                        Err(Error::Cancelled(..)) => next,
+                        Err(error) => {
+                            self.notify(error);
+
+                            next
+                        }
+                    }
+                }
+            },
+            Message::Conversion(message) => {
+                let Some(conversion) = &mut self.conversion else {
+                    return Task::none();
+                };
+
+                match conversion.update(message) {
+                    conversion::Action::None => Task::none(),
+                    conversion::Action::Cancel => {
+                        self.conversion = None;
+
+                        Task::none()
+                    }
+                    conversion::Action::Finished(result) => {
+                        self.conversion = None;
+
+                        match result {
+                            Ok(file) => {
+                                self.toasts.info(format!("{} is converted.", file.name));
 
-                                Task::batch(tasks)
+                                self.scan()
                             }
-                            _ => {
-                                unimplemented!()
+                            Err(error) => {
+                                self.notify(error);
+
+                                Task::none()
                             }
-                        },
+                        }
                     }
+                }
+            }
+            Message::Clipboard(message) => {
+                match self
+                    .clipboard
+                    .update(message, &self.settings.clipboard_actions)
+                {
+                    clipboard::Action::None => Task::none(),
+                    clipboard::Action::Run(task) => task.map(Message::Clipboard),
+                    clipboard::Action::Ask(prompt) => self.ask(prompt),
+                }
+            }
+            Message::Profiles(message) => {
+                let Screen::Profiles(profiles) = &mut self.screen else {
+                    return Task::none();
+                };
+
+                match profiles.update(message) {
+                    profiles::Action::None => Task::none(),
+                    profiles::Action::Run(task) => task.map(Message::Profiles),
+                    profiles::Action::Select(name) => self.start(name),
+                }
+            }
+            Message::Git(message) => {
+                let Screen::Git(git) = &mut self.screen else {
+                    return Task::none();
+                };
+
+                match git.update(message) {
+                    git::Action::None => Task::none(),
+                    git::Action::Run(task) => task.map(Message::Git),
+                    git::Action::Ask(prompt) => self.ask(prompt),
+                }
+            }
+            Message::Escape => {
+                if self.activity.is_open() {
+                    self.activity.update(activity::Message::Close);
+
+                    Task::none()
+                } else if matches!(self.screen, Screen::Search(_) | Screen::Profiles(_)) {
+                    Task::none()
+                } else {
+                    self.open_search()
+                }
+            }
+            Message::OpenPendingLink => match self.pending_link.take() {
+                Some(link) => self.open_link(link),
+                None => Task::none(),
+            },
+            Message::Activated(activation) => {
+                let focus = window::get_oldest().and_then(|window| {
+                    window::minimize(window, false).chain(window::gain_focus(window))
+                });
+
+                let link = match Link::find(&activation.arguments) {
+                    // Links wait until the app is loaded
+                    Some(link) if matches!(self.screen, Screen::Loading | Screen::Profiles(_)) => {
+                        self.pending_link = Some(link);
+                        Task::none()
+                    }
+                    Some(link) => self.open_link(link),
+                    None => Task::none(),
+                };
+
+                Task::batch([focus, link])
+            }
+            Message::OpenChats => {
+                let Some(conversation) = self.last_conversation.take() else {
+                    return Task::none();
+                };
+
+                let task = conversation.restore_scroll();
+                self.screen = Screen::Conversation(conversation);
+
+                task.map(Message::Conversation)
+            }
+            Message::OpenSearch => {
+                if let Screen::Conversation(conversation) =
+                    mem::replace(&mut self.screen, Screen::Loading)
+                {
+                    self.last_conversation = Some(conversation);
+                }
+
+                self.open_search()
+            }
+            Message::OpenSettings => {
+                if let Screen::Conversation(conversation) =
+                    mem::replace(&mut self.screen, Screen::Loading)
+                {
+                    self.last_conversation = Some(conversation);
+                }
+
+                self.open_settings()
+            }
+            Message::OpenBatch => {
+                if let Screen::Conversation(conversation) =
+                    mem::replace(&mut self.screen, Screen::Loading)
+                {
+                    self.last_conversation = Some(conversation);
+                }
+
+                self.open_batch()
+            }
+            Message::OpenArena => {
+                if let Screen::Conversation(conversation) =
+                    mem::replace(&mut self.screen, Screen::Loading)
+                {
+                    self.last_conversation = Some(conversation);
+                }
+
+                self.open_arena()
+            }
+            Message::OpenPersonas => {
+                if let Screen::Conversation(conversation) =
+                    mem::replace(&mut self.screen, Screen::Loading)
+                {
+                    self.last_conversation = Some(conversation);
+                }
+
+                self.open_personas()
+            }
+            Message::OpenGit => {
+                if let Screen::Conversation(conversation) =
+                    mem::replace(&mut self.screen, Screen::Loading)
+                {
+                    self.last_conversation = Some(conversation);
+                }
+
+                self.open_git()
+            }
+            Message::OpenTranslate => {
+                if let Screen::Conversation(conversation) =
+                    mem::replace(&mut self.screen, Screen::Loading)
+                {
+                    self.last_conversation = Some(conversation);
+                }
+
+                self.open_translate()
+            }
+            Message::OpenPlugins => {
+                if let Screen::Conversation(conversation) =
+                    mem::replace(&mut self.screen, Screen::Loading)
+                {
+                    self.last_conversation = Some(conversation);
+                }
+
+                self.open_plugins()
+            }
+            Message::Window(_window, event) => {
+                match event {
+                    window::Event::Moved(position) => {
+                        self.settings.window.position =
+                            Some((position.x.round() as i32, position.y.round() as i32));
+                    }
+                    window::Event::Resized(size) => {
+                        self.settings.window.width = size.width.round() as u32;
+                        self.settings.window.height = size.height.round() as u32;
+                    }
+                    _ => {}
+                }
+
+                Task::none()
+            }
+            Message::MonitorChecked(window, monitor) => {
+                let saved = self.settings.window;
+
+                let Some(monitor) = monitor else {
+                    // The window was restored on a monitor that is gone
+                    self.settings.window.position = None;
+
+                    return window::move_to(window, Point::new(WINDOW_MARGIN, WINDOW_MARGIN));
+                };
+
+                let has_layout_changed = saved.monitor.is_some_and(|(width, height)| {
+                    (width, height) != (monitor.width as u32, monitor.height as u32)
+                });
+
+                let width = saved.width as f32;
+                let height = saved.height as f32;
+
+                if has_layout_changed && (width > monitor.width || height > monitor.height) {
+                    window::resize(
+                        window,
+                        Size::new(
+                            width.min(monitor.width * 0.9),
+                            height.min(monitor.height * 0.9),
+                        ),
+                    )
                 } else {
                     Task::none()
                 }
             }
-            Message::Conversation(message) => {
-                let conversation = if let Screen::Conversation(conversation) = &mut self.screen {
-                    Some(conversation)
-                } else {
-                    self.last_conversation.as_mut()
-                };
+            Message::ToggleZen => {
+                self.toggle_sidebar();
+
+                Task::none()
+            }
+            Message::PaneDragged(pane_grid::DragEvent::Dropped { pane, target }) => {
+                self.panes.drop(pane, target);
+
+                Task::none()
+            }
+            Message::PaneDragged(_) => Task::none(),
+            Message::PaneResized(pane_grid::ResizeEvent { split, ratio }) => {
+                self.panes.resize(split, ratio);
+
+                Task::none()
+            }
+            Message::ClosePane(pane) => {
+                if self.panes.get(pane) != Some(&Pane::Screen) {
+                    let _ = self.panes.close(pane);
+                }
 
-                let Some(conversation) = conversation else {
-                    return Task::none();
-                };
+                Task::none()
+            }
+            Message::Splitter(drag) => match self.splitter.update(drag) {
+                splitter::Action::None => Task::none(),
+                splitter::Action::Resized(width) => {
+                    self.settings.window.sidebar_width = width;
 
-                let action = conversation.update(&self.library, message);
+                    self.save_settings()
+                }
+                splitter::Action::Collapsed => {
+                    self.toggle_sidebar();
 
-                match action {
-                    conversation::Action::None => Task::none(),
-                    conversation::Action::Run(task) => task.map(Message::Conversation),
+                    Task::none()
                 }
+            },
+            Message::CloseRequested(window) => {
+                window::monitor_size(window).map(move |monitor| Message::Closing(window, monitor))
             }
-            Message::Settings(message) => {
-                let Screen::Settings(screen_settings) = &mut self.screen else {
-                    return Task::none();
+            Message::Closing(window, monitor) => {
+                self.settings.window.monitor =
+                    monitor.map(|size| (size.width as u32, size.height as u32));
+
+                let session = self
+                    .session()
+                    .map_or_else(Task::none, |session| Task::future(session.save()).discard());
+
+                let conversation = match &mut self.screen {
+                    Screen::Conversation(conversation) => Some(conversation),
+                    _ => None,
                 };
 
-                match screen_settings.update(message) {
-                    settings::Action::None => Task::none(),
-                    settings::Action::ChangeTheme(theme) => {
-                        self.theme = theme;
+                let mut tasks: Vec<_> = conversation
+                    .into_iter()
+                    .chain(self.last_conversation.as_mut())
+                    .map(|conversation| conversation.shutdown().map(Message::Conversation))
+                    .collect();
 
-                        self.save_settings()
-                    }
-                    settings::Action::ChangeLibraryFolder(library) => Task::perform(
-                        model::Library::scan(self.library.clone(), self.settings.clone()),
-                        Message::Scanned,
-                    ),
-                    settings::Action::Run(task) => task.map(Message::Settings),
-                }
+                tasks.extend([session, self.save_settings()]);
+
+                Task::batch(tasks).chain(window::close(window))
             }
-            Message::Escape => {
-                if matches!(self.screen, Screen::Search(_)) {
-                    Task::none()
+            Message::Synced(Ok(summary)) => {
+                info!(
+                    "synced: {} sent, {} received, {} conflicts",
+                    summary.pushed,
+                    summary.pulled,
+                    summary.conflicts.len()
+                );
+
+                if summary.settings {
+                    self.reload_settings()
                 } else {
-                    self.open_search()
+                    Task::none()
                 }
             }
-            Message::OpenChats => {
-                if let Some(conversation) = self.last_conversation.take() {
-                    self.screen = Screen::Conversation(conversation);
-                }
+            Message::CheckReminders => Task::perform(Reminder::take_due(), Message::RemindersDue),
+            Message::RemindersDue(Ok(reminders)) => {
+                Task::batch(reminders.into_iter().map(|reminder| {
+                    self.toasts.info(&reminder.message);
 
-                Task::none()
+                    Task::perform(
+                        async move { desktop::notify("Reminder", &reminder.message).await },
+                        Message::Ignore,
+                    )
+                }))
             }
-            Message::OpenSearch => {
+            Message::ModelImported(Ok(file)) => Task::batch([
+                self.scan(),
+                self.boot(model::FileAndAPI {
+                    file: Some(file),
+                    api: None,
+                }),
+            ]),
+            Message::ChatFileOpened(path, Ok(chat)) => {
                 if let Screen::Conversation(conversation) =
                     mem::replace(&mut self.screen, Screen::Loading)
                 {
                     self.last_conversation = Some(conversation);
                 }
 
-                self.open_search()
+                let (conversation, task) =
+                    screen::Conversation::read_only(&self.library, chat, path, self.backend());
+
+                self.screen = Screen::Conversation(conversation);
+
+                task.map(Message::Conversation)
             }
-            Message::OpenSettings => {
-                if let Screen::Conversation(conversation) =
-                    mem::replace(&mut self.screen, Screen::Loading)
-                {
-                    self.last_conversation = Some(conversation);
-                }
+            Message::Scanned(Err(Error::Cancelled(..))) => {
+                self.scanning = None;
 
-                self.open_settings()
+                Task::none()
             }
-            Message::SettingsSaved(Ok(lib)) => {
-                self.library = lib;
+            Message::Scanned(Err(error)) => {
+                self.scanning = None;
+                self.notify(error);
+
                 Task::none()
             }
-            Message::Scanned(Err(error))
-            | Message::SettingsSaved(Err(error))
-            | Message::SettingsSavedNull(Err(error)) => {
-                log::error!("{error}");
+            Message::ModelImported(Err(error))
+            | Message::ChatFileOpened(_, Err(error))
+            | Message::SettingsSavedNull(Err(error))
+            | Message::Synced(Err(error))
+            | Message::RemindersDue(Err(error))
+            | Message::Ignore(Err(error)) => {
+                self.notify(error);
 
                 Task::none()
             }
-            _ => Task::none(),
+            Message::SettingsSavedNull(Ok(())) | Message::Ignore(Ok(())) => Task::none(),
         }
     }
 
+    fn notify(&mut self, error: Error) {
+        log::error!("{error}");
+
+        self.toasts.error(error);
+    }
+
     fn view(&self) -> Element<'_, Message> {
         let sidebar = {
             let content = match &self.screen {
                 Screen::Conversation(conversation) => {
                     conversation.sidebar().map(Message::Conversation)
                 }
-                Screen::Search(search) => search.sidebar(&self.library).map(Message::Search),
+                Screen::Search(search) => search
+                    .sidebar(&self.library, self.scanning)
+                    .map(Message::Search),
                 Screen::Settings(settings) => settings.sidebar().map(Message::Settings),
-                Screen::Loading => vertical_space().into(),
+                Screen::Batch(batch) => batch.sidebar().map(Message::Batch),
+                Screen::Arena(arena) => arena.sidebar().map(Message::Arena),
+                Screen::Personas(personas) => personas.sidebar().map(Message::Personas),
+                Screen::Git(git) => git.sidebar().map(Message::Git),
+                Screen::Translate(translate) => translate.sidebar().map(Message::Translate),
+                Screen::Plugins(plugins) => plugins.sidebar().map(Message::Plugins),
+                Screen::Profiles(profiles) => profiles.sidebar().map(Message::Profiles),
+                Screen::Loading => container(screen::skeleton(&[160.0, 120.0, 140.0, 100.0]))
+                    .padding(20)
+                    .into(),
             };
 
             let tab = |icon: Text<'static>, toggled, message| {
@@ -345,24 +1459,70 @@ impl Icebreaker {
                     })
             };
 
+            let unread = match &self.screen {
+                Screen::Conversation(conversation) => Some(conversation),
+                _ => self.last_conversation.as_ref(),
+            }
+            .map_or(0, screen::Conversation::unread);
+
+            let chats = tab(
+                icon::chat(),
+                matches!(self.screen, Screen::Conversation(_)),
+                self.last_conversation
+                    .is_some()
+                    .then_some(Message::OpenChats),
+            );
+
+            let activity = tab(
+                icon::clock(),
+                self.activity.is_open(),
+                Some(Message::Activity(activity::Message::Toggle)),
+            );
+
             let tabs = container(row![
-                tab(
-                    icon::chat(),
-                    matches!(self.screen, Screen::Conversation(_)),
-                    self.last_conversation
-                        .is_some()
-                        .then_some(Message::OpenChats),
-                ),
+                badge(chats, unread),
                 tab(
                     icon::cubes(),
                     matches!(self.screen, Screen::Search(_)),
                     Some(Message::OpenSearch),
                 ),
+                tab(
+                    icon::server(),
+                    matches!(self.screen, Screen::Batch(_)),
+                    Some(Message::OpenBatch),
+                ),
+                tab(
+                    icon::star(),
+                    matches!(self.screen, Screen::Arena(_)),
+                    Some(Message::OpenArena),
+                ),
+                tab(
+                    icon::user(),
+                    matches!(self.screen, Screen::Personas(_)),
+                    Some(Message::OpenPersonas),
+                ),
+                tab(
+                    icon::link(),
+                    matches!(self.screen, Screen::Git(_)),
+                    Some(Message::OpenGit),
+                ),
+                tab(
+                    icon::globe(),
+                    matches!(self.screen, Screen::Translate(_)),
+                    Some(Message::OpenTranslate),
+                ),
+                tab(
+                    icon::sliders(),
+                    matches!(self.screen, Screen::Plugins(_)),
+                    Some(Message::OpenPlugins),
+                ),
                 tab(
                     icon::cog(),
                     matches!(self.screen, Screen::Settings(_)),
                     Some(Message::OpenSettings)
                 ),
+                badge(activity, self.activity.count()),
+                tab(icon::left(), false, Some(Message::ToggleZen)),
             ])
             .padding(10)
             .style(|theme| {
@@ -372,27 +1532,224 @@ impl Icebreaker {
 
             row![
                 container(column![container(content).padding(10).height(Fill), tabs])
-                    .width(250)
+                    .width(self.splitter.width())
                     .style(|theme| {
                         container::Style::default()
                             .background(theme.extended_palette().background.weakest.color)
                     }),
-                vertical_rule(1).style(rule::weak),
+                self.splitter.handle().map(Message::Splitter),
             ]
         };
 
-        let screen = match &self.screen {
-            Screen::Loading => screen::loading(),
+        let screen = self.panes();
+
+        let content: Element<'_, _> = if self.is_sidebar_hidden() {
+            let reveal = button(icon::arrow_right())
+                .on_press(Message::ToggleZen)
+                .style(button::text);
+
+            stack![
+                container(screen).padding(10),
+                container(reveal).height(Fill).align_y(Bottom).padding(10),
+            ]
+            .into()
+        } else {
+            row![sidebar, container(screen).padding(10)].into()
+        };
+
+        let can_ask =
+            matches!(self.screen, Screen::Conversation(_)) || self.last_conversation.is_some();
+
+        let offer = self
+            .clipboard
+            .view(&self.settings.clipboard_actions, can_ask)
+            .map(|offer| offer.map(Message::Clipboard));
+
+        let toasts = self.toasts.view().map(|toasts| toasts.map(Message::Toast));
+
+        let activity = self.activity.view().map(|activity| {
+            container(activity.map(Message::Activity))
+                .height(Fill)
+                .align_y(Bottom)
+                .padding(padding::bottom(70).left(10))
+        });
+
+        let downloads = self
+            .downloads
+            .view()
+            .map(|downloads| downloads.map(Message::Download));
+
+        let conversion = self
+            .conversion
+            .as_ref()
+            .map(|conversion| conversion.view().map(Message::Conversion));
+
+        let menu = match &self.screen {
+            Screen::Conversation(conversation) => conversation
+                .context_menu()
+                .map(|menu| menu.map(Message::Conversation)),
+            Screen::Search(search) => search
+                .context_menu(&self.library)
+                .map(|menu| menu.map(Message::Search)),
+            _ => None,
+        };
+
+        if offer.is_none()
+            && toasts.is_none()
+            && downloads.is_none()
+            && conversion.is_none()
+            && menu.is_none()
+            && activity.is_none()
+        {
+            return content.into();
+        }
+
+        stack![
+            content,
+            bottom_right(
+                column![toasts, downloads, conversion, offer]
+                    .spacing(10)
+                    .align_x(iced::Right)
+            )
+            .padding(20),
+            activity,
+            menu,
+        ]
+        .into()
+    }
+
+    fn screen(&self) -> Element<'_, Message> {
+        match &self.screen {
+            Screen::Loading => screen::loading(self.preloading.as_ref()),
             Screen::Search(search) => search.view(&self.library).map(Message::Search),
-            Screen::Conversation(conversation) => {
-                conversation.view(&self.theme).map(Message::Conversation)
-            }
+            Screen::Conversation(conversation) => conversation
+                .view(&self.theme, &self.settings, self.is_sidebar_hidden())
+                .map(Message::Conversation),
             Screen::Settings(settings) => settings
                 .view(&self.library, &self.theme)
                 .map(Message::Settings),
+            Screen::Batch(batch) => batch
+                .view(
+                    self.last_conversation
+                        .as_ref()
+                        .and_then(screen::Conversation::assistant),
+                )
+                .map(Message::Batch),
+            Screen::Arena(arena) => arena.view(&self.library).map(Message::Arena),
+            Screen::Personas(personas) => personas.view().map(Message::Personas),
+            Screen::Git(git) => git.view().map(Message::Git),
+            Screen::Translate(translate) => translate
+                .view(
+                    self.last_conversation
+                        .as_ref()
+                        .and_then(screen::Conversation::assistant),
+                )
+                .map(Message::Translate),
+            Screen::Plugins(plugins) => plugins.view().map(Message::Plugins),
+            Screen::Profiles(profiles) => profiles.view().map(Message::Profiles),
+        }
+    }
+
+    fn panes(&self) -> Element<'_, Message> {
+        if self.panes.len() == 1 {
+            return self.screen();
+        }
+
+        pane_grid(&self.panes, |id, pane, _is_maximized| {
+            let content = pane_grid::Content::new(match pane {
+                Pane::Screen => self.screen(),
+                Pane::Models => self.models(),
+                Pane::Log => self.log(),
+            });
+
+            if *pane == Pane::Screen {
+                return content;
+            }
+
+            let close = button(icon::cancel().size(12))
+                .on_press(Message::ClosePane(id))
+                .style(button::text);
+
+            content
+                .title_bar(
+                    pane_grid::TitleBar::new(
+                        text(pane::title(*pane)).font(Font::MONOSPACE).size(12),
+                    )
+                    .controls(pane_grid::Controls::new(close))
+                    .padding(5),
+                )
+                .style(container::bordered_box)
+        })
+        .spacing(10)
+        .on_drag(Message::PaneDragged)
+        .on_resize(10, Message::PaneResized)
+        .into()
+    }
+
+    fn models(&self) -> Element<'_, Message> {
+        let mut files: Vec<_> = self
+            .library
+            .files
+            .iter()
+            .map(|(id, file)| (id.to_string(), file))
+            .collect();
+
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let files = files.into_iter().map(|(id, file)| {
+            let details = match file {
+                model::FileOrAPI::File(file) => file
+                    .size
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                model::FileOrAPI::API(_) => "API".to_owned(),
+            };
+
+            row![
+                ellipsized_text(id)
+                    .font(Font::MONOSPACE)
+                    .wrapping(text::Wrapping::None)
+                    .width(Fill),
+                text(details).size(12).style(text::secondary),
+            ]
+            .spacing(10)
+            .align_y(Center)
+            .into()
+        });
+
+        scrollable(column(files).spacing(5).padding(10))
+            .height(Fill)
+            .into()
+    }
+
+    fn log(&self) -> Element<'_, Message> {
+        let conversation = match &self.screen {
+            Screen::Conversation(conversation) => Some(conversation),
+            _ => self.last_conversation.as_ref(),
         };
 
-        row![sidebar, container(screen).padding(10)].into()
+        let lines = conversation
+            .map(screen::Conversation::log)
+            .unwrap_or_default();
+
+        if lines.is_empty() {
+            return center(text("No server output yet.").style(text::secondary)).into();
+        }
+
+        scrollable(
+            column(
+                lines
+                    .iter()
+                    .map(|line| text(line).size(12).font(Font::MONOSPACE).into()),
+            )
+            .spacing(2)
+            .padding(10),
+        )
+        .anchor_y(scrollable::Anchor::End)
+        .width(Fill)
+        .height(Fill)
+        .into()
     }
 
     fn subscription(&self) -> Subscription<Message> {
@@ -400,19 +1757,59 @@ impl Icebreaker {
 
         let screen = match &self.screen {
             Screen::Loading => Subscription::none(),
-            Screen::Search(_) => Subscription::none(),
+            Screen::Search(search) => search
+                .subscription(self.settings.status_interval)
+                .map(Message::Search),
             Screen::Conversation(conversation) => {
                 conversation.subscription().map(Message::Conversation)
             }
             Screen::Settings(_) => Subscription::none(),
+            Screen::Batch(_) => Subscription::none(),
+            Screen::Arena(_) => Subscription::none(),
+            Screen::Personas(_) => Subscription::none(),
+            Screen::Git(_) => Subscription::none(),
+            Screen::Translate(_) => Subscription::none(),
+            Screen::Plugins(_) => Subscription::none(),
+            Screen::Profiles(_) => Subscription::none(),
         };
 
-        let hotkeys = keyboard::on_key_press(|key, _modifiers| match key {
+        let hotkeys = keyboard::on_key_press(|key, modifiers| match key.as_ref() {
             keyboard::Key::Named(keyboard::key::Named::Escape) => Some(Message::Escape),
+            keyboard::Key::Character("b") if modifiers.command() => Some(Message::ToggleZen),
             _ => None,
         });
 
-        Subscription::batch([screen, hotkeys])
+        let clipboard = if self.settings.clipboard_watch {
+            self.clipboard.subscription().map(Message::Clipboard)
+        } else {
+            Subscription::none()
+        };
+
+        let window = Subscription::batch([
+            window::close_requests().map(Message::CloseRequested),
+            window::events().map(|(id, event)| Message::Window(id, event)),
+        ]);
+
+        let splitter = self.splitter.subscription().map(Message::Splitter);
+
+        let instances = Subscription::run(instance::listen).map(Message::Activated);
+
+        let toasts = self.toasts.subscription().map(Message::Toast);
+
+        let library = Subscription::run_with(self.store.clone(), model::Store::changes)
+            .map(Message::LibraryChanged);
+
+        let activity = Subscription::run(job::events)
+            .map(activity::Message::Changed)
+            .map(Message::Activity);
+
+        // Reminders may be set by the assistant at any time, so they are always checked
+        let reminders = time::every(Duration::from_secs(30)).map(|_| Message::CheckReminders);
+
+        Subscription::batch([
+            screen, hotkeys, clipboard, window, splitter, instances, toasts, library, activity,
+            reminders,
+        ])
     }
 
     fn theme(&self) -> Theme {
@@ -420,33 +1817,325 @@ impl Icebreaker {
     }
 
     fn open_search(&mut self) -> Task<Message> {
-        let (search, task) = screen::Search::new(self.library.clone());
+        let (search, task) =
+            screen::Search::new(self.library.clone(), self.settings.status_max_age);
 
         self.screen = Screen::Search(search);
 
-        Task::batch([
-            Task::perform(
-                model::Library::scan(self.library.clone(), self.settings.clone()),
-                Message::Scanned,
-            ),
-            task.map(Message::Search),
-        ])
+        Task::batch([self.scan(), task.map(Message::Search)])
     }
 
     fn open_settings(&mut self) -> Task<Message> {
-        let (settings, task) = screen::Settings::new();
+        let (settings, task) = screen::Settings::new(&self.settings, &self.library);
 
         self.screen = Screen::Settings(settings);
 
         task.map(Message::Settings)
     }
 
-    fn save_settings(&self) -> Task<Message> {
-        let settings = Settings {
-            library: self.library.directory().clone(),
-            theme: theme::to_data(&self.theme),
+    fn open_batch(&mut self) -> Task<Message> {
+        let (batch, task) = screen::Batch::new();
+
+        self.screen = Screen::Batch(batch);
+
+        task.map(Message::Batch)
+    }
+
+    fn open_arena(&mut self) -> Task<Message> {
+        let (arena, task) = screen::Arena::new();
+
+        self.screen = Screen::Arena(arena);
+
+        task.map(Message::Arena)
+    }
+
+    fn open_personas(&mut self) -> Task<Message> {
+        let (personas, task) = screen::Personas::new();
+
+        self.screen = Screen::Personas(personas);
+
+        task.map(Message::Personas)
+    }
+
+    fn open_git(&mut self) -> Task<Message> {
+        let (git, task) = screen::Git::new();
+
+        self.screen = Screen::Git(git);
+
+        task.map(Message::Git)
+    }
+
+    fn open_translate(&mut self) -> Task<Message> {
+        let (translate, task) = screen::Translate::new();
+
+        self.screen = Screen::Translate(translate);
+
+        task.map(Message::Translate)
+    }
+
+    fn open_plugins(&mut self) -> Task<Message> {
+        let (plugins, task) = screen::Plugins::new();
+
+        self.screen = Screen::Plugins(plugins);
+
+        task.map(Message::Plugins)
+    }
+
+    fn session(&self) -> Option<Session> {
+        let screen = self.screen_kind()?;
+
+        let search = match &self.screen {
+            Screen::Search(search) => search.snapshot(),
+            _ => session::Search::default(),
+        };
+
+        let conversation = match &self.screen {
+            Screen::Conversation(conversation) => Some(conversation),
+            _ => self.last_conversation.as_ref(),
+        };
+
+        Some(Session {
+            screen,
+            search,
+            scroll: conversation.and_then(screen::Conversation::scroll_offset),
+            zen: self.zen.clone(),
+            layout: pane::to_layout(&self.panes),
+        })
+    }
+
+    fn screen_kind(&self) -> Option<session::Screen> {
+        Some(match &self.screen {
+            Screen::Loading | Screen::Profiles(_) => return None,
+            Screen::Conversation(_) => session::Screen::Conversation,
+            Screen::Search(_) => session::Screen::Search,
+            Screen::Settings(_) => session::Screen::Settings,
+            Screen::Batch(_) => session::Screen::Batch,
+            Screen::Arena(_) => session::Screen::Arena,
+            Screen::Personas(_) => session::Screen::Personas,
+            Screen::Git(_) => session::Screen::Git,
+            Screen::Translate(_) => session::Screen::Translate,
+            Screen::Plugins(_) => session::Screen::Plugins,
+        })
+    }
+
+    fn toggle_sidebar(&mut self) {
+        if self.is_narrow() {
+            self.is_sidebar_pinned = !self.is_sidebar_pinned;
+        } else if let Some(screen) = self.screen_kind() {
+            if !self.zen.remove(&screen) {
+                let _ = self.zen.insert(screen);
+            }
+        }
+    }
+
+    fn is_narrow(&self) -> bool {
+        self.settings.window.width < NARROW_WIDTH
+    }
+
+    fn is_sidebar_hidden(&self) -> bool {
+        if self.is_narrow() {
+            !self.is_sidebar_pinned
+        } else {
+            self.screen_kind()
+                .is_some_and(|screen| self.zen.contains(&screen))
+        }
+    }
+
+    fn open_profiles(&mut self) -> Task<Message> {
+        let (profiles, task) = screen::Profiles::new();
+
+        self.screen = Screen::Profiles(profiles);
+
+        task.map(Message::Profiles)
+    }
+
+    fn ask(&mut self, prompt: String) -> Task<Message> {
+        if !matches!(self.screen, Screen::Conversation(_)) {
+            let Some(conversation) = self.last_conversation.take() else {
+                return Task::none();
+            };
+
+            self.screen = Screen::Conversation(conversation);
+        }
+
+        let Screen::Conversation(conversation) = &mut self.screen else {
+            return Task::none();
+        };
+
+        match conversation.ask(&self.library, &self.settings, prompt) {
+            conversation::Action::None => Task::none(),
+            conversation::Action::Run(task) => task.map(Message::Conversation),
+        }
+    }
+
+    fn boot(&mut self, file: model::FileAndAPI) -> Task<Message> {
+        let (mut conversation, task) =
+            screen::Conversation::new(&self.library, file, self.backend());
+
+        let start = self
+            .pending_persona
+            .take()
+            .map(|persona| conversation.start(persona))
+            .unwrap_or_else(Task::none);
+
+        let draft = match self
+            .pending_draft
+            .take()
+            .map(|prompt| conversation.draft(&self.library, &self.settings, prompt))
+        {
+            Some(conversation::Action::Run(task)) => task,
+            Some(conversation::Action::None) | None => Task::none(),
+        };
+
+        self.screen = Screen::Conversation(conversation);
+        self.last_conversation = None;
+
+        Task::batch([task, start, draft]).map(Message::Conversation)
+    }
+
+    fn open_link(&mut self, link: Link) -> Task<Message> {
+        match link {
+            Link::ModelFile(path) => Task::perform(
+                model::File::import(path, self.library.directory().clone()),
+                Message::ModelImported,
+            ),
+            Link::ChatFile(path) => Task::perform(export::json::open(path.clone()), move |chat| {
+                Message::ChatFileOpened(path.clone(), chat)
+            }),
+            Link::Model(id) => {
+                if let Screen::Conversation(conversation) =
+                    mem::replace(&mut self.screen, Screen::Loading)
+                {
+                    self.last_conversation = Some(conversation);
+                }
+
+                Task::batch([
+                    self.open_search(),
+                    Task::done(Message::Search(search::Message::Select(
+                        model::EndpointId::Local(id),
+                    ))),
+                ])
+            }
+            Link::NewChat { prompt } => {
+                let prompt = prompt.unwrap_or_default();
+
+                if !matches!(self.screen, Screen::Conversation(_)) {
+                    let Some(conversation) = self.last_conversation.take() else {
+                        // The draft waits until a model is picked
+                        self.pending_draft = Some(prompt);
+
+                        return self.open_search();
+                    };
+
+                    self.screen = Screen::Conversation(conversation);
+                }
+
+                let Screen::Conversation(conversation) = &mut self.screen else {
+                    return Task::none();
+                };
+
+                match conversation.draft(&self.library, &self.settings, prompt) {
+                    conversation::Action::None => Task::none(),
+                    conversation::Action::Run(task) => task.map(Message::Conversation),
+                }
+            }
+        }
+    }
+
+    fn backend(&self) -> assistant::Backend {
+        self.system
+            .as_ref()
+            .map(|system| assistant::Backend::detect(&system.graphics_adapter))
+            .unwrap_or(assistant::Backend::Cpu)
+    }
+
+    fn reload_settings(&mut self) -> Task<Message> {
+        self.settings = Settings {
+            window: self.settings.window,
+            ..Settings::fetch().unwrap_or_default()
         };
+        self.theme = theme::from_data(&self.settings.theme);
+
+        audit::configure(self.settings.audit.clone());
+        timeout::configure(self.settings.timeouts.clone());
+        request::configure(self.settings.throttle);
+        mirror::configure(self.settings.mirror.clone());
+        hook::configure(self.settings.hooks.clone());
+        codebase::configure(self.settings.embeddings, &self.settings.keys);
+        reminder::configure(self.settings.reminders);
+        chat::configure(self.settings.chat_format);
+        filesystem::configure(self.settings.file_roots.clone());
+        web::configure(self.settings.fetch.clone());
+        approval::configure(self.settings.tool_policies.clone());
+
+        self.scan()
+    }
+
+    fn scan(&self) -> Task<Message> {
+        Task::sip(
+            job::run(
+                job::Kind::Scan,
+                "Model library",
+                self.store.scan(self.settings.clone()),
+            ),
+            Message::Scanning,
+            Message::Scanned,
+        )
+    }
+
+    fn update_library<T>(
+        &mut self,
+        change: model::Change,
+        f: impl FnOnce(&mut model::Library) -> T,
+    ) -> T {
+        let output = self.store.update(change, f);
+        self.library = self.store.snapshot();
+
+        output
+    }
 
-        Task::perform(settings.save(), Message::SettingsSavedNull)
+    fn refresh_library(&mut self) -> Task<Message> {
+        let old_library = mem::replace(&mut self.library, self.store.snapshot());
+
+        if old_library.directory() != self.library.directory() {
+            self.save_settings()
+        } else {
+            Task::none()
+        }
+    }
+
+    fn save_bookmarks(&self) -> Task<Message> {
+        Task::perform(
+            self.store.clone().save_bookmarks(self.settings.clone()),
+            Message::SettingsSavedNull,
+        )
+    }
+
+    fn save_settings(&mut self) -> Task<Message> {
+        self.settings.library = self.library.directory().clone();
+        self.settings.theme = theme::to_data(&self.theme);
+
+        Task::perform(self.settings.clone().save(), Message::SettingsSavedNull)
     }
 }
+
+fn badge<'a>(tab: impl Into<Element<'a, Message>>, count: usize) -> Element<'a, Message> {
+    let tab: Element<'a, Message> = tab.into();
+
+    if count == 0 {
+        return tab;
+    }
+
+    let badge = container(text(count).size(10).font(Font::MONOSPACE))
+        .padding([0, 4])
+        .style(|theme: &Theme| {
+            let palette = theme.extended_palette();
+
+            container::Style::default()
+                .background(palette.primary.base.color)
+                .color(palette.primary.base.text)
+                .border(border::rounded(6))
+        });
+
+    stack![tab, bottom_right(badge).padding(2)].into()
+}
@@ -0,0 +1,80 @@
+use crate::core::workspace::{Axis, Layout};
+
+use iced::widget::pane_grid;
+
+pub use crate::core::workspace::Pane;
+
+pub fn from_layout(layout: &Layout) -> pane_grid::State<Pane> {
+    if !layout.is_valid() {
+        return pane_grid::State::new(Pane::Screen).0;
+    }
+
+    pane_grid::State::with_configuration(configuration(layout))
+}
+
+pub fn to_layout(panes: &pane_grid::State<Pane>) -> Layout {
+    fn node(panes: &pane_grid::State<Pane>, node: &pane_grid::Node) -> Layout {
+        match node {
+            pane_grid::Node::Split {
+                axis, ratio, a, b, ..
+            } => Layout::Split {
+                axis: match axis {
+                    pane_grid::Axis::Horizontal => Axis::Horizontal,
+                    pane_grid::Axis::Vertical => Axis::Vertical,
+                },
+                ratio: *ratio,
+                a: Box::new(self::node(panes, a)),
+                b: Box::new(self::node(panes, b)),
+            },
+            pane_grid::Node::Pane(pane) => {
+                Layout::Pane(panes.get(*pane).copied().unwrap_or(Pane::Screen))
+            }
+        }
+    }
+
+    node(panes, panes.layout())
+}
+
+pub fn open(panes: &mut pane_grid::State<Pane>, kind: Pane) {
+    if panes.iter().any(|(_, pane)| *pane == kind) {
+        return;
+    }
+
+    let Some(screen) = panes
+        .iter()
+        .find(|(_, pane)| **pane == Pane::Screen)
+        .map(|(id, _)| *id)
+    else {
+        return;
+    };
+
+    let axis = match kind {
+        Pane::Log => pane_grid::Axis::Horizontal,
+        Pane::Screen | Pane::Models => pane_grid::Axis::Vertical,
+    };
+
+    let _ = panes.split(axis, screen, kind);
+}
+
+pub fn title(pane: Pane) -> &'static str {
+    match pane {
+        Pane::Screen => "Screen",
+        Pane::Models => "Models",
+        Pane::Log => "Server Log",
+    }
+}
+
+fn configuration(layout: &Layout) -> pane_grid::Configuration<Pane> {
+    match layout {
+        Layout::Pane(pane) => pane_grid::Configuration::Pane(*pane),
+        Layout::Split { axis, ratio, a, b } => pane_grid::Configuration::Split {
+            axis: match axis {
+                Axis::Horizontal => pane_grid::Axis::Horizontal,
+                Axis::Vertical => pane_grid::Axis::Vertical,
+            },
+            ratio: *ratio,
+            a: Box::new(configuration(a)),
+            b: Box::new(configuration(b)),
+        },
+    }
+}
@@ -1,21 +1,78 @@
+pub mod arena;
+pub mod batch;
 pub mod conversation;
+pub mod git;
+pub mod personas;
+pub mod plugins;
+pub mod profiles;
 pub mod search;
 pub mod settings;
+pub mod translate;
 
+pub use arena::Arena;
+pub use batch::Batch;
 pub use conversation::Conversation;
+pub use git::Git;
+pub use personas::Personas;
+pub use plugins::Plugins;
+pub use profiles::Profiles;
 pub use search::Search;
 pub use settings::Settings;
+pub use translate::Translate;
 
-use iced::widget::horizontal_space;
-use iced::Element;
+use iced::border;
+use iced::widget::{center, column, container, progress_bar, text};
+use iced::{Center, Element, Theme};
 
 pub enum Screen {
     Loading,
     Search(Search),
     Conversation(Conversation),
     Settings(Settings),
+    Batch(Batch),
+    Arena(Arena),
+    Personas(Personas),
+    Git(Git),
+    Translate(Translate),
+    Plugins(Plugins),
+    Profiles(Profiles),
 }
 
-pub fn loading<'a, Message: 'a>() -> Element<'a, Message> {
-    horizontal_space().into()
+pub fn loading<'a, Message: 'a>(preload: Option<&'a Conversation>) -> Element<'a, Message> {
+    let Some((conversation, (stage, progress))) =
+        preload.and_then(|conversation| Some((conversation, conversation.boot_progress()?)))
+    else {
+        return center(skeleton(&[320.0, 480.0, 400.0, 240.0]))
+            .padding(40)
+            .into();
+    };
+
+    center(
+        column![
+            text!("Loading {}...", conversation.model_name()).size(14),
+            progress_bar(0.0..=100.0, progress as f32)
+                .length(200)
+                .girth(4),
+            text(stage).size(12).style(text::secondary),
+        ]
+        .spacing(10)
+        .align_x(Center),
+    )
+    .into()
+}
+
+pub fn skeleton<'a, Message: 'a>(widths: &[f32]) -> Element<'a, Message> {
+    column(widths.iter().map(|width| {
+        container("")
+            .width(*width)
+            .height(14)
+            .style(|theme: &Theme| container::Style {
+                background: Some(theme.extended_palette().background.weak.color.into()),
+                border: border::rounded(4),
+                ..container::Style::default()
+            })
+            .into()
+    }))
+    .spacing(15)
+    .into()
 }
@@ -0,0 +1,378 @@
+use crate::core::arena::{self, Event, Scores, Side, Vote};
+use crate::core::assistant::{Assistant, Backend, Reply};
+use crate::core::model::{FileAndAPI, FileOrAPI, Library};
+use crate::core::Error;
+use crate::widget::sidebar;
+
+use iced::task::{self, Task};
+use iced::widget::{
+    button, center, column, container, horizontal_space, pick_list, row, scrollable, text,
+    text_input, value,
+};
+use iced::{Center, Element, Fill, Font};
+use iced_palace::widget::ellipsized_text;
+
+use std::fmt;
+
+pub struct Arena {
+    a: Slot,
+    b: Slot,
+    prompt: String,
+    round: Option<Round>,
+    scores: Scores,
+    error: Option<Error>,
+}
+
+enum Slot {
+    Empty,
+    Booting {
+        contender: Contender,
+        _task: task::Handle,
+    },
+    Ready(Assistant),
+}
+
+struct Round {
+    prompt: String,
+    a: Reply,
+    b: Reply,
+    swapped: bool,
+    vote: Option<Vote>,
+    _task: Option<task::Handle>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ScoresFetched(Result<Scores, Error>),
+    Choose(Side, Contender),
+    Booted(Side, Result<Assistant, Error>),
+    PromptChanged(String),
+    Submit,
+    Dueling(Event),
+    Dueled(Result<(), Error>),
+    Vote(Vote),
+    Saved(Result<Scores, Error>),
+}
+
+pub enum Action {
+    None,
+    Run(Task<Message>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contender(FileAndAPI);
+
+impl fmt::Display for Contender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.slash_id().0)
+    }
+}
+
+impl Arena {
+    pub fn new() -> (Self, Task<Message>) {
+        (
+            Self {
+                a: Slot::Empty,
+                b: Slot::Empty,
+                prompt: String::new(),
+                round: None,
+                scores: Scores::default(),
+                error: None,
+            },
+            Task::perform(Scores::fetch(), Message::ScoresFetched),
+        )
+    }
+
+    pub fn title(&self) -> &str {
+        "Arena"
+    }
+
+    pub fn update(&mut self, message: Message, library: &Library, backend: Backend) -> Action {
+        match message {
+            Message::ScoresFetched(Ok(scores)) | Message::Saved(Ok(scores)) => {
+                self.scores = scores;
+
+                Action::None
+            }
+            Message::Choose(side, contender) => {
+                let (boot, handle) = Task::perform(
+                    Assistant::boot(library.clone(), contender.0.clone(), backend),
+                    move |result| Message::Booted(side, result),
+                )
+                .abortable();
+
+                *self.slot_mut(side) = Slot::Booting {
+                    contender,
+                    _task: handle.abort_on_drop(),
+                };
+                self.round = None;
+
+                Action::Run(boot)
+            }
+            Message::Booted(side, Ok(assistant)) => {
+                *self.slot_mut(side) = Slot::Ready(assistant);
+
+                Action::None
+            }
+            Message::PromptChanged(prompt) => {
+                self.prompt = prompt;
+
+                Action::None
+            }
+            Message::Submit => {
+                let (Slot::Ready(a), Slot::Ready(b)) = (&self.a, &self.b) else {
+                    return Action::None;
+                };
+
+                let prompt = self.prompt.trim().to_owned();
+
+                if prompt.is_empty() {
+                    return Action::None;
+                }
+
+                let (duel, handle) = Task::sip(
+                    arena::duel(a, b, prompt.clone()),
+                    Message::Dueling,
+                    Message::Dueled,
+                )
+                .abortable();
+
+                self.prompt = String::new();
+                self.error = None;
+                self.round = Some(Round {
+                    prompt,
+                    a: Reply::default(),
+                    b: Reply::default(),
+                    swapped: rand::random(),
+                    vote: None,
+                    _task: Some(handle.abort_on_drop()),
+                });
+
+                Action::Run(duel)
+            }
+            Message::Dueling(Event::Replied(side, reply)) => {
+                if let Some(round) = &mut self.round {
+                    match side {
+                        Side::A => round.a = reply,
+                        Side::B => round.b = reply,
+                    }
+                }
+
+                Action::None
+            }
+            Message::Dueled(Ok(())) => {
+                if let Some(round) = &mut self.round {
+                    round._task = None;
+                }
+
+                Action::None
+            }
+            Message::Vote(vote) => {
+                let (Slot::Ready(a), Slot::Ready(b)) = (&self.a, &self.b) else {
+                    return Action::None;
+                };
+
+                let Some(round) = &mut self.round else {
+                    return Action::None;
+                };
+
+                if round.vote.is_some() {
+                    return Action::None;
+                }
+
+                // Votes are cast on screen positions, which may be swapped
+                let vote = match (vote, round.swapped) {
+                    (Vote::A, true) => Vote::B,
+                    (Vote::B, true) => Vote::A,
+                    (vote, _) => vote,
+                };
+
+                round.vote = Some(vote);
+
+                let mut scores = self.scores.clone();
+                scores.record(a.name(), b.name(), vote);
+
+                Action::Run(Task::perform(scores.save(), Message::Saved))
+            }
+            Message::Dueled(Err(error)) => {
+                // A failed round cannot be voted on
+                self.round = None;
+                self.error = Some(dbg!(error));
+
+                Action::None
+            }
+            Message::Booted(_, Err(error))
+            | Message::ScoresFetched(Err(error))
+            | Message::Saved(Err(error)) => {
+                self.error = Some(dbg!(error));
+
+                Action::None
+            }
+        }
+    }
+
+    pub fn view<'a>(&'a self, library: &'a Library) -> Element<'a, Message> {
+        let contenders: Vec<_> = library
+            .files
+            .values()
+            .map(|file| {
+                Contender(match file {
+                    FileOrAPI::File(file) => FileAndAPI {
+                        file: Some(file.clone()),
+                        api: None,
+                    },
+                    FileOrAPI::API(api) => FileAndAPI {
+                        file: None,
+                        api: Some(api.clone()),
+                    },
+                })
+            })
+            .collect();
+
+        let slot = |side, slot: &Slot| {
+            let selected = match slot {
+                Slot::Empty => None,
+                Slot::Booting { contender, .. } => Some(contender.clone()),
+                Slot::Ready(assistant) => Some(Contender(assistant.file.clone())),
+            };
+
+            let status = match slot {
+                Slot::Empty => text("Choose a model").style(text::secondary),
+                Slot::Booting { .. } => text("Booting...").style(text::primary),
+                Slot::Ready(_) => text("Ready").style(text::success),
+            };
+
+            column![
+                pick_list(contenders.clone(), selected, move |contender| {
+                    Message::Choose(side, contender)
+                })
+                .width(Fill),
+                status.size(12),
+            ]
+            .spacing(5)
+            .width(Fill)
+        };
+
+        let contenders = row![slot(Side::A, &self.a), text("vs"), slot(Side::B, &self.b)]
+            .spacing(10)
+            .align_y(Center);
+
+        let is_ready = matches!((&self.a, &self.b), (Slot::Ready(_), Slot::Ready(_)));
+
+        let input = text_input("Ask both models...", &self.prompt)
+            .on_input(Message::PromptChanged)
+            .on_submit_maybe(is_ready.then_some(Message::Submit))
+            .padding(10);
+
+        let round: Element<'_, _> = match &self.round {
+            Some(round) => round.view(&self.a, &self.b),
+            None => {
+                center(text("Pick two models and ask them anything.").style(text::secondary)).into()
+            }
+        };
+
+        let error = self
+            .error
+            .as_ref()
+            .map(|error| value(error).font(Font::MONOSPACE).style(text::danger));
+
+        column![contenders, input, error, round].spacing(20).into()
+    }
+
+    pub fn sidebar(&self) -> Element<'_, Message> {
+        let header = sidebar::header("Leaderboard", None);
+
+        let leaderboard = column(self.scores.leaderboard().into_iter().enumerate().map(
+            |(i, (model, rating))| {
+                column![
+                    row![
+                        text!("{}.", i + 1).font(Font::MONOSPACE).size(12),
+                        ellipsized_text(model.to_owned())
+                            .font(Font::MONOSPACE)
+                            .wrapping(text::Wrapping::None)
+                    ]
+                    .spacing(5)
+                    .align_y(Center),
+                    text!(
+                        "{elo:.0} · {wins}W {losses}L {ties}T",
+                        elo = rating.elo,
+                        wins = rating.wins,
+                        losses = rating.losses,
+                        ties = rating.ties
+                    )
+                    .size(12)
+                    .style(text::secondary)
+                ]
+                .spacing(2)
+                .into()
+            },
+        ))
+        .spacing(10);
+
+        column![header, scrollable(leaderboard).height(Fill)]
+            .spacing(10)
+            .into()
+    }
+
+    fn slot_mut(&mut self, side: Side) -> &mut Slot {
+        match side {
+            Side::A => &mut self.a,
+            Side::B => &mut self.b,
+        }
+    }
+}
+
+impl Round {
+    fn view<'a>(&'a self, a: &'a Slot, b: &'a Slot) -> Element<'a, Message> {
+        let (left, right) = if self.swapped {
+            ((&self.b, b), (&self.a, a))
+        } else {
+            ((&self.a, a), (&self.b, b))
+        };
+
+        let is_done = self._task.is_none();
+
+        let response = |label: &'static str, (reply, slot): (&'a Reply, &'a Slot)| {
+            let name = match (self.vote, slot) {
+                (Some(_), Slot::Ready(assistant)) => assistant.name(),
+                _ => label,
+            };
+
+            column![
+                text(name).font(Font::MONOSPACE).style(text::primary),
+                container(text(&reply.content).shaping(text::Shaping::Advanced))
+                    .padding(10)
+                    .width(Fill)
+                    .style(container::bordered_box),
+            ]
+            .spacing(10)
+            .width(Fill)
+        };
+
+        let vote = |label, vote| {
+            button(text(label).size(14))
+                .on_press_maybe((is_done && self.vote.is_none()).then_some(Message::Vote(vote)))
+                .style(button::secondary)
+        };
+
+        let votes = row![
+            vote("← Left is better", Vote::A),
+            horizontal_space(),
+            vote("Tie", Vote::Tie),
+            horizontal_space(),
+            vote("Right is better →", Vote::B),
+        ]
+        .align_y(Center);
+
+        scrollable(
+            column![
+                text(&self.prompt).size(18),
+                row![response("Response 1", left), response("Response 2", right)].spacing(20),
+                votes,
+            ]
+            .spacing(20),
+        )
+        .height(Fill)
+        .into()
+    }
+}
@@ -0,0 +1,373 @@
+use crate::core::assistant::Assistant;
+use crate::core::batch::{self, Event};
+use crate::core::Error;
+use crate::icon;
+use crate::widget::sidebar;
+
+use iced::task::{self, Task};
+use iced::widget::{
+    button, center, column, container, horizontal_space, pick_list, row, scrollable, text, value,
+};
+use iced::{Center, Element, Fill, Font};
+use iced_palace::widget::ellipsized_text;
+use thiserror::capture;
+
+use std::path::PathBuf;
+
+pub struct Batch {
+    file: Option<PathBuf>,
+    rows: Vec<Row>,
+    concurrency: usize,
+    running: Option<task::Handle>,
+    error: Option<Error>,
+}
+
+struct Row {
+    prompt: String,
+    status: Status,
+}
+
+enum Status {
+    Pending,
+    Running(String),
+    Done(String),
+    Failed(Error),
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    PickFile,
+    FilePicked(Option<rfd::FileHandle>),
+    Loaded(PathBuf, Result<Vec<String>, Error>),
+    ConcurrencyChanged(usize),
+    Run,
+    Stop,
+    Running(Event),
+    Finished(Result<(), Error>),
+    Export,
+    ExportPicked(Option<rfd::FileHandle>),
+    Exported(Result<(), Error>),
+}
+
+pub enum Action {
+    None,
+    Run(Task<Message>),
+}
+
+const CONCURRENCY: [usize; 5] = [1, 2, 4, 8, 16];
+
+impl Batch {
+    pub fn new() -> (Self, Task<Message>) {
+        (
+            Self {
+                file: None,
+                rows: Vec::new(),
+                concurrency: 4,
+                running: None,
+                error: None,
+            },
+            Task::none(),
+        )
+    }
+
+    pub fn title(&self) -> &str {
+        "Batch"
+    }
+
+    pub fn update(&mut self, message: Message, assistant: Option<&Assistant>) -> Action {
+        match message {
+            Message::PickFile => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose a file of prompts...")
+                    .add_filter("Prompts", &["csv", "jsonl"])
+                    .pick_file(),
+                Message::FilePicked,
+            )),
+            Message::FilePicked(file) => {
+                let Some(file) = file else {
+                    return Action::None;
+                };
+
+                let path = file.path().to_path_buf();
+
+                Action::Run(Task::perform(batch::load(path.clone()), move |prompts| {
+                    Message::Loaded(path, prompts)
+                }))
+            }
+            Message::Loaded(path, Ok(prompts)) => {
+                self.file = Some(path);
+                self.rows = prompts
+                    .into_iter()
+                    .map(|prompt| Row {
+                        prompt,
+                        status: Status::Pending,
+                    })
+                    .collect();
+                self.running = None;
+                self.error = None;
+
+                Action::None
+            }
+            Message::ConcurrencyChanged(concurrency) => {
+                self.concurrency = concurrency;
+
+                Action::None
+            }
+            Message::Run => {
+                let Some(assistant) = assistant else {
+                    return Action::None;
+                };
+
+                for row in &mut self.rows {
+                    row.status = Status::Pending;
+                }
+
+                let prompts = self.rows.iter().map(|row| row.prompt.clone()).collect();
+
+                let (run, handle) = Task::sip(
+                    batch::run(assistant, prompts, self.concurrency),
+                    Message::Running,
+                    Message::Finished,
+                )
+                .abortable();
+
+                self.running = Some(handle.abort_on_drop());
+                self.error = None;
+
+                Action::Run(run)
+            }
+            Message::Stop => {
+                self.running = None;
+
+                for row in &mut self.rows {
+                    if matches!(row.status, Status::Pending | Status::Running(_)) {
+                        row.status = Status::Failed(Error::Cancelled(capture!()));
+                    }
+                }
+
+                Action::None
+            }
+            Message::Running(event) => {
+                let (i, status) = match event {
+                    Event::Started(i) => (i, Status::Running(String::new())),
+                    Event::Progressed(i, content) => (i, Status::Running(content)),
+                    Event::Finished(i, Ok(content)) => (i, Status::Done(content)),
+                    Event::Finished(i, Err(error)) => (i, Status::Failed(error)),
+                };
+
+                if let Some(row) = self.rows.get_mut(i) {
+                    row.status = status;
+                }
+
+                Action::None
+            }
+            Message::Finished(Ok(())) => {
+                self.running = None;
+
+                Action::None
+            }
+            Message::Export => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Export outputs...")
+                    .set_file_name("outputs.jsonl")
+                    .add_filter("JSON Lines", &["jsonl"])
+                    .add_filter("CSV", &["csv"])
+                    .save_file(),
+                Message::ExportPicked,
+            )),
+            Message::ExportPicked(file) => {
+                let Some(file) = file else {
+                    return Action::None;
+                };
+
+                let outputs = self
+                    .rows
+                    .iter()
+                    .map(|row| batch::Output {
+                        prompt: row.prompt.clone(),
+                        output: match &row.status {
+                            Status::Done(content) => Some(content.clone()),
+                            _ => None,
+                        },
+                        error: match &row.status {
+                            Status::Failed(error) => Some(error.to_string()),
+                            _ => None,
+                        },
+                    })
+                    .collect();
+
+                Action::Run(Task::perform(
+                    batch::export(file.path().to_path_buf(), outputs),
+                    Message::Exported,
+                ))
+            }
+            Message::Exported(Ok(())) => Action::None,
+            Message::Loaded(_, Err(error))
+            | Message::Finished(Err(error))
+            | Message::Exported(Err(error)) => {
+                self.running = None;
+                self.error = Some(dbg!(error));
+
+                Action::None
+            }
+        }
+    }
+
+    pub fn view(&self, assistant: Option<&Assistant>) -> Element<'_, Message> {
+        let controls = {
+            let model: Element<'_, _> = match assistant {
+                Some(assistant) => ellipsized_text(assistant.name().to_owned())
+                    .font(Font::MONOSPACE)
+                    .wrapping(text::Wrapping::None)
+                    .into(),
+                None => text("Open a chat to choose a model for the batch.")
+                    .style(text::secondary)
+                    .into(),
+            };
+
+            let concurrency = pick_list(
+                &CONCURRENCY[..],
+                Some(self.concurrency),
+                Message::ConcurrencyChanged,
+            );
+
+            let run = if self.running.is_some() {
+                button("Stop").on_press(Message::Stop).style(button::danger)
+            } else {
+                button("Run").on_press_maybe(
+                    (assistant.is_some() && !self.rows.is_empty()).then_some(Message::Run),
+                )
+            };
+
+            let export = button("Export")
+                .on_press_maybe(
+                    (self.running.is_none() && self.done() > 0).then_some(Message::Export),
+                )
+                .style(button::secondary);
+
+            row![
+                container(model).width(Fill),
+                text("Concurrency").size(14),
+                concurrency,
+                run,
+                export
+            ]
+            .spacing(10)
+            .align_y(Center)
+        };
+
+        let error = self
+            .error
+            .as_ref()
+            .map(|error| value(error).font(Font::MONOSPACE).style(text::danger));
+
+        let rows: Element<'_, _> = if self.rows.is_empty() {
+            center(
+                button("Load prompts from a CSV or JSONL file...")
+                    .on_press(Message::PickFile)
+                    .style(button::text),
+            )
+            .into()
+        } else {
+            scrollable(
+                column(self.rows.iter().enumerate().map(|(i, row)| row.view(i)))
+                    .spacing(10)
+                    .padding([0, 10]),
+            )
+            .height(Fill)
+            .into()
+        };
+
+        column![controls, error, rows].spacing(20).into()
+    }
+
+    pub fn sidebar(&self) -> Element<'_, Message> {
+        let header = sidebar::header("Batch", Some((icon::folder_open(), Message::PickFile)));
+
+        let summary = self.file.as_ref().map(|file| {
+            column![
+                ellipsized_text(
+                    file.file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .into_owned()
+                )
+                .font(Font::MONOSPACE)
+                .wrapping(text::Wrapping::None),
+                text!(
+                    "{done} / {total} completed",
+                    done = self.done(),
+                    total = self.rows.len()
+                )
+                .size(12)
+                .style(text::secondary),
+                (self.failed() > 0).then(|| {
+                    text!("{} failed", self.failed())
+                        .size(12)
+                        .style(text::danger)
+                }),
+            ]
+            .spacing(5)
+        });
+
+        column![header, summary].spacing(10).into()
+    }
+
+    fn done(&self) -> usize {
+        self.rows
+            .iter()
+            .filter(|row| matches!(row.status, Status::Done(_)))
+            .count()
+    }
+
+    fn failed(&self) -> usize {
+        self.rows
+            .iter()
+            .filter(|row| matches!(row.status, Status::Failed(_)))
+            .count()
+    }
+}
+
+impl Row {
+    fn view(&self, index: usize) -> Element<'_, Message> {
+        let status = match &self.status {
+            Status::Pending => None,
+            Status::Running(_) => Some(icon::refresh().style(text::primary)),
+            Status::Done(_) => Some(icon::check().style(text::success)),
+            Status::Failed(_) => Some(icon::cancel().style(text::danger)),
+        };
+
+        let output: Element<'_, _> = match &self.status {
+            Status::Pending => horizontal_space().into(),
+            Status::Running(content) | Status::Done(content) => {
+                text(content).size(12).font(Font::MONOSPACE).into()
+            }
+            Status::Failed(error) => value(error)
+                .size(12)
+                .font(Font::MONOSPACE)
+                .style(text::danger)
+                .into(),
+        };
+
+        container(
+            column![
+                row![
+                    text!("#{}", index + 1)
+                        .size(12)
+                        .font(Font::MONOSPACE)
+                        .style(text::secondary),
+                    text(&self.prompt).width(Fill),
+                    status.map(|status| status.size(12).line_height(1.0)),
+                ]
+                .spacing(10)
+                .align_y(Center),
+                output
+            ]
+            .spacing(10),
+        )
+        .width(Fill)
+        .padding(10)
+        .style(container::bordered_box)
+        .into()
+    }
+}
@@ -1,29 +1,66 @@
-use crate::core::assistant::{Assistant, Backend, BootEvent};
+use crate::browser;
+use crate::clipboard::read_image;
+use crate::core;
+use crate::core::approval;
+use crate::core::assistant::{self, Assistant, Backend, BootEvent};
+use crate::core::attachment::{self, Attachment};
 use crate::core::chat::{self, Chat, Entry, Id, Strategy};
+use crate::core::codebase;
+use crate::core::database::Database;
+use crate::core::diagnostics;
+use crate::core::diagram;
+use crate::core::diff::{self, Revision};
+use crate::core::diffusion;
+use crate::core::execution;
+use crate::core::export;
+use crate::core::hook;
+use crate::core::job;
 use crate::core::model::{File, Library};
-use crate::core::Error;
+use crate::core::ocr;
+use crate::core::persona::Persona;
+use crate::core::plugin::{self, Plugin};
+use crate::core::preset::Preset;
+use crate::core::prompt;
+use crate::core::quick_action::QuickAction;
+use crate::core::summary;
+use crate::core::telemetry;
+use crate::core::todo::Todo;
+use crate::core::transcription;
+use crate::core::{Error, Settings, Url};
 use crate::icon;
+use crate::ui;
 use crate::ui::markdown;
 use crate::ui::plan;
 use crate::ui::{Markdown, Plan, Reply};
-use crate::widget::{copy, regenerate, sidebar, tip, toggle};
+use crate::widget::context_menu::{self, ContextMenu};
+use crate::widget::table::{self, Table};
+use crate::widget::{copy, diffused_text, regenerate, sidebar, tip, toggle};
 
 use icebreaker_core::model::FileAndAPI;
+use iced::border;
 use iced::clipboard;
 use iced::gradient;
+use iced::keyboard;
 use iced::padding;
 use iced::task::{self, Task};
 use iced::time::{self, Duration, Instant};
 use iced::widget::{
-    self, bottom, bottom_right, button, center, center_x, center_y, column, container,
-    horizontal_space, hover, opaque, progress_bar, right, right_center, row, scrollable, sensor,
-    stack, text, text_editor, tooltip, value, vertical_space,
+    self, bottom, bottom_right, button, center, center_x, center_y, checkbox, column, container,
+    horizontal_rule, horizontal_space, hover, image, opaque, pick_list, progress_bar, right,
+    right_center, row, rule, scrollable, sensor, stack, text, text_editor, text_input, tooltip,
+    value, vertical_space,
 };
 use iced::Degrees;
 use iced::{Center, Color, Element, Fill, Font, Function, Shrink, Size, Subscription, Theme};
 use iced_palace::widget::ellipsized_text;
 use log::warn;
 
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::mem;
+use std::path::PathBuf;
+use std::sync::Arc;
+
 pub struct Conversation {
     backend: Backend,
     chats: Vec<Entry>,
@@ -33,11 +70,143 @@ pub struct Conversation {
     history: History,
     input: text_editor::Content,
     header_height: f32,
+    show_summary: bool,
     chat_width: f32,
     input_height: f32,
     total_width: f32,
     strategy: Strategy,
+    preset: Option<String>,
+    is_indexing: bool,
+    prompts: prompt::History,
+    quick_actions: Vec<QuickAction>,
+    recall: Option<usize>,
+    prompt_search: Option<String>,
+    preview: Option<Markdown>,
+    attachments: Vec<(Attachment, image::Handle)>,
+    recognizing: HashSet<attachment::Id>,
+    scroll: Scroll,
+    expanded: HashSet<usize>,
+    revisions: HashMap<usize, Revision>,
+    diffing: HashSet<usize>,
+    tables: HashMap<usize, Option<Table>>,
+    context_start: Option<usize>,
+    menu: ContextMenu<usize>,
+    persona: Option<Persona>,
+    lock: Option<chat::Lock>,
+    todos: Vec<Todo>,
+    show_todos: bool,
+    extracting_todos: Option<task::Handle>,
+    checkpoints: Vec<chat::Checkpoint>,
+    new_checkpoint: Option<String>,
+    is_read_only: bool,
+    painting: Option<Painting>,
+    transcription: Option<Transcription>,
+    summarizing: Option<summary::Progress>,
+    plugins: Vec<Plugin>,
+    telemetry: Option<telemetry::Sample>,
+    log: Vec<String>,
+    read_only: Option<PathBuf>,
     error: Option<Error>,
+    generation: Option<Generation>,
+    next_generation: u64,
+    background: HashMap<Generation, Background>,
+    approvals: Vec<(Generation, approval::Request)>,
+    unread: HashSet<Id>,
+    submit_on_boot: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Generation(u64);
+
+struct Background {
+    id: Option<Id>,
+    file: FileAndAPI,
+    title: Option<String>,
+    history: History,
+    persona: Option<Persona>,
+    lock: Option<chat::Lock>,
+    todos: Vec<Todo>,
+    checkpoints: Vec<chat::Checkpoint>,
+    context_start: Option<usize>,
+    sending: task::Handle,
+}
+
+impl Background {
+    fn save(self) -> impl Future<Output = Result<Chat, Error>> {
+        let history = self.history.to_data();
+
+        async move {
+            match self.id {
+                Some(id) => {
+                    Chat {
+                        id,
+                        file: self.file,
+                        title: self.title,
+                        history,
+                        persona: self.persona,
+                        lock: self.lock,
+                        todos: self.todos,
+                        checkpoints: self.checkpoints,
+                        is_read_only: false,
+                    }
+                    .save()
+                    .await
+                }
+                None => {
+                    Chat::create(
+                        self.file,
+                        self.title,
+                        history,
+                        self.persona,
+                        self.lock,
+                        self.todos,
+                        self.checkpoints,
+                    )
+                    .await
+                }
+            }
+        }
+    }
+}
+
+struct Transcription {
+    file_name: String,
+    progress: transcription::Progress,
+    _task: task::Handle,
+}
+
+#[derive(Debug, Clone)]
+struct Painting {
+    size: diffusion::Size,
+    steps: String,
+}
+
+impl Default for Painting {
+    fn default() -> Self {
+        let request = diffusion::Request::new("");
+
+        Self {
+            size: request.size,
+            steps: request.steps.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Scroll {
+    offset: scrollable::AbsoluteOffset,
+    is_at_bottom: bool,
+    has_unread: bool,
+}
+
+impl Default for Scroll {
+    fn default() -> Self {
+        Self {
+            offset: scrollable::AbsoluteOffset::default(),
+            is_at_bottom: true,
+            has_unread: false,
+        }
+    }
 }
 
 enum State {
@@ -61,20 +230,82 @@ pub enum Message {
     Booting(BootEvent),
     Booted(Result<Assistant, Error>),
     Tick(Instant),
+    TelemetrySampled(telemetry::Sample),
     InputChanged(text_editor::Action),
+    PromptsFetched(Result<prompt::History, Error>),
+    PromptsSaved(Result<(), Error>),
+    QuickActionsListed(Result<Vec<QuickAction>, Error>),
+    QuickAction(usize, usize),
+    RecallOlder,
+    RecallNewer,
+    TogglePromptSearch,
+    TogglePreview,
+    ToggleSummary,
+    ToggleLock,
+    ToggleReadOnly,
+    ReadOnlyToggled(Result<Chat, Error>),
+    PasteImage,
+    ImagePasted(Result<Option<Attachment>, Error>),
+    ImageRecognized(attachment::Id, Result<Attachment, Error>),
+    RemoveAttachment(usize),
+    Scrolled(scrollable::Viewport),
+    JumpToBottom,
+    ToggleExpanded(usize),
+    ToggleDiff(usize),
+    PromptSearchChanged(String),
+    PromptPicked(String),
     Resized(Size),
     HeaderShown(Size),
     HeaderResized(Size),
     ChatResized(Size),
     InputResized(Size),
     ToggleSearch,
+    ToggleFetch,
+    ToggleAgent,
+    StopAgent,
+    SelectPreset(Preset),
+    ToggleCodebase,
+    SelectDatabase(Database),
+    DetachDatabase,
+    Query(usize, String),
+    CodebasePicked(Option<rfd::FileHandle>),
+    CodebaseIndexed(Result<Arc<codebase::Index>, Error>),
     Submit,
     Regenerate(usize),
-    Chatting(chat::Event),
-    Chatted(Result<(), Error>),
+    Retry,
+    Chatting(Generation, chat::Event),
+    RespondToApproval(approval::Id, bool),
+    Chatted(Generation, Result<(), Error>),
+    Finished(Result<Chat, Error>),
     TitleChanging(String),
     TitleChanged(Result<String, Error>),
     Copy(String),
+    Menu(context_menu::Event<usize>),
+    Edit(usize),
+    RemoveItem(usize),
+    NewCheckpoint,
+    CheckpointNameChanged(String),
+    AddCheckpoint,
+    RollBack(usize),
+    Branch(usize),
+    Branched(Result<Chat, Error>),
+    RemoveCheckpoint(usize),
+    Extract(usize),
+    Extracted(usize, Result<core::table::Table, Error>),
+    ExtractTodos,
+    TodosExtracted(Result<Vec<Todo>, Error>),
+    ToggleTodo(usize, bool),
+    CopyTodos,
+    ExportTodos,
+    TodosExportPicked(Option<rfd::FileHandle>),
+    ClearTodos,
+    Table(usize, table::Interaction),
+    CloseTable(usize),
+    SaveTable(core::table::Table),
+    TableSavePicked(core::table::Table, Option<rfd::FileHandle>),
+    SaveDiagram(diagram::Diagram),
+    DiagramSavePicked(diagram::Diagram, Option<rfd::FileHandle>),
+    MarkdownTable(usize, usize, table::Interaction),
     ToggleReasoning(usize, bool),
     Created(Result<Chat, Error>),
     Saved(Result<Chat, Error>),
@@ -82,9 +313,34 @@ pub enum Message {
     ChatFetched(Result<Chat, Error>),
     LastChatFetched(Result<Chat, Error>),
     Delete,
+    Export,
+    ExportPicked(Option<rfd::FileHandle>),
+    Exported(Result<(), Error>),
+    Keep,
+    Kept(Result<Chat, Error>),
+    Share,
+    Shared(Result<Url, Error>),
     New,
     Plan(usize, plan::Message),
     Markdown(markdown::Interaction),
+    Executed(Result<execution::Output, Error>),
+    TogglePainting,
+    PaintingSizeChanged(diffusion::Size),
+    PaintingStepsChanged(String),
+    Painted(Result<Attachment, Error>),
+    TranscribeFile,
+    TranscriptionFilePicked(Option<rfd::FileHandle>),
+    Transcribing(transcription::Progress),
+    Transcribed(Result<String, Error>),
+    CancelTranscription,
+    SummarizeFile,
+    SummaryFilePicked(Option<rfd::FileHandle>),
+    DocumentLoaded(String, String),
+    Summarizing(summary::Progress),
+    Summarized(Result<assistant::Reply, Error>),
+    PluginsListed(Result<Vec<Plugin>, Error>),
+    CommandRan(Result<plugin::Response, Error>),
+    HooksRan(Result<Vec<String>, Error>),
 }
 
 pub enum Action {
@@ -117,28 +373,97 @@ impl Conversation {
                 history: History::new(),
                 input: text_editor::Content::new(),
                 header_height: 0.0,
+                show_summary: false,
                 chat_width: 0.0,
                 input_height: 0.0,
                 total_width: 0.0,
                 strategy: Strategy::default(),
+                preset: None,
+                is_indexing: false,
+                prompts: prompt::History::default(),
+                quick_actions: Vec::new(),
+                recall: None,
+                prompt_search: None,
+                preview: None,
+                attachments: Vec::new(),
+                recognizing: HashSet::new(),
+                scroll: Scroll::default(),
+                expanded: HashSet::new(),
+                revisions: HashMap::new(),
+                diffing: HashSet::new(),
+                tables: HashMap::new(),
+                context_start: None,
+                menu: ContextMenu::default(),
+                persona: None,
+                lock: None,
+                todos: Vec::new(),
+                show_todos: false,
+                extracting_todos: None,
+                checkpoints: Vec::new(),
+                new_checkpoint: None,
+                is_read_only: false,
+                painting: None,
+                transcription: None,
+                summarizing: None,
+                plugins: Vec::new(),
+                telemetry: None,
+                log: Vec::new(),
+                read_only: None,
                 error: None,
+                generation: None,
+                next_generation: 0,
+                background: HashMap::new(),
+                approvals: Vec::new(),
+                unread: HashSet::new(),
+                submit_on_boot: false,
                 chats: Vec::new(),
             },
-            Task::batch([boot, Task::perform(Chat::list(), Message::ChatsListed)]),
+            Task::batch([
+                boot,
+                Task::perform(Chat::list(), Message::ChatsListed),
+                Task::perform(prompt::History::fetch(), Message::PromptsFetched),
+                Task::perform(QuickAction::list(), Message::QuickActionsListed),
+                Task::perform(Plugin::enabled(), Message::PluginsListed),
+            ]),
         )
     }
 
     pub fn open(library: &Library, chat: Chat, backend: Backend) -> (Self, Task<Message>) {
         let (conversation, task) = Self::new(library, chat.file, backend);
 
+        let mut conversation = Self {
+            id: Some(chat.id),
+            title: chat.title,
+            history: History::restore(chat.history),
+            persona: chat.persona,
+            lock: chat.lock,
+            show_todos: !chat.todos.is_empty(),
+            todos: chat.todos,
+            checkpoints: chat.checkpoints,
+            is_read_only: chat.is_read_only,
+            ..conversation
+        };
+
+        conversation.revise();
+
+        (conversation, task)
+    }
+
+    pub fn read_only(
+        library: &Library,
+        chat: Chat,
+        path: PathBuf,
+        backend: Backend,
+    ) -> (Self, Task<Message>) {
+        let (conversation, _boot) = Self::open(library, chat, backend);
+
         (
             Self {
-                id: Some(chat.id),
-                title: chat.title,
-                history: History::restore(chat.history),
+                id: None,
+                read_only: Some(path),
                 ..conversation
             },
-            task,
+            Task::perform(Chat::list(), Message::ChatsListed),
         )
     }
 
@@ -146,7 +471,7 @@ impl Conversation {
         self.title.as_deref().unwrap_or(self.model_name())
     }
 
-    pub fn update(&mut self, library: &Library, message: Message) -> Action {
+    pub fn update(&mut self, library: &Library, settings: &Settings, message: Message) -> Action {
         match message {
             Message::ChatsListed(Ok(chats)) => {
                 self.chats = chats;
@@ -174,6 +499,12 @@ impl Conversation {
                     Action::None
                 }
                 BootEvent::Logged(log) => {
+                    if self.log.len() == LOG_LIMIT {
+                        let _ = self.log.remove(0);
+                    }
+
+                    self.log.push(log.clone());
+
                     if let State::Booting { logs, .. } = &mut self.state {
                         logs.push(log);
                     }
@@ -186,6 +517,16 @@ impl Conversation {
                     assistant,
                     sending: None,
                 };
+                self.telemetry = None;
+
+                if mem::take(&mut self.submit_on_boot) {
+                    return self.update(library, settings, Message::Submit);
+                }
+
+                Action::None
+            }
+            Message::TelemetrySampled(sample) => {
+                self.telemetry = Some(sample);
 
                 Action::None
             }
@@ -197,566 +538,2978 @@ impl Conversation {
                 Action::None
             }
             Message::InputChanged(action) => {
-                self.input.perform(action);
-                self.error = None;
+                if action.is_edit() {
+                    self.recall = None;
+                }
 
-                Action::None
-            }
-            Message::Resized(bounds) => {
-                self.total_width = bounds.width;
+                match action {
+                    text_editor::Action::Edit(text_editor::Edit::Insert(c)) => {
+                        insert(&mut self.input, c);
+                    }
+                    action => self.input.perform(action),
+                }
 
-                Action::None
-            }
-            Message::HeaderShown(bounds) => {
-                self.header_height = bounds.height;
+                if self.preview.is_some() {
+                    self.preview = Some(Markdown::parse(&self.input.text()));
+                }
 
-                Action::Run(Task::batch([widget::focus_next(), snap_chat_to_end()]))
-            }
-            Message::HeaderResized(bounds) => {
-                self.header_height = bounds.height;
+                self.error = None;
 
                 Action::None
             }
-            Message::ChatResized(bounds) => {
-                self.chat_width = bounds.width;
+            Message::TogglePreview => {
+                self.preview = if self.preview.is_some() {
+                    None
+                } else {
+                    Some(Markdown::parse(&self.input.text()))
+                };
 
                 Action::None
             }
-            Message::InputResized(bounds) => {
-                self.input_height = bounds.height;
+            Message::Scrolled(viewport) => {
+                let offset = viewport.absolute_offset();
+                let max_offset =
+                    (viewport.content_bounds().height - viewport.bounds().height).max(0.0);
 
-                Action::None
-            }
-            Message::ToggleSearch => {
-                self.strategy.search = !self.strategy.search;
+                self.scroll.offset = offset;
+                self.scroll.is_at_bottom = max_offset - offset.y < 5.0;
+
+                if self.scroll.is_at_bottom {
+                    self.scroll.has_unread = false;
+                }
 
                 Action::None
             }
-            Message::Submit => {
-                let State::Running { assistant, sending } = &mut self.state else {
+            Message::ToggleLock => {
+                let State::Running { assistant, .. } = &self.state else {
                     return Action::None;
                 };
 
-                let content = self.input.text();
-                let content = content.trim();
-
-                if content.is_empty() {
+                if self.is_read_only {
                     return Action::None;
                 }
 
-                self.input = text_editor::Content::new();
-                self.history.push(Item::User {
-                    content: content.to_owned(),
-                    markdown: Markdown::parse(content),
-                });
-
-                let (send, handle) = Task::sip(
-                    chat::complete(assistant, &self.history.to_data(), self.strategy),
-                    Message::Chatting,
-                    Message::Chatted,
-                )
-                .abortable();
-
-                *sending = Some(handle.abort_on_drop());
+                self.lock = match self.lock {
+                    Some(_) => None,
+                    None => Some(chat::Lock::new(
+                        assistant,
+                        &self.strategy,
+                        self.persona.as_ref(),
+                    )),
+                };
 
-                Action::Run(Task::batch([send, snap_chat_to_end()]))
+                self.save()
             }
-            Message::Regenerate(index) => {
-                let State::Running { assistant, sending } = &mut self.state else {
+            Message::ToggleReadOnly => {
+                let Some(id) = self.id else {
                     return Action::None;
                 };
 
-                self.history.truncate(index);
+                if !self.can_send() {
+                    return Action::None;
+                }
 
-                let (send, handle) = Task::sip(
-                    chat::complete(assistant, &self.history.to_data(), self.strategy),
-                    Message::Chatting,
-                    Message::Chatted,
-                )
-                .abortable();
+                Action::Run(Task::perform(
+                    Chat::set_read_only(id, !self.is_read_only),
+                    Message::ReadOnlyToggled,
+                ))
+            }
+            Message::ReadOnlyToggled(Ok(chat)) => {
+                if self.id == Some(chat.id) {
+                    self.is_read_only = chat.is_read_only;
+                    self.new_checkpoint = None;
+                }
 
-                *sending = Some(handle.abort_on_drop());
+                Action::None
+            }
+            Message::ToggleSummary => {
+                self.show_summary = !self.show_summary;
 
-                Action::Run(Task::batch([send, snap_chat_to_end()]))
+                Action::None
             }
-            Message::TitleChanging(title) => {
-                self.title = Some(title);
+            Message::ToggleExpanded(index) => {
+                if !self.expanded.remove(&index) {
+                    let _ = self.expanded.insert(index);
+                }
+
                 Action::None
             }
-            Message::TitleChanged(Ok(title)) => {
-                self.title = Some(title);
-                self.save()
+            Message::ToggleDiff(index) => {
+                if !self.diffing.remove(&index) {
+                    let _ = self.diffing.insert(index);
+                }
+
+                Action::None
             }
-            Message::Chatting(event) if !self.can_send() => match event {
-                chat::Event::ReplyAdded => {
-                    self.history.push(Item::Reply(Reply::default()));
+            Message::Menu(event) => {
+                self.menu.update(event);
 
-                    Action::Run(snap_chat_to_end())
+                Action::None
+            }
+            Message::Edit(index) => {
+                if !self.can_change() {
+                    return Action::None;
                 }
-                chat::Event::ReplyChanged(new_reply) => {
-                    if let Some(Item::Reply(reply)) = self.history.last_mut() {
-                        reply.update(new_reply);
-                    }
 
-                    Action::None
-                }
-                chat::Event::PlanAdded => {
-                    self.history.push(Item::Plan(Plan::default()));
+                let Some(Item::User { content, .. }) = self.history.items().nth(index) else {
+                    return Action::None;
+                };
 
-                    Action::None
-                }
-                chat::Event::PlanChanged(event) => {
-                    if let Some(Item::Plan(plan)) = self.history.last_mut() {
-                        plan.apply(event);
-                    }
+                // Everything after the edited prompt is discarded, like when regenerating
+                self.input = text_editor::Content::with_text(content);
+                self.truncate(index);
 
-                    Action::None
+                self.save()
+            }
+            Message::RemoveItem(index) => {
+                if !self.can_change() {
+                    return Action::None;
                 }
-            },
-            Message::Chatting(_outdated_event) => Action::None,
-            Message::Chatted(Ok(())) => {
-                if let State::Running {
-                    sending, assistant, ..
-                } = &mut self.state
-                {
-                    *sending = None;
 
-                    let messages: Vec<_> = self.history.to_data();
+                self.history.remove(index);
 
-                    if self.title.is_none() || messages.len() == 2 || messages.len() == 6 {
-                        Action::Run(Task::sip(
-                            chat::title(assistant, &messages),
-                            Message::TitleChanging,
-                            Message::TitleChanged,
-                        ))
-                    } else {
-                        self.save()
-                    }
-                } else {
-                    Action::None
-                }
-            }
-            Message::Chatted(Err(error)) => {
-                self.error = Some(dbg!(error));
+                let shift = |i: usize| match i.cmp(&index) {
+                    std::cmp::Ordering::Less => Some(i),
+                    std::cmp::Ordering::Equal => None,
+                    std::cmp::Ordering::Greater => Some(i - 1),
+                };
 
-                if let State::Running { sending, .. } = &mut self.state {
-                    *sending = None;
+                self.expanded = self.expanded.drain().filter_map(shift).collect();
+                self.diffing = self.diffing.drain().filter_map(shift).collect();
+                self.revisions = self
+                    .revisions
+                    .drain()
+                    .filter_map(|(i, revision)| Some((shift(i)?, revision)))
+                    .collect();
+                self.tables = self
+                    .tables
+                    .drain()
+                    .filter_map(|(i, table)| Some((shift(i)?, table)))
+                    .collect();
+                self.context_start = self
+                    .context_start
+                    .map(|start| if index < start { start - 1 } else { start })
+                    .filter(|start| *start > 0);
+
+                for checkpoint in &mut self.checkpoints {
+                    if index < checkpoint.length {
+                        checkpoint.length -= 1;
+                    }
                 }
 
-                Action::None
+                self.save()
             }
-            Message::Copy(content) => Action::Run(clipboard::write(content)),
-            Message::ToggleReasoning(index, show) => {
-                if let Some(Item::Reply(reply)) = self.history.get_mut(index) {
-                    reply.toggle_reasoning(show);
+            Message::NewCheckpoint => {
+                if self.new_checkpoint.take().is_some() {
+                    return Action::None;
                 }
 
-                Action::None
-            }
-            Message::Created(Ok(chat)) | Message::Saved(Ok(chat)) => {
-                self.id = Some(chat.id);
+                self.new_checkpoint = Some(String::new());
 
-                Action::Run(Task::perform(Chat::list(), Message::ChatsListed))
+                Action::Run(text_input::focus(CHECKPOINT_NAME))
             }
-            Message::Open(chat) => {
-                Action::Run(Task::perform(Chat::fetch(chat), Message::ChatFetched))
+            Message::CheckpointNameChanged(name) => {
+                self.new_checkpoint = Some(name);
+
+                Action::None
             }
-            Message::ChatFetched(Ok(chat)) | Message::LastChatFetched(Ok(chat)) => {
-                match &mut self.state {
-                    State::Booting { file, .. } if file == &chat.file => {
-                        self.id = Some(chat.id);
-                        self.title = chat.title;
-                        self.history = History::restore(chat.history);
-                        self.input = text_editor::Content::new();
+            Message::AddCheckpoint => {
+                if !self.can_change() {
+                    return Action::None;
+                }
 
-                        Action::None
-                    }
-                    State::Running { assistant, sending } if &assistant.file == &chat.file => {
-                        self.id = Some(chat.id);
-                        self.title = chat.title;
-                        self.history = History::restore(chat.history);
-                        self.input = text_editor::Content::new();
-                        self.error = None;
+                let Some(name) = self.new_checkpoint.take() else {
+                    return Action::None;
+                };
 
-                        *sending = None;
+                let name = name.trim();
+                let length = self.history.items().count();
 
-                        Action::None
-                    }
-                    _ => {
-                        let (mut conversation, task) = Self::open(library, chat, self.backend);
-                        conversation.input_height = self.input_height;
+                let checkpoint = chat::Checkpoint {
+                    name: if name.is_empty() {
+                        format!("Checkpoint {}", self.checkpoints.len() + 1)
+                    } else {
+                        name.to_owned()
+                    },
+                    length,
+                };
 
-                        *self = conversation;
+                // A newer checkpoint at the same point replaces the older one
+                self.checkpoints.retain(|other| other.length != length);
+                self.checkpoints.push(checkpoint);
+                self.checkpoints.sort_by_key(|checkpoint| checkpoint.length);
 
-                        Action::Run(task)
-                    }
+                self.save()
+            }
+            Message::RollBack(index) => {
+                if !self.can_change() {
+                    return Action::None;
                 }
+
+                let Some(checkpoint) = self.checkpoints.get(index) else {
+                    return Action::None;
+                };
+
+                self.truncate(checkpoint.length);
+
+                self.save()
             }
-            Message::New | Message::LastChatFetched(Err(_)) => {
-                self.id = None;
-                self.title = None;
-                self.history = History::new();
-                self.input = text_editor::Content::new();
-                self.error = None;
+            Message::Branch(index) => {
+                let State::Running { assistant, .. } = &self.state else {
+                    return Action::None;
+                };
 
-                if let State::Running { sending, .. } = &mut self.state {
-                    *sending = None;
-                }
+                let Some(checkpoint) = self.checkpoints.get(index) else {
+                    return Action::None;
+                };
 
-                Action::Run(widget::focus_next())
+                let mut history = self.history.to_data();
+                history.truncate(checkpoint.length);
+
+                let title = format!(
+                    "{} ({})",
+                    self.title.as_deref().unwrap_or("Untitled"),
+                    checkpoint.name
+                );
+
+                Action::Run(Task::perform(
+                    Chat::create(
+                        assistant.file.clone(),
+                        Some(title),
+                        history,
+                        self.persona.clone(),
+                        self.lock.clone(),
+                        self.todos.clone(),
+                        self.checkpoints[..=index].to_vec(),
+                    ),
+                    Message::Branched,
+                ))
             }
-            Message::Delete => {
-                if let Some(id) = self.id {
-                    Action::Run(Task::future(Chat::delete(id)).and_then(|_| {
-                        Task::batch([
-                            Task::perform(Chat::fetch_last_opened(), Message::LastChatFetched),
-                            Task::perform(Chat::list(), Message::ChatsListed),
-                        ])
-                    }))
-                } else {
-                    Action::None
+            Message::Branched(Ok(chat)) => Action::Run(Task::batch([
+                Task::perform(Chat::list(), Message::ChatsListed),
+                Task::done(Message::ChatFetched(Ok(chat))),
+            ])),
+            Message::RemoveCheckpoint(index) => {
+                if self.is_read_only || index >= self.checkpoints.len() {
+                    return Action::None;
                 }
+
+                let _ = self.checkpoints.remove(index);
+
+                self.save()
             }
-            Message::Plan(index, message) => {
-                let Some(Item::Plan(plan)) = self.history.items.get_mut(index) else {
+            Message::Extract(index) => {
+                let State::Running { assistant, .. } = &self.state else {
                     return Action::None;
                 };
 
-                Action::Run(plan.update(message).map(Message::Plan.with(index)))
+                let Some(item) = self.history.items().nth(index) else {
+                    return Action::None;
+                };
+
+                let _ = self.tables.insert(index, None);
+
+                Action::Run(Task::perform(
+                    core::table::extract(assistant.clone(), item.to_text()),
+                    Message::Extracted.with(index),
+                ))
             }
-            Message::Markdown(interaction) => Action::Run(interaction.perform()),
-            Message::Booted(Err(error))
-            | Message::Created(Err(error))
-            | Message::Saved(Err(error))
-            | Message::TitleChanged(Err(error))
-            | Message::ChatFetched(Err(error)) => {
-                self.error = Some(dbg!(error));
+            Message::Extracted(index, Ok(data)) => {
+                let _ = self.tables.insert(index, Some(Table::new(data)));
 
                 Action::None
             }
-        }
-    }
+            Message::Extracted(index, Err(error)) => {
+                let _ = self.tables.remove(&index);
+                self.error = Some(error);
 
-    pub fn save(&self) -> Action {
+                Action::None
+            }
+            Message::ExtractTodos => {
+                let State::Running { assistant, .. } = &self.state else {
+                    return Action::None;
+                };
+
+                let (task, handle) = Task::perform(
+                    chat::todos(assistant.clone(), self.history.to_data()),
+                    Message::TodosExtracted,
+                )
+                .abortable();
+
+                self.show_todos = true;
+                self.extracting_todos = Some(handle.abort_on_drop());
+
+                Action::Run(task)
+            }
+            Message::TodosExtracted(Ok(todos)) => {
+                self.extracting_todos = None;
+
+                // Extracting again keeps what was already checked off
+                self.todos = todos
+                    .into_iter()
+                    .map(|todo| Todo {
+                        is_done: self
+                            .todos
+                            .iter()
+                            .any(|done| done.is_done && done.task == todo.task),
+                        ..todo
+                    })
+                    .collect();
+
+                self.save()
+            }
+            Message::TodosExtracted(Err(error)) => {
+                self.extracting_todos = None;
+                self.error = Some(error);
+
+                Action::None
+            }
+            Message::ToggleTodo(index, is_done) => {
+                let Some(todo) = self.todos.get_mut(index) else {
+                    return Action::None;
+                };
+
+                todo.is_done = is_done;
+
+                self.save()
+            }
+            Message::CopyTodos => Action::Run(clipboard::write(Todo::to_markdown(&self.todos))),
+            Message::ExportTodos => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Export action items...")
+                    .set_file_name("todo.md")
+                    .add_filter("Markdown", &["md"])
+                    .save_file(),
+                Message::TodosExportPicked,
+            )),
+            Message::TodosExportPicked(file) => {
+                let Some(file) = file else {
+                    return Action::None;
+                };
+
+                Action::Run(Task::perform(
+                    Todo::save(self.todos.clone(), file.path().to_path_buf()),
+                    Message::Exported,
+                ))
+            }
+            Message::ClearTodos => {
+                self.todos.clear();
+                self.show_todos = false;
+                self.extracting_todos = None;
+
+                self.save()
+            }
+            Message::Table(index, interaction) => {
+                if let Some(Some(table)) = self.tables.get_mut(&index) {
+                    table.update(interaction);
+                }
+
+                Action::None
+            }
+            Message::CloseTable(index) => {
+                let _ = self.tables.remove(&index);
+
+                Action::None
+            }
+            Message::SaveTable(table) => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Export table...")
+                    .set_file_name("table.csv")
+                    .add_filter("CSV", &["csv"])
+                    .add_filter("JSON", &["json"])
+                    .save_file(),
+                Message::TableSavePicked.with(table),
+            )),
+            Message::TableSavePicked(table, file) => {
+                let Some(file) = file else {
+                    return Action::None;
+                };
+
+                Action::Run(Task::perform(
+                    table.save(file.path().to_path_buf()),
+                    Message::Exported,
+                ))
+            }
+            Message::SaveDiagram(diagram) => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Export diagram...")
+                    .set_file_name("diagram.svg")
+                    .add_filter("SVG", &["svg"])
+                    .save_file(),
+                Message::DiagramSavePicked.with(diagram),
+            )),
+            Message::DiagramSavePicked(diagram, file) => {
+                let Some(file) = file else {
+                    return Action::None;
+                };
+
+                Action::Run(Task::perform(
+                    diagram.save(file.path().to_path_buf()),
+                    Message::Exported,
+                ))
+            }
+            Message::MarkdownTable(index, table, interaction) => {
+                match self.history.items.get_mut(index) {
+                    Some(Item::User { markdown, .. }) => {
+                        markdown.update_table(table, interaction);
+                    }
+                    Some(Item::Reply(reply)) => {
+                        reply.update_table(table, interaction);
+                    }
+                    _ => {}
+                }
+
+                Action::None
+            }
+            Message::JumpToBottom => {
+                self.scroll.is_at_bottom = true;
+                self.scroll.has_unread = false;
+
+                Action::Run(snap_chat_to_end())
+            }
+            Message::PasteImage => Action::Run(Task::perform(read_image(), Message::ImagePasted)),
+            Message::ImagePasted(Ok(Some(attachment))) => {
+                let handle = image::Handle::from_path(attachment.path());
+                self.attachments.push((attachment.clone(), handle));
+
+                if self.file().supports_vision() {
+                    return Action::None;
+                }
+
+                let id = attachment.id;
+                let _ = self.recognizing.insert(id);
+
+                Action::Run(Task::perform(
+                    ocr::recognize(settings.ocr.clone(), attachment),
+                    move |result| Message::ImageRecognized(id, result),
+                ))
+            }
+            Message::ImageRecognized(id, result) => {
+                let _ = self.recognizing.remove(&id);
+
+                match result {
+                    Ok(recognized) => {
+                        if let Some((attachment, _handle)) = self
+                            .attachments
+                            .iter_mut()
+                            .find(|(attachment, _handle)| attachment.id == id)
+                        {
+                            *attachment = recognized;
+                        }
+                    }
+                    Err(error) => {
+                        self.error = Some(error);
+                    }
+                }
+
+                Action::None
+            }
+            Message::ImagePasted(Ok(None)) => Action::None,
+            Message::RemoveAttachment(index) => {
+                if index < self.attachments.len() {
+                    let _ = self.attachments.remove(index);
+                }
+
+                Action::None
+            }
+            Message::PromptsFetched(Ok(prompts)) => {
+                self.prompts = prompts;
+
+                Action::None
+            }
+            Message::PromptsSaved(Ok(())) => Action::None,
+            Message::QuickActionsListed(Ok(quick_actions)) => {
+                self.quick_actions = quick_actions;
+
+                Action::None
+            }
+            Message::QuickAction(index, action) => {
+                let (Some(item), Some(action)) = (
+                    self.history.items().nth(index),
+                    self.quick_actions.get(action),
+                ) else {
+                    return Action::None;
+                };
+
+                if !self.can_change() {
+                    return Action::None;
+                }
+
+                // Quote the message unless it is the one being replied to
+                let is_last = index + 1 == self.history.items().count();
+                let reply = item.to_text();
+                let prompt = action.prompt((!is_last).then_some(reply.as_str()));
+
+                self.input = text_editor::Content::with_text(&prompt);
+                self.update(library, settings, Message::Submit)
+            }
+            Message::RecallOlder => {
+                let prompts = self.recallable();
+                let next = self.recall.map_or(0, |recall| recall + 1);
+
+                if let Some(prompt) = prompts.get(next) {
+                    self.input = text_editor::Content::with_text(prompt);
+                    self.recall = Some(next);
+                }
+
+                Action::None
+            }
+            Message::RecallNewer => {
+                let Some(recall) = self.recall else {
+                    return Action::None;
+                };
+
+                if recall == 0 {
+                    self.input = text_editor::Content::new();
+                    self.recall = None;
+                } else if let Some(prompt) = self.recallable().get(recall - 1) {
+                    self.input = text_editor::Content::with_text(prompt);
+                    self.recall = Some(recall - 1);
+                }
+
+                Action::None
+            }
+            Message::TogglePromptSearch => {
+                if self.prompt_search.take().is_some() {
+                    return Action::Run(widget::focus_next());
+                }
+
+                self.prompt_search = Some(String::new());
+
+                Action::Run(text_input::focus(PROMPT_SEARCH))
+            }
+            Message::PromptSearchChanged(query) => {
+                self.prompt_search = Some(query);
+
+                Action::None
+            }
+            Message::PromptPicked(prompt) => {
+                self.input = text_editor::Content::with_text(&prompt);
+                self.prompt_search = None;
+                self.recall = None;
+
+                Action::Run(widget::focus_next())
+            }
+            Message::Resized(bounds) => {
+                self.total_width = bounds.width;
+
+                Action::None
+            }
+            Message::HeaderShown(bounds) => {
+                self.header_height = bounds.height;
+
+                Action::Run(Task::batch([widget::focus_next(), snap_chat_to_end()]))
+            }
+            Message::HeaderResized(bounds) => {
+                self.header_height = bounds.height;
+
+                Action::None
+            }
+            Message::ChatResized(bounds) => {
+                self.chat_width = bounds.width;
+
+                Action::None
+            }
+            Message::InputResized(bounds) => {
+                self.input_height = bounds.height;
+
+                Action::None
+            }
+            Message::ToggleCodebase => {
+                if self.strategy.codebase.take().is_some() || self.is_indexing {
+                    return Action::None;
+                }
+
+                Action::Run(Task::perform(
+                    rfd::AsyncFileDialog::new()
+                        .set_title("Choose a codebase to ask about...")
+                        .pick_folder(),
+                    Message::CodebasePicked,
+                ))
+            }
+            Message::CodebasePicked(Some(folder)) => {
+                self.is_indexing = true;
+
+                Action::Run(Task::perform(
+                    codebase::Index::build(folder.path().to_path_buf()),
+                    |result| Message::CodebaseIndexed(result.map(Arc::new)),
+                ))
+            }
+            Message::CodebasePicked(None) => Action::None,
+            Message::CodebaseIndexed(Ok(index)) => {
+                self.is_indexing = false;
+                self.strategy.codebase = Some(index);
+
+                Action::None
+            }
+            Message::CodebaseIndexed(Err(error)) => {
+                self.is_indexing = false;
+                self.error = Some(dbg!(error));
+
+                Action::None
+            }
+            Message::SelectDatabase(database) => {
+                self.strategy.database = Some(database);
+
+                Action::None
+            }
+            Message::DetachDatabase => {
+                self.strategy.database = None;
+
+                Action::None
+            }
+            Message::Query(index, sql) => {
+                let Some(database) = self.strategy.database.clone() else {
+                    return Action::None;
+                };
+
+                let _ = self.tables.insert(index, None);
+
+                Action::Run(Task::perform(
+                    database.query(sql),
+                    Message::Extracted.with(index),
+                ))
+            }
+            Message::ToggleSearch => {
+                self.strategy.search = !self.strategy.search;
+
+                Action::None
+            }
+            Message::ToggleFetch => {
+                self.strategy.fetch = !self.strategy.fetch;
+
+                Action::None
+            }
+            Message::ToggleAgent => {
+                self.strategy.agent = match self.strategy.agent {
+                    Some(_) => None,
+                    None => Some(settings.agent_steps),
+                };
+
+                Action::None
+            }
+            Message::StopAgent => {
+                let State::Running { sending, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                // Dropping the task aborts the loop, keeping the steps taken so far
+                *sending = None;
+
+                if let Some(generation) = self.generation.take() {
+                    self.approvals
+                        .retain(|(pending, _request)| *pending != generation);
+                }
+
+                self.save()
+            }
+            Message::SelectPreset(_) if self.lock.is_some() => Action::None,
+            Message::SelectPreset(preset) => {
+                if self.preset.as_ref() == Some(&preset.name) {
+                    self.preset = None;
+                    self.strategy.parameters = None;
+                } else {
+                    self.preset = Some(preset.name);
+                    self.strategy.parameters = Some(preset.parameters);
+                }
+
+                Action::None
+            }
+            Message::Submit => {
+                let State::Running { sending, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                let content = self.input.text();
+                let content = content.trim();
+
+                if content.is_empty() || !self.recognizing.is_empty() || self.is_read_only {
+                    return Action::None;
+                }
+
+                self.input = text_editor::Content::new();
+                self.scroll = Scroll::default();
+                self.recall = None;
+                self.preview = self.preview.as_ref().map(|_| Markdown::default());
+                self.prompts.push(content);
+
+                for (attachment, handle) in self.attachments.drain(..) {
+                    self.history.push(Item::Attachment(attachment, handle));
+                }
+
+                self.history.push(Item::User {
+                    content: content.to_owned(),
+                    markdown: Markdown::parse(content),
+                });
+
+                if let Some((plugin, request)) = plugin::command(&self.plugins, content) {
+                    let plugin = plugin.clone();
+
+                    let (run, handle) = Task::perform(
+                        async move { plugin.call(request).await },
+                        Message::CommandRan,
+                    )
+                    .abortable();
+
+                    *sending = Some(handle.abort_on_drop());
+                    self.generation = None;
+
+                    return Action::Run(Task::batch([
+                        run,
+                        snap_chat_to_end(),
+                        Task::perform(self.prompts.clone().save(), Message::PromptsSaved),
+                    ]));
+                }
+
+                if let Some(painting) = &self.painting {
+                    let mut request = diffusion::Request::new(content);
+                    request.size = painting.size;
+                    request.steps = painting.steps.trim().parse().unwrap_or(request.steps);
+
+                    let (paint, handle) = Task::perform(
+                        diffusion::generate(settings.diffusion.clone(), request),
+                        Message::Painted,
+                    )
+                    .abortable();
+
+                    *sending = Some(handle.abort_on_drop());
+                    self.generation = None;
+
+                    return Action::Run(Task::batch([
+                        paint,
+                        snap_chat_to_end(),
+                        Task::perform(self.prompts.clone().save(), Message::PromptsSaved),
+                    ]));
+                }
+
+                let send = self.generate();
+
+                Action::Run(Task::batch([
+                    send,
+                    snap_chat_to_end(),
+                    Task::perform(self.prompts.clone().save(), Message::PromptsSaved),
+                ]))
+            }
+            Message::Regenerate(index) => {
+                if !matches!(self.state, State::Running { .. }) || self.is_read_only {
+                    return Action::None;
+                }
+
+                self.truncate(index);
+
+                Action::Run(Task::batch([self.generate(), snap_chat_to_end()]))
+            }
+            Message::Retry => {
+                self.error = None;
+
+                let count = self.history.items().count();
+
+                // Discard whatever was streamed before the model stalled
+                let index = match self.history.items().last() {
+                    Some(Item::Reply(_) | Item::Plan(_)) => count - 1,
+                    _ => count,
+                };
+
+                self.update(library, settings, Message::Regenerate(index))
+            }
+            Message::TitleChanging(title) => {
+                self.title = Some(title);
+                Action::None
+            }
+            Message::TitleChanged(Ok(title)) => {
+                self.title = Some(title);
+                self.save()
+            }
+            Message::Chatting(
+                generation,
+                chat::Event::PlanChanged(core::plan::Event::ApprovalRequested(request)),
+            ) => {
+                self.approvals.push((generation, request));
+
+                Action::None
+            }
+            Message::RespondToApproval(id, is_approved) => {
+                approval::respond(id, is_approved);
+                self.approvals
+                    .retain(|(_generation, request)| request.id != id);
+
+                Action::None
+            }
+            Message::Chatting(generation, event)
+                if self.generation == Some(generation) && !self.can_send() =>
+            {
+                match event {
+                    chat::Event::ContextFitted(first) => {
+                        self.context_start = (first > 0).then_some(first);
+
+                        Action::None
+                    }
+                    event => {
+                        self.history.apply(event);
+
+                        self.follow()
+                    }
+                }
+            }
+            Message::Chatting(generation, event) => {
+                if let Some(background) = self.background.get_mut(&generation) {
+                    match event {
+                        chat::Event::ContextFitted(first) => {
+                            background.context_start = (first > 0).then_some(first);
+                        }
+                        event => background.history.apply(event),
+                    }
+                }
+
+                Action::None
+            }
+            Message::Chatted(generation, result) if self.generation != Some(generation) => {
+                self.approvals
+                    .retain(|(pending, _request)| *pending != generation);
+
+                let Some(background) = self.background.remove(&generation) else {
+                    return Action::None;
+                };
+
+                if let Err(error) = result {
+                    warn!("background reply failed: {error}");
+                }
+
+                // Whatever was generated is kept, even if the reply failed midway
+                Action::Run(Task::perform(background.save(), Message::Finished))
+            }
+            Message::Finished(Ok(chat)) => {
+                let _ = self.unread.insert(chat.id);
+
+                Action::Run(Task::perform(Chat::list(), Message::ChatsListed))
+            }
+            Message::Chatted(generation, Ok(())) => {
+                self.generation = None;
+                self.approvals
+                    .retain(|(pending, _request)| *pending != generation);
+                self.revise();
+
+                let reply = self
+                    .history
+                    .items()
+                    .next_back()
+                    .map(Item::to_text)
+                    .unwrap_or_default();
+
+                let payload = hook::message(self.id, &reply);
+
+                let hooks = if hook::injects(hook::Event::MessageReceived) {
+                    Task::perform(
+                        hook::collect(hook::Event::MessageReceived, payload),
+                        Message::HooksRan,
+                    )
+                } else {
+                    hook::fire(hook::Event::MessageReceived, payload);
+
+                    Task::none()
+                };
+
+                if let State::Running {
+                    sending, assistant, ..
+                } = &mut self.state
+                {
+                    *sending = None;
+
+                    let messages: Vec<_> = self.history.to_data();
+
+                    if self.title.is_none() || messages.len() == 2 || messages.len() == 6 {
+                        Action::Run(Task::batch([
+                            Task::sip(
+                                chat::title(assistant, &messages),
+                                Message::TitleChanging,
+                                Message::TitleChanged,
+                            ),
+                            hooks,
+                        ]))
+                    } else {
+                        match self.save() {
+                            Action::Run(save) => Action::Run(Task::batch([save, hooks])),
+                            Action::None => Action::Run(hooks),
+                        }
+                    }
+                } else {
+                    Action::Run(hooks)
+                }
+            }
+            Message::HooksRan(Ok(outputs)) => {
+                if outputs.is_empty() {
+                    return Action::None;
+                }
+
+                for output in outputs {
+                    self.history
+                        .push(Item::Reply(Reply::from_data(assistant::Reply {
+                            reasoning: None,
+                            content: output,
+                            last_token: None,
+                            endpoint: None,
+                            provenance: None,
+                            usage: None,
+                        })));
+                }
+
+                self.save()
+            }
+            Message::Chatted(generation, Err(error)) => {
+                self.generation = None;
+                self.approvals
+                    .retain(|(pending, _request)| *pending != generation);
+
+                if !matches!(error, Error::Cancelled(..)) {
+                    self.error = Some(dbg!(error));
+                }
+
+                if let State::Running { sending, .. } = &mut self.state {
+                    *sending = None;
+                }
+
+                Action::None
+            }
+            Message::Copy(content) => Action::Run(clipboard::write(content)),
+            Message::ToggleReasoning(index, show) => {
+                if let Some(Item::Reply(reply)) = self.history.get_mut(index) {
+                    reply.toggle_reasoning(show);
+                }
+
+                Action::None
+            }
+            Message::Created(Ok(chat)) | Message::Saved(Ok(chat)) => {
+                self.id = Some(chat.id);
+
+                Action::Run(Task::perform(Chat::list(), Message::ChatsListed))
+            }
+            Message::Open(chat) => {
+                let _ = self.unread.remove(&chat);
+
+                let resumable = self.background.iter().find_map(|(generation, background)| {
+                    (background.id == Some(chat) && &background.file == self.file())
+                        .then_some(*generation)
+                });
+
+                if let Some(generation) = resumable {
+                    self.resume(generation);
+
+                    return Action::Run(snap_chat_to_end());
+                }
+
+                Action::Run(Task::perform(Chat::fetch(chat), Message::ChatFetched))
+            }
+            Message::ChatFetched(Ok(chat)) | Message::LastChatFetched(Ok(chat)) => {
+                self.detach();
+
+                match &mut self.state {
+                    State::Booting { file, .. }
+                        if file == &chat.file && self.read_only.is_none() =>
+                    {
+                        self.id = Some(chat.id);
+                        self.title = chat.title;
+                        self.history = History::restore(chat.history);
+                        self.persona = chat.persona;
+                        self.lock = chat.lock;
+                        self.show_todos = !chat.todos.is_empty();
+                        self.todos = chat.todos;
+                        self.extracting_todos = None;
+                        self.checkpoints = chat.checkpoints;
+                        self.new_checkpoint = None;
+                        self.is_read_only = chat.is_read_only;
+                        self.input = text_editor::Content::new();
+                        self.scroll = Scroll::default();
+                        self.expanded.clear();
+                        self.diffing.clear();
+                        self.tables.clear();
+                        self.context_start = None;
+                        self.revise();
+
+                        Action::None
+                    }
+                    State::Running { assistant, sending } if &assistant.file == &chat.file => {
+                        self.id = Some(chat.id);
+                        self.title = chat.title;
+                        self.history = History::restore(chat.history);
+                        self.persona = chat.persona;
+                        self.lock = chat.lock;
+                        self.show_todos = !chat.todos.is_empty();
+                        self.todos = chat.todos;
+                        self.extracting_todos = None;
+                        self.checkpoints = chat.checkpoints;
+                        self.new_checkpoint = None;
+                        self.is_read_only = chat.is_read_only;
+                        self.input = text_editor::Content::new();
+                        self.scroll = Scroll::default();
+                        self.expanded.clear();
+                        self.diffing.clear();
+                        self.tables.clear();
+                        self.context_start = None;
+                        self.error = None;
+
+                        *sending = None;
+
+                        self.revise();
+
+                        Action::None
+                    }
+                    _ => {
+                        let (mut conversation, task) = Self::open(library, chat, self.backend);
+                        conversation.input_height = self.input_height;
+                        conversation.next_generation = self.next_generation;
+                        conversation.background = mem::take(&mut self.background);
+                        conversation.unread = mem::take(&mut self.unread);
+
+                        *self = conversation;
+
+                        Action::Run(task)
+                    }
+                }
+            }
+            Message::New | Message::LastChatFetched(Err(_)) => {
+                if self.read_only.is_some() {
+                    let (mut conversation, task) =
+                        Self::new(library, self.file().clone(), self.backend);
+                    conversation.input_height = self.input_height;
+                    conversation.next_generation = self.next_generation;
+                    conversation.background = mem::take(&mut self.background);
+                    conversation.unread = mem::take(&mut self.unread);
+
+                    *self = conversation;
+
+                    return Action::Run(Task::batch([task, widget::focus_next()]));
+                }
+
+                self.reset();
+
+                Action::Run(widget::focus_next())
+            }
+            Message::Delete => {
+                if let Some(id) = self.id {
+                    // A deleted chat has nothing to finish in the background
+                    if let State::Running { sending, .. } = &mut self.state {
+                        *sending = None;
+                    }
+
+                    Action::Run(Task::future(Chat::delete(id)).and_then(|_| {
+                        Task::batch([
+                            Task::perform(Chat::fetch_last_opened(), Message::LastChatFetched),
+                            Task::perform(Chat::list(), Message::ChatsListed),
+                        ])
+                    }))
+                } else {
+                    Action::None
+                }
+            }
+            Message::Export => {
+                let file_name = format!(
+                    "{}.html",
+                    self.title
+                        .as_deref()
+                        .unwrap_or("chat")
+                        .replace(['/', '\\', ':'], "-")
+                );
+
+                Action::Run(Task::perform(
+                    rfd::AsyncFileDialog::new()
+                        .set_title("Export chat...")
+                        .set_file_name(file_name)
+                        .add_filter("HTML", &["html"])
+                        .add_filter("PDF", &["pdf"])
+                        .add_filter("Jupyter Notebook", &["ipynb"])
+                        .add_filter("Icebreaker Chat", &[export::json::EXTENSION])
+                        .save_file(),
+                    Message::ExportPicked,
+                ))
+            }
+            Message::ExportPicked(file) => {
+                let (Some(file), Some(chat)) = (file, self.chat()) else {
+                    return Action::None;
+                };
+
+                let path = file.path().to_path_buf();
+
+                if path.extension().is_some_and(|extension| extension == "pdf") {
+                    Action::Run(Task::perform(
+                        export::pdf::save(chat, path),
+                        Message::Exported,
+                    ))
+                } else if path
+                    .extension()
+                    .is_some_and(|extension| extension == "ipynb")
+                {
+                    Action::Run(Task::perform(
+                        export::notebook::save(chat, path),
+                        Message::Exported,
+                    ))
+                } else if path
+                    .extension()
+                    .is_some_and(|extension| extension == "json")
+                {
+                    // Only the full extension lets the system open the chat with the app
+                    let path = if path
+                        .to_string_lossy()
+                        .ends_with(&format!(".{}", export::json::EXTENSION))
+                    {
+                        path
+                    } else {
+                        path.with_extension(export::json::EXTENSION)
+                    };
+
+                    Action::Run(Task::perform(
+                        export::json::save(chat, path),
+                        Message::Exported,
+                    ))
+                } else {
+                    Action::Run(Task::perform(
+                        export::html::save(chat, path),
+                        Message::Exported,
+                    ))
+                }
+            }
+            Message::Exported(Ok(())) => Action::None,
+            Message::Keep => {
+                let Some(path) = self.read_only.clone() else {
+                    return Action::None;
+                };
+
+                Action::Run(Task::perform(export::json::import(path), Message::Kept))
+            }
+            Message::Kept(Ok(chat)) => {
+                let (mut conversation, task) = Self::open(library, chat, self.backend);
+                conversation.input_height = self.input_height;
+
+                *self = conversation;
+
+                Action::Run(task)
+            }
+            Message::Share => {
+                let (Some(endpoint), Some(chat)) = (settings.share_endpoint.clone(), self.chat())
+                else {
+                    return Action::None;
+                };
+
+                Action::Run(Task::perform(
+                    export::html::paste(chat, endpoint),
+                    Message::Shared,
+                ))
+            }
+            Message::Shared(Ok(url)) => {
+                browser::open(&url);
+
+                Action::Run(clipboard::write(url.to_string()))
+            }
+            Message::Markdown(markdown::Interaction::SaveTable(table))
+            | Message::Plan(_, plan::Message::Markdown(markdown::Interaction::SaveTable(table))) => {
+                self.update(library, settings, Message::SaveTable(table))
+            }
+            Message::Markdown(markdown::Interaction::SaveDiagram(diagram))
+            | Message::Plan(
+                _,
+                plan::Message::Markdown(markdown::Interaction::SaveDiagram(diagram)),
+            ) => self.update(library, settings, Message::SaveDiagram(diagram)),
+            Message::Plan(index, message) => {
+                let Some(Item::Plan(plan)) = self.history.items.get_mut(index) else {
+                    return Action::None;
+                };
+
+                Action::Run(plan.update(message).map(Message::Plan.with(index)))
+            }
+            Message::Markdown(markdown::Interaction::Run(language, code)) => {
+                if !settings.sandbox.enabled || !self.can_change() {
+                    return Action::None;
+                }
+
+                Action::Run(Task::perform(
+                    execution::run(settings.sandbox.clone(), language, code),
+                    Message::Executed,
+                ))
+            }
+            Message::Markdown(interaction) => Action::Run(interaction.perform()),
+            Message::Executed(Ok(output)) => {
+                self.history.push(Item::Execution(output));
+
+                self.save()
+            }
+            Message::TogglePainting => {
+                self.painting = match self.painting {
+                    Some(_) => None,
+                    None => Some(Painting::default()),
+                };
+
+                Action::None
+            }
+            Message::PaintingSizeChanged(size) => {
+                if let Some(painting) = &mut self.painting {
+                    painting.size = size;
+                }
+
+                Action::None
+            }
+            Message::PaintingStepsChanged(steps) => {
+                if let Some(painting) = &mut self.painting {
+                    if steps.chars().all(|c| c.is_ascii_digit()) {
+                        painting.steps = steps;
+                    }
+                }
+
+                Action::None
+            }
+            Message::TranscribeFile => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose an audio or video file to transcribe...")
+                    .add_filter(
+                        "Audio and video",
+                        &[
+                            "wav", "mp3", "m4a", "flac", "ogg", "opus", "webm", "mp4", "mkv", "mov",
+                        ],
+                    )
+                    .pick_file(),
+                Message::TranscriptionFilePicked,
+            )),
+            Message::TranscriptionFilePicked(Some(file)) => {
+                let (task, handle) = Task::sip(
+                    transcription::transcribe(settings.whisper.clone(), file.path().to_path_buf()),
+                    Message::Transcribing,
+                    Message::Transcribed,
+                )
+                .abortable();
+
+                self.transcription = Some(Transcription {
+                    file_name: file.file_name(),
+                    progress: transcription::Progress {
+                        stage: "Starting...",
+                        percent: 0,
+                    },
+                    _task: handle.abort_on_drop(),
+                });
+
+                Action::Run(task)
+            }
+            Message::TranscriptionFilePicked(None) => Action::None,
+            Message::Transcribing(progress) => {
+                if let Some(transcription) = &mut self.transcription {
+                    transcription.progress = progress;
+                }
+
+                Action::None
+            }
+            Message::Transcribed(Ok(transcript)) => {
+                let Some(transcription) = self.transcription.take() else {
+                    return Action::None;
+                };
+
+                self.reset();
+                self.input = text_editor::Content::with_text(&format!(
+                    "Summarize this transcript of {}:\n\n{transcript}",
+                    transcription.file_name
+                ));
+
+                Action::Run(widget::focus_next())
+            }
+            Message::Transcribed(Err(error)) => {
+                self.transcription = None;
+                self.error = Some(error);
+
+                Action::None
+            }
+            Message::CancelTranscription => {
+                self.transcription = None;
+
+                Action::None
+            }
+            Message::SummarizeFile => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose a document to summarize...")
+                    .add_filter(
+                        "Text",
+                        &["txt", "md", "markdown", "rst", "org", "csv", "log"],
+                    )
+                    .pick_file(),
+                Message::SummaryFilePicked,
+            )),
+            Message::SummaryFilePicked(Some(file)) => Action::Run(Task::future(async move {
+                let document = String::from_utf8_lossy(&file.read().await).into_owned();
+
+                Message::DocumentLoaded(file.file_name(), document)
+            })),
+            Message::SummaryFilePicked(None) => Action::None,
+            Message::DocumentLoaded(name, document) => {
+                let State::Running {
+                    assistant,
+                    sending: sending @ None,
+                } = &mut self.state
+                else {
+                    return Action::None;
+                };
+
+                let content = format!("Summarize \"{name}\"");
+
+                self.history.push(Item::User {
+                    markdown: Markdown::parse(&content),
+                    content,
+                });
+
+                let (summarize, handle) = Task::sip(
+                    summary::summarize(assistant, name, document),
+                    Message::Summarizing,
+                    Message::Summarized,
+                )
+                .abortable();
+
+                *sending = Some(handle.abort_on_drop());
+                self.generation = None;
+                self.summarizing = Some(summary::Progress::Mapped { done: 0, total: 1 });
+
+                Action::Run(Task::batch([summarize, snap_chat_to_end()]))
+            }
+            Message::Summarizing(progress) => {
+                self.summarizing = Some(progress);
+
+                Action::None
+            }
+            Message::Summarized(result) => {
+                self.summarizing = None;
+
+                if let State::Running { sending, .. } = &mut self.state {
+                    *sending = None;
+                }
+
+                match result {
+                    Ok(reply) => {
+                        self.history.push(Item::Reply(Reply::from_data(reply)));
+
+                        self.save()
+                    }
+                    Err(error) => {
+                        self.error = Some(error);
+
+                        Action::None
+                    }
+                }
+            }
+            Message::Painted(result) => {
+                if let State::Running { sending, .. } = &mut self.state {
+                    *sending = None;
+                }
+
+                match result {
+                    Ok(attachment) => {
+                        let handle = image::Handle::from_path(attachment.path());
+                        self.history.push(Item::Attachment(attachment, handle));
+
+                        self.save()
+                    }
+                    Err(error) => {
+                        self.error = Some(error);
+
+                        Action::None
+                    }
+                }
+            }
+            Message::PluginsListed(Ok(plugins)) => {
+                self.plugins = plugins;
+
+                Action::None
+            }
+            Message::CommandRan(result) => {
+                let State::Running { sending, .. } = &mut self.state else {
+                    return Action::None;
+                };
+
+                *sending = None;
+
+                match result {
+                    Ok(plugin::Response::Prompt(prompt)) => {
+                        // The model sees the prompt the command expanded to
+                        if let Some(Item::User { content, markdown }) = self.history.last_mut() {
+                            *markdown = Markdown::parse(&prompt);
+                            *content = prompt;
+                        }
+
+                        Action::Run(Task::batch([self.generate(), snap_chat_to_end()]))
+                    }
+                    Ok(
+                        plugin::Response::Reply(content)
+                        | plugin::Response::Text(content)
+                        | plugin::Response::Error(content),
+                    ) => {
+                        self.history
+                            .push(Item::Reply(Reply::from_data(assistant::Reply {
+                                reasoning: None,
+                                content,
+                                last_token: None,
+                                endpoint: None,
+                                provenance: None,
+                                usage: None,
+                            })));
+
+                        self.save()
+                    }
+                    Err(error) => {
+                        self.error = Some(error);
+
+                        Action::None
+                    }
+                }
+            }
+            Message::Booted(Err(error))
+            | Message::PluginsListed(Err(error))
+            | Message::HooksRan(Err(error))
+            | Message::Created(Err(error))
+            | Message::Saved(Err(error))
+            | Message::Exported(Err(error))
+            | Message::Kept(Err(error))
+            | Message::ReadOnlyToggled(Err(error))
+            | Message::Branched(Err(error))
+            | Message::PromptsFetched(Err(error))
+            | Message::PromptsSaved(Err(error))
+            | Message::QuickActionsListed(Err(error))
+            | Message::ImagePasted(Err(error))
+            | Message::Executed(Err(error))
+            | Message::Shared(Err(error))
+            | Message::TitleChanged(Err(error))
+            | Message::Finished(Err(error))
+            | Message::ChatFetched(Err(error)) => {
+                self.error = Some(dbg!(error));
+
+                Action::None
+            }
+        }
+    }
+
+    pub fn shutdown(&mut self) -> Task<Message> {
+        if let State::Running { sending, .. } = &mut self.state {
+            *sending = None;
+        }
+
+        let save = match self.save() {
+            Action::Run(task) => task,
+            Action::None => Task::none(),
+        };
+
+        let background = Task::batch(
+            self.background
+                .drain()
+                .map(|(_, background)| Task::perform(background.save(), Message::Finished)),
+        );
+
+        let stop = match self.assistant().cloned() {
+            Some(assistant) => Task::future(async move { assistant.shutdown().await }).discard(),
+            None => Task::none(),
+        };
+
+        Task::batch([save, background]).chain(stop)
+    }
+
+    pub fn save(&self) -> Action {
         let State::Running { assistant, sending } = &self.state else {
             return Action::None;
         };
 
-        if sending.is_some() {
-            return Action::None;
+        if sending.is_some() {
+            return Action::None;
+        }
+
+        let items = self.history.to_data();
+
+        if let Some(id) = &self.id {
+            Action::Run(Task::perform(
+                Chat {
+                    id: *id,
+                    file: assistant.file.clone(),
+                    title: self.title.clone(),
+                    history: items,
+                    persona: self.persona.clone(),
+                    lock: self.lock.clone(),
+                    todos: self.todos.clone(),
+                    checkpoints: self.checkpoints.clone(),
+                    is_read_only: self.is_read_only,
+                }
+                .save(),
+                Message::Saved,
+            ))
+        } else {
+            Action::Run(Task::perform(
+                Chat::create(
+                    assistant.file.clone(),
+                    self.title.clone(),
+                    items,
+                    self.persona.clone(),
+                    self.lock.clone(),
+                    self.todos.clone(),
+                    self.checkpoints.clone(),
+                ),
+                Message::Created,
+            ))
+        }
+    }
+
+    pub fn view(&self, theme: &Theme, settings: &Settings, is_zen: bool) -> Element<'_, Message> {
+        let column_width = if is_zen {
+            ZEN_COLUMN_WIDTH
+        } else {
+            COLUMN_WIDTH
+        };
+
+        let collapse_height = (settings.collapse_height > 0).then_some(settings.collapse_height);
+
+        let header: Element<'_, _> = {
+            let title: Element<'_, _> = match &self.title {
+                Some(title) => column![
+                    text(title).size(20).width(Fill).align_x(Center),
+                    text(self.model_name())
+                        .font(Font::MONOSPACE)
+                        .size(14)
+                        .style(text::secondary)
+                ]
+                .spacing(5)
+                .align_x(Center)
+                .into(),
+                None => text(self.model_name())
+                    .font(Font::MONOSPACE)
+                    .size(20)
+                    .align_x(Center)
+                    .into(),
+            };
+
+            let title: Element<'_, _> = match &self.persona {
+                Some(persona) => column![
+                    title,
+                    row![
+                        icon::user().size(12).style(text::secondary),
+                        text(&persona.name).size(12).style(text::secondary)
+                    ]
+                    .spacing(5)
+                    .align_y(Center)
+                ]
+                .spacing(5)
+                .align_x(Center)
+                .into(),
+                None => title,
+            };
+
+            let title: Element<'_, _> = if self.is_read_only {
+                column![title, text("Read-only").size(12).style(text::secondary)]
+                    .spacing(5)
+                    .align_x(Center)
+                    .into()
+            } else {
+                title
+            };
+
+            let actions: Element<'_, _> = if self.id.is_some() {
+                let share = settings.share_endpoint.is_some().then(|| {
+                    tip(
+                        button(icon::link())
+                            .padding(0)
+                            .on_press(Message::Share)
+                            .style(button::text),
+                        "Share Link",
+                        tip::Position::Bottom,
+                    )
+                });
+
+                row![
+                    tip(
+                        button(
+                            text(if self.lock.is_some() {
+                                "Unlock"
+                            } else {
+                                "Lock"
+                            })
+                            .size(12),
+                        )
+                        .padding(0)
+                        .on_press(Message::ToggleLock)
+                        .style(button::text),
+                        "Freeze model, parameters, and system prompt",
+                        tip::Position::Bottom,
+                    ),
+                    tip(
+                        button(
+                            text(if self.is_read_only {
+                                "Make Editable"
+                            } else {
+                                "Make Read-only"
+                            })
+                            .size(12),
+                        )
+                        .padding(0)
+                        .on_press_maybe(self.can_send().then_some(Message::ToggleReadOnly))
+                        .style(button::text),
+                        "Keep the transcript as a record, without edits or replies",
+                        tip::Position::Bottom,
+                    ),
+                    tip(
+                        button(if self.show_summary {
+                            icon::clock().style(text::primary)
+                        } else {
+                            icon::clock()
+                        })
+                        .padding(0)
+                        .on_press(Message::ToggleSummary)
+                        .style(button::text),
+                        "Summary",
+                        tip::Position::Bottom,
+                    ),
+                    tip(
+                        button(if self.new_checkpoint.is_some() {
+                            icon::star().style(text::primary)
+                        } else {
+                            icon::star()
+                        })
+                        .padding(0)
+                        .on_press_maybe(self.can_change().then_some(Message::NewCheckpoint))
+                        .style(button::text),
+                        "Add Checkpoint",
+                        tip::Position::Bottom,
+                    ),
+                    tip(
+                        button(if self.show_todos {
+                            icon::check().style(text::primary)
+                        } else {
+                            icon::check()
+                        })
+                        .padding(0)
+                        .on_press_maybe(
+                            (matches!(self.state, State::Running { .. })
+                                && self.extracting_todos.is_none())
+                            .then_some(Message::ExtractTodos),
+                        )
+                        .style(button::text),
+                        "Extract Action Items",
+                        tip::Position::Bottom,
+                    ),
+                    share,
+                    tip(
+                        button(icon::download())
+                            .padding(0)
+                            .on_press(Message::Export)
+                            .style(button::text),
+                        "Export as HTML",
+                        tip::Position::Bottom,
+                    ),
+                    tip(
+                        button(icon::trash().style(text::danger))
+                            .padding(0)
+                            .on_press(Message::Delete)
+                            .style(button::text),
+                        "Delete Chat",
+                        tip::Position::Left,
+                    )
+                ]
+                .spacing(10)
+                .into()
+            } else {
+                horizontal_space().into()
+            };
+
+            let title: Element<'_, _> = match &self.lock {
+                Some(lock) => column![title, locked(lock)]
+                    .spacing(5)
+                    .align_x(Center)
+                    .into(),
+                None => title,
+            };
+
+            let title: Element<'_, _> = if self.show_summary {
+                column![title, summary(&self.history)]
+                    .spacing(5)
+                    .align_x(Center)
+                    .into()
+            } else {
+                title
+            };
+
+            let t_bar = hover(center_x(title).padding([0, 40]), right_center(actions));
+
+            match &self.state {
+                _ if self.read_only.is_some() => t_bar,
+                State::Booting {
+                    logs,
+                    stage,
+                    progress,
+                    tick,
+                    ..
+                } => {
+                    let progress = {
+                        let stage = if self.error.is_none() {
+                            text!(
+                                "{stage} {spinner}",
+                                stage = stage,
+                                spinner = match tick % 4 {
+                                    0 => "|",
+                                    1 => "/",
+                                    2 => "—",
+                                    _ => "\\",
+                                }
+                            )
+                        } else {
+                            text(stage)
+                        }
+                        .font(Font::MONOSPACE)
+                        .size(10);
+
+                        let bar = progress_bar(0.0..=100.0, *progress as f32)
+                            .length(200)
+                            .girth(30);
+
+                        stack![
+                            if self.error.is_none() {
+                                bar
+                            } else {
+                                bar.style(progress_bar::danger)
+                            },
+                            center(stage.style(|theme: &Theme| text::Style {
+                                color: Some(theme.palette().background)
+                            }))
+                            .clip(true)
+                        ]
+                    };
+
+                    let logs = {
+                        let error = self
+                            .error
+                            .as_ref()
+                            .map(|error| value(error).font(Font::MONOSPACE).style(text::danger));
+
+                        scrollable(
+                            column(
+                                logs.iter()
+                                    .map(|log| text(log).size(12).font(Font::MONOSPACE).into()),
+                            )
+                            .push(error)
+                            .spacing(5)
+                            .padding(padding::right(20)),
+                        )
+                        .anchor_y(scrollable::Anchor::End)
+                        .width(400)
+                        .height(600)
+                    };
+
+                    let progress = tooltip(
+                        progress,
+                        container(logs).padding(10).style(container::dark),
+                        tooltip::Position::Bottom,
+                    );
+
+                    stack![t_bar, right_center(progress)].into()
+                }
+                State::Running { .. } => match &self.telemetry {
+                    Some(sample) => stack![t_bar, center_y(gauges(sample))].into(),
+                    None => t_bar,
+                },
+            }
+        };
+
+        let messages: Element<'_, _> = if let Some(Error::ServerCrashed(report, ..)) = &self.error {
+            crash(report)
+        } else if self.history.is_empty() {
+            center(
+                match &self.state {
+                    State::Running { .. } => column![
+                        text("Your assistant is ready."),
+                        text("Break the ice! ↓")
+                            .style(text::primary)
+                            .shaping(text::Shaping::Advanced)
+                    ],
+                    State::Booting { .. } => column![
+                        text("Your assistant is launching..."),
+                        text("You can begin typing while you wait! ↓")
+                            .style(text::success)
+                            .shaping(text::Shaping::Advanced),
+                    ],
+                }
+                .spacing(10)
+                .align_x(Center),
+            )
+            .into()
+        } else {
+            scrollable(column![
+                sensor(horizontal_space())
+                    .key(self.id)
+                    .on_resize(Message::ChatResized),
+                center_x(
+                    column(self.checklist().into_iter().chain(
+                        self.history.items().enumerate().map(|(i, item)| {
+                            let view = item.view(
+                                i,
+                                theme,
+                                collapse_height,
+                                self.expanded.contains(&i),
+                                markdown::Runnable {
+                                    code: settings.sandbox.enabled,
+                                    queries: self.strategy.database.is_some(),
+                                },
+                                self.revisions
+                                    .get(&i)
+                                    .map(|revision| (revision, self.diffing.contains(&i))),
+                                &self.quick_actions,
+                            );
+
+                            let table = self
+                                .tables
+                                .get(&i)
+                                .map(|table| view_table(i, table.as_ref()));
+
+                            let view =
+                                self.menu
+                                    .area(column![view, table].spacing(10), i, Message::Menu);
+
+                            let view = if self.context_start == Some(i) {
+                                column![context_cut(), view].spacing(20).into()
+                            } else {
+                                view
+                            };
+
+                            column(self.checkpoints_at(i)).push(view).spacing(20).into()
+                        })
+                    ))
+                    .extend(self.checkpoints_at(self.history.items().count()))
+                    .push(self.new_checkpoint())
+                    .push(self.is_waiting().then(typing))
+                    .padding(padding::all(20).top(0))
+                    .max_width(column_width),
+                )
+                .padding(padding::top(self.header_height).bottom(self.input_height))
+            ])
+            .id(CHAT)
+            .on_scroll(Message::Scrolled)
+            .spacing(10)
+            .height(Fill)
+            .into()
+        };
+
+        let pill = (!self.scroll.is_at_bottom && !self.history.is_empty()).then(|| {
+            let label = if self.scroll.has_unread {
+                "New messages"
+            } else {
+                "Jump to bottom"
+            };
+
+            bottom(center_x(
+                button(
+                    row![icon::arrow_down().size(12), text(label).size(12)]
+                        .spacing(5)
+                        .align_y(Center),
+                )
+                .on_press(Message::JumpToBottom)
+                .padding([5, 10])
+                .style(if self.scroll.has_unread {
+                    button::primary
+                } else {
+                    button::secondary
+                }),
+            ))
+            .padding(padding::bottom(self.input_height + 10.0))
+        });
+
+        let agent = self
+            .strategy
+            .agent
+            .filter(|_| self.generation.is_some())
+            .and_then(|budget| match self.history.items().next_back() {
+                Some(Item::Plan(plan)) => Some((plan.steps().len(), budget)),
+                _ => None,
+            })
+            .map(|(steps, budget)| {
+                bottom(center_x(
+                    container(
+                        row![
+                            text!("Agent step {} of {budget}", steps.max(1)).size(14),
+                            button(
+                                row![icon::cancel(), text("Stop")]
+                                    .spacing(10)
+                                    .align_y(Center),
+                            )
+                            .on_press(Message::StopAgent)
+                            .padding([10, 20])
+                            .style(button::danger),
+                        ]
+                        .spacing(20)
+                        .align_y(Center),
+                    )
+                    .padding([10, 20])
+                    .style(container::bordered_box),
+                ))
+                .padding(padding::bottom(self.input_height + 10.0))
+            });
+
+        let stalled =
+            (matches!(self.error, Some(Error::TimedOut(..))) && self.can_change()).then(|| {
+                bottom(center_x(
+                    container(
+                        row![
+                            self.error.as_ref().map(|error| value(error).size(12)),
+                            button(
+                                row![icon::refresh().size(12), text("Retry").size(12)]
+                                    .spacing(5)
+                                    .align_y(Center),
+                            )
+                            .on_press(Message::Retry)
+                            .padding([5, 10])
+                            .style(button::danger),
+                        ]
+                        .spacing(10)
+                        .align_y(Center),
+                    )
+                    .padding([5, 10])
+                    .style(container::bordered_box),
+                ))
+                .padding(padding::bottom(self.input_height + 10.0))
+            });
+
+        let input: Element<'_, _> = if let Some(path) = &self.read_only {
+            container(
+                row![
+                    icon::folder_open().style(text::secondary),
+                    text!("Reading {}", path.file_name().unwrap_or_default().display())
+                        .size(14)
+                        .style(text::secondary)
+                        .width(Fill),
+                    button(text("Keep in chats").size(14)).on_press(Message::Keep),
+                ]
+                .spacing(10)
+                .align_y(Center),
+            )
+            .padding(15)
+            .max_width(column_width)
+            .style(container::bordered_box)
+            .into()
+        } else if self.is_read_only {
+            container(
+                row![
+                    text("This chat is read-only")
+                        .size(14)
+                        .style(text::secondary)
+                        .width(Fill),
+                    button(text("Make editable").size(14)).on_press(Message::ToggleReadOnly),
+                ]
+                .spacing(10)
+                .align_y(Center),
+            )
+            .padding(15)
+            .max_width(column_width)
+            .style(container::bordered_box)
+            .into()
+        } else {
+            let is_recalling = self.recall.is_some();
+            let can_recall = is_recalling || self.input.is_empty();
+
+            let editor = text_editor(&self.input)
+                .placeholder(if self.painting.is_some() {
+                    "Describe the image to generate..."
+                } else {
+                    "Type your message here..."
+                })
+                .on_action(Message::InputChanged)
+                .padding(padding::all(15).bottom(50))
+                .min_height(16.0 * 1.3 * 2.0) // approx. 2 lines with 1.3 line height
+                .max_height(16.0 * 1.3 * 20.0) // approx. 20 lines
+                .key_binding(move |key_press| {
+                    let modifiers = key_press.modifiers;
+
+                    match key_press.key.as_ref() {
+                        keyboard::Key::Named(keyboard::key::Named::ArrowUp)
+                            if can_recall && modifiers.is_empty() =>
+                        {
+                            return Some(text_editor::Binding::Custom(Message::RecallOlder));
+                        }
+                        keyboard::Key::Named(keyboard::key::Named::ArrowDown)
+                            if is_recalling && modifiers.is_empty() =>
+                        {
+                            return Some(text_editor::Binding::Custom(Message::RecallNewer));
+                        }
+                        keyboard::Key::Character("v") if modifiers.command() => {
+                            return Some(text_editor::Binding::Sequence(vec![
+                                text_editor::Binding::Paste,
+                                text_editor::Binding::Custom(Message::PasteImage),
+                            ]));
+                        }
+                        keyboard::Key::Character("r") if modifiers.command() => {
+                            return Some(text_editor::Binding::Custom(Message::TogglePromptSearch));
+                        }
+                        _ => {}
+                    }
+
+                    match text_editor::Binding::from_key_press(key_press) {
+                        Some(text_editor::Binding::Enter) if !modifiers.shift() => {
+                            Some(text_editor::Binding::Custom(Message::Submit))
+                        }
+                        binding => binding,
+                    }
+                })
+                .style(|theme, status| {
+                    let style = text_editor::default(theme, status);
+
+                    text_editor::Style {
+                        border: style.border.rounded(10),
+                        ..style
+                    }
+                });
+
+            let strategy = {
+                let search = tip(
+                    toggle(icon::globe(), "Search", self.strategy.search)
+                        .on_press(Message::ToggleSearch),
+                    "Very Experimental!",
+                    tip::Position::Left,
+                );
+
+                let fetch = tip(
+                    toggle(icon::link(), "Fetch", self.strategy.fetch)
+                        .on_press(Message::ToggleFetch),
+                    "Read the links you give",
+                    tip::Position::Left,
+                );
+
+                let agent = tip(
+                    toggle(icon::cubes(), "Agent", self.strategy.agent.is_some())
+                        .on_press(Message::ToggleAgent),
+                    "Call tools in a loop until done",
+                    tip::Position::Left,
+                );
+
+                let preview = toggle(icon::chat(), "Preview", self.preview.is_some())
+                    .on_press(Message::TogglePreview);
+
+                let painting = toggle(icon::palette(), "Image", self.painting.is_some())
+                    .on_press(Message::TogglePainting);
+
+                let codebase: Element<'_, _> = match &self.strategy.codebase {
+                    Some(index) => tip(
+                        toggle(icon::folder_open(), "Codebase", true)
+                            .on_press(Message::ToggleCodebase),
+                        index.summary(),
+                        tip::Position::Left,
+                    ),
+                    None => toggle(
+                        icon::folder(),
+                        if self.is_indexing {
+                            "Indexing..."
+                        } else {
+                            "Codebase"
+                        },
+                        false,
+                    )
+                    .on_press_maybe((!self.is_indexing).then_some(Message::ToggleCodebase))
+                    .into(),
+                };
+
+                let database: Option<Element<'_, _>> = match &self.strategy.database {
+                    Some(database) => Some(tip(
+                        toggle(icon::server(), &database.name, true)
+                            .on_press(Message::DetachDatabase),
+                        "Queries run read-only once you approve them",
+                        tip::Position::Left,
+                    )),
+                    None if !settings.databases.is_empty() => Some(
+                        pick_list(
+                            settings.databases.clone(),
+                            None::<Database>,
+                            Message::SelectDatabase,
+                        )
+                        .placeholder("Database")
+                        .text_size(14)
+                        .into(),
+                    ),
+                    None => None,
+                };
+
+                bottom_right(
+                    row![painting, preview, codebase, database, fetch, search, agent].spacing(10),
+                )
+                .padding(10)
+            };
+
+            let preview = self.preview.as_ref().map(|preview| {
+                container(scrollable(preview.view(theme).map(Message::Markdown)).height(Shrink))
+                    .max_height(300)
+                    .width(Fill)
+                    .padding(15)
+                    .style(container::bordered_box)
+            });
+
+            let prompt_search = self.prompt_search.as_ref().map(|query| {
+                let matches = column(self.prompts.search(query).into_iter().take(8).map(
+                    |prompt| {
+                        button(
+                            ellipsized_text(prompt.to_owned())
+                                .wrapping(text::Wrapping::None)
+                                .size(14),
+                        )
+                        .on_press_with(move || Message::PromptPicked(prompt.to_owned()))
+                        .width(Fill)
+                        .style(button::text)
+                        .into()
+                    },
+                ));
+
+                container(
+                    column![
+                        text_input("Search previous prompts...", query)
+                            .id(PROMPT_SEARCH)
+                            .on_input(Message::PromptSearchChanged)
+                            .on_submit_maybe(
+                                self.prompts
+                                    .search(query)
+                                    .first()
+                                    .map(|prompt| Message::PromptPicked((*prompt).to_owned())),
+                            )
+                            .padding(10),
+                        matches,
+                    ]
+                    .spacing(5),
+                )
+                .padding(10)
+                .style(container::bordered_box)
+            });
+
+            let attachments = (!self.attachments.is_empty()).then(|| {
+                row(self
+                    .attachments
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (attachment, handle))| {
+                        let status = if self.recognizing.contains(&attachment.id) {
+                            Some("Reading...")
+                        } else if attachment.text.is_some() {
+                            Some("OCR")
+                        } else {
+                            None
+                        };
+
+                        stack![
+                            container(image(handle.clone()).width(60).height(60))
+                                .padding(2)
+                                .style(container::bordered_box),
+                            status.map(|status| {
+                                bottom(
+                                    container(text(status).size(10))
+                                        .padding([0, 4])
+                                        .style(container::dark),
+                                )
+                            }),
+                            right(
+                                button(icon::cancel().size(10))
+                                    .padding(2)
+                                    .on_press(Message::RemoveAttachment(index))
+                                    .style(button::danger)
+                            ),
+                        ]
+                        .into()
+                    }))
+                .spacing(10)
+            });
+
+            let transcription = self.transcription.as_ref().map(|transcription| {
+                container(
+                    row![
+                        column![
+                            row![
+                                ellipsized_text(transcription.file_name.clone())
+                                    .font(Font::MONOSPACE)
+                                    .wrapping(text::Wrapping::None)
+                                    .width(Fill),
+                                text!(
+                                    "{} {}%",
+                                    transcription.progress.stage,
+                                    transcription.progress.percent
+                                )
+                                .size(12)
+                                .style(text::secondary),
+                            ]
+                            .spacing(10),
+                            progress_bar(0.0..=100.0, transcription.progress.percent as f32)
+                                .girth(4),
+                        ]
+                        .spacing(5),
+                        button(icon::cancel().size(12))
+                            .on_press(Message::CancelTranscription)
+                            .style(button::text),
+                    ]
+                    .spacing(10)
+                    .align_y(Center),
+                )
+                .padding(10)
+                .style(container::bordered_box)
+            });
+
+            let summarizing = self.summarizing.map(|progress| {
+                let stage = match progress {
+                    summary::Progress::Mapped { done, total } => {
+                        format!("Summarizing part {} of {total}...", (done + 1).min(total))
+                    }
+                    summary::Progress::Reducing { round } => {
+                        format!("Combining summaries (round {round})...")
+                    }
+                };
+
+                container(
+                    column![
+                        text(stage).size(12).style(text::secondary),
+                        progress_bar(0.0..=100.0, progress.percent() as f32).girth(4),
+                    ]
+                    .spacing(5),
+                )
+                .padding(10)
+                .style(container::bordered_box)
+            });
+
+            let painting = self.painting.as_ref().map(|painting| {
+                row![
+                    text("Size").size(14),
+                    pick_list(
+                        diffusion::Size::ALL,
+                        Some(painting.size),
+                        Message::PaintingSizeChanged
+                    )
+                    .text_size(14),
+                    text("Steps").size(14),
+                    text_input("20", &painting.steps)
+                        .on_input(Message::PaintingStepsChanged)
+                        .size(14)
+                        .width(60),
+                    horizontal_space(),
+                    (!settings.diffusion.is_configured()).then(|| {
+                        text("Set up image generation in the settings first")
+                            .size(12)
+                            .style(text::danger)
+                    }),
+                ]
+                .spacing(10)
+                .align_y(Center)
+            });
+
+            container(
+                column![
+                    prompt_search,
+                    preview,
+                    attachments,
+                    transcription,
+                    summarizing,
+                    painting,
+                    presets(
+                        &settings.presets,
+                        self.preset.as_deref(),
+                        self.lock.is_none()
+                    ),
+                    stack![editor, strategy]
+                ]
+                .spacing(10),
+            )
+            .width(Shrink)
+            .max_width(column_width)
+            .into()
+        };
+
+        let header = container(header)
+            .padding(padding::bottom(15))
+            .style(|theme| container::Style {
+                background: Some(
+                    gradient::Linear::new(0)
+                        .add_stop(0.0, Color::TRANSPARENT)
+                        .add_stop(0.1, theme.palette().background.scale_alpha(0.95))
+                        .add_stop(1.0, theme.palette().background)
+                        .into(),
+                ),
+                ..container::Style::default()
+            });
+
+        let input = column![
+            container(horizontal_space())
+                .height(10)
+                .style(|theme: &Theme| container::Style {
+                    background: Some(
+                        gradient::Linear::new(Degrees(180.0))
+                            .add_stop(0.0, Color::TRANSPARENT)
+                            .add_stop(1.0, theme.palette().background)
+                            .into(),
+                    ),
+                    ..container::Style::default()
+                }),
+            input
+        ]
+        .align_x(Center);
+
+        stack![
+            sensor(messages)
+                .key(self.id)
+                .on_show(Message::Resized)
+                .on_resize(Message::Resized),
+            stalled.or(agent).or(pill),
+            column![
+                sensor(opaque(header))
+                    .key(self.id)
+                    .on_show(Message::HeaderShown)
+                    .on_resize(Message::HeaderResized),
+                vertical_space(),
+                sensor(input)
+                    .key(self.id)
+                    .on_show(Message::InputResized)
+                    .on_resize(Message::InputResized)
+            ]
+            .padding(padding::right(
+                (self.total_width - self.chat_width).clamp(0.0, 20.0)
+            )),
+            self.approval(),
+        ]
+        .into()
+    }
+
+    fn approval(&self) -> Option<Element<'_, Message>> {
+        let (generation, request) = self.approvals.first()?;
+
+        let chat = if self.generation == Some(*generation) {
+            None
+        } else {
+            self.background
+                .get(generation)
+                .map(|background| background.title.as_deref().unwrap_or("Untitled chat"))
+        };
+
+        let inputs: Element<'_, _> = if request.inputs.is_empty() {
+            text("No arguments").size(12).style(text::secondary).into()
+        } else {
+            scrollable(
+                column(request.inputs.iter().map(|input| {
+                    container(text(input).size(12).font(Font::MONOSPACE))
+                        .width(Fill)
+                        .padding(10)
+                        .style(container::dark)
+                        .into()
+                }))
+                .spacing(10),
+            )
+            .height(Shrink)
+            .into()
+        };
+
+        let dialog = container(
+            column![
+                text!("Allow {}?", request.tool)
+                    .font(Font::MONOSPACE)
+                    .size(20),
+                chat.map(|title| text!("Requested by {title}")
+                    .size(12)
+                    .style(text::secondary)),
+                text("The assistant wants to call this tool with these exact arguments:"),
+                container(inputs).max_height(300),
+                row![
+                    horizontal_space(),
+                    button("Deny")
+                        .on_press(Message::RespondToApproval(request.id, false))
+                        .style(button::secondary),
+                    button("Approve").on_press(Message::RespondToApproval(request.id, true)),
+                ]
+                .spacing(10),
+            ]
+            .spacing(15),
+        )
+        .max_width(600)
+        .padding(20)
+        .style(container::bordered_box);
+
+        Some(opaque(center(dialog).padding(20).style(|_theme| {
+            container::Style::default().background(Color::BLACK.scale_alpha(0.5))
+        })))
+    }
+
+    pub fn sidebar(&self) -> Element<'_, Message> {
+        let header = sidebar::header("Chats", Some((icon::plus(), Message::New)));
+
+        let chats = column(self.chats.iter().map(|chat| {
+            let card = match &chat.title {
+                Some(title) => {
+                    let mut t = title.to_owned();
+                    t = t.chars().take(20).collect();
+
+                    ellipsized_text(t)
+                }
+                None => ellipsized_text(chat.file.slash_id().0.clone()).font(Font::MONOSPACE),
+            }
+            .wrapping(text::Wrapping::None);
+
+            let is_active = Some(&chat.id) == self.id.as_ref();
+
+            let unread = self
+                .unread
+                .contains(&chat.id)
+                .then(|| text("●").size(8).style(text::primary));
+
+            sidebar::item(
+                row![card, horizontal_space()]
+                    .push(unread)
+                    .spacing(5)
+                    .align_y(Center),
+                is_active,
+                move || Message::Open(chat.id),
+            )
+        }))
+        .clip(true);
+
+        let transcribe = button(
+            row![
+                icon::folder_open().size(12),
+                text("Transcribe file...").size(12)
+            ]
+            .spacing(5)
+            .align_y(Center),
+        )
+        .on_press_maybe(
+            self.transcription
+                .is_none()
+                .then_some(Message::TranscribeFile),
+        )
+        .padding([5, 10])
+        .style(button::text);
+
+        let summarize = button(
+            row![icon::chat().size(12), text("Summarize file...").size(12)]
+                .spacing(5)
+                .align_y(Center),
+        )
+        .on_press_maybe(self.can_send().then_some(Message::SummarizeFile))
+        .padding([5, 10])
+        .style(button::text);
+
+        column![
+            header,
+            scrollable(chats).height(Fill).spacing(10),
+            column![transcribe, summarize]
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        let menu = self.menu.subscription().map(Message::Menu);
+
+        let state = match &self.state {
+            _ if self.read_only.is_some() => Subscription::none(),
+            State::Booting { .. } => time::every(Duration::from_millis(100)).map(Message::Tick),
+            State::Running { assistant, .. } => match assistant.telemetry() {
+                Some(target) => {
+                    Subscription::run_with(target, |target| telemetry::watch(target.clone()))
+                        .map(Message::TelemetrySampled)
+                }
+                None => Subscription::none(),
+            },
+        };
+
+        Subscription::batch([state, menu])
+    }
+
+    pub fn context_menu(&self) -> Option<Element<'_, Message>> {
+        use context_menu::entry;
+
+        let can_change = self.can_change() && self.read_only.is_none();
+
+        self.menu.view(|&index| {
+            let Some(item) = self.history.items().nth(index) else {
+                return Vec::new();
+            };
+
+            // Retrying a prompt regenerates the reply that follows it
+            let retry = match item {
+                Item::User { .. } => Some(index + 1),
+                Item::Reply(_) | Item::Plan(_) => Some(index),
+                Item::Attachment(..) | Item::Execution(_) => None,
+            };
+
+            vec![
+                entry(
+                    icon::clipboard(),
+                    "Copy",
+                    Some(Message::Copy(item.to_text())),
+                ),
+                entry(
+                    icon::arrow_up(),
+                    "Edit",
+                    (can_change && matches!(item, Item::User { .. }))
+                        .then_some(Message::Edit(index)),
+                ),
+                entry(
+                    icon::refresh(),
+                    "Retry",
+                    retry.filter(|_| can_change).map(Message::Regenerate),
+                ),
+                entry(
+                    icon::filter(),
+                    "Extract as table",
+                    (matches!(item, Item::Reply(_) | Item::Plan(_))
+                        && matches!(self.state, State::Running { .. }))
+                    .then_some(Message::Extract(index)),
+                ),
+                entry(
+                    icon::trash(),
+                    "Delete",
+                    can_change.then_some(Message::RemoveItem(index)),
+                ),
+            ]
+        })
+    }
+
+    pub fn file(&self) -> &FileAndAPI {
+        match &self.state {
+            State::Booting { file, .. } => file,
+            State::Running { assistant, .. } => &assistant.file,
+        }
+    }
+
+    pub fn model_name(&self) -> &str {
+        match &self.state {
+            State::Booting { file, .. } => file.slash_id().name(),
+            State::Running { assistant, .. } => assistant.name(),
+        }
+    }
+
+    pub fn boot_progress(&self) -> Option<(&str, u32)> {
+        match &self.state {
+            State::Booting {
+                stage, progress, ..
+            } => Some((stage, *progress)),
+            State::Running { .. } => None,
         }
+    }
 
-        let items = self.history.to_data();
+    pub fn assistant(&self) -> Option<&Assistant> {
+        match &self.state {
+            State::Booting { .. } => None,
+            State::Running { assistant, .. } => Some(assistant),
+        }
+    }
 
-        if let Some(id) = &self.id {
-            Action::Run(Task::perform(
-                Chat {
-                    id: *id,
-                    file: assistant.file.clone(),
-                    title: self.title.clone(),
-                    history: items,
-                }
-                .save(),
-                Message::Saved,
-            ))
+    pub fn log(&self) -> &[String] {
+        &self.log
+    }
+
+    pub fn scroll_offset(&self) -> Option<f32> {
+        (!self.scroll.is_at_bottom).then_some(self.scroll.offset.y)
+    }
+
+    pub fn scroll_to(&mut self, offset: Option<f32>) -> Task<Message> {
+        if let Some(y) = offset {
+            self.scroll.offset = scrollable::AbsoluteOffset { x: 0.0, y };
+            self.scroll.is_at_bottom = false;
+        }
+
+        self.restore_scroll()
+    }
+
+    pub fn restore_scroll(&self) -> Task<Message> {
+        if self.scroll.is_at_bottom {
+            snap_chat_to_end()
         } else {
-            Action::Run(Task::perform(
-                Chat::create(assistant.file.clone(), self.title.clone(), items),
-                Message::Created,
-            ))
+            scrollable::scroll_to(CHAT, self.scroll.offset)
         }
     }
 
-    pub fn view(&self, theme: &Theme) -> Element<'_, Message> {
-        let header: Element<'_, _> = {
-            let title: Element<'_, _> = match &self.title {
-                Some(title) => column![
-                    text(title).size(20).width(Fill).align_x(Center),
-                    text(self.model_name())
-                        .font(Font::MONOSPACE)
-                        .size(14)
-                        .style(text::secondary)
-                ]
-                .spacing(5)
-                .align_x(Center)
-                .into(),
-                None => text(self.model_name())
-                    .font(Font::MONOSPACE)
-                    .size(20)
-                    .align_x(Center)
-                    .into(),
-            };
+    fn follow(&mut self) -> Action {
+        if self.scroll.is_at_bottom {
+            Action::Run(snap_chat_to_end())
+        } else {
+            self.scroll.has_unread = true;
 
-            let delete: Element<'_, _> = if self.id.is_some() {
-                tip(
-                    button(icon::trash().style(text::danger))
-                        .padding(0)
-                        .on_press(Message::Delete)
-                        .style(button::text),
-                    "Delete Chat",
-                    tip::Position::Left,
-                )
-            } else {
-                horizontal_space().into()
-            };
+            Action::None
+        }
+    }
 
-            let t_bar = hover(center_x(title).padding([0, 40]), right_center(delete));
+    pub fn start(&mut self, persona: Persona) -> Task<Message> {
+        self.reset();
+        self.persona = Some(persona);
 
-            match &self.state {
-                State::Booting {
-                    logs,
-                    stage,
-                    progress,
-                    tick,
-                    ..
-                } => {
-                    let progress = {
-                        let stage = if self.error.is_none() {
-                            text!(
-                                "{stage} {spinner}",
-                                stage = stage,
-                                spinner = match tick % 4 {
-                                    0 => "|",
-                                    1 => "/",
-                                    2 => "—",
-                                    _ => "\\",
-                                }
-                            )
-                        } else {
-                            text(stage)
-                        }
-                        .font(Font::MONOSPACE)
-                        .size(10);
+        widget::focus_next()
+    }
 
-                        let bar = progress_bar(0.0..=100.0, *progress as f32)
-                            .length(200)
-                            .girth(30);
+    fn reset(&mut self) {
+        self.detach();
 
-                        stack![
-                            if self.error.is_none() {
-                                bar
-                            } else {
-                                bar.style(progress_bar::danger)
-                            },
-                            center(stage.style(|theme: &Theme| text::Style {
-                                color: Some(theme.palette().background)
-                            }))
-                            .clip(true)
-                        ]
-                    };
+        self.id = None;
+        self.title = None;
+        self.history = History::new();
+        self.persona = None;
+        self.lock = None;
+        self.todos.clear();
+        self.show_todos = false;
+        self.extracting_todos = None;
+        self.checkpoints.clear();
+        self.new_checkpoint = None;
+        self.is_read_only = false;
+        self.input = text_editor::Content::new();
+        self.scroll = Scroll::default();
+        self.expanded.clear();
+        self.revisions.clear();
+        self.diffing.clear();
+        self.tables.clear();
+        self.context_start = None;
+        self.error = None;
 
-                    let logs = {
-                        let error = self
-                            .error
-                            .as_ref()
-                            .map(|error| value(error).font(Font::MONOSPACE).style(text::danger));
+        if let State::Running { sending, .. } = &mut self.state {
+            *sending = None;
+        }
+    }
 
-                        scrollable(
-                            column(
-                                logs.iter()
-                                    .map(|log| text(log).size(12).font(Font::MONOSPACE).into()),
-                            )
-                            .push(error)
-                            .spacing(5)
-                            .padding(padding::right(20)),
-                        )
-                        .anchor_y(scrollable::Anchor::End)
-                        .width(400)
-                        .height(600)
-                    };
+    fn truncate(&mut self, length: usize) {
+        self.history.truncate(length);
+        self.checkpoints
+            .retain(|checkpoint| checkpoint.length <= length);
+    }
 
-                    let progress = tooltip(
-                        progress,
-                        container(logs).padding(10).style(container::dark),
-                        tooltip::Position::Bottom,
-                    );
+    fn generate(&mut self) -> Task<Message> {
+        let State::Running { assistant, sending } = &mut self.state else {
+            return Task::none();
+        };
 
-                    stack![t_bar, right_center(progress)].into()
-                }
-                State::Running { .. } => t_bar,
-            }
+        let generation = Generation(self.next_generation);
+        self.next_generation += 1;
+
+        let (send, handle) = Task::sip(
+            job::run(
+                job::Kind::Generation,
+                self.title.clone().unwrap_or_else(|| "New chat".to_owned()),
+                chat::complete(
+                    assistant,
+                    &self.history.to_data(),
+                    self.strategy.clone(),
+                    self.persona.as_ref(),
+                    self.lock.as_ref(),
+                ),
+            ),
+            Message::Chatting.with(generation),
+            Message::Chatted.with(generation),
+        )
+        .abortable();
+
+        *sending = Some(handle.abort_on_drop());
+        self.generation = Some(generation);
+
+        send
+    }
+
+    fn detach(&mut self) {
+        let State::Running { assistant, sending } = &mut self.state else {
+            return;
         };
 
-        let messages: Element<'_, _> = if self.history.is_empty() {
-            center(
-                match &self.state {
-                    State::Running { .. } => column![
-                        text("Your assistant is ready."),
-                        text("Break the ice! ↓")
-                            .style(text::primary)
-                            .shaping(text::Shaping::Advanced)
-                    ],
-                    State::Booting { .. } => column![
-                        text("Your assistant is launching..."),
-                        text("You can begin typing while you wait! ↓")
-                            .style(text::success)
-                            .shaping(text::Shaping::Advanced),
-                    ],
-                }
-                .spacing(10)
-                .align_x(Center),
-            )
-            .into()
-        } else {
-            scrollable(column![
-                sensor(horizontal_space())
-                    .key(self.id)
-                    .on_resize(Message::ChatResized),
-                center_x(
-                    column(
-                        self.history
-                            .items()
-                            .enumerate()
-                            .map(|(i, item)| item.view(i, theme)),
-                    )
-                    .padding(padding::all(20).top(0))
-                    .max_width(600),
-                )
-                .padding(padding::top(self.header_height).bottom(self.input_height))
-            ])
-            .id(CHAT)
-            .spacing(10)
-            .height(Fill)
-            .into()
+        let Some(generation) = self.generation.take() else {
+            return;
         };
 
-        let input = {
-            let editor = text_editor(&self.input)
-                .placeholder("Type your message here...")
-                .on_action(Message::InputChanged)
-                .padding(padding::all(15).bottom(50))
-                .min_height(16.0 * 1.3 * 2.0) // approx. 2 lines with 1.3 line height
-                .max_height(16.0 * 1.3 * 20.0) // approx. 20 lines
-                .key_binding(|key_press| {
-                    let modifiers = key_press.modifiers;
+        let Some(sending) = sending.take() else {
+            return;
+        };
 
-                    match text_editor::Binding::from_key_press(key_press) {
-                        Some(text_editor::Binding::Enter) if !modifiers.shift() => {
-                            Some(text_editor::Binding::Custom(Message::Submit))
-                        }
-                        binding => binding,
-                    }
-                })
-                .style(|theme, status| {
-                    let style = text_editor::default(theme, status);
+        let _ = self.background.insert(
+            generation,
+            Background {
+                id: self.id,
+                file: assistant.file.clone(),
+                title: self.title.clone(),
+                history: mem::replace(&mut self.history, History::new()),
+                persona: self.persona.clone(),
+                lock: self.lock.clone(),
+                todos: mem::take(&mut self.todos),
+                checkpoints: mem::take(&mut self.checkpoints),
+                context_start: self.context_start,
+                sending,
+            },
+        );
+    }
 
-                    text_editor::Style {
-                        border: style.border.rounded(10),
-                        ..style
-                    }
-                });
+    fn resume(&mut self, generation: Generation) {
+        let Some(background) = self.background.remove(&generation) else {
+            return;
+        };
 
-            let strategy = {
-                let search = tip(
-                    toggle(icon::globe(), "Search", self.strategy.search)
-                        .on_press(Message::ToggleSearch),
-                    "Very Experimental!",
-                    tip::Position::Left,
-                );
+        self.reset();
 
-                bottom_right(search).padding(10)
-            };
+        self.id = background.id;
+        self.title = background.title;
+        self.history = background.history;
+        self.persona = background.persona;
+        self.lock = background.lock;
+        self.show_todos = !background.todos.is_empty();
+        self.todos = background.todos;
+        self.checkpoints = background.checkpoints;
+        self.context_start = background.context_start;
+        self.generation = Some(generation);
+
+        if let State::Running { sending, .. } = &mut self.state {
+            *sending = Some(background.sending);
+        }
+
+        self.revise();
+    }
+
+    pub fn unread(&self) -> usize {
+        self.unread.len()
+    }
+
+    pub fn ask(&mut self, library: &Library, settings: &Settings, prompt: String) -> Action {
+        let new = self.update(library, settings, Message::New);
+
+        self.input = text_editor::Content::with_text(&prompt);
+
+        // A read-only chat is left by booting a new one, which the prompt waits for
+        if matches!(self.state, State::Booting { .. }) {
+            self.submit_on_boot = true;
+
+            return new;
+        }
+
+        match (new, self.update(library, settings, Message::Submit)) {
+            (Action::Run(new), Action::Run(submit)) => Action::Run(Task::batch([new, submit])),
+            (Action::Run(task), Action::None) | (Action::None, Action::Run(task)) => {
+                Action::Run(task)
+            }
+            (Action::None, Action::None) => Action::None,
+        }
+    }
 
-            container(stack![editor, strategy])
-                .width(Shrink)
-                .max_width(600)
+    pub fn draft(&mut self, library: &Library, settings: &Settings, prompt: String) -> Action {
+        let action = self.update(library, settings, Message::New);
+
+        self.input = text_editor::Content::with_text(&prompt);
+
+        action
+    }
+
+    fn revise(&mut self) {
+        let texts: Vec<String> = self.history.items().map(Item::to_text).collect();
+
+        self.revisions = self
+            .history
+            .items()
+            .enumerate()
+            .filter(|(_, item)| matches!(item, Item::Reply(_) | Item::Plan(_)))
+            .filter_map(|(index, _)| {
+                let revision =
+                    diff::revision(texts[..index].iter().map(String::as_str), &texts[index])?;
+
+                Some((index, revision))
+            })
+            .collect();
+    }
+
+    fn recallable(&self) -> Vec<&str> {
+        let mut prompts: Vec<&str> = self
+            .history
+            .items()
+            .rev()
+            .filter_map(|item| match item {
+                Item::User { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        for prompt in self.prompts.recent() {
+            if !prompts.contains(&prompt) {
+                prompts.push(prompt);
+            }
+        }
+
+        prompts
+    }
+
+    fn chat(&self) -> Option<Chat> {
+        let file = match &self.state {
+            State::Booting { file, .. } => file.clone(),
+            State::Running { assistant, .. } => assistant.file.clone(),
         };
 
-        let header = container(header)
-            .padding(padding::bottom(15))
-            .style(|theme| container::Style {
-                background: Some(
-                    gradient::Linear::new(0)
-                        .add_stop(0.0, Color::TRANSPARENT)
-                        .add_stop(0.1, theme.palette().background.scale_alpha(0.95))
-                        .add_stop(1.0, theme.palette().background)
-                        .into(),
-                ),
-                ..container::Style::default()
-            });
+        Some(Chat {
+            id: self.id?,
+            file,
+            title: self.title.clone(),
+            history: self.history.to_data(),
+            persona: self.persona.clone(),
+            lock: self.lock.clone(),
+            todos: self.todos.clone(),
+            checkpoints: self.checkpoints.clone(),
+            is_read_only: self.is_read_only,
+        })
+    }
+
+    fn checkpoints_at(&self, index: usize) -> impl Iterator<Item = Element<'_, Message>> {
+        let can_change = self.can_change();
+        let can_branch = self.can_send();
+
+        self.checkpoints
+            .iter()
+            .enumerate()
+            .filter(move |(_i, checkpoint)| checkpoint.length == index)
+            .map(move |(i, checkpoint)| {
+                let rule = || horizontal_rule(1).style(rule::weak);
+
+                let action = |icon: Element<'static, Message>, label, message: Option<Message>| {
+                    tip(
+                        button(icon)
+                            .padding(0)
+                            .on_press_maybe(message)
+                            .style(button::text),
+                        label,
+                        tip::Position::Top,
+                    )
+                };
 
-        let input = column![
-            container(horizontal_space())
-                .height(10)
-                .style(|theme: &Theme| container::Style {
-                    background: Some(
-                        gradient::Linear::new(Degrees(180.0))
-                            .add_stop(0.0, Color::TRANSPARENT)
-                            .add_stop(1.0, theme.palette().background)
-                            .into(),
+                row![
+                    rule(),
+                    icon::star().size(12).style(text::primary),
+                    text(&checkpoint.name)
+                        .size(12)
+                        .style(text::secondary)
+                        .width(Shrink),
+                    action(
+                        icon::refresh().size(12).into(),
+                        "Roll Back",
+                        can_change.then_some(Message::RollBack(i)),
                     ),
-                    ..container::Style::default()
-                }),
-            input
-        ]
-        .align_x(Center);
+                    action(
+                        icon::link().size(12).into(),
+                        "Branch",
+                        can_branch.then_some(Message::Branch(i)),
+                    ),
+                    action(
+                        icon::cancel().size(12).style(text::danger).into(),
+                        "Remove Checkpoint",
+                        (!self.is_read_only).then_some(Message::RemoveCheckpoint(i)),
+                    ),
+                    rule(),
+                ]
+                .spacing(10)
+                .align_y(Center)
+                .into()
+            })
+    }
 
-        stack![
-            sensor(messages)
-                .key(self.id)
-                .on_show(Message::Resized)
-                .on_resize(Message::Resized),
-            column![
-                sensor(opaque(header))
-                    .key(self.id)
-                    .on_show(Message::HeaderShown)
-                    .on_resize(Message::HeaderResized),
-                vertical_space(),
-                sensor(input)
-                    .key(self.id)
-                    .on_show(Message::InputResized)
-                    .on_resize(Message::InputResized)
+    fn new_checkpoint(&self) -> Option<Element<'_, Message>> {
+        let name = self.new_checkpoint.as_ref()?;
+
+        Some(
+            row![
+                text_input(&format!("Checkpoint {}", self.checkpoints.len() + 1), name)
+                    .id(CHECKPOINT_NAME)
+                    .on_input(Message::CheckpointNameChanged)
+                    .on_submit(Message::AddCheckpoint)
+                    .size(14)
+                    .padding(5),
+                button(text("Add").size(14))
+                    .on_press(Message::AddCheckpoint)
+                    .padding([5, 10]),
+                button(text("Cancel").size(14))
+                    .on_press(Message::NewCheckpoint)
+                    .padding([5, 10])
+                    .style(button::text),
             ]
-            .padding(padding::right(
-                (self.total_width - self.chat_width).clamp(0.0, 20.0)
-            ))
-        ]
-        .into()
+            .spacing(10)
+            .align_y(Center)
+            .into(),
+        )
     }
 
-    pub fn sidebar(&self) -> Element<'_, Message> {
-        let header = sidebar::header("Chats", Some((icon::plus(), Message::New)));
+    fn checklist(&self) -> Option<Element<'_, Message>> {
+        if !self.show_todos {
+            return None;
+        }
 
-        let chats = column(self.chats.iter().map(|chat| {
-            let card = match &chat.title {
-                Some(title) => {
-                    let mut t = title.to_owned();
-                    t = t.chars().take(20).collect();
+        fn action<'a>(label: &'a str, on_press: Option<Message>) -> Element<'a, Message> {
+            button(text(label).size(12))
+                .padding([2, 8])
+                .on_press_maybe(on_press)
+                .style(button::secondary)
+                .into()
+        }
 
-                    ellipsized_text(t)
-                }
-                None => ellipsized_text(chat.file.slash_id().0.clone()).font(Font::MONOSPACE),
-            }
-            .wrapping(text::Wrapping::None);
+        let done = self.todos.iter().filter(|todo| todo.is_done).count();
 
-            let is_active = Some(&chat.id) == self.id.as_ref();
+        let items: Element<'_, _> = if self.extracting_todos.is_some() {
+            text("Extracting action items...")
+                .size(12)
+                .style(text::secondary)
+                .into()
+        } else if self.todos.is_empty() {
+            text("No action items were found.")
+                .size(12)
+                .style(text::secondary)
+                .into()
+        } else {
+            column(self.todos.iter().enumerate().map(|(i, todo)| {
+                let details: Vec<&str> = [todo.owner.as_deref(), todo.due.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .collect();
 
-            sidebar::item(card, is_active, move || Message::Open(chat.id))
-        }))
-        .clip(true);
+                let label = if details.is_empty() {
+                    todo.task.clone()
+                } else {
+                    format!("{} ({})", todo.task, details.join(", "))
+                };
 
-        column![header, scrollable(chats).height(Fill).spacing(10)]
-            .spacing(10)
+                checkbox(label, todo.is_done)
+                    .on_toggle(Message::ToggleTodo.with(i))
+                    .size(14)
+                    .text_size(14)
+                    .text_shaping(text::Shaping::Advanced)
+                    .into()
+            }))
+            .spacing(8)
             .into()
-    }
+        };
 
-    pub fn subscription(&self) -> Subscription<Message> {
-        match &self.state {
-            State::Booting { .. } => time::every(Duration::from_millis(100)).map(Message::Tick),
-            State::Running { .. } => Subscription::none(),
-        }
+        let has_todos = !self.todos.is_empty();
+
+        Some(
+            container(
+                column![
+                    row![
+                        icon::check().size(12),
+                        text("Action Items").size(12).width(Fill),
+                        text!("{done}/{}", self.todos.len())
+                            .size(12)
+                            .font(Font::MONOSPACE)
+                            .style(text::secondary),
+                    ]
+                    .spacing(5)
+                    .align_y(Center),
+                    items,
+                    row![
+                        action("Copy Markdown", has_todos.then_some(Message::CopyTodos)),
+                        action("Export", has_todos.then_some(Message::ExportTodos)),
+                        action("Clear", Some(Message::ClearTodos)),
+                    ]
+                    .spacing(5),
+                ]
+                .spacing(10),
+            )
+            .padding(10)
+            .style(container::bordered_box)
+            .into(),
+        )
     }
 
-    pub fn model_name(&self) -> &str {
-        match &self.state {
-            State::Booting { file, .. } => file.slash_id().name(),
-            State::Running { assistant, .. } => assistant.name(),
+    fn is_waiting(&self) -> bool {
+        matches!(
+            self.state,
+            State::Running {
+                sending: Some(_),
+                ..
+            }
+        ) && match self.history.items().next_back() {
+            Some(Item::Reply(reply)) => reply.is_empty(),
+            Some(Item::User { .. } | Item::Attachment(..) | Item::Execution(_)) => true,
+            Some(Item::Plan(_)) | None => false,
         }
     }
 
     pub fn can_send(&self) -> bool {
         matches!(self.state, State::Running { sending: None, .. })
     }
+
+    fn can_change(&self) -> bool {
+        self.can_send() && !self.is_read_only
+    }
 }
 
 pub struct History {
@@ -778,7 +3531,7 @@ impl History {
         self.items.is_empty()
     }
 
-    pub fn items(&self) -> impl Iterator<Item = &Item> {
+    pub fn items(&self) -> impl DoubleEndedIterator<Item = &Item> {
         self.items.iter()
     }
 
@@ -794,10 +3547,38 @@ impl History {
         self.items.last_mut()
     }
 
+    fn apply(&mut self, event: chat::Event) {
+        match event {
+            chat::Event::ContextFitted(_) => {}
+            chat::Event::ReplyAdded => {
+                self.push(Item::Reply(Reply::default()));
+            }
+            chat::Event::ReplyChanged(new_reply) => {
+                if let Some(Item::Reply(reply)) = self.last_mut() {
+                    reply.update(new_reply);
+                }
+            }
+            chat::Event::PlanAdded => {
+                self.push(Item::Plan(Plan::default()));
+            }
+            chat::Event::PlanChanged(event) => {
+                if let Some(Item::Plan(plan)) = self.last_mut() {
+                    plan.apply(event);
+                }
+            }
+        }
+    }
+
     pub fn truncate(&mut self, amount: usize) {
         self.items.truncate(amount);
     }
 
+    pub fn remove(&mut self, index: usize) {
+        if index < self.items.len() {
+            let _ = self.items.remove(index);
+        }
+    }
+
     pub fn to_data(&self) -> Vec<chat::Item> {
         // TODO: Cache
         self.items.iter().map(Item::to_data).collect()
@@ -809,16 +3590,83 @@ pub enum Item {
     User { content: String, markdown: Markdown },
     Reply(Reply),
     Plan(Plan),
+    Attachment(Attachment, image::Handle),
+    Execution(execution::Output),
 }
 
 impl Item {
-    pub fn view<'a>(&'a self, index: usize, theme: &Theme) -> Element<'a, Message> {
-        use iced::border;
+    pub fn view<'a>(
+        &'a self,
+        index: usize,
+        theme: &Theme,
+        collapse_height: Option<u32>,
+        is_expanded: bool,
+        runnable: markdown::Runnable,
+        revision: Option<(&'a Revision, bool)>,
+        quick_actions: &'a [QuickAction],
+    ) -> Element<'a, Message> {
+        let revised = |content: Element<'a, Message>| -> Element<'a, Message> {
+            let Some((revision, is_diffing)) = revision else {
+                return content;
+            };
+
+            let toggle = toggle(icon::sliders(), "Diff vs original", is_diffing)
+                .on_press(Message::ToggleDiff(index));
+
+            column![
+                content,
+                toggle,
+                is_diffing.then(|| ui::diff::view(revision))
+            ]
+            .spacing(10)
+            .into()
+        };
+
+        let collapsible = |content: Element<'a, Message>| -> Element<'a, Message> {
+            let Some(height) = collapse_height else {
+                return content;
+            };
+
+            if estimated_height(&self.to_text()) <= height as f32 {
+                return content;
+            }
+
+            let toggle = button(
+                row![
+                    if is_expanded {
+                        icon::arrow_up()
+                    } else {
+                        icon::arrow_down()
+                    }
+                    .size(12),
+                    text(if is_expanded {
+                        "Show less"
+                    } else {
+                        "Show more"
+                    })
+                    .size(12),
+                ]
+                .spacing(5)
+                .align_y(Center),
+            )
+            .on_press(Message::ToggleExpanded(index))
+            .style(button::text);
+
+            if is_expanded {
+                column![content, toggle].into()
+            } else {
+                column![
+                    container(content).max_height(height as f32).clip(true),
+                    toggle
+                ]
+                .into()
+            }
+        };
 
         match self {
             Self::User { markdown, .. } => {
                 let message = container(
-                    container(markdown.view(theme).map(Message::Markdown))
+                    container(markdown.view(theme).map(on_markdown(index)))
                         .style(|theme: &Theme| {
                             let palette = theme.extended_palette();
 
@@ -840,15 +3688,92 @@ impl Item {
                 .into()
             }
             Self::Reply(reply) => self.with_actions(
-                reply.view(
+                revised(collapsible(reply.view(
                     theme,
+                    runnable,
                     Message::ToggleReasoning.with(index),
-                    Message::Markdown,
-                ),
+                    on_markdown(index),
+                ))),
+                index,
+                quick_actions,
+            ),
+            Self::Plan(plan) => self.with_actions(
+                revised(collapsible(plan.view(theme).map(Message::Plan.with(index)))),
                 index,
+                quick_actions,
             ),
-            Self::Plan(plan) => {
-                self.with_actions(plan.view(theme).map(Message::Plan.with(index)), index)
+            Self::Attachment(attachment, handle) => {
+                let preview = right(
+                    container(image(handle.clone()).width(240))
+                        .padding(padding::top(20))
+                        .style(|_theme| container::Style::default().border(border::rounded(10))),
+                );
+
+                match &attachment.text {
+                    Some(extracted) => column![
+                        preview,
+                        right(
+                            column![
+                                text("The model cannot see images; it got the text read with OCR:")
+                                    .size(12)
+                                    .style(text::secondary),
+                                text(if extracted.is_empty() {
+                                    "(no text found)"
+                                } else {
+                                    extracted
+                                })
+                                .size(12)
+                                .font(Font::MONOSPACE),
+                            ]
+                            .spacing(5)
+                            .max_width(400)
+                        ),
+                    ]
+                    .spacing(10)
+                    .into(),
+                    None => preview.into(),
+                }
+            }
+            Self::Execution(output) => {
+                let style: fn(&Theme) -> text::Style = if output.is_success() {
+                    text::secondary
+                } else {
+                    text::danger
+                };
+
+                let status = row![
+                    icon::server().size(12).style(style),
+                    text!("{} · {}", output.language.name(), output.summary())
+                        .size(12)
+                        .style(style),
+                ]
+                .spacing(5)
+                .align_y(Center);
+
+                let stream = |content: &'a str, style: fn(&Theme) -> text::Style| {
+                    (!content.trim().is_empty()).then(|| {
+                        text(content.trim_end())
+                            .font(Font::MONOSPACE)
+                            .size(12)
+                            .style(style)
+                    })
+                };
+
+                container(
+                    container(
+                        column![
+                            status,
+                            stream(&output.stdout, text::default),
+                            stream(&output.stderr, text::danger),
+                        ]
+                        .spacing(10),
+                    )
+                    .width(Fill)
+                    .padding(10)
+                    .style(container::bordered_box),
+                )
+                .padding(padding::top(20))
+                .into()
             }
         }
     }
@@ -857,24 +3782,51 @@ impl Item {
         &'a self,
         base: Element<'a, Message>,
         index: usize,
+        quick_actions: &'a [QuickAction],
     ) -> Element<'a, Message> {
+        let quick_actions = row(quick_actions.iter().enumerate().map(|(i, action)| {
+            button(text(&action.name).size(12))
+                .padding([2, 8])
+                .on_press(Message::QuickAction(index, i))
+                .style(button::secondary)
+                .into()
+        }))
+        .spacing(5);
+
         let actions = row![
             copy(|| Message::Copy(self.to_text())),
-            regenerate(move || Message::Regenerate(index))
+            regenerate(move || Message::Regenerate(index)),
+            quick_actions,
         ]
-        .spacing(10);
+        .spacing(10)
+        .align_y(Center);
 
         hover(container(base).padding([30, 0]), bottom(actions))
     }
 
+    fn stats(&self) -> Option<markdown::Stats> {
+        match self {
+            Self::User { markdown, .. } => Some(markdown.stats()),
+            Self::Reply(reply) => Some(reply.stats()),
+            Self::Plan(plan) => Some(markdown::Stats::of(&plan.to_text())),
+            Self::Attachment(..) | Self::Execution(_) => None,
+        }
+    }
+
+    fn usage(&self) -> Option<assistant::Usage> {
+        match self {
+            Self::Reply(reply) => reply.usage(),
+            _ => None,
+        }
+    }
+
     pub fn to_text(&self) -> String {
         match self {
             Self::User { content, .. } => content.clone(),
             Self::Reply(reply) => reply.to_text(),
-            Self::Plan { .. } => {
-                // TODO
-                "TODO".to_owned()
-            }
+            Self::Plan(plan) => plan.to_text(),
+            Self::Attachment(..) => String::new(),
+            Self::Execution(output) => output.to_prompt(),
         }
     }
 
@@ -886,6 +3838,12 @@ impl Item {
             },
             chat::Item::Reply(reply) => Self::Reply(Reply::from_data(reply)),
             chat::Item::Plan(plan) => Self::Plan(Plan::from_data(plan)),
+            chat::Item::Attachment(attachment) => {
+                let handle = image::Handle::from_path(attachment.path());
+
+                Self::Attachment(attachment, handle)
+            }
+            chat::Item::Execution(output) => Self::Execution(output),
         }
     }
 
@@ -894,12 +3852,376 @@ impl Item {
             Self::User { content, .. } => chat::Item::User(content.clone()),
             Self::Reply(reply) => chat::Item::Reply(reply.to_data()),
             Self::Plan(plan) => chat::Item::Plan(plan.to_data()),
+            Self::Attachment(attachment, _handle) => chat::Item::Attachment(attachment.clone()),
+            Self::Execution(output) => chat::Item::Execution(output.clone()),
+        }
+    }
+}
+
+fn crash(report: &diagnostics::Report) -> Element<'_, Message> {
+    let title = column![
+        text("The assistant crashed while launching")
+            .font(Font::MONOSPACE)
+            .size(20),
+        text(match report.exit_code {
+            Some(code) => format!("llama-server exited with code {code}."),
+            None => "llama-server exited unexpectedly.".to_owned(),
+        })
+        .style(text::secondary),
+    ]
+    .spacing(5);
+
+    let suggestions = column(report.suggestions().into_iter().map(|suggestion| {
+        row![
+            icon::arrow_right().size(12).style(text::primary),
+            text(suggestion)
+        ]
+        .spacing(10)
+        .into()
+    }))
+    .spacing(10);
+
+    let output = container(
+        scrollable(
+            column![
+                text(&report.command)
+                    .font(Font::MONOSPACE)
+                    .size(12)
+                    .style(text::primary),
+                column(
+                    report
+                        .output
+                        .iter()
+                        .map(|line| { text(line).font(Font::MONOSPACE).size(12).into() })
+                )
+            ]
+            .spacing(10)
+            .padding(10),
+        )
+        .anchor_y(scrollable::Anchor::End)
+        .height(300),
+    )
+    .style(container::bordered_box);
+
+    let copy = button(
+        row![icon::clipboard(), text("Copy Report")]
+            .spacing(10)
+            .align_y(Center),
+    )
+    .on_press(Message::Copy(report.to_string()));
+
+    center(
+        column![title, suggestions, output, right(copy)]
+            .spacing(20)
+            .max_width(700),
+    )
+    .padding(20)
+    .into()
+}
+
+fn presets<'a>(
+    custom: &'a [Preset],
+    selected: Option<&str>,
+    is_enabled: bool,
+) -> Element<'a, Message> {
+    let segments = Preset::builtins()
+        .into_iter()
+        .chain(custom.iter().cloned())
+        .map(|preset| {
+            let style: fn(&Theme, button::Status) -> button::Style =
+                if selected == Some(preset.name.as_str()) {
+                    button::primary
+                } else {
+                    button::text
+                };
+
+            button(text(preset.name.clone()).size(12))
+                .padding([4, 10])
+                .style(style)
+                .on_press_maybe(is_enabled.then_some(Message::SelectPreset(preset)))
+                .into()
+        });
+
+    container(row(segments).spacing(2))
+        .padding(2)
+        .style(|theme: &Theme| {
+            container::Style::default().border(
+                border::rounded(5)
+                    .color(theme.extended_palette().background.strong.color)
+                    .width(1),
+            )
+        })
+        .into()
+}
+
+fn locked(lock: &chat::Lock) -> Element<'_, Message> {
+    let parameters = &lock.parameters;
+
+    let details = [
+        Some(format!("Model: {}", lock.file.slash_id().0)),
+        parameters
+            .temperature
+            .map(|temperature| format!("Temperature: {temperature}")),
+        parameters.top_p.map(|top_p| format!("Top P: {top_p}")),
+        parameters
+            .max_tokens
+            .map(|max_tokens| format!("Max tokens: {max_tokens}")),
+        Some(format!("System prompt: {}", lock.system_prompt)),
+    ];
+
+    tooltip(
+        container(text("Locked").size(10).style(text::primary))
+            .padding([2, 6])
+            .style(container::bordered_box),
+        container(
+            column(
+                details
+                    .into_iter()
+                    .flatten()
+                    .map(|detail| text(detail).size(12).font(Font::MONOSPACE).into()),
+            )
+            .spacing(5)
+            .max_width(400),
+        )
+        .padding(10)
+        .style(container::dark),
+        tooltip::Position::Bottom,
+    )
+    .into()
+}
+
+fn typing<'a>() -> Element<'a, Message> {
+    diffused_text("Typing...")
+        .font(Font::MONOSPACE)
+        .duration(time::seconds(1))
+        .into()
+}
+
+fn context_cut<'a>() -> Element<'a, Message> {
+    let rule = || horizontal_rule(1).style(rule::weak);
+
+    row![
+        rule(),
+        text("Older messages not included, as they no longer fit in the context")
+            .size(12)
+            .style(text::secondary)
+            .width(Shrink),
+        rule(),
+    ]
+    .spacing(10)
+    .align_y(Center)
+    .into()
+}
+
+fn summary(history: &History) -> Element<'_, Message> {
+    const WORDS_PER_MINUTE: usize = 230;
+    const CHARACTERS_PER_TOKEN: usize = 4;
+
+    let (messages, words, characters) = history.items().filter_map(Item::stats).fold(
+        (0, 0, 0),
+        |(messages, words, characters), stats| {
+            (
+                messages + 1,
+                words + stats.words,
+                characters + stats.characters,
+            )
+        },
+    );
+
+    let usage = history
+        .items()
+        .filter_map(Item::usage)
+        .reduce(|total, usage| total + usage);
+
+    // Providers report what was spent, which beats any estimate
+    let tokens = match usage {
+        Some(usage) => ui::reply::usage_label(usage),
+        None => format!("~{} tokens", characters.div_ceil(CHARACTERS_PER_TOKEN)),
+    };
+
+    text!(
+        "{messages} messages · {words} words · {minutes} min read · {tokens}",
+        minutes = words.div_ceil(WORDS_PER_MINUTE),
+    )
+    .font(Font::MONOSPACE)
+    .size(12)
+    .style(text::secondary)
+    .shaping(text::Shaping::Advanced)
+    .into()
+}
+
+fn on_markdown(index: usize) -> impl Fn(markdown::Interaction) -> Message + Clone + 'static {
+    move |interaction| match interaction {
+        markdown::Interaction::Table(table, interaction) => {
+            Message::MarkdownTable(index, table, interaction)
         }
+        markdown::Interaction::Query(sql) => Message::Query(index, sql),
+        interaction => Message::Markdown(interaction),
+    }
+}
+
+fn view_table(index: usize, table: Option<&Table>) -> Element<'_, Message> {
+    let Some(table) = table else {
+        return text("Loading table...")
+            .size(12)
+            .style(text::secondary)
+            .into();
+    };
+
+    fn action<'a>(label: &'a str, on_press: impl Fn() -> Message + 'a) -> Element<'a, Message> {
+        button(text(label).size(12))
+            .padding([2, 8])
+            .on_press_with(on_press)
+            .style(button::secondary)
+            .into()
+    }
+
+    column![
+        table.view().map(Message::Table.with(index)),
+        row![
+            action("Copy CSV", || Message::Copy(table.to_data().to_csv())),
+            action("Copy JSON", || Message::Copy(table.to_data().to_json())),
+            action("Export", || Message::SaveTable(table.to_data())),
+            action("Close", move || Message::CloseTable(index)),
+        ]
+        .spacing(5),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn gauges(sample: &telemetry::Sample) -> Element<'_, Message> {
+    fn gauge<'a>(label: String, value: f32) -> Element<'a, Message> {
+        column![
+            text(label).font(Font::MONOSPACE).size(10),
+            progress_bar(0.0..=1.0, value).length(80).girth(4)
+        ]
+        .spacing(2)
+        .into()
+    }
+
+    fn gigabytes(bytes: u64) -> String {
+        format!("{:.1}", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
+    }
+
+    let gpu = sample.gpu.map(|gpu| {
+        row![
+            gauge(
+                format!("GPU {}%", gpu.utilization),
+                gpu.utilization as f32 / 100.0
+            ),
+            gauge(
+                format!("VRAM {}/{} GB", gigabytes(gpu.used), gigabytes(gpu.total)),
+                gpu.memory()
+            ),
+        ]
+        .spacing(10)
+    });
+
+    let memory = sample.memory.map(|memory| {
+        let label = match sample.rss {
+            Some(rss) => format!(
+                "RAM {}/{} GB · model {} GB",
+                gigabytes(memory.total - memory.available.min(memory.total)),
+                gigabytes(memory.total),
+                gigabytes(rss)
+            ),
+            None => format!(
+                "RAM {}/{} GB",
+                gigabytes(memory.total - memory.available.min(memory.total)),
+                gigabytes(memory.total)
+            ),
+        };
+
+        gauge(label, memory.used())
+    });
+
+    let warning = sample.is_under_pressure().then(|| {
+        tip(
+            text("Low memory").size(10).style(text::danger),
+            "The model may crash if it runs out of memory",
+            tip::Position::Bottom,
+        )
+    });
+
+    row![gpu, memory, warning]
+        .spacing(10)
+        .align_y(Center)
+        .into()
+}
+
+fn estimated_height(text: &str) -> f32 {
+    const LINE_HEIGHT: f32 = 16.0 * 1.3;
+    const CHARS_PER_LINE: usize = 80;
+
+    let lines: usize = text
+        .lines()
+        .map(|line| line.chars().count().div_ceil(CHARS_PER_LINE).max(1))
+        .sum();
+
+    lines as f32 * LINE_HEIGHT
+}
+
+fn insert(content: &mut text_editor::Content, c: char) {
+    use text_editor::{Action, Edit, Motion};
+
+    let cursor = content.cursor();
+
+    if cursor.selection.is_some() {
+        content.perform(Action::Edit(Edit::Insert(c)));
+        return;
+    }
+
+    let line = content
+        .line(cursor.position.line)
+        .map(|line| line.text.into_owned())
+        .unwrap_or_default();
+
+    let split = line
+        .char_indices()
+        .nth(cursor.position.column)
+        .map_or(line.len(), |(i, _)| i);
+
+    let (before, after) = line.split_at(split);
+    let next = after.chars().next();
+
+    if matches!(c, ')' | ']' | '}' | '"' | '`') && next == Some(c) {
+        content.perform(Action::Move(Motion::Right));
+        return;
+    }
+
+    content.perform(Action::Edit(Edit::Insert(c)));
+
+    if c == '`' && before.trim_start() == "``" && after.is_empty() {
+        content.perform(Action::Edit(Edit::Paste(Arc::new("\n\n```".to_owned()))));
+        content.perform(Action::Move(Motion::Up));
+        content.perform(Action::Move(Motion::Up));
+        content.perform(Action::Move(Motion::End));
+        return;
+    }
+
+    let closing = match c {
+        '(' => ')',
+        '[' => ']',
+        '{' => '}',
+        '"' | '`' if next.is_none_or(char::is_whitespace) => c,
+        _ => return,
+    };
+
+    if next.is_none_or(|next| next.is_whitespace() || ")]}".contains(next)) {
+        content.perform(Action::Edit(Edit::Insert(closing)));
+        content.perform(Action::Move(Motion::Left));
     }
 }
 
 const CHAT: &str = "chat";
 
+const COLUMN_WIDTH: f32 = 600.0;
+const LOG_LIMIT: usize = 1_000;
+const ZEN_COLUMN_WIDTH: f32 = 900.0;
+const PROMPT_SEARCH: &str = "prompt_search";
+const CHECKPOINT_NAME: &str = "checkpoint_name";
+
 fn snap_chat_to_end() -> Task<Message> {
     scrollable::snap_to(CHAT, scrollable::RelativeOffset::END)
 }
@@ -0,0 +1,265 @@
+use crate::core::git::{Changes, Repository};
+use crate::core::Error;
+use crate::icon;
+use crate::widget::sidebar;
+
+use iced::widget::{
+    button, center, column, container, horizontal_space, row, scrollable, text, value,
+};
+use iced::{Center, Element, Fill, Font, Function, Task, Theme};
+use iced_palace::widget::ellipsized_text;
+
+const LOG_LIMIT: usize = 20;
+
+pub struct Git {
+    repository: Option<Repository>,
+    changes: Changes,
+    diff: Option<String>,
+    log: String,
+    error: Option<Error>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    PickRepository,
+    RepositoryPicked(Option<rfd::FileHandle>),
+    Opened(Result<Repository, Error>),
+    Show(Changes),
+    Diffed(Changes, Result<String, Error>),
+    Logged(Result<String, Error>),
+    Ask,
+}
+
+pub enum Action {
+    None,
+    Run(Task<Message>),
+    Ask(String),
+}
+
+impl Git {
+    pub fn new() -> (Self, Task<Message>) {
+        (
+            Self {
+                repository: None,
+                changes: Changes::Staged,
+                diff: None,
+                log: String::new(),
+                error: None,
+            },
+            Task::none(),
+        )
+    }
+
+    pub fn title(&self) -> &str {
+        "Git"
+    }
+
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::PickRepository => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose a git repository...")
+                    .pick_folder(),
+                Message::RepositoryPicked,
+            )),
+            Message::RepositoryPicked(folder) => {
+                let Some(folder) = folder else {
+                    return Action::None;
+                };
+
+                Action::Run(Task::perform(
+                    Repository::open(folder.path().to_path_buf()),
+                    Message::Opened,
+                ))
+            }
+            Message::Opened(Ok(repository)) => {
+                self.repository = Some(repository.clone());
+                self.diff = None;
+                self.error = None;
+
+                Action::Run(Task::batch([
+                    Task::perform(repository.clone().log(LOG_LIMIT), Message::Logged),
+                    Task::perform(
+                        repository.diff(self.changes),
+                        Message::Diffed.with(self.changes),
+                    ),
+                ]))
+            }
+            Message::Show(changes) => {
+                let Some(repository) = self.repository.clone() else {
+                    return Action::None;
+                };
+
+                self.changes = changes;
+                self.diff = None;
+
+                Action::Run(Task::perform(
+                    repository.diff(changes),
+                    Message::Diffed.with(changes),
+                ))
+            }
+            Message::Diffed(changes, Ok(diff)) => {
+                if changes == self.changes {
+                    self.diff = Some(diff);
+                }
+
+                Action::None
+            }
+            Message::Logged(Ok(log)) => {
+                self.log = log;
+
+                Action::None
+            }
+            Message::Ask => {
+                let Some(diff) = self.diff.as_deref().filter(|diff| !diff.trim().is_empty()) else {
+                    return Action::None;
+                };
+
+                Action::Ask(self.changes.prompt(diff, &self.log))
+            }
+            Message::Opened(Err(error))
+            | Message::Diffed(_, Err(error))
+            | Message::Logged(Err(error)) => {
+                self.error = Some(dbg!(error));
+
+                Action::None
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let Some(repository) = &self.repository else {
+            return center(
+                column![
+                    text("Choose a repository to generate commit messages and review diffs.")
+                        .style(text::secondary),
+                    button("Choose Repository...").on_press(Message::PickRepository),
+                    self.error
+                        .as_ref()
+                        .map(|error| value(error).font(Font::MONOSPACE).style(text::danger)),
+                ]
+                .spacing(20)
+                .align_x(Center),
+            )
+            .into();
+        };
+
+        let tab = |label, changes| {
+            let style: fn(&Theme, button::Status) -> button::Style = if self.changes == changes {
+                button::primary
+            } else {
+                button::secondary
+            };
+
+            button(text(label).size(14))
+                .on_press(Message::Show(changes))
+                .style(style)
+        };
+
+        let has_changes = self
+            .diff
+            .as_ref()
+            .is_some_and(|diff| !diff.trim().is_empty());
+
+        let ask = button(text(match self.changes {
+            Changes::Staged => "Generate Commit Message",
+            Changes::Uncommitted => "Review This Diff",
+        }))
+        .on_press_maybe(has_changes.then_some(Message::Ask));
+
+        let controls = row![
+            tab("Staged", Changes::Staged),
+            tab("All Uncommitted", Changes::Uncommitted),
+            horizontal_space(),
+            ask,
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        let diff: Element<'_, _> = match &self.diff {
+            None => center(text("Loading changes...").style(text::secondary)).into(),
+            Some(diff) if diff.trim().is_empty() => center(
+                text(match self.changes {
+                    Changes::Staged => "No staged changes.",
+                    Changes::Uncommitted => "No uncommitted changes.",
+                })
+                .style(text::secondary),
+            )
+            .into(),
+            Some(diff) => scrollable(
+                container(
+                    column(diff.lines().map(|line| {
+                        let style: fn(&Theme) -> text::Style =
+                            if line.starts_with('+') && !line.starts_with("+++") {
+                                text::success
+                            } else if line.starts_with('-') && !line.starts_with("---") {
+                                text::danger
+                            } else if line.starts_with("@@") {
+                                text::primary
+                            } else {
+                                text::default
+                            };
+
+                        text(line)
+                            .font(Font::MONOSPACE)
+                            .size(12)
+                            .wrapping(text::Wrapping::None)
+                            .style(style)
+                            .into()
+                    }))
+                    .padding(10),
+                )
+                .width(Fill)
+                .style(container::bordered_box),
+            )
+            .direction(scrollable::Direction::Both {
+                vertical: scrollable::Scrollbar::default(),
+                horizontal: scrollable::Scrollbar::default(),
+            })
+            .height(Fill)
+            .into(),
+        };
+
+        let error = self
+            .error
+            .as_ref()
+            .map(|error| value(error).font(Font::MONOSPACE).style(text::danger));
+
+        column![
+            text!("{} · {}", repository.name(), repository.branch())
+                .font(Font::MONOSPACE)
+                .size(18),
+            controls,
+            error,
+            diff
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    pub fn sidebar(&self) -> Element<'_, Message> {
+        let header = sidebar::header("Git", Some((icon::folder_open(), Message::PickRepository)));
+
+        let log = self.repository.as_ref().map(|repository| {
+            column![
+                text(repository.root().display().to_string())
+                    .font(Font::MONOSPACE)
+                    .size(12)
+                    .style(text::secondary),
+                scrollable(
+                    column(self.log.lines().map(|commit| {
+                        ellipsized_text(commit.to_owned())
+                            .font(Font::MONOSPACE)
+                            .wrapping(text::Wrapping::None)
+                            .into()
+                    }))
+                    .spacing(5)
+                )
+                .height(Fill),
+            ]
+            .spacing(10)
+        });
+
+        column![header, log].spacing(10).into()
+    }
+}
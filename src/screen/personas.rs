@@ -0,0 +1,489 @@
+use crate::core::assistant::Parameters;
+use crate::core::persona::Persona;
+use crate::core::Error;
+use crate::icon;
+use crate::widget::{sidebar, tip};
+
+use iced::task::Task;
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text, text_editor, text_input,
+    value,
+};
+use iced::{Center, Element, Fill, Font, Function};
+
+pub struct Personas {
+    personas: Vec<Persona>,
+    editor: Option<Editor>,
+    error: Option<Error>,
+}
+
+struct Editor {
+    original: Option<Persona>,
+    name: String,
+    description: String,
+    system_prompt: text_editor::Content,
+    temperature: String,
+    top_p: String,
+    max_tokens: String,
+    model_class: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Listed(Result<Vec<Persona>, Error>),
+    Start(usize),
+    Create,
+    Edit(usize),
+    NameChanged(String),
+    DescriptionChanged(String),
+    SystemPromptEdited(text_editor::Action),
+    TemperatureChanged(String),
+    TopPChanged(String),
+    MaxTokensChanged(String),
+    ModelClassChanged(String),
+    Save,
+    Saved(Result<Persona, Error>),
+    Delete,
+    Deleted(Result<(), Error>),
+    Cancel,
+    Export(usize),
+    ExportPicked(Persona, Option<rfd::FileHandle>),
+    Exported(Result<Persona, Error>),
+    Import,
+    ImportPicked(Option<rfd::FileHandle>),
+}
+
+pub enum Action {
+    None,
+    Run(Task<Message>),
+    Start(Persona),
+}
+
+impl Personas {
+    pub fn new() -> (Self, Task<Message>) {
+        (
+            Self {
+                personas: Persona::builtins(),
+                editor: None,
+                error: None,
+            },
+            Task::perform(Persona::list(), Message::Listed),
+        )
+    }
+
+    pub fn title(&self) -> &str {
+        "Personas"
+    }
+
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::Listed(Ok(personas)) => {
+                self.personas = personas;
+
+                Action::None
+            }
+            Message::Start(index) => {
+                let Some(persona) = self.personas.get(index) else {
+                    return Action::None;
+                };
+
+                Action::Start(persona.clone())
+            }
+            Message::Create => {
+                self.editor = Some(Editor::new(None));
+
+                Action::None
+            }
+            Message::Edit(index) => {
+                self.editor = self.personas.get(index).cloned().map(Some).map(Editor::new);
+
+                Action::None
+            }
+            Message::NameChanged(name) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.name = name;
+                }
+
+                Action::None
+            }
+            Message::DescriptionChanged(description) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.description = description;
+                }
+
+                Action::None
+            }
+            Message::SystemPromptEdited(action) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.system_prompt.perform(action);
+                }
+
+                Action::None
+            }
+            Message::TemperatureChanged(temperature) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.temperature = temperature;
+                }
+
+                Action::None
+            }
+            Message::TopPChanged(top_p) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.top_p = top_p;
+                }
+
+                Action::None
+            }
+            Message::MaxTokensChanged(max_tokens) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.max_tokens = max_tokens;
+                }
+
+                Action::None
+            }
+            Message::ModelClassChanged(model_class) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.model_class = model_class;
+                }
+
+                Action::None
+            }
+            Message::Save => {
+                let Some(editor) = &self.editor else {
+                    return Action::None;
+                };
+
+                let Some(persona) = editor.persona() else {
+                    return Action::None;
+                };
+
+                let renamed = editor
+                    .original
+                    .clone()
+                    .filter(|original| original.name != persona.name);
+
+                Action::Run(Task::perform(
+                    async move {
+                        if let Some(original) = renamed {
+                            original.delete().await?;
+                        }
+
+                        persona.save().await
+                    },
+                    Message::Saved,
+                ))
+            }
+            Message::Saved(Ok(_)) | Message::Deleted(Ok(())) => {
+                self.editor = None;
+                self.error = None;
+
+                Action::Run(Task::perform(Persona::list(), Message::Listed))
+            }
+            Message::Delete => {
+                let Some(original) = self
+                    .editor
+                    .as_ref()
+                    .and_then(|editor| editor.original.clone())
+                else {
+                    return Action::None;
+                };
+
+                Action::Run(Task::perform(original.delete(), Message::Deleted))
+            }
+            Message::Cancel => {
+                self.editor = None;
+
+                Action::None
+            }
+            Message::Export(index) => {
+                let Some(persona) = self.personas.get(index).cloned() else {
+                    return Action::None;
+                };
+
+                let file_name = format!("{}.toml", persona.name);
+
+                Action::Run(Task::perform(
+                    rfd::AsyncFileDialog::new()
+                        .set_title("Export persona...")
+                        .set_file_name(file_name)
+                        .add_filter("TOML", &["toml"])
+                        .save_file(),
+                    Message::ExportPicked.with(persona),
+                ))
+            }
+            Message::ExportPicked(persona, Some(file)) => Action::Run(Task::perform(
+                persona.export(file.path().to_path_buf()),
+                Message::Exported,
+            )),
+            Message::Import => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Import persona...")
+                    .add_filter("TOML", &["toml"])
+                    .pick_file(),
+                Message::ImportPicked,
+            )),
+            Message::ImportPicked(Some(file)) => Action::Run(Task::perform(
+                async move { Persona::import(file.path()).await?.save().await },
+                Message::Saved,
+            )),
+            Message::ExportPicked(_, None)
+            | Message::ImportPicked(None)
+            | Message::Exported(Ok(_)) => Action::None,
+            Message::Listed(Err(error))
+            | Message::Saved(Err(error))
+            | Message::Deleted(Err(error))
+            | Message::Exported(Err(error)) => {
+                self.error = Some(dbg!(error));
+
+                Action::None
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let error = self
+            .error
+            .as_ref()
+            .map(|error| value(error).font(Font::MONOSPACE).style(text::danger));
+
+        let content: Element<'_, _> = match &self.editor {
+            Some(editor) => editor.view(),
+            None => scrollable(
+                row(self
+                    .personas
+                    .iter()
+                    .enumerate()
+                    .map(|(index, persona)| card(index, persona)))
+                .spacing(10)
+                .wrap(),
+            )
+            .height(Fill)
+            .into(),
+        };
+
+        column![error, content].spacing(10).into()
+    }
+
+    pub fn sidebar(&self) -> Element<'_, Message> {
+        let header = sidebar::header("Personas", Some((icon::plus(), Message::Create)));
+
+        let personas = column(self.personas.iter().enumerate().map(|(index, persona)| {
+            button(text(&persona.name).size(14))
+                .on_press(Message::Edit(index))
+                .width(Fill)
+                .style(button::text)
+                .into()
+        }))
+        .spacing(5);
+
+        let import = button(
+            row![icon::folder_open().size(12), text("Import...").size(14)]
+                .spacing(10)
+                .align_y(Center),
+        )
+        .on_press(Message::Import)
+        .width(Fill)
+        .style(button::secondary);
+
+        column![header, scrollable(personas).height(Fill), import]
+            .spacing(10)
+            .into()
+    }
+}
+
+impl Editor {
+    fn new(persona: Option<Persona>) -> Self {
+        let persona_or_default = persona.clone().unwrap_or_else(|| Persona {
+            name: String::new(),
+            description: String::new(),
+            system_prompt: String::new(),
+            parameters: Parameters::default(),
+            model_class: None,
+        });
+
+        Self {
+            name: persona_or_default.name,
+            description: persona_or_default.description,
+            system_prompt: text_editor::Content::with_text(&persona_or_default.system_prompt),
+            temperature: number(persona_or_default.parameters.temperature),
+            top_p: number(persona_or_default.parameters.top_p),
+            max_tokens: number(persona_or_default.parameters.max_tokens),
+            model_class: persona_or_default.model_class.unwrap_or_default(),
+            original: persona,
+        }
+    }
+
+    fn persona(&self) -> Option<Persona> {
+        let name = self.name.trim();
+        let system_prompt = self.system_prompt.text();
+
+        if name.is_empty() || system_prompt.trim().is_empty() {
+            return None;
+        }
+
+        let model_class = self.model_class.trim();
+
+        Some(Persona {
+            name: name.to_owned(),
+            description: self.description.trim().to_owned(),
+            system_prompt: system_prompt.trim().to_owned(),
+            parameters: Parameters {
+                temperature: self.temperature.trim().parse().ok(),
+                top_p: self.top_p.trim().parse().ok(),
+                max_tokens: self.max_tokens.trim().parse().ok(),
+            },
+            model_class: (!model_class.is_empty()).then(|| model_class.to_owned()),
+        })
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let title = text(match &self.original {
+            Some(original) => format!("Edit {}", original.name),
+            None => "New Persona".to_owned(),
+        })
+        .size(20);
+
+        let parameters = row![
+            field(
+                "Temperature",
+                text_input("Default", &self.temperature).on_input(Message::TemperatureChanged)
+            ),
+            field(
+                "Top P",
+                text_input("Default", &self.top_p).on_input(Message::TopPChanged)
+            ),
+            field(
+                "Max Tokens",
+                text_input("Default", &self.max_tokens).on_input(Message::MaxTokensChanged)
+            ),
+            field(
+                "Model Class",
+                text_input("e.g. coder", &self.model_class).on_input(Message::ModelClassChanged)
+            ),
+        ]
+        .spacing(10);
+
+        let delete = self
+            .original
+            .as_ref()
+            .filter(|original| !original.is_builtin())
+            .map(|_| {
+                tip(
+                    button(icon::trash())
+                        .on_press(Message::Delete)
+                        .style(button::danger),
+                    "Delete",
+                    tip::Position::Top,
+                )
+            });
+
+        let actions = row![
+            delete,
+            horizontal_space(),
+            button(text("Cancel"))
+                .on_press(Message::Cancel)
+                .style(button::secondary),
+            button(text("Save"))
+                .on_press_maybe(self.persona().map(|_| Message::Save))
+                .style(button::primary),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        scrollable(
+            column![
+                title,
+                field(
+                    "Name",
+                    text_input("Name", &self.name).on_input(Message::NameChanged)
+                ),
+                field(
+                    "Description",
+                    text_input("What is this persona good at?", &self.description)
+                        .on_input(Message::DescriptionChanged)
+                ),
+                field(
+                    "System Prompt",
+                    text_editor(&self.system_prompt)
+                        .placeholder("You are a helpful assistant.")
+                        .on_action(Message::SystemPromptEdited)
+                        .height(200)
+                ),
+                parameters,
+                actions,
+            ]
+            .spacing(20)
+            .max_width(700),
+        )
+        .height(Fill)
+        .into()
+    }
+}
+
+fn number(number: Option<impl ToString>) -> String {
+    number.as_ref().map(ToString::to_string).unwrap_or_default()
+}
+
+fn field<'a>(label: &'a str, input: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    column![text(label).size(12).style(text::secondary), input.into()]
+        .spacing(5)
+        .into()
+}
+
+fn card(index: usize, persona: &Persona) -> Element<'_, Message> {
+    let Parameters {
+        temperature,
+        top_p,
+        max_tokens,
+    } = persona.parameters;
+
+    let details = [
+        temperature.map(|temperature| format!("temp {temperature}")),
+        top_p.map(|top_p| format!("top_p {top_p}")),
+        max_tokens.map(|max_tokens| format!("max {max_tokens}")),
+        persona.model_class.clone(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" · ");
+
+    let actions = row![
+        button(text("Start Chat").size(14))
+            .on_press(Message::Start(index))
+            .style(button::primary),
+        horizontal_space(),
+        tip(
+            button(icon::download())
+                .on_press(Message::Export(index))
+                .style(button::text),
+            "Export",
+            tip::Position::Top,
+        ),
+        tip(
+            button(icon::sliders())
+                .on_press(Message::Edit(index))
+                .style(button::text),
+            "Edit",
+            tip::Position::Top,
+        ),
+    ]
+    .spacing(5)
+    .align_y(Center);
+
+    container(
+        column![
+            row![icon::user(), text(&persona.name).size(18)]
+                .spacing(10)
+                .align_y(Center),
+            text(&persona.description).size(14).style(text::secondary),
+            text(details).font(Font::MONOSPACE).size(12),
+            actions,
+        ]
+        .spacing(10),
+    )
+    .width(280)
+    .padding(15)
+    .style(container::bordered_box)
+    .into()
+}
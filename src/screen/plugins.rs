@@ -0,0 +1,259 @@
+use crate::core::plugin::{self, Plugin};
+use crate::core::Error;
+use crate::icon;
+use crate::widget::{sidebar, tip};
+
+use iced::widget::{
+    button, center, column, container, horizontal_space, row, scrollable, text, toggler, value,
+};
+use iced::{Center, Element, Fill, Font, Task};
+
+pub struct Plugins {
+    plugins: Vec<Plugin>,
+    error: Option<Error>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Listed(Result<Vec<Plugin>, Error>),
+    Install,
+    InstallPicked(Option<rfd::FileHandle>),
+    Installed(Result<Plugin, Error>),
+    Toggle(usize, bool),
+    Toggled(Result<Plugin, Error>),
+    Uninstall(usize),
+    Uninstalled(String, Result<(), Error>),
+}
+
+pub enum Action {
+    None,
+    Run(Task<Message>),
+    Changed,
+}
+
+impl Plugins {
+    pub fn new() -> (Self, Task<Message>) {
+        (
+            Self {
+                plugins: Vec::new(),
+                error: None,
+            },
+            Task::perform(Plugin::list(), Message::Listed),
+        )
+    }
+
+    pub fn title(&self) -> &str {
+        "Plugins"
+    }
+
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::Listed(Ok(plugins)) => {
+                self.plugins = plugins;
+
+                Action::None
+            }
+            Message::Install => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Install plugin...")
+                    .pick_folder(),
+                Message::InstallPicked,
+            )),
+            Message::InstallPicked(Some(folder)) => Action::Run(Task::perform(
+                Plugin::install(folder.path().to_path_buf()),
+                Message::Installed,
+            )),
+            Message::InstallPicked(None) => Action::None,
+            Message::Installed(Ok(plugin)) => {
+                self.plugins.retain(|other| other.name() != plugin.name());
+                self.plugins.push(plugin);
+                self.plugins.sort_by(|a, b| a.name().cmp(b.name()));
+                self.error = None;
+
+                Action::None
+            }
+            Message::Toggle(index, is_enabled) => {
+                let Some(plugin) = self.plugins.get(index) else {
+                    return Action::None;
+                };
+
+                Action::Run(Task::perform(
+                    plugin.clone().set_enabled(is_enabled),
+                    Message::Toggled,
+                ))
+            }
+            Message::Toggled(Ok(plugin)) => {
+                if let Some(current) = self
+                    .plugins
+                    .iter_mut()
+                    .find(|current| current.name() == plugin.name())
+                {
+                    *current = plugin;
+                }
+
+                Action::Changed
+            }
+            Message::Uninstall(index) => {
+                let Some(plugin) = self.plugins.get(index) else {
+                    return Action::None;
+                };
+
+                let name = plugin.name().to_owned();
+
+                Action::Run(Task::perform(plugin.clone().uninstall(), move |result| {
+                    Message::Uninstalled(name.clone(), result)
+                }))
+            }
+            Message::Uninstalled(name, Ok(())) => {
+                self.plugins.retain(|plugin| plugin.name() != name);
+
+                Action::Changed
+            }
+            Message::Listed(Err(error))
+            | Message::Installed(Err(error))
+            | Message::Toggled(Err(error))
+            | Message::Uninstalled(_, Err(error)) => {
+                self.error = Some(dbg!(error));
+
+                Action::None
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let error = self
+            .error
+            .as_ref()
+            .map(|error| value(error).font(Font::MONOSPACE).style(text::danger));
+
+        let content: Element<'_, _> = if self.plugins.is_empty() {
+            center(
+                column![
+                    text("No plugins installed").size(20),
+                    text("Plugins add tools, slash commands, and providers to icebreaker.")
+                        .style(text::secondary),
+                    button("Install...").on_press(Message::Install),
+                ]
+                .spacing(10)
+                .align_x(Center),
+            )
+            .into()
+        } else {
+            scrollable(
+                row(self
+                    .plugins
+                    .iter()
+                    .enumerate()
+                    .map(|(index, plugin)| card(index, plugin)))
+                .spacing(10)
+                .wrap(),
+            )
+            .height(Fill)
+            .into()
+        };
+
+        column![error, content].spacing(10).into()
+    }
+
+    pub fn sidebar(&self) -> Element<'_, Message> {
+        let header = sidebar::header("Plugins", Some((icon::plus(), Message::Install)));
+
+        let commands = column(
+            self.plugins
+                .iter()
+                .filter(|plugin| plugin.is_enabled)
+                .flat_map(|plugin| &plugin.manifest.commands)
+                .map(|command| {
+                    column![
+                        text(format!("/{}", command.name))
+                            .font(Font::MONOSPACE)
+                            .size(14),
+                        text(&command.description).size(12).style(text::secondary),
+                    ]
+                    .spacing(2)
+                    .into()
+                }),
+        )
+        .spacing(10);
+
+        column![
+            header,
+            text("Slash commands").size(12).style(text::secondary),
+            scrollable(commands).height(Fill),
+            text!("Plugin API v{}", plugin::API_VERSION)
+                .font(Font::MONOSPACE)
+                .size(12)
+                .style(text::secondary),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
+
+fn card(index: usize, plugin: &Plugin) -> Element<'_, Message> {
+    let manifest = &plugin.manifest;
+
+    let count = |amount: usize, noun: &str| match amount {
+        0 => None,
+        1 => Some(format!("1 {noun}")),
+        _ => Some(format!("{amount} {noun}s")),
+    };
+
+    let details = [
+        count(manifest.tools.len(), "tool"),
+        count(manifest.commands.len(), "command"),
+        count(manifest.providers.len(), "provider"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" · ");
+
+    let status: Element<'_, _> = if plugin.is_compatible() {
+        toggler(plugin.is_enabled)
+            .label("Enabled")
+            .on_toggle(move |is_enabled| Message::Toggle(index, is_enabled))
+            .into()
+    } else {
+        text!("Built for API v{}", manifest.api_version)
+            .size(14)
+            .style(text::danger)
+            .into()
+    };
+
+    let actions = row![
+        status,
+        horizontal_space(),
+        tip(
+            button(icon::trash())
+                .on_press(Message::Uninstall(index))
+                .style(button::text),
+            "Uninstall",
+            tip::Position::Top,
+        ),
+    ]
+    .spacing(5)
+    .align_y(Center);
+
+    container(
+        column![
+            row![
+                text(&manifest.name).size(18),
+                text(&manifest.version)
+                    .font(Font::MONOSPACE)
+                    .size(12)
+                    .style(text::secondary),
+            ]
+            .spacing(10)
+            .align_y(Center),
+            text(&manifest.description).size(14).style(text::secondary),
+            text(details).font(Font::MONOSPACE).size(12),
+            actions,
+        ]
+        .spacing(10),
+    )
+    .width(280)
+    .padding(15)
+    .style(container::bordered_box)
+    .into()
+}
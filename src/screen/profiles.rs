@@ -0,0 +1,179 @@
+use crate::core::profile::{self, Profiles as Data};
+use crate::core::Error;
+use crate::icon;
+use crate::widget::sidebar;
+
+use iced::font;
+use iced::widget::{
+    button, center, column, container, horizontal_space, row, scrollable, text, text_input,
+    toggler, value,
+};
+use iced::{Center, Element, Fill, Font, Task};
+
+pub struct Profiles {
+    profiles: Data,
+    name: String,
+    error: Option<Error>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Select(String),
+    NameChanged(String),
+    Create,
+    Delete(String),
+    ToggleAskAtStartup(bool),
+    Saved(Result<Data, Error>),
+}
+
+pub enum Action {
+    None,
+    Select(String),
+    Run(Task<Message>),
+}
+
+impl Profiles {
+    pub fn new() -> (Self, Task<Message>) {
+        (
+            Self {
+                profiles: Data::fetch(),
+                name: String::new(),
+                error: None,
+            },
+            Task::none(),
+        )
+    }
+
+    pub fn title(&self) -> &str {
+        "Profiles"
+    }
+
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::Select(name) => Action::Select(name),
+            Message::NameChanged(name) => {
+                self.name = name;
+
+                Action::None
+            }
+            Message::Create => Action::Run(Task::perform(
+                self.profiles.clone().create(self.name.clone()),
+                Message::Saved,
+            )),
+            Message::Delete(name) => Action::Run(Task::perform(
+                self.profiles.clone().delete(name),
+                Message::Saved,
+            )),
+            Message::ToggleAskAtStartup(ask_at_startup) => {
+                self.profiles.ask_at_startup = ask_at_startup;
+
+                Action::Run(Task::perform(self.profiles.clone().save(), Message::Saved))
+            }
+            Message::Saved(Ok(profiles)) => {
+                self.profiles = profiles;
+                self.name.clear();
+                self.error = None;
+
+                Action::None
+            }
+            Message::Saved(Err(error)) => {
+                self.error = Some(error);
+
+                Action::None
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Choose a profile")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Each profile has its own settings, API keys, chats, and bookmarks. \
+                Models are shared by all of them."
+            )
+            .style(text::secondary),
+        ]
+        .spacing(10);
+
+        let profiles = column(self.profiles.names.iter().map(|name| {
+            let is_current = *name == self.profiles.current;
+            let can_delete = !is_current && name != profile::DEFAULT;
+
+            row![
+                button(text(name).font(Font::MONOSPACE).width(Fill))
+                    .on_press(Message::Select(name.clone()))
+                    .style(if is_current {
+                        button::primary
+                    } else {
+                        button::secondary
+                    })
+                    .width(Fill),
+                can_delete.then(|| {
+                    button(icon::trash())
+                        .on_press(Message::Delete(name.clone()))
+                        .style(button::danger)
+                }),
+            ]
+            .spacing(10)
+            .align_y(Center)
+            .into()
+        }))
+        .spacing(10);
+
+        let create = row![
+            text_input("New profile name...", &self.name)
+                .on_input(Message::NameChanged)
+                .on_submit(Message::Create),
+            button(icon::plus())
+                .on_press_maybe((!self.name.trim().is_empty()).then_some(Message::Create)),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        let settings = row![
+            toggler(self.profiles.ask_at_startup)
+                .label("Ask at startup")
+                .on_toggle(Message::ToggleAskAtStartup),
+            horizontal_space(),
+            text("or start with --profile <name>")
+                .font(Font::MONOSPACE)
+                .size(12)
+                .style(text::secondary),
+        ]
+        .align_y(Center);
+
+        center(scrollable(
+            container(
+                column![
+                    header,
+                    profiles,
+                    create,
+                    settings,
+                    self.error
+                        .as_ref()
+                        .map(|error| value(error).font(Font::MONOSPACE).style(text::danger)),
+                ]
+                .spacing(20),
+            )
+            .max_width(500)
+            .padding(20),
+        ))
+        .into()
+    }
+
+    pub fn sidebar(&self) -> Element<'_, Message> {
+        column![
+            sidebar::header("Profiles", None),
+            text!("Using {}", self.profiles.current)
+                .font(Font::MONOSPACE)
+                .style(text::secondary),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
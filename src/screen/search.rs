@@ -1,55 +1,107 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
-use crate::core::model;
+use crate::core::conversion::{self, Quantization};
+use crate::core::desktop;
+use crate::core::job;
+use crate::core::model::{self, hugging_face};
+use crate::core::session;
 use crate::core::{Error, HFModel};
+use crate::debounce::Debounce;
+use crate::download;
 use crate::model::Model;
 use crate::screen::search;
-use crate::widget::sidebar;
+use crate::widget::context_menu::{self, ContextMenu};
+use crate::widget::reorder::{self, Reorder};
+use crate::widget::{sidebar, tip};
 use crate::{icon, APIAccess};
 
 use icebreaker_core::model::{EndpointId, FileAndAPI, Library, ModelOnline, ModelsMap};
 use icebreaker_core::Settings;
 use iced::border;
+use iced::clipboard;
 use iced::font;
-use iced::time::Duration;
+use iced::task;
+use iced::time::{self, Duration};
 use iced::widget::{
     self, button, center, center_x, column, container, grid, horizontal_rule, horizontal_space,
-    right, row, rule, scrollable, text, text_input, value, Text,
+    pick_list, right, row, rule, scrollable, text, text_input, value, Text,
 };
-use iced::{Center, Element, Fill, Font, Right, Shrink, Task, Theme};
+use iced::{Center, Element, Fill, Font, Function, Right, Shrink, Subscription, Task, Theme};
 use iced_palace::widget::ellipsized_text;
 
 use function::Binary;
 use log::info;
+use thiserror::capture;
+
+#[cfg(test)]
+mod tests;
 
 pub struct Search {
     pub models: ModelsMap,
     search: String,
-    search_temperature: usize,
+    query: String,
+    debounce: Debounce,
+    hf_search: Option<task::Handle>,
+    backoff: Option<Duration>,
+    status_checks: Option<model::StatusProgress>,
+    status_task: Option<task::Handle>,
+    status_max_age: Duration,
+    reorder: Reorder,
+    menu: ContextMenu<Target>,
     is_searching: bool,
     mode: Mode,
     show_filters: bool,
     show_local_models: bool,
     show_online_models: bool,
+    hub: model::Hub,
+    quantization: Quantization,
+    is_conversion_understood: bool,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     ModelsListed(Result<ModelsMap, Error>),
+    HFModelsFound(Vec<HFModel>),
+    HFModelsListed(Result<Vec<HFModel>, Error>),
     SearchChanged(String),
     SearchCooled,
     Select(model::EndpointId),
     HFDetailsFetched(model::EndpointId, Result<model::Details, Error>),
     FilesListed(model::EndpointId, Result<model::Files, Error>),
+    SourceFetched(model::EndpointId, Result<Option<conversion::Source>, Error>),
+    SelectQuantization(Quantization),
+    UnderstandConversion(bool),
+    Convert(conversion::Source),
     Boot(model::FileAndAPI),
+    Download(Vec<model::File>, download::Then),
     Back,
     ToggleFilters,
     ToggleLocalModels(bool),
     ToggleOnlineModels(bool),
+    SelectHub(model::Hub),
     Bookmark(model::EndpointId, bool),
+    AddFallback(model::EndpointId, model::EndpointId),
+    RemoveFallback(model::EndpointId, usize),
     CheckStatus { bookmarks: bool, first_n: usize },
+    Reorder(reorder::Drag),
+    Menu(context_menu::Event<Target>),
+    CheckModel(model::EndpointId),
+    CopyId(model::EndpointId),
+    OpenOnHF(model::EndpointId),
+    ShowInFolder(model::EndpointId),
+    Delete(model::EndpointId),
+    Opened(Result<(), Error>),
+    StatusChecking(model::StatusProgress),
+    StatusChecked(Result<(), Error>),
+    CheckRateLimit,
+}
+
+#[derive(Debug, Clone)]
+pub enum Target {
+    Model(model::EndpointId),
+    Bookmark(model::EndpointId),
 }
 
 pub enum Mode {
@@ -58,6 +110,7 @@ pub enum Mode {
         model: model::EndpointId,
         details: Option<model::Details>,
         files: Option<model::Files>,
+        source: Option<conversion::Source>,
     },
     APIDetails {
         model: model::EndpointId,
@@ -67,27 +120,46 @@ pub enum Mode {
 
 pub enum Action {
     None,
+    Error(Error),
     Boot(model::FileAndAPI),
+    Download(Vec<model::File>, download::Then),
+    Convert(conversion::Source, Quantization),
     Run(Task<Message>),
     Bookmark(model::EndpointId, bool),
-    Wrap(Message),
+    MoveBookmark { from: usize, to: usize },
+    Delete(model::EndpointId),
+    Failover(model::EndpointId, Vec<ModelOnline>),
 }
 
 impl Search {
-    pub fn new(lib: Arc<Library>) -> (Self, Task<Message>) {
-        let k = Self {
+    pub fn new(lib: Arc<Library>, status_max_age: Duration) -> (Self, Task<Message>) {
+        let mut k = Self {
             models: HashMap::new(),
             search: String::new(),
-            search_temperature: 0,
+            query: String::new(),
+            debounce: Debounce::new(Duration::from_millis(200)),
+            hf_search: None,
+            backoff: None,
+            status_checks: None,
+            status_task: None,
+            status_max_age,
+            reorder: Reorder::default(),
+            menu: ContextMenu::default(),
             is_searching: true,
             mode: Mode::Search,
             show_filters: false,
             show_local_models: false,
             show_online_models: true,
+            hub: model::Hub::default(),
+            quantization: Quantization::default(),
+            is_conversion_understood: false,
         };
+        let hf_search = k.search_hf(String::new());
+
         (
             k,
             Task::batch([
+                hf_search,
                 Task::perform(Model::list(lib), Message::ModelsListed).chain(Task::done(
                     Message::CheckStatus {
                         bookmarks: true,
@@ -99,6 +171,97 @@ impl Search {
         )
     }
 
+    fn search_hf(&mut self, query: String) -> Task<Message> {
+        let (task, handle) = Task::sip(
+            HFModel::search(self.hub, query),
+            Message::HFModelsFound,
+            Message::HFModelsListed,
+        )
+        .abortable();
+
+        // Dropping the previous handle cancels any search still in flight
+        self.hf_search = Some(handle.abort_on_drop());
+
+        task
+    }
+
+    fn hub(&self, id: &model::EndpointId) -> model::Hub {
+        match self.models.get(id) {
+            Some(Model::HF(model)) => model.hub,
+            _ => self.hub,
+        }
+    }
+
+    fn check_status(&mut self, models: Vec<Model>, concurrency: usize) -> Task<Message> {
+        let title = match models.as_slice() {
+            [model] => model.endpoint_id().to_string(),
+            models => format!("{} models", models.len()),
+        };
+
+        let (task, handle) = Task::sip(
+            job::run(
+                job::Kind::StatusCheck,
+                title,
+                Model::update_statuses(models, concurrency),
+            ),
+            Message::StatusChecking,
+            Message::StatusChecked,
+        )
+        .abortable();
+
+        self.status_task = Some(handle.abort_on_drop());
+
+        task
+    }
+
+    pub fn subscription(&self, interval: Option<Duration>) -> Subscription<Message> {
+        let reorder = self.reorder.subscription().map(Message::Reorder);
+        let menu = self.menu.subscription().map(Message::Menu);
+
+        // Requests to Hugging Face may be waiting out its rate limit
+        let is_fetching = self.hf_search.is_some()
+            || self.backoff.is_some()
+            || matches!(self.mode, Mode::HFDetails { details: None, .. });
+
+        let rate_limit = if is_fetching {
+            time::every(Duration::from_secs(1)).map(|_| Message::CheckRateLimit)
+        } else {
+            Subscription::none()
+        };
+
+        let Some(interval) = interval else {
+            return Subscription::batch([reorder, menu, rate_limit]);
+        };
+
+        Subscription::batch([
+            reorder,
+            menu,
+            rate_limit,
+            time::every(interval).map(|_| Message::CheckStatus {
+                bookmarks: true,
+                first_n: 0,
+            }),
+        ])
+    }
+
+    pub fn snapshot(&self) -> session::Search {
+        session::Search {
+            query: self.search.clone(),
+            show_filters: self.show_filters,
+            show_local_models: self.show_local_models,
+            show_online_models: self.show_online_models,
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: session::Search) {
+        self.debounce.cancel();
+        self.query = snapshot.query.clone();
+        self.search = snapshot.query;
+        self.show_filters = snapshot.show_filters;
+        self.show_local_models = snapshot.show_local_models;
+        self.show_online_models = snapshot.show_online_models;
+    }
+
     pub fn title(&self) -> &str {
         match &self.mode {
             Mode::Search => "Models",
@@ -110,69 +273,109 @@ impl Search {
         }
     }
 
-    pub fn update(
-        &mut self,
-        message: Message,
-        lib: &mut Library,
-        settings: &mut Settings,
-    ) -> Action {
+    pub fn changed(&mut self, lib: Arc<Library>, change: model::Change) -> Task<Message> {
+        match change {
+            model::Change::Scanned | model::Change::Providers => {
+                Task::perform(Model::list(lib), Message::ModelsListed)
+            }
+            model::Change::Bookmarks | model::Change::Files | model::Change::Failover => {
+                Task::none()
+            }
+        }
+    }
+
+    pub fn update(&mut self, message: Message, lib: &Library, settings: &mut Settings) -> Action {
         match message {
             Message::ModelsListed(Ok(models)) => {
-                self.models = models;
+                // Hugging Face models may have been found already
+                self.models.extend(models);
                 self.is_searching = false;
 
                 Action::None
             }
-            Message::ModelsListed(Err(error)) => {
-                log::error!("{error}");
+            // A newer search took over
+            Message::HFModelsListed(Err(Error::Cancelled(..))) => Action::None,
+            Message::ModelsListed(Err(error)) | Message::HFModelsListed(Err(error)) => {
+                Action::Error(error)
+            }
+            Message::HFModelsFound(models) => {
+                for model in models {
+                    let _ = self
+                        .models
+                        .entry(model.endpoint_id())
+                        .or_insert(Model::HF(model));
+                }
+
+                Action::None
+            }
+            Message::CheckRateLimit => {
+                self.backoff = hugging_face::backoff();
+
+                Action::None
+            }
+            Message::StatusChecking(progress) => {
+                self.status_checks = Some(progress);
+
+                Action::None
+            }
+            Message::StatusChecked(result) => {
+                self.status_checks = None;
+                self.status_task = None;
+
+                match result {
+                    Ok(()) | Err(Error::Cancelled(..)) => Action::None,
+                    Err(error) => Action::Error(error),
+                }
+            }
+            Message::HFModelsListed(Ok(_)) => {
+                self.hf_search = None;
 
                 Action::None
             }
             Message::SearchChanged(search) => {
                 self.search = search;
-                self.search_temperature += 1;
 
-                Action::None
+                Action::Run(self.debounce.run(Message::SearchCooled))
             }
             Message::SearchCooled => {
-                self.search_temperature = self.search_temperature.saturating_sub(1);
+                self.query = self.search.clone();
 
-                Action::None
+                Action::Run(self.search_hf(self.query.trim().to_owned()))
             }
-            Message::Select(id) => {
-                let model = self.models.get(&id);
-                if let Some(model) = model {
-                    match model {
-                        Model::HF(_) => {
-                            self.mode = Mode::HFDetails {
-                                model: id.clone(),
-                                details: None,
-                                files: None,
-                            };
-                            Action::Run(Task::batch([
-                                Task::perform(
-                                    model::Details::fetch(id.clone()),
-                                    Message::HFDetailsFetched.with(id.clone()),
-                                ),
-                                Task::perform(
-                                    model::File::list(id.slash_id().clone()),
-                                    Message::FilesListed.with(id.clone()),
-                                ),
-                            ]))
-                        }
-                        Model::API(model_online) => {
-                            self.mode = Mode::APIDetails {
-                                model: id.clone(),
-                                model_online: model_online.clone(),
-                            };
-                            Action::None
-                        }
-                    }
-                } else {
-                    log::warn!("select {:?}", &id);
+            Message::Select(id) => match self.models.get(&id) {
+                Some(Model::API(model_online)) => {
+                    self.mode = Mode::APIDetails {
+                        model: id.clone(),
+                        model_online: model_online.clone(),
+                    };
                     Action::None
                 }
-            }
+                // Local models not found yet may still exist in Hugging Face (e.g. deep links)
+                Some(Model::HF(_)) | None if matches!(id, model::EndpointId::Local(_)) => {
+                    self.mode = Mode::HFDetails {
+                        model: id.clone(),
+                        details: None,
+                        files: None,
+                        source: None,
+                    };
+                    self.is_conversion_understood = false;
+
+                    let hub = self.hub(&id);
+
+                    Action::Run(Task::batch([
+                        Task::perform(
+                            model::Details::fetch(id.clone(), hub),
+                            Message::HFDetailsFetched.with(id.clone()),
+                        ),
+                        Task::perform(
+                            model::File::list(id.slash_id().clone(), hub),
+                            Message::FilesListed.with(id.clone()),
+                        ),
+                    ]))
+                }
+                // The model may be gone since (e.g. its provider was removed)
+                _ => Action::Error(Error::ModelNotFound(id.to_string(), capture!())),
+            },
             Message::HFDetailsFetched(new_model, Ok(new_details)) => {
                 match &mut self.mode {
                     Mode::HFDetails { model, details, .. } if model == &new_model => {
@@ -184,26 +387,57 @@ impl Search {
                 Action::None
             }
             Message::FilesListed(new_model, Ok(new_files)) => {
+                let hub = self.hub(&new_model);
+
                 match &mut self.mode {
                     Mode::HFDetails { model, files, .. } if model == &new_model => {
+                        // Only Hugging Face repositories can be converted for now
+                        let is_convertible = new_files.is_empty() && hub == model::Hub::HuggingFace;
+
                         *files = Some(new_files);
+
+                        if is_convertible {
+                            return Action::Run(Task::perform(
+                                conversion::Source::fetch(new_model.slash_id().clone()),
+                                Message::SourceFetched.with(new_model),
+                            ));
+                        }
                     }
                     _ => {}
                 }
 
                 Action::None
             }
+            Message::SourceFetched(new_model, Ok(new_source)) => {
+                if let Mode::HFDetails { model, source, .. } = &mut self.mode {
+                    if model == &new_model {
+                        *source = new_source;
+                    }
+                }
+
+                Action::None
+            }
+            Message::SelectQuantization(quantization) => {
+                self.quantization = quantization;
+
+                Action::None
+            }
+            Message::UnderstandConversion(is_understood) => {
+                self.is_conversion_understood = is_understood;
+
+                Action::None
+            }
+            Message::Convert(source) => Action::Convert(source, self.quantization),
             Message::Back => {
                 self.mode = Mode::Search;
 
                 Action::Run(widget::focus_next())
             }
             Message::Boot(file) => Action::Boot(file),
-            Message::HFDetailsFetched(_, Err(error)) | Message::FilesListed(_, Err(error)) => {
-                log::error!("{error}");
-
-                Action::None
-            }
+            Message::Download(files, then) => Action::Download(files, then),
+            Message::HFDetailsFetched(_, Err(error))
+            | Message::FilesListed(_, Err(error))
+            | Message::SourceFetched(_, Err(error)) => Action::Error(error),
             Message::ToggleFilters => {
                 self.show_filters = !self.show_filters;
                 Action::None
@@ -216,18 +450,93 @@ impl Search {
                 self.show_online_models = t;
                 Action::None
             }
+            Message::SelectHub(hub) => {
+                self.hub = hub;
+
+                // The same repositories are often mirrored in every hub
+                self.models
+                    .retain(|_, model| !matches!(model, Model::HF(model) if model.hub != hub));
+
+                Action::Run(self.search_hf(self.query.trim().to_owned()))
+            }
             Message::Bookmark(id, bool) => {
                 // Add model to local registry of favorited models
                 log::info!("Bookmarking API model {:?}", id);
-                let ap = self.models.get(&id).unwrap();
-                let ap = match ap {
-                    Model::API(ap) => ap,
-                    _ => unreachable!(),
+
+                // The model may be gone since (e.g. its provider was removed)
+                if bool && self.online(lib, &id).is_none() {
+                    return Action::Error(Error::ModelNotFound(id.to_string(), capture!()));
+                }
+
+                Action::Bookmark(id, bool)
+            }
+            Message::AddFallback(primary, fallback) => {
+                let Some(model) = self.online(lib, &fallback).cloned() else {
+                    return Action::None;
                 };
 
-                Action::Bookmark(ap.endpoint_id.clone(), bool)
+                let mut fallbacks = lib.fallbacks(&primary).to_vec();
+                fallbacks.push(model);
+
+                Action::Failover(primary, fallbacks)
+            }
+            Message::RemoveFallback(primary, index) => {
+                let mut fallbacks = lib.fallbacks(&primary).to_vec();
+
+                if index < fallbacks.len() {
+                    let _ = fallbacks.remove(index);
+                }
+
+                Action::Failover(primary, fallbacks)
+            }
+            Message::Reorder(drag) => match self.reorder.update(drag) {
+                reorder::Action::None => Action::None,
+                reorder::Action::Clicked(index) => match lib.bookmarks.get(index).cloned() {
+                    Some(id) => self.update(Message::Select(id), lib, settings),
+                    None => Action::None,
+                },
+                reorder::Action::Moved { from, to } => Action::MoveBookmark { from, to },
+            },
+            Message::Menu(event) => {
+                self.menu.update(event);
+
+                Action::None
+            }
+            Message::CheckModel(id) => match self.models.get(&id).cloned() {
+                Some(model) => Action::Run(self.check_status(vec![model], settings.status_checks)),
+                None => Action::None,
+            },
+            Message::CopyId(id) => Action::Run(clipboard::write(id.slash_id().0.clone())),
+            Message::OpenOnHF(id) => Action::Run(Task::perform(
+                desktop::open(self.hub(&id).page(id.slash_id())),
+                Message::Opened,
+            )),
+            Message::ShowInFolder(id) => match lib.files.get(&id) {
+                Some(model::FileOrAPI::File(file)) => Action::Run(Task::perform(
+                    desktop::reveal(lib.directory_of(file).path().join(file.relative_path())),
+                    Message::Opened,
+                )),
+                _ => Action::None,
+            },
+            Message::Opened(Ok(())) => Action::None,
+            Message::Opened(Err(error)) => Action::Error(error),
+            Message::Delete(id) => Action::Delete(id),
+            Message::CheckStatus { bookmarks, first_n } => {
+                let mut scheduled_ids = HashSet::new();
+
+                if bookmarks {
+                    scheduled_ids.extend(lib.bookmarks.iter().cloned());
+                }
+
+                scheduled_ids.extend(self.models.keys().take(first_n).cloned());
+
+                let models = scheduled_ids
+                    .iter()
+                    .filter_map(|id| self.models.get(id).cloned())
+                    .collect();
+
+                Action::Run(self.check_status(models, settings.status_checks))
             }
-            msg => Action::Wrap(msg),
         }
     }
 
@@ -238,7 +547,14 @@ impl Search {
                 model,
                 details,
                 files,
-            } => self.details(model.slash_id(), details.as_ref(), files.as_ref(), library),
+                source,
+            } => self.details(
+                model.slash_id(),
+                details.as_ref(),
+                files.as_ref(),
+                source.as_ref(),
+                library,
+            ),
             Mode::APIDetails {
                 model,
                 model_online,
@@ -303,21 +619,29 @@ impl Search {
                 .label("Online Models".to_string())
                 .on_toggle(Message::ToggleOnlineModels);
 
-            let check_button = button("Check Status")
-                .on_press(Message::CheckStatus {
+            let check_button = match self.status_checks {
+                Some(progress) => button(text!(
+                    "Checking Status {}/{}",
+                    progress.checked,
+                    progress.total
+                )),
+                None => button("Check Status").on_press(Message::CheckStatus {
                     bookmarks: false,
                     first_n: 20,
-                })
-                .style(button::secondary);
+                }),
+            }
+            .style(button::secondary);
+
+            let hub = pick_list(model::Hub::ALL, Some(self.hub), Message::SelectHub);
 
-            container(column![local_toggle, online_toggle, check_button].spacing(10))
+            container(column![local_toggle, online_toggle, hub, check_button].spacing(10))
                 .padding(10)
                 .style(container::bordered_box)
         });
 
         let models: Element<'_, _> = {
             let search_terms: Vec<_> = self
-                .search
+                .query
                 .trim()
                 .split(' ')
                 .map(str::to_lowercase)
@@ -327,7 +651,7 @@ impl Search {
                 .models
                 .values()
                 .filter(|model| {
-                    self.search.is_empty()
+                    self.query.is_empty()
                         || search_terms.iter().all(|term| {
                             model.slash_id().name().to_lowercase().contains(term)
                                 || model.slash_id().author().to_lowercase().contains(term)
@@ -338,16 +662,24 @@ impl Search {
             if filtered_models.peek().is_none() {
                 center(text("No models found")).into()
             } else {
-                let cards = grid(filtered_models.map(model_card))
-                    .spacing(10)
-                    .fluid(650)
-                    .height(Shrink);
+                let cards = grid(filtered_models.map(|model| {
+                    self.menu.area(
+                        model_card(model, self.status_max_age),
+                        Target::Model(model.endpoint_id()),
+                        Message::Menu,
+                    )
+                }))
+                .spacing(10)
+                .fluid(650)
+                .height(Shrink);
 
                 scrollable(cards).height(Fill).spacing(10).into()
             }
         };
 
-        column![search_row, filter_panel, models].spacing(10).into()
+        column![search_row, self.rate_limit(), filter_panel, models]
+            .spacing(10)
+            .into()
     }
 
     pub fn details<'a>(
@@ -355,6 +687,7 @@ impl Search {
         model: &'a model::Id,
         details: Option<&'a model::Details>,
         files: Option<&'a model::Files>,
+        source: Option<&'a conversion::Source>,
         library: &'a model::Library,
     ) -> Element<'a, Message> {
         use iced::widget::Text;
@@ -397,7 +730,9 @@ impl Search {
 
             let badges = details.map(|details| {
                 row![
-                    badge(icon::sliders(), value(details.parameters)),
+                    details
+                        .parameters
+                        .map(|parameters| badge(icon::sliders(), value(parameters))),
                     details
                         .architecture
                         .as_ref()
@@ -413,13 +748,43 @@ impl Search {
                 .spacing(10)
             });
 
-            column![title, badges].spacing(10).align_x(Center)
+            let id = model::EndpointId::Local(model.clone());
+            let is_downloaded = matches!(library.files.get(&id), Some(model::FileOrAPI::File(_)));
+
+            let links = row![
+                button(
+                    row![
+                        icon::globe().size(12),
+                        text(self.hub(&id).to_string()).size(12)
+                    ]
+                    .spacing(5)
+                    .align_y(Center)
+                )
+                .on_press(Message::OpenOnHF(id.clone()))
+                .style(button::secondary),
+                is_downloaded.then(|| {
+                    button(
+                        row![
+                            icon::folder_open().size(12),
+                            text("Show in folder").size(12)
+                        ]
+                        .spacing(5)
+                        .align_y(Center),
+                    )
+                    .on_press(Message::ShowInFolder(id))
+                    .style(button::secondary)
+                }),
+            ]
+            .spacing(10);
+
+            column![title, badges, links].spacing(10).align_x(Center)
         };
 
         let download = files.map(|files| view_files(files, library));
+        let convert = source.map(|source| self.conversion(source));
 
         scrollable(center_x(
-            column![back, header, download]
+            column![back, header, self.rate_limit(), download, convert]
                 .spacing(20)
                 .max_width(600)
                 .clip(true),
@@ -428,6 +793,84 @@ impl Search {
         .into()
     }
 
+    fn rate_limit<'a>(&self) -> Option<Text<'a>> {
+        self.backoff.map(|backoff| {
+            text!(
+                "Hugging Face is limiting requests. Retrying in {}s...",
+                backoff.as_secs() + 1
+            )
+            .size(12)
+            .style(text::secondary)
+        })
+    }
+
+    fn conversion<'a>(&self, source: &'a conversion::Source) -> Element<'a, Message> {
+        let estimate = source.estimate(self.quantization);
+
+        let warning = |warning: String| {
+            row![
+                icon::clock().size(12).style(text::danger),
+                text(warning).size(12)
+            ]
+            .spacing(10)
+            .align_y(Center)
+        };
+
+        container(
+            column![
+                text("Convert to GGUF")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(16),
+                text(
+                    "This repository only holds safetensors weights. They can be downloaded \
+                    and converted with llama.cpp, once its tools are set up in the settings."
+                )
+                .size(12),
+                row![
+                    text("Quantization").size(12),
+                    horizontal_space(),
+                    pick_list(
+                        Quantization::ALL,
+                        Some(self.quantization),
+                        Message::SelectQuantization
+                    )
+                    .text_size(12),
+                ]
+                .align_y(Center),
+                column![
+                    warning(format!(
+                        "Needs about {} of free disk space for a {} file.",
+                        estimate.disk, estimate.output
+                    )),
+                    warning(format!(
+                        "Takes {}+ minutes besides the download, keeping your machine busy.",
+                        estimate.minutes
+                    )),
+                ]
+                .spacing(5),
+                row![
+                    widget::toggler(self.is_conversion_understood)
+                        .label("I understand")
+                        .text_size(12)
+                        .on_toggle(Message::UnderstandConversion),
+                    horizontal_space(),
+                    button(text("Convert").size(12)).on_press_maybe(
+                        self.is_conversion_understood
+                            .then(|| Message::Convert(source.clone()))
+                    ),
+                ]
+                .align_y(Center),
+            ]
+            .spacing(10),
+        )
+        .padding(10)
+        .style(container::bordered_box)
+        .into()
+    }
+
     pub fn details_api<'a>(
         &self,
         model_online: &'a ModelOnline,
@@ -483,6 +926,12 @@ impl Search {
                     ]
                     .spacing(10)
                 }),
+                model_online
+                    .speed
+                    .map(|speed| badge(icon::clock(), text!("{speed} tok/s"))),
+                model_online
+                    .function_calling
+                    .then(|| badge(icon::cubes(), text("Function calling"))),
             ]
             .align_y(Center)
             .spacing(10);
@@ -538,8 +987,60 @@ impl Search {
                 }
             });
 
+        let failover = {
+            let primary = &model_online.endpoint_id;
+            let fallbacks = library.fallbacks(primary);
+
+            let candidates: Vec<EndpointId> = library
+                .bookmarks
+                .iter()
+                .filter(|id| {
+                    *id != primary
+                        && !fallbacks
+                            .iter()
+                            .any(|fallback| &fallback.endpoint_id == *id)
+                        && self.online(library, id).is_some()
+                })
+                .cloned()
+                .collect();
+
+            column![
+                text("Failover").size(18).font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                }),
+                text(
+                    "When this endpoint fails or is rate-limited, \
+                    the reply is retried on these endpoints, in order."
+                )
+                .size(14)
+                .style(text::secondary),
+                column(fallbacks.iter().enumerate().map(|(index, fallback)| {
+                    row![
+                        text!("{}. {}", index + 1, fallback.endpoint_id)
+                            .font(Font::MONOSPACE)
+                            .width(Fill),
+                        button(icon::trash().size(12))
+                            .on_press(Message::RemoveFallback(primary.clone(), index))
+                            .style(button::text),
+                    ]
+                    .align_y(Center)
+                    .into()
+                }))
+                .spacing(5),
+                pick_list(
+                    candidates,
+                    None::<EndpointId>,
+                    Message::AddFallback.with(primary.clone())
+                )
+                .placeholder("Add a bookmarked endpoint...")
+                .width(Fill),
+            ]
+            .spacing(10)
+        };
+
         scrollable(center_x(
-            column![back, header, boot_button, install_button]
+            column![back, header, boot_button, install_button, failover]
                 .spacing(20)
                 .max_width(600)
                 .clip(true),
@@ -548,16 +1049,45 @@ impl Search {
         .into()
     }
 
-    pub fn sidebar<'a>(&'a self, library: &'a model::Library) -> Element<'a, Message> {
+    fn online<'a>(&'a self, library: &'a Library, id: &EndpointId) -> Option<&'a ModelOnline> {
+        match self.models.get(id) {
+            Some(Model::API(model)) => Some(model),
+            _ => match library.files.get(id) {
+                Some(model::FileOrAPI::API(model)) => Some(model),
+                _ => None,
+            },
+        }
+    }
+
+    pub fn sidebar<'a>(
+        &'a self,
+        library: &'a model::Library,
+        scanning: Option<model::ScanProgress>,
+    ) -> Element<'a, Message> {
         let header = sidebar::header("Models", Some((icon::search(), Message::Back)));
 
+        let scanning = scanning.map(|progress| {
+            row![
+                icon::refresh().size(10).style(text::secondary),
+                text!("Scanning library {}/{}", progress.scanned, progress.total)
+                    .size(12)
+                    .style(text::secondary),
+            ]
+            .spacing(5)
+            .align_y(Center)
+        });
+
         if library.bookmarks.is_empty() {
-            return column![header, center(icon::search().width(Fill).center())]
-                .spacing(10)
-                .into();
+            return column![
+                header,
+                scanning,
+                center(icon::search().width(Fill).center())
+            ]
+            .spacing(10)
+            .into();
         }
 
-        let library = column(library.bookmarks.iter().map(|id| {
+        let library = column(library.bookmarks.iter().enumerate().map(|(index, id)| {
             use model::*;
 
             let title: Element<'_, _> = match id {
@@ -593,25 +1123,33 @@ impl Search {
 
             let variant = match id {
                 EndpointId::Remote { api_type, id } => None,
-                EndpointId::Local(_) => library.files.get(id).map(|file| match file {
-                    FileOrAPI::File(file) => Some(file.variant().map(|variant| {
+                EndpointId::Local(_) => match library.files.get(id) {
+                    Some(FileOrAPI::File(file)) => file.variant().map(|variant| {
                         text(variant)
                             .font(Font::MONOSPACE)
                             .size(12)
                             .style(text::secondary)
-                    })),
-                    _ => unreachable!(),
-                }),
+                    }),
+                    _ => None,
+                },
             };
 
             let model = self.models.get(id);
             let state = match model {
-                Some(model::Model::API(api)) => status_icon(&api),
+                Some(model::Model::API(api)) => status_icon(api, self.status_max_age),
                 _ => None,
             };
+            let shared = library.is_shared(id).then(|| {
+                tip(
+                    icon::cubes().size(10).style(text::secondary),
+                    "Shared library",
+                    tip::Position::Right,
+                )
+            });
+
             let entry = column![
                 title,
-                row![author, state, horizontal_space(), variant]
+                row![author, state, shared, horizontal_space(), variant]
                     .spacing(5)
                     .align_y(Center)
             ]
@@ -626,16 +1164,91 @@ impl Search {
                 _ => false,
             };
 
-            sidebar::item(entry, is_active, || Message::Select(id.clone()))
+            self.menu.area(
+                sidebar::item(
+                    self.reorder.item(index, entry, Message::Reorder),
+                    is_active,
+                    || Message::Select(id.clone()),
+                ),
+                Target::Bookmark(id.clone()),
+                Message::Menu,
+            )
         }));
 
-        column![header, scrollable(library).spacing(10).height(Fill)]
-            .spacing(10)
-            .into()
+        column![
+            header,
+            scanning,
+            scrollable(library).spacing(10).height(Fill)
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    pub fn context_menu<'a>(&'a self, library: &'a Library) -> Option<Element<'a, Message>> {
+        use context_menu::entry;
+
+        self.menu.view(|target| match target {
+            Target::Model(id) => {
+                let is_bookmarked = library.bookmarks.contains(id);
+
+                vec![
+                    entry(
+                        icon::star(),
+                        if is_bookmarked {
+                            "Remove bookmark"
+                        } else {
+                            "Bookmark"
+                        },
+                        Some(Message::Bookmark(id.clone(), !is_bookmarked)),
+                    ),
+                    entry(
+                        icon::refresh(),
+                        "Check status",
+                        matches!(self.models.get(id), Some(Model::API(_)))
+                            .then(|| Message::CheckModel(id.clone())),
+                    ),
+                    entry(
+                        icon::clipboard(),
+                        "Copy id",
+                        Some(Message::CopyId(id.clone())),
+                    ),
+                    entry(
+                        icon::globe(),
+                        "Open on Hugging Face",
+                        matches!(id, EndpointId::Local(_)).then(|| Message::OpenOnHF(id.clone())),
+                    ),
+                ]
+            }
+            Target::Bookmark(id) => {
+                let boot = match library.files.get(id) {
+                    Some(model::FileOrAPI::File(file)) => Some(FileAndAPI {
+                        file: Some(file.clone()),
+                        api: None,
+                    }),
+                    Some(model::FileOrAPI::API(model_online)) => Some(FileAndAPI {
+                        file: None,
+                        api: Some(model_online.clone()),
+                    }),
+                    None => None,
+                };
+
+                let is_file = matches!(library.files.get(id), Some(model::FileOrAPI::File(_)));
+
+                vec![
+                    entry(icon::chat(), "Chat", boot.map(Message::Boot)),
+                    entry(
+                        icon::folder_open(),
+                        "Show in folder",
+                        is_file.then(|| Message::ShowInFolder(id.clone())),
+                    ),
+                    entry(icon::trash(), "Delete", Some(Message::Delete(id.clone()))),
+                ]
+            }
+        })
     }
 }
 
-fn model_card(model: &Model) -> Element<'_, Message> {
+fn model_card(model: &Model, status_max_age: Duration) -> Element<'_, Message> {
     use iced::widget::Text;
 
     fn stat<'a>(
@@ -706,7 +1319,7 @@ fn model_card(model: &Model) -> Element<'_, Message> {
             let title = ellipsized_text(model.endpoint_id.slash_id().name())
                 .font(Font::MONOSPACE)
                 .wrapping(text::Wrapping::None);
-            let status_icon = status_icon(model);
+            let status_icon = status_icon(model, status_max_age);
 
             let metadata = row![
                 stat(
@@ -731,6 +1344,14 @@ fn model_card(model: &Model) -> Element<'_, Message> {
                     ]
                     .spacing(10)
                 }),
+                model.speed.map(|speed| stat(
+                    icon::clock(),
+                    text!("{speed} tok/s"),
+                    text::secondary
+                )),
+                model
+                    .function_calling
+                    .then(|| stat(icon::cubes(), text("Tools"), text::secondary)),
             ]
             .spacing(20);
 
@@ -769,24 +1390,36 @@ fn model_card(model: &Model) -> Element<'_, Message> {
     }
 }
 
-fn status_icon(model: &ModelOnline) -> Option<Element<'_, Message>> {
-    let status_icon = match model.state_check.read().as_ref() {
-        model::StatusCheck::Up { rtt } => Some(
+fn status_icon(model: &ModelOnline, max_age: Duration) -> Option<Element<'_, Message>> {
+    let status = model.status().fresh(max_age);
+    let age = status.age().map(|age| {
+        text(format!(" · {}", ago(age)))
+            .size(12)
+            .line_height(1.2)
+            .style(text::secondary)
+    });
+
+    let status_icon = match status {
+        model::StatusCheck::Up { rtt, .. } => Some(
             row![
                 icon::check().style(text::success).size(10).line_height(1.2),
                 horizontal_space().width(2),
-                value(format!("{} ms", rtt.as_millis()))
+                value(format!("Up · {} ms", rtt.as_millis()))
                     .size(14)
-                    .line_height(1.2)
+                    .line_height(1.2),
+                age,
             ]
+            .align_y(Center)
             .into(),
         ),
-        model::StatusCheck::Down => Some(
-            icon::cancel()
-                .style(text::danger)
-                .size(10)
-                .line_height(1.0)
-                .into(),
+        model::StatusCheck::Down { .. } => Some(
+            row![
+                icon::cancel().style(text::danger).size(10).line_height(1.0),
+                age,
+            ]
+            .spacing(2)
+            .align_y(Center)
+            .into(),
         ),
         model::StatusCheck::CheckingStatus => Some(
             icon::refresh()
@@ -801,6 +1434,15 @@ fn status_icon(model: &ModelOnline) -> Option<Element<'_, Message>> {
     status_icon
 }
 
+fn ago(age: Duration) -> String {
+    match age.as_secs() {
+        ..60 => "just now".to_owned(),
+        seconds @ ..3600 => format!("{}m ago", seconds / 60),
+        seconds @ ..86400 => format!("{}h ago", seconds / 3600),
+        seconds => format!("{}d ago", seconds / 86400),
+    }
+}
+
 pub fn view_files<'a>(
     files: &'a model::Files,
     library: &'a model::Library,
@@ -814,46 +1456,60 @@ pub fn view_files<'a>(
         let variant = file.variant()?;
         let is_ready = library.files.contains_key(&file.endpoint());
 
-        Some(
-            button(
-                row![
-                    is_ready.then(|| icon::check().style(text::primary).size(12)),
-                    text(variant)
-                        .font(Font::MONOSPACE)
-                        .size(12)
-                        .style(if is_ready {
-                            text::primary
-                        } else {
-                            text::default
-                        }),
-                    file.size.map(|size| value(size)
-                        .font(Font::MONOSPACE)
-                        .size(10)
-                        .style(text::secondary))
-                ]
-                .align_y(Center)
-                .spacing(5),
-            )
-            .on_press_with(|| {
+        let boot = button(
+            row![
+                is_ready.then(|| icon::check().style(text::primary).size(12)),
+                text(variant)
+                    .font(Font::MONOSPACE)
+                    .size(12)
+                    .style(if is_ready {
+                        text::primary
+                    } else {
+                        text::default
+                    }),
+                file.size.map(|size| value(size)
+                    .font(Font::MONOSPACE)
+                    .size(10)
+                    .style(text::secondary))
+            ]
+            .align_y(Center)
+            .spacing(5),
+        )
+        .on_press_with(move || {
+            // Missing files are booted once downloaded
+            if is_ready {
                 Message::Boot(model::FileAndAPI {
                     file: Some(file.clone()),
                     ..Default::default()
                 })
-            })
-            .style(move |theme, status| {
-                let base = button::background(theme, status);
+            } else {
+                Message::Download(vec![file.clone()], download::Then::Boot)
+            }
+        })
+        .style(move |theme, status| {
+            let base = button::background(theme, status);
 
-                if is_ready {
-                    button::Style {
-                        border: base.border.color(theme.palette().primary).width(1),
-                        ..base
-                    }
-                } else {
-                    base
+            if is_ready {
+                button::Style {
+                    border: base.border.color(theme.palette().primary).width(1),
+                    ..base
                 }
-            })
-            .into(),
-        )
+            } else {
+                base
+            }
+        });
+
+        let download = (!is_ready).then(|| {
+            tip(
+                button(icon::download().size(12))
+                    .on_press_with(|| Message::Download(vec![file.clone()], download::Then::Notify))
+                    .style(button::text),
+                "Download",
+                tip::Position::Top,
+            )
+        });
+
+        Some(row![boot].push(download).align_y(Center).into())
     }
 
     let files: Element<'_, _> = if files.is_empty() {
@@ -866,8 +1522,28 @@ pub fn view_files<'a>(
         .into()
     } else {
         let files = files.iter().map(|(bit, variants)| {
+            let missing: Vec<_> = variants
+                .iter()
+                .filter(|file| !library.files.contains_key(&file.endpoint()))
+                .cloned()
+                .collect();
+
+            let download_all = (!missing.is_empty()).then(|| {
+                tip(
+                    button(icon::download().size(12))
+                        .on_press(Message::Download(missing, download::Then::Notify))
+                        .style(button::text),
+                    "Download all",
+                    tip::Position::Top,
+                )
+            });
+
             row![
-                value(bit).font(Font::MONOSPACE).size(14).width(80),
+                row![value(bit).font(Font::MONOSPACE).size(14)]
+                    .push(download_all)
+                    .spacing(5)
+                    .width(80)
+                    .align_y(Center),
                 right(
                     row(variants.iter().filter_map(|file| view_file(file, library)))
                         .spacing(10)
@@ -894,7 +1570,7 @@ pub fn view_files<'a>(
 
 pub async fn status_check(models: &ModelsMap, id: EndpointId) -> Result<(), Error> {
     if let Some(Model::API(api)) = models.get(&id) {
-        let _ = api.state_check.write(api.check().await?);
+        let _ = api.refresh_status().await?;
     }
 
     Ok(())
@@ -0,0 +1,57 @@
+use super::{Action, Message, Search};
+use crate::core::model::{APIType, EndpointId, Id, Library};
+use crate::core::{Error, Settings};
+
+use std::sync::Arc;
+use std::time::Duration;
+
+// A model in neither the search nor the library, like one removed mid-search
+fn removed() -> (Search, EndpointId) {
+    let (search, _task) = Search::new(Arc::new(Library::default()), Duration::from_secs(60));
+
+    let id = EndpointId::Remote {
+        api_type: APIType::NanoGPT,
+        id: Id("removed/model".to_owned()),
+    };
+
+    (search, id)
+}
+
+#[test]
+fn bookmarking_a_removed_model_fails() {
+    let (mut search, id) = removed();
+
+    let action = search.update(
+        Message::Bookmark(id, true),
+        &Library::default(),
+        &mut Settings::default(),
+    );
+
+    assert!(matches!(action, Action::Error(Error::ModelNotFound(..))));
+}
+
+#[test]
+fn unbookmarking_a_removed_model_succeeds() {
+    let (mut search, id) = removed();
+
+    let action = search.update(
+        Message::Bookmark(id.clone(), false),
+        &Library::default(),
+        &mut Settings::default(),
+    );
+
+    assert!(matches!(action, Action::Bookmark(bookmark, false) if bookmark == id));
+}
+
+#[test]
+fn selecting_a_removed_model_fails() {
+    let (mut search, id) = removed();
+
+    let action = search.update(
+        Message::Select(id),
+        &Library::default(),
+        &mut Settings::default(),
+    );
+
+    assert!(matches!(action, Action::Error(Error::ModelNotFound(..))));
+}
@@ -1,3 +1,41 @@
+use crate::core;
+use crate::core::approval::{self, Policies, Policy};
+use crate::core::assistant::Parameters;
+use crate::core::audit::Audit;
+use crate::core::azure::Azure;
+use crate::core::backup::{self, Manifest};
+use crate::core::chat::{self, Chat};
+use crate::core::codebase::Embeddings;
+use crate::core::conversion::Converter;
+use crate::core::database::{self, Database};
+use crate::core::desktop;
+use crate::core::diffusion::{self, Diffusion};
+use crate::core::discovery;
+use crate::core::duplicate::{self, Duplicate};
+use crate::core::execution::Sandbox;
+use crate::core::filesystem;
+use crate::core::hook::{self, Hook};
+use crate::core::hosted;
+use crate::core::maintenance::{self, Corruption, Issue, Report};
+use crate::core::mirror;
+use crate::core::model::{APIAccess, APIType, Library};
+use crate::core::ocr::Ocr;
+use crate::core::plugin::Plugin;
+use crate::core::preset::Preset;
+use crate::core::profile::Profiles;
+use crate::core::provider::Provider;
+use crate::core::quick_action::QuickAction;
+use crate::core::reminder::{self, Reminder};
+use crate::core::request::Throttle;
+use crate::core::settings::ClipboardAction;
+use crate::core::setup;
+use crate::core::ssh::Ssh;
+use crate::core::sync::{self, Resolution};
+use crate::core::timeout::{Timeout, Timeouts};
+use crate::core::transcription::Whisper;
+use crate::core::web;
+use crate::core::workspace::{Layout, Pane, Workspace};
+use crate::core::{Error, Url};
 use crate::icon;
 use crate::model;
 use crate::widget::sidebar;
@@ -6,18 +44,90 @@ use iced::border;
 use iced::font;
 use iced::padding;
 use iced::widget::{
-    button, center_x, center_y, column, container, float, grid, horizontal_space, hover,
-    right_center, row, scrollable, stack, svg, text, value, Svg,
+    button, center_x, center_y, column, container, float, grid, horizontal_space, hover, pick_list,
+    progress_bar, right_center, row, scrollable, stack, svg, text, text_input, toggler, value, Svg,
 };
-use iced::{Center, Element, Fill, Font, Shrink, Task, Theme};
+use iced::{Center, Element, Fill, Font, Function, Shrink, Task, Theme};
 use iced_palace::widget::{ellipsized_text, typewriter};
 
+use std::collections::HashMap;
+use std::iter;
 use std::path::PathBuf;
 use std::sync::LazyLock;
+use std::time::Duration;
 
 pub struct Settings {
     section: Section,
     themes: Vec<Theme>,
+    share_endpoint: String,
+    share_endpoint_error: Option<String>,
+    mirror: Option<Url>,
+    mirror_input: String,
+    mirror_error: Option<String>,
+    mirror_health: HashMap<String, Option<Result<Duration, Error>>>,
+    clipboard_watch: bool,
+    clipboard_actions: Vec<ClipboardAction>,
+    collapse_height: u32,
+    reminders: bool,
+    upcoming: Option<Result<Vec<Reminder>, Error>>,
+    sandbox: Sandbox,
+    sandbox_timeout: String,
+    file_roots: Vec<PathBuf>,
+    fetch_domains: [String; 2],
+    databases: Vec<Database>,
+    new_database: Database,
+    tool_policies: Policies,
+    plugin_tools: Vec<String>,
+    agent_steps: usize,
+    preload: Option<model::File>,
+    chat_format: chat::Format,
+    chat_conversion: Option<Result<usize, Error>>,
+    is_converting_chats: bool,
+    audit: Audit,
+    timeouts: Timeouts,
+    timeout_provider: Provider,
+    timeout_fields: [String; 3],
+    throttle: Throttle,
+    status_checks: usize,
+    status_max_age: Duration,
+    status_interval: Option<Duration>,
+    azure: Azure,
+    azure_deployments: String,
+    diffusion: Diffusion,
+    converter: Converter,
+    whisper: Whisper,
+    ocr: Ocr,
+    hooks: Vec<Hook>,
+    presets: Vec<(String, [String; 3])>,
+    keys: hosted::Keys,
+    embeddings: Embeddings,
+    servers: Vec<discovery::Server>,
+    is_scanning: bool,
+    scan_error: Option<Error>,
+    duplicates: Option<Result<Vec<Duplicate>, Error>>,
+    is_deduplicating: bool,
+    maintenance: Option<(model::Directory, Result<Report, Error>)>,
+    maintenance_progress: Option<maintenance::Progress>,
+    maintenance_task: Option<iced::task::Handle>,
+    verify_checksums: bool,
+    is_repairing: bool,
+    registered: Option<String>,
+    ssh: Ssh,
+    ssh_port: String,
+    ssh_test: Option<Result<usize, Error>>,
+    backup_models: bool,
+    backup: Option<Result<Manifest, Error>>,
+    restore: Option<(PathBuf, Result<Manifest, Error>)>,
+    is_archiving: bool,
+    setup: Option<Result<String, Error>>,
+    setup_progress: Option<setup::Progress>,
+    sync_folder: Option<PathBuf>,
+    sync: Option<Result<sync::Summary, Error>>,
+    is_syncing: bool,
+    profile: String,
+    workspaces: Vec<Workspace>,
+    workspace_name: String,
+    workspace_error: Option<Error>,
 }
 
 #[derive(Debug, Clone)]
@@ -27,17 +137,215 @@ pub enum Message {
     OpenTechne,
     PickLibraryFolder,
     PickedLibraryFolder(Option<rfd::FileHandle>),
+    PickSharedLibrary,
+    SharedLibraryPicked(Option<rfd::FileHandle>),
+    RemoveSharedLibrary,
+    FindDuplicates(Vec<PathBuf>),
+    DuplicatesFound(Result<Vec<Duplicate>, Error>),
+    Deduplicate(usize, duplicate::Strategy),
+    Deduplicated(usize, Result<u64, Error>),
+    ToggleChecksums(bool),
+    CheckLibrary(model::Directory),
+    LibraryChecking(maintenance::Progress),
+    LibraryChecked(Result<Report, Error>),
+    Repair(usize),
+    Repaired(usize, Result<Option<model::File>, Error>),
+    ShareEndpointChanged(String),
+    SaveShareEndpoint,
+    MirrorChanged(String),
+    SaveMirror,
+    UseMirror(String),
+    CheckMirror(String),
+    MirrorChecked(String, Result<Duration, Error>),
+    ToggleClipboardWatch(bool),
+    ClipboardActionNameChanged(usize, String),
+    ClipboardActionPromptChanged(usize, String),
+    AddClipboardAction,
+    RemoveClipboardAction(usize),
+    SaveClipboardActions,
+    ToggleReminders(bool),
+    AddFileRoot,
+    FileRootPicked(Option<rfd::FileHandle>),
+    RemoveFileRoot(usize),
+    OpenFileAccessLog,
+    FetchDomainsChanged(usize, String),
+    SaveFetchDomains,
+    NewDatabaseNameChanged(String),
+    NewDatabaseKindChanged(database::Kind),
+    NewDatabaseConnectionChanged(String),
+    AddDatabase,
+    RemoveDatabase(usize),
+    ToolPolicyChanged(String, Policy),
+    PluginsListed(Result<Vec<Plugin>, Error>),
+    ChangeAgentSteps(usize),
+    RemindersListed(Result<Vec<Reminder>, Error>),
+    DeleteReminder(reminder::Id),
+    ReminderDeleted(Result<(), Error>),
+    PresetNameChanged(usize, String),
+    PresetFieldChanged(usize, usize, String),
+    AddPreset,
+    RemovePreset(usize),
+    SavePresets,
+    HookEventChanged(usize, hook::Event),
+    HookCommandChanged(usize, String),
+    ToggleHookInject(usize, bool),
+    AddHook,
+    RemoveHook(usize),
+    SaveHooks,
+    ChangeCollapseHeight(Collapse),
+    ChangePreload(Preload),
+    ChangeChatFormat(chat::Format),
+    ConvertChats,
+    ChatsConverted(Result<usize, Error>),
+    ToggleSandbox(bool),
+    PythonInterpreterChanged(String),
+    ShellInterpreterChanged(String),
+    SandboxTimeoutChanged(String),
+    SaveSandbox,
+    ToggleAudit(Provider, bool),
+    OpenAuditLogs,
+    OpenChats,
+    Opened(Result<(), Error>),
+    SelectTimeoutProvider(Provider),
+    TimeoutChanged(usize, String),
+    SaveTimeouts,
+    ChangeDownloadLimit(DownloadLimit),
+    TogglePauseDownloads(bool),
+    ChangeStatusChecks(usize),
+    ChangeStatusMaxAge(StaleAfter),
+    ChangeStatusInterval(CheckEvery),
+    ResetTimeouts,
+    AzureEndpointChanged(String),
+    AzureApiKeyChanged(String),
+    AzureApiVersionChanged(String),
+    AzureDeploymentsChanged(String),
+    SaveAzure,
+    SelectDiffusionBackend(diffusion::Backend),
+    DiffusionProgramChanged(String),
+    DiffusionModelChanged(String),
+    PickDiffusionModel,
+    DiffusionModelPicked(Option<rfd::FileHandle>),
+    DiffusionBaseUrlChanged(String),
+    DiffusionApiKeyChanged(String),
+    DiffusionApiModelChanged(String),
+    SaveDiffusion,
+    ConverterPythonChanged(String),
+    ConverterScriptChanged(String),
+    PickConverterScript,
+    ConverterScriptPicked(Option<rfd::FileHandle>),
+    ConverterQuantizeChanged(String),
+    SaveConverter,
+    WhisperProgramChanged(String),
+    WhisperModelChanged(String),
+    PickWhisperModel,
+    WhisperModelPicked(Option<rfd::FileHandle>),
+    FfmpegChanged(String),
+    SaveWhisper,
+    OcrProgramChanged(String),
+    OcrLanguagesChanged(String),
+    SaveOcr,
+    GroqKeyChanged(String),
+    TogetherKeyChanged(String),
+    MistralKeyChanged(String),
+    SelectEmbeddings(Embeddings),
+    SaveKeys,
+    ScanNetwork,
+    NetworkScanned(Result<Vec<discovery::Server>, Error>),
+    RegisterServer(discovery::Server),
+    SshHostChanged(String),
+    SshPortChanged(String),
+    SshIdentityChanged(String),
+    SshDirectoryChanged(String),
+    SshServerChanged(String),
+    SaveSsh,
+    TestSsh,
+    SshTested(Result<usize, Error>),
+    ToggleBackupModels(bool),
+    CreateBackup,
+    BackupPathPicked(Option<rfd::FileHandle>),
+    BackupCreated(Result<Manifest, Error>),
+    PickBackup,
+    BackupPicked(Option<rfd::FileHandle>),
+    BackupInspected(PathBuf, Result<Manifest, Error>),
+    Restore,
+    CancelRestore,
+    Restored(Result<Manifest, Error>),
+    ExportSetup,
+    SetupPathPicked(Option<rfd::FileHandle>),
+    SetupExported(Result<setup::Manifest, Error>),
+    ImportSetup,
+    SetupPicked(Option<rfd::FileHandle>),
+    SetupRead(Result<setup::Manifest, Error>),
+    SetupInstalling(setup::Progress),
+    SetupInstalled(Result<usize, Error>),
+    PickSyncFolder,
+    SyncFolderPicked(Option<rfd::FileHandle>),
+    StopSyncing,
+    SyncNow,
+    Synced(Result<sync::Summary, Error>),
+    Resolve(usize, Resolution),
+    Resolved(usize, Result<(), Error>),
+    OpenProfiles,
+    OpenPane(Pane),
+    WorkspaceNameChanged(String),
+    SaveWorkspace,
+    ApplyWorkspace(usize),
+    DeleteWorkspace(usize),
+    WorkspacesSaved(Result<Vec<Workspace>, Error>),
 }
 
 pub enum Action {
     None,
+    Error(Error),
     ChangeTheme(Theme),
     ChangeLibraryFolder(PathBuf),
+    ChangeSharedLibrary(Option<PathBuf>),
+    ChangeShareEndpoint(Option<Url>),
+    ChangeMirror(Option<Url>),
+    ChangeClipboardWatch(bool),
+    ChangeClipboardActions(Vec<ClipboardAction>),
+    ChangePresets(Vec<Preset>),
+    ChangeCollapseHeight(u32),
+    ChangePreload(Option<model::File>),
+    ChangeChatFormat(chat::Format),
+    ChangeReminders(bool),
+    ChangeFileRoots(Vec<PathBuf>),
+    ChangeFetchDomains(web::Domains),
+    ChangeDatabases(Vec<Database>),
+    ChangeToolPolicies(Policies),
+    ChangeAgentSteps(usize),
+    ChangeSandbox(Sandbox),
+    ChangeAudit(Audit),
+    ChangeTimeouts(Timeouts),
+    ChangeThrottle(Throttle),
+    ChangeStatusChecks(usize),
+    ChangeStatusMaxAge(Duration),
+    ChangeStatusInterval(Option<Duration>),
+    ChangeAzure(Azure),
+    ChangeDiffusion(Diffusion),
+    ChangeConverter(Converter),
+    Redownload(model::File),
+    ChangeWhisper(Whisper),
+    ChangeOcr(Ocr),
+    ChangeHooks(Vec<Hook>),
+    ChangeKeys(hosted::Keys),
+    ChangeEmbeddings(Embeddings),
+    RegisterServer(APIAccess),
+    ChangeSsh(Ssh),
+    Restored,
+    ExportSetup(PathBuf),
+    InstallSetup(setup::Manifest),
+    ChangeSyncFolder(Option<PathBuf>),
+    SettingsSynced(sync::Summary),
+    OpenProfiles,
+    OpenPane(Pane),
+    SaveWorkspace(String),
+    ApplyWorkspace(Layout),
     Run(Task<Message>),
 }
 
 impl Settings {
-    pub fn new() -> (Self, Task<Message>) {
+    pub fn new(settings: &core::Settings, library: &Library) -> (Self, Task<Message>) {
         use itertools::Itertools;
 
         (
@@ -51,6 +359,89 @@ impl Settings {
                     .rev()
                     .cloned()
                     .collect(),
+                share_endpoint: settings
+                    .share_endpoint
+                    .as_ref()
+                    .map(Url::to_string)
+                    .unwrap_or_default(),
+                share_endpoint_error: None,
+                mirror: settings.mirror.clone(),
+                mirror_input: settings
+                    .mirror
+                    .as_ref()
+                    .map(Url::to_string)
+                    .unwrap_or_default(),
+                mirror_error: None,
+                mirror_health: HashMap::new(),
+                clipboard_watch: settings.clipboard_watch,
+                clipboard_actions: settings.clipboard_actions.clone(),
+                collapse_height: settings.collapse_height,
+                reminders: settings.reminders,
+                upcoming: None,
+                sandbox: settings.sandbox.clone(),
+                sandbox_timeout: settings.sandbox.timeout.to_string(),
+                file_roots: settings.file_roots.clone(),
+                fetch_domains: [
+                    settings.fetch.allow.join(", "),
+                    settings.fetch.deny.join(", "),
+                ],
+                databases: settings.databases.clone(),
+                new_database: Database::default(),
+                tool_policies: settings.tool_policies.clone(),
+                plugin_tools: Vec::new(),
+                agent_steps: settings.agent_steps,
+                preload: settings.preload.clone(),
+                chat_format: settings.chat_format,
+                chat_conversion: None,
+                is_converting_chats: false,
+                audit: settings.audit.clone(),
+                timeouts: settings.timeouts.clone(),
+                throttle: settings.throttle,
+                status_checks: settings.status_checks,
+                status_max_age: settings.status_max_age,
+                status_interval: settings.status_interval,
+                timeout_provider: Provider::Local,
+                timeout_fields: fields(settings.timeouts.get(Provider::Local)),
+                azure: settings.azure.clone(),
+                azure_deployments: settings.azure.deployments.join(", "),
+                diffusion: settings.diffusion.clone(),
+                converter: settings.converter.clone(),
+                whisper: settings.whisper.clone(),
+                ocr: settings.ocr.clone(),
+                hooks: settings.hooks.clone(),
+                presets: settings.presets.iter().map(preset_fields).collect(),
+                keys: settings.keys.clone(),
+                embeddings: settings.embeddings,
+                servers: Vec::new(),
+                is_scanning: false,
+                scan_error: None,
+                duplicates: None,
+                is_deduplicating: false,
+                maintenance: None,
+                maintenance_progress: None,
+                maintenance_task: None,
+                verify_checksums: false,
+                is_repairing: false,
+                registered: library
+                    .api_src
+                    .get(&APIType::OpenAICompatible)
+                    .and_then(|access| access.base_url.clone()),
+                ssh: settings.ssh.clone(),
+                ssh_port: settings.ssh.port.to_string(),
+                ssh_test: None,
+                backup_models: false,
+                backup: None,
+                restore: None,
+                is_archiving: false,
+                setup: None,
+                setup_progress: None,
+                sync_folder: settings.sync.clone(),
+                sync: None,
+                is_syncing: false,
+                profile: Profiles::fetch().current,
+                workspaces: Workspace::list(),
+                workspace_name: String::new(),
+                workspace_error: None,
             },
             Task::none(),
         )
@@ -65,7 +456,15 @@ impl Settings {
             Message::Open(section) => {
                 self.section = section;
 
-                Action::None
+                match section {
+                    Section::Chat => {
+                        Action::Run(Task::perform(Reminder::list(), Message::RemindersListed))
+                    }
+                    Section::Execution => {
+                        Action::Run(Task::perform(Plugin::enabled(), Message::PluginsListed))
+                    }
+                    _ => Action::None,
+                }
             }
             Message::ChangeTheme(theme) => Action::ChangeTheme(theme),
             Message::OpenTechne => {
@@ -84,106 +483,3317 @@ impl Settings {
                     return Action::None;
                 };
 
-                Action::ChangeLibraryFolder(directory.path().to_path_buf())
-            }
+                Action::ChangeLibraryFolder(directory.path().to_path_buf())
+            }
+            Message::PickSharedLibrary => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose a shared model library...")
+                    .pick_folder(),
+                Message::SharedLibraryPicked,
+            )),
+            Message::SharedLibraryPicked(Some(directory)) => {
+                Action::ChangeSharedLibrary(Some(directory.path().to_path_buf()))
+            }
+            Message::SharedLibraryPicked(None) => Action::None,
+            Message::RemoveSharedLibrary => Action::ChangeSharedLibrary(None),
+            Message::FindDuplicates(directories) => {
+                self.is_deduplicating = true;
+
+                Action::Run(Task::perform(
+                    duplicate::find(directories),
+                    Message::DuplicatesFound,
+                ))
+            }
+            Message::DuplicatesFound(result) => {
+                self.duplicates = Some(result);
+                self.is_deduplicating = false;
+
+                Action::None
+            }
+            Message::Deduplicate(index, strategy) => {
+                let Some(Ok(duplicates)) = &self.duplicates else {
+                    return Action::None;
+                };
+
+                let Some(duplicate) = duplicates.get(index) else {
+                    return Action::None;
+                };
+
+                self.is_deduplicating = true;
+
+                Action::Run(Task::perform(
+                    duplicate::resolve(duplicate.clone(), strategy),
+                    Message::Deduplicated.with(index),
+                ))
+            }
+            Message::Deduplicated(index, result) => {
+                self.is_deduplicating = false;
+
+                match result {
+                    Ok(_freed) => {
+                        if let Some(Ok(duplicates)) = &mut self.duplicates {
+                            let _ = duplicates.remove(index);
+                        }
+                    }
+                    Err(error) => {
+                        self.duplicates = Some(Err(error));
+                    }
+                }
+
+                Action::None
+            }
+            Message::ToggleChecksums(verify_checksums) => {
+                self.verify_checksums = verify_checksums;
+
+                Action::None
+            }
+            Message::CheckLibrary(directory) => {
+                let (task, handle) = Task::sip(
+                    maintenance::check(directory.clone(), self.verify_checksums),
+                    Message::LibraryChecking,
+                    Message::LibraryChecked,
+                )
+                .abortable();
+
+                self.maintenance = Some((directory, Ok(Report::default())));
+                self.maintenance_progress = Some(maintenance::Progress {
+                    checked: 0,
+                    total: 0,
+                });
+                self.maintenance_task = Some(handle.abort_on_drop());
+
+                Action::Run(task)
+            }
+            Message::LibraryChecking(progress) => {
+                self.maintenance_progress = Some(progress);
+
+                Action::None
+            }
+            Message::LibraryChecked(result) => {
+                self.maintenance_progress = None;
+                self.maintenance_task = None;
+
+                if let Some((_, report)) = &mut self.maintenance {
+                    *report = result;
+                }
+
+                Action::None
+            }
+            Message::Repair(index) => {
+                let Some((directory, Ok(report))) = &self.maintenance else {
+                    return Action::None;
+                };
+
+                let Some(issue) = report.issues.get(index) else {
+                    return Action::None;
+                };
+
+                self.is_repairing = true;
+
+                Action::Run(Task::perform(
+                    maintenance::repair(issue.clone(), directory.clone()),
+                    Message::Repaired.with(index),
+                ))
+            }
+            Message::Repaired(index, result) => {
+                self.is_repairing = false;
+
+                let Some((_, report)) = &mut self.maintenance else {
+                    return Action::None;
+                };
+
+                match result {
+                    Ok(redownload) => {
+                        if let Ok(report) = report {
+                            let _ = report.issues.remove(index);
+                        }
+
+                        if let Some(file) = redownload {
+                            return Action::Redownload(file);
+                        }
+                    }
+                    Err(error) => {
+                        *report = Err(error);
+                    }
+                }
+
+                Action::None
+            }
+            Message::ShareEndpointChanged(endpoint) => {
+                self.share_endpoint = endpoint;
+                self.share_endpoint_error = None;
+
+                Action::None
+            }
+            Message::SaveShareEndpoint => {
+                let endpoint = self.share_endpoint.trim();
+
+                if endpoint.is_empty() {
+                    return Action::ChangeShareEndpoint(None);
+                }
+
+                match Url::parse(endpoint) {
+                    Ok(url) => Action::ChangeShareEndpoint(Some(url)),
+                    Err(error) => {
+                        self.share_endpoint_error = Some(error.to_string());
+
+                        Action::None
+                    }
+                }
+            }
+            Message::MirrorChanged(mirror) => {
+                self.mirror_input = mirror;
+                self.mirror_error = None;
+
+                Action::None
+            }
+            Message::SaveMirror => {
+                let mirror = self.mirror_input.trim().to_owned();
+
+                self.update(Message::UseMirror(mirror))
+            }
+            Message::UseMirror(mirror) => {
+                if mirror.is_empty() || mirror == mirror::DEFAULT {
+                    self.mirror = None;
+                    self.mirror_input = String::new();
+
+                    return Action::ChangeMirror(None);
+                }
+
+                match Url::parse(&mirror) {
+                    Ok(url) => {
+                        self.mirror = Some(url.clone());
+                        self.mirror_input = mirror;
+
+                        Action::ChangeMirror(Some(url))
+                    }
+                    Err(error) => {
+                        self.mirror_error = Some(error.to_string());
+
+                        Action::None
+                    }
+                }
+            }
+            Message::CheckMirror(mirror) => {
+                let Ok(url) = Url::parse(&mirror) else {
+                    return Action::None;
+                };
+
+                let _ = self.mirror_health.insert(mirror.clone(), None);
+
+                Action::Run(Task::perform(
+                    mirror::check(url),
+                    Message::MirrorChecked.with(mirror),
+                ))
+            }
+            Message::MirrorChecked(mirror, health) => {
+                let _ = self.mirror_health.insert(mirror, Some(health));
+
+                Action::None
+            }
+            Message::ToggleReminders(reminders) => {
+                self.reminders = reminders;
+
+                Action::ChangeReminders(reminders)
+            }
+            Message::RemindersListed(result) => {
+                self.upcoming = Some(result);
+
+                Action::None
+            }
+            Message::DeleteReminder(id) => {
+                if let Some(Ok(upcoming)) = &mut self.upcoming {
+                    upcoming.retain(|reminder| reminder.id != id);
+                }
+
+                Action::Run(Task::perform(
+                    Reminder::delete(id),
+                    Message::ReminderDeleted,
+                ))
+            }
+            Message::ReminderDeleted(Ok(())) => Action::None,
+            Message::ReminderDeleted(Err(error)) => Action::Error(error),
+            Message::ToggleClipboardWatch(watch) => {
+                self.clipboard_watch = watch;
+
+                Action::ChangeClipboardWatch(watch)
+            }
+            Message::ClipboardActionNameChanged(index, name) => {
+                if let Some(action) = self.clipboard_actions.get_mut(index) {
+                    action.name = name;
+                }
+
+                Action::None
+            }
+            Message::ClipboardActionPromptChanged(index, prompt) => {
+                if let Some(action) = self.clipboard_actions.get_mut(index) {
+                    action.prompt = prompt;
+                }
+
+                Action::None
+            }
+            Message::AddClipboardAction => {
+                self.clipboard_actions.push(ClipboardAction {
+                    name: String::new(),
+                    prompt: "{text}".to_owned(),
+                });
+
+                Action::None
+            }
+            Message::RemoveClipboardAction(index) => {
+                if index < self.clipboard_actions.len() {
+                    let _ = self.clipboard_actions.remove(index);
+                }
+
+                Action::None
+            }
+            Message::ChangeDownloadLimit(limit) => {
+                self.throttle.limit = limit.0;
+
+                Action::ChangeThrottle(self.throttle)
+            }
+            Message::TogglePauseDownloads(pause) => {
+                self.throttle.pause_while_generating = pause;
+
+                Action::ChangeThrottle(self.throttle)
+            }
+            Message::ChangeStatusChecks(checks) => {
+                self.status_checks = checks;
+
+                Action::ChangeStatusChecks(checks)
+            }
+            Message::ChangeStatusMaxAge(stale_after) => {
+                self.status_max_age = stale_after.0;
+
+                Action::ChangeStatusMaxAge(self.status_max_age)
+            }
+            Message::ChangeStatusInterval(check_every) => {
+                self.status_interval = check_every.0;
+
+                Action::ChangeStatusInterval(self.status_interval)
+            }
+            Message::ChangeCollapseHeight(collapse) => {
+                self.collapse_height = collapse.height();
+
+                Action::ChangeCollapseHeight(self.collapse_height)
+            }
+            Message::ChangePreload(preload) => {
+                self.preload = match preload {
+                    Preload::Nothing => None,
+                    Preload::File(file) => Some(file),
+                };
+
+                Action::ChangePreload(self.preload.clone())
+            }
+            Message::ChangeChatFormat(format) => {
+                self.chat_format = format;
+                self.chat_conversion = None;
+
+                Action::ChangeChatFormat(format)
+            }
+            Message::ConvertChats => {
+                self.is_converting_chats = true;
+
+                Action::Run(Task::perform(
+                    Chat::convert(self.chat_format),
+                    Message::ChatsConverted,
+                ))
+            }
+            Message::ChatsConverted(result) => {
+                self.is_converting_chats = false;
+                self.chat_conversion = Some(result);
+
+                Action::None
+            }
+            Message::SaveClipboardActions => {
+                self.clipboard_actions
+                    .retain(|action| !action.name.trim().is_empty());
+
+                Action::ChangeClipboardActions(self.clipboard_actions.clone())
+            }
+            Message::PresetNameChanged(index, name) => {
+                if let Some((preset, _)) = self.presets.get_mut(index) {
+                    *preset = name;
+                }
+
+                Action::None
+            }
+            Message::PresetFieldChanged(index, field, value) => {
+                if let Some(field) = self
+                    .presets
+                    .get_mut(index)
+                    .and_then(|(_, fields)| fields.get_mut(field))
+                {
+                    *field = value;
+                }
+
+                Action::None
+            }
+            Message::AddPreset => {
+                self.presets.push((String::new(), Default::default()));
+
+                Action::None
+            }
+            Message::RemovePreset(index) => {
+                if index < self.presets.len() {
+                    let _ = self.presets.remove(index);
+                }
+
+                Action::None
+            }
+            Message::SavePresets => {
+                self.presets.retain(|(name, _)| !name.trim().is_empty());
+
+                // Invalid values are dropped, so the defaults of the model apply
+                let presets: Vec<Preset> = self
+                    .presets
+                    .iter()
+                    .map(|(name, [temperature, top_p, max_tokens])| Preset {
+                        name: name.trim().to_owned(),
+                        parameters: Parameters {
+                            temperature: temperature.trim().parse().ok(),
+                            top_p: top_p.trim().parse().ok(),
+                            max_tokens: max_tokens.trim().parse().ok(),
+                        },
+                    })
+                    .collect();
+
+                self.presets = presets.iter().map(preset_fields).collect();
+
+                Action::ChangePresets(presets)
+            }
+            Message::HookEventChanged(index, event) => {
+                if let Some(hook) = self.hooks.get_mut(index) {
+                    hook.event = event;
+                    hook.inject &= event.can_inject();
+                }
+
+                Action::None
+            }
+            Message::HookCommandChanged(index, command) => {
+                if let Some(hook) = self.hooks.get_mut(index) {
+                    hook.command = command;
+                }
+
+                Action::None
+            }
+            Message::ToggleHookInject(index, inject) => {
+                if let Some(hook) = self.hooks.get_mut(index) {
+                    hook.inject = inject && hook.event.can_inject();
+                }
+
+                Action::None
+            }
+            Message::AddHook => {
+                self.hooks.push(Hook {
+                    event: hook::Event::MessageReceived,
+                    command: String::new(),
+                    inject: false,
+                });
+
+                Action::None
+            }
+            Message::RemoveHook(index) => {
+                if index < self.hooks.len() {
+                    let _ = self.hooks.remove(index);
+                }
+
+                Action::None
+            }
+            Message::SaveHooks => {
+                self.hooks.retain(|hook| !hook.command.trim().is_empty());
+
+                Action::ChangeHooks(self.hooks.clone())
+            }
+            Message::ToggleSandbox(enabled) => {
+                self.sandbox.enabled = enabled;
+
+                Action::ChangeSandbox(self.sandbox.clone())
+            }
+            Message::PythonInterpreterChanged(python) => {
+                self.sandbox.python = python;
+
+                Action::None
+            }
+            Message::ShellInterpreterChanged(shell) => {
+                self.sandbox.shell = shell;
+
+                Action::None
+            }
+            Message::SandboxTimeoutChanged(timeout) => {
+                if let Ok(seconds) = timeout.trim().parse() {
+                    self.sandbox.timeout = seconds;
+                }
+
+                self.sandbox_timeout = timeout;
+
+                Action::None
+            }
+            Message::SaveSandbox => Action::ChangeSandbox(self.sandbox.clone()),
+            Message::ToggleAudit(provider, enabled) => {
+                self.audit.toggle(provider, enabled);
+
+                Action::ChangeAudit(self.audit.clone())
+            }
+            Message::OpenAuditLogs => Action::Run(Task::perform(
+                desktop::open(Audit::directory()),
+                Message::Opened,
+            )),
+            Message::AddFileRoot => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose a folder the assistant may read...")
+                    .pick_folder(),
+                Message::FileRootPicked,
+            )),
+            Message::FileRootPicked(folder) => {
+                let Some(folder) = folder else {
+                    return Action::None;
+                };
+
+                let folder = folder.path().to_path_buf();
+
+                if self.file_roots.contains(&folder) {
+                    return Action::None;
+                }
+
+                self.file_roots.push(folder);
+
+                Action::ChangeFileRoots(self.file_roots.clone())
+            }
+            Message::RemoveFileRoot(index) => {
+                if index < self.file_roots.len() {
+                    let _ = self.file_roots.remove(index);
+                }
+
+                Action::ChangeFileRoots(self.file_roots.clone())
+            }
+            Message::OpenFileAccessLog => Action::Run(Task::perform(
+                desktop::reveal(filesystem::access_log()),
+                Message::Opened,
+            )),
+            Message::FetchDomainsChanged(list, domains) => {
+                if let Some(field) = self.fetch_domains.get_mut(list) {
+                    *field = domains;
+                }
+
+                Action::None
+            }
+            Message::SaveFetchDomains => {
+                fn parse(domains: &str) -> Vec<String> {
+                    domains
+                        .split([',', ' ', '\n'])
+                        .map(|domain| domain.trim().to_lowercase())
+                        .filter(|domain| !domain.is_empty())
+                        .collect()
+                }
+
+                let [allow, deny] = &self.fetch_domains;
+
+                let domains = web::Domains {
+                    allow: parse(allow),
+                    deny: parse(deny),
+                };
+
+                self.fetch_domains = [domains.allow.join(", "), domains.deny.join(", ")];
+
+                Action::ChangeFetchDomains(domains)
+            }
+            Message::NewDatabaseNameChanged(name) => {
+                self.new_database.name = name;
+
+                Action::None
+            }
+            Message::NewDatabaseKindChanged(kind) => {
+                self.new_database.kind = kind;
+
+                Action::None
+            }
+            Message::NewDatabaseConnectionChanged(connection) => {
+                self.new_database.connection = connection;
+
+                Action::None
+            }
+            Message::AddDatabase => {
+                let name = self.new_database.name.trim();
+                let connection = self.new_database.connection.trim();
+
+                if name.is_empty()
+                    || connection.is_empty()
+                    || self.databases.iter().any(|database| database.name == name)
+                {
+                    return Action::None;
+                }
+
+                let database = Database {
+                    name: name.to_owned(),
+                    kind: self.new_database.kind,
+                    connection: connection.to_owned(),
+                };
+
+                self.databases.push(database);
+                self.new_database = Database::default();
+
+                Action::ChangeDatabases(self.databases.clone())
+            }
+            Message::RemoveDatabase(index) => {
+                if index < self.databases.len() {
+                    let _ = self.databases.remove(index);
+                }
+
+                Action::ChangeDatabases(self.databases.clone())
+            }
+            Message::ToolPolicyChanged(tool, policy) => {
+                let _ = self.tool_policies.tools.insert(tool, policy);
+
+                Action::ChangeToolPolicies(self.tool_policies.clone())
+            }
+            Message::PluginsListed(Ok(plugins)) => {
+                self.plugin_tools = plugins
+                    .iter()
+                    .flat_map(|plugin| &plugin.manifest.tools)
+                    .map(|tool| tool.name.clone())
+                    .collect();
+
+                Action::None
+            }
+            Message::ChangeAgentSteps(steps) => {
+                self.agent_steps = steps;
+
+                Action::ChangeAgentSteps(steps)
+            }
+            Message::PluginsListed(Err(error)) => {
+                log::warn!("Plugins unavailable: {error}");
+
+                Action::None
+            }
+            Message::OpenChats => Action::Run(Task::perform(
+                desktop::open(Chat::directory()),
+                Message::Opened,
+            )),
+            Message::Opened(Ok(())) => Action::None,
+            Message::Opened(Err(error)) => Action::Error(error),
+            Message::SelectTimeoutProvider(provider) => {
+                self.timeout_provider = provider;
+                self.timeout_fields = fields(self.timeouts.get(provider));
+
+                Action::None
+            }
+            Message::TimeoutChanged(index, seconds) => {
+                if let Some(field) = self.timeout_fields.get_mut(index) {
+                    *field = seconds;
+                }
+
+                Action::None
+            }
+            Message::SaveTimeouts => {
+                let current = self.timeouts.get(self.timeout_provider);
+                let [connect, first_token, inter_token] = self
+                    .timeout_fields
+                    .clone()
+                    .map(|field| field.trim().parse().ok());
+
+                self.timeouts.set(
+                    self.timeout_provider,
+                    Timeout {
+                        connect: connect.unwrap_or(current.connect),
+                        first_token: first_token.unwrap_or(current.first_token),
+                        inter_token: inter_token.unwrap_or(current.inter_token),
+                    },
+                );
+                self.timeout_fields = fields(self.timeouts.get(self.timeout_provider));
+
+                Action::ChangeTimeouts(self.timeouts.clone())
+            }
+            Message::AzureEndpointChanged(endpoint) => {
+                self.azure.endpoint = endpoint;
+
+                Action::None
+            }
+            Message::AzureApiKeyChanged(api_key) => {
+                self.azure.api_key = api_key;
+
+                Action::None
+            }
+            Message::AzureApiVersionChanged(api_version) => {
+                self.azure.api_version = api_version;
+
+                Action::None
+            }
+            Message::AzureDeploymentsChanged(deployments) => {
+                self.azure_deployments = deployments;
+
+                Action::None
+            }
+            Message::SaveAzure => {
+                self.azure.deployments = self
+                    .azure_deployments
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|deployment| !deployment.is_empty())
+                    .map(str::to_owned)
+                    .collect();
+
+                Action::ChangeAzure(self.azure.clone())
+            }
+            Message::SelectDiffusionBackend(backend) => {
+                self.diffusion.backend = backend;
+
+                Action::None
+            }
+            Message::DiffusionProgramChanged(program) => {
+                self.diffusion.program = program;
+
+                Action::None
+            }
+            Message::DiffusionModelChanged(model) => {
+                self.diffusion.model = model;
+
+                Action::None
+            }
+            Message::PickDiffusionModel => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose a stable-diffusion.cpp model...")
+                    .add_filter("Model", &["gguf", "safetensors", "ckpt"])
+                    .pick_file(),
+                Message::DiffusionModelPicked,
+            )),
+            Message::DiffusionModelPicked(Some(file)) => {
+                self.diffusion.model = file.path().to_string_lossy().into_owned();
+
+                Action::ChangeDiffusion(self.diffusion.clone())
+            }
+            Message::DiffusionModelPicked(None) => Action::None,
+            Message::DiffusionBaseUrlChanged(base_url) => {
+                self.diffusion.base_url = base_url;
+
+                Action::None
+            }
+            Message::DiffusionApiKeyChanged(api_key) => {
+                self.diffusion.api_key = api_key;
+
+                Action::None
+            }
+            Message::DiffusionApiModelChanged(api_model) => {
+                self.diffusion.api_model = api_model;
+
+                Action::None
+            }
+            Message::SaveDiffusion => Action::ChangeDiffusion(self.diffusion.clone()),
+            Message::ConverterPythonChanged(python) => {
+                self.converter.python = python;
+
+                Action::None
+            }
+            Message::ConverterScriptChanged(script) => {
+                self.converter.script = script;
+
+                Action::None
+            }
+            Message::PickConverterScript => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose convert_hf_to_gguf.py...")
+                    .add_filter("Python", &["py"])
+                    .pick_file(),
+                Message::ConverterScriptPicked,
+            )),
+            Message::ConverterScriptPicked(Some(file)) => {
+                self.converter.script = file.path().to_string_lossy().into_owned();
+
+                Action::ChangeConverter(self.converter.clone())
+            }
+            Message::ConverterScriptPicked(None) => Action::None,
+            Message::ConverterQuantizeChanged(quantize) => {
+                self.converter.quantize = quantize;
+
+                Action::None
+            }
+            Message::SaveConverter => Action::ChangeConverter(self.converter.clone()),
+            Message::WhisperProgramChanged(program) => {
+                self.whisper.program = program;
+
+                Action::None
+            }
+            Message::WhisperModelChanged(model) => {
+                self.whisper.model = model;
+
+                Action::None
+            }
+            Message::PickWhisperModel => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose a whisper.cpp model...")
+                    .add_filter("Model", &["bin"])
+                    .pick_file(),
+                Message::WhisperModelPicked,
+            )),
+            Message::WhisperModelPicked(Some(file)) => {
+                self.whisper.model = file.path().to_string_lossy().into_owned();
+
+                Action::ChangeWhisper(self.whisper.clone())
+            }
+            Message::WhisperModelPicked(None) => Action::None,
+            Message::FfmpegChanged(ffmpeg) => {
+                self.whisper.ffmpeg = ffmpeg;
+
+                Action::None
+            }
+            Message::SaveWhisper => Action::ChangeWhisper(self.whisper.clone()),
+            Message::OcrProgramChanged(program) => {
+                self.ocr.program = program;
+
+                Action::None
+            }
+            Message::OcrLanguagesChanged(languages) => {
+                self.ocr.languages = languages;
+
+                Action::None
+            }
+            Message::SaveOcr => Action::ChangeOcr(self.ocr.clone()),
+            Message::GroqKeyChanged(key) => {
+                self.keys.groq = key;
+
+                Action::None
+            }
+            Message::TogetherKeyChanged(key) => {
+                self.keys.together = key;
+
+                Action::None
+            }
+            Message::MistralKeyChanged(key) => {
+                self.keys.mistral = key;
+
+                Action::None
+            }
+            Message::SaveKeys => Action::ChangeKeys(self.keys.clone()),
+            Message::ScanNetwork => {
+                self.is_scanning = true;
+                self.scan_error = None;
+
+                Action::Run(Task::perform(discovery::scan(), Message::NetworkScanned))
+            }
+            Message::NetworkScanned(Ok(servers)) => {
+                self.is_scanning = false;
+                self.servers = servers;
+
+                Action::None
+            }
+            Message::NetworkScanned(Err(error)) => {
+                self.is_scanning = false;
+                self.scan_error = Some(dbg!(error));
+
+                Action::None
+            }
+            Message::RegisterServer(server) => {
+                self.registered = Some(server.base_url());
+
+                Action::RegisterServer(server.access())
+            }
+            Message::SshHostChanged(host) => {
+                self.ssh.host = host;
+
+                Action::None
+            }
+            Message::SshPortChanged(port) => {
+                self.ssh_port = port;
+
+                Action::None
+            }
+            Message::SshIdentityChanged(identity) => {
+                self.ssh.identity = identity;
+
+                Action::None
+            }
+            Message::SshDirectoryChanged(directory) => {
+                self.ssh.directory = directory;
+
+                Action::None
+            }
+            Message::SshServerChanged(server) => {
+                self.ssh.server = server;
+
+                Action::None
+            }
+            Message::SaveSsh => {
+                if let Ok(port) = self.ssh_port.trim().parse() {
+                    self.ssh.port = port;
+                }
+
+                self.ssh_port = self.ssh.port.to_string();
+
+                Action::ChangeSsh(self.ssh.clone())
+            }
+            Message::TestSsh => {
+                let ssh = self.ssh.clone();
+                self.ssh_test = None;
+
+                Action::Run(Task::perform(
+                    async move { ssh.models().await.map(|models| models.len()) },
+                    Message::SshTested,
+                ))
+            }
+            Message::SshTested(result) => {
+                self.ssh_test = Some(result);
+
+                Action::None
+            }
+            Message::ToggleBackupModels(models) => {
+                self.backup_models = models;
+
+                Action::None
+            }
+            Message::CreateBackup => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Save backup as...")
+                    .set_file_name("icebreaker-backup.zip")
+                    .save_file(),
+                Message::BackupPathPicked,
+            )),
+            Message::BackupPathPicked(Some(file)) => {
+                self.is_archiving = true;
+                self.backup = None;
+
+                Action::Run(Task::perform(
+                    backup::create(file.path().to_path_buf(), self.backup_models),
+                    Message::BackupCreated,
+                ))
+            }
+            Message::BackupCreated(result) => {
+                self.is_archiving = false;
+                self.backup = Some(result);
+
+                Action::None
+            }
+            Message::ExportSetup => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Save library manifest as...")
+                    .set_file_name("icebreaker-library.toml")
+                    .add_filter("TOML", &["toml"])
+                    .save_file(),
+                Message::SetupPathPicked,
+            )),
+            Message::SetupPathPicked(Some(file)) => {
+                self.is_archiving = true;
+                self.setup = None;
+
+                Action::ExportSetup(file.path().to_path_buf())
+            }
+            Message::SetupExported(result) => {
+                self.is_archiving = false;
+                self.setup = Some(result.map(|manifest| {
+                    format!(
+                        "Exported {} models and {} providers.",
+                        manifest.models.len(),
+                        manifest.providers.len()
+                    )
+                }));
+
+                Action::None
+            }
+            Message::ImportSetup => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose a library manifest...")
+                    .add_filter("TOML", &["toml"])
+                    .pick_file(),
+                Message::SetupPicked,
+            )),
+            Message::SetupPicked(Some(file)) => {
+                self.is_archiving = true;
+                self.setup = None;
+
+                Action::Run(Task::perform(
+                    setup::read(file.path().to_path_buf()),
+                    Message::SetupRead,
+                ))
+            }
+            Message::SetupRead(Ok(manifest)) => Action::InstallSetup(manifest),
+            Message::SetupInstalling(progress) => {
+                self.setup_progress = Some(progress);
+
+                Action::None
+            }
+            Message::SetupInstalled(result) => {
+                self.is_archiving = false;
+                self.setup_progress = None;
+                self.setup = Some(result.map(|downloaded| match downloaded {
+                    0 => "Every model was in the library already.".to_owned(),
+                    1 => "Downloaded 1 model.".to_owned(),
+                    _ => format!("Downloaded {downloaded} models."),
+                }));
+
+                Action::None
+            }
+            Message::SetupRead(Err(error)) => {
+                self.is_archiving = false;
+                self.setup = Some(Err(error));
+
+                Action::None
+            }
+            Message::SetupPathPicked(None) | Message::SetupPicked(None) => Action::None,
+            Message::PickBackup => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose a backup to restore...")
+                    .add_filter("zip", &["zip"])
+                    .pick_file(),
+                Message::BackupPicked,
+            )),
+            Message::BackupPicked(Some(file)) => {
+                let path = file.path().to_path_buf();
+
+                Action::Run(Task::perform(
+                    backup::inspect(path.clone()),
+                    move |result| Message::BackupInspected(path.clone(), result),
+                ))
+            }
+            Message::BackupPathPicked(None) | Message::BackupPicked(None) => Action::None,
+            Message::BackupInspected(path, result) => {
+                self.restore = Some((path, result));
+
+                Action::None
+            }
+            Message::Restore => {
+                let Some((path, Ok(manifest))) = &self.restore else {
+                    return Action::None;
+                };
+
+                if !manifest.is_compatible() {
+                    return Action::None;
+                }
+
+                self.is_archiving = true;
+
+                Action::Run(Task::perform(
+                    backup::restore(path.clone()),
+                    Message::Restored,
+                ))
+            }
+            Message::CancelRestore => {
+                self.restore = None;
+
+                Action::None
+            }
+            Message::Restored(Ok(_manifest)) => {
+                self.is_archiving = false;
+                self.restore = None;
+
+                Action::Restored
+            }
+            Message::Restored(Err(error)) => {
+                self.is_archiving = false;
+
+                if let Some((_, result)) = &mut self.restore {
+                    *result = Err(error);
+                }
+
+                Action::None
+            }
+            Message::PickSyncFolder => Action::Run(Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Choose a shared folder...")
+                    .pick_folder(),
+                Message::SyncFolderPicked,
+            )),
+            Message::SyncFolderPicked(Some(folder)) => {
+                let folder = folder.path().to_path_buf();
+
+                self.sync_folder = Some(folder.clone());
+                self.sync = None;
+
+                Action::ChangeSyncFolder(Some(folder))
+            }
+            Message::SyncFolderPicked(None) => Action::None,
+            Message::StopSyncing => {
+                self.sync_folder = None;
+                self.sync = None;
+
+                Action::ChangeSyncFolder(None)
+            }
+            Message::SyncNow => {
+                let Some(folder) = self.sync_folder.clone() else {
+                    return Action::None;
+                };
+
+                self.is_syncing = true;
+
+                Action::Run(Task::perform(sync::run(folder), Message::Synced))
+            }
+            Message::Synced(result) => {
+                self.is_syncing = false;
+
+                let action = match &result {
+                    Ok(summary) if summary.settings => Action::SettingsSynced(summary.clone()),
+                    _ => Action::None,
+                };
+
+                self.sync = Some(result);
+
+                action
+            }
+            Message::Resolve(index, resolution) => {
+                let (Some(folder), Some(Ok(summary))) = (&self.sync_folder, &self.sync) else {
+                    return Action::None;
+                };
+
+                let Some(conflict) = summary.conflicts.get(index) else {
+                    return Action::None;
+                };
+
+                self.is_syncing = true;
+
+                Action::Run(Task::perform(
+                    sync::resolve(folder.clone(), conflict.clone(), resolution),
+                    move |result| Message::Resolved(index, result),
+                ))
+            }
+            Message::Resolved(index, result) => {
+                self.is_syncing = false;
+
+                match result {
+                    Ok(()) => {
+                        if let Some(Ok(summary)) = &mut self.sync {
+                            if index < summary.conflicts.len() {
+                                let _ = summary.conflicts.remove(index);
+                            }
+                        }
+                    }
+                    Err(error) => {
+                        self.sync = Some(Err(error));
+                    }
+                }
+
+                Action::None
+            }
+            Message::OpenProfiles => Action::OpenProfiles,
+            Message::OpenPane(pane) => Action::OpenPane(pane),
+            Message::WorkspaceNameChanged(name) => {
+                self.workspace_name = name;
+
+                Action::None
+            }
+            Message::SaveWorkspace => {
+                let name = self.workspace_name.trim();
+
+                if name.is_empty() {
+                    return Action::None;
+                }
+
+                Action::SaveWorkspace(name.to_owned())
+            }
+            Message::ApplyWorkspace(index) => {
+                let Some(workspace) = self.workspaces.get(index) else {
+                    return Action::None;
+                };
+
+                Action::ApplyWorkspace(workspace.layout.clone())
+            }
+            Message::DeleteWorkspace(index) => {
+                let Some(workspace) = self.workspaces.get(index) else {
+                    return Action::None;
+                };
+
+                Action::Run(Task::perform(
+                    Workspace::delete(workspace.name.clone()),
+                    Message::WorkspacesSaved,
+                ))
+            }
+            Message::WorkspacesSaved(Ok(workspaces)) => {
+                self.workspaces = workspaces;
+                self.workspace_name.clear();
+                self.workspace_error = None;
+
+                Action::None
+            }
+            Message::WorkspacesSaved(Err(error)) => {
+                self.workspace_error = Some(error);
+
+                Action::None
+            }
+            Message::SelectEmbeddings(embeddings) => {
+                self.embeddings = embeddings;
+
+                Action::ChangeEmbeddings(embeddings)
+            }
+            Message::ResetTimeouts => {
+                let recommended = Timeout::recommended(self.timeout_provider);
+
+                self.timeouts.set(self.timeout_provider, recommended);
+                self.timeout_fields = fields(recommended);
+
+                Action::ChangeTimeouts(self.timeouts.clone())
+            }
+        }
+    }
+
+    pub fn view<'a>(&'a self, library: &model::Library, theme: &'a Theme) -> Element<'a, Message> {
+        let section = match self.section {
+            Section::Storage => self.storage(library),
+            Section::Maintenance => self.maintenance(library),
+            Section::Theme => self.theme(theme),
+            Section::Chat => self.chat(library),
+            Section::Clipboard => self.clipboard(),
+            Section::Sharing => self.sharing(),
+            Section::Execution => self.execution(),
+            Section::Audit => self.audit(),
+            Section::Timeouts => self.timeouts(),
+            Section::Azure => self.azure(),
+            Section::Images => self.images(),
+            Section::Transcription => self.transcription(),
+            Section::Ocr => self.ocr(),
+            Section::Hooks => self.hooks(),
+            Section::Hosted => self.hosted(),
+            Section::Network => self.network(),
+            Section::Remote => self.remote(),
+            Section::Backup => self.backup(),
+            Section::Sync => self.sync(),
+            Section::Profiles => self.profiles(),
+            Section::Workspaces => self.workspaces(),
+            Section::Mcp => self.mcp(),
+        };
+
+        center_y(scrollable(
+            center_x(container(section).max_width(600)).padding(20),
+        ))
+        .into()
+    }
+
+    pub fn storage(&self, library: &model::Library) -> Element<'_, Message> {
+        let own = row![
+            column![
+                text("Model Library")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(20),
+                text("Models will be downloaded and stored in this directory.").width(Fill)
+            ]
+            .spacing(10),
+            row![
+                container(value(library.directory().path().display()).font(Font::MONOSPACE))
+                    .width(300)
+                    .padding(10)
+                    .style(container::bordered_box),
+                button(icon::folder_open()).on_press(Message::PickLibraryFolder),
+            ]
+            .align_y(Center)
+            .spacing(10)
+        ]
+        .align_y(Center)
+        .spacing(20);
+
+        let shared = row![
+            column![
+                text("Shared Library")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(20),
+                text(
+                    "Models in this read-only directory (e.g. /opt/models) are available \
+                    to every user of this machine."
+                )
+                .width(Fill)
+            ]
+            .spacing(10),
+            row![
+                container(match library.shared() {
+                    Some(shared) => value(shared.path().display()).font(Font::MONOSPACE),
+                    None => text("None").style(text::secondary),
+                })
+                .width(300)
+                .padding(10)
+                .style(container::bordered_box),
+                button(icon::folder_open()).on_press(Message::PickSharedLibrary),
+                library.shared().map(|_| {
+                    button(icon::cancel())
+                        .on_press(Message::RemoveSharedLibrary)
+                        .style(button::danger)
+                }),
+            ]
+            .align_y(Center)
+            .spacing(10)
+        ]
+        .align_y(Center)
+        .spacing(20);
+
+        let directories: Vec<PathBuf> = library
+            .shared()
+            .into_iter()
+            .chain([library.directory()])
+            .map(|directory| directory.path().to_path_buf())
+            .collect();
+
+        let duplicates = column![
+            row![
+                column![
+                    text("Duplicates")
+                        .font(Font {
+                            weight: font::Weight::Semibold,
+                            ..Font::MONOSPACE
+                        })
+                        .size(20),
+                    text("Find model files stored more than once across your libraries."),
+                ]
+                .spacing(10)
+                .width(Fill),
+                button(if self.is_deduplicating {
+                    "Scanning..."
+                } else {
+                    "Find Duplicates"
+                })
+                .on_press_maybe(
+                    (!self.is_deduplicating).then(|| Message::FindDuplicates(directories))
+                ),
+            ]
+            .spacing(20)
+            .align_y(Center),
+            self.duplicates.as_ref().map(duplicate_list),
+        ]
+        .spacing(10);
+
+        let limit = row![
+            column![
+                text("Download Speed")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(20),
+                text("Keep large downloads from saturating your connection.").width(Fill),
+            ]
+            .spacing(10),
+            pick_list(
+                DownloadLimit::ALL,
+                Some(DownloadLimit(self.throttle.limit)),
+                Message::ChangeDownloadLimit,
+            ),
+        ]
+        .align_y(Center)
+        .spacing(20);
+
+        let pause = row![
+            column![
+                text("Pause While Generating")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(20),
+                text("Downloads wait until the replies being generated are done.").width(Fill),
+            ]
+            .spacing(10),
+            toggler(self.throttle.pause_while_generating).on_toggle(Message::TogglePauseDownloads),
+        ]
+        .align_y(Center)
+        .spacing(20);
+
+        let hub = self.hub();
+        let converter = self.converter();
+
+        column![own, shared, hub, converter, duplicates, limit, pause]
+            .spacing(30)
+            .into()
+    }
+
+    fn hub(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Model Hub")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Models are searched and downloaded from Hugging Face, or from a mirror \
+                of it where Hugging Face is unreachable."
+            ),
+        ]
+        .spacing(10);
+
+        let current = self.mirror.as_ref().map_or(mirror::DEFAULT, |mirror| {
+            mirror.as_str().trim_end_matches('/')
+        });
+
+        let mirrors = mirror::KNOWN
+            .iter()
+            .copied()
+            .chain((!mirror::KNOWN.contains(&current)).then_some(current))
+            .map(|url| {
+                let is_current = url == current;
+
+                let health: Option<Element<'_, _>> =
+                    self.mirror_health.get(url).map(|health| match health {
+                        None => text("Checking...").size(12).style(text::secondary).into(),
+                        Some(Ok(latency)) => text!("{} ms", latency.as_millis())
+                            .size(12)
+                            .font(Font::MONOSPACE)
+                            .style(text::success)
+                            .into(),
+                        Some(Err(_error)) => {
+                            text("Unreachable").size(12).style(text::danger).into()
+                        }
+                    });
+
+                container(
+                    row![
+                        is_current.then(|| icon::check().style(text::success)),
+                        text(url).font(Font::MONOSPACE).width(Fill),
+                        health,
+                        button(text("Check").size(12))
+                            .on_press(Message::CheckMirror(url.to_owned()))
+                            .style(button::secondary),
+                        (!is_current).then(|| {
+                            button(text("Use").size(12))
+                                .on_press(Message::UseMirror(url.to_owned()))
+                        }),
+                    ]
+                    .spacing(10)
+                    .align_y(Center),
+                )
+                .padding(10)
+                .style(container::bordered_box)
+                .into()
+            });
+
+        column![
+            header,
+            column(mirrors).spacing(10),
+            text_input("https://hf-mirror.com", &self.mirror_input)
+                .on_input(Message::MirrorChanged)
+                .on_submit(Message::SaveMirror)
+                .font(Font::MONOSPACE)
+                .padding(10),
+            self.mirror_error
+                .as_ref()
+                .map(|error| text(error).size(12).style(text::danger)),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    pub fn maintenance(&self, library: &model::Library) -> Element<'_, Message> {
+        let header = column![
+            text("Maintenance")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Verify the model files of your library against Hugging Face, and clean up \
+                abandoned downloads and empty folders. Stored chats can be converted \
+                here too."
+            ),
+        ]
+        .spacing(10);
+
+        let is_checking = self.maintenance_progress.is_some();
+
+        let check = row![
+            toggler(self.verify_checksums)
+                .label("Compare checksums (reads every file, slow)")
+                .on_toggle_maybe((!is_checking).then_some(Message::ToggleChecksums)),
+            horizontal_space(),
+            button(if is_checking {
+                "Checking..."
+            } else {
+                "Check Library"
+            })
+            .on_press_maybe(
+                (!is_checking).then(|| Message::CheckLibrary(library.directory().clone()))
+            ),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        let progress = self.maintenance_progress.map(|progress| {
+            column![
+                text!(
+                    "Checked {} of {} files...",
+                    progress.checked,
+                    progress.total
+                )
+                .size(12)
+                .style(text::secondary),
+                progress_bar(0.0..=progress.total.max(1) as f32, progress.checked as f32).girth(4),
+            ]
+            .spacing(5)
+        });
+
+        let report = self
+            .maintenance
+            .as_ref()
+            .filter(|_| !is_checking)
+            .map(|(_, report)| maintenance_report(report, self.is_repairing));
+
+        let conversion = column![
+            row![
+                text!(
+                    "Chats saved before changing their format stay as they were. \
+                    Convert them all to {} at once.",
+                    self.chat_format
+                )
+                .width(Fill),
+                button(if self.is_converting_chats {
+                    "Converting..."
+                } else {
+                    "Convert Chats"
+                })
+                .on_press_maybe((!self.is_converting_chats).then_some(Message::ConvertChats)),
+            ]
+            .spacing(10)
+            .align_y(Center),
+            self.chat_conversion
+                .as_ref()
+                .filter(|_| !self.is_converting_chats)
+                .map(|result| match result {
+                    Ok(0) => text("Every chat was in this format already.")
+                        .size(12)
+                        .style(text::secondary),
+                    Ok(converted) => text!("Converted {converted} chats.")
+                        .size(12)
+                        .style(text::success),
+                    Err(error) => text(error.to_string())
+                        .font(Font::MONOSPACE)
+                        .size(12)
+                        .style(text::danger),
+                }),
+        ]
+        .spacing(5);
+
+        column![header, check, progress, report, conversion]
+            .spacing(20)
+            .into()
+    }
+
+    fn converter(&self) -> Element<'_, Message> {
+        let header = column![
+            text("GGUF Conversion")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Repositories without GGUF files can be converted with the tools of \
+                llama.cpp. Point these to a checkout of it with its Python requirements \
+                installed."
+            ),
+        ]
+        .spacing(10);
+
+        fn field<'a>(
+            label: &'a str,
+            input: impl Into<Element<'a, Message>>,
+        ) -> Element<'a, Message> {
+            row![text(label).width(150), input.into()]
+                .spacing(10)
+                .align_y(Center)
+                .into()
+        }
+
+        column![
+            header,
+            field(
+                "Python",
+                text_input("python3", &self.converter.python)
+                    .on_input(Message::ConverterPythonChanged)
+                    .on_submit(Message::SaveConverter)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+            ),
+            field(
+                "Conversion script",
+                row![
+                    text_input("convert_hf_to_gguf.py", &self.converter.script)
+                        .on_input(Message::ConverterScriptChanged)
+                        .on_submit(Message::SaveConverter)
+                        .font(Font::MONOSPACE)
+                        .padding(10),
+                    button(icon::folder_open())
+                        .on_press(Message::PickConverterScript)
+                        .padding(10),
+                ]
+                .spacing(10),
+            ),
+            field(
+                "Quantizer",
+                text_input("llama-quantize", &self.converter.quantize)
+                    .on_input(Message::ConverterQuantizeChanged)
+                    .on_submit(Message::SaveConverter)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+            ),
+            row![
+                horizontal_space(),
+                button("Save").on_press(Message::SaveConverter)
+            ],
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    pub fn backup(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Backup")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Save your chats, settings, bookmarks, prompts, and personas to a single \
+                archive, and restore them on another machine."
+            ),
+        ]
+        .spacing(10);
+
+        let create = column![
+            row![
+                toggler(self.backup_models)
+                    .label("Include model metadata (never the weights)")
+                    .on_toggle(Message::ToggleBackupModels),
+                horizontal_space(),
+                button("Create Backup...")
+                    .on_press_maybe((!self.is_archiving).then_some(Message::CreateBackup)),
+            ]
+            .spacing(10)
+            .align_y(Center),
+            self.backup.as_ref().map(|result| match result {
+                Ok(manifest) => text!("Backed up {} files.", manifest.files).style(text::success),
+                Err(error) => text(error.to_string())
+                    .font(Font::MONOSPACE)
+                    .style(text::danger),
+            }),
+        ]
+        .spacing(10);
+
+        let restore: Element<'_, _> = match &self.restore {
+            None => row![
+                text("Restoring overwrites the current data.").style(text::secondary),
+                horizontal_space(),
+                button("Restore...")
+                    .on_press_maybe((!self.is_archiving).then_some(Message::PickBackup))
+                    .style(button::secondary),
+            ]
+            .align_y(Center)
+            .into(),
+            Some((path, Ok(manifest))) => {
+                let summary = text!(
+                    "{path} · {files} files · made by icebreaker {version} on {date}",
+                    path = path.display(),
+                    files = manifest.files,
+                    version = manifest.app_version,
+                    date = manifest.created_at.format("%Y-%m-%d %H:%M"),
+                )
+                .font(Font::MONOSPACE)
+                .size(12);
+
+                let warning = if manifest.is_compatible() {
+                    text("Your current chats and settings will be overwritten.").style(text::danger)
+                } else {
+                    text("This backup was made by a newer version of icebreaker.")
+                        .style(text::danger)
+                };
+
+                column![
+                    summary,
+                    row![
+                        warning,
+                        horizontal_space(),
+                        button("Cancel")
+                            .on_press(Message::CancelRestore)
+                            .style(button::secondary),
+                        button("Restore")
+                            .on_press_maybe(
+                                (manifest.is_compatible() && !self.is_archiving)
+                                    .then_some(Message::Restore)
+                            )
+                            .style(button::danger),
+                    ]
+                    .spacing(10)
+                    .align_y(Center)
+                ]
+                .spacing(10)
+                .into()
+            }
+            Some((_path, Err(error))) => row![
+                text(error.to_string())
+                    .font(Font::MONOSPACE)
+                    .style(text::danger),
+                horizontal_space(),
+                button("Cancel")
+                    .on_press(Message::CancelRestore)
+                    .style(button::secondary),
+            ]
+            .spacing(10)
+            .align_y(Center)
+            .into(),
+        };
+
+        let manifest = column![
+            column![
+                text("Library Manifest")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(20),
+                text(
+                    "List your models and providers, without any secrets, so another machine \
+                    can download the same toolkit."
+                ),
+            ]
+            .spacing(10),
+            row![
+                horizontal_space(),
+                button("Import...")
+                    .on_press_maybe((!self.is_archiving).then_some(Message::ImportSetup))
+                    .style(button::secondary),
+                button("Export...")
+                    .on_press_maybe((!self.is_archiving).then_some(Message::ExportSetup)),
+            ]
+            .spacing(10)
+            .align_y(Center),
+            self.setup_progress.map(|progress| {
+                column![
+                    text!(
+                        "Downloading model {} of {}...",
+                        (progress.model + 1).min(progress.total),
+                        progress.total
+                    )
+                    .size(12)
+                    .style(text::secondary),
+                    progress_bar(0.0..=100.0, progress.percent as f32).girth(4),
+                ]
+                .spacing(5)
+            }),
+            self.setup.as_ref().map(|result| match result {
+                Ok(summary) => text(summary).style(text::success),
+                Err(error) => text(error.to_string())
+                    .font(Font::MONOSPACE)
+                    .style(text::danger),
+            }),
+        ]
+        .spacing(10);
+
+        column![header, create, restore, manifest]
+            .spacing(20)
+            .into()
+    }
+
+    pub fn profiles(&self) -> Element<'_, Message> {
+        row![
+            column![
+                text("Profiles")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(20),
+                text("Keep separate settings, API keys, chats, and bookmarks for work and home.")
+                    .width(Fill)
+            ]
+            .spacing(10),
+            row![
+                container(value(&self.profile).font(Font::MONOSPACE))
+                    .padding(10)
+                    .style(container::bordered_box),
+                button("Switch...").on_press(Message::OpenProfiles),
+            ]
+            .align_y(Center)
+            .spacing(10)
+        ]
+        .align_y(Center)
+        .spacing(20)
+        .into()
+    }
+
+    pub fn workspaces(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Workspaces")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Split the main area to keep the model library or the server log next to \
+                the current screen. Drag a title bar to move a pane and save arrangements \
+                you like under a name."
+            ),
+        ]
+        .spacing(10);
+
+        let panes = row![
+            button("Open Models")
+                .on_press(Message::OpenPane(Pane::Models))
+                .style(button::secondary),
+            button("Open Server Log")
+                .on_press(Message::OpenPane(Pane::Log))
+                .style(button::secondary),
+        ]
+        .spacing(10);
+
+        let save = row![
+            text_input("Workspace name...", &self.workspace_name)
+                .on_input(Message::WorkspaceNameChanged)
+                .on_submit(Message::SaveWorkspace),
+            button("Save current").on_press_maybe(
+                (!self.workspace_name.trim().is_empty()).then_some(Message::SaveWorkspace)
+            ),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        let workspaces = column(
+            self.workspaces
+                .iter()
+                .enumerate()
+                .map(|(index, workspace)| {
+                    row![
+                        text(&workspace.name).font(Font::MONOSPACE).width(Fill),
+                        button("Apply").on_press(Message::ApplyWorkspace(index)),
+                        button(icon::trash())
+                            .on_press(Message::DeleteWorkspace(index))
+                            .style(button::danger),
+                    ]
+                    .spacing(10)
+                    .align_y(Center)
+                    .into()
+                }),
+        )
+        .spacing(10);
+
+        column![
+            header,
+            panes,
+            save,
+            workspaces,
+            self.workspace_error
+                .as_ref()
+                .map(|error| value(error).font(Font::MONOSPACE).style(text::danger)),
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    pub fn sync(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Sync")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Keep your chats and settings in a folder shared with your other devices \
+                through Syncthing, Dropbox, or similar. The library and sync folders \
+                stay specific to each device."
+            ),
+        ]
+        .spacing(10);
+
+        let folder = row![
+            container(
+                value(
+                    self.sync_folder
+                        .as_deref()
+                        .map(|folder| folder.display().to_string())
+                        .unwrap_or_else(|| "Not syncing".to_owned())
+                )
+                .font(Font::MONOSPACE)
+            )
+            .width(Fill)
+            .padding(10)
+            .style(container::bordered_box),
+            button(icon::folder_open()).on_press(Message::PickSyncFolder),
+            self.sync_folder.as_ref().map(|_| button("Stop Syncing")
+                .on_press(Message::StopSyncing)
+                .style(button::secondary)),
+        ]
+        .align_y(Center)
+        .spacing(10);
+
+        let status: Element<'_, _> = match &self.sync {
+            None => text("Chats are also synced every time icebreaker starts.")
+                .style(text::secondary)
+                .into(),
+            Some(Ok(summary)) => text!(
+                "Sent {pushed} and received {pulled} files.",
+                pushed = summary.pushed,
+                pulled = summary.pulled,
+            )
+            .style(text::success)
+            .into(),
+            Some(Err(error)) => text(error.to_string())
+                .font(Font::MONOSPACE)
+                .style(text::danger)
+                .into(),
+        };
+
+        let actions = row![
+            status,
+            horizontal_space(),
+            button("Sync Now").on_press_maybe(
+                (self.sync_folder.is_some() && !self.is_syncing).then_some(Message::SyncNow)
+            ),
+        ]
+        .align_y(Center)
+        .spacing(10);
+
+        let conflicts = self
+            .sync
+            .as_ref()
+            .and_then(|result| result.as_ref().ok())
+            .map(|summary| summary.conflicts.as_slice())
+            .unwrap_or_default();
+
+        let conflicts = column(conflicts.iter().enumerate().map(|(index, conflict)| {
+            let side = |label, chat: &core::Chat| {
+                let last = chat.history.iter().rev().find_map(|item| match item {
+                    core::chat::Item::User(message) => Some(message.as_str()),
+                    core::chat::Item::Reply(reply) => Some(reply.content.as_str()),
+                    _ => None,
+                });
+
+                column![
+                    text!("{label} · {} messages", chat.history.len())
+                        .font(Font::MONOSPACE)
+                        .size(12)
+                        .style(text::secondary),
+                    ellipsized_text(last.unwrap_or_default().to_owned())
+                        .wrapping(text::Wrapping::None)
+                        .width(Fill),
+                ]
+                .spacing(5)
+                .width(Fill)
+            };
+
+            let resolve = |label, resolution| {
+                button(text(label).size(14))
+                    .on_press_maybe(
+                        (!self.is_syncing).then_some(Message::Resolve(index, resolution)),
+                    )
+                    .style(button::secondary)
+            };
+
+            container(
+                column![
+                    text(conflict.local.title.as_deref().unwrap_or("Untitled")).font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::default()
+                    }),
+                    row![
+                        side("This device", &conflict.local),
+                        side("Other device", &conflict.remote),
+                    ]
+                    .spacing(20),
+                    row![
+                        text("Changed on both devices.").style(text::danger),
+                        horizontal_space(),
+                        resolve("Keep Mine", Resolution::KeepLocal),
+                        resolve("Keep Theirs", Resolution::KeepRemote),
+                        resolve("Keep Both", Resolution::KeepBoth),
+                    ]
+                    .spacing(10)
+                    .align_y(Center),
+                ]
+                .spacing(10),
+            )
+            .padding(10)
+            .style(container::bordered_box)
+            .into()
+        }))
+        .spacing(10);
+
+        column![header, folder, actions, conflicts]
+            .spacing(20)
+            .into()
+    }
+
+    pub fn theme<'a>(&'a self, current: &'a Theme) -> Element<'a, Message> {
+        let swatch = |color| {
+            container(horizontal_space())
+                .width(10)
+                .height(10)
+                .style(move |_theme: &Theme| {
+                    container::Style::default()
+                        .background(color)
+                        .border(border::rounded(5))
+                })
+                .into()
+        };
+
+        let themes = self.themes.iter().map(|theme| {
+            let palette = theme.palette();
+
+            let item = button(
+                ellipsized_text(theme.to_string())
+                    .font(Font::MONOSPACE)
+                    .width(Fill)
+                    .wrapping(text::Wrapping::None)
+                    .center(),
+            )
+            .on_press_with(|| Message::ChangeTheme(theme.clone()))
+            .padding([10, 0])
+            .style(move |_theme, status| {
+                let mut style = button::background(theme, status);
+
+                style.border = style.border.rounded(5);
+
+                if current == theme {
+                    button::Style {
+                        border: style.border.color(current.palette().primary).width(3),
+                        ..style
+                    }
+                } else {
+                    style
+                }
+            });
+
+            let swatches = {
+                let colors = [palette.primary, palette.success, palette.danger];
+
+                right_center(row(colors.into_iter().map(swatch)).spacing(5))
+                    .padding(padding::right(10))
+            };
+
+            if current == theme {
+                float(stack![item, swatches]).scale(1.1).into()
+            } else {
+                hover(item, swatches)
+            }
+        });
+
+        container(grid(themes).spacing(10).fluid(300).height(Shrink)).into()
+    }
+
+    pub fn chat(&self, library: &model::Library) -> Element<'_, Message> {
+        let preloads: Vec<_> = iter::once(Preload::Nothing)
+            .chain(library.files.values().filter_map(|file| match file {
+                model::FileOrAPI::File(file) => Some(Preload::File(file.clone())),
+                model::FileOrAPI::API(_) => None,
+            }))
+            .collect();
+
+        // Older settings stored files without their size
+        let selected = match &self.preload {
+            Some(preload) => preloads.iter().find(|option| {
+                matches!(
+                    option,
+                    Preload::File(file) if file.model == preload.model && file.name == preload.name
+                )
+            }),
+            None => preloads.first(),
+        }
+        .cloned();
+
+        let preload = row![
+            column![
+                text("Preload at Startup")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(20),
+                text(
+                    "This model starts loading as soon as the app opens, \
+                    so your first message does not wait for it."
+                )
+                .width(Fill)
+            ]
+            .spacing(10),
+            pick_list(preloads, selected, Message::ChangePreload),
+        ]
+        .align_y(Center)
+        .spacing(20);
+
+        let collapse = row![
+            column![
+                text("Long Messages")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(20),
+                text("Assistant replies taller than this are collapsed behind \"Show more\".")
+                    .width(Fill)
+            ]
+            .spacing(10),
+            pick_list(
+                Collapse::ALL,
+                Some(Collapse::from_height(self.collapse_height)),
+                Message::ChangeCollapseHeight,
+            ),
+        ]
+        .align_y(Center)
+        .spacing(20);
+
+        let quick_actions = column![
+            text("Quick Actions")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text("Follow-up buttons shown under assistant replies. Edit them in:"),
+            value(QuickAction::path().display())
+                .font(Font::MONOSPACE)
+                .size(14)
+                .style(text::secondary),
+        ]
+        .spacing(10);
+
+        let history = column![
+            text("Chat History")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text("Every chat is stored as a file in this directory."),
+            row![
+                text("Format").width(Fill),
+                pick_list(
+                    chat::Format::ALL,
+                    Some(self.chat_format),
+                    Message::ChangeChatFormat
+                ),
+            ]
+            .spacing(10)
+            .align_y(Center),
+            row![
+                ellipsized_text(Chat::directory().display().to_string())
+                    .font(Font::MONOSPACE)
+                    .wrapping(text::Wrapping::None),
+                button(text("Open").size(12))
+                    .on_press(Message::OpenChats)
+                    .style(button::secondary),
+            ]
+            .spacing(10)
+            .align_y(Center),
+        ]
+        .spacing(10);
+
+        let presets = column![
+            text("Generation Presets")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Shown next to the input, after Creative, Balanced and Precise. \
+                Empty fields keep the defaults of the model."
+            ),
+            column(
+                self.presets
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (name, fields))| {
+                        let field = |field: usize, placeholder: &'static str| {
+                            text_input(placeholder, &fields[field])
+                                .on_input(move |value| {
+                                    Message::PresetFieldChanged(index, field, value)
+                                })
+                                .font(Font::MONOSPACE)
+                                .width(110)
+                                .padding(10)
+                        };
+
+                        row![
+                            text_input("Name", name)
+                                .on_input(move |name| Message::PresetNameChanged(index, name))
+                                .padding(10),
+                            field(0, "Temperature"),
+                            field(1, "Top P"),
+                            field(2, "Max tokens"),
+                            button(icon::trash())
+                                .on_press(Message::RemovePreset(index))
+                                .style(button::text),
+                        ]
+                        .spacing(10)
+                        .align_y(Center)
+                        .into()
+                    })
+            )
+            .spacing(10),
+            row![
+                button(icon::plus())
+                    .on_press(Message::AddPreset)
+                    .style(button::secondary),
+                horizontal_space(),
+                button("Save").on_press(Message::SavePresets),
+            ],
+        ]
+        .spacing(10);
+
+        let reminders = {
+            let toggle = row![
+                column![
+                    text("Reminders")
+                        .font(Font {
+                            weight: font::Weight::Semibold,
+                            ..Font::MONOSPACE
+                        })
+                        .size(20),
+                    text(
+                        "Let the assistant set reminders while searching, \
+                        shown as desktop notifications when due."
+                    ),
+                ]
+                .spacing(10)
+                .width(Fill),
+                toggler(self.reminders).on_toggle(Message::ToggleReminders),
+            ]
+            .spacing(20)
+            .align_y(Center);
+
+            let upcoming: Element<'_, _> = match &self.upcoming {
+                None => text("Listing reminders...").style(text::secondary).into(),
+                Some(Err(error)) => value(error).style(text::danger).into(),
+                Some(Ok(upcoming)) if upcoming.is_empty() => text("No reminders are pending.")
+                    .style(text::secondary)
+                    .into(),
+                Some(Ok(upcoming)) => column(upcoming.iter().map(|reminder| {
+                    row![
+                        text(reminder.at.format("%Y-%m-%d %H:%M").to_string())
+                            .font(Font::MONOSPACE)
+                            .size(14),
+                        text(&reminder.message).size(14).width(Fill),
+                        button(icon::trash())
+                            .on_press(Message::DeleteReminder(reminder.id))
+                            .style(button::text),
+                    ]
+                    .spacing(10)
+                    .align_y(Center)
+                    .into()
+                }))
+                .spacing(10)
+                .into(),
+            };
+
+            column![toggle, upcoming].spacing(10)
+        };
+
+        column![
+            preload,
+            collapse,
+            presets,
+            quick_actions,
+            history,
+            reminders
+        ]
+        .spacing(30)
+        .into()
+    }
+
+    pub fn clipboard(&self) -> Element<'_, Message> {
+        let watch = row![
+            column![
+                text("Clipboard Watch")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(20),
+                text("Offer quick actions whenever new text is copied to the clipboard."),
+            ]
+            .spacing(10)
+            .width(Fill),
+            toggler(self.clipboard_watch).on_toggle(Message::ToggleClipboardWatch),
+        ]
+        .spacing(20)
+        .align_y(Center);
+
+        let actions = column(
+            self.clipboard_actions
+                .iter()
+                .enumerate()
+                .map(|(index, action)| {
+                    row![
+                        text_input("Name", &action.name)
+                            .on_input(move |name| Message::ClipboardActionNameChanged(index, name))
+                            .width(120)
+                            .padding(10),
+                        text_input("Prompt, with {text} as placeholder", &action.prompt)
+                            .on_input(move |prompt| Message::ClipboardActionPromptChanged(
+                                index, prompt
+                            ))
+                            .font(Font::MONOSPACE)
+                            .padding(10),
+                        button(icon::trash())
+                            .on_press(Message::RemoveClipboardAction(index))
+                            .style(button::text),
+                    ]
+                    .spacing(10)
+                    .align_y(Center)
+                    .into()
+                }),
+        )
+        .spacing(10);
+
+        column![
+            watch,
+            text("Quick Actions")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            actions,
+            row![
+                button(icon::plus())
+                    .on_press(Message::AddClipboardAction)
+                    .style(button::secondary),
+                horizontal_space(),
+                button("Save").on_press(Message::SaveClipboardActions),
+            ]
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    pub fn sharing(&self) -> Element<'_, Message> {
+        column![
+            text("Paste Endpoint")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Shared chats are rendered as a single HTML page and uploaded here. \
+                The endpoint must reply with the URL of the paste. \
+                Leave it empty to only allow exporting to a file."
+            ),
+            text_input("https://paste.example.com", &self.share_endpoint)
+                .on_input(Message::ShareEndpointChanged)
+                .on_submit(Message::SaveShareEndpoint)
+                .font(Font::MONOSPACE)
+                .padding(10),
+            self.share_endpoint_error
+                .as_ref()
+                .map(|error| text(error).size(12).style(text::danger)),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    pub fn execution(&self) -> Element<'_, Message> {
+        let enabled = row![
+            column![
+                text("Code Execution")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(20),
+                text(
+                    "Show a Run button on Python and shell code blocks. \
+                    Code runs in a temporary directory without network access \
+                    or access to your home folder, and its output is added to \
+                    the chat. Requires bubblewrap on Linux."
+                ),
+            ]
+            .spacing(10)
+            .width(Fill),
+            toggler(self.sandbox.enabled).on_toggle(Message::ToggleSandbox),
+        ]
+        .spacing(20)
+        .align_y(Center);
+
+        let field = |label, input| {
+            row![text(label).width(150), input]
+                .spacing(10)
+                .align_y(Center)
+        };
+
+        column![
+            enabled,
+            field(
+                "Python",
+                text_input("python3", &self.sandbox.python)
+                    .on_input(Message::PythonInterpreterChanged)
+                    .on_submit(Message::SaveSandbox)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+            ),
+            field(
+                "Shell",
+                text_input("sh", &self.sandbox.shell)
+                    .on_input(Message::ShellInterpreterChanged)
+                    .on_submit(Message::SaveSandbox)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+            ),
+            field(
+                "Timeout (seconds)",
+                text_input("10", &self.sandbox_timeout)
+                    .on_input(Message::SandboxTimeoutChanged)
+                    .on_submit(Message::SaveSandbox)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+            ),
+            row![
+                horizontal_space(),
+                button("Save").on_press(Message::SaveSandbox)
+            ],
+            self.file_access(),
+            self.fetching(),
+            self.databases(),
+            self.tool_approvals(),
+            self.agent(),
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    fn fetching(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Page Fetching")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Chats with Fetch enabled let the assistant read the pages of these domains, \
+                including their subdomains. Leave the allowed domains empty to allow any \
+                domain that is not denied."
+            ),
+        ]
+        .spacing(10);
+
+        let field = |list: usize, label, placeholder| {
+            row![
+                text(label).width(150),
+                text_input(placeholder, &self.fetch_domains[list])
+                    .on_input(Message::FetchDomainsChanged.with(list))
+                    .on_submit(Message::SaveFetchDomains)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+            ]
+            .spacing(10)
+            .align_y(Center)
+        };
+
+        column![
+            header,
+            field(0, "Allowed", "docs.rs, wikipedia.org"),
+            field(1, "Denied", "example.com"),
+            row![
+                horizontal_space(),
+                button("Save").on_press(Message::SaveFetchDomains)
+            ],
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    fn agent(&self) -> Element<'_, Message> {
+        row![
+            column![
+                text("Agent Steps")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(20),
+                text(
+                    "How many tools an agent may call for a single reply, \
+                    including its final answer."
+                )
+                .width(Fill),
+            ]
+            .spacing(10),
+            pick_list(
+                [4, 8, 16, 32],
+                Some(self.agent_steps),
+                Message::ChangeAgentSteps
+            ),
+        ]
+        .align_y(Center)
+        .spacing(20)
+        .into()
+    }
+
+    fn tool_approvals(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Tool Approvals")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Choose whether each tool of the assistant runs right away, asks you first \
+                with its exact arguments, or never runs. Approved calls are kept with the chat."
+            ),
+        ]
+        .spacing(10);
+
+        let mut tools: Vec<&str> = approval::BUILT_IN.to_vec();
+
+        for tool in self
+            .plugin_tools
+            .iter()
+            .chain(self.tool_policies.tools.keys())
+        {
+            if !tools.contains(&tool.as_str()) {
+                tools.push(tool);
+            }
+        }
+
+        let tools = column(tools.into_iter().map(|tool| {
+            row![
+                text(tool).font(Font::MONOSPACE).width(Fill),
+                pick_list(
+                    Policy::ALL,
+                    Some(self.tool_policies.get(tool)),
+                    move |policy| Message::ToolPolicyChanged(tool.to_owned(), policy)
+                ),
+            ]
+            .spacing(10)
+            .align_y(Center)
+            .into()
+        }))
+        .spacing(10);
+
+        column![header, tools].spacing(20).into()
+    }
+
+    fn databases(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Databases")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Attach a database to a chat to let the assistant propose SQL queries. \
+                Queries only run once you press Run query, in a read-only session, and \
+                their results are shown as a table. Needs sqlite3 or psql to be installed."
+            ),
+        ]
+        .spacing(10);
+
+        let databases = column(self.databases.iter().enumerate().map(|(index, database)| {
+            row![
+                icon::server().style(text::secondary),
+                text(&database.name).width(150),
+                text(database.kind.to_string())
+                    .size(12)
+                    .style(text::secondary),
+                ellipsized_text(database.connection.clone())
+                    .font(Font::MONOSPACE)
+                    .wrapping(text::Wrapping::None)
+                    .width(Fill),
+                button(icon::trash())
+                    .on_press(Message::RemoveDatabase(index))
+                    .style(button::text),
+            ]
+            .spacing(10)
+            .align_y(Center)
+            .into()
+        }))
+        .spacing(10);
+
+        let placeholder = match self.new_database.kind {
+            database::Kind::Sqlite => "/path/to/database.sqlite",
+            database::Kind::Postgres => "postgres://reader@localhost/db",
+        };
+
+        let new = row![
+            text_input("Name", &self.new_database.name)
+                .on_input(Message::NewDatabaseNameChanged)
+                .on_submit(Message::AddDatabase)
+                .padding(10)
+                .width(150),
+            pick_list(
+                database::Kind::ALL,
+                Some(self.new_database.kind),
+                Message::NewDatabaseKindChanged
+            )
+            .padding(10),
+            text_input(placeholder, &self.new_database.connection)
+                .on_input(Message::NewDatabaseConnectionChanged)
+                .on_submit(Message::AddDatabase)
+                .font(Font::MONOSPACE)
+                .padding(10),
+            button("Add").on_press(Message::AddDatabase),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        column![header, databases, new].spacing(20).into()
+    }
+
+    fn file_access(&self) -> Element<'_, Message> {
+        let header = column![
+            text("File Access")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Let the assistant read files and list directories while searching, \
+                but only inside these folders. Every access is logged and shown in the chat."
+            ),
+        ]
+        .spacing(10);
+
+        let roots = column(self.file_roots.iter().enumerate().map(|(index, root)| {
+            row![
+                icon::folder().style(text::secondary),
+                ellipsized_text(root.display().to_string())
+                    .font(Font::MONOSPACE)
+                    .wrapping(text::Wrapping::None)
+                    .width(Fill),
+                button(icon::trash())
+                    .on_press(Message::RemoveFileRoot(index))
+                    .style(button::text),
+            ]
+            .spacing(10)
+            .align_y(Center)
+            .into()
+        }))
+        .spacing(10);
+
+        let actions = row![
+            button(text("Add folder").size(12))
+                .on_press(Message::AddFileRoot)
+                .style(button::secondary),
+            horizontal_space(),
+            button(text("Show access log").size(12))
+                .on_press(Message::OpenFileAccessLog)
+                .style(button::secondary),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        column![header, roots, actions].spacing(20).into()
+    }
+
+    pub fn audit(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Audit Log")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Record every request and response to rotating JSONL files, \
+                with credentials stripped. Useful to debug billing discrepancies \
+                or to reproduce issues."
+            ),
+        ]
+        .spacing(10);
+
+        let providers = column(Provider::ALL.iter().map(|provider| {
+            row![
+                text(provider.name()).width(Fill),
+                toggler(self.audit.is_enabled(*provider))
+                    .on_toggle(Message::ToggleAudit.with(*provider)),
+            ]
+            .align_y(Center)
+            .into()
+        }))
+        .spacing(10);
+
+        let location = row![
+            ellipsized_text(Audit::directory().display().to_string())
+                .font(Font::MONOSPACE)
+                .wrapping(text::Wrapping::None),
+            button(text("Open").size(12))
+                .on_press(Message::OpenAuditLogs)
+                .style(button::secondary),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        column![header, providers, location].spacing(20).into()
+    }
+
+    pub fn timeouts(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Timeouts")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "How many seconds to wait for a server to accept the connection, \
+                to send its first token, and between tokens, before giving up."
+            ),
+        ]
+        .spacing(10);
+
+        let status_checks = row![
+            column![
+                text("Status Checks")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(20),
+                text("How many models can be checked at the same time in the models screen.")
+                    .width(Fill),
+            ]
+            .spacing(10),
+            pick_list(
+                [1, 2, 4, 8, 16],
+                Some(self.status_checks),
+                Message::ChangeStatusChecks,
+            ),
+        ]
+        .align_y(Center)
+        .spacing(20);
+
+        let check_every = row![
+            column![
+                text("Automatic Checks")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(20),
+                text("How often bookmarked models are checked while the models screen is open.")
+                    .width(Fill),
+            ]
+            .spacing(10),
+            pick_list(
+                CheckEvery::ALL,
+                Some(CheckEvery(self.status_interval)),
+                Message::ChangeStatusInterval,
+            ),
+        ]
+        .align_y(Center)
+        .spacing(20);
+
+        let stale_after = row![
+            column![
+                text("Stale Status")
+                    .font(Font {
+                        weight: font::Weight::Semibold,
+                        ..Font::MONOSPACE
+                    })
+                    .size(20),
+                text("Status checks older than this are shown as unchecked again.").width(Fill),
+            ]
+            .spacing(10),
+            pick_list(
+                StaleAfter::ALL,
+                Some(StaleAfter(self.status_max_age)),
+                Message::ChangeStatusMaxAge,
+            ),
+        ]
+        .align_y(Center)
+        .spacing(20);
+
+        let provider = pick_list(
+            Provider::ALL,
+            Some(self.timeout_provider),
+            Message::SelectTimeoutProvider,
+        )
+        .width(Fill);
+
+        let fields = ["Connect", "First token", "Between tokens"]
+            .into_iter()
+            .zip(&self.timeout_fields)
+            .enumerate()
+            .skip(usize::from(!Timeout::has_connect(self.timeout_provider)))
+            .map(|(index, (label, seconds))| {
+                row![
+                    text(label).width(150),
+                    text_input("seconds", seconds)
+                        .on_input(Message::TimeoutChanged.with(index))
+                        .on_submit(Message::SaveTimeouts)
+                        .font(Font::MONOSPACE)
+                        .padding(10),
+                ]
+                .spacing(10)
+                .align_y(Center)
+                .into()
+            });
+
+        column![
+            header,
+            provider,
+            column(fields).spacing(20),
+            row![
+                horizontal_space(),
+                button("Reset")
+                    .on_press(Message::ResetTimeouts)
+                    .style(button::secondary),
+                button("Save").on_press(Message::SaveTimeouts)
+            ]
+            .spacing(10),
+            status_checks,
+            check_every,
+            stale_after,
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    pub fn azure(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Azure OpenAI")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Chat with the models deployed to an Azure OpenAI resource. \
+                Each deployment shows up as a model in the search screen."
+            ),
+        ]
+        .spacing(10);
+
+        let field = |label, input| {
+            row![text(label).width(150), input]
+                .spacing(10)
+                .align_y(Center)
+        };
+
+        column![
+            header,
+            field(
+                "Endpoint",
+                text_input("https://my-resource.openai.azure.com", &self.azure.endpoint)
+                    .on_input(Message::AzureEndpointChanged)
+                    .on_submit(Message::SaveAzure)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+            ),
+            field(
+                "API key",
+                text_input("api-key", &self.azure.api_key)
+                    .on_input(Message::AzureApiKeyChanged)
+                    .on_submit(Message::SaveAzure)
+                    .secure(true)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+            ),
+            field(
+                "API version",
+                text_input("2024-10-21", &self.azure.api_version)
+                    .on_input(Message::AzureApiVersionChanged)
+                    .on_submit(Message::SaveAzure)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+            ),
+            field(
+                "Deployments",
+                text_input("gpt-4o, gpt-4o-mini", &self.azure_deployments)
+                    .on_input(Message::AzureDeploymentsChanged)
+                    .on_submit(Message::SaveAzure)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+            ),
+            row![
+                horizontal_space(),
+                button("Save").on_press(Message::SaveAzure)
+            ],
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    pub fn images(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Image Generation")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Generate images from a conversation with the Image toggle. \
+                Run a model locally with stable-diffusion.cpp or use any API \
+                compatible with OpenAI's image generations endpoint."
+            ),
+        ]
+        .spacing(10);
+
+        fn field<'a>(
+            label: &'a str,
+            input: impl Into<Element<'a, Message>>,
+        ) -> Element<'a, Message> {
+            row![text(label).width(150), input.into()]
+                .spacing(10)
+                .align_y(Center)
+                .into()
         }
+
+        let backend = field(
+            "Backend",
+            pick_list(
+                diffusion::Backend::ALL,
+                Some(self.diffusion.backend),
+                Message::SelectDiffusionBackend,
+            ),
+        );
+
+        let fields: Element<'_, _> = match self.diffusion.backend {
+            diffusion::Backend::Local => column![
+                field(
+                    "Executable",
+                    text_input("sd", &self.diffusion.program)
+                        .on_input(Message::DiffusionProgramChanged)
+                        .on_submit(Message::SaveDiffusion)
+                        .font(Font::MONOSPACE)
+                        .padding(10),
+                ),
+                field(
+                    "Model",
+                    row![
+                        text_input("/path/to/model.safetensors", &self.diffusion.model)
+                            .on_input(Message::DiffusionModelChanged)
+                            .on_submit(Message::SaveDiffusion)
+                            .font(Font::MONOSPACE)
+                            .padding(10),
+                        button(icon::folder_open())
+                            .on_press(Message::PickDiffusionModel)
+                            .padding(10),
+                    ]
+                    .spacing(10),
+                ),
+            ]
+            .spacing(20)
+            .into(),
+            diffusion::Backend::Api => column![
+                field(
+                    "Base URL",
+                    text_input("https://api.openai.com/v1", &self.diffusion.base_url)
+                        .on_input(Message::DiffusionBaseUrlChanged)
+                        .on_submit(Message::SaveDiffusion)
+                        .font(Font::MONOSPACE)
+                        .padding(10),
+                ),
+                field(
+                    "API key",
+                    text_input("sk-...", &self.diffusion.api_key)
+                        .on_input(Message::DiffusionApiKeyChanged)
+                        .on_submit(Message::SaveDiffusion)
+                        .secure(true)
+                        .font(Font::MONOSPACE)
+                        .padding(10),
+                ),
+                field(
+                    "Model",
+                    text_input("dall-e-3", &self.diffusion.api_model)
+                        .on_input(Message::DiffusionApiModelChanged)
+                        .on_submit(Message::SaveDiffusion)
+                        .font(Font::MONOSPACE)
+                        .padding(10),
+                ),
+            ]
+            .spacing(20)
+            .into(),
+        };
+
+        column![
+            header,
+            backend,
+            fields,
+            row![
+                horizontal_space(),
+                button("Save").on_press(Message::SaveDiffusion)
+            ],
+        ]
+        .spacing(20)
+        .into()
     }
 
-    pub fn view<'a>(&'a self, library: &model::Library, theme: &'a Theme) -> Element<'a, Message> {
-        let section = match self.section {
-            Section::Storage => self.storage(library),
-            Section::Theme => self.theme(theme),
-            Section::Mcp => self.mcp(),
+    pub fn transcription(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Transcription")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Turn audio and video files into a chat with Transcribe file... \
+                in the chat list. Files are converted with ffmpeg and transcribed \
+                locally by whisper.cpp."
+            ),
+        ]
+        .spacing(10);
+
+        let field = |label, input| {
+            row![text(label).width(150), input]
+                .spacing(10)
+                .align_y(Center)
         };
 
-        center_y(scrollable(
-            center_x(container(section).max_width(600)).padding(20),
-        ))
+        column![
+            header,
+            field(
+                "Executable",
+                text_input("whisper-cli", &self.whisper.program)
+                    .on_input(Message::WhisperProgramChanged)
+                    .on_submit(Message::SaveWhisper)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+            ),
+            row![
+                text("Model").width(150),
+                text_input("/path/to/ggml-base.en.bin", &self.whisper.model)
+                    .on_input(Message::WhisperModelChanged)
+                    .on_submit(Message::SaveWhisper)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+                button(icon::folder_open())
+                    .on_press(Message::PickWhisperModel)
+                    .padding(10),
+            ]
+            .spacing(10)
+            .align_y(Center),
+            field(
+                "ffmpeg",
+                text_input("ffmpeg", &self.whisper.ffmpeg)
+                    .on_input(Message::FfmpegChanged)
+                    .on_submit(Message::SaveWhisper)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+            ),
+            row![
+                horizontal_space(),
+                button("Save").on_press(Message::SaveWhisper)
+            ],
+        ]
+        .spacing(20)
         .into()
     }
 
-    pub fn storage(&self, library: &model::Library) -> Element<'_, Message> {
-        row![
+    pub fn ocr(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Text Recognition")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Models that cannot see images get the text in them instead, \
+                read locally by tesseract when the image is attached."
+            ),
+        ]
+        .spacing(10);
+
+        let field = |label, input| {
+            row![text(label).width(150), input]
+                .spacing(10)
+                .align_y(Center)
+        };
+
+        column![
+            header,
+            field(
+                "Executable",
+                text_input("tesseract", &self.ocr.program)
+                    .on_input(Message::OcrProgramChanged)
+                    .on_submit(Message::SaveOcr)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+            ),
+            field(
+                "Languages",
+                text_input("eng+deu", &self.ocr.languages)
+                    .on_input(Message::OcrLanguagesChanged)
+                    .on_submit(Message::SaveOcr)
+                    .font(Font::MONOSPACE)
+                    .padding(10),
+            ),
+            row![
+                horizontal_space(),
+                button("Save").on_press(Message::SaveOcr)
+            ],
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    pub fn hooks(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Hooks")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Run commands whenever something happens. Each command gets the event \
+                as JSON in its standard input; the output of message hooks can be \
+                added to the chat."
+            ),
+        ]
+        .spacing(10);
+
+        let hooks = column(self.hooks.iter().enumerate().map(|(index, hook)| {
+            let inject = hook.event.can_inject().then(|| {
+                toggler(hook.inject)
+                    .label("Add output to chat")
+                    .on_toggle(move |inject| Message::ToggleHookInject(index, inject))
+            });
+
             column![
-                text("Model Library")
-                    .font(Font {
-                        weight: font::Weight::Semibold,
-                        ..Font::MONOSPACE
+                row![
+                    pick_list(hook::Event::ALL, Some(hook.event), move |event| {
+                        Message::HookEventChanged(index, event)
                     })
-                    .size(20),
-                text("Models will be downloaded and stored in this directory.").width(Fill)
+                    .width(180),
+                    text_input("notify-send \"$ICEBREAKER_EVENT\"", &hook.command)
+                        .on_input(move |command| Message::HookCommandChanged(index, command))
+                        .font(Font::MONOSPACE)
+                        .padding(10),
+                    button(icon::trash())
+                        .on_press(Message::RemoveHook(index))
+                        .style(button::text),
+                ]
+                .spacing(10)
+                .align_y(Center),
+                inject,
             ]
-            .spacing(10),
+            .spacing(10)
+            .into()
+        }))
+        .spacing(20);
+
+        column![
+            header,
+            hooks,
             row![
-                container(value(library.directory().path().display()).font(Font::MONOSPACE))
-                    .width(300)
+                button(icon::plus())
+                    .on_press(Message::AddHook)
+                    .style(button::secondary),
+                horizontal_space(),
+                button("Save").on_press(Message::SaveHooks),
+            ]
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    pub fn hosted(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Hosted Providers")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Chat with the models served by Groq, Together.ai, and Mistral. \
+                Their catalogs, pricing, and speeds show up in the search screen."
+            ),
+        ]
+        .spacing(10);
+
+        let field = |label, key, on_input: fn(String) -> Message| {
+            row![
+                text(label).width(150),
+                text_input("API key", key)
+                    .on_input(on_input)
+                    .on_submit(Message::SaveKeys)
+                    .secure(true)
+                    .font(Font::MONOSPACE)
                     .padding(10)
-                    .style(container::bordered_box),
-                button(icon::folder_open()).on_press(Message::PickLibraryFolder),
             ]
+            .spacing(10)
             .align_y(Center)
+        };
+
+        column![
+            header,
+            field("Groq", &self.keys.groq, Message::GroqKeyChanged),
+            field(
+                "Together.ai",
+                &self.keys.together,
+                Message::TogetherKeyChanged
+            ),
+            field("Mistral", &self.keys.mistral, Message::MistralKeyChanged),
+            row![
+                horizontal_space(),
+                button("Save").on_press(Message::SaveKeys)
+            ],
+            row![
+                text("Codebase embeddings").width(150),
+                pick_list(
+                    Embeddings::ALL,
+                    Some(self.embeddings),
+                    Message::SelectEmbeddings
+                )
+                .width(Fill),
+            ]
             .spacing(10)
+            .align_y(Center),
+            text("Codebases are embedded when you attach them to a conversation.")
+                .size(12)
+                .style(text::secondary),
         ]
-        .align_y(Center)
         .spacing(20)
         .into()
     }
 
-    pub fn theme<'a>(&'a self, current: &'a Theme) -> Element<'a, Message> {
-        let swatch = |color| {
-            container(horizontal_space())
-                .width(10)
-                .height(10)
-                .style(move |_theme: &Theme| {
-                    container::Style::default()
-                        .background(color)
-                        .border(border::rounded(5))
+    pub fn network(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Local Network")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
                 })
-                .into()
-        };
+                .size(20),
+            text(
+                "Find llama.cpp, vLLM, and LM Studio servers on your local network \
+                and chat with their models. Registering a server replaces the previous one."
+            ),
+        ]
+        .spacing(10);
 
-        let themes = self.themes.iter().map(|theme| {
-            let palette = theme.palette();
+        let scan = row![
+            horizontal_space(),
+            button(if self.is_scanning {
+                "Scanning..."
+            } else {
+                "Scan Network"
+            })
+            .on_press_maybe((!self.is_scanning).then_some(Message::ScanNetwork))
+        ];
 
-            let item = button(
-                ellipsized_text(theme.to_string())
-                    .font(Font::MONOSPACE)
-                    .width(Fill)
-                    .wrapping(text::Wrapping::None)
-                    .center(),
+        let servers = column(self.servers.iter().map(|server| {
+            let base_url = server.base_url();
+            let is_registered = self.registered.as_ref() == Some(&base_url);
+
+            let register: Element<'_, _> = if is_registered {
+                row![icon::check().style(text::success), text("Registered")]
+                    .spacing(5)
+                    .align_y(Center)
+                    .into()
+            } else {
+                button("Register")
+                    .on_press(Message::RegisterServer(server.clone()))
+                    .into()
+            };
+
+            container(
+                row![
+                    column![
+                        text!("{} · {base_url}", server.kind).font(Font::MONOSPACE),
+                        text(server.models.join(", "))
+                            .size(12)
+                            .style(text::secondary),
+                    ]
+                    .spacing(5)
+                    .width(Fill),
+                    register,
+                ]
+                .spacing(10)
+                .align_y(Center),
             )
-            .on_press_with(|| Message::ChangeTheme(theme.clone()))
-            .padding([10, 0])
-            .style(move |_theme, status| {
-                let mut style = button::background(theme, status);
+            .padding(10)
+            .style(container::bordered_box)
+            .into()
+        }))
+        .spacing(10);
 
-                style.border = style.border.rounded(5);
+        let status = if let Some(error) = &self.scan_error {
+            Some(value(error).font(Font::MONOSPACE).style(text::danger))
+        } else if !self.is_scanning && self.servers.is_empty() {
+            Some(text("No servers found yet.").style(text::secondary))
+        } else {
+            None
+        };
 
-                if current == theme {
-                    button::Style {
-                        border: style.border.color(current.palette().primary).width(3),
-                        ..style
-                    }
-                } else {
-                    style
-                }
-            });
+        column![header, scan, status, servers].spacing(20).into()
+    }
 
-            let swatches = {
-                let colors = [palette.primary, palette.success, palette.danger];
+    pub fn remote(&self) -> Element<'_, Message> {
+        let header = column![
+            text("Remote Machine")
+                .font(Font {
+                    weight: font::Weight::Semibold,
+                    ..Font::MONOSPACE
+                })
+                .size(20),
+            text(
+                "Run the models of another machine over SSH. icebreaker starts \
+                llama-server there and tunnels it here, so its models show up in the search screen."
+            ),
+        ]
+        .spacing(10);
 
-                right_center(row(colors.into_iter().map(swatch)).spacing(5))
-                    .padding(padding::right(10))
-            };
+        let field = |label, placeholder, value, on_input: fn(String) -> Message| {
+            row![
+                text(label).width(150),
+                text_input(placeholder, value)
+                    .on_input(on_input)
+                    .on_submit(Message::SaveSsh)
+                    .font(Font::MONOSPACE)
+                    .padding(10)
+            ]
+            .spacing(10)
+            .align_y(Center)
+        };
 
-            if current == theme {
-                float(stack![item, swatches]).scale(1.1).into()
-            } else {
-                hover(item, swatches)
-            }
-        });
+        let test = match &self.ssh_test {
+            Some(Ok(models)) => Some(text!("Found {models} models.").style(text::success)),
+            Some(Err(error)) => Some(
+                text(error.to_string())
+                    .font(Font::MONOSPACE)
+                    .style(text::danger),
+            ),
+            None => None,
+        };
 
-        container(grid(themes).spacing(10).fluid(300).height(Shrink)).into()
+        column![
+            header,
+            field(
+                "Host",
+                "user@desktop.local",
+                &self.ssh.host,
+                Message::SshHostChanged
+            ),
+            field("Port", "22", &self.ssh_port, Message::SshPortChanged),
+            field(
+                "Private key",
+                "~/.ssh/id_ed25519 (optional)",
+                &self.ssh.identity,
+                Message::SshIdentityChanged
+            ),
+            field(
+                "Model directory",
+                "/home/user/models",
+                &self.ssh.directory,
+                Message::SshDirectoryChanged
+            ),
+            field(
+                "llama-server",
+                "llama-server",
+                &self.ssh.server,
+                Message::SshServerChanged
+            ),
+            row![
+                test,
+                horizontal_space(),
+                button("Test")
+                    .on_press_maybe(self.ssh.is_configured().then_some(Message::TestSsh))
+                    .style(button::secondary),
+                button("Save").on_press(Message::SaveSsh)
+            ]
+            .spacing(10)
+            .align_y(Center),
+        ]
+        .spacing(20)
+        .into()
     }
 
     pub fn mcp(&self) -> Element<'_, Message> {
@@ -206,17 +3816,40 @@ impl Settings {
     pub fn sidebar(&self) -> Element<'_, Message> {
         let header = sidebar::header("Settings", None);
 
-        let sections = [Section::Storage, Section::Theme, Section::Mcp]
-            .into_iter()
-            .map(|section| {
-                sidebar::item(
-                    row![section.icon(), text(section.title())]
-                        .align_y(Center)
-                        .spacing(10),
-                    self.section == section,
-                    move || Message::Open(section),
-                )
-            });
+        let sections = [
+            Section::Storage,
+            Section::Maintenance,
+            Section::Profiles,
+            Section::Workspaces,
+            Section::Backup,
+            Section::Sync,
+            Section::Theme,
+            Section::Chat,
+            Section::Clipboard,
+            Section::Sharing,
+            Section::Execution,
+            Section::Audit,
+            Section::Timeouts,
+            Section::Azure,
+            Section::Hosted,
+            Section::Images,
+            Section::Transcription,
+            Section::Ocr,
+            Section::Hooks,
+            Section::Network,
+            Section::Remote,
+            Section::Mcp,
+        ]
+        .into_iter()
+        .map(|section| {
+            sidebar::item(
+                row![section.icon(), text(section.title())]
+                    .align_y(Center)
+                    .spacing(10),
+                self.section == section,
+                move || Message::Open(section),
+            )
+        });
 
         column![header, scrollable(column(sections)).spacing(10)]
             .spacing(10)
@@ -227,15 +3860,174 @@ impl Settings {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Section {
     Storage,
+    Maintenance,
+    Profiles,
+    Workspaces,
+    Backup,
+    Sync,
     Theme,
+    Chat,
+    Clipboard,
+    Sharing,
+    Execution,
+    Audit,
+    Timeouts,
+    Azure,
+    Hosted,
+    Images,
+    Transcription,
+    Ocr,
+    Hooks,
+    Network,
+    Remote,
     Mcp,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collapse {
+    Never,
+    After(u32),
+}
+
+impl Collapse {
+    const ALL: &[Self] = &[
+        Self::Never,
+        Self::After(300),
+        Self::After(600),
+        Self::After(1000),
+    ];
+
+    fn from_height(height: u32) -> Self {
+        if height == 0 {
+            Self::Never
+        } else {
+            Self::After(height)
+        }
+    }
+
+    fn height(self) -> u32 {
+        match self {
+            Self::Never => 0,
+            Self::After(height) => height,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckEvery(Option<Duration>);
+
+impl CheckEvery {
+    const ALL: &[Self] = &[
+        Self(None),
+        Self(Some(Duration::from_secs(60))),
+        Self(Some(Duration::from_secs(5 * 60))),
+        Self(Some(Duration::from_secs(15 * 60))),
+    ];
+}
+
+impl std::fmt::Display for CheckEvery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(interval) => write!(f, "Every {} min", interval.as_secs() / 60),
+            None => f.write_str("Never"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Preload {
+    Nothing,
+    File(model::File),
+}
+
+impl std::fmt::Display for Preload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Nothing => f.write_str("Nothing"),
+            Self::File(file) => write!(
+                f,
+                "{} ({})",
+                file.model.name(),
+                file.variant().unwrap_or(&file.name)
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadLimit(Option<u64>);
+
+impl DownloadLimit {
+    const ALL: &[Self] = &[
+        Self(None),
+        Self(Some(1_000)),
+        Self(Some(5_000)),
+        Self(Some(10_000)),
+        Self(Some(25_000)),
+        Self(Some(50_000)),
+    ];
+}
+
+impl std::fmt::Display for DownloadLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(limit) => write!(f, "{} MB/s", limit / 1_000),
+            None => f.write_str("Unlimited"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleAfter(Duration);
+
+impl StaleAfter {
+    const ALL: &[Self] = &[
+        Self(Duration::from_secs(60)),
+        Self(Duration::from_secs(5 * 60)),
+        Self(Duration::from_secs(15 * 60)),
+        Self(Duration::from_secs(60 * 60)),
+    ];
+}
+
+impl std::fmt::Display for StaleAfter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "After {} min", self.0.as_secs() / 60)
+    }
+}
+
+impl std::fmt::Display for Collapse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Never => f.write_str("Never collapse"),
+            Self::After(height) => write!(f, "After {height}px"),
+        }
+    }
+}
+
 impl Section {
     pub fn title(self) -> &'static str {
         match self {
             Self::Storage => "Storage",
+            Self::Maintenance => "Maintenance",
+            Self::Profiles => "Profiles",
+            Self::Workspaces => "Workspaces",
+            Self::Backup => "Backup",
+            Self::Sync => "Sync",
             Self::Theme => "Theme",
+            Self::Chat => "Chat",
+            Self::Clipboard => "Clipboard",
+            Self::Sharing => "Sharing",
+            Self::Execution => "Code Execution",
+            Self::Audit => "Audit Log",
+            Self::Timeouts => "Timeouts",
+            Self::Azure => "Azure OpenAI",
+            Self::Hosted => "Hosted Providers",
+            Self::Images => "Image Generation",
+            Self::Transcription => "Transcription",
+            Self::Ocr => "Text Recognition",
+            Self::Hooks => "Hooks",
+            Self::Network => "Local Network",
+            Self::Remote => "Remote Machine",
             Self::Mcp => "MCP",
         }
     }
@@ -243,7 +4035,26 @@ impl Section {
     pub fn icon(self) -> Element<'static, Message> {
         match self {
             Self::Storage => icon::folder().line_height(1.0).into(),
+            Self::Maintenance => icon::check().line_height(1.0).into(),
+            Self::Profiles => icon::user().line_height(1.0).into(),
+            Self::Workspaces => icon::sliders().line_height(1.0).into(),
+            Self::Backup => icon::download().line_height(1.0).into(),
+            Self::Sync => icon::refresh().line_height(1.0).into(),
             Self::Theme => icon::palette().line_height(1.0).into(),
+            Self::Chat => icon::chat().line_height(1.0).into(),
+            Self::Clipboard => icon::clipboard().line_height(1.0).into(),
+            Self::Sharing => icon::link().line_height(1.0).into(),
+            Self::Execution => icon::server().line_height(1.0).into(),
+            Self::Audit => icon::search().line_height(1.0).into(),
+            Self::Timeouts => icon::clock().line_height(1.0).into(),
+            Self::Azure => icon::cloud().line_height(1.0).into(),
+            Self::Hosted => icon::globe().line_height(1.0).into(),
+            Self::Images => icon::star().line_height(1.0).into(),
+            Self::Transcription => icon::chat().line_height(1.0).into(),
+            Self::Ocr => icon::filter().line_height(1.0).into(),
+            Self::Hooks => icon::link().line_height(1.0).into(),
+            Self::Network => icon::server().line_height(1.0).into(),
+            Self::Remote => icon::cubes().line_height(1.0).into(),
             Self::Mcp => mcp()
                 .width(16)
                 .height(16)
@@ -255,6 +4066,24 @@ impl Section {
     }
 }
 
+fn preset_fields(preset: &Preset) -> (String, [String; 3]) {
+    let parameters = preset.parameters;
+
+    (
+        preset.name.clone(),
+        [
+            parameters.temperature.map(|value| value.to_string()),
+            parameters.top_p.map(|value| value.to_string()),
+            parameters.max_tokens.map(|value| value.to_string()),
+        ]
+        .map(Option::unwrap_or_default),
+    )
+}
+
+fn fields(timeout: Timeout) -> [String; 3] {
+    [timeout.connect, timeout.first_token, timeout.inter_token].map(|seconds| seconds.to_string())
+}
+
 fn mcp() -> Svg<'static> {
     static ICON: LazyLock<svg::Handle> =
         LazyLock::new(|| svg::Handle::from_memory(include_bytes!("../../assets/mcp.svg")));
@@ -263,3 +4092,134 @@ fn mcp() -> Svg<'static> {
         color: Some(theme.palette().text),
     })
 }
+
+fn maintenance_report(report: &Result<Report, Error>, is_repairing: bool) -> Element<'_, Message> {
+    let report = match report {
+        Ok(report) => report,
+        Err(error) => {
+            return text(error.to_string())
+                .font(Font::MONOSPACE)
+                .style(text::danger)
+                .into();
+        }
+    };
+
+    let summary = text!(
+        "{checked} files checked · {unverified} not found in Hugging Face · {issues} issues",
+        checked = report.checked,
+        unverified = report.unverified,
+        issues = report.issues.len(),
+    )
+    .size(12)
+    .style(if report.issues.is_empty() {
+        text::success
+    } else {
+        text::secondary
+    });
+
+    let issues = report.issues.iter().enumerate().map(|(index, issue)| {
+        let (name, details, fix) = match issue {
+            Issue::Corrupted { file, reason } => (
+                file.relative_path().display().to_string(),
+                match reason {
+                    Corruption::Size { expected, actual } => {
+                        format!("Corrupted · {actual} instead of {expected}")
+                    }
+                    Corruption::Checksum => "Corrupted · checksum mismatch".to_owned(),
+                },
+                "Redownload",
+            ),
+            Issue::Leftover { path, size } => (
+                path.display().to_string(),
+                format!("Abandoned download · {size}"),
+                "Delete",
+            ),
+            Issue::EmptyDirectory(path) => (
+                path.display().to_string(),
+                "Empty folder".to_owned(),
+                "Delete",
+            ),
+        };
+
+        row![
+            column![
+                ellipsized_text(name)
+                    .font(Font::MONOSPACE)
+                    .wrapping(text::Wrapping::None),
+                text(details).size(12).style(text::secondary),
+            ]
+            .spacing(5)
+            .width(Fill),
+            button(text(fix).size(14))
+                .on_press_maybe((!is_repairing).then_some(Message::Repair(index)))
+                .style(button::danger),
+        ]
+        .spacing(10)
+        .align_y(Center)
+        .into()
+    });
+
+    column![summary, column(issues).spacing(10)]
+        .spacing(10)
+        .into()
+}
+
+fn duplicate_list(duplicates: &Result<Vec<Duplicate>, Error>) -> Element<'_, Message> {
+    let duplicates = match duplicates {
+        Ok(duplicates) => duplicates,
+        Err(error) => {
+            return text(error.to_string())
+                .font(Font::MONOSPACE)
+                .style(text::danger)
+                .into();
+        }
+    };
+
+    if duplicates.is_empty() {
+        return text("No duplicates found.").style(text::success).into();
+    }
+
+    let reclaimable: u64 = duplicates.iter().map(Duplicate::reclaimable).sum();
+
+    let entries = duplicates.iter().enumerate().map(|(index, duplicate)| {
+        let details = text!(
+            "{copies} copies · {size} each{verified}",
+            copies = duplicate.paths.len(),
+            size = model::Size::from(duplicate.size),
+            verified = if duplicate.is_verified {
+                " · same checksum"
+            } else {
+                ""
+            },
+        )
+        .size(12)
+        .style(text::secondary);
+
+        row![
+            column![
+                ellipsized_text(duplicate.name())
+                    .font(Font::MONOSPACE)
+                    .wrapping(text::Wrapping::None),
+                details,
+            ]
+            .spacing(5)
+            .width(Fill),
+            button(text("Link").size(14))
+                .on_press(Message::Deduplicate(index, duplicate::Strategy::Hardlink))
+                .style(button::secondary),
+            button(text("Delete Copies").size(14))
+                .on_press(Message::Deduplicate(index, duplicate::Strategy::Delete))
+                .style(button::danger),
+        ]
+        .spacing(10)
+        .align_y(Center)
+        .into()
+    });
+
+    column![
+        text!("{} reclaimable", model::Size::from(reclaimable)).style(text::primary),
+        column(entries).spacing(10),
+    ]
+    .spacing(10)
+    .into()
+}
@@ -0,0 +1,320 @@
+use crate::core::assistant::Assistant;
+use crate::core::translation::{self, Glossary, Term, Translation};
+use crate::core::Error;
+use crate::icon;
+use crate::widget::sidebar;
+
+use iced::clipboard;
+use iced::task::{self, Task};
+use iced::widget::{
+    button, column, container, horizontal_space, pick_list, row, scrollable, text, text_editor,
+    text_input, value,
+};
+use iced::{Center, Element, Fill, Font};
+use iced_palace::widget::ellipsized_text;
+
+pub struct Translate {
+    source: text_editor::Content,
+    target: &'static str,
+    translation: Translation,
+    translating: Option<task::Handle>,
+    glossary: Glossary,
+    term_source: String,
+    term_target: String,
+    error: Option<Error>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SourceEdited(text_editor::Action),
+    TargetSelected(&'static str),
+    Translate,
+    Stop,
+    Translating(Translation),
+    Translated(Result<Translation, Error>),
+    Copy,
+    GlossaryFetched(Result<Glossary, Error>),
+    TermSourceChanged(String),
+    TermTargetChanged(String),
+    AddTerm,
+    RemoveTerm(usize),
+    GlossarySaved(Result<Glossary, Error>),
+}
+
+pub enum Action {
+    None,
+    Run(Task<Message>),
+}
+
+impl Translate {
+    pub fn new() -> (Self, Task<Message>) {
+        (
+            Self {
+                source: text_editor::Content::new(),
+                target: "English",
+                translation: Translation::default(),
+                translating: None,
+                glossary: Glossary::default(),
+                term_source: String::new(),
+                term_target: String::new(),
+                error: None,
+            },
+            Task::perform(Glossary::fetch(), Message::GlossaryFetched),
+        )
+    }
+
+    pub fn title(&self) -> &str {
+        "Translate"
+    }
+
+    pub fn update(&mut self, message: Message, assistant: Option<&Assistant>) -> Action {
+        match message {
+            Message::SourceEdited(action) => {
+                self.source.perform(action);
+
+                Action::None
+            }
+            Message::TargetSelected(target) => {
+                self.target = target;
+
+                Action::None
+            }
+            Message::Translate => {
+                let Some(assistant) = assistant else {
+                    return Action::None;
+                };
+
+                let source = self.source.text();
+
+                if source.trim().is_empty() {
+                    return Action::None;
+                }
+
+                let (translate, handle) = Task::sip(
+                    translation::translate(
+                        assistant,
+                        self.target.to_owned(),
+                        self.glossary.clone(),
+                        source,
+                    ),
+                    Message::Translating,
+                    Message::Translated,
+                )
+                .abortable();
+
+                self.translation = Translation::default();
+                self.translating = Some(handle.abort_on_drop());
+                self.error = None;
+
+                Action::Run(translate)
+            }
+            Message::Stop => {
+                self.translating = None;
+
+                Action::None
+            }
+            Message::Translating(translation) => {
+                self.translation = translation;
+
+                Action::None
+            }
+            Message::Translated(Ok(translation)) => {
+                self.translation = translation;
+                self.translating = None;
+
+                Action::None
+            }
+            Message::Copy => Action::Run(clipboard::write(self.translation.content.clone())),
+            Message::GlossaryFetched(Ok(glossary)) | Message::GlossarySaved(Ok(glossary)) => {
+                self.glossary = glossary;
+
+                Action::None
+            }
+            Message::TermSourceChanged(source) => {
+                self.term_source = source;
+
+                Action::None
+            }
+            Message::TermTargetChanged(target) => {
+                self.term_target = target;
+
+                Action::None
+            }
+            Message::AddTerm => {
+                if self.term_source.trim().is_empty() || self.term_target.trim().is_empty() {
+                    return Action::None;
+                }
+
+                let mut glossary = self.glossary.clone();
+
+                glossary.terms.push(Term {
+                    source: self.term_source.trim().to_owned(),
+                    target: self.term_target.trim().to_owned(),
+                });
+
+                self.term_source.clear();
+                self.term_target.clear();
+
+                Action::Run(Task::perform(glossary.save(), Message::GlossarySaved))
+            }
+            Message::RemoveTerm(index) => {
+                if index >= self.glossary.terms.len() {
+                    return Action::None;
+                }
+
+                let mut glossary = self.glossary.clone();
+                let _ = glossary.terms.remove(index);
+
+                Action::Run(Task::perform(glossary.save(), Message::GlossarySaved))
+            }
+            Message::Translated(Err(error))
+            | Message::GlossaryFetched(Err(error))
+            | Message::GlossarySaved(Err(error)) => {
+                self.translating = None;
+                self.error = Some(dbg!(error));
+
+                Action::None
+            }
+        }
+    }
+
+    pub fn view(&self, assistant: Option<&Assistant>) -> Element<'_, Message> {
+        let controls = {
+            let model: Element<'_, _> = match assistant {
+                Some(assistant) => ellipsized_text(assistant.name().to_owned())
+                    .font(Font::MONOSPACE)
+                    .wrapping(text::Wrapping::None)
+                    .into(),
+                None => text("Open a chat to choose a model for translating.")
+                    .style(text::secondary)
+                    .into(),
+            };
+
+            let target = pick_list(
+                translation::LANGUAGES,
+                Some(self.target),
+                Message::TargetSelected,
+            );
+
+            let translate = if self.translating.is_some() {
+                button("Stop").on_press(Message::Stop).style(button::danger)
+            } else {
+                button("Translate").on_press_maybe(
+                    (assistant.is_some() && !self.source.is_empty()).then_some(Message::Translate),
+                )
+            };
+
+            row![
+                container(model).width(Fill),
+                text("Into").size(14),
+                target,
+                translate
+            ]
+            .spacing(10)
+            .align_y(Center)
+        };
+
+        let error = self
+            .error
+            .as_ref()
+            .map(|error| value(error).font(Font::MONOSPACE).style(text::danger));
+
+        let source = column![
+            text(
+                self.translation
+                    .source
+                    .as_deref()
+                    .map_or("Source".to_owned(), |language| format!(
+                        "Source · {language}"
+                    ))
+            )
+            .size(12)
+            .style(text::secondary),
+            text_editor(&self.source)
+                .placeholder("Paste or type the text to translate...")
+                .on_action(Message::SourceEdited)
+                .padding(10)
+                .height(Fill),
+        ]
+        .spacing(5)
+        .width(Fill);
+
+        let target = column![
+            row![
+                text(self.target).size(12).style(text::secondary),
+                horizontal_space(),
+                button(icon::clipboard().size(12))
+                    .on_press_maybe((!self.translation.content.is_empty()).then_some(Message::Copy))
+                    .padding(0)
+                    .style(button::text),
+            ]
+            .align_y(Center),
+            container(scrollable(text(&self.translation.content).width(Fill)))
+                .padding(10)
+                .height(Fill)
+                .width(Fill)
+                .style(container::bordered_box),
+        ]
+        .spacing(5)
+        .width(Fill);
+
+        column![
+            controls,
+            error,
+            row![source, target].spacing(20).height(Fill)
+        ]
+        .spacing(20)
+        .into()
+    }
+
+    pub fn sidebar(&self) -> Element<'_, Message> {
+        let header = sidebar::header("Glossary", None);
+
+        let terms = column(self.glossary.terms.iter().enumerate().map(|(index, term)| {
+            row![
+                column![
+                    ellipsized_text(term.source.clone())
+                        .font(Font::MONOSPACE)
+                        .wrapping(text::Wrapping::None),
+                    ellipsized_text(format!("→ {}", term.target))
+                        .font(Font::MONOSPACE)
+                        .wrapping(text::Wrapping::None),
+                ]
+                .width(Fill),
+                button(icon::trash().size(12))
+                    .on_press(Message::RemoveTerm(index))
+                    .style(button::text),
+            ]
+            .spacing(5)
+            .align_y(Center)
+            .into()
+        }))
+        .spacing(10);
+
+        let add = column![
+            text_input("Term", &self.term_source)
+                .on_input(Message::TermSourceChanged)
+                .on_submit(Message::AddTerm),
+            row![
+                text_input("Translation", &self.term_target)
+                    .on_input(Message::TermTargetChanged)
+                    .on_submit(Message::AddTerm),
+                button(icon::plus()).on_press(Message::AddTerm),
+            ]
+            .spacing(5)
+            .align_y(Center),
+        ]
+        .spacing(5);
+
+        column![
+            header,
+            text("Preferred translations of specific terms")
+                .size(12)
+                .style(text::secondary),
+            scrollable(terms).height(Fill),
+            add
+        ]
+        .spacing(10)
+        .into()
+    }
+}
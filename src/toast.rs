@@ -0,0 +1,112 @@
+use crate::icon;
+
+use iced::time::{self, Duration, Instant};
+use iced::widget::{button, column, container, horizontal_space, row, text};
+use iced::{Center, Element, Subscription, Theme};
+
+#[cfg(test)]
+mod tests;
+
+const LIFETIME: Duration = Duration::from_secs(6);
+
+#[derive(Debug, Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+    next: usize,
+}
+
+#[derive(Debug)]
+struct Toast {
+    id: usize,
+    text: String,
+    is_error: bool,
+    shown_at: Instant,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Dismiss(usize),
+    Tick(Instant),
+}
+
+impl Toasts {
+    pub fn error(&mut self, error: impl ToString) {
+        self.push(error.to_string(), true);
+    }
+
+    pub fn info(&mut self, text: impl ToString) {
+        self.push(text.to_string(), false);
+    }
+
+    fn push(&mut self, text: String, is_error: bool) {
+        self.toasts.push(Toast {
+            id: self.next,
+            text,
+            is_error,
+            shown_at: Instant::now(),
+        });
+
+        self.next += 1;
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Dismiss(id) => {
+                self.toasts.retain(|toast| toast.id != id);
+            }
+            Message::Tick(now) => {
+                self.toasts
+                    .retain(|toast| now.duration_since(toast.shown_at) < LIFETIME);
+            }
+        }
+    }
+
+    pub fn view(&self) -> Option<Element<'_, Message>> {
+        if self.toasts.is_empty() {
+            return None;
+        }
+
+        let toasts = self.toasts.iter().map(|toast| {
+            let is_error = toast.is_error;
+
+            container(
+                row![
+                    text(&toast.text).size(14),
+                    horizontal_space(),
+                    button(icon::cancel().size(12))
+                        .padding(0)
+                        .on_press(Message::Dismiss(toast.id))
+                        .style(button::text),
+                ]
+                .spacing(10)
+                .align_y(Center)
+                .width(350),
+            )
+            .padding(10)
+            .style(move |theme: &Theme| {
+                let style = container::bordered_box(theme);
+                let palette = theme.palette();
+
+                container::Style {
+                    border: style.border.color(if is_error {
+                        palette.danger
+                    } else {
+                        palette.primary
+                    }),
+                    ..style
+                }
+            })
+            .into()
+        });
+
+        Some(column(toasts).spacing(10).into())
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        if self.toasts.is_empty() {
+            return Subscription::none();
+        }
+
+        time::every(Duration::from_secs(1)).map(Message::Tick)
+    }
+}
@@ -0,0 +1,25 @@
+use super::{Message, Toasts, LIFETIME};
+
+use iced::time::Instant;
+
+#[test]
+fn errors_are_shown_until_dismissed() {
+    let mut toasts = Toasts::default();
+    assert!(toasts.view().is_none());
+
+    toasts.error("model not found: removed/model");
+    assert!(toasts.view().is_some());
+    assert!(toasts.toasts[0].is_error);
+
+    toasts.update(Message::Dismiss(toasts.toasts[0].id));
+    assert!(toasts.view().is_none());
+}
+
+#[test]
+fn toasts_expire() {
+    let mut toasts = Toasts::default();
+    toasts.info("Bookmarked");
+
+    toasts.update(Message::Tick(Instant::now() + LIFETIME));
+    assert!(toasts.view().is_none());
+}
@@ -0,0 +1,76 @@
+use crate::core::diff::{Change, Revision};
+
+use iced::widget::{column, container, row, scrollable, text};
+use iced::{Element, Fill, Font, Theme};
+
+pub fn view<'a, Message: 'a>(revision: &'a Revision) -> Element<'a, Message> {
+    let number = |number: Option<usize>| {
+        text(number.map(|number| number.to_string()).unwrap_or_default())
+            .font(Font::MONOSPACE)
+            .size(12)
+            .width(32)
+            .style(text::secondary)
+    };
+
+    let lines = column(revision.lines.iter().map(|line| {
+        let sign = match line.change {
+            Change::Equal => " ",
+            Change::Insert => "+",
+            Change::Delete => "-",
+        };
+
+        let change = line.change;
+
+        container(
+            row![
+                number(line.old),
+                number(line.new),
+                text!("{sign} {}", line.content)
+                    .font(Font::MONOSPACE)
+                    .size(12)
+                    .wrapping(text::Wrapping::None)
+            ]
+            .spacing(5),
+        )
+        .width(Fill)
+        .style(move |theme: &Theme| {
+            let palette = theme.extended_palette();
+
+            let background = match change {
+                Change::Equal => None,
+                Change::Insert => Some(palette.success.weak.color.scale_alpha(0.3)),
+                Change::Delete => Some(palette.danger.weak.color.scale_alpha(0.3)),
+            };
+
+            container::Style {
+                background: background.map(Into::into),
+                ..container::Style::default()
+            }
+        })
+        .into()
+    }));
+
+    let summary = text!(
+        "{language} +{insertions} -{deletions}",
+        language = revision.language,
+        insertions = revision.insertions,
+        deletions = revision.deletions
+    )
+    .font(Font::MONOSPACE)
+    .size(12)
+    .style(text::secondary);
+
+    container(
+        column![
+            summary,
+            scrollable(lines).direction(scrollable::Direction::Horizontal(
+                scrollable::Scrollbar::default()
+            ))
+        ]
+        .spacing(10),
+    )
+    .padding(10)
+    .width(Fill)
+    .style(container::bordered_box)
+    .into()
+}
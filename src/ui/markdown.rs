@@ -1,37 +1,171 @@
 use crate::browser;
-use crate::widget::copy;
+use crate::core;
+use crate::core::execution::Language;
+use crate::icon;
+use crate::widget::table::{self, Table};
+use crate::widget::{action, copy};
 
 use iced::clipboard;
-use iced::widget::{container, hover, markdown, right};
-use iced::{Element, Task, Theme};
+use iced::widget::{container, hover, markdown, right, row, svg};
+use iced::{Element, Shrink, Task, Theme};
+
+use std::cell::Cell;
 
 #[derive(Debug, Default)]
 pub struct Markdown {
     content: markdown::Content,
+    source: String,
+    tables: Vec<Table>,
+    diagrams: Vec<Diagram>,
+    stats: Stats,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stats {
+    pub words: usize,
+    pub characters: usize,
+    is_in_word: bool,
+}
+
+impl Stats {
+    pub fn of(text: &str) -> Self {
+        let mut stats = Self::default();
+        stats.push_str(text);
+        stats
+    }
+
+    fn push_str(&mut self, text: &str) {
+        for c in text.chars() {
+            self.characters += 1;
+
+            if c.is_whitespace() {
+                self.is_in_word = false;
+            } else if !self.is_in_word {
+                self.is_in_word = true;
+                self.words += 1;
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Diagram {
+    code: String,
+    rendered: Option<(core::diagram::Diagram, svg::Handle)>,
 }
 
 impl Markdown {
     pub fn parse(markdown: &str) -> Self {
         let content = markdown::Content::parse(markdown);
 
-        Self { content }
+        let mut markdown = Self {
+            content,
+            source: markdown.to_owned(),
+            tables: Vec::new(),
+            diagrams: Vec::new(),
+            stats: Stats::of(markdown),
+        };
+
+        markdown.sync();
+        markdown
     }
 
     pub fn push_str(&mut self, markdown: &str) {
         self.content.push_str(markdown);
+        self.source.push_str(markdown);
+        self.stats.push_str(markdown);
+
+        // Tables and diagrams only change once a line is complete
+        if markdown.contains('\n') {
+            self.sync();
+        }
+    }
+
+    fn sync(&mut self) {
+        if self.source.contains('|') {
+            let mut tables = core::table::Table::from_markdown(&self.source).into_iter();
+
+            for (table, data) in self.tables.iter_mut().zip(tables.by_ref()) {
+                table.refresh(data);
+            }
+
+            self.tables.extend(tables.map(Table::new));
+        }
+
+        if self.source.contains("mermaid") {
+            let mut diagrams = std::mem::take(&mut self.diagrams);
+
+            self.diagrams = core::diagram::find(&self.source)
+                .into_iter()
+                .map(
+                    |code| match diagrams.iter().position(|diagram| diagram.code == code) {
+                        Some(index) => diagrams.swap_remove(index),
+                        None => {
+                            let rendered = core::diagram::Diagram::parse(&code).map(|diagram| {
+                                let handle =
+                                    svg::Handle::from_memory(diagram.svg.clone().into_bytes());
+
+                                (diagram, handle)
+                            });
+
+                            Diagram { code, rendered }
+                        }
+                    },
+                )
+                .collect();
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    pub fn update_table(&mut self, index: usize, interaction: table::Interaction) {
+        if let Some(table) = self.tables.get_mut(index) {
+            table.update(interaction);
+        }
     }
 
     pub fn view(&self, theme: &Theme) -> Element<'_, Interaction> {
-        markdown::view_with(self.content.items(), theme, &Viewer)
+        self.view_runnable(theme, Runnable::default())
+    }
+
+    pub fn view_runnable(&self, theme: &Theme, runnable: Runnable) -> Element<'_, Interaction> {
+        markdown::view_with(self.content.items(), theme, &Viewer::new(self, runnable))
     }
 }
 
-struct Viewer;
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Runnable {
+    pub code: bool,
+    pub queries: bool,
+}
+
+struct Viewer<'a> {
+    markdown: &'a Markdown,
+    runnable: Runnable,
+    next_table: Cell<usize>,
+}
+
+impl<'a> Viewer<'a> {
+    fn new(markdown: &'a Markdown, runnable: Runnable) -> Self {
+        Self {
+            markdown,
+            runnable,
+            next_table: Cell::new(0),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum Interaction {
     Open(markdown::Url),
     Copy(String),
+    Run(Language, String),
+    Query(String),
+    Table(usize, table::Interaction),
+    SaveTable(core::table::Table),
+    SaveDiagram(core::diagram::Diagram),
 }
 
 impl Interaction {
@@ -43,11 +177,16 @@ impl Interaction {
                 Task::none()
             }
             Interaction::Copy(text) => clipboard::write(text),
+            Interaction::Run(..)
+            | Interaction::Query(_)
+            | Interaction::Table(..)
+            | Interaction::SaveTable(_)
+            | Interaction::SaveDiagram(_) => Task::none(),
         }
     }
 }
 
-impl<'a> markdown::Viewer<'a, Interaction> for Viewer {
+impl<'a> markdown::Viewer<'a, Interaction> for Viewer<'a> {
     fn on_link_click(url: markdown::Url) -> Interaction {
         Interaction::Open(url)
     }
@@ -55,16 +194,95 @@ impl<'a> markdown::Viewer<'a, Interaction> for Viewer {
     fn code_block(
         &self,
         settings: markdown::Settings,
-        _language: Option<&'a str>,
+        language: Option<&'a str>,
         code: &'a str,
         lines: &'a [markdown::Text],
     ) -> Element<'a, Interaction> {
-        let code_block = markdown::code_block(settings, lines, Interaction::Open);
         let copy = copy(|| Interaction::Copy(code.to_owned()));
 
+        // Unsupported diagrams are shown as code
+        if let Some((diagram, handle)) = language
+            .filter(|language| *language == "mermaid")
+            .and_then(|_| {
+                self.markdown
+                    .diagrams
+                    .iter()
+                    .find(|diagram| diagram.code == code.trim())
+            })
+            .and_then(|diagram| diagram.rendered.as_ref())
+        {
+            let save = action(icon::download(), "Save as SVG", move || {
+                Interaction::SaveDiagram(diagram.clone())
+            });
+
+            return hover(
+                container(svg(handle.clone()).width(Shrink).height(Shrink)).padding(10),
+                right(container(row![copy, save].spacing(5)).style(container::dark))
+                    .padding(settings.code_size / 2),
+            );
+        }
+
+        let code_block = markdown::code_block(settings, lines, Interaction::Open);
+
+        let is_query = language.is_some_and(|language| language.eq_ignore_ascii_case("sql"));
+
+        let run = if is_query {
+            self.runnable.queries.then(|| {
+                action(icon::server(), "Run query", move || {
+                    Interaction::Query(code.to_owned())
+                })
+            })
+        } else {
+            language
+                .and_then(Language::detect)
+                .filter(|_| self.runnable.code)
+                .map(|language| {
+                    action(icon::arrow_right(), "Run", move || {
+                        Interaction::Run(language, code.to_owned())
+                    })
+                })
+        };
+
         hover(
             code_block,
-            right(container(copy).style(container::dark)).padding(settings.code_size / 2),
+            right(container(row![run, copy].spacing(5)).style(container::dark))
+                .padding(settings.code_size / 2),
+        )
+    }
+
+    fn table(
+        &self,
+        settings: markdown::Settings,
+        columns: &'a [markdown::Column],
+        rows: &'a [markdown::Row],
+    ) -> Element<'a, Interaction> {
+        let index = self.next_table.get();
+        self.next_table.set(index + 1);
+
+        let Some(table) = self.markdown.tables.get(index) else {
+            return markdown::table(self, settings, columns, rows);
+        };
+
+        let selected = table.selected().map(|cell| {
+            action(icon::clipboard(), "Copy cell", move || {
+                Interaction::Copy(cell.to_owned())
+            })
+        });
+
+        let actions = row![
+            selected,
+            copy(|| Interaction::Copy(table.to_data().to_csv())),
+            action(icon::download(), "Save as CSV", || {
+                Interaction::SaveTable(table.to_data())
+            }),
+        ]
+        .spacing(5);
+
+        hover(
+            table
+                .view()
+                .map(move |interaction| Interaction::Table(index, interaction)),
+            right(container(actions).style(container::dark)).padding(settings.code_size / 2),
         )
     }
 }
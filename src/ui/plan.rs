@@ -1,11 +1,12 @@
 use crate::browser;
-use crate::core::plan::{self, Event, Status, Step};
+use crate::core::approval;
+use crate::core::plan::{self, Citation, Event, Status, Step};
 use crate::core::web;
 use crate::core::{self, Url};
 use crate::icon;
 use crate::ui::markdown;
 use crate::ui::{Reasoning, Reply};
-use crate::widget::diffused_text;
+use crate::widget::{diffused_text, table};
 
 use iced::border;
 use iced::theme;
@@ -15,20 +16,28 @@ use iced::widget::{
 };
 use iced::{Bottom, Center, Element, Fill, Font, Function, Task, Theme};
 
+use std::collections::HashSet;
+use std::mem;
+
 #[derive(Debug, Default)]
 pub struct Plan {
     reasoning: Option<Reasoning>,
     steps: Vec<Step>,
     outcomes: Vec<Outcome>,
+    citations: Vec<Citation>,
+    calls: Vec<approval::Record>,
     active_step: Option<usize>,
+    open_citation: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     ToggleAnswerReasoning(usize, bool),
     Markdown(markdown::Interaction),
+    AnswerTable(usize, usize, table::Interaction),
     OpenLink(Url),
     ChangeStep(usize),
+    ToggleCitation(usize),
 }
 
 impl Plan {
@@ -37,7 +46,10 @@ impl Plan {
             reasoning: plan.reasoning.map(Reasoning::from_data),
             steps: plan.steps,
             outcomes: plan.outcomes.into_iter().map(Outcome::from_data).collect(),
+            citations: plan.citations,
+            calls: plan.calls,
             active_step: None,
+            open_citation: None,
         }
     }
 
@@ -46,9 +58,26 @@ impl Plan {
             reasoning: self.reasoning.as_ref().map(Reasoning::to_data),
             steps: self.steps.clone(),
             outcomes: self.outcomes.iter().map(Outcome::to_data).collect(),
+            citations: self.citations.clone(),
+            calls: self.calls.clone(),
         }
     }
 
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    pub fn to_text(&self) -> String {
+        self.outcomes
+            .iter()
+            .filter_map(|outcome| match outcome {
+                Outcome::Answer(Status::Done(reply)) => Some(reply.to_text()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
     pub fn apply(&mut self, event: Event) {
         match event {
             Event::Designing(reasoning) => {
@@ -58,24 +87,43 @@ impl Plan {
                 self.reasoning = plan.reasoning.map(Reasoning::from_data);
                 self.steps = plan.steps;
             }
+            Event::Cited(citations) => {
+                self.citations = citations;
+            }
+            Event::StepAdded(step) => {
+                self.steps.push(step);
+            }
+            Event::ApprovalRequested(_) => {}
+            Event::Approved(record) => {
+                self.calls.push(record);
+            }
             Event::OutcomeAdded(outcome) => {
                 self.outcomes.push(Outcome::from_data(outcome));
             }
-            Event::OutcomeChanged(new_outcome) => {
-                let Some(Outcome::Answer(Status::Active(mut reply))) = self.outcomes.pop() else {
-                    self.outcomes.push(Outcome::from_data(new_outcome));
+            Event::OutcomeChanged(index, new_outcome) => {
+                let Some(outcome) = self.outcomes.get_mut(index) else {
                     return;
                 };
 
-                let plan::Outcome::Answer(new_status) = new_outcome else {
-                    return;
-                };
-
-                self.outcomes
-                    .push(Outcome::Answer(new_status.map(move |new_reply| {
-                        reply.update(new_reply);
-                        reply
-                    })));
+                match (outcome, new_outcome) {
+                    (
+                        Outcome::Answer(status @ Status::Active(_)),
+                        plan::Outcome::Answer(new_status),
+                    ) => {
+                        // Replies are updated in place, keeping their parsed markdown
+                        if let Status::Active(mut reply) =
+                            mem::replace(status, Status::Errored(String::new()))
+                        {
+                            *status = new_status.map(move |new_reply| {
+                                reply.update(new_reply);
+                                reply
+                            });
+                        }
+                    }
+                    (outcome, new_outcome) => {
+                        *outcome = Outcome::from_data(new_outcome);
+                    }
+                }
             }
         }
     }
@@ -90,6 +138,15 @@ impl Plan {
                 Task::none()
             }
             Message::Markdown(interaction) => interaction.perform(),
+            Message::AnswerTable(index, table, interaction) => {
+                if let Some(Outcome::Answer(Status::Active(reply) | Status::Done(reply))) =
+                    self.outcomes.get_mut(index)
+                {
+                    reply.update_table(table, interaction);
+                }
+
+                Task::none()
+            }
             Message::OpenLink(url) => {
                 browser::open(&url);
 
@@ -98,6 +155,15 @@ impl Plan {
             Message::ChangeStep(step) => {
                 self.active_step = Some(step);
 
+                Task::none()
+            }
+            Message::ToggleCitation(number) => {
+                self.open_citation = if self.open_citation == Some(number) {
+                    None
+                } else {
+                    Some(number)
+                };
+
                 Task::none()
             }
         }
@@ -197,17 +263,178 @@ impl Plan {
                         .align_x(Center)
                         .style(text_style);
 
-                    column![title, outcome.view(i, theme)].spacing(20).into()
+                    let citations = match outcome {
+                        Outcome::Answer(status) => {
+                            status.result().ok().and_then(|reply| self.citations(reply))
+                        }
+                        _ => None,
+                    };
+
+                    column![title, outcome.view(i, theme), citations]
+                        .spacing(20)
+                        .into()
                 })
                 .unwrap_or_else(|| horizontal_space().into());
 
             column![steps, current].spacing(10).into()
         };
 
-        steps
+        column![steps, self.calls()].spacing(10).into()
+    }
+
+    fn calls(&self) -> Option<Element<'_, Message>> {
+        if self.calls.is_empty() {
+            return None;
+        }
+
+        let calls = column(self.calls.iter().map(|call| {
+            text!(
+                "{} {}({}) {}",
+                call.at.format("%H:%M:%S"),
+                call.tool,
+                call.inputs
+                    .iter()
+                    .map(|input| brief(input))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                if call.by_user {
+                    "approved by you"
+                } else {
+                    "auto-approved"
+                }
+            )
+            .size(10)
+            .font(Font::MONOSPACE)
+            .style(text::secondary)
+            .wrapping(text::Wrapping::None)
+            .into()
+        }));
+
+        Some(
+            scrollable(calls)
+                .direction(scrollable::Direction::Horizontal(
+                    scrollable::Scrollbar::default(),
+                ))
+                .into(),
+        )
+    }
+
+    fn citations<'a>(&'a self, reply: &Reply) -> Option<Element<'a, Message>> {
+        if self.citations.is_empty() {
+            return None;
+        }
+
+        let answer = reply.to_text();
+
+        let chips = row(self.citations.iter().map(|citation| {
+            let is_cited = answer.contains(&citation.marker());
+            let is_open = self.open_citation == Some(citation.number);
+
+            button(
+                row![
+                    text(citation.marker()).size(12).font(Font::MONOSPACE),
+                    text(
+                        citation
+                            .url
+                            .host_str()
+                            .unwrap_or(citation.url.as_str())
+                            .trim_start_matches("www.")
+                    )
+                    .size(12)
+                    .wrapping(text::Wrapping::None),
+                ]
+                .spacing(5),
+            )
+            .on_press(Message::ToggleCitation(citation.number))
+            .padding([2, 8])
+            .style(if is_open {
+                button::primary
+            } else if is_cited {
+                button::secondary
+            } else {
+                button::text
+            })
+            .into()
+        }))
+        .spacing(5)
+        .wrap();
+
+        let preview = self
+            .open_citation
+            .and_then(|number| self.citations.iter().find(|c| c.number == number))
+            .map(|citation| source_preview(citation, &answer));
+
+        Some(column![chips, preview].spacing(10).into())
+    }
+}
+
+fn brief(input: &str) -> String {
+    let line = input.lines().next().unwrap_or_default();
+
+    if line.chars().count() > 60 || line.len() < input.trim_end().len() {
+        format!("{}...", line.chars().take(60).collect::<String>())
+    } else {
+        line.to_owned()
     }
 }
 
+fn source_preview<'a>(citation: &'a Citation, answer: &str) -> Element<'a, Message> {
+    let claims: HashSet<String> = answer
+        .split(['.', '\n'])
+        .filter(|sentence| sentence.contains(&citation.marker()))
+        .flat_map(keywords)
+        .collect();
+
+    let lines = column(citation.excerpt.lines().map(|line| {
+        let overlap = keywords(line).filter(|word| claims.contains(word)).count();
+
+        let line = text(line).size(12).font(Font::MONOSPACE);
+
+        if overlap >= 2 {
+            container(line)
+                .padding([0, 4])
+                .style(|theme: &Theme| {
+                    let palette = theme.extended_palette();
+
+                    container::Style::default()
+                        .background(palette.primary.weak.color)
+                        .color(palette.primary.weak.text)
+                })
+                .into()
+        } else {
+            line.into()
+        }
+    }))
+    .spacing(5);
+
+    let header = button(
+        row![
+            text(citation.url.as_str())
+                .size(12)
+                .font(Font::MONOSPACE)
+                .wrapping(text::Wrapping::None),
+            icon::link().size(12)
+        ]
+        .spacing(5)
+        .align_y(Center),
+    )
+    .on_press_with(|| Message::OpenLink(citation.url.clone()))
+    .padding(0)
+    .style(button::text);
+
+    container(column![header, scrollable(lines).height(iced::Shrink).spacing(5)].spacing(10))
+        .max_height(250)
+        .padding(10)
+        .style(container::bordered_box)
+        .into()
+}
+
+fn keywords(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 3)
+        .map(str::to_lowercase)
+}
+
 #[derive(Debug)]
 pub enum Outcome {
     Search(Status<Vec<Url>>),
@@ -378,7 +605,13 @@ fn summary_grid(summaries: &[web::Summary], stage: Stage) -> Element<'_, Message
 fn reply<'a>(reply: &'a Reply, index: usize, theme: &Theme) -> Element<'a, Message> {
     reply.view(
         theme,
+        markdown::Runnable::default(),
         Message::ToggleAnswerReasoning.with(index),
-        Message::Markdown,
+        move |interaction| match interaction {
+            markdown::Interaction::Table(table, interaction) => {
+                Message::AnswerTable(index, table, interaction)
+            }
+            interaction => Message::Markdown(interaction),
+        },
     )
 }
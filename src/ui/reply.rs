@@ -1,15 +1,19 @@
 use crate::core::assistant;
 use crate::ui::markdown;
 use crate::ui::{Markdown, Reasoning};
+use crate::widget::table;
 
-use iced::widget::column;
-use iced::{Element, Theme};
+use iced::widget::{column, container, row, text, tooltip};
+use iced::{Element, Font, Theme};
 
 #[derive(Debug, Default)]
 pub struct Reply {
     reasoning: Option<Reasoning>,
     content: String,
     markdown: Markdown,
+    endpoint: Option<String>,
+    provenance: Option<assistant::Provenance>,
+    usage: Option<assistant::Usage>,
 }
 
 impl Reply {
@@ -18,6 +22,9 @@ impl Reply {
             reasoning: reply.reasoning.map(Reasoning::from_data),
             markdown: Markdown::parse(&reply.content),
             content: reply.content,
+            endpoint: reply.endpoint,
+            provenance: reply.provenance,
+            usage: reply.usage,
         }
     }
 
@@ -26,6 +33,9 @@ impl Reply {
             reasoning: self.reasoning.as_ref().map(Reasoning::to_data),
             content: self.content.as_str().to_owned(),
             last_token: None,
+            endpoint: self.endpoint.clone(),
+            provenance: self.provenance.clone(),
+            usage: self.usage,
         }
     }
 
@@ -50,16 +60,37 @@ impl Reply {
     pub fn update(&mut self, new_reply: assistant::Reply) {
         self.reasoning = new_reply.reasoning.map(Reasoning::from_data);
         self.content = new_reply.content;
+        self.provenance = new_reply.provenance;
+        self.usage = new_reply.usage;
 
         if let Some(reasoning) = &mut self.reasoning {
             reasoning.show = new_reply.last_token.is_none();
         }
 
-        if let Some(token) = new_reply.last_token {
+        if new_reply.endpoint != self.endpoint {
+            self.endpoint = new_reply.endpoint;
+            self.markdown = Markdown::parse(&self.content);
+        } else if let Some(token) = new_reply.last_token {
             self.markdown.push_str(&token);
         }
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.content.is_empty() && self.reasoning.is_none()
+    }
+
+    pub fn usage(&self) -> Option<assistant::Usage> {
+        self.usage
+    }
+
+    pub fn stats(&self) -> markdown::Stats {
+        self.markdown.stats()
+    }
+
+    pub fn update_table(&mut self, index: usize, interaction: table::Interaction) {
+        self.markdown.update_table(index, interaction);
+    }
+
     pub fn toggle_reasoning(&mut self, show: bool) {
         if let Some(reasoning) = &mut self.reasoning {
             reasoning.show = show;
@@ -69,20 +100,91 @@ impl Reply {
     pub fn view<Message>(
         &self,
         theme: &Theme,
+        runnable: markdown::Runnable,
         on_reasoning_toggle: impl Fn(bool) -> Message,
         on_markdown_interaction: impl Fn(markdown::Interaction) -> Message + 'static,
     ) -> Element<'_, Message>
     where
         Message: Clone + 'static,
     {
-        let message = self.markdown.view(theme).map(on_markdown_interaction);
+        let message = self
+            .markdown
+            .view_runnable(theme, runnable)
+            .map(on_markdown_interaction);
 
-        if let Some(reasoning) = &self.reasoning {
-            column![reasoning.quote(on_reasoning_toggle), message]
-                .spacing(20)
+        let endpoint = self.endpoint.as_ref().map(|endpoint| {
+            text!("Served by fallback {endpoint}")
+                .size(12)
+                .font(Font::MONOSPACE)
+                .style(text::secondary)
+        });
+
+        let provenance = self.provenance.as_ref().map(|provenance| {
+            let label = text(
+                [
+                    Some(provenance.model.as_str()),
+                    provenance.quantization.as_deref(),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" · "),
+            )
+            .size(12)
+            .font(Font::MONOSPACE)
+            .style(text::secondary);
+
+            let details = column(provenance.details().into_iter().map(|(label, value)| {
+                row![
+                    text(label).size(12).width(100).style(text::secondary),
+                    text(value).size(12).font(Font::MONOSPACE),
+                ]
                 .into()
-        } else {
-            message
-        }
+            }))
+            .spacing(5);
+
+            tooltip(
+                label,
+                container(details).padding(10).style(container::dark),
+                tooltip::Position::Top,
+            )
+        });
+
+        let usage = self.usage.map(|usage| {
+            text(usage_label(usage))
+                .size(12)
+                .font(Font::MONOSPACE)
+                .style(text::secondary)
+        });
+
+        column![
+            self.reasoning
+                .as_ref()
+                .map(|reasoning| reasoning.quote(on_reasoning_toggle)),
+            message,
+            endpoint,
+            provenance,
+            usage,
+        ]
+        .spacing(20)
+        .into()
     }
 }
+
+pub fn usage_label(usage: assistant::Usage) -> String {
+    [
+        Some(format!(
+            "{} prompt + {} completion tokens",
+            usage.prompt_tokens, usage.completion_tokens
+        )),
+        usage
+            .duration
+            .map(|duration| format!("{:.1} s", duration.as_secs_f32())),
+        usage.energy.map(|energy| format!("{energy:.2} Wh")),
+        usage.cost.map(|cost| format!("${cost:.4}")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" · ")
+}
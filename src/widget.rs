@@ -1,4 +1,8 @@
+pub mod context_menu;
+pub mod reorder;
 pub mod sidebar;
+pub mod splitter;
+pub mod table;
 
 pub use iced_palace::widget::diffused_text;
 
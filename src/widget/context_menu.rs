@@ -0,0 +1,114 @@
+use iced::event::{self, Event as Input};
+use iced::keyboard;
+use iced::mouse;
+use iced::widget::{button, column, container, mouse_area, opaque, pin, row, text, Text};
+use iced::{Center, Element, Fill, Point, Subscription};
+
+#[derive(Debug, Clone)]
+pub struct ContextMenu<T> {
+    cursor: Point,
+    open: Option<(T, Point)>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event<T> {
+    Moved(Point),
+    Opened(T),
+    Closed,
+}
+
+impl<T> Default for ContextMenu<T> {
+    fn default() -> Self {
+        Self {
+            cursor: Point::ORIGIN,
+            open: None,
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> ContextMenu<T> {
+    pub fn update(&mut self, event: Event<T>) {
+        match event {
+            Event::Moved(position) => {
+                self.cursor = position;
+            }
+            Event::Opened(target) => {
+                self.open = Some((target, self.cursor));
+            }
+            Event::Closed => {
+                self.open = None;
+            }
+        }
+    }
+
+    pub fn target(&self) -> Option<&T> {
+        self.open.as_ref().map(|(target, _)| target)
+    }
+
+    pub fn area<'a, Message: 'a>(
+        &self,
+        content: impl Into<Element<'a, Message>>,
+        target: T,
+        on_event: impl Fn(Event<T>) -> Message,
+    ) -> Element<'a, Message> {
+        mouse_area(content)
+            .on_right_press(on_event(Event::Opened(target)))
+            .into()
+    }
+
+    pub fn view<'a, Message: 'a>(
+        &self,
+        entries: impl FnOnce(&T) -> Vec<Element<'a, Message>>,
+    ) -> Option<Element<'a, Message>> {
+        let (target, position) = self.open.as_ref()?;
+
+        let menu = container(column(entries(target)).spacing(2).width(200))
+            .padding(5)
+            .style(container::bordered_box);
+
+        Some(pin(opaque(menu)).x(position.x).y(position.y).into())
+    }
+
+    pub fn subscription(&self) -> Subscription<Event<T>> {
+        let cursor = event::listen_with(|event, _status, _window| match event {
+            Input::Mouse(mouse::Event::CursorMoved { position }) => Some(Event::Moved(position)),
+            _ => None,
+        });
+
+        if self.open.is_none() {
+            return cursor;
+        }
+
+        // Entries publish their message on release as well, before the menu is gone
+        let close = event::listen_with(|event, _status, _window| match event {
+            Input::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))
+            | Input::Keyboard(keyboard::Event::KeyPressed {
+                key: keyboard::Key::Named(keyboard::key::Named::Escape),
+                ..
+            }) => Some(Event::Closed),
+            _ => None,
+        });
+
+        Subscription::batch([cursor, close])
+    }
+}
+
+pub fn entry<'a, Message: 'a>(
+    icon: Text<'a>,
+    label: &'a str,
+    on_press: Option<Message>,
+) -> Element<'a, Message> {
+    button(
+        row![
+            icon.size(12).width(14).center().line_height(1.0),
+            text(label).size(14),
+        ]
+        .spacing(10)
+        .align_y(Center),
+    )
+    .width(Fill)
+    .padding([5, 10])
+    .style(button::text)
+    .on_press_maybe(on_press)
+    .into()
+}
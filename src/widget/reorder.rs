@@ -0,0 +1,89 @@
+use iced::event::{self, Event};
+use iced::mouse;
+use iced::widget::{column, container, mouse_area, Space};
+use iced::{Element, Fill, Subscription};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Reorder {
+    drag: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Drag {
+    Started(usize),
+    Entered(usize),
+    Ended,
+}
+
+pub enum Action {
+    None,
+    Clicked(usize),
+    Moved { from: usize, to: usize },
+}
+
+impl Reorder {
+    pub fn update(&mut self, drag: Drag) -> Action {
+        match drag {
+            Drag::Started(index) => {
+                self.drag = Some((index, index));
+
+                Action::None
+            }
+            Drag::Entered(index) => {
+                if let Some((_from, to)) = &mut self.drag {
+                    *to = index;
+                }
+
+                Action::None
+            }
+            Drag::Ended => match self.drag.take() {
+                Some((from, to)) if from == to => Action::Clicked(from),
+                Some((from, to)) => Action::Moved { from, to },
+                None => Action::None,
+            },
+        }
+    }
+
+    pub fn item<'a, Message: Clone + 'a>(
+        &self,
+        index: usize,
+        content: impl Into<Element<'a, Message>>,
+        on_drag: impl Fn(Drag) -> Message,
+    ) -> Element<'a, Message> {
+        // The entry is dropped before the target when moving up, and after it when moving down
+        let (above, below) = match self.drag {
+            Some((from, to)) if to == index && from > to => (true, false),
+            Some((from, to)) if to == index && from < to => (false, true),
+            _ => (false, false),
+        };
+
+        let area = mouse_area(content)
+            .on_press(on_drag(Drag::Started(index)))
+            .on_enter(on_drag(Drag::Entered(index)));
+
+        let area = if self.drag.is_some() {
+            area.interaction(mouse::Interaction::Grabbing)
+        } else {
+            area
+        };
+
+        column![above.then(indicator), area, below.then(indicator),].into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Drag> {
+        if self.drag.is_none() {
+            return Subscription::none();
+        }
+
+        event::listen_with(|event, _status, _window| match event {
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => Some(Drag::Ended),
+            _ => None,
+        })
+    }
+}
+
+fn indicator<'a, Message: 'a>() -> Element<'a, Message> {
+    container(Space::new(Fill, 2))
+        .style(|theme| container::Style::default().background(theme.palette().primary))
+        .into()
+}
@@ -0,0 +1,91 @@
+use iced::event::{self, Event};
+use iced::mouse;
+use iced::widget::{center_x, mouse_area, rule, vertical_rule};
+use iced::{Element, Subscription};
+
+const MIN_WIDTH: f32 = 180.0;
+const MAX_WIDTH: f32 = 600.0;
+const HANDLE_WIDTH: f32 = 7.0;
+
+const COLLAPSE_WIDTH: f32 = 100.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Splitter {
+    width: f32,
+    drag: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Drag {
+    Started,
+    Moved(f32),
+    Ended,
+}
+
+pub enum Action {
+    None,
+    Resized(u32),
+    Collapsed,
+}
+
+impl Splitter {
+    pub fn new(width: u32) -> Self {
+        Self {
+            width: (width as f32).clamp(MIN_WIDTH, MAX_WIDTH),
+            drag: None,
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.drag.unwrap_or(self.width).clamp(MIN_WIDTH, MAX_WIDTH)
+    }
+
+    pub fn update(&mut self, drag: Drag) -> Action {
+        match drag {
+            Drag::Started => {
+                self.drag = Some(self.width);
+
+                Action::None
+            }
+            Drag::Moved(x) => {
+                if self.drag.is_some() {
+                    self.drag = Some(x);
+                }
+
+                Action::None
+            }
+            Drag::Ended => {
+                let Some(x) = self.drag.take() else {
+                    return Action::None;
+                };
+
+                if x < COLLAPSE_WIDTH {
+                    return Action::Collapsed;
+                }
+
+                self.width = x.clamp(MIN_WIDTH, MAX_WIDTH);
+
+                Action::Resized(self.width.round() as u32)
+            }
+        }
+    }
+
+    pub fn handle<'a>(&self) -> Element<'a, Drag> {
+        mouse_area(center_x(vertical_rule(1).style(rule::weak)).width(HANDLE_WIDTH))
+            .on_press(Drag::Started)
+            .interaction(mouse::Interaction::ResizingHorizontally)
+            .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Drag> {
+        if self.drag.is_none() {
+            return Subscription::none();
+        }
+
+        event::listen_with(|event, _status, _window| match event {
+            Event::Mouse(mouse::Event::CursorMoved { position }) => Some(Drag::Moved(position.x)),
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => Some(Drag::Ended),
+            _ => None,
+        })
+    }
+}
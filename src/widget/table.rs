@@ -0,0 +1,201 @@
+use crate::core::table;
+use crate::icon;
+
+use iced::border;
+use iced::widget::{button, column, container, row, text};
+use iced::{Center, Element, Fill, Font, Theme};
+
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone)]
+pub struct Table {
+    data: table::Table,
+    sort: Option<(usize, Order)>,
+    selection: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Order {
+    Ascending,
+    Descending,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Interaction {
+    Sort(usize),
+    Select { row: usize, column: usize },
+}
+
+impl Table {
+    pub fn new(data: table::Table) -> Self {
+        Self {
+            data,
+            sort: None,
+            selection: None,
+        }
+    }
+
+    pub fn refresh(&mut self, data: table::Table) {
+        if data.columns != self.data.columns {
+            self.sort = None;
+            self.selection = None;
+        }
+
+        if self
+            .selection
+            .is_some_and(|(row, _column)| row >= data.rows.len())
+        {
+            self.selection = None;
+        }
+
+        self.data = data;
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        let (row, column) = self.selection?;
+
+        self.data.rows.get(row)?.get(column).map(String::as_str)
+    }
+
+    pub fn update(&mut self, interaction: Interaction) {
+        match interaction {
+            Interaction::Sort(column) => {
+                // Ascending, then descending, then back to the original order
+                self.sort = match self.sort {
+                    Some((current, Order::Ascending)) if current == column => {
+                        Some((column, Order::Descending))
+                    }
+                    Some((current, Order::Descending)) if current == column => None,
+                    _ => Some((column, Order::Ascending)),
+                };
+            }
+            Interaction::Select { row, column } => {
+                self.selection = if self.selection == Some((row, column)) {
+                    None
+                } else {
+                    Some((row, column))
+                };
+            }
+        }
+    }
+
+    pub fn to_data(&self) -> table::Table {
+        table::Table {
+            columns: self.data.columns.clone(),
+            rows: self
+                .rows()
+                .into_iter()
+                .map(|(_, row)| row.clone())
+                .collect(),
+        }
+    }
+
+    fn rows(&self) -> Vec<(usize, &Vec<String>)> {
+        let mut rows: Vec<_> = self.data.rows.iter().enumerate().collect();
+
+        if let Some((column, order)) = self.sort {
+            rows.sort_by(|(_, a), (_, b)| {
+                let ordering = compare(&a[column], &b[column]);
+
+                match order {
+                    Order::Ascending => ordering,
+                    Order::Descending => ordering.reverse(),
+                }
+            });
+        }
+
+        rows
+    }
+
+    pub fn view(&self) -> Element<'_, Interaction> {
+        let header = row(self.data.columns.iter().enumerate().map(|(index, name)| {
+            let arrow = match self.sort {
+                Some((column, Order::Ascending)) if column == index => {
+                    Some(icon::arrow_up().size(10))
+                }
+                Some((column, Order::Descending)) if column == index => {
+                    Some(icon::arrow_down().size(10))
+                }
+                _ => None,
+            };
+
+            button(
+                row![text(name).size(12).font(Font::MONOSPACE), arrow]
+                    .spacing(5)
+                    .align_y(Center),
+            )
+            .width(Fill)
+            .padding([5, 10])
+            .style(button::text)
+            .on_press(Interaction::Sort(index))
+            .into()
+        }));
+
+        let rows = self
+            .rows()
+            .into_iter()
+            .enumerate()
+            .map(|(index, (row_index, cells))| {
+                container(row(cells.iter().enumerate().map(|(column, cell)| {
+                    let is_selected = self.selection == Some((row_index, column));
+
+                    button(text(cell).size(12))
+                        .width(Fill)
+                        .padding([5, 10])
+                        .style(move |theme: &Theme, status| {
+                            let base = button::text(theme, status);
+
+                            if is_selected {
+                                let palette = theme.extended_palette();
+
+                                button::Style {
+                                    background: Some(palette.primary.weak.color.into()),
+                                    text_color: palette.primary.weak.text,
+                                    ..base
+                                }
+                            } else {
+                                base
+                            }
+                        })
+                        .on_press(Interaction::Select {
+                            row: row_index,
+                            column,
+                        })
+                        .into()
+                })))
+                .style(move |theme: &Theme| {
+                    if index % 2 == 0 {
+                        container::Style::default()
+                    } else {
+                        container::Style::default()
+                            .background(theme.extended_palette().background.weak.color)
+                    }
+                })
+                .into()
+            });
+
+        container(column![
+            container(header).style(|theme: &Theme| {
+                container::Style::default()
+                    .background(theme.extended_palette().background.strong.color)
+            }),
+            column(rows),
+        ])
+        .style(|theme: &Theme| {
+            container::Style::default().border(
+                border::rounded(5)
+                    .color(theme.extended_palette().background.strong.color)
+                    .width(1),
+            )
+        })
+        .clip(true)
+        .into()
+    }
+}
+
+fn compare(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}